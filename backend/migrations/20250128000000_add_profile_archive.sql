@@ -0,0 +1,73 @@
+-- ========================================
+-- StarRocks Admin - Query Profile Archive
+-- ========================================
+-- Created: 2025-01-28
+-- Purpose: Persist query profiles beyond the FE in-memory PROFILELIST retention,
+--          together with an analysis summary for search and filtering
+
+-- 1. Profile archive table
+-- profile_content holds the raw profile text, gzip-compressed when compression = 'gzip'
+-- rule_ids is a JSON array of diagnostic rule ids, ordered by severity (top rules first)
+CREATE TABLE IF NOT EXISTS profile_archives (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    query_id VARCHAR(64) NOT NULL,
+    query_user VARCHAR(100),
+    default_db VARCHAR(255),
+    sql_statement TEXT,
+    query_state VARCHAR(32),
+    query_start_time VARCHAR(32),
+    total_time_ms REAL,
+    peak_memory_bytes INTEGER,
+    performance_score REAL,
+    rule_ids TEXT NOT NULL DEFAULT '[]',
+    diagnostic_count INTEGER NOT NULL DEFAULT 0,
+    conclusion TEXT,
+    profile_content BLOB NOT NULL,
+    compression VARCHAR(16) NOT NULL DEFAULT 'none',
+    raw_size INTEGER NOT NULL DEFAULT 0,
+    source VARCHAR(16) NOT NULL DEFAULT 'manual',
+    archived_by INTEGER,
+    archived_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    analyzed_at TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE,
+    FOREIGN KEY (archived_by) REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE (cluster_id, query_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_profile_archives_cluster_time
+    ON profile_archives(cluster_id, query_start_time);
+CREATE INDEX IF NOT EXISTS idx_profile_archives_archived_at ON profile_archives(archived_at);
+CREATE INDEX IF NOT EXISTS idx_profile_archives_user ON profile_archives(cluster_id, query_user);
+
+-- 2. Permissions for the profile archive APIs
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:profile_archives:list', '查询Profile归档列表', 'api', 'clusters', 'profile_archives:list', 'GET /api/clusters/profile-archives'),
+('api:clusters:profile_archives:get', '查看Profile归档详情', 'api', 'clusters', 'profile_archives:get', 'GET /api/clusters/profile-archives/:id'),
+('api:clusters:profile_archives:analyze', '重新分析归档Profile', 'api', 'clusters', 'profile_archives:analyze', 'GET /api/clusters/profile-archives/:id/analyze'),
+('api:clusters:profile_archives:delete', '删除Profile归档', 'api', 'clusters', 'profile_archives:delete', 'DELETE /api/clusters/profile-archives/:id'),
+('api:clusters:profiles:archive', '归档Profile', 'api', 'clusters', 'profiles:archive', 'POST /api/clusters/profiles/:query_id/archive');
+
+-- 3. Attach the new APIs to the Profiles menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:queries:profiles')
+WHERE code IN (
+    'api:clusters:profile_archives:list',
+    'api:clusters:profile_archives:get',
+    'api:clusters:profile_archives:analyze',
+    'api:clusters:profile_archives:delete',
+    'api:clusters:profiles:archive'
+);
+
+-- 4. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN (
+    'api:clusters:profile_archives:list',
+    'api:clusters:profile_archives:get',
+    'api:clusters:profile_archives:analyze',
+    'api:clusters:profile_archives:delete',
+    'api:clusters:profiles:archive'
+  );
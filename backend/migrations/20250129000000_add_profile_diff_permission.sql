@@ -0,0 +1,21 @@
+-- ========================================
+-- StarRocks Admin - Query Profile Diff
+-- ========================================
+-- Created: 2025-01-29
+-- Purpose: Permission for comparing two query profiles (live, archived or uploaded)
+
+-- 1. Permission for the profile diff API
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:profiles:diff', '对比Profile', 'api', 'clusters', 'profiles:diff', 'POST /api/clusters/profiles/diff');
+
+-- 2. Attach to the Profiles menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:queries:profiles')
+WHERE code = 'api:clusters:profiles:diff';
+
+-- 3. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code = 'api:clusters:profiles:diff';
@@ -0,0 +1,110 @@
+-- ========================================
+-- StarRocks Admin - Alert Rules Engine
+-- ========================================
+-- Created: 2025-01-30
+-- Purpose: User-defined alert rules evaluated against collected metrics snapshots,
+--          with notification channels and alert history
+
+-- 1. Notification channels
+-- config is a JSON object whose shape depends on channel_type:
+--   webhook:               {"url": "...", "headers": {"K": "V"}}
+--   dingtalk/feishu/slack: {"url": "..."}
+--   smtp:                  {"host": "...", "port": 465, "tls": "tls|starttls|none",
+--                           "username": "...", "password": "<encrypted>", "from": "...", "to": ["..."]}
+CREATE TABLE IF NOT EXISTS alert_channels (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    channel_type VARCHAR(16) NOT NULL,
+    config TEXT NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_by INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE (cluster_id, name)
+);
+
+-- 2. Alert rules with their evaluation state
+-- metric is a MetricsSnapshot field (e.g. disk_usage_pct) or a derived metric (e.g. backend_offline)
+-- channel_ids is a JSON array of alert_channels ids
+-- state: ok -> pending (condition holds, waiting for duration_secs) -> firing -> ok
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    metric VARCHAR(64) NOT NULL,
+    comparator VARCHAR(4) NOT NULL,
+    threshold REAL NOT NULL,
+    duration_secs INTEGER NOT NULL DEFAULT 0,
+    severity VARCHAR(16) NOT NULL DEFAULT 'warning',
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    channel_ids TEXT NOT NULL DEFAULT '[]',
+    state VARCHAR(16) NOT NULL DEFAULT 'ok',
+    pending_since TIMESTAMP,
+    last_value REAL,
+    last_evaluated_at TIMESTAMP,
+    silenced_until TIMESTAMP,
+    created_by INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE (cluster_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_rules_cluster ON alert_rules(cluster_id, enabled);
+
+-- 3. Alert history (one row per firing / resolved transition)
+-- Rows outlive their rule; rule_name/metric/threshold are copied at event time
+CREATE TABLE IF NOT EXISTS alert_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    rule_id INTEGER,
+    rule_name VARCHAR(100) NOT NULL,
+    metric VARCHAR(64) NOT NULL,
+    comparator VARCHAR(4) NOT NULL,
+    threshold REAL NOT NULL,
+    value REAL NOT NULL,
+    severity VARCHAR(16) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    message TEXT NOT NULL,
+    silenced BOOLEAN NOT NULL DEFAULT 0,
+    notified BOOLEAN NOT NULL DEFAULT 0,
+    notification_error TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE,
+    FOREIGN KEY (rule_id) REFERENCES alert_rules(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_history_cluster_time ON alert_history(cluster_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_alert_history_rule ON alert_history(rule_id);
+
+-- 4. Permissions for the alert APIs
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:alerts:metrics', '查询告警指标', 'api', 'clusters', 'alerts:metrics', 'GET /api/clusters/alerts/metrics'),
+('api:clusters:alerts:rules:list', '查询告警规则', 'api', 'clusters', 'alerts:rules:list', 'GET /api/clusters/alerts/rules'),
+('api:clusters:alerts:rules:create', '创建告警规则', 'api', 'clusters', 'alerts:rules:create', 'POST /api/clusters/alerts/rules'),
+('api:clusters:alerts:rules:update', '更新告警规则', 'api', 'clusters', 'alerts:rules:update', 'PUT /api/clusters/alerts/rules/:id'),
+('api:clusters:alerts:rules:delete', '删除告警规则', 'api', 'clusters', 'alerts:rules:delete', 'DELETE /api/clusters/alerts/rules/:id'),
+('api:clusters:alerts:rules:silence', '静默告警规则', 'api', 'clusters', 'alerts:rules:silence', 'POST/DELETE /api/clusters/alerts/rules/:id/silence'),
+('api:clusters:alerts:channels:list', '查询通知渠道', 'api', 'clusters', 'alerts:channels:list', 'GET /api/clusters/alerts/channels'),
+('api:clusters:alerts:channels:create', '创建通知渠道', 'api', 'clusters', 'alerts:channels:create', 'POST /api/clusters/alerts/channels'),
+('api:clusters:alerts:channels:update', '更新通知渠道', 'api', 'clusters', 'alerts:channels:update', 'PUT /api/clusters/alerts/channels/:id'),
+('api:clusters:alerts:channels:delete', '删除通知渠道', 'api', 'clusters', 'alerts:channels:delete', 'DELETE /api/clusters/alerts/channels/:id'),
+('api:clusters:alerts:channels:test', '测试通知渠道', 'api', 'clusters', 'alerts:channels:test', 'POST /api/clusters/alerts/channels/:id/test'),
+('api:clusters:alerts:history', '查询告警历史', 'api', 'clusters', 'alerts:history', 'GET /api/clusters/alerts/history');
+
+-- 5. Attach the alert APIs to the Overview menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:overview')
+WHERE code LIKE 'api:clusters:alerts:%';
+
+-- 6. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code LIKE 'api:clusters:alerts:%';
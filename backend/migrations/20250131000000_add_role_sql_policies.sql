@@ -0,0 +1,43 @@
+-- ========================================
+-- StarRocks Admin - Role SQL Execution Policies
+-- ========================================
+-- Created: 2025-01-31
+-- Purpose: Restrict what users can run in the SQL editor, per role
+--          (statement classes, catalogs/databases, row and time limits)
+
+-- 1. One optional policy per role
+-- A role without a policy is unrestricted. A user's effective policy is the union of
+-- their roles' policies (the most permissive value of each field wins).
+-- allowed_statements: JSON array of 'query', 'dml', 'ddl', 'admin'
+-- allowed_catalogs / allowed_databases: JSON arrays of names, '[]' = any
+CREATE TABLE IF NOT EXISTS role_sql_policies (
+    role_id INTEGER PRIMARY KEY,
+    allowed_statements TEXT NOT NULL DEFAULT '["query"]',
+    allowed_catalogs TEXT NOT NULL DEFAULT '[]',
+    allowed_databases TEXT NOT NULL DEFAULT '[]',
+    max_rows INTEGER,
+    max_execution_secs INTEGER,
+    updated_by INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- 2. Permissions for managing role SQL policies
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:roles:sql_policy:get', '查看角色SQL策略', 'api', 'roles', 'sql_policy:get', 'GET /api/roles/:id/sql-policy'),
+('api:roles:sql_policy:update', '更新角色SQL策略', 'api', 'roles', 'sql_policy:update', 'PUT /api/roles/:id/sql-policy'),
+('api:roles:sql_policy:delete', '删除角色SQL策略', 'api', 'roles', 'sql_policy:delete', 'DELETE /api/roles/:id/sql-policy');
+
+-- 3. Attach to the Roles menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:roles')
+WHERE code LIKE 'api:roles:sql_policy:%';
+
+-- 4. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code LIKE 'api:roles:sql_policy:%';
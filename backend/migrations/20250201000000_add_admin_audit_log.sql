@@ -0,0 +1,61 @@
+-- ========================================
+-- StarRocks Admin - Admin Audit Log
+-- ========================================
+-- Created: 2025-02-01
+-- Purpose: Record every state-changing action performed through starrocks-admin
+--          (who, in which organization and cluster, what, with which payload and result)
+
+-- 1. One row per audited request
+-- action: permission of the route (e.g. clusters:queries:kill), or "METHOD /route" when the
+--         route has no permission mapping
+-- target: path parameters of the route (e.g. query_id=...)
+-- payload: request body with secrets redacted, truncated
+-- cluster_id/cluster_name: cluster the action applied to; the name survives cluster deletion
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER,
+    username VARCHAR(100) NOT NULL,
+    organization_id INTEGER,
+    cluster_id INTEGER,
+    cluster_name VARCHAR(100),
+    action VARCHAR(200) NOT NULL,
+    method VARCHAR(10) NOT NULL,
+    path TEXT NOT NULL,
+    target TEXT,
+    payload TEXT,
+    status_code INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    error_message TEXT,
+    duration_ms INTEGER NOT NULL,
+    client_ip VARCHAR(64),
+    created_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL,
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_org_time
+    ON admin_audit_log(organization_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_user ON admin_audit_log(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_cluster ON admin_audit_log(cluster_id, created_at);
+
+-- 2. Menu and API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('menu:system:audit-logs', '操作审计', 'menu', 'system:audit-logs', 'view', '查看操作审计'),
+('api:audit_logs:list', '查看操作审计日志', 'api', 'audit_logs', 'list', 'GET /api/audit-logs'),
+('api:audit_logs:export', '导出操作审计日志', 'api', 'audit_logs', 'export', 'GET /api/audit-logs/export');
+
+-- 3. Attach to the System menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system')
+WHERE code = 'menu:system:audit-logs';
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:audit-logs')
+WHERE code IN ('api:audit_logs:list', 'api:audit_logs:export');
+
+-- 4. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN ('menu:system:audit-logs', 'api:audit_logs:list', 'api:audit_logs:export');
@@ -0,0 +1,30 @@
+-- ========================================
+-- StarRocks Admin - Fleet Overview
+-- ========================================
+-- Created: 2025-02-02
+-- Purpose: Permission of the multi-cluster fleet overview (GET /api/clusters/fleet).
+--          Cluster-scoped routes (/api/clusters/:id/...) reuse the permissions of the
+--          unscoped routes and need no new entries.
+
+-- 1. API permission
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:fleet', '多集群总览', 'api', 'clusters', 'fleet', 'GET /api/clusters/fleet');
+
+-- 2. Attach to the overview menu
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:overview')
+WHERE code = 'api:clusters:fleet';
+
+-- 3. Grant to every role that can see the single-cluster overview
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions overview ON overview.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:fleet'
+WHERE overview.code = 'api:clusters:overview';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code = 'api:clusters:fleet';
@@ -0,0 +1,33 @@
+-- ========================================
+-- StarRocks Admin - Per-user Active Cluster
+-- ========================================
+-- Created: 2025-02-03
+-- Purpose: Store the active cluster per user instead of switching it for everyone.
+--          clusters.is_active is kept as the default of an organization, used by users
+--          who have not selected a cluster yet.
+
+-- 1. Active cluster selected by each user
+CREATE TABLE IF NOT EXISTS user_active_clusters (
+    user_id INTEGER PRIMARY KEY,
+    cluster_id INTEGER NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_active_clusters_cluster ON user_active_clusters(cluster_id);
+
+-- 2. Setting the default cluster of an organization (PUT /api/clusters/:id/default)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:default', '设置默认集群', 'api', 'clusters', 'default', 'PUT /api/clusters/:id/default');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:dashboard')
+WHERE code = 'api:clusters:default';
+
+-- 3. Grant to built-in admin roles
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code = 'api:clusters:default';
@@ -0,0 +1,32 @@
+-- ========================================
+-- StarRocks Admin - Multiple FE Endpoints
+-- ========================================
+-- Created: 2025-02-04
+-- Purpose: Additional FE endpoints per cluster for failover, and the permission of the
+--          endpoint status route (GET /api/clusters/frontends/endpoints).
+
+-- 1. Additional FE endpoints (JSON array of {host, http_port, query_port}),
+--    tried after fe_host when it is unreachable
+ALTER TABLE clusters ADD COLUMN fe_endpoints TEXT;
+
+-- 2. API permission
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:frontends:endpoints', '查看FE节点连接状态', 'api', 'clusters', 'frontends:endpoints', 'GET /api/clusters/frontends/endpoints');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:nodes:frontends')
+WHERE code = 'api:clusters:frontends:endpoints';
+
+-- 3. Grant to every role that can list the frontends
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions frontends ON frontends.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:frontends:endpoints'
+WHERE frontends.code = 'api:clusters:frontends';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code = 'api:clusters:frontends:endpoints';
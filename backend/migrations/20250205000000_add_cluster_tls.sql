@@ -0,0 +1,14 @@
+-- ========================================
+-- StarRocks Admin - Cluster TLS
+-- ========================================
+-- Created: 2025-02-05
+-- Purpose: Per-cluster TLS settings for the MySQL protocol connection and the HTTP client,
+--          used when clusters.enable_ssl is set.
+
+-- 1. CA bundle and client certificate (PEM); the client key is encrypted like the password
+ALTER TABLE clusters ADD COLUMN tls_ca_cert TEXT;
+ALTER TABLE clusters ADD COLUMN tls_client_cert TEXT;
+ALTER TABLE clusters ADD COLUMN tls_client_key_encrypted TEXT;
+
+-- 2. Host name verification of the FE certificate
+ALTER TABLE clusters ADD COLUMN tls_verify_hostname BOOLEAN NOT NULL DEFAULT 1;
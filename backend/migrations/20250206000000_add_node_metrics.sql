@@ -0,0 +1,87 @@
+-- ========================================
+-- StarRocks Admin - Per-node Metrics History
+-- ========================================
+-- Created: 2025-02-06
+-- Purpose: Keep one metrics row per BE/CN/FE node and collection tick next to the cluster
+--          aggregates in metrics_snapshots, so node level trends and drill-downs can be
+--          answered after the fact. Rows follow the metrics retention (metrics.retention_days).
+
+-- 1. Node metrics snapshots (High-frequency: same interval as metrics_snapshots)
+CREATE TABLE IF NOT EXISTS node_metrics_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    collected_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    -- Node identity
+    node_type VARCHAR(10) NOT NULL,                     -- be, cn, fe
+    node_id VARCHAR(255) NOT NULL,                      -- BackendId (BE/CN) or Name (FE)
+    host VARCHAR(255) NOT NULL,
+    port INTEGER NOT NULL DEFAULT 0,                    -- Heartbeat port (BE/CN), edit log port (FE)
+
+    -- Liveness
+    alive BOOLEAN NOT NULL DEFAULT 0,
+    last_heartbeat VARCHAR(50),
+
+    -- Resources (BE/CN)
+    cpu_usage_pct REAL,
+    mem_usage_pct REAL,
+    disk_total_bytes BIGINT,
+    disk_used_bytes BIGINT,
+    disk_usage_pct REAL,
+    data_used_bytes BIGINT,
+    disk_paths TEXT,                                    -- JSON: per storage path usage (BE)
+
+    -- Storage (BE/CN)
+    tablet_count BIGINT,
+    max_compaction_score REAL,
+    num_running_queries INTEGER,
+
+    -- FE
+    role VARCHAR(20),
+    is_leader BOOLEAN NOT NULL DEFAULT 0,
+    replayed_journal_id BIGINT,
+
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_metrics_snapshots_node_time
+ON node_metrics_snapshots(cluster_id, node_type, node_id, collected_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_node_metrics_snapshots_time
+ON node_metrics_snapshots(collected_at DESC);
+
+-- 2. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:overview:nodes', '节点指标列表', 'api', 'clusters', 'overview:nodes', 'GET /api/clusters/overview/nodes'),
+('api:clusters:overview:nodes:get', '节点指标详情', 'api', 'clusters', 'overview:nodes:get', 'GET /api/clusters/overview/nodes/:node_type/:node_id'),
+('api:clusters:overview:nodes:trends', '节点指标趋势', 'api', 'clusters', 'overview:nodes:trends', 'GET /api/clusters/overview/nodes/:node_type/:node_id/trends');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:overview')
+WHERE code IN (
+    'api:clusters:overview:nodes',
+    'api:clusters:overview:nodes:get',
+    'api:clusters:overview:nodes:trends'
+);
+
+-- 3. Grant to every role that can read the resource trends
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions resources ON resources.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:overview:nodes',
+    'api:clusters:overview:nodes:get',
+    'api:clusters:overview:nodes:trends'
+)
+WHERE resources.code = 'api:clusters:overview:resources';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN (
+    'api:clusters:overview:nodes',
+    'api:clusters:overview:nodes:get',
+    'api:clusters:overview:nodes:trends'
+  );
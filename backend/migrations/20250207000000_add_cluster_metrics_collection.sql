@@ -0,0 +1,58 @@
+-- ========================================
+-- StarRocks Admin - Per-cluster Metrics Collection
+-- ========================================
+-- Created: 2025-02-07
+-- Purpose: Collection settings (enable flag, interval) and collection status (last success,
+--          last error, consecutive failures, next scheduled run) of each cluster.
+--          A cluster without a row is collected with the defaults of the [metrics] config.
+
+-- 1. Collection settings and status per cluster
+CREATE TABLE IF NOT EXISTS cluster_metrics_collection (
+    cluster_id INTEGER PRIMARY KEY,
+
+    -- Settings
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    interval_secs INTEGER,                              -- NULL: metrics.interval_secs
+
+    -- Status
+    last_attempt_at TIMESTAMP,
+    last_success_at TIMESTAMP,
+    last_error TEXT,
+    last_error_at TIMESTAMP,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    last_duration_ms INTEGER,
+    next_collect_at TIMESTAMP,                          -- NULL: collect on the next tick
+
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+-- 2. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:collection', '查看指标采集状态', 'api', 'clusters', 'collection', 'GET /api/clusters/collection, GET /api/clusters/:id/collection'),
+('api:clusters:collection:update', '修改指标采集设置', 'api', 'clusters', 'collection:update', 'PUT /api/clusters/:id/collection');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:dashboard')
+WHERE code IN ('api:clusters:collection', 'api:clusters:collection:update');
+
+-- 3. Grant status to roles that can list clusters, settings to roles that can update them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions list ON list.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:collection'
+WHERE list.code = 'api:clusters:list';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions updates ON updates.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:collection:update'
+WHERE updates.code = 'api:clusters:update';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN ('api:clusters:collection', 'api:clusters:collection:update');
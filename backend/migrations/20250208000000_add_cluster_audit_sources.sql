@@ -0,0 +1,48 @@
+-- ========================================
+-- StarRocks Admin - Per-cluster Audit Source
+-- ========================================
+-- Created: 2025-02-08
+-- Purpose: Audit table (database, table, column mapping) of each cluster, used by query
+--          history, latency percentiles, schema change and table access statistics.
+--          A cluster without a row uses the [audit] config with auto-detected columns.
+
+-- 1. Audit source settings per cluster
+CREATE TABLE IF NOT EXISTS cluster_audit_sources (
+    cluster_id INTEGER PRIMARY KEY,
+    database_name TEXT,                                 -- NULL: audit.database
+    table_name TEXT,                                    -- NULL: audit.table
+    column_mapping TEXT,                                -- JSON {logical column: table column}; unmapped columns are auto-detected
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+-- 2. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:audit_source', '查看审计日志来源', 'api', 'clusters', 'audit_source', 'GET /api/clusters/:id/audit-source'),
+('api:clusters:audit_source:update', '修改审计日志来源', 'api', 'clusters', 'audit_source:update', 'PUT /api/clusters/:id/audit-source, DELETE /api/clusters/:id/audit-source'),
+('api:clusters:audit_source:validate', '校验审计日志来源', 'api', 'clusters', 'audit_source:validate', 'POST /api/clusters/:id/audit-source/validate');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:dashboard')
+WHERE code IN ('api:clusters:audit_source', 'api:clusters:audit_source:update', 'api:clusters:audit_source:validate');
+
+-- 3. Grant viewing to roles that can view clusters, changes to roles that can update them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions getter ON getter.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:audit_source'
+WHERE getter.code = 'api:clusters:get';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions updates ON updates.id = rp.permission_id
+JOIN permissions p ON p.code IN ('api:clusters:audit_source:update', 'api:clusters:audit_source:validate')
+WHERE updates.code = 'api:clusters:update';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN ('api:clusters:audit_source', 'api:clusters:audit_source:update', 'api:clusters:audit_source:validate');
@@ -0,0 +1,79 @@
+-- ========================================
+-- StarRocks Admin - API Tokens and Service Accounts
+-- ========================================
+-- Created: 2025-02-09
+-- Purpose: Long-lived, revocable personal access tokens for automation, and service
+--          account users that only authenticate with such tokens.
+--          Only the SHA-256 hash of a token is stored; the token itself is shown once.
+
+-- 1. Service accounts (users that cannot log in with a password)
+CREATE TABLE IF NOT EXISTS service_accounts (
+    user_id INTEGER PRIMARY KEY,
+    description TEXT,
+    created_by INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- 2. API tokens
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,                           -- Token acts as this user
+    name VARCHAR(100) NOT NULL,
+    token_prefix VARCHAR(16) NOT NULL,                  -- First characters, to recognize a token
+    token_hash VARCHAR(64) NOT NULL UNIQUE,             -- Hex SHA-256 of the token
+    scopes TEXT,                                        -- JSON permission codes; NULL: all of the user's
+    cluster_ids TEXT,                                   -- JSON cluster ids; NULL: any cluster
+    expires_at TIMESTAMP,                               -- NULL: never expires
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    created_by INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens(user_id);
+
+-- 3. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:service_accounts:list', '查询服务账号列表', 'api', 'service_accounts', 'list', 'GET /api/service-accounts'),
+('api:service_accounts:get', '查看服务账号详情', 'api', 'service_accounts', 'get', 'GET /api/service-accounts/:id'),
+('api:service_accounts:create', '创建服务账号', 'api', 'service_accounts', 'create', 'POST /api/service-accounts'),
+('api:service_accounts:delete', '删除服务账号', 'api', 'service_accounts', 'delete', 'DELETE /api/service-accounts/:id'),
+('api:service_accounts:tokens:list', '查看服务账号令牌', 'api', 'service_accounts', 'tokens:list', 'GET /api/service-accounts/:id/tokens'),
+('api:service_accounts:tokens:create', '创建服务账号令牌', 'api', 'service_accounts', 'tokens:create', 'POST /api/service-accounts/:id/tokens'),
+('api:service_accounts:tokens:revoke', '吊销服务账号令牌', 'api', 'service_accounts', 'tokens:revoke', 'DELETE /api/service-accounts/:id/tokens/:token_id');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:users')
+WHERE code LIKE 'api:service_accounts:%';
+
+-- 4. Grant read access to roles that can list users, management to roles that can create them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions list ON list.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:service_accounts:list',
+    'api:service_accounts:get',
+    'api:service_accounts:tokens:list'
+)
+WHERE list.code = 'api:users:list';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions creates ON creates.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:service_accounts:create',
+    'api:service_accounts:delete',
+    'api:service_accounts:tokens:create',
+    'api:service_accounts:tokens:revoke'
+)
+WHERE creates.code = 'api:users:create';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code LIKE 'api:service_accounts:%';
@@ -0,0 +1,59 @@
+-- ========================================
+-- StarRocks Admin - Login Sessions
+-- ========================================
+-- Created: 2025-02-10
+-- Purpose: Server-side login sessions. Short-lived access tokens carry the session id and
+--          are refused once the session is revoked; refresh tokens rotate on every use and
+--          only their SHA-256 hash is stored. Users can be disabled.
+
+-- 1. Disabled users cannot log in nor use their existing sessions and API tokens
+ALTER TABLE users ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT 1;
+
+-- 2. Sessions
+CREATE TABLE IF NOT EXISTS user_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    refresh_token_hash VARCHAR(64) NOT NULL UNIQUE,     -- Hex SHA-256 of the current refresh token
+    previous_token_hash VARCHAR(64),                    -- Rotated-out token, to detect its reuse
+    user_agent TEXT,
+    ip_address VARCHAR(64),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_refreshed_at TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,                      -- Refresh token expiry, extended on refresh
+    revoked_at TIMESTAMP,
+    revoke_reason VARCHAR(32),                          -- logout, revoked, password_changed, ...
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_id ON user_sessions(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_sessions_previous_token ON user_sessions(previous_token_hash);
+
+-- 3. API permissions (a user's own sessions are managed under /api/auth without permission)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:users:sessions:list', '查看用户会话', 'api', 'users', 'sessions:list', 'GET /api/users/:id/sessions'),
+('api:users:sessions:revoke', '注销用户会话', 'api', 'users', 'sessions:revoke', 'DELETE /api/users/:id/sessions[/:session_id]');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:users')
+WHERE code LIKE 'api:users:sessions:%';
+
+-- 4. Grant listing to roles that can view users, revocation to roles that can update them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions get ON get.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:users:sessions:list'
+WHERE get.code = 'api:users:get';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions updates ON updates.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:users:sessions:revoke'
+WHERE updates.code = 'api:users:update';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code LIKE 'api:users:sessions:%';
@@ -0,0 +1,44 @@
+-- ========================================
+-- StarRocks Admin - Single Sign-On
+-- ========================================
+-- Created: 2025-02-11
+-- Purpose: Users authenticated by an LDAP directory or an OIDC identity provider are
+--          provisioned on first login; their directory groups (or OIDC claims) decide
+--          their organization and roles through group mappings.
+
+-- 1. Where a user authenticates (local password, ldap or oidc) and their identity there
+ALTER TABLE users ADD COLUMN auth_provider VARCHAR(16) NOT NULL DEFAULT 'local';
+ALTER TABLE users ADD COLUMN external_id TEXT;                  -- LDAP DN or OIDC subject
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_external_identity
+ON users(auth_provider, external_id) WHERE external_id IS NOT NULL;
+
+-- 2. Group mappings: a matching group places the user in the organization and grants the role
+CREATE TABLE IF NOT EXISTS sso_group_mappings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider VARCHAR(16) NOT NULL,                  -- ldap, oidc
+    group_name VARCHAR(255) NOT NULL,               -- Group DN or CN, or value of the groups claim
+    organization_id INTEGER NOT NULL,
+    role_id INTEGER,                                -- NULL: only places the user in the organization
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sso_group_mappings_provider ON sso_group_mappings(provider);
+
+-- 3. API permissions (mappings span organizations, so only system administrators manage them)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:sso:mappings:list', '查看SSO组映射', 'api', 'sso', 'mappings:list', 'GET /api/sso/mappings'),
+('api:sso:mappings:create', '创建SSO组映射', 'api', 'sso', 'mappings:create', 'POST /api/sso/mappings'),
+('api:sso:mappings:delete', '删除SSO组映射', 'api', 'sso', 'mappings:delete', 'DELETE /api/sso/mappings/:id');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:roles')
+WHERE code LIKE 'api:sso:mappings:%';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin')
+  AND p.code LIKE 'api:sso:mappings:%';
@@ -0,0 +1,67 @@
+-- ========================================
+-- StarRocks Admin - Login Hardening
+-- ========================================
+-- Created: 2025-02-12
+-- Purpose: Accounts are locked for a while after repeated failed logins, local users can
+--          protect their login with a TOTP second factor and one-time recovery codes, and
+--          super administrators can turn public registration off.
+
+-- 1. Consecutive failed logins and the end of the current lock
+ALTER TABLE users ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN locked_until TIMESTAMP;
+
+-- 2. TOTP second factor, enabled once the user confirmed a first code
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id INTEGER PRIMARY KEY,
+    secret TEXT NOT NULL,                           -- Base32 secret, encrypted with the master key
+    enabled BOOLEAN NOT NULL DEFAULT 0,
+    last_used_step INTEGER,                         -- Time step of the last accepted code (no replay)
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    enabled_at TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- 3. One-time recovery codes, only their SHA-256 hash is stored
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user_id ON user_recovery_codes(user_id);
+
+-- 4. Settings changed at runtime by super administrators
+CREATE TABLE IF NOT EXISTS system_settings (
+    key VARCHAR(64) PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_by INTEGER,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (updated_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+INSERT OR IGNORE INTO system_settings (key, value) VALUES ('registration_enabled', 'true');
+
+-- 5. API permissions (a user's own second factor is managed under /api/auth without permission)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:users:two_factor:reset', '重置用户双因素认证', 'api', 'users', 'two_factor:reset', 'DELETE /api/users/:id/two-factor'),
+('api:users:lockout:clear', '解锁用户', 'api', 'users', 'lockout:clear', 'DELETE /api/users/:id/lockout');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:system:users')
+WHERE code IN ('api:users:two_factor:reset', 'api:users:lockout:clear');
+
+-- 6. Grant both to roles that can update users
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions updates ON updates.id = rp.permission_id
+JOIN permissions p ON p.code IN ('api:users:two_factor:reset', 'api:users:lockout:clear')
+WHERE updates.code = 'api:users:update';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND p.code IN ('api:users:two_factor:reset', 'api:users:lockout:clear');
@@ -0,0 +1,26 @@
+-- ========================================
+-- StarRocks Admin - Cluster-level Role Permissions
+-- ========================================
+-- Created: 2025-02-13
+-- Purpose: Roles can hold a permission on some clusters only (read-only on prod, full on
+--          staging), and the SQL editor and materialized view permissions on some catalogs
+--          or databases of a cluster. Permissions in role_permissions keep applying to every
+--          cluster of the role's organization.
+
+CREATE TABLE IF NOT EXISTS role_permission_scopes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    role_id INTEGER NOT NULL,
+    permission_id INTEGER NOT NULL,
+    cluster_id INTEGER NOT NULL,
+    catalog_name TEXT,                              -- NULL: every catalog of the cluster
+    database_name TEXT,                             -- NULL: every database of the catalog
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    FOREIGN KEY (permission_id) REFERENCES permissions(id) ON DELETE CASCADE,
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_role_permission_scopes_grant ON role_permission_scopes (
+    role_id, permission_id, cluster_id, COALESCE(catalog_name, ''), COALESCE(database_name, '')
+);
+CREATE INDEX IF NOT EXISTS idx_role_permission_scopes_cluster ON role_permission_scopes (cluster_id);
@@ -0,0 +1,42 @@
+-- ========================================
+-- StarRocks Admin - Load Job Center
+-- ========================================
+-- Created: 2025-02-14
+-- Purpose: Menu and API permissions of the load job center (list, inspect, cancel and
+--          tracking log of Broker, Spark, INSERT and Stream Load jobs from
+--          information_schema.loads). Like materialized views, they can be granted per
+--          cluster or per database.
+
+-- 1. Menu and API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('menu:loads', '导入任务', 'menu', 'loads', 'view', '查看导入任务'),
+('api:clusters:loads', '导入任务列表', 'api', 'clusters', 'loads', 'GET /api/clusters/loads'),
+('api:clusters:loads:get', '查看导入任务详情', 'api', 'clusters', 'loads:get', 'GET /api/clusters/loads/:job_id'),
+('api:clusters:loads:cancel', '取消导入任务', 'api', 'clusters', 'loads:cancel', 'POST /api/clusters/loads/:job_id/cancel'),
+('api:clusters:loads:tracking_log', '查看导入错误日志', 'api', 'clusters', 'loads:tracking_log', 'GET /api/clusters/loads/:job_id/tracking-log');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:loads')
+WHERE code LIKE 'api:clusters:loads%';
+
+-- 2. Grant viewing to roles that can view materialized views, cancelling to roles that
+--    can kill queries
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions viewer ON viewer.id = rp.permission_id
+JOIN permissions p ON p.code IN ('menu:loads', 'api:clusters:loads', 'api:clusters:loads:get', 'api:clusters:loads:tracking_log')
+WHERE viewer.code = 'api:clusters:materialized_views';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions killer ON killer.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:loads:cancel'
+WHERE killer.code = 'api:clusters:queries:kill';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND (p.code = 'menu:loads' OR p.code LIKE 'api:clusters:loads%');
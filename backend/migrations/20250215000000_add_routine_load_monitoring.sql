@@ -0,0 +1,81 @@
+-- ========================================
+-- StarRocks Admin - Routine Load Monitoring
+-- ========================================
+-- Created: 2025-02-15
+-- Purpose: Routine Load lifecycle (list, inspect, pause, resume, stop) and consumption lag
+--          monitoring. The metrics collector records the lag of every job each tick and
+--          summarizes paused jobs and the largest lag into metrics_snapshots, so alert rules
+--          can fire on them. Rows follow the metrics retention (metrics.retention_days).
+
+-- 1. Lag history (High-frequency: same interval as metrics_snapshots)
+CREATE TABLE IF NOT EXISTS routine_load_lag_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    database_name VARCHAR(255) NOT NULL,
+    job_name VARCHAR(255) NOT NULL,
+    job_id BIGINT NOT NULL,
+    state VARCHAR(20) NOT NULL,                         -- NEED_SCHEDULE, RUNNING, PAUSED, ...
+    total_lag BIGINT,                                   -- NULL when no partition offset is known
+    partition_lag TEXT,                                 -- JSON: partition -> lag
+    loaded_rows BIGINT NOT NULL DEFAULT 0,
+    error_rows BIGINT NOT NULL DEFAULT 0,
+    collected_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_routine_load_lag_history_job_time
+ON routine_load_lag_history(cluster_id, database_name, job_name, collected_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_routine_load_lag_history_time
+ON routine_load_lag_history(collected_at DESC);
+
+-- 2. Cluster level summary for alert rules
+ALTER TABLE metrics_snapshots ADD COLUMN routine_load_paused INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE metrics_snapshots ADD COLUMN routine_load_max_lag BIGINT NOT NULL DEFAULT 0;
+
+-- 3. Menu and API permissions (granted per cluster or per database like load jobs)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('menu:routine-loads', 'Routine Load', 'menu', 'routine-loads', 'view', '查看 Routine Load 任务'),
+('api:clusters:routine_loads', 'Routine Load 列表', 'api', 'clusters', 'routine_loads', 'GET /api/clusters/routine-loads'),
+('api:clusters:routine_loads:get', '查看 Routine Load 详情', 'api', 'clusters', 'routine_loads:get', 'GET /api/clusters/routine-loads/:database/:name'),
+('api:clusters:routine_loads:tasks', '查看 Routine Load 子任务', 'api', 'clusters', 'routine_loads:tasks', 'GET /api/clusters/routine-loads/:database/:name/tasks'),
+('api:clusters:routine_loads:lag', '查看 Routine Load 消费延迟', 'api', 'clusters', 'routine_loads:lag', 'GET /api/clusters/routine-loads/:database/:name/lag'),
+('api:clusters:routine_loads:pause', '暂停 Routine Load', 'api', 'clusters', 'routine_loads:pause', 'POST /api/clusters/routine-loads/:database/:name/pause'),
+('api:clusters:routine_loads:resume', '恢复 Routine Load', 'api', 'clusters', 'routine_loads:resume', 'POST /api/clusters/routine-loads/:database/:name/resume'),
+('api:clusters:routine_loads:stop', '停止 Routine Load', 'api', 'clusters', 'routine_loads:stop', 'POST /api/clusters/routine-loads/:database/:name/stop');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:routine-loads')
+WHERE code LIKE 'api:clusters:routine_loads%';
+
+-- 4. Grant viewing to roles that can view load jobs, control to roles that can cancel them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions viewer ON viewer.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'menu:routine-loads',
+    'api:clusters:routine_loads',
+    'api:clusters:routine_loads:get',
+    'api:clusters:routine_loads:tasks',
+    'api:clusters:routine_loads:lag'
+)
+WHERE viewer.code = 'api:clusters:loads';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions canceller ON canceller.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:routine_loads:pause',
+    'api:clusters:routine_loads:resume',
+    'api:clusters:routine_loads:stop'
+)
+WHERE canceller.code = 'api:clusters:loads:cancel';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r, permissions p
+WHERE r.code IN ('admin', 'super_admin', 'org_admin_default_org')
+  AND (p.code = 'menu:routine-loads' OR p.code LIKE 'api:clusters:routine_loads%');
@@ -0,0 +1,34 @@
+-- ========================================
+-- StarRocks Admin - Materialized View Dependencies
+-- ========================================
+-- Created: 2025-02-16
+-- Purpose: API permissions of the materialized view dependency graph, the staleness report
+--          (inactive views, repeatedly failing refreshes, base tables loaded after the last
+--          refresh) and the impact of dropping a table. Like the other materialized view
+--          permissions, they can be granted per cluster or per database.
+
+-- 1. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:materialized_views:dependencies', '查看物化视图依赖关系', 'api', 'clusters', 'materialized_views:dependencies', 'GET /api/clusters/materialized_views/dependencies'),
+('api:clusters:materialized_views:staleness', '查看物化视图新鲜度', 'api', 'clusters', 'materialized_views:staleness', 'GET /api/clusters/materialized_views/staleness'),
+('api:clusters:materialized_views:impact', '查看删表影响的物化视图', 'api', 'clusters', 'materialized_views:impact', 'GET /api/clusters/materialized_views/impact');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:materialized-views')
+WHERE code IN (
+    'api:clusters:materialized_views:dependencies',
+    'api:clusters:materialized_views:staleness',
+    'api:clusters:materialized_views:impact'
+);
+
+-- 2. Grant to roles that can view materialized views
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions viewer ON viewer.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:materialized_views:dependencies',
+    'api:clusters:materialized_views:staleness',
+    'api:clusters:materialized_views:impact'
+)
+WHERE viewer.code = 'api:clusters:materialized_views';
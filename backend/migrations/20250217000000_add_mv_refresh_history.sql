@@ -0,0 +1,61 @@
+-- ========================================
+-- StarRocks Admin - Materialized View Refresh History
+-- ========================================
+-- Created: 2025-02-17
+-- Purpose: Refresh task runs of the async materialized views, copied from
+--          information_schema.task_runs by the metrics collector so that they outlive the FE
+--          task run retention and restarts. Times are kept as reported by the FE. Rows follow
+--          the metrics retention (metrics.retention_days).
+
+-- 1. Refresh task runs (one row per run, updated until the run finishes)
+CREATE TABLE IF NOT EXISTS mv_refresh_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    database_name VARCHAR(255) NOT NULL,
+    mv_name VARCHAR(255) NOT NULL,
+    task_name VARCHAR(255) NOT NULL,                    -- mv-<materialized view id>
+    query_id VARCHAR(64) NOT NULL,
+    state VARCHAR(20) NOT NULL,                         -- PENDING, RUNNING, SUCCESS, FAILED, ...
+    create_time VARCHAR(32) NOT NULL,                   -- FE time, YYYY-MM-DD HH:MM:SS
+    finish_time VARCHAR(32),
+    duration_ms BIGINT,
+    error_code BIGINT,
+    error_message TEXT,
+    refreshed_partitions TEXT,                          -- JSON array of partition names
+    collected_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    UNIQUE (cluster_id, query_id),
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_mv_refresh_history_view_time
+ON mv_refresh_history(cluster_id, database_name, mv_name, create_time DESC);
+
+CREATE INDEX IF NOT EXISTS idx_mv_refresh_history_collected
+ON mv_refresh_history(collected_at);
+
+-- 2. API permissions (granted per cluster or per database like the other view permissions)
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:materialized_views:refresh_stats', '物化视图刷新统计', 'api', 'clusters', 'materialized_views:refresh_stats', 'GET /api/clusters/materialized_views/refresh-stats'),
+('api:clusters:materialized_views:refresh_history', '查看物化视图刷新历史', 'api', 'clusters', 'materialized_views:refresh_history', 'GET /api/clusters/materialized_views/:mv_name/refresh-history'),
+('api:clusters:materialized_views:refresh_trends', '查看物化视图刷新趋势', 'api', 'clusters', 'materialized_views:refresh_trends', 'GET /api/clusters/materialized_views/:mv_name/refresh-trends');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:materialized-views')
+WHERE code IN (
+    'api:clusters:materialized_views:refresh_stats',
+    'api:clusters:materialized_views:refresh_history',
+    'api:clusters:materialized_views:refresh_trends'
+);
+
+-- 3. Grant to roles that can view materialized views
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions viewer ON viewer.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:materialized_views:refresh_stats',
+    'api:clusters:materialized_views:refresh_history',
+    'api:clusters:materialized_views:refresh_trends'
+)
+WHERE viewer.code = 'api:clusters:materialized_views';
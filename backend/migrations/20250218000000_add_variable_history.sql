@@ -0,0 +1,84 @@
+-- ========================================
+-- StarRocks Admin - Variable Change History and Snapshots
+-- ========================================
+-- Created: 2025-02-18
+-- Purpose: Variable changes made through starrocks-admin with the value they replaced (for
+--          rollback), and snapshots of all GLOBAL variables taken by the metrics collector.
+--          Changes are kept as an audit trail; snapshots follow the metrics retention
+--          (metrics.retention_days).
+
+-- 1. Changes (SET [GLOBAL] through the API, including rollbacks)
+CREATE TABLE IF NOT EXISTS variable_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    variable_name VARCHAR(255) NOT NULL,
+    scope VARCHAR(10) NOT NULL,                         -- GLOBAL or SESSION
+    old_value TEXT,                                     -- NULL when it could not be read
+    new_value TEXT NOT NULL,
+    changed_by INTEGER,
+    changed_by_username VARCHAR(100) NOT NULL,
+    rollback_of INTEGER,                                -- change this one rolled back
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE,
+    FOREIGN KEY (changed_by) REFERENCES users(id) ON DELETE SET NULL,
+    FOREIGN KEY (rollback_of) REFERENCES variable_changes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_variable_changes_cluster
+ON variable_changes(cluster_id, id DESC);
+
+-- 2. GLOBAL variable snapshots (stored when the variables changed, at least hourly)
+CREATE TABLE IF NOT EXISTS variable_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    cluster_id INTEGER NOT NULL,
+    variable_count INTEGER NOT NULL,
+    variables TEXT NOT NULL,                            -- JSON object: name -> value
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (cluster_id) REFERENCES clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_variable_snapshots_cluster
+ON variable_snapshots(cluster_id, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_variable_snapshots_created
+ON variable_snapshots(created_at);
+
+-- 3. API permissions
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:variables:history', '查看变量变更历史', 'api', 'clusters', 'variables:history', 'GET /api/clusters/variables/history'),
+('api:clusters:variables:rollback', '回滚变量', 'api', 'clusters', 'variables:rollback', 'POST /api/clusters/variables/history/:change_id/rollback'),
+('api:clusters:variables:snapshots', '查看变量快照', 'api', 'clusters', 'variables:snapshots', 'GET /api/clusters/variables/snapshots'),
+('api:clusters:variables:snapshots:create', '创建变量快照', 'api', 'clusters', 'variables:snapshots:create', 'POST /api/clusters/variables/snapshots');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:variables')
+WHERE code IN (
+    'api:clusters:variables:history',
+    'api:clusters:variables:rollback',
+    'api:clusters:variables:snapshots',
+    'api:clusters:variables:snapshots:create'
+);
+
+-- 4. Grant viewing to roles that can view variables, rollback and snapshots to roles that
+--    can change them
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions viewer ON viewer.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:variables:history',
+    'api:clusters:variables:snapshots'
+)
+WHERE viewer.code = 'api:clusters:variables';
+
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions editor ON editor.id = rp.permission_id
+JOIN permissions p ON p.code IN (
+    'api:clusters:variables:rollback',
+    'api:clusters:variables:snapshots:create'
+)
+WHERE editor.code = 'api:clusters:variables:update';
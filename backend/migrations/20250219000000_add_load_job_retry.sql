@@ -0,0 +1,23 @@
+-- ========================================
+-- StarRocks Admin - Load Job Retry
+-- ========================================
+-- Created: 2025-02-19
+-- Purpose: API permission to resubmit a cancelled Broker, Spark or INSERT load job from its
+--          statement in the audit log of the cluster. Granted to roles that can cancel load
+--          jobs.
+
+-- 1. API permission
+INSERT OR IGNORE INTO permissions (code, name, type, resource, action, description) VALUES
+('api:clusters:loads:retry', '重试导入任务', 'api', 'clusters', 'loads:retry', 'POST /api/clusters/loads/:job_id/retry');
+
+UPDATE permissions
+SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:loads')
+WHERE code = 'api:clusters:loads:retry';
+
+-- 2. Grant to roles that can cancel load jobs
+INSERT OR IGNORE INTO role_permissions (role_id, permission_id)
+SELECT rp.role_id, p.id
+FROM role_permissions rp
+JOIN permissions canceller ON canceller.id = rp.permission_id
+JOIN permissions p ON p.code = 'api:clusters:loads:retry'
+WHERE canceller.code = 'api:clusters:loads:cancel';
@@ -0,0 +1,10 @@
+-- ========================================
+-- StarRocks Admin - Profile Archive Re-analysis over POST
+-- ========================================
+-- Created: 2025-02-20
+-- Purpose: Re-analyzing an archived profile stores its new summary, so the endpoint is
+--          POST /api/clusters/profile-archives/:id/analyze. Same permission, new description.
+
+UPDATE permissions
+SET description = 'POST /api/clusters/profile-archives/:id/analyze'
+WHERE code = 'api:clusters:profile_archives:analyze';
@@ -13,9 +13,14 @@ pub struct Config {
     pub static_config: StaticConfig,
     pub metrics: MetricsCollectorConfig,
     pub audit: AuditLogConfig,
+    pub security: SecurityConfig,
+    pub profile_archive: ProfileArchiveConfig,
+    pub alert: AlertConfig,
+    pub sso: SsoConfig,
 }
 
 /// Audit log configuration for StarRocks audit table
+/// (default of every cluster; overridden per cluster with /api/clusters/:id/audit-source)
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct AuditLogConfig {
@@ -41,6 +46,19 @@ impl AuditLogConfig {
     }
 }
 
+/// Master key configuration for encrypting cluster credentials at rest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Base64-encoded 32-byte master key (takes precedence over master_key_file)
+    pub master_key: Option<String>,
+    /// File holding the base64-encoded master key; generated on first start if missing
+    pub master_key_file: String,
+    /// New key file to re-encrypt all credentials with (CLI only, runs and exits)
+    #[serde(skip)]
+    pub rotate_master_key_to: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct ServerConfig {
@@ -58,7 +76,82 @@ pub struct DatabaseConfig {
 #[serde(default)]
 pub struct AuthConfig {
     pub jwt_secret: String,
+    /// Lifetime of an access token (default: 24h). The web UI does not call
+    /// `/api/auth/refresh` yet and logs out when the token expires, so shorter values only
+    /// suit API clients that refresh themselves.
     pub jwt_expires_in: String,
+    /// Lifetime of a login session without refresh (e.g. "7d"), extended by every refresh
+    pub refresh_expires_in: String,
+    /// Rules for passwords set at registration, by an administrator or by their owner
+    pub password_policy: PasswordPolicyConfig,
+    /// Throttling of failed logins (local and LDAP)
+    pub lockout: LoginLockoutConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyConfig {
+    /// Minimum number of characters (default: 8)
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    /// Require a character that is neither a letter nor a digit
+    pub require_symbol: bool,
+    /// Refuse passwords containing the username, ignoring case (default: true)
+    pub reject_username: bool,
+}
+
+impl PasswordPolicyConfig {
+    /// Check a new password, listing every unmet rule in the error
+    pub fn check(&self, username: &str, password: &str) -> Result<(), String> {
+        let mut unmet = Vec::new();
+        if password.chars().count() < self.min_length {
+            unmet.push(format!("at least {} characters", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            unmet.push("an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            unmet.push("a lowercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            unmet.push("a digit".to_string());
+        }
+        if self.require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+            unmet.push("a symbol".to_string());
+        }
+        if !unmet.is_empty() {
+            return Err(format!("Password must contain {}", unmet.join(", ")));
+        }
+
+        let username = username.trim().to_lowercase();
+        if self.reject_username
+            && !username.is_empty()
+            && password.to_lowercase().contains(&username)
+        {
+            return Err("Password must not contain the username".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A login failing max_failed_attempts times in a row locks the account for lockout_secs;
+/// an address failing ip_max_failed_attempts times within ip_window_secs is refused until the
+/// window ends. 0 disables either check.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoginLockoutConfig {
+    /// Consecutive failures locking an account (default: 5)
+    pub max_failed_attempts: u32,
+    /// How long an account stays locked (default: 15m)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub lockout_secs: u64,
+    /// Failures accepted from one client address within the window (default: 20)
+    pub ip_max_failed_attempts: u32,
+    /// Window of the per-address limit (default: 15m)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub ip_window_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -87,6 +180,116 @@ pub struct MetricsCollectorConfig {
     pub retention_days: i64,
     /// Whether to enable the metrics collector at startup (default: true)
     pub enabled: bool,
+    /// Bearer token required to scrape GET /metrics (default: none)
+    pub exporter_token: Option<String>,
+    /// Serve GET /metrics without a token to loopback peers when no token is set (default:
+    /// false). Behind a reverse proxy on the same host every request comes from loopback.
+    pub allow_unauthenticated_local: bool,
+    /// Clusters collected at the same time (default: 4)
+    pub max_concurrency: usize,
+    /// Longest delay before retrying a cluster that keeps failing, in seconds (default: 600)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub max_backoff_secs: u64,
+}
+
+/// Query profile archive configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProfileArchiveConfig {
+    /// Automatically archive finished profiles from SHOW PROFILELIST (default: true)
+    pub auto_archive: bool,
+    /// Archive/cleanup cycle interval in seconds (default: 300)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub interval_secs: u64,
+    /// Archived profile retention days (default: 30)
+    #[serde(deserialize_with = "deserialize_days_i64")]
+    pub retention_days: i64,
+    /// Only auto-archive queries running at least this long, in ms (default: 1000)
+    pub min_duration_ms: u64,
+    /// Gzip-compress stored profile text (default: true)
+    pub compress: bool,
+}
+
+/// Alert rules engine configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Alert history retention days (default: 30)
+    #[serde(deserialize_with = "deserialize_days_i64")]
+    pub history_retention_days: i64,
+    /// Timeout for a single notification delivery in seconds (default: 10)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub notify_timeout_secs: u64,
+}
+
+/// Single sign-on providers (local password login stays available)
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct SsoConfig {
+    /// Let in users no group mapping matches, in the default organization without roles
+    /// (default: false)
+    pub allow_unmapped_users: bool,
+    pub ldap: LdapConfig,
+    pub oidc: OidcConfig,
+}
+
+/// LDAP directory: users log in with their directory password at /api/auth/ldap/login
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LdapConfig {
+    pub enabled: bool,
+    /// Server URL, ldap:// or ldaps://
+    pub url: String,
+    /// Upgrade ldap:// connections with STARTTLS (default: false)
+    pub starttls: bool,
+    /// Service account searching for users (anonymous search when unset)
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    /// Where users are searched
+    pub user_base_dn: String,
+    /// User search filter, `{username}` is replaced with the escaped login name
+    pub user_filter: String,
+    /// Attribute holding the login name (default: uid)
+    pub username_attribute: String,
+    /// Attribute holding the email address (default: mail)
+    pub email_attribute: String,
+    /// Attribute of user entries listing their group DNs, read when group_base_dn is unset
+    /// (default: memberOf)
+    pub group_membership_attribute: String,
+    /// Where groups are searched with group_filter (default: unset, memberOf is read)
+    pub group_base_dn: Option<String>,
+    /// Group search filter, `{dn}` is replaced with the user DN and `{username}` with the
+    /// login name
+    pub group_filter: String,
+    /// Connection and operation timeout in seconds (default: 10)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub timeout_secs: u64,
+}
+
+/// OpenID Connect provider: authorization-code flow started at /api/auth/oidc/authorize
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    pub enabled: bool,
+    /// Issuer URL, `/.well-known/openid-configuration` is read from it
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Callback registered at the provider, ending in /api/auth/oidc/callback
+    pub redirect_url: String,
+    /// Requested scopes (default: openid, profile, email)
+    pub scopes: Vec<String>,
+    /// ID token claim holding the login name (default: preferred_username)
+    pub username_claim: String,
+    /// ID token claim holding the email address (default: email)
+    pub email_claim: String,
+    /// ID token claim listing the groups matched against group mappings (default: groups)
+    pub groups_claim: String,
+    /// Frontend page receiving the tokens (or the error) in its URL fragment after login
+    pub post_login_redirect: String,
+    /// Timeout of requests to the provider in seconds (default: 10)
+    #[serde(deserialize_with = "deserialize_duration_secs")]
+    pub timeout_secs: u64,
 }
 
 /// Command line arguments for configuration overrides
@@ -118,6 +321,10 @@ pub struct CommandLineArgs {
     #[arg(long, value_name = "DURATION")]
     pub jwt_expires_in: Option<String>,
 
+    /// Refresh token expiration time (overrides config file, e.g., "7d")
+    #[arg(long, value_name = "DURATION")]
+    pub refresh_expires_in: Option<String>,
+
     /// Logging level (overrides config file, e.g., "info,starrocks_admin_backend=debug")
     #[arg(long, value_name = "LEVEL")]
     pub log_level: Option<String>,
@@ -141,6 +348,26 @@ pub struct CommandLineArgs {
     /// Audit log table name (overrides config file, default: starrocks_audit_tbl__)
     #[arg(long, value_name = "TABLE")]
     pub audit_table: Option<String>,
+
+    /// Base64-encoded master key for credential encryption (overrides config file)
+    #[arg(long, value_name = "KEY")]
+    pub master_key: Option<String>,
+
+    /// Master key file for credential encryption (overrides config file)
+    #[arg(long, value_name = "PATH")]
+    pub master_key_file: Option<String>,
+
+    /// Re-encrypt all stored credentials with the key in PATH (generated if missing), then exit
+    #[arg(long, value_name = "PATH")]
+    pub rotate_master_key: Option<String>,
+
+    /// Enable/disable automatic profile archiving (overrides config file)
+    #[arg(long, value_name = "BOOL")]
+    pub profile_archive_auto: Option<bool>,
+
+    /// Archived profile retention days (overrides config file, e.g., "30d", "4w")
+    #[arg(long, value_name = "DAYS")]
+    pub profile_archive_retention_days: Option<String>,
 }
 
 impl Config {
@@ -188,8 +415,18 @@ impl Config {
     /// - APP_METRICS_INTERVAL_SECS: Metrics collection interval in seconds (accepts "30s", "5m", "1h")
     /// - APP_METRICS_RETENTION_DAYS: Retention days for metrics (accepts "7d")
     /// - APP_METRICS_ENABLED: Enable/disable metrics collector (true/false)
+    /// - APP_METRICS_EXPORTER_TOKEN: Bearer token required to scrape /metrics
+    /// - APP_METRICS_ALLOW_UNAUTHENTICATED_LOCAL: Serve /metrics to local scrapers without a token
+    /// - APP_METRICS_MAX_CONCURRENCY: Clusters collected at the same time
     /// - APP_AUDIT_DATABASE: Audit log database name (default: starrocks_audit_db__)
     /// - APP_AUDIT_TABLE: Audit log table name (default: starrocks_audit_tbl__)
+    /// - APP_MASTER_KEY: Base64-encoded master key for credential encryption
+    /// - APP_MASTER_KEY_FILE: Master key file (default: data/master.key)
+    /// - APP_PROFILE_ARCHIVE_AUTO: Enable/disable automatic profile archiving (true/false)
+    /// - APP_PROFILE_ARCHIVE_RETENTION_DAYS: Archived profile retention days (accepts "30d")
+    /// - APP_ALERT_HISTORY_RETENTION_DAYS: Alert history retention days (accepts "30d")
+    /// - APP_LDAP_BIND_PASSWORD: Password of the LDAP search account
+    /// - APP_OIDC_CLIENT_SECRET: OIDC client secret
     fn apply_env_overrides(&mut self) {
         if let Ok(host) = std::env::var("APP_SERVER_HOST") {
             self.server.host = host;
@@ -218,6 +455,14 @@ impl Config {
             tracing::info!("Override auth.jwt_expires_in from env: {}", self.auth.jwt_expires_in);
         }
 
+        if let Ok(expires) = std::env::var("APP_REFRESH_EXPIRES_IN") {
+            self.auth.refresh_expires_in = expires;
+            tracing::info!(
+                "Override auth.refresh_expires_in from env: {}",
+                self.auth.refresh_expires_in
+            );
+        }
+
         if let Ok(level) = std::env::var("APP_LOG_LEVEL") {
             self.logging.level = level;
             tracing::info!("Override logging.level from env: {}", self.logging.level);
@@ -267,6 +512,31 @@ impl Config {
             tracing::info!("Override metrics.enabled from env: {}", self.metrics.enabled);
         }
 
+        if let Ok(token) = std::env::var("APP_METRICS_EXPORTER_TOKEN") {
+            self.metrics.exporter_token = Some(token).filter(|t| !t.is_empty());
+            tracing::info!("Override metrics.exporter_token from env");
+        }
+
+        if let Ok(allow) = std::env::var("APP_METRICS_ALLOW_UNAUTHENTICATED_LOCAL")
+            && let Ok(val) = allow.parse()
+        {
+            self.metrics.allow_unauthenticated_local = val;
+            tracing::info!(
+                "Override metrics.allow_unauthenticated_local from env: {}",
+                self.metrics.allow_unauthenticated_local
+            );
+        }
+
+        if let Ok(concurrency) = std::env::var("APP_METRICS_MAX_CONCURRENCY")
+            && let Ok(val) = concurrency.parse()
+        {
+            self.metrics.max_concurrency = val;
+            tracing::info!(
+                "Override metrics.max_concurrency from env: {}",
+                self.metrics.max_concurrency
+            );
+        }
+
         // Audit log overrides
         if let Ok(database) = std::env::var("APP_AUDIT_DATABASE") {
             self.audit.database = database;
@@ -277,6 +547,77 @@ impl Config {
             self.audit.table = table;
             tracing::info!("Override audit.table from env: {}", self.audit.table);
         }
+
+        // Credential encryption overrides
+        if let Ok(key) = std::env::var("APP_MASTER_KEY") {
+            self.security.master_key = Some(key);
+            tracing::info!("Override security.master_key from env");
+        }
+
+        if let Ok(path) = std::env::var("APP_MASTER_KEY_FILE") {
+            self.security.master_key_file = path;
+            tracing::info!(
+                "Override security.master_key_file from env: {}",
+                self.security.master_key_file
+            );
+        }
+
+        // Profile archive overrides
+        if let Ok(auto) = std::env::var("APP_PROFILE_ARCHIVE_AUTO")
+            && let Ok(val) = auto.parse()
+        {
+            self.profile_archive.auto_archive = val;
+            tracing::info!(
+                "Override profile_archive.auto_archive from env: {}",
+                self.profile_archive.auto_archive
+            );
+        }
+
+        if let Ok(retention) = std::env::var("APP_PROFILE_ARCHIVE_RETENTION_DAYS") {
+            match parse_days_to_i64(&retention) {
+                Ok(val) => {
+                    self.profile_archive.retention_days = val;
+                    tracing::info!(
+                        "Override profile_archive.retention_days from env: {}",
+                        self.profile_archive.retention_days
+                    );
+                },
+                Err(e) => tracing::warn!(
+                    "Invalid APP_PROFILE_ARCHIVE_RETENTION_DAYS '{}': {} (keep {})",
+                    retention,
+                    e,
+                    self.profile_archive.retention_days
+                ),
+            }
+        }
+
+        if let Ok(retention) = std::env::var("APP_ALERT_HISTORY_RETENTION_DAYS") {
+            match parse_days_to_i64(&retention) {
+                Ok(val) => {
+                    self.alert.history_retention_days = val;
+                    tracing::info!(
+                        "Override alert.history_retention_days from env: {}",
+                        self.alert.history_retention_days
+                    );
+                },
+                Err(e) => tracing::warn!(
+                    "Invalid APP_ALERT_HISTORY_RETENTION_DAYS '{}': {} (keep {})",
+                    retention,
+                    e,
+                    self.alert.history_retention_days
+                ),
+            }
+        }
+
+        if let Ok(password) = std::env::var("APP_LDAP_BIND_PASSWORD") {
+            self.sso.ldap.bind_password = Some(password);
+            tracing::info!("Override sso.ldap.bind_password from env");
+        }
+
+        if let Ok(secret) = std::env::var("APP_OIDC_CLIENT_SECRET") {
+            self.sso.oidc.client_secret = secret;
+            tracing::info!("Override sso.oidc.client_secret from env");
+        }
     }
 
     /// Apply command line argument overrides (highest priority)
@@ -306,6 +647,14 @@ impl Config {
             tracing::info!("Override auth.jwt_expires_in from CLI: {}", self.auth.jwt_expires_in);
         }
 
+        if let Some(expires) = &args.refresh_expires_in {
+            self.auth.refresh_expires_in = expires.clone();
+            tracing::info!(
+                "Override auth.refresh_expires_in from CLI: {}",
+                self.auth.refresh_expires_in
+            );
+        }
+
         if let Some(level) = &args.log_level {
             self.logging.level = level.clone();
             tracing::info!("Override logging.level from CLI: {}", self.logging.level);
@@ -361,6 +710,47 @@ impl Config {
             self.audit.table = table.clone();
             tracing::info!("Override audit.table from CLI: {}", self.audit.table);
         }
+
+        if let Some(key) = &args.master_key {
+            self.security.master_key = Some(key.clone());
+            tracing::info!("Override security.master_key from CLI");
+        }
+
+        if let Some(path) = &args.master_key_file {
+            self.security.master_key_file = path.clone();
+            tracing::info!(
+                "Override security.master_key_file from CLI: {}",
+                self.security.master_key_file
+            );
+        }
+
+        self.security.rotate_master_key_to = args.rotate_master_key.clone();
+
+        if let Some(auto) = args.profile_archive_auto {
+            self.profile_archive.auto_archive = auto;
+            tracing::info!(
+                "Override profile_archive.auto_archive from CLI: {}",
+                self.profile_archive.auto_archive
+            );
+        }
+
+        if let Some(retention) = &args.profile_archive_retention_days {
+            match parse_days_to_i64(retention) {
+                Ok(val) => {
+                    self.profile_archive.retention_days = val;
+                    tracing::info!(
+                        "Override profile_archive.retention_days from CLI: {}",
+                        self.profile_archive.retention_days
+                    );
+                },
+                Err(e) => tracing::warn!(
+                    "Invalid --profile-archive-retention-days '{}': {} (keep {})",
+                    retention,
+                    e,
+                    self.profile_archive.retention_days
+                ),
+            }
+        }
     }
 
     /// Validate configuration
@@ -384,6 +774,18 @@ impl Config {
             anyhow::bail!("Database URL cannot be empty");
         }
 
+        // Validate login hardening
+        if self.auth.password_policy.min_length == 0 {
+            anyhow::bail!("auth.password_policy.min_length must be > 0");
+        }
+        let lockout = &self.auth.lockout;
+        if lockout.max_failed_attempts > 0 && lockout.lockout_secs == 0 {
+            anyhow::bail!("auth.lockout.lockout_secs must be > 0");
+        }
+        if lockout.ip_max_failed_attempts > 0 && lockout.ip_window_secs == 0 {
+            anyhow::bail!("auth.lockout.ip_window_secs must be > 0");
+        }
+
         // Validate metrics collector
         if self.metrics.interval_secs == 0 {
             anyhow::bail!("metrics.interval_secs must be > 0");
@@ -391,6 +793,63 @@ impl Config {
         if self.metrics.retention_days <= 0 {
             anyhow::bail!("metrics.retention_days must be > 0");
         }
+        if self.metrics.max_concurrency == 0 {
+            anyhow::bail!("metrics.max_concurrency must be > 0");
+        }
+
+        // Validate profile archive
+        if self.profile_archive.interval_secs == 0 {
+            anyhow::bail!("profile_archive.interval_secs must be > 0");
+        }
+        if self.profile_archive.retention_days <= 0 {
+            anyhow::bail!("profile_archive.retention_days must be > 0");
+        }
+
+        // Validate alerting
+        if self.alert.history_retention_days <= 0 {
+            anyhow::bail!("alert.history_retention_days must be > 0");
+        }
+        if self.alert.notify_timeout_secs == 0 {
+            anyhow::bail!("alert.notify_timeout_secs must be > 0");
+        }
+
+        // Validate single sign-on
+        let ldap = &self.sso.ldap;
+        if ldap.enabled {
+            if ldap.url.is_empty() || ldap.user_base_dn.is_empty() {
+                anyhow::bail!("sso.ldap.url and sso.ldap.user_base_dn must be set");
+            }
+            if !ldap.user_filter.contains("{username}") {
+                anyhow::bail!("sso.ldap.user_filter must contain {{username}}");
+            }
+            if ldap.timeout_secs == 0 {
+                anyhow::bail!("sso.ldap.timeout_secs must be > 0");
+            }
+        }
+        let oidc = &self.sso.oidc;
+        if oidc.enabled {
+            if oidc.issuer_url.is_empty()
+                || oidc.client_id.is_empty()
+                || oidc.redirect_url.is_empty()
+            {
+                anyhow::bail!(
+                    "sso.oidc.issuer_url, sso.oidc.client_id and sso.oidc.redirect_url must be set"
+                );
+            }
+            if oidc.timeout_secs == 0 {
+                anyhow::bail!("sso.oidc.timeout_secs must be > 0");
+            }
+        }
+
+        // Validate credential encryption
+        let has_inline_key = self
+            .security
+            .master_key
+            .as_deref()
+            .is_some_and(|k| !k.is_empty());
+        if !has_inline_key && self.security.master_key_file.is_empty() {
+            anyhow::bail!("security.master_key or security.master_key_file must be set");
+        }
 
         Ok(())
     }
@@ -431,6 +890,33 @@ impl Default for AuthConfig {
         Self {
             jwt_secret: "dev-secret-key-change-in-production".to_string(),
             jwt_expires_in: "24h".to_string(),
+            refresh_expires_in: "7d".to_string(),
+            password_policy: PasswordPolicyConfig::default(),
+            lockout: LoginLockoutConfig::default(),
+        }
+    }
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: false,
+            require_symbol: false,
+            reject_username: true,
+        }
+    }
+}
+
+impl Default for LoginLockoutConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts: 5,
+            lockout_secs: 15 * 60,
+            ip_max_failed_attempts: 20,
+            ip_window_secs: 15 * 60,
         }
     }
 }
@@ -450,9 +936,83 @@ impl Default for StaticConfig {
     }
 }
 
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            master_key: None,
+            master_key_file: "data/master.key".to_string(),
+            rotate_master_key_to: None,
+        }
+    }
+}
+
+impl Default for ProfileArchiveConfig {
+    fn default() -> Self {
+        Self {
+            auto_archive: true,
+            interval_secs: 300,
+            retention_days: 30,
+            min_duration_ms: 1000,
+            compress: true,
+        }
+    }
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self { history_retention_days: 30, notify_timeout_secs: 10 }
+    }
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            starttls: false,
+            bind_dn: None,
+            bind_password: None,
+            user_base_dn: String::new(),
+            user_filter: "(uid={username})".to_string(),
+            username_attribute: "uid".to_string(),
+            email_attribute: "mail".to_string(),
+            group_membership_attribute: "memberOf".to_string(),
+            group_base_dn: None,
+            group_filter: "(member={dn})".to_string(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            issuer_url: String::new(),
+            client_id: String::new(),
+            client_secret: String::new(),
+            redirect_url: String::new(),
+            scopes: vec!["openid".to_string(), "profile".to_string(), "email".to_string()],
+            username_claim: "preferred_username".to_string(),
+            email_claim: "email".to_string(),
+            groups_claim: "groups".to_string(),
+            post_login_redirect: "/#/auth/sso".to_string(),
+            timeout_secs: 10,
+        }
+    }
+}
+
 impl Default for MetricsCollectorConfig {
     fn default() -> Self {
-        Self { interval_secs: 30, retention_days: 7, enabled: true }
+        Self {
+            interval_secs: 30,
+            retention_days: 7,
+            enabled: true,
+            exporter_token: None,
+            allow_unauthenticated_local: false,
+            max_concurrency: 4,
+            max_backoff_secs: 600,
+        }
     }
 }
 
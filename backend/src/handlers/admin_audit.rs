@@ -0,0 +1,90 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::models::{AdminAuditListResponse, AdminAuditQuery};
+use crate::utils::ApiResult;
+
+/// List the admin audit log of the caller's organization
+#[utoipa::path(
+    get,
+    path = "/api/audit-logs",
+    params(
+        ("organization_id" = Option<i64>, Query, description = "Organization ID (super admin only)"),
+        ("user_id" = Option<i64>, Query, description = "User ID"),
+        ("username" = Option<String>, Query, description = "Username"),
+        ("cluster_id" = Option<i64>, Query, description = "Cluster ID"),
+        ("action" = Option<String>, Query, description = "Action prefix, e.g. clusters:queries"),
+        ("success" = Option<bool>, Query, description = "Only successful or failed actions"),
+        ("start_time" = Option<String>, Query, description = "From time (RFC 3339)"),
+        ("end_time" = Option<String>, Query, description = "To time (RFC 3339)"),
+        ("keyword" = Option<String>, Query, description = "Matches path, target and payload"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20, max: 200)")
+    ),
+    responses(
+        (status = 200, description = "Admin audit log", body = AdminAuditListResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin Audit"
+)]
+pub async fn list_admin_audit_logs(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(query): Query<AdminAuditQuery>,
+) -> ApiResult<Json<AdminAuditListResponse>> {
+    let logs = state
+        .admin_audit_service
+        .list(org_ctx.organization_id, org_ctx.is_super_admin, &query)
+        .await?;
+    Ok(Json(logs))
+}
+
+/// Export the admin audit log of the caller's organization as CSV
+#[utoipa::path(
+    get,
+    path = "/api/audit-logs/export",
+    params(
+        ("organization_id" = Option<i64>, Query, description = "Organization ID (super admin only)"),
+        ("user_id" = Option<i64>, Query, description = "User ID"),
+        ("username" = Option<String>, Query, description = "Username"),
+        ("cluster_id" = Option<i64>, Query, description = "Cluster ID"),
+        ("action" = Option<String>, Query, description = "Action prefix, e.g. clusters:queries"),
+        ("success" = Option<bool>, Query, description = "Only successful or failed actions"),
+        ("start_time" = Option<String>, Query, description = "From time (RFC 3339)"),
+        ("end_time" = Option<String>, Query, description = "To time (RFC 3339)")
+    ),
+    responses(
+        (status = 200, description = "CSV file (at most 10000 rows)", content_type = "text/csv")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin Audit"
+)]
+pub async fn export_admin_audit_logs(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(query): Query<AdminAuditQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let csv = state
+        .admin_audit_service
+        .export_csv(org_ctx.organization_id, org_ctx.is_super_admin, &query)
+        .await?;
+
+    let filename = format!("admin_audit_log_{}.csv", chrono::Utc::now().format("%Y%m%d%H%M%S"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        // BOM so spreadsheet tools detect UTF-8
+        format!("\u{feff}{}", csv),
+    ))
+}
@@ -0,0 +1,421 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use std::sync::Arc;
+
+use crate::models::{
+    AlertChannel, AlertHistoryListResponse, AlertHistoryQuery, AlertMetricInfo, AlertRule,
+    CreateAlertChannelRequest, CreateAlertRuleRequest, SilenceAlertRuleRequest,
+    UpdateAlertChannelRequest, UpdateAlertRuleRequest,
+};
+use crate::utils::ApiResult;
+
+// ========================================
+// Alert Rules
+// ========================================
+
+/// List metrics that alert rules can watch
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/metrics",
+    responses(
+        (status = 200, description = "Alert metrics", body = Vec<AlertMetricInfo>)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn list_alert_metrics(
+    State(state): State<Arc<crate::AppState>>,
+) -> ApiResult<Json<Vec<AlertMetricInfo>>> {
+    Ok(Json(state.alert_service.list_metrics()))
+}
+/// List alert rules of the active cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/rules",
+    responses(
+        (status = 200, description = "Alert rules", body = Vec<AlertRule>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn list_alert_rules(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Vec<AlertRule>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rules = state.alert_service.list_rules(cluster.id).await?;
+    Ok(Json(rules))
+}
+/// Create an alert rule for the active cluster
+#[utoipa::path(
+    post,
+    path = "/api/clusters/alerts/rules",
+    request_body = CreateAlertRuleRequest,
+    responses(
+        (status = 200, description = "Alert rule created", body = AlertRule),
+        (status = 400, description = "Invalid rule"),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn create_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(req): Json<CreateAlertRuleRequest>,
+) -> ApiResult<Json<AlertRule>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rule = state
+        .alert_service
+        .create_rule(cluster.id, req, Some(org_ctx.user_id))
+        .await?;
+    Ok(Json(rule))
+}
+/// Get an alert rule
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/rules/{id}",
+    params(
+        ("id" = i64, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule", body = AlertRule),
+        (status = 404, description = "Alert rule not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn get_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<AlertRule>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rule = state.alert_service.get_rule(cluster.id, id).await?;
+    Ok(Json(rule))
+}
+/// Update an alert rule
+#[utoipa::path(
+    put,
+    path = "/api/clusters/alerts/rules/{id}",
+    request_body = UpdateAlertRuleRequest,
+    params(
+        ("id" = i64, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule updated", body = AlertRule),
+        (status = 400, description = "Invalid rule"),
+        (status = 404, description = "Alert rule not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn update_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateAlertRuleRequest>,
+) -> ApiResult<Json<AlertRule>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rule = state.alert_service.update_rule(cluster.id, id, req).await?;
+    Ok(Json(rule))
+}
+/// Delete an alert rule (its history is kept)
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/alerts/rules/{id}",
+    params(
+        ("id" = i64, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule deleted"),
+        (status = 404, description = "Alert rule not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn delete_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    state.alert_service.delete_rule(cluster.id, id).await?;
+    Ok(Json(serde_json::json!({ "message": "Alert rule deleted" })))
+}
+/// Silence notifications of an alert rule for a period
+#[utoipa::path(
+    post,
+    path = "/api/clusters/alerts/rules/{id}/silence",
+    request_body = SilenceAlertRuleRequest,
+    params(
+        ("id" = i64, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule silenced", body = AlertRule),
+        (status = 404, description = "Alert rule not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn silence_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+    Json(req): Json<SilenceAlertRuleRequest>,
+) -> ApiResult<Json<AlertRule>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rule = state
+        .alert_service
+        .silence_rule(cluster.id, id, req.duration_secs)
+        .await?;
+    Ok(Json(rule))
+}
+/// Remove the silence of an alert rule
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/alerts/rules/{id}/silence",
+    params(
+        ("id" = i64, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule unsilenced", body = AlertRule),
+        (status = 404, description = "Alert rule not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn unsilence_alert_rule(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<AlertRule>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let rule = state.alert_service.unsilence_rule(cluster.id, id).await?;
+    Ok(Json(rule))
+}
+
+// ========================================
+// Notification Channels
+// ========================================
+/// List notification channels of the active cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/channels",
+    responses(
+        (status = 200, description = "Notification channels", body = Vec<AlertChannel>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn list_alert_channels(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Vec<AlertChannel>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let channels = state.alert_service.list_channels(cluster.id).await?;
+    Ok(Json(channels))
+}
+/// Create a notification channel
+#[utoipa::path(
+    post,
+    path = "/api/clusters/alerts/channels",
+    request_body = CreateAlertChannelRequest,
+    responses(
+        (status = 200, description = "Notification channel created", body = AlertChannel),
+        (status = 400, description = "Invalid channel config"),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn create_alert_channel(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(req): Json<CreateAlertChannelRequest>,
+) -> ApiResult<Json<AlertChannel>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let channel = state
+        .alert_service
+        .create_channel(cluster.id, req, Some(org_ctx.user_id))
+        .await?;
+    Ok(Json(channel))
+}
+/// Get a notification channel
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/channels/{id}",
+    params(
+        ("id" = i64, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Notification channel", body = AlertChannel),
+        (status = 404, description = "Notification channel not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn get_alert_channel(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<AlertChannel>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let channel = state.alert_service.get_channel(cluster.id, id).await?;
+    Ok(Json(channel))
+}
+/// Update a notification channel
+#[utoipa::path(
+    put,
+    path = "/api/clusters/alerts/channels/{id}",
+    request_body = UpdateAlertChannelRequest,
+    params(
+        ("id" = i64, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Notification channel updated", body = AlertChannel),
+        (status = 400, description = "Invalid channel config"),
+        (status = 404, description = "Notification channel not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn update_alert_channel(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateAlertChannelRequest>,
+) -> ApiResult<Json<AlertChannel>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let channel = state
+        .alert_service
+        .update_channel(cluster.id, id, req)
+        .await?;
+    Ok(Json(channel))
+}
+/// Delete a notification channel and detach it from alert rules
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/alerts/channels/{id}",
+    params(
+        ("id" = i64, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Notification channel deleted"),
+        (status = 404, description = "Notification channel not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn delete_alert_channel(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    state.alert_service.delete_channel(cluster.id, id).await?;
+    Ok(Json(serde_json::json!({ "message": "Notification channel deleted" })))
+}
+/// Send a test notification through a channel
+#[utoipa::path(
+    post,
+    path = "/api/clusters/alerts/channels/{id}/test",
+    params(
+        ("id" = i64, Path, description = "Channel ID")
+    ),
+    responses(
+        (status = 200, description = "Test notification sent"),
+        (status = 404, description = "Notification channel not found"),
+        (status = 500, description = "Delivery failed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn test_alert_channel(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    state.alert_service.test_channel(&cluster, id).await?;
+    Ok(Json(serde_json::json!({ "message": "Test notification sent" })))
+}
+
+// ========================================
+// Alert History
+// ========================================
+/// List alert history (firing/resolved events) of the active cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/alerts/history",
+    params(
+        ("rule_id" = Option<i64>, Query, description = "Alert rule ID"),
+        ("status" = Option<String>, Query, description = "firing or resolved"),
+        ("severity" = Option<String>, Query, description = "info, warning or critical"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20, max: 200)")
+    ),
+    responses(
+        (status = 200, description = "Alert history", body = AlertHistoryListResponse),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Alerts"
+)]
+pub async fn list_alert_history(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(query): Query<AlertHistoryQuery>,
+) -> ApiResult<Json<AlertHistoryListResponse>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let history = state.alert_service.list_history(cluster.id, &query).await?;
+    Ok(Json(history))
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+};
+
+use crate::AppState;
+use crate::middleware::OrgContext;
+use crate::models::{ApiToken, CreateApiTokenRequest, CreatedApiToken};
+use crate::utils::ApiResult;
+
+/// List the caller's personal API tokens
+#[utoipa::path(
+    get,
+    path = "/api/auth/tokens",
+    responses(
+        (status = 200, description = "Personal API tokens (revoked and expired included)", body = Vec<ApiToken>)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Authentication"
+)]
+pub async fn list_tokens(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+) -> ApiResult<Json<Vec<ApiToken>>> {
+    let tokens = state.api_token_service.list_tokens(org_ctx.user_id).await?;
+    Ok(Json(tokens))
+}
+
+/// Create a personal API token (the token is only returned by this call)
+#[utoipa::path(
+    post,
+    path = "/api/auth/tokens",
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 200, description = "Token created", body = CreatedApiToken),
+        (status = 400, description = "Invalid name, expiry, scopes or clusters"),
+        (status = 403, description = "Request authenticated with an API token")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Authentication"
+)]
+pub async fn create_token(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> ApiResult<Json<CreatedApiToken>> {
+    let created = state
+        .api_token_service
+        .create_token(org_ctx.user_id, req, &org_ctx)
+        .await?;
+    Ok(Json(created))
+}
+
+/// Revoke a personal API token
+#[utoipa::path(
+    delete,
+    path = "/api/auth/tokens/{id}",
+    params(("id" = i64, Path, description = "Token ID")),
+    responses(
+        (status = 200, description = "Token revoked", body = ApiToken),
+        (status = 404, description = "Token not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Authentication"
+)]
+pub async fn revoke_token(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ApiToken>> {
+    let token = state
+        .api_token_service
+        .revoke_token(org_ctx.user_id, id)
+        .await?;
+    Ok(Json(token))
+}
@@ -1,11 +1,19 @@
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::middleware::OrgContext;
 use crate::models::{
-    CreateUserRequest, LoginRequest, LoginResponse, UpdateUserRequest, UserResponse,
+    CreateUserRequest, LoginRequest, LoginResponse, RecoveryCodes, RefreshTokenRequest,
+    RegistrationSettings, RevokedSessions, TokenResponse, TwoFactorCodeRequest, TwoFactorSetup,
+    TwoFactorStatus, UpdateUserRequest, UserResponse, UserSession,
 };
-use crate::utils::ApiResult;
+use crate::services::{SessionClient, SessionRevokeReason};
+use crate::utils::{ApiError, ApiResult};
 
 // Register a new user
 #[utoipa::path(
@@ -14,7 +22,8 @@ use crate::utils::ApiResult;
     request_body = CreateUserRequest,
     responses(
         (status = 200, description = "User registered successfully", body = UserResponse),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Username taken or password refused by the password policy"),
+        (status = 401, description = "Registration is disabled")
     ),
     tag = "Authentication"
 )]
@@ -38,18 +47,25 @@ pub async fn register(
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = LoginResponse),
-        (status = 401, description = "Invalid credentials")
+        (status = 401, description = "Invalid credentials, or two-factor code required (code 1005)"),
+        (status = 429, description = "Account locked or client address throttled after failed logins")
     ),
     tag = "Authentication"
 )]
 pub async fn login(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> ApiResult<Json<LoginResponse>> {
     tracing::info!("User login attempt for username: {}", req.username);
     tracing::debug!("Login request: username={}", req.username);
 
-    let (user, token) = state.auth_service.login(req).await?;
+    let client = SessionClient::from_headers(&headers);
+    let username = req.username.clone();
+    let (user, tokens) = state
+        .login_guard
+        .attempt(&username, &client, state.auth_service.login(req, &client))
+        .await?;
     let username = user.username.clone();
     let user_id = user.id;
     let user_response = state.auth_service.to_user_response(user).await?;
@@ -57,7 +73,12 @@ pub async fn login(
     tracing::info!("User logged in successfully: {} (ID: {})", username, user_id);
     tracing::debug!("JWT token generated for user: {}", username);
 
-    Ok(Json(LoginResponse { token, user: user_response }))
+    Ok(Json(LoginResponse {
+        token: tokens.token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+        user: user_response,
+    }))
 }
 
 // Get current user info
@@ -98,9 +119,10 @@ pub async fn get_me(
     path = "/api/auth/me",
     request_body = UpdateUserRequest,
     responses(
-        (status = 200, description = "User updated successfully", body = UserResponse),
+        (status = 200, description = "User updated successfully (changing the password ends the other sessions)", body = UserResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 400, description = "Bad request")
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Request authenticated with an API token")
     ),
     security(
         ("bearer_auth" = [])
@@ -110,8 +132,14 @@ pub async fn get_me(
 pub async fn update_me(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(user_id): axum::extract::Extension<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
     Json(req): Json<UpdateUserRequest>,
 ) -> ApiResult<Json<UserResponse>> {
+    // A token must not be able to take over its user's password login
+    if org_ctx.api_token_id.is_some() {
+        return Err(ApiError::forbidden("The profile cannot be updated with an API token"));
+    }
+
     tracing::info!("User update attempt for user_id: {}", user_id);
     tracing::debug!(
         "Update request: email={:?}, avatar={:?}, changing_password={}",
@@ -120,7 +148,15 @@ pub async fn update_me(
         req.new_password.is_some()
     );
 
+    let changes_password = req.current_password.is_some() && req.new_password.is_some();
     let user = state.auth_service.update_user(user_id, req).await?;
+    // Other sessions may have been opened with the old password
+    if changes_password {
+        state
+            .session_service
+            .revoke_user_sessions(user_id, org_ctx.session_id, SessionRevokeReason::PasswordChanged)
+            .await?;
+    }
     let updated_username = user.username.clone();
     let updated_user_id = user.id;
     let response = state.auth_service.to_user_response(user).await?;
@@ -128,3 +164,309 @@ pub async fn update_me(
     tracing::info!("User updated successfully: {} (ID: {})", updated_username, updated_user_id,);
     Ok(Json(response))
 }
+
+// Exchange a refresh token for new tokens
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New access and refresh tokens (the presented refresh token is used up)", body = TokenResponse),
+        (status = 401, description = "Invalid, expired or revoked refresh token")
+    ),
+    tag = "Authentication"
+)]
+pub async fn refresh(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> ApiResult<Json<TokenResponse>> {
+    let tokens = state.session_service.refresh(&req.refresh_token).await?;
+    Ok(Json(tokens))
+}
+
+// Logout: end the current session
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    responses(
+        (status = 200, description = "Session ended"),
+        (status = 400, description = "Request not authenticated with a login session")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn logout(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+) -> ApiResult<Json<()>> {
+    let session_id = current_session(&org_ctx)?;
+    state
+        .session_service
+        .revoke_session(org_ctx.user_id, session_id, SessionRevokeReason::Logout)
+        .await?;
+    tracing::info!("User {} logged out (session {})", org_ctx.username, session_id);
+    Ok(Json(()))
+}
+
+// List the current user's open sessions
+#[utoipa::path(
+    get,
+    path = "/api/auth/sessions",
+    responses(
+        (status = 200, description = "Open sessions, most recently used first", body = Vec<UserSession>)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+) -> ApiResult<Json<Vec<UserSession>>> {
+    let sessions = state
+        .session_service
+        .list_sessions(org_ctx.user_id, org_ctx.session_id)
+        .await?;
+    Ok(Json(sessions))
+}
+
+// Log out of every other session of the current user
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions",
+    responses(
+        (status = 200, description = "Other sessions revoked (the current one is kept)", body = RevokedSessions)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn revoke_other_sessions(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+) -> ApiResult<Json<RevokedSessions>> {
+    let revoked = state
+        .session_service
+        .revoke_user_sessions(org_ctx.user_id, org_ctx.session_id, SessionRevokeReason::Logout)
+        .await?;
+    Ok(Json(RevokedSessions { revoked }))
+}
+
+// Revoke one session of the current user
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions/{id}",
+    params(("id" = i64, Path, description = "Session ID")),
+    responses(
+        (status = 200, description = "Session revoked"),
+        (status = 404, description = "Session not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn revoke_session(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<()>> {
+    state
+        .session_service
+        .revoke_session(org_ctx.user_id, id, SessionRevokeReason::Logout)
+        .await?;
+    Ok(Json(()))
+}
+
+// Whether public registration is open
+#[utoipa::path(
+    get,
+    path = "/api/auth/registration",
+    responses(
+        (status = 200, description = "Registration switch", body = RegistrationSettings)
+    ),
+    tag = "Authentication"
+)]
+pub async fn get_registration(
+    State(state): State<Arc<AppState>>,
+) -> ApiResult<Json<RegistrationSettings>> {
+    let enabled = state.auth_service.registration_enabled().await?;
+    Ok(Json(RegistrationSettings { enabled }))
+}
+
+// Open or close public registration
+#[utoipa::path(
+    put,
+    path = "/api/auth/registration",
+    request_body = RegistrationSettings,
+    responses(
+        (status = 200, description = "Registration switch updated", body = RegistrationSettings),
+        (status = 401, description = "Not a super administrator")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn update_registration(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Json(req): Json<RegistrationSettings>,
+) -> ApiResult<Json<RegistrationSettings>> {
+    if !org_ctx.is_super_admin {
+        return Err(ApiError::forbidden("Only super administrators can change registration"));
+    }
+    state
+        .auth_service
+        .set_registration_enabled(req.enabled, org_ctx.user_id)
+        .await?;
+    Ok(Json(req))
+}
+
+// Second factor of the current user
+#[utoipa::path(
+    get,
+    path = "/api/auth/2fa",
+    responses(
+        (status = 200, description = "Two-factor authentication status", body = TwoFactorStatus)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn two_factor_status(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+) -> ApiResult<Json<TwoFactorStatus>> {
+    let status = state.two_factor_service.status(org_ctx.user_id).await?;
+    Ok(Json(status))
+}
+
+// Start enrolling an authenticator app
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/setup",
+    responses(
+        (status = 200, description = "New secret, confirmed with /api/auth/2fa/enable", body = TwoFactorSetup),
+        (status = 400, description = "Already enabled, or the user logs in with single sign-on"),
+        (status = 403, description = "Request authenticated with an API token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn setup_two_factor(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+) -> ApiResult<Json<TwoFactorSetup>> {
+    refuse_api_token(&org_ctx)?;
+    let user = state.auth_service.get_user_by_id(org_ctx.user_id).await?;
+    let setup = state.two_factor_service.begin_setup(&user).await?;
+    Ok(Json(setup))
+}
+
+// Enable two-factor authentication with a first code of the app
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/enable",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "Enabled; the recovery codes are only shown now", body = RecoveryCodes),
+        (status = 400, description = "No pending setup or invalid code"),
+        (status = 403, description = "Request authenticated with an API token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn enable_two_factor(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> ApiResult<Json<RecoveryCodes>> {
+    refuse_api_token(&org_ctx)?;
+    let codes = state
+        .two_factor_service
+        .enable(org_ctx.user_id, &req.code)
+        .await?;
+    Ok(Json(codes))
+}
+
+// Disable two-factor authentication
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/disable",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "Disabled, recovery codes deleted"),
+        (status = 400, description = "Not enabled or invalid code"),
+        (status = 403, description = "Request authenticated with an API token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn disable_two_factor(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> ApiResult<Json<()>> {
+    refuse_api_token(&org_ctx)?;
+    state
+        .two_factor_service
+        .disable(org_ctx.user_id, &req.code)
+        .await?;
+    Ok(Json(()))
+}
+
+// Replace the recovery codes of the current user
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/recovery-codes",
+    request_body = TwoFactorCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes, the previous ones no longer work", body = RecoveryCodes),
+        (status = 400, description = "Not enabled or invalid code"),
+        (status = 403, description = "Request authenticated with an API token")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn regenerate_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> ApiResult<Json<RecoveryCodes>> {
+    refuse_api_token(&org_ctx)?;
+    let codes = state
+        .two_factor_service
+        .regenerate_recovery_codes(org_ctx.user_id, &req.code)
+        .await?;
+    Ok(Json(codes))
+}
+
+/// A token must not be able to change how its user logs in
+fn refuse_api_token(org_ctx: &OrgContext) -> ApiResult<()> {
+    if org_ctx.api_token_id.is_some() {
+        return Err(ApiError::forbidden(
+            "Two-factor authentication cannot be managed with an API token",
+        ));
+    }
+    Ok(())
+}
+
+fn current_session(org_ctx: &OrgContext) -> ApiResult<i64> {
+    org_ctx.session_id.ok_or_else(|| {
+        ApiError::validation_error("Request is not authenticated with a login session")
+    })
+}
@@ -26,15 +26,8 @@ pub async fn list_backends(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<Backend>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let client = StarRocksClient::new(cluster, state.mysql_pool_manager.clone());
     let backends = client.get_backends().await?;
     Ok(Json(backends))
@@ -63,15 +56,8 @@ pub async fn delete_backend(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path((host, port)): Path<(String, String)>,
 ) -> ApiResult<Json<serde_json::Value>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     tracing::info!("Deleting backend {}:{} from cluster {}", host, port, cluster.id);
 
     let client = StarRocksClient::new(cluster, state.mysql_pool_manager.clone());
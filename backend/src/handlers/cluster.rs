@@ -6,6 +6,10 @@ use std::sync::Arc;
 
 use crate::AppState;
 use crate::models::{ClusterHealth, ClusterResponse, CreateClusterRequest, UpdateClusterRequest};
+use crate::services::{
+    AuditSourceSettings, AuditSourceValidation, ClusterAuditSource, ClusterCollectionStatus,
+    UpdateCollectionSettingsRequest,
+};
 use crate::utils::ApiResult;
 use serde::Deserialize;
 
@@ -75,16 +79,24 @@ pub async fn list_clusters(
         org_ctx.is_super_admin
     );
 
-    let clusters = state.cluster_service.list_clusters().await?;
-    let filtered = if org_ctx.is_super_admin {
-        clusters
-    } else {
-        clusters
-            .into_iter()
-            .filter(|c| c.organization_id == org_ctx.organization_id)
-            .collect()
-    };
-    let responses: Vec<ClusterResponse> = filtered.into_iter().map(|c| c.into()).collect();
+    let filtered = state
+        .cluster_service
+        .list_visible_clusters(&org_ctx)
+        .await?;
+    // Mark the caller's active cluster, not the organization default
+    let active_id = state
+        .cluster_service
+        .resolve_cluster(&org_ctx)
+        .await
+        .ok()
+        .map(|cluster| cluster.id);
+    let responses: Vec<ClusterResponse> = filtered
+        .into_iter()
+        .map(|c| {
+            let is_active = Some(c.id) == active_id;
+            ClusterResponse { is_active, ..c.into() }
+        })
+        .collect();
 
     tracing::debug!("Retrieved {} clusters for user {}", responses.len(), org_ctx.user_id);
     Ok(Json(responses))
@@ -114,14 +126,8 @@ pub async fn get_active_cluster(
         org_ctx.is_super_admin
     );
 
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // The caller's own selection, falling back to the organization default
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     tracing::debug!(
         "Active cluster for user {}: {} (ID: {})",
@@ -129,10 +135,10 @@ pub async fn get_active_cluster(
         cluster.name,
         cluster.id
     );
-    Ok(Json(cluster.into()))
+    Ok(Json(ClusterResponse { is_active: true, ..cluster.into() }))
 }
 
-// Set a cluster as active
+// Set a cluster as the caller's active cluster (other users are not affected)
 #[utoipa::path(
     put,
     path = "/api/clusters/{id}/activate",
@@ -169,7 +175,10 @@ pub async fn activate_cluster(
         ));
     }
 
-    let cluster = state.cluster_service.set_active_cluster(id).await?;
+    let cluster = state
+        .cluster_service
+        .set_user_active_cluster(org_ctx.user_id, id)
+        .await?;
 
     tracing::info!(
         "Cluster activated successfully: {} (ID: {}) by user {}",
@@ -177,6 +186,45 @@ pub async fn activate_cluster(
         cluster.id,
         org_ctx.user_id
     );
+    Ok(Json(ClusterResponse { is_active: true, ..cluster.into() }))
+}
+
+// Set a cluster as the default of its organization (active cluster of users without a selection)
+#[utoipa::path(
+    put,
+    path = "/api/clusters/{id}/default",
+    params(
+        ("id" = i64, Path, description = "Cluster ID to use as default")
+    ),
+    responses(
+        (status = 200, description = "Default cluster set successfully", body = ClusterResponse),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn set_default_cluster(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<ClusterResponse>> {
+    let target = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && target.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only change the default cluster of your organization",
+        ));
+    }
+
+    let cluster = state.cluster_service.set_active_cluster(id).await?;
+
+    tracing::info!(
+        "Default cluster set: {} (ID: {}) by user {}",
+        cluster.name,
+        cluster.id,
+        org_ctx.user_id
+    );
     Ok(Json(cluster.into()))
 }
 
@@ -306,6 +354,252 @@ pub struct HealthCheckRequest {
     pub enable_ssl: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub catalog: Option<String>,
+    #[serde(default)]
+    pub tls: Option<crate::models::ClusterTlsRequest>,
+}
+
+// List metrics collection status of all clusters
+#[utoipa::path(
+    get,
+    path = "/api/clusters/collection",
+    responses(
+        (status = 200, description = "Collection settings and status of each cluster", body = Vec<ClusterCollectionStatus>)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn list_collection_status(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Vec<ClusterCollectionStatus>>> {
+    let clusters = state
+        .cluster_service
+        .list_visible_clusters(&org_ctx)
+        .await?;
+    let statuses = state
+        .metrics_collector_service
+        .list_collection_status(&clusters)
+        .await?;
+    Ok(Json(statuses))
+}
+
+// Get metrics collection status of a cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/{id}/collection",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Collection settings and status", body = ClusterCollectionStatus),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn get_collection_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<ClusterCollectionStatus>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only view clusters within your organization",
+        ));
+    }
+
+    let status = state
+        .metrics_collector_service
+        .get_collection_status(&cluster)
+        .await?;
+    Ok(Json(status))
+}
+
+// Update metrics collection settings of a cluster
+#[utoipa::path(
+    put,
+    path = "/api/clusters/{id}/collection",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    request_body = UpdateCollectionSettingsRequest,
+    responses(
+        (status = 200, description = "Collection settings updated", body = ClusterCollectionStatus),
+        (status = 400, description = "Invalid collection interval"),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn update_collection_settings(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(req): Json<UpdateCollectionSettingsRequest>,
+) -> ApiResult<Json<ClusterCollectionStatus>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only update clusters within your organization",
+        ));
+    }
+
+    let status = state
+        .metrics_collector_service
+        .update_collection_settings(&cluster, req)
+        .await?;
+    Ok(Json(status))
+}
+
+// Get the audit log source of a cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/{id}/audit-source",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Audit source settings and resolved columns", body = ClusterAuditSource),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn get_audit_source(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<ClusterAuditSource>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only view clusters within your organization",
+        ));
+    }
+
+    let source = state
+        .audit_source_service
+        .get_audit_source(&cluster)
+        .await?;
+    Ok(Json(source))
+}
+
+// Update the audit log source of a cluster
+#[utoipa::path(
+    put,
+    path = "/api/clusters/{id}/audit-source",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    request_body = AuditSourceSettings,
+    responses(
+        (status = 200, description = "Audit source updated", body = ClusterAuditSource),
+        (status = 400, description = "Invalid database, table or column name"),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn update_audit_source(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(req): Json<AuditSourceSettings>,
+) -> ApiResult<Json<ClusterAuditSource>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only update clusters within your organization",
+        ));
+    }
+
+    let source = state
+        .audit_source_service
+        .update_settings(&cluster, req)
+        .await?;
+    Ok(Json(source))
+}
+
+// Reset the audit log source of a cluster to the [audit] config
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/{id}/audit-source",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    responses(
+        (status = 200, description = "Audit source reset", body = ClusterAuditSource),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn reset_audit_source(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<ClusterAuditSource>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only update clusters within your organization",
+        ));
+    }
+
+    let source = state.audit_source_service.reset_settings(&cluster).await?;
+    Ok(Json(source))
+}
+
+// Check that the audit table of a cluster exists and is readable
+// Validates the settings in the body (before saving them), or the stored ones without a body
+#[utoipa::path(
+    post,
+    path = "/api/clusters/{id}/audit-source/validate",
+    params(
+        ("id" = i64, Path, description = "Cluster ID")
+    ),
+    request_body = Option<AuditSourceSettings>,
+    responses(
+        (status = 200, description = "Validation result", body = AuditSourceValidation),
+        (status = 400, description = "Invalid database, table or column name"),
+        (status = 404, description = "Cluster not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Clusters"
+)]
+pub async fn validate_audit_source(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    body: Option<Json<AuditSourceSettings>>,
+) -> ApiResult<Json<AuditSourceValidation>> {
+    let cluster = state.cluster_service.get_cluster(id).await?;
+    if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+        return Err(crate::utils::ApiError::forbidden(
+            "You can only view clusters within your organization",
+        ));
+    }
+
+    let validation = state
+        .audit_source_service
+        .validate(&cluster, body.map(|Json(settings)| settings))
+        .await?;
+    Ok(Json(validation))
 }
 
 // Get cluster health
@@ -350,7 +644,7 @@ pub async fn get_cluster_health(
             health_req.catalog.as_deref().unwrap_or("default_catalog")
         );
 
-        let temp_cluster = Cluster {
+        let mut temp_cluster = Cluster {
             id: 0,
             name: "test".to_string(),
             description: None,
@@ -358,7 +652,10 @@ pub async fn get_cluster_health(
             fe_http_port: health_req.fe_http_port.unwrap_or(8030),
             fe_query_port: health_req.fe_query_port.unwrap_or(9030),
             username: health_req.username.unwrap_or_else(|| "root".to_string()),
-            password_encrypted: health_req.password.unwrap_or_default(),
+            password_encrypted: state
+                .mysql_pool_manager
+                .cipher()
+                .encrypt(&health_req.password.unwrap_or_default())?,
             enable_ssl: health_req.enable_ssl,
             connection_timeout: 10,
             catalog: health_req
@@ -371,7 +668,18 @@ pub async fn get_cluster_health(
             created_by: None,
             organization_id: None,
             deployment_mode: crate::models::cluster::DeploymentMode::default(),
+            fe_endpoints: None,
+            tls_ca_cert: None,
+            tls_client_cert: None,
+            tls_client_key_encrypted: None,
+            tls_verify_hostname: true,
         };
+        if let Some(tls) = health_req.tls {
+            state
+                .cluster_service
+                .prepare_tls(tls, None)?
+                .apply_to(&mut temp_cluster);
+        }
 
         let health = state
             .cluster_service
@@ -422,7 +730,7 @@ pub async fn test_cluster_connection(
         return Err(crate::utils::ApiError::validation_error("Missing required field: fe_host"));
     }
 
-    let temp_cluster = Cluster {
+    let mut temp_cluster = Cluster {
         id: 0,
         name: "test".to_string(),
         description: None,
@@ -430,7 +738,10 @@ pub async fn test_cluster_connection(
         fe_http_port: health_req.fe_http_port.unwrap_or(8030),
         fe_query_port: health_req.fe_query_port.unwrap_or(9030),
         username: health_req.username.unwrap_or_else(|| "root".to_string()),
-        password_encrypted: health_req.password.unwrap_or_default(),
+        password_encrypted: state
+            .mysql_pool_manager
+            .cipher()
+            .encrypt(&health_req.password.unwrap_or_default())?,
         enable_ssl: health_req.enable_ssl,
         connection_timeout: 10,
         catalog: health_req
@@ -443,7 +754,18 @@ pub async fn test_cluster_connection(
         created_by: None,
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls_ca_cert: None,
+        tls_client_cert: None,
+        tls_client_key_encrypted: None,
+        tls_verify_hostname: true,
     };
+    if let Some(tls) = health_req.tls {
+        state
+            .cluster_service
+            .prepare_tls(tls, None)?
+            .apply_to(&mut temp_cluster);
+    }
 
     let health = state
         .cluster_service
@@ -2,7 +2,7 @@ use axum::{Json, extract::State};
 use std::sync::Arc;
 
 use crate::AppState;
-use crate::models::Frontend;
+use crate::models::{FeEndpointStatus, Frontend};
 use crate::services::StarRocksClient;
 use crate::utils::ApiResult;
 
@@ -23,16 +23,37 @@ pub async fn list_frontends(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<Frontend>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let client = StarRocksClient::new(cluster, state.mysql_pool_manager.clone());
     let frontends = client.get_frontends().await?;
     Ok(Json(frontends))
 }
+
+// Get the FE endpoints of a cluster with their failover state
+#[utoipa::path(
+    get,
+    path = "/api/clusters/frontends/endpoints",
+    responses(
+        (status = 200, description = "Configured and discovered FE endpoints", body = Vec<FeEndpointStatus>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Frontends"
+)]
+pub async fn list_frontend_endpoints(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Vec<FeEndpointStatus>>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    // Refresh the connection state; an unreachable cluster still lists its endpoints
+    if let Err(e) = state.mysql_pool_manager.get_pool(&cluster).await {
+        tracing::warn!("No reachable FE for cluster {}: {}", cluster.id, e);
+    }
+
+    Ok(Json(state.mysql_pool_manager.endpoint_status(&cluster)))
+}
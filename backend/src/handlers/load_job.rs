@@ -0,0 +1,220 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::middleware::OrgContext;
+use crate::models::{EffectiveSqlPolicy, LoadJob, LoadJobDetail, LoadJobFilter, LoadTrackingLog};
+use crate::services::{LoadJobService, MySQLClient, sql_policy_service};
+use crate::utils::ApiResult;
+
+/// Catalog load jobs write to
+const DEFAULT_CATALOG: &str = "default_catalog";
+
+/// GET /api/clusters/loads - List load jobs across databases
+#[utoipa::path(
+    get,
+    path = "/api/clusters/loads",
+    params(
+        ("state" = Option<String>, Query, description = "State filter, e.g. LOADING or CANCELLED"),
+        ("label" = Option<String>, Query, description = "Label substring filter"),
+        ("database" = Option<String>, Query, description = "Database name filter"),
+        ("load_type" = Option<String>, Query, description = "Load type filter, e.g. BROKER or INSERT"),
+        ("start_time" = Option<String>, Query, description = "Created at or after (YYYY-MM-DD HH:MM:SS)"),
+        ("end_time" = Option<String>, Query, description = "Created before (YYYY-MM-DD HH:MM:SS)"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of jobs (default 200, max 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Load jobs, newest first", body = Vec<LoadJob>),
+        (status = 400, description = "Invalid time filter"),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Load Jobs"
+)]
+pub async fn list_load_jobs(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Query(filter): Query<LoadJobFilter>,
+) -> ApiResult<Json<Vec<LoadJob>>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let load_service = LoadJobService::new(MySQLClient::from_pool(pool));
+
+    if let Some(database) = &filter.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut jobs = load_service.list_load_jobs(&filter).await?;
+    jobs.retain(|job| {
+        org_ctx
+            .check_database_access(cluster.id, DEFAULT_CATALOG, &job.database_name)
+            .is_ok()
+    });
+
+    Ok(Json(jobs))
+}
+
+/// GET /api/clusters/loads/{job_id} - Get load job details
+#[utoipa::path(
+    get,
+    path = "/api/clusters/loads/{job_id}",
+    params(
+        ("job_id" = i64, Path, description = "Load job ID"),
+    ),
+    responses(
+        (status = 200, description = "Load job details", body = LoadJobDetail),
+        (status = 404, description = "Load job not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Load Jobs"
+)]
+pub async fn get_load_job(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<LoadJobDetail>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let load_service = LoadJobService::new(MySQLClient::from_pool(pool));
+
+    let detail = load_service.get_load_job(job_id).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &detail.job.database_name)?;
+
+    Ok(Json(detail))
+}
+
+/// POST /api/clusters/loads/{job_id}/cancel - Cancel a load job
+#[utoipa::path(
+    post,
+    path = "/api/clusters/loads/{job_id}/cancel",
+    params(
+        ("job_id" = i64, Path, description = "Load job ID"),
+    ),
+    responses(
+        (status = 200, description = "Load job cancelled"),
+        (status = 400, description = "Load job already settled or not cancellable"),
+        (status = 404, description = "Load job not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Load Jobs"
+)]
+pub async fn cancel_load_job(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let load_service = LoadJobService::new(MySQLClient::from_pool(pool));
+
+    if org_ctx.database_scopes(cluster.id).is_some() {
+        let detail = load_service.get_load_job(job_id).await?;
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &detail.job.database_name)?;
+    }
+    let job = load_service.cancel_load_job(job_id).await?;
+
+    Ok(Json(json!({
+        "message": "Load job cancelled",
+        "job_id": job.id,
+        "label": job.label,
+        "database_name": job.database_name,
+    })))
+}
+
+/// POST /api/clusters/loads/{job_id}/retry - Resubmit a cancelled load job
+#[utoipa::path(
+    post,
+    path = "/api/clusters/loads/{job_id}/retry",
+    params(
+        ("job_id" = i64, Path, description = "Load job ID"),
+    ),
+    responses(
+        (status = 200, description = "Load job resubmitted with its label"),
+        (status = 400, description = "Load job not cancelled, not retryable or with masked credentials"),
+        (status = 404, description = "Load job or its statement in the audit log not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Load Jobs"
+)]
+pub async fn retry_load_job(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let load_service = LoadJobService::new(MySQLClient::from_pool(pool));
+
+    if org_ctx.database_scopes(cluster.id).is_some() {
+        let detail = load_service.get_load_job(job_id).await?;
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &detail.job.database_name)?;
+    }
+    let audit = state.audit_source_service.resolve(&cluster).await?;
+    let (job, statement) = load_service.retry_statement(job_id, &audit).await?;
+
+    // The statement runs as the cluster user: hold it to the caller's SQL policy and scope
+    let statements = std::slice::from_ref(&statement);
+    let policy = if org_ctx.is_super_admin {
+        EffectiveSqlPolicy::default()
+    } else {
+        state
+            .sql_policy_service
+            .effective_policy(org_ctx.user_id)
+            .await?
+    };
+    sql_policy_service::check_statements(&policy, statements, None, Some(&job.database_name))?;
+    if let Some(scopes) = org_ctx.database_scopes(cluster.id) {
+        sql_policy_service::check_scoped_statements(
+            &scopes,
+            statements,
+            None,
+            Some(&job.database_name),
+        )?;
+    }
+    load_service.resubmit_load_job(&job, &statement).await?;
+
+    Ok(Json(json!({
+        "message": "Load job resubmitted",
+        "job_id": job.id,
+        "label": job.label,
+        "database_name": job.database_name,
+    })))
+}
+
+/// GET /api/clusters/loads/{job_id}/tracking-log - Rejected rows of a load job
+#[utoipa::path(
+    get,
+    path = "/api/clusters/loads/{job_id}/tracking-log",
+    params(
+        ("job_id" = i64, Path, description = "Load job ID"),
+    ),
+    responses(
+        (status = 200, description = "Tracking log of the load job", body = LoadTrackingLog),
+        (status = 404, description = "Load job not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Load Jobs"
+)]
+pub async fn get_load_tracking_log(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path(job_id): Path<i64>,
+) -> ApiResult<Json<LoadTrackingLog>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let load_service = LoadJobService::new(MySQLClient::from_pool(pool));
+
+    let log = load_service.get_tracking_log(job_id).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &log.database_name)?;
+
+    Ok(Json(log))
+}
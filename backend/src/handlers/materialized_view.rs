@@ -9,13 +9,21 @@ use serde_json::json;
 use std::sync::Arc;
 
 use crate::AppState;
+use crate::middleware::OrgContext;
 use crate::models::{
     AlterMaterializedViewRequest, CreateMaterializedViewRequest, MaterializedView,
-    MaterializedViewDDL, RefreshMaterializedViewRequest,
+    MaterializedViewDDL, MvDependencyGraph, MvImpact, MvRefreshDetail, MvRefreshHistoryFilter,
+    MvRefreshStats, MvRefreshTrendInterval, MvRefreshTrends, MvStaleness,
+    RefreshMaterializedViewRequest,
+};
+use crate::services::{
+    MaterializedViewService, MySQLClient, materialized_view_service, sql_policy_service,
 };
-use crate::services::{MaterializedViewService, MySQLClient};
 use crate::utils::ApiResult;
 
+/// Catalog materialized views are managed in
+const DEFAULT_CATALOG: &str = "default_catalog";
+
 #[derive(Debug, Deserialize)]
 pub struct ListMVParams {
     pub database: Option<String>,
@@ -33,6 +41,36 @@ pub struct CancelRefreshParams {
     pub force: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StalenessParams {
+    pub database: Option<String>,
+    /// Only return views with at least one issue
+    #[serde(default)]
+    pub stale_only: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImpactParams {
+    /// `db.table` or `catalog.db.table`
+    pub table: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshStatsParams {
+    pub database: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshTrendsParams {
+    pub database: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    #[serde(default)]
+    pub interval: MvRefreshTrendInterval,
+}
+
 /// GET /api/clusters/materialized_views - List all materialized views
 #[utoipa::path(
     get,
@@ -52,23 +90,24 @@ pub async fn list_materialized_views(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<ListMVParams>,
 ) -> ApiResult<Json<Vec<MaterializedView>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
 
-    let mvs = mv_service
+    if let Some(database) = &params.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut mvs = mv_service
         .list_materialized_views(params.database.as_deref())
         .await?;
+    mvs.retain(|mv| {
+        org_ctx
+            .check_database_access(cluster.id, DEFAULT_CATALOG, &mv.database_name)
+            .is_ok()
+    });
 
     Ok(Json(mvs))
 }
@@ -92,19 +131,13 @@ pub async fn get_materialized_view(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path(mv_name): Path<String>,
 ) -> ApiResult<Json<MaterializedView>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     let mv = mv_service.get_materialized_view(&mv_name).await?;
     Ok(Json(mv))
@@ -129,19 +162,13 @@ pub async fn get_materialized_view_ddl(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path(mv_name): Path<String>,
 ) -> ApiResult<Json<MaterializedViewDDL>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     let ddl = mv_service.get_materialized_view_ddl(&mv_name).await?;
     Ok(Json(MaterializedViewDDL { mv_name: mv_name.clone(), ddl }))
@@ -164,20 +191,21 @@ pub async fn create_materialized_view(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Json(request): Json<CreateMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
 
+    if let Some(scopes) = org_ctx.database_scopes(cluster.id) {
+        sql_policy_service::check_scoped_statements(
+            &scopes,
+            std::slice::from_ref(&request.sql),
+            None,
+            None,
+        )?;
+    }
     mv_service.create_materialized_view(&request.sql).await?;
 
     Ok((StatusCode::CREATED, Json(json!({ "message": "Materialized view created successfully" }))))
@@ -204,19 +232,13 @@ pub async fn delete_materialized_view(
     Path(mv_name): Path<String>,
     Query(params): Query<DeleteMVParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     mv_service
         .drop_materialized_view(&mv_name, params.if_exists)
@@ -242,19 +264,13 @@ pub async fn refresh_materialized_view(
     Path(mv_name): Path<String>,
     Json(request): Json<RefreshMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     mv_service
         .refresh_materialized_view(
@@ -288,19 +304,13 @@ pub async fn cancel_refresh_materialized_view(
     Path(mv_name): Path<String>,
     Query(params): Query<CancelRefreshParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     mv_service
         .cancel_refresh_materialized_view(&mv_name, params.force)
@@ -326,19 +336,13 @@ pub async fn alter_materialized_view(
     Path(mv_name): Path<String>,
     Json(request): Json<AlterMaterializedViewRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
     let mv_service = MaterializedViewService::new(mysql_client);
+    check_view_access(&org_ctx, cluster.id, &mv_service, &mv_name).await?;
 
     mv_service
         .alter_materialized_view(&mv_name, &request.alter_clause)
@@ -346,3 +350,274 @@ pub async fn alter_materialized_view(
 
     Ok((StatusCode::OK, Json(json!({ "message": "Materialized view altered successfully" }))))
 }
+
+/// GET /api/clusters/materialized_views/dependencies - Dependency graph of async materialized views
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/dependencies",
+    params(
+        ("database" = Option<String>, Query, description = "Only views of this database and the objects they read"),
+    ),
+    responses(
+        (status = 200, description = "Materialized views, base tables and their edges", body = MvDependencyGraph),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_dependency_graph(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<ListMVParams>,
+) -> ApiResult<Json<MvDependencyGraph>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let mysql_client = MySQLClient::from_pool(pool);
+    let mv_service = MaterializedViewService::new(mysql_client);
+
+    if let Some(database) = &params.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut graph = mv_service
+        .get_dependency_graph(params.database.as_deref())
+        .await?;
+    graph.nodes.retain(|node| {
+        org_ctx
+            .check_database_access(cluster.id, &node.catalog, &node.database_name)
+            .is_ok()
+    });
+    let nodes = &graph.nodes;
+    graph.edges.retain(|edge| {
+        nodes.iter().any(|n| n.id == edge.source) && nodes.iter().any(|n| n.id == edge.target)
+    });
+
+    Ok(Json(graph))
+}
+
+/// GET /api/clusters/materialized_views/staleness - Freshness report of async materialized views
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/staleness",
+    params(
+        ("database" = Option<String>, Query, description = "Database name filter"),
+        ("stale_only" = Option<bool>, Query, description = "Only inactive, failing or outdated views"),
+    ),
+    responses(
+        (status = 200, description = "Freshness of every view with the issues found", body = Vec<MvStaleness>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_staleness_report(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<StalenessParams>,
+) -> ApiResult<Json<Vec<MvStaleness>>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let mysql_client = MySQLClient::from_pool(pool);
+    let mv_service = MaterializedViewService::new(mysql_client);
+
+    if let Some(database) = &params.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut report = mv_service
+        .get_staleness_report(params.database.as_deref())
+        .await?;
+    report.retain(|mv| {
+        (!params.stale_only || !mv.issues.is_empty())
+            && org_ctx
+                .check_database_access(cluster.id, DEFAULT_CATALOG, &mv.database_name)
+                .is_ok()
+    });
+
+    Ok(Json(report))
+}
+
+/// GET /api/clusters/materialized_views/impact - Materialized views depending on a table
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/impact",
+    params(
+        ("table" = String, Query, description = "Table as db.table or catalog.db.table"),
+    ),
+    responses(
+        (status = 200, description = "Views that break if the table is dropped, nearest first", body = MvImpact),
+        (status = 400, description = "Invalid table name")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_impact(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<ImpactParams>,
+) -> ApiResult<Json<MvImpact>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let (catalog, database, _) = materialized_view_service::parse_object_id(&params.table)?;
+    org_ctx.check_database_access(cluster.id, &catalog, &database)?;
+
+    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
+    let mysql_client = MySQLClient::from_pool(pool);
+    let mv_service = MaterializedViewService::new(mysql_client);
+
+    let mut impact = mv_service.get_impact(&params.table).await?;
+    impact.views.retain(|view| {
+        org_ctx
+            .check_database_access(cluster.id, DEFAULT_CATALOG, &view.database_name)
+            .is_ok()
+    });
+
+    Ok(Json(impact))
+}
+
+/// GET /api/clusters/materialized_views/refresh-stats - Refresh statistics of every view
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/refresh-stats",
+    params(
+        ("database" = Option<String>, Query, description = "Database name filter"),
+        ("start_time" = Option<String>, Query, description = "Runs created at or after (YYYY-MM-DD HH:MM:SS)"),
+        ("end_time" = Option<String>, Query, description = "Runs created before (YYYY-MM-DD HH:MM:SS)"),
+    ),
+    responses(
+        (status = 200, description = "Runs, failure rate and durations per view, most failures first", body = Vec<MvRefreshStats>),
+        (status = 400, description = "Invalid time filter")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_refresh_stats(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<RefreshStatsParams>,
+) -> ApiResult<Json<Vec<MvRefreshStats>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    if let Some(database) = &params.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut stats = state
+        .mv_refresh_service
+        .get_stats(
+            cluster.id,
+            params.database.as_deref(),
+            params.start_time.as_deref(),
+            params.end_time.as_deref(),
+        )
+        .await?;
+    stats.retain(|s| {
+        org_ctx
+            .check_database_access(cluster.id, DEFAULT_CATALOG, &s.database_name)
+            .is_ok()
+    });
+
+    Ok(Json(stats))
+}
+
+/// GET /api/clusters/materialized_views/{mv_name}/refresh-history - Refresh runs and failures
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/{mv_name}/refresh-history",
+    params(
+        ("mv_name" = String, Path, description = "Materialized view name"),
+        ("database" = Option<String>, Query, description = "Database name, required when several databases have the view"),
+        ("state" = Option<String>, Query, description = "Run state filter, e.g. FAILED"),
+        ("start_time" = Option<String>, Query, description = "Runs created at or after (YYYY-MM-DD HH:MM:SS)"),
+        ("end_time" = Option<String>, Query, description = "Runs created before (YYYY-MM-DD HH:MM:SS)"),
+        ("limit" = Option<i64>, Query, description = "Most recent runs returned (default 100)"),
+    ),
+    responses(
+        (status = 200, description = "Statistics, runs and failed runs grouped by error", body = MvRefreshDetail),
+        (status = 404, description = "No refresh history")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_refresh_history(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(mv_name): Path<String>,
+    Query(filter): Query<MvRefreshHistoryFilter>,
+) -> ApiResult<Json<MvRefreshDetail>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let database = state
+        .mv_refresh_service
+        .resolve_database(cluster.id, &mv_name, filter.database.as_deref())
+        .await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &database)?;
+
+    let detail = state
+        .mv_refresh_service
+        .get_detail(cluster.id, &database, &mv_name, &filter)
+        .await?;
+    Ok(Json(detail))
+}
+
+/// GET /api/clusters/materialized_views/{mv_name}/refresh-trends - Refresh duration and failure rate
+#[utoipa::path(
+    get,
+    path = "/api/clusters/materialized_views/{mv_name}/refresh-trends",
+    params(
+        ("mv_name" = String, Path, description = "Materialized view name"),
+        ("database" = Option<String>, Query, description = "Database name, required when several databases have the view"),
+        ("start_time" = Option<String>, Query, description = "Runs created at or after (YYYY-MM-DD HH:MM:SS)"),
+        ("end_time" = Option<String>, Query, description = "Runs created before (YYYY-MM-DD HH:MM:SS)"),
+        ("interval" = Option<MvRefreshTrendInterval>, Query, description = "Failure rate bucket: hour or day (default)"),
+    ),
+    responses(
+        (status = 200, description = "Duration of every run and failure rate per bucket", body = MvRefreshTrends),
+        (status = 404, description = "No refresh history")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Materialized Views"
+)]
+pub async fn get_refresh_trends(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(mv_name): Path<String>,
+    Query(params): Query<RefreshTrendsParams>,
+) -> ApiResult<Json<MvRefreshTrends>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let database = state
+        .mv_refresh_service
+        .resolve_database(cluster.id, &mv_name, params.database.as_deref())
+        .await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &database)?;
+
+    let trends = state
+        .mv_refresh_service
+        .get_trends(
+            cluster.id,
+            &database,
+            &mv_name,
+            params.start_time.as_deref(),
+            params.end_time.as_deref(),
+            params.interval,
+        )
+        .await?;
+    Ok(Json(trends))
+}
+
+/// Refuse a view outside the databases the caller's permission is granted on
+async fn check_view_access(
+    org_ctx: &OrgContext,
+    cluster_id: i64,
+    mv_service: &MaterializedViewService,
+    mv_name: &str,
+) -> ApiResult<()> {
+    if org_ctx.database_scopes(cluster_id).is_none() {
+        return Ok(());
+    }
+    let mv = mv_service.get_materialized_view(mv_name).await?;
+    org_ctx.check_database_access(cluster_id, DEFAULT_CATALOG, &mv.database_name)
+}
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, header},
+    response::IntoResponse,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::utils::crypto::secrets_equal;
+use crate::utils::{ApiError, ApiResult};
+
+/// Prometheus exposition of cluster snapshots and process metrics
+///
+/// Not under /api: scraped by Prometheus with `metrics.exporter_token` as bearer token
+/// instead of a user JWT. Without a token nothing is served, unless
+/// `metrics.allow_unauthenticated_local` opts in to serving local (loopback) scrapers.
+pub async fn prometheus_metrics(
+    State(state): State<Arc<crate::AppState>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    match state.metrics_exporter_token.as_deref() {
+        Some(expected) => {
+            let provided = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            if !provided.is_some_and(|token| secrets_equal(token, expected)) {
+                return Err(ApiError::unauthorized("Invalid metrics token"));
+            }
+        },
+        None if !state.metrics_allow_unauthenticated_local => {
+            return Err(ApiError::unauthorized("Set metrics.exporter_token to scrape /metrics"));
+        },
+        None => {
+            if !peer.is_some_and(|ConnectInfo(addr)| addr.ip().is_loopback()) {
+                return Err(ApiError::unauthorized(
+                    "Set metrics.exporter_token to scrape /metrics from another host",
+                ));
+            }
+        },
+    }
+
+    let body = state.metrics_exporter_service.render().await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], body))
+}
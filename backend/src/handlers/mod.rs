@@ -1,8 +1,13 @@
+pub mod admin_audit;
+pub mod alert;
+pub mod api_token;
 pub mod auth;
 pub mod backend;
 pub mod cluster;
 pub mod frontend;
+pub mod load_job;
 pub mod materialized_view;
+pub mod metrics;
 pub mod organization;
 pub mod overview;
 pub mod permission;
@@ -10,7 +15,10 @@ pub mod profile;
 pub mod query;
 pub mod query_history;
 pub mod role;
+pub mod routine_load;
+pub mod service_account;
 pub mod sessions;
+pub mod sso;
 pub mod system;
 pub mod system_function;
 pub mod system_management;
@@ -4,7 +4,7 @@
 
 use axum::{
     Json,
-    extract::{Query, State},
+    extract::{Path, Query, State},
 };
 use serde::Deserialize;
 use std::sync::Arc;
@@ -12,7 +12,8 @@ use std::sync::Arc;
 use crate::AppState;
 use crate::services::{
     CapacityPrediction, ClusterOverview, CompactionDetailStats, DataStatistics,
-    ExtendedClusterOverview, HealthCard, PerformanceTrends, ResourceTrends, TimeRange,
+    ExtendedClusterOverview, FleetOverview, HealthCard, NodeMetricsSummary, NodeMetricsTrends,
+    NodeType, PerformanceTrends, ResourceTrends, TimeRange,
 };
 use crate::utils::ApiResult;
 
@@ -27,6 +28,17 @@ fn default_time_range() -> TimeRange {
     TimeRange::Hours24
 }
 
+/// Query parameters for the fleet overview
+#[derive(Debug, Deserialize)]
+pub struct FleetQueryParams {
+    /// Per-cluster timeout in milliseconds
+    pub timeout_ms: Option<u64>,
+}
+
+/// Default and bounds of the per-cluster fleet timeout (ms)
+const FLEET_TIMEOUT_MS: u64 = 5_000;
+const FLEET_TIMEOUT_RANGE_MS: (u64, u64) = (500, 30_000);
+
 /// Query parameters for trend endpoints
 #[derive(Debug, Deserialize)]
 pub struct TrendQueryParams {
@@ -68,15 +80,8 @@ pub async fn get_cluster_overview(
 ) -> ApiResult<Json<ClusterOverview>> {
     tracing::debug!("GET /api/clusters/overview?time_range={:?}", params.time_range);
 
-    // Get the active cluster with organization isolation
-    let active_cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let active_cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let cluster_id = active_cluster.id;
 
     let overview = state
@@ -111,15 +116,8 @@ pub async fn get_health_cards(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<HealthCard>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     tracing::debug!("GET /api/clusters/overview/health");
 
     let cards = state.overview_service.get_health_cards(cluster.id).await?;
@@ -154,15 +152,8 @@ pub async fn get_performance_trends(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<PerformanceTrends>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     tracing::debug!("GET /api/clusters/overview/performance?time_range={:?}", params.time_range);
 
     let trends = state
@@ -200,15 +191,8 @@ pub async fn get_resource_trends(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<ResourceTrends>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     tracing::debug!("GET /api/clusters/overview/resources?time_range={:?}", params.time_range);
 
     let trends = state
@@ -249,15 +233,8 @@ pub async fn get_data_statistics(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<DataStatistics>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let stats = state
         .overview_service
         .get_data_statistics(cluster.id, Some(&params.time_range))
@@ -286,15 +263,8 @@ pub async fn get_capacity_prediction(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<CapacityPrediction>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     tracing::debug!("GET /api/clusters/overview/capacity-prediction");
 
     let prediction = state.overview_service.predict_capacity(cluster.id).await?;
@@ -334,15 +304,8 @@ pub async fn get_extended_cluster_overview(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<OverviewQueryParams>,
 ) -> ApiResult<Json<ExtendedClusterOverview>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let overview = state
         .overview_service
         .get_extended_overview(cluster.id, params.time_range)
@@ -378,15 +341,8 @@ pub async fn get_compaction_detail_stats(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<TrendQueryParams>,
 ) -> ApiResult<Json<CompactionDetailStats>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let time_range_str = match params.time_range {
         TimeRange::Hours1 => "1h",
@@ -402,3 +358,167 @@ pub async fn get_compaction_detail_stats(
 
     Ok(Json(stats))
 }
+
+/// List node metrics
+///
+/// Returns every BE/CN/FE node collected in the time range with its latest metrics
+/// (CPU, memory, disk, tablets, compaction score, heartbeat) and its peaks, so the node
+/// behind a cluster level spike can be found.
+#[utoipa::path(
+    get,
+    path = "/api/clusters/overview/nodes",
+    params(
+        ("time_range" = Option<String>, Query, description = "Time range: 1h, 6h, 24h, 3d (default: 24h)")
+    ),
+    responses(
+        (status = 200, description = "Latest metrics and peaks of each node", body = Vec<NodeMetricsSummary>),
+        (status = 404, description = "No active cluster found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Overview"
+)]
+pub async fn list_node_metrics(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<TrendQueryParams>,
+) -> ApiResult<Json<Vec<NodeMetricsSummary>>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    tracing::debug!("GET /api/clusters/overview/nodes?time_range={:?}", params.time_range);
+
+    let nodes = state
+        .node_metrics_service
+        .list_nodes(cluster.id, &params.time_range)
+        .await?;
+
+    Ok(Json(nodes))
+}
+
+/// Get node metrics
+///
+/// Drill-down of one node: latest metrics including the usage of each storage path (BE),
+/// peaks and availability over the time range.
+#[utoipa::path(
+    get,
+    path = "/api/clusters/overview/nodes/{node_type}/{node_id}",
+    params(
+        ("node_type" = NodeType, Path, description = "Node type: be, cn, fe"),
+        ("node_id" = String, Path, description = "Backend ID (BE/CN) or name (FE)"),
+        ("time_range" = Option<String>, Query, description = "Time range: 1h, 6h, 24h, 3d (default: 24h)")
+    ),
+    responses(
+        (status = 200, description = "Node metrics", body = NodeMetricsSummary),
+        (status = 404, description = "No metrics collected for the node"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Overview"
+)]
+pub async fn get_node_metrics(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path((node_type, node_id)): Path<(NodeType, String)>,
+    Query(params): Query<TrendQueryParams>,
+) -> ApiResult<Json<NodeMetricsSummary>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let node = state
+        .node_metrics_service
+        .get_node(cluster.id, node_type, &node_id, &params.time_range)
+        .await?;
+
+    Ok(Json(node))
+}
+
+/// Get node metrics trends
+///
+/// Returns time series of one node: CPU, memory, disk (total and per storage path),
+/// data used, tablets, compaction score, running queries and liveness.
+#[utoipa::path(
+    get,
+    path = "/api/clusters/overview/nodes/{node_type}/{node_id}/trends",
+    params(
+        ("node_type" = NodeType, Path, description = "Node type: be, cn, fe"),
+        ("node_id" = String, Path, description = "Backend ID (BE/CN) or name (FE)"),
+        ("time_range" = Option<String>, Query, description = "Time range: 1h, 6h, 24h, 3d (default: 24h)")
+    ),
+    responses(
+        (status = 200, description = "Node metrics trends", body = NodeMetricsTrends),
+        (status = 404, description = "No active cluster found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Overview"
+)]
+pub async fn get_node_metrics_trends(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path((node_type, node_id)): Path<(NodeType, String)>,
+    Query(params): Query<TrendQueryParams>,
+) -> ApiResult<Json<NodeMetricsTrends>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let trends = state
+        .node_metrics_service
+        .get_node_trends(cluster.id, node_type, &node_id, &params.time_range)
+        .await?;
+
+    Ok(Json(trends))
+}
+
+/// Get fleet overview
+///
+/// Returns health card, KPIs and alerts of every cluster visible to the caller in one call.
+/// Clusters are queried concurrently with a per-cluster timeout; clusters that fail or time
+/// out are returned with their status instead of failing the request.
+#[utoipa::path(
+    get,
+    path = "/api/clusters/fleet",
+    params(
+        ("timeout_ms" = Option<u64>, Query, description = "Per-cluster timeout in ms (500-30000, default: 5000)")
+    ),
+    responses(
+        (status = 200, description = "Fleet overview", body = FleetOverview),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Cluster Overview"
+)]
+pub async fn get_fleet_overview(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<FleetQueryParams>,
+) -> ApiResult<Json<FleetOverview>> {
+    let clusters = state
+        .cluster_service
+        .list_visible_clusters(&org_ctx)
+        .await?;
+    let (min, max) = FLEET_TIMEOUT_RANGE_MS;
+    let timeout_ms = params
+        .timeout_ms
+        .unwrap_or(FLEET_TIMEOUT_MS)
+        .clamp(min, max);
+    tracing::debug!(
+        "GET /api/clusters/fleet: {} clusters, timeout {}ms",
+        clusters.len(),
+        timeout_ms
+    );
+
+    let fleet = state
+        .overview_service
+        .get_fleet_overview(clusters, std::time::Duration::from_millis(timeout_ms))
+        .await;
+
+    Ok(Json(fleet))
+}
@@ -1,39 +1,21 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use std::sync::Arc;
 
-use crate::models::{ProfileDetail, ProfileListItem};
+use crate::models::{
+    Cluster, ProfileArchiveDetail, ProfileArchiveItem, ProfileArchiveListResponse,
+    ProfileArchiveQuery, ProfileDetail, ProfileDiffRequest, ProfileDiffSource, ProfileListItem,
+};
 use crate::services::MySQLClient;
 use crate::services::profile_analyzer::{
-    AnalysisContext, ClusterVariables, ProfileAnalysisResponse, analyze_profile_with_context,
+    AnalysisContext, ClusterVariables, ProfileAnalysisResponse, ProfileDiffResponse,
+    analyze_profile_with_context, diff_profiles,
 };
+use crate::services::profile_archive_service::sanitize_query_id;
 use crate::utils::{ApiResult, error::ApiError};
 
-/// Validate and sanitize query_id to prevent SQL injection
-/// StarRocks query_id format: UUID like "12345678-1234-1234-1234-123456789abc"
-///
-/// Returns the sanitized (trimmed) query_id as a String.
-/// The sanitized version is what should be used for:
-/// - SQL queries (security)
-/// - API responses (consistency)
-/// - Error messages (clarity)
-fn sanitize_query_id(query_id: &str) -> Result<String, ApiError> {
-    let id = query_id.trim();
-    // Allow alphanumeric, hyphens, and underscores (UUID format)
-    if id.is_empty()
-        || id.len() > 64
-        || !id
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err(ApiError::invalid_data("Invalid query_id format"));
-    }
-    // Return owned String to avoid lifetime issues and ensure consistency
-    Ok(id.to_string())
-}
-
 // List all query profiles for a cluster
 #[utoipa::path(
     get,
@@ -51,15 +33,8 @@ pub async fn list_profiles(
     State(state): State<Arc<crate::AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<ProfileListItem>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     tracing::info!("Fetching profile list for cluster {}", cluster.id);
 
@@ -120,15 +95,8 @@ pub async fn get_profile(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path(query_id): Path<String>,
 ) -> ApiResult<Json<ProfileDetail>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Sanitize query_id to prevent SQL injection
     // Note: This trims whitespace and validates format. The sanitized version
@@ -189,15 +157,8 @@ pub async fn analyze_profile_handler(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path(query_id): Path<String>,
 ) -> ApiResult<Json<ProfileAnalysisResponse>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Sanitize query_id to prevent SQL injection
     // Note: This trims whitespace and validates format. The sanitized version
@@ -308,3 +269,237 @@ async fn fetch_cluster_variables(mysql_client: &MySQLClient) -> Option<ClusterVa
         },
     }
 }
+
+// ========================================
+// Profile Archive
+// ========================================
+
+/// Archive a query profile from the active cluster
+#[utoipa::path(
+    post,
+    path = "/api/clusters/profiles/{query_id}/archive",
+    params(
+        ("query_id" = String, Path, description = "Query ID to archive")
+    ),
+    responses(
+        (status = 200, description = "Archived profile summary", body = ProfileArchiveItem),
+        (status = 404, description = "No active cluster found or profile not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn archive_profile(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(query_id): Path<String>,
+) -> ApiResult<Json<ProfileArchiveItem>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let item = state
+        .profile_archive_service
+        .archive_from_cluster(&cluster, &query_id, Some(org_ctx.user_id))
+        .await?;
+    Ok(Json(item))
+}
+
+/// List archived profiles of the active cluster
+#[utoipa::path(
+    get,
+    path = "/api/clusters/profile-archives",
+    params(
+        ("user" = Option<String>, Query, description = "Query user"),
+        ("db" = Option<String>, Query, description = "Default database"),
+        ("min_duration_ms" = Option<f64>, Query, description = "Minimum total time (ms)"),
+        ("max_duration_ms" = Option<f64>, Query, description = "Maximum total time (ms)"),
+        ("rule_id" = Option<String>, Query, description = "Diagnostic rule id, e.g. S001"),
+        ("start_time" = Option<String>, Query, description = "Query start time lower bound"),
+        ("end_time" = Option<String>, Query, description = "Query start time upper bound"),
+        ("keyword" = Option<String>, Query, description = "Search in query id and SQL"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20, max: 200)")
+    ),
+    responses(
+        (status = 200, description = "Archived profiles", body = ProfileArchiveListResponse),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn list_profile_archives(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(query): Query<ProfileArchiveQuery>,
+) -> ApiResult<Json<ProfileArchiveListResponse>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let response = state
+        .profile_archive_service
+        .list_archives(cluster.id, &query)
+        .await?;
+    Ok(Json(response))
+}
+
+/// Get an archived profile with its raw text
+#[utoipa::path(
+    get,
+    path = "/api/clusters/profile-archives/{id}",
+    params(
+        ("id" = i64, Path, description = "Archive ID")
+    ),
+    responses(
+        (status = 200, description = "Archived profile", body = ProfileArchiveDetail),
+        (status = 404, description = "Archived profile not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn get_profile_archive(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ProfileArchiveDetail>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let detail = state
+        .profile_archive_service
+        .get_archive(cluster.id, id)
+        .await?;
+    Ok(Json(detail))
+}
+
+/// Re-analyze an archived profile with the current rule engine (stores the new summary)
+#[utoipa::path(
+    post,
+    path = "/api/clusters/profile-archives/{id}/analyze",
+    params(
+        ("id" = i64, Path, description = "Archive ID")
+    ),
+    responses(
+        (status = 200, description = "Profile analysis result with execution tree"),
+        (status = 404, description = "Archived profile not found"),
+        (status = 500, description = "Profile parsing failed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn analyze_profile_archive(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ProfileAnalysisResponse>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let analysis = state
+        .profile_archive_service
+        .reanalyze(cluster.id, id)
+        .await?;
+    Ok(Json(analysis))
+}
+
+/// Delete an archived profile
+#[utoipa::path(
+    delete,
+    path = "/api/clusters/profile-archives/{id}",
+    params(
+        ("id" = i64, Path, description = "Archive ID")
+    ),
+    responses(
+        (status = 200, description = "Archived profile deleted"),
+        (status = 404, description = "Archived profile not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn delete_profile_archive(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    state
+        .profile_archive_service
+        .delete_archive(cluster.id, id)
+        .await?;
+    Ok(Json(serde_json::json!({ "message": "Archived profile deleted" })))
+}
+
+// ========================================
+// Profile Diff
+// ========================================
+
+/// Compare two query profiles (before/after tuning)
+///
+/// Each side can be a live query id, an archived profile or uploaded profile text.
+#[utoipa::path(
+    post,
+    path = "/api/clusters/profiles/diff",
+    request_body = ProfileDiffRequest,
+    responses(
+        (status = 200, description = "Per-node metric deltas, diagnostic changes and score change"),
+        (status = 400, description = "Invalid query_id or empty profile text"),
+        (status = 404, description = "No active cluster found or profile not found"),
+        (status = 500, description = "Profile parsing failed")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Profiles"
+)]
+pub async fn diff_profiles_handler(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(request): Json<ProfileDiffRequest>,
+) -> ApiResult<Json<ProfileDiffResponse>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    let baseline_text = load_diff_source(&state, &cluster, &request.baseline).await?;
+    let target_text = load_diff_source(&state, &cluster, &request.target).await?;
+
+    // Both sides are analyzed without cluster variables so rule results stay comparable
+    let context = AnalysisContext::default();
+    let baseline = analyze_profile_with_context(&baseline_text, &context)
+        .map_err(|e| ApiError::internal_error(format!("Baseline analysis failed: {}", e)))?;
+    let target = analyze_profile_with_context(&target_text, &context)
+        .map_err(|e| ApiError::internal_error(format!("Target analysis failed: {}", e)))?;
+
+    Ok(Json(diff_profiles(&baseline, &target)))
+}
+
+/// Resolve a diff source to raw profile text
+async fn load_diff_source(
+    state: &crate::AppState,
+    cluster: &Cluster,
+    source: &ProfileDiffSource,
+) -> ApiResult<String> {
+    match source {
+        ProfileDiffSource::QueryId { query_id } => {
+            let safe_query_id = sanitize_query_id(query_id)?;
+            state
+                .profile_archive_service
+                .fetch_profile(cluster, &safe_query_id)
+                .await
+        },
+        ProfileDiffSource::Archive { archive_id } => Ok(state
+            .profile_archive_service
+            .get_archive(cluster.id, *archive_id)
+            .await?
+            .profile_content),
+        ProfileDiffSource::Text { profile_text } => {
+            if profile_text.trim().is_empty() {
+                return Err(ApiError::invalid_data("Profile text is empty"));
+            }
+            Ok(profile_text.clone())
+        },
+    }
+}
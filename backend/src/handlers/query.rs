@@ -10,12 +10,12 @@ use std::time::Instant;
 
 use crate::AppState;
 use crate::models::{
-    CatalogWithDatabases, CatalogsWithDatabasesResponse, Query, QueryExecuteRequest,
-    QueryExecuteResponse, SingleQueryResult, TableMetadata, TableObjectType,
+    CatalogWithDatabases, CatalogsWithDatabasesResponse, EffectiveSqlPolicy, Query,
+    QueryExecuteRequest, QueryExecuteResponse, SingleQueryResult, TableMetadata, TableObjectType,
 };
-use crate::services::StarRocksClient;
 use crate::services::mysql_client::MySQLClient;
-use crate::utils::ApiResult;
+use crate::services::{StarRocksClient, sql_policy_service};
+use crate::utils::{ApiResult, sql_lexer};
 
 // Get list of catalogs using MySQL client
 #[utoipa::path(
@@ -34,15 +34,8 @@ pub async fn list_catalogs(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<String>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Use MySQL client to execute SHOW CATALOGS
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
@@ -85,15 +78,8 @@ pub async fn list_databases(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
 ) -> ApiResult<Json<Vec<String>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Use MySQL client
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
@@ -146,15 +132,8 @@ pub async fn list_tables(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
 ) -> ApiResult<Json<Vec<TableMetadata>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Use MySQL client
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
@@ -329,15 +308,8 @@ pub async fn list_catalogs_with_databases(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<CatalogsWithDatabasesResponse>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Use MySQL client
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
@@ -414,15 +386,8 @@ pub async fn list_queries(
     State(state): State<Arc<AppState>>,
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
 ) -> ApiResult<Json<Vec<Query>>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
     let client = StarRocksClient::new(cluster, state.mysql_pool_manager.clone());
     let queries = client.get_queries().await?;
     Ok(Json(queries))
@@ -450,15 +415,8 @@ pub async fn kill_query(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Path(query_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
@@ -492,22 +450,15 @@ pub async fn execute_sql(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Json(request): Json<QueryExecuteRequest>,
 ) -> ApiResult<Json<QueryExecuteResponse>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Use pool manager to get cached pool (avoid intermittent failures from creating new pools)
     let pool: mysql_async::Pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql_client = MySQLClient::from_pool(pool);
 
-    // Parse SQL statements first (split by semicolon, handling simple cases)
-    let sql_statements = parse_sql_statements(&request.sql);
+    // Split into statements (semicolons in strings, identifiers and comments are ignored)
+    let sql_statements = sql_lexer::split_statements(&request.sql);
 
     // Limit to maximum 5 statements
     let sql_statements: Vec<String> = sql_statements.into_iter().take(5).collect();
@@ -517,6 +468,35 @@ pub async fn execute_sql(
         return Ok(Json(QueryExecuteResponse { results: Vec::new(), total_execution_time_ms: 0 }));
     }
 
+    // Enforce the SQL policy of the user's roles before anything reaches the cluster
+    let policy = if org_ctx.is_super_admin {
+        EffectiveSqlPolicy::default()
+    } else {
+        state
+            .sql_policy_service
+            .effective_policy(org_ctx.user_id)
+            .await?
+    };
+    sql_policy_service::check_statements(
+        &policy,
+        &sql_statements,
+        request.catalog.as_deref(),
+        request.database.as_deref(),
+    )?;
+    // Execution granted on some catalogs or databases of the cluster only
+    if let Some(scopes) = org_ctx.database_scopes(cluster.id) {
+        sql_policy_service::check_scoped_statements(
+            &scopes,
+            &sql_statements,
+            request.catalog.as_deref(),
+            request.database.as_deref(),
+        )?;
+    }
+    let row_limit = sql_policy_service::effective_row_limit(&policy, request.limit);
+    let max_execution = policy
+        .max_execution_secs
+        .map(|secs| std::time::Duration::from_secs(secs as u64));
+
     // CRITICAL: Create a session with a dedicated connection
     // This ensures USE CATALOG/DATABASE state persists across all queries
     let mut session = mysql_client.create_session().await?;
@@ -531,6 +511,10 @@ pub async fn execute_sql(
         session.use_database(db).await?;
     }
 
+    if let Some(secs) = policy.max_execution_secs {
+        session.set_query_timeout(secs).await?;
+    }
+
     let total_start = Instant::now();
     let mut results = Vec::new();
 
@@ -540,14 +524,41 @@ pub async fn execute_sql(
             continue;
         }
 
-        let sql_with_limit = apply_query_limit(&sql, request.limit.unwrap_or(1000));
+        let sql_with_limit = sql_lexer::apply_limit(&sql, row_limit);
 
         // Execute query on the session's connection that has persistent database context
         // Use execute to get accurate SQL execution time (excluding data processing)
-        let query_result = session.execute(&sql_with_limit).await;
+        let query_result = match max_execution {
+            // query_timeout stops the query on the FE; this also covers statements it does not apply to
+            Some(max) => match tokio::time::timeout(max, session.execute(&sql_with_limit)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    // Dropping the future only stops waiting: stop the query on the FE too
+                    if let Err(e) = session.kill_query().await {
+                        tracing::warn!("Failed to kill the timed-out query: {}", e);
+                    }
+                    results.push(SingleQueryResult {
+                        sql,
+                        columns: Vec::new(),
+                        rows: Vec::new(),
+                        row_count: 0,
+                        execution_time_ms: max.as_millis(),
+                        success: false,
+                        error: Some(format!("执行超过角色允许的最长时间 {}s", max.as_secs())),
+                    });
+                    // The connection is still busy with the cancelled statement
+                    break;
+                },
+            },
+            None => session.execute(&sql_with_limit).await,
+        };
 
         match query_result {
-            Ok((columns, data_rows, execution_time_ms)) => {
+            Ok((columns, mut data_rows, execution_time_ms)) => {
+                // SHOW and statements with their own LIMIT are capped after the fact
+                if let Some(max_rows) = policy.max_rows {
+                    data_rows.truncate(usize::try_from(max_rows).unwrap_or(usize::MAX));
+                }
                 let row_count = data_rows.len();
                 results.push(SingleQueryResult {
                     sql,
@@ -579,69 +590,3 @@ pub async fn execute_sql(
 
     Ok(Json(QueryExecuteResponse { results, total_execution_time_ms }))
 }
-
-// Simple SQL statement parser - splits by semicolon, ignoring those in single/double quotes
-fn parse_sql_statements(sql: &str) -> Vec<String> {
-    let mut statements = Vec::new();
-    let mut current = String::new();
-    let mut in_single_quote = false;
-    let mut in_double_quote = false;
-    let chars = sql.chars().peekable();
-
-    for ch in chars {
-        match ch {
-            '\'' if !in_double_quote => {
-                in_single_quote = !in_single_quote;
-                current.push(ch);
-            },
-            '"' if !in_single_quote => {
-                in_double_quote = !in_double_quote;
-                current.push(ch);
-            },
-            ';' if !in_single_quote && !in_double_quote => {
-                let trimmed = current.trim();
-                if !trimmed.is_empty() {
-                    statements.push(trimmed.to_string());
-                }
-                current.clear();
-            },
-            _ => {
-                current.push(ch);
-            },
-        }
-    }
-
-    // Add the last statement if exists
-    let trimmed = current.trim();
-    if !trimmed.is_empty() {
-        statements.push(trimmed.to_string());
-    }
-
-    statements
-}
-
-fn apply_query_limit(sql: &str, limit: i32) -> String {
-    let trimmed = sql.trim();
-    let sql_upper = trimmed.to_uppercase();
-
-    // Return original SQL if it already has LIMIT
-    if sql_upper.contains("LIMIT") {
-        return trimmed.to_string();
-    }
-
-    // Only add LIMIT to SELECT queries that don't contain special keywords
-    if sql_upper.starts_with("SELECT") {
-        if sql_upper.contains("GET_QUERY_PROFILE")
-            || sql_upper.contains("SHOW_PROFILE")
-            || sql_upper.contains("EXPLAIN")
-        {
-            return trimmed.to_string();
-        }
-
-        // Add LIMIT to SELECT query
-        let sql_without_semicolon = trimmed.trim_end_matches(';');
-        format!("{} LIMIT {}", sql_without_semicolon, limit)
-    } else {
-        trimmed.to_string()
-    }
-}
@@ -3,6 +3,7 @@ use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::models::starrocks::{QueryHistoryItem, QueryHistoryResponse};
+use crate::services::AuditColumn;
 use crate::services::mysql_client::MySQLClient;
 use crate::utils::error::ApiResult;
 
@@ -40,19 +41,16 @@ pub async fn list_query_history(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     axum::extract::Query(params): axum::extract::Query<HistoryQueryParams>,
 ) -> ApiResult<Json<QueryHistoryResponse>> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
     let mysql = MySQLClient::from_pool(pool);
 
+    // Audit table of the cluster
+    let audit = state.audit_source_service.resolve(&cluster).await?;
+    let ts = audit.col(AuditColumn::Timestamp);
+
     let limit = params.limit;
     let offset = params.offset;
     let keyword = params.keyword.as_deref().unwrap_or("");
@@ -61,32 +59,35 @@ pub async fn list_query_history(
 
     // Build WHERE conditions
     let mut where_conditions = vec![
-        "isQuery = 1".to_string(),
-        "`timestamp` >= DATE_SUB(NOW(), INTERVAL 7 DAY)".to_string(),
+        format!("{} = 1", audit.col(AuditColumn::IsQuery)),
+        format!("{} >= DATE_SUB(NOW(), INTERVAL 7 DAY)", ts),
     ];
 
     // Add keyword search if provided
     if !keyword.is_empty() {
+        let keyword = keyword.replace('\'', "''"); // Escape single quotes
         where_conditions.push(format!(
-            "(`queryId` LIKE '%{}%' OR `stmt` LIKE '%{}%' OR `user` LIKE '%{}%')",
-            keyword.replace('\'', "''"), // Escape single quotes
-            keyword.replace('\'', "''"),
-            keyword.replace('\'', "''")
+            "({} LIKE '%{}%' OR {} LIKE '%{}%' OR {} LIKE '%{}%')",
+            audit.col(AuditColumn::QueryId),
+            keyword,
+            audit.col(AuditColumn::Stmt),
+            keyword,
+            audit.col(AuditColumn::User),
+            keyword
         ));
     }
 
     // Add time range filters if provided
     if let Some(start) = start_time {
-        where_conditions.push(format!("`timestamp` >= '{}'", start));
+        where_conditions.push(format!("{} >= '{}'", ts, start));
     }
     if let Some(end) = end_time {
-        where_conditions.push(format!("`timestamp` <= '{}'", end));
+        where_conditions.push(format!("{} <= '{}'", ts, end));
     }
 
     let where_clause = where_conditions.join(" AND ");
 
-    // Get audit table name from config
-    let audit_table = state.audit_config.full_table_name();
+    let audit_table = audit.full_table_name();
 
     // First, get the total count (required for ng2-smart-table pagination)
     let count_sql = format!(
@@ -123,21 +124,34 @@ pub async fn list_query_history(
     let sql = format!(
         r#"
         SELECT 
-            queryId,
-            `user`,
-            COALESCE(`db`, '') AS db,
-            `stmt`,
-            `queryType`,
-            `timestamp` AS start_time,
-            `queryTime` AS total_ms,
-            `state`,
-            COALESCE(`resourceGroup`, '') AS warehouse
+            {} AS queryId,
+            {} AS `user`,
+            COALESCE({}, '') AS db,
+            {} AS `stmt`,
+            {} AS `queryType`,
+            {} AS start_time,
+            {} AS total_ms,
+            {} AS `state`,
+            COALESCE({}, '') AS warehouse
         FROM {}
         WHERE {}
-        ORDER BY `timestamp` DESC
+        ORDER BY {} DESC
         LIMIT {} OFFSET {}
     "#,
-        audit_table, where_clause, limit, offset
+        audit.col(AuditColumn::QueryId),
+        audit.col(AuditColumn::User),
+        audit.col(AuditColumn::Db),
+        audit.col(AuditColumn::Stmt),
+        audit.col(AuditColumn::QueryType),
+        ts,
+        audit.col(AuditColumn::QueryTime),
+        audit.col(AuditColumn::State),
+        audit.col(AuditColumn::ResourceGroup),
+        audit_table,
+        where_clause,
+        ts,
+        limit,
+        offset
     );
 
     tracing::info!(
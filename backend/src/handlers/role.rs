@@ -6,8 +6,8 @@ use std::sync::Arc;
 
 use crate::AppState;
 use crate::models::{
-    CreateRoleRequest, RoleResponse, RoleWithPermissions, UpdateRolePermissionsRequest,
-    UpdateRoleRequest,
+    CreateRoleRequest, RoleResponse, RoleSqlPolicy, RoleWithPermissions,
+    UpdateRolePermissionsRequest, UpdateRoleRequest, UpdateRoleSqlPolicyRequest,
 };
 use crate::utils::ApiResult;
 
@@ -285,9 +285,10 @@ pub async fn update_role_permissions(
     Json(req): Json<UpdateRolePermissionsRequest>,
 ) -> ApiResult<Json<()>> {
     tracing::info!(
-        "Role permissions update request: ID={}, permission_count={} by user {} (org: {:?}, super_admin: {})",
+        "Role permissions update request: ID={}, permission_count={}, scoped_count={:?} by user {} (org: {:?}, super_admin: {})",
         id,
         req.permission_ids.len(),
+        req.scoped_permissions.as_ref().map(Vec::len),
         org_ctx.user_id,
         org_ctx.organization_id,
         org_ctx.is_super_admin
@@ -302,3 +303,95 @@ pub async fn update_role_permissions(
     tracing::info!("Role permissions updated successfully: ID={} by user {}", id, org_ctx.user_id);
     Ok(Json(()))
 }
+
+// Get role SQL execution policy
+#[utoipa::path(
+    get,
+    path = "/api/roles/{id}/sql-policy",
+    responses(
+        (status = 200, description = "Role SQL policy (null when the role is unrestricted)", body = Option<RoleSqlPolicy>),
+        (status = 404, description = "Role not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Roles"
+)]
+pub async fn get_role_sql_policy(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Option<RoleSqlPolicy>>> {
+    // Ensure the role is visible to the requester
+    state
+        .role_service
+        .get_role(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+
+    let policy = state.sql_policy_service.get_role_policy(id).await?;
+    Ok(Json(policy))
+}
+
+// Create or replace role SQL execution policy
+#[utoipa::path(
+    put,
+    path = "/api/roles/{id}/sql-policy",
+    request_body = UpdateRoleSqlPolicyRequest,
+    responses(
+        (status = 200, description = "Role SQL policy updated", body = RoleSqlPolicy),
+        (status = 404, description = "Role not found"),
+        (status = 400, description = "Bad request")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Roles"
+)]
+pub async fn update_role_sql_policy(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Json(req): Json<UpdateRoleSqlPolicyRequest>,
+) -> ApiResult<Json<RoleSqlPolicy>> {
+    state
+        .role_service
+        .get_role(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+
+    let policy = state
+        .sql_policy_service
+        .set_role_policy(id, req, Some(org_ctx.user_id))
+        .await?;
+
+    tracing::info!("Role SQL policy updated: ID={} by user {}", id, org_ctx.user_id);
+    Ok(Json(policy))
+}
+
+// Remove role SQL execution policy
+#[utoipa::path(
+    delete,
+    path = "/api/roles/{id}/sql-policy",
+    responses(
+        (status = 200, description = "Role SQL policy removed"),
+        (status = 404, description = "Role or policy not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Roles"
+)]
+pub async fn delete_role_sql_policy(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<()>> {
+    state
+        .role_service
+        .get_role(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+
+    state.sql_policy_service.delete_role_policy(id).await?;
+
+    tracing::info!("Role SQL policy removed: ID={} by user {}", id, org_ctx.user_id);
+    Ok(Json(()))
+}
@@ -0,0 +1,242 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::middleware::OrgContext;
+use crate::models::{RoutineLoadAction, RoutineLoadFilter, RoutineLoadJob, RoutineLoadTask};
+use crate::services::{RoutineLoadLagTrends, TimeRange};
+use crate::utils::ApiResult;
+
+/// Catalog Routine Load jobs write to
+const DEFAULT_CATALOG: &str = "default_catalog";
+
+/// Query parameters of the lag history
+#[derive(Debug, Deserialize)]
+pub struct LagQueryParams {
+    #[serde(default = "default_time_range")]
+    pub time_range: TimeRange,
+}
+
+fn default_time_range() -> TimeRange {
+    TimeRange::Hours24
+}
+
+/// GET /api/clusters/routine-loads - List Routine Load jobs
+#[utoipa::path(
+    get,
+    path = "/api/clusters/routine-loads",
+    params(
+        ("database" = Option<String>, Query, description = "Database name filter"),
+        ("state" = Option<String>, Query, description = "State filter, e.g. RUNNING or PAUSED"),
+        ("include_finished" = Option<bool>, Query, description = "Include STOPPED and CANCELLED jobs"),
+    ),
+    responses(
+        (status = 200, description = "Routine Load jobs with partition offsets and lag", body = Vec<RoutineLoadJob>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn list_routine_loads(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Query(filter): Query<RoutineLoadFilter>,
+) -> ApiResult<Json<Vec<RoutineLoadJob>>> {
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+
+    if let Some(database) = &filter.database {
+        org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+    }
+    let mut jobs = state
+        .routine_load_service
+        .list_jobs(&cluster, &filter)
+        .await?;
+    jobs.retain(|job| {
+        org_ctx
+            .check_database_access(cluster.id, DEFAULT_CATALOG, &job.database_name)
+            .is_ok()
+    });
+
+    Ok(Json(jobs))
+}
+
+/// GET /api/clusters/routine-loads/{database}/{name} - Get a Routine Load job
+#[utoipa::path(
+    get,
+    path = "/api/clusters/routine-loads/{database}/{name}",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Routine Load job", body = RoutineLoadJob),
+        (status = 404, description = "Job not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn get_routine_load(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+) -> ApiResult<Json<RoutineLoadJob>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &database)?;
+
+    let job = state
+        .routine_load_service
+        .get_job(&cluster, &database, &name)
+        .await?;
+    Ok(Json(job))
+}
+
+/// GET /api/clusters/routine-loads/{database}/{name}/tasks - Tasks of a Routine Load job
+#[utoipa::path(
+    get,
+    path = "/api/clusters/routine-loads/{database}/{name}/tasks",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Routine Load tasks", body = Vec<RoutineLoadTask>)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn list_routine_load_tasks(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+) -> ApiResult<Json<Vec<RoutineLoadTask>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &database)?;
+
+    let tasks = state
+        .routine_load_service
+        .list_tasks(&cluster, &database, &name)
+        .await?;
+    Ok(Json(tasks))
+}
+
+/// GET /api/clusters/routine-loads/{database}/{name}/lag - Lag history of a Routine Load job
+#[utoipa::path(
+    get,
+    path = "/api/clusters/routine-loads/{database}/{name}/lag",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+        ("time_range" = Option<String>, Query, description = "Time range: 1h, 6h, 24h, 3d (default: 24h)"),
+    ),
+    responses(
+        (status = 200, description = "Lag, rows and state changes recorded by the metrics collector", body = RoutineLoadLagTrends)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn get_routine_load_lag(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+    Query(params): Query<LagQueryParams>,
+) -> ApiResult<Json<RoutineLoadLagTrends>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, &database)?;
+
+    let trends = state
+        .routine_load_service
+        .get_lag_trends(cluster.id, &database, &name, &params.time_range)
+        .await?;
+    Ok(Json(trends))
+}
+
+/// POST /api/clusters/routine-loads/{database}/{name}/pause - Pause a Routine Load job
+#[utoipa::path(
+    post,
+    path = "/api/clusters/routine-loads/{database}/{name}/pause",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Job paused", body = RoutineLoadJob),
+        (status = 400, description = "Job is not running")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn pause_routine_load(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+) -> ApiResult<Json<RoutineLoadJob>> {
+    control(&state, &org_ctx, &database, &name, RoutineLoadAction::Pause).await
+}
+
+/// POST /api/clusters/routine-loads/{database}/{name}/resume - Resume a paused Routine Load job
+#[utoipa::path(
+    post,
+    path = "/api/clusters/routine-loads/{database}/{name}/resume",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Job resumed", body = RoutineLoadJob),
+        (status = 400, description = "Job is not paused")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn resume_routine_load(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+) -> ApiResult<Json<RoutineLoadJob>> {
+    control(&state, &org_ctx, &database, &name, RoutineLoadAction::Resume).await
+}
+
+/// POST /api/clusters/routine-loads/{database}/{name}/stop - Stop a Routine Load job for good
+#[utoipa::path(
+    post,
+    path = "/api/clusters/routine-loads/{database}/{name}/stop",
+    params(
+        ("database" = String, Path, description = "Database name"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Job stopped", body = RoutineLoadJob),
+        (status = 400, description = "Job already stopped")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Routine Load"
+)]
+pub async fn stop_routine_load(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<OrgContext>,
+    Path((database, name)): Path<(String, String)>,
+) -> ApiResult<Json<RoutineLoadJob>> {
+    control(&state, &org_ctx, &database, &name, RoutineLoadAction::Stop).await
+}
+
+async fn control(
+    state: &AppState,
+    org_ctx: &OrgContext,
+    database: &str,
+    name: &str,
+    action: RoutineLoadAction,
+) -> ApiResult<Json<RoutineLoadJob>> {
+    let cluster = state.cluster_service.resolve_cluster(org_ctx).await?;
+    org_ctx.check_database_access(cluster.id, DEFAULT_CATALOG, database)?;
+
+    let job = state
+        .routine_load_service
+        .control_job(&cluster, database, name, action)
+        .await?;
+    Ok(Json(job))
+}
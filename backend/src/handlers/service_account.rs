@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+};
+
+use crate::AppState;
+use crate::middleware::OrgContext;
+use crate::models::{
+    ApiToken, CreateApiTokenRequest, CreateServiceAccountRequest, CreatedApiToken, ServiceAccount,
+};
+use crate::utils::{ApiError, ApiResult};
+
+/// List service accounts
+#[utoipa::path(
+    get,
+    path = "/api/service-accounts",
+    responses(
+        (status = 200, description = "Service accounts with their roles", body = Vec<ServiceAccount>)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn list_service_accounts(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+) -> ApiResult<Json<Vec<ServiceAccount>>> {
+    let accounts = state
+        .user_service
+        .list_service_accounts(org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    Ok(Json(accounts))
+}
+
+/// Get a service account
+#[utoipa::path(
+    get,
+    path = "/api/service-accounts/{id}",
+    params(("id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, description = "Service account", body = ServiceAccount),
+        (status = 404, description = "Service account not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn get_service_account(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<ServiceAccount>> {
+    let account = state
+        .user_service
+        .get_service_account(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    Ok(Json(account))
+}
+
+/// Create a service account
+#[utoipa::path(
+    post,
+    path = "/api/service-accounts",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 200, description = "Service account created", body = ServiceAccount),
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "A role grants permissions the caller does not hold")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn create_service_account(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Json(req): Json<CreateServiceAccountRequest>,
+) -> ApiResult<Json<ServiceAccount>> {
+    if !org_ctx.is_super_admin && req.organization_id.is_some() {
+        return Err(ApiError::forbidden(
+            "Organization administrators cannot override organization assignment",
+        ));
+    }
+
+    let account = state
+        .user_service
+        .create_service_account(
+            req,
+            org_ctx.user_id,
+            org_ctx.organization_id,
+            org_ctx.is_super_admin,
+        )
+        .await?;
+    Ok(Json(account))
+}
+
+/// Delete a service account and its tokens
+#[utoipa::path(
+    delete,
+    path = "/api/service-accounts/{id}",
+    params(("id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, description = "Service account deleted"),
+        (status = 404, description = "Service account not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn delete_service_account(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<()>> {
+    state
+        .user_service
+        .delete_service_account(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    tracing::info!("User {} deleted service account {}", org_ctx.user_id, id);
+    Ok(Json(()))
+}
+
+/// List the API tokens of a service account
+#[utoipa::path(
+    get,
+    path = "/api/service-accounts/{id}/tokens",
+    params(("id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, description = "Tokens (revoked and expired included)", body = Vec<ApiToken>),
+        (status = 404, description = "Service account not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn list_service_account_tokens(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<ApiToken>>> {
+    let account = state
+        .user_service
+        .get_service_account(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    let tokens = state.api_token_service.list_tokens(account.id).await?;
+    Ok(Json(tokens))
+}
+
+/// Create an API token for a service account (the token is only returned by this call)
+#[utoipa::path(
+    post,
+    path = "/api/service-accounts/{id}/tokens",
+    params(("id" = i64, Path, description = "Service account ID")),
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 200, description = "Token created", body = CreatedApiToken),
+        (status = 400, description = "Invalid name, expiry, scopes or clusters"),
+        (status = 403, description = "A scope is not held by the caller"),
+        (status = 404, description = "Service account not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn create_service_account_token(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> ApiResult<Json<CreatedApiToken>> {
+    let account = state
+        .user_service
+        .get_service_account(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    let created = state
+        .api_token_service
+        .create_token(account.id, req, &org_ctx)
+        .await?;
+    Ok(Json(created))
+}
+
+/// Revoke an API token of a service account
+#[utoipa::path(
+    delete,
+    path = "/api/service-accounts/{id}/tokens/{token_id}",
+    params(
+        ("id" = i64, Path, description = "Service account ID"),
+        ("token_id" = i64, Path, description = "Token ID")
+    ),
+    responses(
+        (status = 200, description = "Token revoked", body = ApiToken),
+        (status = 404, description = "Service account or token not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Service Accounts"
+)]
+pub async fn revoke_service_account_token(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path((id, token_id)): Path<(i64, i64)>,
+) -> ApiResult<Json<ApiToken>> {
+    let account = state
+        .user_service
+        .get_service_account(id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    let token = state
+        .api_token_service
+        .revoke_token(account.id, token_id)
+        .await?;
+    Ok(Json(token))
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Redirect},
+};
+
+use crate::AppState;
+use crate::middleware::OrgContext;
+use crate::models::{
+    AuthProviders, CreateSsoGroupMappingRequest, LoginRequest, LoginResponse, OidcCallbackQuery,
+    SsoGroupMapping,
+};
+use crate::services::SessionClient;
+use crate::services::sso_service::PENDING_LOGIN_TTL;
+use crate::utils::crypto::secrets_equal;
+use crate::utils::{ApiError, ApiResult};
+
+/// Cookie holding the state of the OIDC login the browser started
+const OIDC_STATE_COOKIE: &str = "oidc_state";
+
+/// Login methods offered besides the local password
+#[utoipa::path(
+    get,
+    path = "/api/auth/providers",
+    responses(
+        (status = 200, description = "Enabled single sign-on providers", body = AuthProviders)
+    ),
+    tag = "Authentication"
+)]
+pub async fn providers(State(state): State<Arc<AppState>>) -> Json<AuthProviders> {
+    Json(state.sso_service.providers())
+}
+
+/// Log in with an LDAP directory password (the user is provisioned on first login)
+#[utoipa::path(
+    post,
+    path = "/api/auth/ldap/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "LDAP login is not enabled"),
+        (status = 401, description = "Invalid credentials, disabled user or no matching group mapping"),
+        (status = 429, description = "Account locked or client address throttled after failed logins")
+    ),
+    tag = "Authentication"
+)]
+pub async fn ldap_login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<LoginRequest>,
+) -> ApiResult<Json<LoginResponse>> {
+    tracing::info!("LDAP login attempt for username: {}", req.username);
+
+    let client = SessionClient::from_headers(&headers);
+    let (user, tokens) = state
+        .login_guard
+        .attempt(&req.username, &client, state.sso_service.ldap_login(&req, &client))
+        .await?;
+    let user = state.auth_service.to_user_response(user).await?;
+
+    Ok(Json(LoginResponse {
+        token: tokens.token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+        user,
+    }))
+}
+
+/// Start an OIDC login: redirect the browser to the identity provider
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/authorize",
+    responses(
+        (status = 303, description = "Redirect to the authorization endpoint of the provider"),
+        (status = 400, description = "OIDC login is not enabled")
+    ),
+    tag = "Authentication"
+)]
+pub async fn oidc_authorize(State(state): State<Arc<AppState>>) -> ApiResult<impl IntoResponse> {
+    let (url, login_state) = state.sso_service.oidc_authorization_url().await?;
+    let cookie = state_cookie(&state, &login_state, PENDING_LOGIN_TTL.as_secs());
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&url)))
+}
+
+/// Finish an OIDC login started in the same browser (its state cookie matches). The browser is
+/// sent to the configured frontend page with the tokens, or the error, in the URL fragment.
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/callback",
+    params(OidcCallbackQuery),
+    responses(
+        (status = 303, description = "Redirect to the frontend with `token`, `refresh_token` and `expires_in`, or `error`")
+    ),
+    tag = "Authentication"
+)]
+pub async fn oidc_callback(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> impl IntoResponse {
+    let browser_state = cookie_value(&headers, OIDC_STATE_COOKIE);
+    let result = match (&query.error, &query.code, &query.state) {
+        (Some(error), _, _) => Err(ApiError::unauthorized(format!(
+            "Sign-in failed at the identity provider: {}",
+            query.error_description.as_deref().unwrap_or(error)
+        ))),
+        // A callback link of a login started elsewhere would sign the browser in as someone else
+        (None, Some(_), Some(login_state))
+            if !browser_state.is_some_and(|expected| secrets_equal(login_state, expected)) =>
+        {
+            Err(ApiError::unauthorized(
+                "Sign-in was not started in this browser, please sign in again",
+            ))
+        },
+        (None, Some(code), Some(login_state)) => {
+            let client = SessionClient::from_headers(&headers);
+            state
+                .sso_service
+                .oidc_login(code, login_state, &client)
+                .await
+        },
+        _ => Err(ApiError::validation_error("Missing code or state")),
+    };
+
+    let params = match result {
+        Ok((_, tokens)) => vec![
+            ("token", tokens.token),
+            ("refresh_token", tokens.refresh_token),
+            ("expires_in", tokens.expires_in.to_string()),
+        ],
+        Err(e) => {
+            tracing::warn!("OIDC login failed: {}", e);
+            vec![("error", e.to_string())]
+        },
+    };
+    let cleared = state_cookie(&state, "", 0);
+    (
+        [(header::SET_COOKIE, cleared)],
+        Redirect::to(&with_fragment_params(state.sso_service.post_login_redirect(), &params)),
+    )
+}
+
+/// Cookie binding an OIDC login to the browser that started it. SameSite=Lax, as the provider
+/// redirects back with a cross-site top-level navigation.
+fn state_cookie(state: &AppState, value: &str, max_age_secs: u64) -> String {
+    let secure = if state.sso_service.oidc_callback_is_https() { "; Secure" } else { "" };
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        OIDC_STATE_COOKIE, value, max_age_secs, secure
+    )
+}
+
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name && !value.is_empty()).then_some(value)
+        })
+}
+
+/// Append parameters to the fragment of a URL, as query parameters of the route for
+/// hash-routed pages (`/#/auth/sso?token=...`). Fragments never reach servers or their logs.
+fn with_fragment_params(url: &str, params: &[(&str, String)]) -> String {
+    let separator = match url.split_once('#') {
+        None => '#',
+        Some((_, fragment)) if fragment.contains('?') => '&',
+        Some(_) => '?',
+    };
+    let encoded: Vec<String> = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, urlencoding::encode(value)))
+        .collect();
+    format!("{}{}{}", url, separator, encoded.join("&"))
+}
+
+/// List group mappings of the SSO providers
+#[utoipa::path(
+    get,
+    path = "/api/sso/mappings",
+    responses(
+        (status = 200, description = "Group mappings", body = Vec<SsoGroupMapping>),
+        (status = 401, description = "Not a super administrator")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Single Sign-On"
+)]
+pub async fn list_mappings(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+) -> ApiResult<Json<Vec<SsoGroupMapping>>> {
+    require_super_admin(&org_ctx)?;
+    let mappings = state.sso_service.list_mappings().await?;
+    Ok(Json(mappings))
+}
+
+/// Map a directory group to an organization and role
+#[utoipa::path(
+    post,
+    path = "/api/sso/mappings",
+    request_body = CreateSsoGroupMappingRequest,
+    responses(
+        (status = 200, description = "Mapping created, applied at the next login of members", body = SsoGroupMapping),
+        (status = 400, description = "Invalid provider, group or role"),
+        (status = 401, description = "Not a super administrator"),
+        (status = 404, description = "Organization or role not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Single Sign-On"
+)]
+pub async fn create_mapping(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Json(req): Json<CreateSsoGroupMappingRequest>,
+) -> ApiResult<Json<SsoGroupMapping>> {
+    require_super_admin(&org_ctx)?;
+    let mapping = state.sso_service.create_mapping(req).await?;
+    Ok(Json(mapping))
+}
+
+/// Delete a group mapping
+#[utoipa::path(
+    delete,
+    path = "/api/sso/mappings/{id}",
+    params(("id" = i64, Path, description = "Mapping ID")),
+    responses(
+        (status = 200, description = "Mapping deleted, members lose its role at their next login"),
+        (status = 401, description = "Not a super administrator"),
+        (status = 404, description = "Mapping not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Single Sign-On"
+)]
+pub async fn delete_mapping(
+    State(state): State<Arc<AppState>>,
+    Extension(org_ctx): Extension<OrgContext>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<()>> {
+    require_super_admin(&org_ctx)?;
+    state.sso_service.delete_mapping(id).await?;
+    Ok(Json(()))
+}
+
+/// Mappings grant roles of any organization, system roles included
+fn require_super_admin(org_ctx: &OrgContext) -> ApiResult<()> {
+    if !org_ctx.is_super_admin {
+        return Err(ApiError::forbidden(
+            "Only super administrators can manage single sign-on group mappings",
+        ));
+    }
+    Ok(())
+}
@@ -3,7 +3,11 @@ use std::sync::Arc;
 use axum::{Json, extract::Path, extract::State};
 
 use crate::AppState;
-use crate::models::{AdminCreateUserRequest, AdminUpdateUserRequest, UserWithRolesResponse};
+use crate::models::{
+    AdminCreateUserRequest, AdminUpdateUserRequest, RevokedSessions, UserSession,
+    UserWithRolesResponse,
+};
+use crate::services::SessionRevokeReason;
 use crate::utils::ApiResult;
 
 /// List users with their roles
@@ -114,7 +118,7 @@ pub async fn create_user(
     path = "/api/users/{id}",
     request_body = AdminUpdateUserRequest,
     responses(
-        (status = 200, description = "User updated", body = UserWithRolesResponse),
+        (status = 200, description = "User updated (disabling or setting the password ends the user's sessions)", body = UserWithRolesResponse),
         (status = 404, description = "User not found"),
         (status = 400, description = "Validation error"),
     ),
@@ -142,6 +146,18 @@ pub async fn update_user(
             "Organization administrators cannot reassign user organization",
         ));
     }
+    if payload.is_active == Some(false) && user_id == org_ctx.user_id {
+        return Err(crate::utils::ApiError::validation_error("You cannot disable yourself"));
+    }
+
+    // Existing sessions must not outlive a disable or a password reset
+    let revoke_reason = if payload.is_active == Some(false) {
+        Some(SessionRevokeReason::UserDisabled)
+    } else if payload.password.is_some() {
+        Some(SessionRevokeReason::PasswordChanged)
+    } else {
+        None
+    };
 
     tracing::info!(
         "Updating user_id={} by user {} (org: {:?}, super_admin: {})",
@@ -154,6 +170,12 @@ pub async fn update_user(
         .user_service
         .update_user(user_id, payload, org_ctx.organization_id, org_ctx.is_super_admin)
         .await?;
+    if let Some(reason) = revoke_reason {
+        state
+            .session_service
+            .revoke_user_sessions(user_id, None, reason)
+            .await?;
+    }
     tracing::info!(
         "Updated user: {} (ID: {}) by user {}",
         user.user.username,
@@ -193,3 +215,152 @@ pub async fn delete_user(
     tracing::info!("Deleted user_id={} by user {}", user_id, org_ctx.user_id);
     Ok(Json(()))
 }
+
+/// List the open login sessions of a user
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/sessions",
+    params(("id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "Open sessions, most recently used first", body = Vec<UserSession>),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn list_user_sessions(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<Vec<UserSession>>> {
+    state
+        .user_service
+        .get_user(user_id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    let sessions = state
+        .session_service
+        .list_sessions(user_id, org_ctx.session_id)
+        .await?;
+    Ok(Json(sessions))
+}
+
+/// Log a user out of all their sessions
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/sessions",
+    params(("id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "Sessions revoked", body = RevokedSessions),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn revoke_user_sessions(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<RevokedSessions>> {
+    state
+        .user_service
+        .get_user(user_id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    let revoked = state
+        .session_service
+        .revoke_user_sessions(user_id, None, SessionRevokeReason::Revoked)
+        .await?;
+    tracing::info!(
+        "User {} logged user_id={} out of {} session(s)",
+        org_ctx.user_id,
+        user_id,
+        revoked
+    );
+    Ok(Json(RevokedSessions { revoked }))
+}
+
+/// Revoke one login session of a user
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/sessions/{session_id}",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("session_id" = i64, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 200, description = "Session revoked"),
+        (status = 404, description = "User or session not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn revoke_user_session(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, session_id)): Path<(i64, i64)>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<()>> {
+    state
+        .user_service
+        .get_user(user_id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    state
+        .session_service
+        .revoke_session(user_id, session_id, SessionRevokeReason::Revoked)
+        .await?;
+    Ok(Json(()))
+}
+
+/// Remove the second factor of a user who lost their authenticator app and recovery codes
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/two-factor",
+    params(("id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "Two-factor authentication disabled, the user's sessions ended"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn reset_user_two_factor(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<()>> {
+    state
+        .user_service
+        .get_user(user_id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    state.two_factor_service.reset(user_id).await?;
+    state
+        .session_service
+        .revoke_user_sessions(user_id, None, SessionRevokeReason::Revoked)
+        .await?;
+    tracing::info!("User {} reset the second factor of user_id={}", org_ctx.user_id, user_id);
+    Ok(Json(()))
+}
+
+/// Lift the lock of a user after too many failed logins
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/lockout",
+    params(("id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User unlocked"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn unlock_user(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<i64>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<Json<()>> {
+    state
+        .user_service
+        .get_user(user_id, org_ctx.organization_id, org_ctx.is_super_admin)
+        .await?;
+    state.login_guard.unlock(user_id).await?;
+    tracing::info!("User {} unlocked user_id={}", org_ctx.user_id, user_id);
+    Ok(Json(()))
+}
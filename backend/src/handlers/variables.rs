@@ -9,7 +9,10 @@ use serde_json::json;
 use std::sync::Arc;
 
 use crate::{
-    models::starrocks::{UpdateVariableRequest, Variable},
+    models::{
+        UpdateVariableRequest, Variable, VariableChange, VariableChangeFilter, VariableSnapshot,
+        VariableSnapshotDiff, VariableSnapshotSummary,
+    },
     services::mysql_client::MySQLClient,
     utils::error::{ApiError, ApiResult},
 };
@@ -47,15 +50,8 @@ pub async fn get_variables(
     axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
     Query(params): Query<VariableQueryParams>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
     // Get MySQL client from pool
     let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
@@ -111,32 +107,205 @@ pub async fn update_variable(
     Path(variable_name): Path<String>,
     Json(request): Json<UpdateVariableRequest>,
 ) -> ApiResult<impl IntoResponse> {
-    // Get the active cluster with organization isolation
-    let cluster = if org_ctx.is_super_admin {
-        state.cluster_service.get_active_cluster().await?
-    } else {
-        state
-            .cluster_service
-            .get_active_cluster_by_org(org_ctx.organization_id)
-            .await?
-    };
+    // Get the addressed (or active) cluster with organization isolation
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
 
-    // Get MySQL client from pool
-    let pool = state.mysql_pool_manager.get_pool(&cluster).await?;
-    let mysql_client = MySQLClient::from_pool(pool);
+    // Execute SET and record the change with the previous value
+    let change = state
+        .variable_history_service
+        .update_variable(
+            &cluster,
+            &variable_name,
+            &request.scope,
+            &request.value,
+            org_ctx.user_id,
+            &org_ctx.username,
+        )
+        .await?;
 
-    // Validate scope
-    let scope = match request.scope.to_uppercase().as_str() {
-        "GLOBAL" => "GLOBAL",
-        "SESSION" => "SESSION",
-        _ => return Err(ApiError::invalid_data("Invalid scope. Must be GLOBAL or SESSION")),
-    };
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "message": "Variable updated successfully", "change": change })),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotListParams {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnapshotDiffParams {
+    pub from: i64,
+    pub to: i64,
+}
+
+/// Get variable change history
+#[utoipa::path(
+    get,
+    path = "/api/clusters/variables/history",
+    params(
+        ("variable" = Option<String>, Query, description = "Variable name"),
+        ("scope" = Option<String>, Query, description = "GLOBAL or SESSION"),
+        ("limit" = Option<i64>, Query, description = "Most recent changes returned (default 100)")
+    ),
+    responses(
+        (status = 200, description = "Changes made through starrocks-admin, newest first", body = Vec<VariableChange>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn get_variable_history(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(filter): Query<VariableChangeFilter>,
+) -> ApiResult<Json<Vec<VariableChange>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let changes = state
+        .variable_history_service
+        .list_changes(cluster.id, &filter)
+        .await?;
+    Ok(Json(changes))
+}
+
+/// Roll a GLOBAL variable back to the value before a change
+#[utoipa::path(
+    post,
+    path = "/api/clusters/variables/history/{change_id}/rollback",
+    params(
+        ("change_id" = i64, Path, description = "Change to roll back")
+    ),
+    responses(
+        (status = 200, description = "Variable rolled back; the rollback is recorded as a new change", body = VariableChange),
+        (status = 400, description = "SESSION change or unknown previous value"),
+        (status = 404, description = "Change not found")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn rollback_variable(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(change_id): Path<i64>,
+) -> ApiResult<Json<VariableChange>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let change = state
+        .variable_history_service
+        .rollback(&cluster, change_id, org_ctx.user_id, &org_ctx.username)
+        .await?;
+    Ok(Json(change))
+}
+
+/// List snapshots of the GLOBAL variables
+#[utoipa::path(
+    get,
+    path = "/api/clusters/variables/snapshots",
+    params(
+        ("limit" = Option<i64>, Query, description = "Most recent snapshots returned (default 100)")
+    ),
+    responses(
+        (status = 200, description = "Snapshots, newest first", body = Vec<VariableSnapshotSummary>),
+        (status = 404, description = "No active cluster found")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn list_variable_snapshots(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<SnapshotListParams>,
+) -> ApiResult<Json<Vec<VariableSnapshotSummary>>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let snapshots = state
+        .variable_history_service
+        .list_snapshots(cluster.id, params.limit)
+        .await?;
+    Ok(Json(snapshots))
+}
 
-    // Build SET command
-    let sql = format!("SET {} {} = {}", scope, variable_name, request.value);
+/// Snapshot the GLOBAL variables now
+#[utoipa::path(
+    post,
+    path = "/api/clusters/variables/snapshots",
+    responses(
+        (status = 201, description = "Snapshot created", body = VariableSnapshotSummary),
+        (status = 404, description = "No active cluster found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn create_variable_snapshot(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+) -> ApiResult<impl IntoResponse> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let snapshot = state
+        .variable_history_service
+        .take_snapshot(&cluster, true)
+        .await?
+        .ok_or_else(|| ApiError::internal_error("Snapshot was not stored"))?;
+    Ok((StatusCode::CREATED, Json(snapshot)))
+}
 
-    // Execute command
-    mysql_client.execute(&sql).await?;
+/// Get a snapshot with its variables
+#[utoipa::path(
+    get,
+    path = "/api/clusters/variables/snapshots/{snapshot_id}",
+    params(
+        ("snapshot_id" = i64, Path, description = "Snapshot ID")
+    ),
+    responses(
+        (status = 200, description = "Snapshot", body = VariableSnapshot),
+        (status = 404, description = "Snapshot not found")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn get_variable_snapshot(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Path(snapshot_id): Path<i64>,
+) -> ApiResult<Json<VariableSnapshot>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let snapshot = state
+        .variable_history_service
+        .get_snapshot(cluster.id, snapshot_id)
+        .await?;
+    Ok(Json(snapshot))
+}
 
-    Ok((StatusCode::OK, Json(json!({ "message": "Variable updated successfully" }))))
+/// Compare two snapshots
+#[utoipa::path(
+    get,
+    path = "/api/clusters/variables/snapshots/diff",
+    params(
+        ("from" = i64, Query, description = "Older snapshot ID"),
+        ("to" = i64, Query, description = "Newer snapshot ID")
+    ),
+    responses(
+        (status = 200, description = "Variables added, removed or changed", body = VariableSnapshotDiff),
+        (status = 404, description = "Snapshot not found")
+    ),
+    security(
+        ("bearer" = [])
+    )
+)]
+pub async fn diff_variable_snapshots(
+    State(state): State<Arc<crate::AppState>>,
+    axum::extract::Extension(org_ctx): axum::extract::Extension<crate::middleware::OrgContext>,
+    Query(params): Query<SnapshotDiffParams>,
+) -> ApiResult<Json<VariableSnapshotDiff>> {
+    let cluster = state.cluster_service.resolve_cluster(&org_ctx).await?;
+    let diff = state
+        .variable_history_service
+        .diff_snapshots(cluster.id, params.from, params.to)
+        .await?;
+    Ok(Json(diff))
 }
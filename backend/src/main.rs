@@ -27,12 +27,15 @@ mod tests;
 use config::Config;
 use embedded::WebAssets;
 use services::{
-    AuthService, CasbinService, ClusterService, DataStatisticsService, MetricsCollectorService,
-    MySQLPoolManager, OrganizationService, OverviewService, PermissionService, RoleService,
-    SystemFunctionService, UserRoleService, UserService,
+    AdminAuditService, AlertService, ApiTokenService, AuditSourceService, AuthService,
+    CasbinService, ClusterService, DataStatisticsService, LoginGuard, MetricsCollectorService,
+    MetricsExporterService, MvRefreshService, MySQLPoolManager, NodeMetricsService,
+    OrganizationService, OverviewService, PermissionService, ProfileArchiveService, RoleService,
+    RoutineLoadService, SessionService, SqlPolicyService, SsoService, SystemFunctionService,
+    TwoFactorService, UserRoleService, UserService, VariableHistoryService,
 };
 use sqlx::SqlitePool;
-use utils::{JwtUtil, ScheduledExecutor};
+use utils::{CredentialCipher, JwtUtil, ProcessMetrics, ScheduledExecutor};
 
 /// Application shared state
 ///
@@ -47,18 +50,33 @@ pub struct AppState {
     // Managers
     pub mysql_pool_manager: Arc<MySQLPoolManager>,
     pub jwt_util: Arc<JwtUtil>,
+    pub process_metrics: Arc<ProcessMetrics>,
 
     // Config
-    pub audit_config: config::AuditLogConfig,
+    pub metrics_exporter_token: Option<String>,
+    pub metrics_allow_unauthenticated_local: bool,
 
     // Services (grouped by domain)
     pub auth_service: Arc<AuthService>,
+    pub session_service: Arc<SessionService>,
+    pub sso_service: Arc<SsoService>,
+    pub login_guard: Arc<LoginGuard>,
+    pub two_factor_service: Arc<TwoFactorService>,
+    pub api_token_service: Arc<ApiTokenService>,
     pub cluster_service: Arc<ClusterService>,
     pub organization_service: Arc<OrganizationService>,
     pub system_function_service: Arc<SystemFunctionService>,
     pub metrics_collector_service: Arc<MetricsCollectorService>,
+    pub metrics_exporter_service: Arc<MetricsExporterService>,
     pub data_statistics_service: Arc<DataStatisticsService>,
     pub overview_service: Arc<OverviewService>,
+    pub node_metrics_service: Arc<NodeMetricsService>,
+    pub audit_source_service: Arc<AuditSourceService>,
+    pub profile_archive_service: Arc<ProfileArchiveService>,
+    pub alert_service: Arc<AlertService>,
+    pub routine_load_service: Arc<RoutineLoadService>,
+    pub mv_refresh_service: Arc<MvRefreshService>,
+    pub variable_history_service: Arc<VariableHistoryService>,
 
     // RBAC Services
     pub casbin_service: Arc<CasbinService>,
@@ -66,6 +84,8 @@ pub struct AppState {
     pub role_service: Arc<RoleService>,
     pub user_role_service: Arc<UserRoleService>,
     pub user_service: Arc<UserService>,
+    pub sql_policy_service: Arc<SqlPolicyService>,
+    pub admin_audit_service: Arc<AdminAuditService>,
 }
 
 #[derive(OpenApi)]
@@ -75,6 +95,28 @@ pub struct AppState {
         handlers::auth::login,
         handlers::auth::get_me,
         handlers::auth::update_me,
+        handlers::auth::refresh,
+        handlers::auth::logout,
+        handlers::auth::list_sessions,
+        handlers::auth::revoke_other_sessions,
+        handlers::auth::revoke_session,
+        handlers::auth::get_registration,
+        handlers::auth::update_registration,
+        handlers::auth::two_factor_status,
+        handlers::auth::setup_two_factor,
+        handlers::auth::enable_two_factor,
+        handlers::auth::disable_two_factor,
+        handlers::auth::regenerate_recovery_codes,
+        handlers::sso::providers,
+        handlers::sso::ldap_login,
+        handlers::sso::oidc_authorize,
+        handlers::sso::oidc_callback,
+        handlers::sso::list_mappings,
+        handlers::sso::create_mapping,
+        handlers::sso::delete_mapping,
+        handlers::api_token::list_tokens,
+        handlers::api_token::create_token,
+        handlers::api_token::revoke_token,
         handlers::cluster::create_cluster,
         handlers::cluster::list_clusters,
         handlers::cluster::get_active_cluster,
@@ -82,14 +124,23 @@ pub struct AppState {
         handlers::cluster::update_cluster,
         handlers::cluster::delete_cluster,
         handlers::cluster::activate_cluster,
+        handlers::cluster::set_default_cluster,
         handlers::organization::create_organization,
         handlers::organization::list_organizations,
         handlers::organization::get_organization,
         handlers::organization::update_organization,
         handlers::organization::delete_organization,
         handlers::cluster::get_cluster_health,
+        handlers::cluster::list_collection_status,
+        handlers::cluster::get_collection_status,
+        handlers::cluster::update_collection_settings,
+        handlers::cluster::get_audit_source,
+        handlers::cluster::update_audit_source,
+        handlers::cluster::reset_audit_source,
+        handlers::cluster::validate_audit_source,
         handlers::backend::list_backends,
         handlers::frontend::list_frontends,
+        handlers::frontend::list_frontend_endpoints,
         handlers::materialized_view::list_materialized_views,
         handlers::materialized_view::get_materialized_view,
         handlers::materialized_view::get_materialized_view_ddl,
@@ -98,6 +149,24 @@ pub struct AppState {
         handlers::materialized_view::refresh_materialized_view,
         handlers::materialized_view::cancel_refresh_materialized_view,
         handlers::materialized_view::alter_materialized_view,
+        handlers::materialized_view::get_dependency_graph,
+        handlers::materialized_view::get_staleness_report,
+        handlers::materialized_view::get_impact,
+        handlers::materialized_view::get_refresh_stats,
+        handlers::materialized_view::get_refresh_history,
+        handlers::materialized_view::get_refresh_trends,
+        handlers::load_job::list_load_jobs,
+        handlers::load_job::get_load_job,
+        handlers::load_job::cancel_load_job,
+        handlers::load_job::retry_load_job,
+        handlers::load_job::get_load_tracking_log,
+        handlers::routine_load::list_routine_loads,
+        handlers::routine_load::get_routine_load,
+        handlers::routine_load::list_routine_load_tasks,
+        handlers::routine_load::get_routine_load_lag,
+        handlers::routine_load::pause_routine_load,
+        handlers::routine_load::resume_routine_load,
+        handlers::routine_load::stop_routine_load,
         handlers::query::list_catalogs,
         handlers::query::list_databases,
         handlers::query::list_catalogs_with_databases,
@@ -109,9 +178,36 @@ pub struct AppState {
         handlers::sessions::kill_session,
         handlers::variables::get_variables,
         handlers::variables::update_variable,
+        handlers::variables::get_variable_history,
+        handlers::variables::rollback_variable,
+        handlers::variables::list_variable_snapshots,
+        handlers::variables::create_variable_snapshot,
+        handlers::variables::get_variable_snapshot,
+        handlers::variables::diff_variable_snapshots,
         handlers::profile::list_profiles,
         handlers::profile::get_profile,
         handlers::profile::analyze_profile_handler,
+        handlers::profile::archive_profile,
+        handlers::profile::list_profile_archives,
+        handlers::profile::get_profile_archive,
+        handlers::profile::analyze_profile_archive,
+        handlers::profile::delete_profile_archive,
+        handlers::profile::diff_profiles_handler,
+        handlers::alert::list_alert_metrics,
+        handlers::alert::list_alert_rules,
+        handlers::alert::create_alert_rule,
+        handlers::alert::get_alert_rule,
+        handlers::alert::update_alert_rule,
+        handlers::alert::delete_alert_rule,
+        handlers::alert::silence_alert_rule,
+        handlers::alert::unsilence_alert_rule,
+        handlers::alert::list_alert_channels,
+        handlers::alert::create_alert_channel,
+        handlers::alert::get_alert_channel,
+        handlers::alert::update_alert_channel,
+        handlers::alert::delete_alert_channel,
+        handlers::alert::test_alert_channel,
+        handlers::alert::list_alert_history,
         handlers::system_management::get_system_functions,
         handlers::system_management::get_system_function_detail,
         handlers::system::get_runtime_info,
@@ -122,6 +218,10 @@ pub struct AppState {
         handlers::overview::get_data_statistics,
         handlers::overview::get_capacity_prediction,
         handlers::overview::get_extended_cluster_overview,
+        handlers::overview::get_fleet_overview,
+        handlers::overview::list_node_metrics,
+        handlers::overview::get_node_metrics,
+        handlers::overview::get_node_metrics_trends,
         handlers::cluster::test_cluster_connection,
         // RBAC Handlers
         handlers::role::list_roles,
@@ -131,6 +231,11 @@ pub struct AppState {
         handlers::role::delete_role,
         handlers::role::get_role_with_permissions,
         handlers::role::update_role_permissions,
+        handlers::role::get_role_sql_policy,
+        handlers::role::update_role_sql_policy,
+        handlers::role::delete_role_sql_policy,
+        handlers::admin_audit::list_admin_audit_logs,
+        handlers::admin_audit::export_admin_audit_logs,
         handlers::permission::list_permissions,
         handlers::permission::list_menu_permissions,
         handlers::permission::list_api_permissions,
@@ -144,6 +249,18 @@ pub struct AppState {
         handlers::user::create_user,
         handlers::user::update_user,
         handlers::user::delete_user,
+        handlers::user::list_user_sessions,
+        handlers::user::revoke_user_sessions,
+        handlers::user::revoke_user_session,
+        handlers::user::reset_user_two_factor,
+        handlers::user::unlock_user,
+        handlers::service_account::list_service_accounts,
+        handlers::service_account::get_service_account,
+        handlers::service_account::create_service_account,
+        handlers::service_account::delete_service_account,
+        handlers::service_account::list_service_account_tokens,
+        handlers::service_account::create_service_account_token,
+        handlers::service_account::revoke_service_account_token,
     ),
     components(
         schemas(
@@ -155,8 +272,30 @@ pub struct AppState {
             models::LoginRequest,
             models::LoginResponse,
             models::AdminUpdateUserRequest,
+            models::RefreshTokenRequest,
+            models::TokenResponse,
+            models::UserSession,
+            models::RevokedSessions,
+            models::RegistrationSettings,
+            models::TwoFactorStatus,
+            models::TwoFactorSetup,
+            models::TwoFactorCodeRequest,
+            models::RecoveryCodes,
+            models::AuthProviders,
+            models::AuthProvider,
+            models::SsoGroupMapping,
+            models::CreateSsoGroupMappingRequest,
+            models::ApiToken,
+            models::CreateApiTokenRequest,
+            models::CreatedApiToken,
+            models::ServiceAccount,
+            models::CreateServiceAccountRequest,
             models::Cluster,
             models::ClusterResponse,
+            models::ClusterTlsRequest,
+            models::FeEndpoint,
+            models::FeEndpointSource,
+            models::FeEndpointStatus,
             models::CreateClusterRequest,
             models::UpdateClusterRequest,
             models::ClusterHealth,
@@ -169,6 +308,39 @@ pub struct AppState {
             models::RefreshMaterializedViewRequest,
             models::AlterMaterializedViewRequest,
             models::MaterializedViewDDL,
+            models::MvDependencyNodeType,
+            models::MvDependencyNode,
+            models::MvDependencyEdge,
+            models::MvDependencyGraph,
+            models::MvStalenessIssue,
+            models::MvStaleness,
+            models::MvImpactedView,
+            models::MvImpact,
+            models::MvRefreshRun,
+            models::MvRefreshStats,
+            models::MvRefreshErrorGroup,
+            models::MvRefreshDetail,
+            models::MvRefreshDurationPoint,
+            models::MvRefreshFailureBucket,
+            models::MvRefreshTrendInterval,
+            models::MvRefreshTrends,
+            models::LoadJob,
+            models::LoadJobDetail,
+            models::LoadTrackingLog,
+            models::RoutineLoadJob,
+            models::RoutineLoadPartition,
+            models::RoutineLoadTask,
+            models::RoutineLoadAction,
+            services::RoutineLoadLagTrends,
+            services::RoutineLoadPartitionLag,
+            services::RoutineLoadStateChange,
+            models::Variable,
+            models::VariableChange,
+            models::VariableSnapshotSummary,
+            models::VariableSnapshot,
+            models::VariableDiffKind,
+            models::VariableDiffEntry,
+            models::VariableSnapshotDiff,
             models::Query,
             models::QueryExecuteRequest,
             models::QueryExecuteResponse,
@@ -178,6 +350,27 @@ pub struct AppState {
             models::QueryHistoryResponse,
             models::ProfileListItem,
             models::ProfileDetail,
+            models::ProfileDiffSource,
+            models::ProfileDiffRequest,
+            models::ProfileArchiveItem,
+            models::ProfileArchiveDetail,
+            models::ProfileArchiveListResponse,
+            models::AlertComparator,
+            models::AlertSeverity,
+            models::AlertState,
+            models::AlertStatus,
+            models::AlertChannelType,
+            models::AlertRule,
+            models::CreateAlertRuleRequest,
+            models::UpdateAlertRuleRequest,
+            models::SilenceAlertRuleRequest,
+            models::AlertMetricInfo,
+            models::AlertChannel,
+            models::CreateAlertChannelRequest,
+            models::UpdateAlertChannelRequest,
+            models::AlertHistoryItem,
+            models::AlertHistoryListResponse,
+            models::AlertEvent,
             models::RuntimeInfo,
             models::MetricsSummary,
             models::SystemFunction,
@@ -193,9 +386,23 @@ pub struct AppState {
             models::PermissionResponse,
             models::PermissionTree,
             models::UpdateRolePermissionsRequest,
+            models::PermissionScopeLevel,
+            models::PermissionScope,
+            models::ScopedPermissionGrant,
+            models::ScopedPermissionResponse,
+            models::SqlStatementClass,
+            models::RoleSqlPolicy,
+            models::UpdateRoleSqlPolicyRequest,
+            models::SqlPolicyRule,
+            models::EffectiveSqlPolicy,
+            models::AdminAuditLog,
+            models::AdminAuditListResponse,
             models::AssignUserRoleRequest,
             services::ClusterOverview,
             services::ExtendedClusterOverview,
+            services::FleetOverview,
+            services::FleetClusterSummary,
+            services::FleetClusterStatus,
             services::HealthCard,
             services::HealthStatus,
             services::ClusterHealth,
@@ -219,6 +426,20 @@ pub struct AppState {
             services::PerformanceTrends,
             services::ResourceTrends,
             services::MetricsSnapshot,
+            services::CollectionState,
+            services::ClusterCollectionStatus,
+            services::UpdateCollectionSettingsRequest,
+            services::AuditColumn,
+            services::AuditSource,
+            services::AuditSourceSettings,
+            services::AuditSourceValidation,
+            services::ClusterAuditSource,
+            services::NodeType,
+            services::NodeDiskPath,
+            services::NodeMetricsSnapshot,
+            services::NodeMetricsSummary,
+            services::NodeDiskPathTrend,
+            services::NodeMetricsTrends,
             services::DataStatistics,
             services::TopTableBySize,
             services::TopTableByAccess,
@@ -231,12 +452,18 @@ pub struct AppState {
         (name = "Backends", description = "Backend node management"),
         (name = "Frontends", description = "Frontend node management"),
         (name = "Materialized Views", description = "Materialized view management"),
+        (name = "Load Jobs", description = "Load job center"),
+        (name = "Routine Load", description = "Routine Load job management and lag monitoring"),
         (name = "Queries", description = "Query management"),
         (name = "Profiles", description = "Query profile management"),
+        (name = "Alerts", description = "Alert rules and notification channels"),
+        (name = "Admin Audit", description = "Audit trail of admin actions"),
         (name = "System", description = "System information"),
         (name = "Roles", description = "Role management"),
         (name = "Permissions", description = "Permission management"),
         (name = "Users", description = "User role management"),
+        (name = "Service Accounts", description = "Service accounts and their API tokens"),
+        (name = "Single Sign-On", description = "Group mappings of LDAP and OIDC users"),
     ),
     modifiers(&SecurityAddon)
 )]
@@ -300,13 +527,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize core components
     let jwt_util = Arc::new(JwtUtil::new(&config.auth.jwt_secret, &config.auth.jwt_expires_in));
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let credential_cipher = Arc::new(
+        CredentialCipher::from_config(&config.security)
+            .map_err(|e| format!("Failed to load master key: {}", e))?,
+    );
+    tracing::info!("Credential encryption enabled (master key {})", credential_cipher.key_id());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::new(Arc::clone(&credential_cipher)));
 
-    let auth_service = Arc::new(AuthService::new(pool.clone(), Arc::clone(&jwt_util)));
+    let session_service = Arc::new(SessionService::new(
+        pool.clone(),
+        Arc::clone(&jwt_util),
+        &config.auth.refresh_expires_in,
+    ));
+    let two_factor_service =
+        Arc::new(TwoFactorService::new(pool.clone(), Arc::clone(&credential_cipher)));
+    let auth_service = Arc::new(
+        AuthService::new(
+            pool.clone(),
+            Arc::clone(&session_service),
+            Arc::clone(&two_factor_service),
+        )
+        .with_password_policy(config.auth.password_policy.clone()),
+    );
+    let login_guard = Arc::new(LoginGuard::new(pool.clone(), config.auth.lockout.clone()));
+    let api_token_service = Arc::new(ApiTokenService::new(pool.clone()));
 
     let cluster_service =
         Arc::new(ClusterService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
 
+    // Encrypt any credentials left in plaintext by earlier versions
+    cluster_service.encrypt_plaintext_credentials().await?;
+
+    // One-shot key rotation: re-encrypt everything with the new key and exit
+    if let Some(new_key_file) = &config.security.rotate_master_key_to {
+        let new_cipher = CredentialCipher::from_key_file(new_key_file, true)?;
+        let rotated = cluster_service.rotate_credentials(&new_cipher).await?;
+        tracing::info!(
+            "Master key rotation complete: {} credential(s) re-encrypted. \
+             Point security.master_key_file at {} (or set APP_MASTER_KEY) before restarting.",
+            rotated,
+            new_key_file
+        );
+        return Ok(());
+    }
+
     let organization_service = Arc::new(OrganizationService::new(pool.clone()));
 
     let system_function_service = Arc::new(SystemFunctionService::new(
@@ -315,19 +579,60 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Arc::clone(&cluster_service),
     ));
 
+    let alert_service = Arc::new(AlertService::new(
+        pool.clone(),
+        Arc::clone(&mysql_pool_manager),
+        config.alert.clone(),
+    ));
+
     // Create new services for cluster overview
-    let metrics_collector_service = Arc::new(MetricsCollectorService::new(
+    let process_metrics = Arc::new(ProcessMetrics::new());
+
+    let node_metrics_service = Arc::new(NodeMetricsService::new(pool.clone()));
+
+    let routine_load_service =
+        Arc::new(RoutineLoadService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+
+    let mv_refresh_service =
+        Arc::new(MvRefreshService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+
+    let variable_history_service =
+        Arc::new(VariableHistoryService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+
+    let audit_source_service = Arc::new(AuditSourceService::new(
         pool.clone(),
+        Arc::clone(&mysql_pool_manager),
+        config.audit.clone(),
+    ));
+
+    let metrics_collector_service = Arc::new(
+        MetricsCollectorService::new(
+            pool.clone(),
+            Arc::clone(&cluster_service),
+            Arc::clone(&mysql_pool_manager),
+            Arc::clone(&alert_service),
+            Arc::clone(&process_metrics),
+            Arc::clone(&node_metrics_service),
+            config.metrics.clone(),
+        )
+        .with_audit_source(Arc::clone(&audit_source_service))
+        .with_routine_load(Arc::clone(&routine_load_service))
+        .with_mv_refresh(Arc::clone(&mv_refresh_service))
+        .with_variable_history(Arc::clone(&variable_history_service)),
+    );
+
+    let metrics_exporter_service = Arc::new(MetricsExporterService::new(
         Arc::clone(&cluster_service),
+        Arc::clone(&metrics_collector_service),
         Arc::clone(&mysql_pool_manager),
-        config.metrics.retention_days,
+        Arc::clone(&process_metrics),
     ));
 
     let data_statistics_service = Arc::new(DataStatisticsService::new(
         pool.clone(),
         Arc::clone(&cluster_service),
         Arc::clone(&mysql_pool_manager),
-        config.audit.clone(),
+        Arc::clone(&audit_source_service),
     ));
 
     let overview_service = Arc::new(
@@ -335,10 +640,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             pool.clone(),
             Arc::clone(&cluster_service),
             Arc::clone(&mysql_pool_manager),
+            Arc::clone(&audit_source_service),
         )
-        .with_data_statistics(Arc::clone(&data_statistics_service)),
+        .with_data_statistics(Arc::clone(&data_statistics_service))
+        .with_alert_service(Arc::clone(&alert_service)),
     );
 
+    let profile_archive_service = Arc::new(ProfileArchiveService::new(
+        pool.clone(),
+        Arc::clone(&cluster_service),
+        Arc::clone(&mysql_pool_manager),
+        config.profile_archive.clone(),
+    ));
+
     // Initialize RBAC services
     let casbin_service = Arc::new(
         CasbinService::new()
@@ -365,37 +679,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let user_role_service =
         Arc::new(UserRoleService::new(pool.clone(), Arc::clone(&casbin_service)));
 
-    let user_service = Arc::new(UserService::new(pool.clone(), Arc::clone(&casbin_service)));
+    let user_service = Arc::new(
+        UserService::new(pool.clone(), Arc::clone(&casbin_service))
+            .with_password_policy(config.auth.password_policy.clone()),
+    );
+
+    let sql_policy_service = Arc::new(SqlPolicyService::new(pool.clone()));
+
+    let sso_service = Arc::new(SsoService::new(
+        pool.clone(),
+        &config.sso,
+        Arc::clone(&casbin_service),
+        Arc::clone(&session_service),
+    ));
+    let providers = sso_service.providers();
+    tracing::info!("Single sign-on: ldap={}, oidc={}", providers.ldap, providers.oidc);
+
+    let admin_audit_service = Arc::new(AdminAuditService::new(pool.clone()));
 
     // Build AppState with all services
     let app_state = AppState {
         db: pool.clone(),
         mysql_pool_manager: Arc::clone(&mysql_pool_manager),
         jwt_util: Arc::clone(&jwt_util),
-        audit_config: config.audit.clone(),
+        process_metrics: Arc::clone(&process_metrics),
+        metrics_exporter_token: config.metrics.exporter_token.clone(),
+        metrics_allow_unauthenticated_local: config.metrics.allow_unauthenticated_local,
         auth_service: Arc::clone(&auth_service),
+        session_service: Arc::clone(&session_service),
+        sso_service: Arc::clone(&sso_service),
+        login_guard: Arc::clone(&login_guard),
+        two_factor_service: Arc::clone(&two_factor_service),
+        api_token_service: Arc::clone(&api_token_service),
         cluster_service: Arc::clone(&cluster_service),
         organization_service: Arc::clone(&organization_service),
         system_function_service: Arc::clone(&system_function_service),
         metrics_collector_service: Arc::clone(&metrics_collector_service),
+        metrics_exporter_service: Arc::clone(&metrics_exporter_service),
         data_statistics_service: Arc::clone(&data_statistics_service),
         overview_service: Arc::clone(&overview_service),
+        node_metrics_service: Arc::clone(&node_metrics_service),
+        audit_source_service: Arc::clone(&audit_source_service),
+        profile_archive_service: Arc::clone(&profile_archive_service),
+        alert_service: Arc::clone(&alert_service),
+        routine_load_service: Arc::clone(&routine_load_service),
+        mv_refresh_service: Arc::clone(&mv_refresh_service),
+        variable_history_service: Arc::clone(&variable_history_service),
         casbin_service: Arc::clone(&casbin_service),
         permission_service: Arc::clone(&permission_service),
         role_service: Arc::clone(&role_service),
         user_role_service: Arc::clone(&user_role_service),
         user_service: Arc::clone(&user_service),
+        sql_policy_service: Arc::clone(&sql_policy_service),
+        admin_audit_service: Arc::clone(&admin_audit_service),
     };
 
     // Start metrics collector using ScheduledExecutor (configurable interval)
     if config.metrics.enabled {
-        let interval = std::time::Duration::from_secs(config.metrics.interval_secs);
         tracing::info!(
-            "Starting metrics collector with interval: {}s (retention_days={})",
+            "Starting metrics collector with interval: {}s (retention_days={}, max_concurrency={})",
             config.metrics.interval_secs,
-            config.metrics.retention_days
+            config.metrics.retention_days,
+            config.metrics.max_concurrency
         );
-        let executor = ScheduledExecutor::new("metrics-collector", interval);
+        let executor =
+            ScheduledExecutor::new("metrics-collector", metrics_collector_service.tick_interval());
         let service = Arc::clone(&metrics_collector_service);
         tokio::spawn(async move {
             executor.start(service).await;
@@ -404,6 +752,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tracing::warn!("Metrics collector disabled by configuration");
     }
 
+    // Start profile archiver (auto-archive + retention cleanup)
+    {
+        let interval = std::time::Duration::from_secs(config.profile_archive.interval_secs);
+        tracing::info!(
+            "Starting profile archiver with interval: {}s (auto_archive={}, retention_days={})",
+            config.profile_archive.interval_secs,
+            config.profile_archive.auto_archive,
+            config.profile_archive.retention_days
+        );
+        let executor = ScheduledExecutor::new("profile-archiver", interval);
+        let service = Arc::clone(&profile_archive_service);
+        tokio::spawn(async move {
+            executor.start(service).await;
+        });
+    }
+
     // Wrap AppState in Arc for shared ownership across routes
     let app_state_arc = Arc::new(app_state);
 
@@ -411,6 +775,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let auth_state = middleware::AuthState {
         jwt_util: Arc::clone(&jwt_util),
         casbin_service: Arc::clone(&casbin_service),
+        api_token_service: Arc::clone(&api_token_service),
+        session_service: Arc::clone(&session_service),
         db: pool.clone(),
     };
 
@@ -418,6 +784,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let public_routes = Router::new()
         .route("/api/auth/register", post(handlers::auth::register))
         .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .route("/api/auth/registration", get(handlers::auth::get_registration))
+        .route("/api/auth/providers", get(handlers::sso::providers))
+        .route("/api/auth/ldap/login", post(handlers::sso::ldap_login))
+        .route("/api/auth/oidc/authorize", get(handlers::sso::oidc_authorize))
+        .route("/api/auth/oidc/callback", get(handlers::sso::oidc_callback))
         .with_state(Arc::clone(&app_state_arc));
 
     // Protected routes (require authentication)
@@ -425,16 +797,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Auth
         .route("/api/auth/me", get(handlers::auth::get_me))
         .route("/api/auth/me", put(handlers::auth::update_me))
+        .route("/api/auth/logout", post(handlers::auth::logout))
+        .route(
+            "/api/auth/sessions",
+            get(handlers::auth::list_sessions).delete(handlers::auth::revoke_other_sessions),
+        )
+        .route("/api/auth/sessions/:id", delete(handlers::auth::revoke_session))
+        .route("/api/auth/registration", put(handlers::auth::update_registration))
+        .route("/api/auth/2fa", get(handlers::auth::two_factor_status))
+        .route("/api/auth/2fa/setup", post(handlers::auth::setup_two_factor))
+        .route("/api/auth/2fa/enable", post(handlers::auth::enable_two_factor))
+        .route("/api/auth/2fa/disable", post(handlers::auth::disable_two_factor))
+        .route("/api/auth/2fa/recovery-codes", post(handlers::auth::regenerate_recovery_codes))
+        .route(
+            "/api/auth/tokens",
+            get(handlers::api_token::list_tokens).post(handlers::api_token::create_token),
+        )
+        .route("/api/auth/tokens/:id", delete(handlers::api_token::revoke_token))
         // Clusters
         .route("/api/clusters", post(handlers::cluster::create_cluster))
         .route("/api/clusters", get(handlers::cluster::list_clusters))
         .route("/api/clusters/active", get(handlers::cluster::get_active_cluster))
+        .route("/api/clusters/collection", get(handlers::cluster::list_collection_status))
         .route("/api/clusters/health/test", post(handlers::cluster::test_cluster_connection))
         // Backends
         .route("/api/clusters/backends", get(handlers::backend::list_backends))
         .route("/api/clusters/backends/:host/:port", delete(handlers::backend::delete_backend))
         // Frontends
         .route("/api/clusters/frontends", get(handlers::frontend::list_frontends))
+        .route(
+            "/api/clusters/frontends/endpoints",
+            get(handlers::frontend::list_frontend_endpoints),
+        )
         // Queries
         .route("/api/clusters/catalogs", get(handlers::query::list_catalogs))
         .route("/api/clusters/databases", get(handlers::query::list_databases))
@@ -452,10 +846,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/clusters/:id", put(handlers::cluster::update_cluster))
         .route("/api/clusters/:id", delete(handlers::cluster::delete_cluster))
         .route("/api/clusters/:id/activate", put(handlers::cluster::activate_cluster))
+        .route("/api/clusters/:id/default", put(handlers::cluster::set_default_cluster))
         .route(
             "/api/clusters/:id/health",
             get(handlers::cluster::get_cluster_health).post(handlers::cluster::get_cluster_health),
         )
+        .route(
+            "/api/clusters/:id/collection",
+            get(handlers::cluster::get_collection_status)
+                .put(handlers::cluster::update_collection_settings),
+        )
+        .route(
+            "/api/clusters/:id/audit-source",
+            get(handlers::cluster::get_audit_source)
+                .put(handlers::cluster::update_audit_source)
+                .delete(handlers::cluster::reset_audit_source),
+        )
+        .route(
+            "/api/clusters/:id/audit-source/validate",
+            post(handlers::cluster::validate_audit_source),
+        )
         // Organizations
         .route(
             "/api/organizations",
@@ -474,6 +884,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(handlers::materialized_view::list_materialized_views)
                 .post(handlers::materialized_view::create_materialized_view),
         )
+        .route(
+            "/api/clusters/materialized_views/dependencies",
+            get(handlers::materialized_view::get_dependency_graph),
+        )
+        .route(
+            "/api/clusters/materialized_views/staleness",
+            get(handlers::materialized_view::get_staleness_report),
+        )
+        .route(
+            "/api/clusters/materialized_views/impact",
+            get(handlers::materialized_view::get_impact),
+        )
+        .route(
+            "/api/clusters/materialized_views/refresh-stats",
+            get(handlers::materialized_view::get_refresh_stats),
+        )
         .route(
             "/api/clusters/materialized_views/:mv_name",
             get(handlers::materialized_view::get_materialized_view)
@@ -492,19 +918,120 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/clusters/materialized_views/:mv_name/cancel",
             post(handlers::materialized_view::cancel_refresh_materialized_view),
         )
+        .route(
+            "/api/clusters/materialized_views/:mv_name/refresh-history",
+            get(handlers::materialized_view::get_refresh_history),
+        )
+        .route(
+            "/api/clusters/materialized_views/:mv_name/refresh-trends",
+            get(handlers::materialized_view::get_refresh_trends),
+        )
+        // Load Jobs
+        .route("/api/clusters/loads", get(handlers::load_job::list_load_jobs))
+        .route("/api/clusters/loads/:job_id", get(handlers::load_job::get_load_job))
+        .route("/api/clusters/loads/:job_id/cancel", post(handlers::load_job::cancel_load_job))
+        .route("/api/clusters/loads/:job_id/retry", post(handlers::load_job::retry_load_job))
+        .route(
+            "/api/clusters/loads/:job_id/tracking-log",
+            get(handlers::load_job::get_load_tracking_log),
+        )
+        // Routine Load
+        .route("/api/clusters/routine-loads", get(handlers::routine_load::list_routine_loads))
+        .route(
+            "/api/clusters/routine-loads/:database/:name",
+            get(handlers::routine_load::get_routine_load),
+        )
+        .route(
+            "/api/clusters/routine-loads/:database/:name/tasks",
+            get(handlers::routine_load::list_routine_load_tasks),
+        )
+        .route(
+            "/api/clusters/routine-loads/:database/:name/lag",
+            get(handlers::routine_load::get_routine_load_lag),
+        )
+        .route(
+            "/api/clusters/routine-loads/:database/:name/pause",
+            post(handlers::routine_load::pause_routine_load),
+        )
+        .route(
+            "/api/clusters/routine-loads/:database/:name/resume",
+            post(handlers::routine_load::resume_routine_load),
+        )
+        .route(
+            "/api/clusters/routine-loads/:database/:name/stop",
+            post(handlers::routine_load::stop_routine_load),
+        )
         // Profiles
         .route("/api/clusters/profiles", get(handlers::profile::list_profiles))
+        .route("/api/clusters/profiles/diff", post(handlers::profile::diff_profiles_handler))
         .route("/api/clusters/profiles/:query_id", get(handlers::profile::get_profile))
         .route(
             "/api/clusters/profiles/:query_id/analyze",
             get(handlers::profile::analyze_profile_handler),
         )
+        .route("/api/clusters/profiles/:query_id/archive", post(handlers::profile::archive_profile))
+        .route("/api/clusters/profile-archives", get(handlers::profile::list_profile_archives))
+        .route(
+            "/api/clusters/profile-archives/:id",
+            get(handlers::profile::get_profile_archive)
+                .delete(handlers::profile::delete_profile_archive),
+        )
+        .route(
+            "/api/clusters/profile-archives/:id/analyze",
+            post(handlers::profile::analyze_profile_archive),
+        )
+        // Alerts
+        .route("/api/clusters/alerts/metrics", get(handlers::alert::list_alert_metrics))
+        .route(
+            "/api/clusters/alerts/rules",
+            get(handlers::alert::list_alert_rules).post(handlers::alert::create_alert_rule),
+        )
+        .route(
+            "/api/clusters/alerts/rules/:id",
+            get(handlers::alert::get_alert_rule)
+                .put(handlers::alert::update_alert_rule)
+                .delete(handlers::alert::delete_alert_rule),
+        )
+        .route(
+            "/api/clusters/alerts/rules/:id/silence",
+            post(handlers::alert::silence_alert_rule).delete(handlers::alert::unsilence_alert_rule),
+        )
+        .route(
+            "/api/clusters/alerts/channels",
+            get(handlers::alert::list_alert_channels).post(handlers::alert::create_alert_channel),
+        )
+        .route(
+            "/api/clusters/alerts/channels/:id",
+            get(handlers::alert::get_alert_channel)
+                .put(handlers::alert::update_alert_channel)
+                .delete(handlers::alert::delete_alert_channel),
+        )
+        .route("/api/clusters/alerts/channels/:id/test", post(handlers::alert::test_alert_channel))
+        .route("/api/clusters/alerts/history", get(handlers::alert::list_alert_history))
         // Sessions
         .route("/api/clusters/sessions", get(handlers::sessions::get_sessions))
         .route("/api/clusters/sessions/:session_id", delete(handlers::sessions::kill_session))
         // Variables
         .route("/api/clusters/variables", get(handlers::variables::get_variables))
         .route("/api/clusters/variables/:variable_name", put(handlers::variables::update_variable))
+        .route("/api/clusters/variables/history", get(handlers::variables::get_variable_history))
+        .route(
+            "/api/clusters/variables/history/:change_id/rollback",
+            post(handlers::variables::rollback_variable),
+        )
+        .route(
+            "/api/clusters/variables/snapshots",
+            get(handlers::variables::list_variable_snapshots)
+                .post(handlers::variables::create_variable_snapshot),
+        )
+        .route(
+            "/api/clusters/variables/snapshots/diff",
+            get(handlers::variables::diff_variable_snapshots),
+        )
+        .route(
+            "/api/clusters/variables/snapshots/:snapshot_id",
+            get(handlers::variables::get_variable_snapshot),
+        )
         // System
         .route("/api/clusters/system/runtime_info", get(handlers::system::get_runtime_info))
         .route("/api/clusters/system", get(handlers::system_management::get_system_functions))
@@ -544,6 +1071,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             delete(handlers::system_function::delete_category),
         )
         // Overview
+        .route("/api/clusters/fleet", get(handlers::overview::get_fleet_overview))
         .route("/api/clusters/overview", get(handlers::overview::get_cluster_overview))
         .route(
             "/api/clusters/overview/extended",
@@ -564,6 +1092,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/clusters/overview/compaction-details",
             get(handlers::overview::get_compaction_detail_stats),
         )
+        .route("/api/clusters/overview/nodes", get(handlers::overview::list_node_metrics))
+        .route(
+            "/api/clusters/overview/nodes/:node_type/:node_id",
+            get(handlers::overview::get_node_metrics),
+        )
+        .route(
+            "/api/clusters/overview/nodes/:node_type/:node_id/trends",
+            get(handlers::overview::get_node_metrics_trends),
+        )
         // RBAC Routes
         // Roles
         .route("/api/roles", get(handlers::role::list_roles).post(handlers::role::create_role))
@@ -578,6 +1115,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(handlers::role::get_role_with_permissions)
                 .put(handlers::role::update_role_permissions),
         )
+        .route(
+            "/api/roles/:id/sql-policy",
+            get(handlers::role::get_role_sql_policy)
+                .put(handlers::role::update_role_sql_policy)
+                .delete(handlers::role::delete_role_sql_policy),
+        )
         // Permissions
         .route("/api/permissions", get(handlers::permission::list_permissions))
         .route("/api/permissions/menu", get(handlers::permission::list_menu_permissions))
@@ -598,6 +1141,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             get(handlers::user_role::get_user_roles).post(handlers::user_role::assign_role_to_user),
         )
         .route("/api/users/:id/roles/:role_id", delete(handlers::user_role::remove_role_from_user))
+        // User Sessions
+        .route(
+            "/api/users/:id/sessions",
+            get(handlers::user::list_user_sessions).delete(handlers::user::revoke_user_sessions),
+        )
+        .route("/api/users/:id/sessions/:session_id", delete(handlers::user::revoke_user_session))
+        .route("/api/users/:id/two-factor", delete(handlers::user::reset_user_two_factor))
+        .route("/api/users/:id/lockout", delete(handlers::user::unlock_user))
+        // Service Accounts
+        .route(
+            "/api/service-accounts",
+            get(handlers::service_account::list_service_accounts)
+                .post(handlers::service_account::create_service_account),
+        )
+        .route(
+            "/api/service-accounts/:id",
+            get(handlers::service_account::get_service_account)
+                .delete(handlers::service_account::delete_service_account),
+        )
+        .route(
+            "/api/service-accounts/:id/tokens",
+            get(handlers::service_account::list_service_account_tokens)
+                .post(handlers::service_account::create_service_account_token),
+        )
+        .route(
+            "/api/service-accounts/:id/tokens/:token_id",
+            delete(handlers::service_account::revoke_service_account_token),
+        )
+        // Single Sign-On
+        .route(
+            "/api/sso/mappings",
+            get(handlers::sso::list_mappings).post(handlers::sso::create_mapping),
+        )
+        .route("/api/sso/mappings/:id", delete(handlers::sso::delete_mapping))
+        // Admin Audit
+        .route("/api/audit-logs", get(handlers::admin_audit::list_admin_audit_logs))
+        .route("/api/audit-logs/export", get(handlers::admin_audit::export_admin_audit_logs))
+        // Route layer so it runs after authentication and sees the matched route
+        .route_layer(axum_middleware::from_fn_with_state(
+            Arc::clone(&app_state_arc),
+            middleware::admin_audit_middleware,
+        ))
         .with_state(Arc::clone(&app_state_arc))
         .layer(axum_middleware::from_fn_with_state(auth_state, middleware::auth_middleware));
 
@@ -605,6 +1190,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/health", get(health_check))
         .route("/ready", get(ready_check));
 
+    // Prometheus scrape endpoint (optional bearer token instead of JWT)
+    let metrics_routes = Router::new()
+        .route("/metrics", get(handlers::metrics::prometheus_metrics))
+        .with_state(Arc::clone(&app_state_arc));
+
     // Static file serving from embedded assets
     let static_routes = if config.static_config.enabled {
         tracing::info!("Static file serving enabled, serving from embedded assets");
@@ -619,12 +1209,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .merge(public_routes)
         .merge(protected_routes)
         .merge(health_routes)
+        .merge(metrics_routes)
         .merge(static_routes); // Must be last to serve as fallback for SPA routes
 
     let app = app
+        .layer(axum_middleware::from_fn_with_state(
+            Arc::clone(&process_metrics),
+            middleware::http_metrics_middleware,
+        ))
         .layer(tower_http::trace::TraceLayer::new_for_http())
         .layer(tower_http::cors::CorsLayer::permissive());
 
+    // Cluster-scoped routes (/api/clusters/:id/...) are rewritten before routing
+    let app =
+        tower::Layer::layer(&axum_middleware::from_fn(middleware::cluster_scope_middleware), app);
+
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
@@ -632,7 +1231,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("API documentation available at http://{}/api-docs", addr);
     tracing::info!("StarRocks Admin is ready to serve requests");
 
-    axum::serve(listener, app).await?;
+    // Peer addresses let GET /metrics serve local scrapers without a token
+    axum::serve(
+        listener,
+        axum::ServiceExt::<axum::extract::Request>::into_make_service_with_connect_info::<
+            std::net::SocketAddr,
+        >(app),
+    )
+    .await?;
 
     Ok(())
 }
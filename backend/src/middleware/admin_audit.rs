@@ -0,0 +1,177 @@
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, MatchedPath, OriginalUri, RawPathParams, Request, State},
+    http::{Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::AppState;
+use crate::middleware::{OrgContext, permission_extractor};
+use crate::models::NewAdminAuditLog;
+use crate::services::admin_audit_service;
+use crate::utils::ApiError;
+
+/// Largest request body read for the audit log (same as axum's default body limit)
+const MAX_REQUEST_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Largest error response body read for the error message
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Admin audit middleware.
+/// Runs after `auth_middleware` as a route layer and records every state-changing request
+/// (anything but GET/HEAD/OPTIONS) with its user, organization, cluster, redacted payload,
+/// result and duration. Recording failures are logged and never fail the request.
+pub async fn admin_audit_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+    let Some(org_ctx) = req.extensions().get::<OrgContext>().cloned() else {
+        return next.run(req).await;
+    };
+
+    let (mut parts, body) = req.into_parts();
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    // Cluster-scoped requests are recorded with the path the client sent
+    let recorded_path = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|original| original.path().to_string())
+        .unwrap_or_else(|| path.clone());
+    let route = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+    let target = RawPathParams::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .map(|params| {
+            params
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|target| !target.is_empty());
+    let client_ip = client_ip(&parts.headers);
+
+    let content_length = parts
+        .headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    let (body, payload) = match content_length {
+        Some(length) if length > MAX_REQUEST_BODY_BYTES => {
+            (body, Some(format!("<{} bytes, not recorded>", length)))
+        },
+        _ => match axum::body::to_bytes(body, MAX_REQUEST_BODY_BYTES).await {
+            Ok(bytes) => {
+                let payload = admin_audit_service::redact_payload(&bytes);
+                (Body::from(bytes), payload)
+            },
+            Err(_) => {
+                return ApiError::validation_error("Request body too large").into_response();
+            },
+        },
+    };
+
+    // Resolve the cluster before the handler runs (activation/deletion changes it)
+    let (cluster_id, cluster_name) = resolve_cluster(&state, &org_ctx, &path).await;
+
+    let action = permission_extractor::extract_permission(&method, &path)
+        .map(|(resource, action)| format!("{}:{}", resource, action))
+        .unwrap_or_else(|| format!("{} {}", method, route));
+
+    let started = Instant::now();
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let status = response.status();
+    let (response, error_message) = if status.is_client_error() || status.is_server_error() {
+        let (parts, body) = response.into_parts();
+        match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+            Ok(bytes) => {
+                let message = serde_json::from_slice::<serde_json::Value>(&bytes)
+                    .ok()
+                    .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+                    .or_else(|| status.canonical_reason().map(str::to_string));
+                (Response::from_parts(parts, Body::from(bytes)), message)
+            },
+            Err(_) => (
+                Response::from_parts(parts, Body::empty()),
+                status.canonical_reason().map(str::to_string),
+            ),
+        }
+    } else {
+        (response, None)
+    };
+
+    let entry = NewAdminAuditLog {
+        user_id: Some(org_ctx.user_id),
+        username: org_ctx.username,
+        organization_id: org_ctx.organization_id,
+        cluster_id,
+        cluster_name,
+        action,
+        method,
+        path: recorded_path,
+        target,
+        payload,
+        status_code: status.as_u16() as i64,
+        success: !(status.is_client_error() || status.is_server_error()),
+        error_message,
+        duration_ms,
+        client_ip,
+    };
+    if let Err(err) = state.admin_audit_service.record(entry).await {
+        tracing::warn!("Failed to record admin audit log: {}", err);
+    }
+
+    response
+}
+
+/// Cluster an admin request applies to: the cluster in the path for `/api/clusters/:id` and
+/// cluster-scoped routes, the caller's active cluster for other cluster routes, none otherwise
+async fn resolve_cluster(
+    state: &AppState,
+    org_ctx: &OrgContext,
+    path: &str,
+) -> (Option<i64>, Option<String>) {
+    let Some(rest) = path.strip_prefix("/api/clusters/") else {
+        return (None, None);
+    };
+
+    let first = rest.split('/').next().unwrap_or_default();
+    let cluster = if let Ok(cluster_id) = first.parse::<i64>() {
+        state.cluster_service.get_cluster(cluster_id).await
+    } else {
+        state.cluster_service.resolve_cluster(org_ctx).await
+    };
+
+    match cluster {
+        Ok(cluster) => (Some(cluster.id), Some(cluster.name)),
+        Err(_) => (None, None),
+    }
+}
+
+pub(crate) fn client_ip(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
@@ -6,15 +6,19 @@ use axum::{
 };
 use std::sync::Arc;
 
-use crate::middleware::permission_extractor;
+use crate::middleware::{ClusterScope, permission_extractor};
+use crate::models::{PermissionScope, PermissionScopeLevel};
 use crate::services::casbin_service::CasbinService;
-use crate::utils::{ApiError, JwtUtil};
+use crate::services::{API_TOKEN_PREFIX, ApiTokenService, SessionService};
+use crate::utils::{ApiError, ApiResult, JwtUtil};
 use sqlx::SqlitePool;
 
 #[derive(Clone)]
 pub struct AuthState {
     pub jwt_util: Arc<JwtUtil>,
     pub casbin_service: Arc<CasbinService>,
+    pub api_token_service: Arc<ApiTokenService>,
+    pub session_service: Arc<SessionService>,
     pub db: SqlitePool,
 }
 
@@ -24,10 +28,95 @@ pub struct OrgContext {
     pub username: String,
     pub organization_id: Option<i64>,
     pub is_super_admin: bool,
+    /// Cluster addressed by a `/api/clusters/:id/...` request; handlers fall back to the
+    /// active cluster when unset
+    #[serde(default)]
+    pub cluster_id: Option<i64>,
+    /// Login session the request's access token belongs to (`None` for an API token)
+    #[serde(default)]
+    pub session_id: Option<i64>,
+    /// API token the request authenticated with (`None` for a login session)
+    #[serde(default)]
+    pub api_token_id: Option<i64>,
+    /// Clusters the request's API token is limited to (`None`: no restriction)
+    #[serde(default)]
+    pub allowed_cluster_ids: Option<Vec<i64>>,
+    /// Where the permission checked for the request is held when the user's roles only
+    /// grant it on some clusters, catalogs or databases (`None`: organization-wide)
+    #[serde(default)]
+    pub permission_scopes: Option<Vec<PermissionScope>>,
+}
+
+impl OrgContext {
+    /// Whether the request may act on a cluster (organization checks are separate)
+    pub fn can_access_cluster(&self, cluster_id: i64) -> bool {
+        self.allowed_cluster_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&cluster_id))
+            && self
+                .permission_scopes
+                .as_ref()
+                .is_none_or(|scopes| scopes.iter().any(|scope| scope.cluster_id == cluster_id))
+    }
+
+    /// Refuse a cluster outside the API token restriction or the permission's scopes
+    pub fn check_cluster_access(&self, cluster_id: i64) -> ApiResult<()> {
+        if self
+            .allowed_cluster_ids
+            .as_ref()
+            .is_some_and(|ids| !ids.contains(&cluster_id))
+        {
+            return Err(ApiError::forbidden(format!(
+                "API token is not allowed to access cluster {}",
+                cluster_id
+            )));
+        }
+        if !self.can_access_cluster(cluster_id) {
+            return Err(ApiError::forbidden(format!(
+                "Permission denied on cluster {}",
+                cluster_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Scopes limiting the permission to some catalogs or databases of a cluster (`None`:
+    /// the whole cluster)
+    pub fn database_scopes(&self, cluster_id: i64) -> Option<Vec<&PermissionScope>> {
+        let scopes: Vec<&PermissionScope> = self
+            .permission_scopes
+            .as_ref()?
+            .iter()
+            .filter(|scope| scope.cluster_id == cluster_id)
+            .collect();
+        (!scopes.iter().any(|scope| scope.is_cluster_wide())).then_some(scopes)
+    }
+
+    /// Refuse a database outside the permission's scopes on a cluster
+    pub fn check_database_access(
+        &self,
+        cluster_id: i64,
+        catalog: &str,
+        database: &str,
+    ) -> ApiResult<()> {
+        match self.database_scopes(cluster_id) {
+            Some(scopes)
+                if !scopes
+                    .iter()
+                    .any(|s| s.covers(cluster_id, catalog, database)) =>
+            {
+                Err(ApiError::forbidden(format!(
+                    "Permission denied on database {}.{}",
+                    catalog, database
+                )))
+            },
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Authentication + authorization middleware.
-/// 1. 验证 JWT
+/// 1. 验证 JWT (及其登录会话未被注销) 或 API token (`sra_` 前缀), 拒绝已禁用的用户
 /// 2. 将 `user_id` 写入 request extensions
 /// 3. 根据 URI/Method 推导权限码并交给 Casbin 检查
 pub async fn auth_middleware(
@@ -56,22 +145,61 @@ pub async fn auth_middleware(
         ApiError::unauthorized("Invalid authorization header format")
     })?;
 
-    let claims = state.jwt_util.verify_token(token).map_err(|err| {
-        tracing::warn!("JWT verification failed for {} {}: {:?}", method, uri, err);
-        err
-    })?;
+    let (user_id, username, session_id, api_token) = if token.starts_with(API_TOKEN_PREFIX) {
+        let principal = state
+            .api_token_service
+            .authenticate(token)
+            .await
+            .map_err(|err| {
+                tracing::warn!("API token verification failed for {} {}: {:?}", method, uri, err);
+                err
+            })?;
+        tracing::debug!(
+            "API token {} verified for user {} (ID: {}) on {} {}",
+            principal.token_id,
+            principal.username,
+            principal.user_id,
+            method,
+            uri
+        );
+        (principal.user_id, principal.username.clone(), None, Some(principal))
+    } else {
+        let claims = state.jwt_util.verify_token(token).map_err(|err| {
+            tracing::warn!("JWT verification failed for {} {}: {:?}", method, uri, err);
+            err
+        })?;
 
-    let user_id = claims.sub.parse::<i64>().unwrap_or_default();
-    tracing::debug!(
-        "JWT token verified for user {} (ID: {}) on {} {}",
-        claims.username,
-        user_id,
-        method,
-        uri
-    );
+        let user_id = claims.sub.parse::<i64>().unwrap_or_default();
+        let session_id = claims.sid.ok_or_else(|| {
+            tracing::warn!("JWT without session for user {} on {} {}", user_id, method, uri);
+            ApiError::TokenExpired
+        })?;
+        state
+            .session_service
+            .validate_session(session_id, user_id)
+            .await
+            .inspect_err(|_| {
+                tracing::warn!(
+                    "Session {} of user {} rejected for {} {}",
+                    session_id,
+                    user_id,
+                    method,
+                    uri
+                );
+            })?;
+        tracing::debug!(
+            "JWT token verified for user {} (ID: {}, session {}) on {} {}",
+            claims.username,
+            user_id,
+            session_id,
+            method,
+            uri
+        );
+        (user_id, claims.username, Some(session_id), None)
+    };
 
-    // Load organization and role info with a single query
-    let (is_super_admin, organization_id): (bool, Option<i64>) = sqlx::query_as(
+    // Load organization, role and status info with a single query
+    let user: Option<(bool, Option<i64>, bool)> = sqlx::query_as(
         r#"
         SELECT
             COALESCE(EXISTS (
@@ -80,7 +208,8 @@ pub async fn auth_middleware(
                 JOIN roles r ON r.id = ur.role_id
                 WHERE ur.user_id = ? AND r.code = 'super_admin'
             ), 0) as is_super_admin,
-            NULLIF(u.organization_id, 0) as organization_id
+            NULLIF(u.organization_id, 0) as organization_id,
+            u.is_active
         FROM users u
         WHERE u.id = ?
         "#,
@@ -88,9 +217,17 @@ pub async fn auth_middleware(
     .bind(user_id)
     .bind(user_id)
     .fetch_optional(&state.db)
-    .await
-    .unwrap_or(None)
-    .unwrap_or((false, None));
+    .await?;
+
+    // A deleted user's session or API token must not outlive the account
+    let Some((is_super_admin, organization_id, is_active)) = user else {
+        tracing::warn!("Deleted user {} refused on {} {}", user_id, method, uri);
+        return Err(ApiError::unauthorized("User account no longer exists"));
+    };
+    if !is_active {
+        tracing::warn!("Disabled user {} refused on {} {}", user_id, method, uri);
+        return Err(ApiError::unauthorized("User account is disabled"));
+    }
 
     // Fallback: fetch from user_organizations if organization_id is still None
     let organization_id = if organization_id.is_none() {
@@ -101,12 +238,42 @@ pub async fn auth_middleware(
 
     // Insert legacy extensions to keep backward compatibility
     req.extensions_mut().insert(user_id);
-    req.extensions_mut().insert(claims.username.clone());
+    req.extensions_mut().insert(username.clone());
 
     // Insert org context for downstream services/handlers
-    let org_ctx =
-        OrgContext { user_id, username: claims.username.clone(), organization_id, is_super_admin };
-    req.extensions_mut().insert(org_ctx.clone());
+    let cluster_id = req.extensions().get::<ClusterScope>().map(|scope| scope.0);
+    let mut org_ctx = OrgContext {
+        user_id,
+        username,
+        organization_id,
+        is_super_admin,
+        cluster_id,
+        session_id,
+        api_token_id: api_token.as_ref().map(|principal| principal.token_id),
+        allowed_cluster_ids: api_token
+            .as_ref()
+            .and_then(|principal| principal.cluster_ids.clone()),
+        permission_scopes: None,
+    };
+    let addressed_cluster = cluster_id.or_else(|| addressed_cluster_id(&uri));
+
+    // A cluster-restricted token cannot address other clusters (handlers resolving the
+    // active cluster check the restriction themselves)
+    if let Some(addressed) = addressed_cluster
+        && !org_ctx.can_access_cluster(addressed)
+    {
+        tracing::warn!(
+            "API token of user {} is not allowed on cluster {} ({} {})",
+            user_id,
+            addressed,
+            method,
+            uri
+        );
+        return Err(ApiError::forbidden(format!(
+            "API token is not allowed to access cluster {}",
+            addressed
+        )));
+    }
 
     if let Some((resource, action)) = permission_extractor::extract_permission(&method, &uri) {
         let resource_scope = if org_ctx.is_super_admin || org_ctx.organization_id.is_none() {
@@ -125,11 +292,32 @@ pub async fn auth_middleware(
             action
         );
 
-        let allowed = state
+        // Not granted organization-wide: the user may still hold it on some clusters, then
+        // handlers only act on those (and on granted databases, for database-level
+        // permissions)
+        let mut allowed = state
             .casbin_service
             .enforce(user_id, &resource_scope, &action)
             .await
             .unwrap_or(false);
+        if !allowed && let Some(level) = permission_extractor::scope_level(&resource, &action) {
+            let scopes: Vec<PermissionScope> = state
+                .casbin_service
+                .granted_scopes(user_id, &resource_scope, &action)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|scope| level == PermissionScopeLevel::Database || scope.is_cluster_wide())
+                .collect();
+            allowed = !scopes.is_empty()
+                && addressed_cluster
+                    .is_none_or(|addressed| scopes.iter().any(|s| s.cluster_id == addressed));
+            org_ctx.permission_scopes = Some(scopes);
+        }
+        allowed = allowed
+            && api_token
+                .as_ref()
+                .is_none_or(|principal| principal.allows(&resource, &action));
 
         if !allowed {
             tracing::warn!(
@@ -147,11 +335,26 @@ pub async fn auth_middleware(
         }
 
         tracing::debug!("Permission granted for user {} on {} {}", user_id, method, uri);
+    } else if api_token.is_some() && !permission_extractor::api_token_allows_unmapped(&method, &uri)
+    {
+        // Token scopes cannot express routes without a permission
+        tracing::warn!("API token of user {} refused on unmapped {} {}", user_id, method, uri);
+        return Err(ApiError::forbidden(format!("API tokens cannot access {} {}", method, uri)));
     }
 
+    req.extensions_mut().insert(org_ctx);
     Ok(next.run(req).await)
 }
 
+// Cluster id of a `/api/clusters/:id[/...]` path
+fn addressed_cluster_id(uri: &str) -> Option<i64> {
+    uri.strip_prefix("/api/clusters/")?
+        .split('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
 // Helper to fetch organization from user_organizations when users.organization_id is NULL
 async fn fetch_org_from_user_organizations(db: &SqlitePool, user_id: i64) -> Option<i64> {
     sqlx::query_scalar::<_, i64>(
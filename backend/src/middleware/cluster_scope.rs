@@ -0,0 +1,87 @@
+use axum::{
+    extract::{OriginalUri, Request},
+    http::Uri,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::utils::ApiError;
+
+/// Header addressing a cluster without a cluster-scoped path
+pub const CLUSTER_ID_HEADER: &str = "x-cluster-id";
+
+/// Cluster addressed by a request (`/api/clusters/:id/<route>` or the `X-Cluster-Id` header)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClusterScope(pub i64);
+
+/// Route prefixes under `/api/clusters/` that act on "the current cluster" and can be
+/// addressed per cluster as `/api/clusters/:id/<prefix>/...`
+const SCOPED_PREFIXES: &[&str] = &[
+    "alerts",
+    "backends",
+    "catalogs",
+    "catalogs-databases",
+    "databases",
+    "frontends",
+    "loads",
+    "materialized_views",
+    "overview",
+    "profile-archives",
+    "profiles",
+    "queries",
+    "routine-loads",
+    "sessions",
+    "system",
+    "system-functions",
+    "tables",
+    "variables",
+];
+
+/// Split `/api/clusters/<id>/<prefix>/...` into the cluster id and the unscoped path
+/// `/api/clusters/<prefix>/...`; `None` for any other path
+pub fn split_cluster_scoped_path(path: &str) -> Option<(i64, String)> {
+    let rest = path.strip_prefix("/api/clusters/")?;
+    let (id, route) = rest.split_once('/')?;
+    let cluster_id = id.parse::<i64>().ok()?;
+    let prefix = route.split('/').next().unwrap_or_default();
+    if !SCOPED_PREFIXES.contains(&prefix) {
+        return None;
+    }
+    Some((cluster_id, format!("/api/clusters/{}", route)))
+}
+
+/// Cluster scope middleware.
+/// Wraps the whole router (it must run before routing): rewrites cluster-scoped requests to
+/// the unscoped route and records the cluster as a `ClusterScope` extension, which
+/// `auth_middleware` copies into `OrgContext::cluster_id`. Requests on unscoped routes can
+/// address a cluster with the `X-Cluster-Id` header instead; the path wins over the header.
+/// Handlers then resolve the cluster from the request instead of the caller's active cluster.
+pub async fn cluster_scope_middleware(mut req: Request, next: Next) -> Response {
+    if let Some((cluster_id, path)) = split_cluster_scoped_path(req.uri().path()) {
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        };
+        let mut parts = req.uri().clone().into_parts();
+        if let Ok(path_and_query) = path_and_query.parse() {
+            parts.path_and_query = Some(path_and_query);
+            if let Ok(uri) = Uri::from_parts(parts) {
+                tracing::debug!("Cluster {} scoped request: {} -> {}", cluster_id, req.uri(), uri);
+                let original = OriginalUri(std::mem::replace(req.uri_mut(), uri));
+                req.extensions_mut().insert(original);
+                req.extensions_mut().insert(ClusterScope(cluster_id));
+            }
+        }
+    } else if let Some(value) = req.headers().get(CLUSTER_ID_HEADER) {
+        let Some(cluster_id) = value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse::<i64>().ok())
+        else {
+            return ApiError::validation_error("Invalid X-Cluster-Id header").into_response();
+        };
+        req.extensions_mut().insert(ClusterScope(cluster_id));
+    }
+
+    next.run(req).await
+}
@@ -0,0 +1,32 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::utils::ProcessMetrics;
+
+/// HTTP metrics middleware.
+/// Records the latency of every request by method, matched route and status. Requests
+/// without a matched route (static assets, SPA fallback) share the "unmatched" route so
+/// arbitrary paths do not create new series.
+pub async fn http_metrics_middleware(
+    State(metrics): State<Arc<ProcessMetrics>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    metrics.observe_http_request(&method, &route, response.status().as_u16(), started.elapsed());
+
+    response
+}
@@ -1,4 +1,10 @@
+pub mod admin_audit;
 pub mod auth;
+pub mod cluster_scope;
+pub mod http_metrics;
 pub mod permission_extractor;
 
+pub use admin_audit::admin_audit_middleware;
 pub use auth::{AuthState, OrgContext, auth_middleware};
+pub use cluster_scope::{ClusterScope, cluster_scope_middleware};
+pub use http_metrics::http_metrics_middleware;
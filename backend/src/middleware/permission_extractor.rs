@@ -1,3 +1,5 @@
+use crate::models::PermissionScopeLevel;
+
 /// Permission extraction module for cleaner code organization
 /// Uses strategy pattern to handle different route patterns
 /// Extract permission from URI and method
@@ -20,6 +22,9 @@ pub fn extract_permission(method: &str, uri: &str) -> Option<(String, String)> {
         "permissions" => "permissions",
         "users" => "users",
         "clusters" => "clusters",
+        "audit-logs" => "audit_logs",
+        "service-accounts" => "service_accounts",
+        "sso" => "sso",
         _ => return None,
     };
 
@@ -30,6 +35,39 @@ pub fn extract_permission(method: &str, uri: &str) -> Option<(String, String)> {
     Some((resource.to_string(), action))
 }
 
+/// Routes without a permission that API tokens may still call: the identity of the token
+/// owner and the active cluster. Tokens are refused on every other unmapped route
+/// (organizations, system functions, sessions, two-factor and token management).
+const API_TOKEN_UNMAPPED_ROUTES: &[(&str, &str)] =
+    &[("GET", "/api/auth/me"), ("GET", "/api/auth/permissions"), ("GET", "/api/clusters/active")];
+
+/// Whether an API token may call a route `extract_permission` maps to no permission
+pub fn api_token_allows_unmapped(method: &str, uri: &str) -> bool {
+    API_TOKEN_UNMAPPED_ROUTES.contains(&(method, uri))
+}
+
+/// Cluster actions that do not act on existing clusters
+const UNSCOPED_CLUSTER_ACTIONS: &[&str] = &["create", "active", "health:test"];
+
+/// Finest scope a permission can be granted at below the organization: cluster actions
+/// per cluster (listings only show the granted clusters), the SQL editor, materialized
+/// views, load jobs and Routine Load jobs also per catalog or database
+pub fn scope_level(resource: &str, action: &str) -> Option<PermissionScopeLevel> {
+    if resource != "clusters" || UNSCOPED_CLUSTER_ACTIONS.contains(&action) {
+        return None;
+    }
+    if action == "queries:execute"
+        || matches!(
+            action.split(':').next(),
+            Some("materialized_views" | "loads" | "routine_loads")
+        )
+    {
+        Some(PermissionScopeLevel::Database)
+    } else {
+        Some(PermissionScopeLevel::Cluster)
+    }
+}
+
 /// Extract action with special route handlers
 fn extract_action_with_special_handlers(
     resource: &str,
@@ -40,6 +78,8 @@ fn extract_action_with_special_handlers(
         "roles" => extract_roles_action(segments, method),
         "users" => extract_users_action(segments, method),
         "clusters" => extract_clusters_action_special(segments, method),
+        "service_accounts" => extract_service_accounts_action(segments, method),
+        "sso" => extract_sso_action(segments, method),
         _ => None,
     }
 }
@@ -53,6 +93,14 @@ fn extract_roles_action(segments: &[&str], method: &str) -> Option<String> {
             _ => None,
         };
     }
+    if segments.len() == 3 && segments.get(2) == Some(&"sql-policy") {
+        return match method {
+            "GET" => Some("sql_policy:get".to_string()),
+            "PUT" => Some("sql_policy:update".to_string()),
+            "DELETE" => Some("sql_policy:delete".to_string()),
+            _ => None,
+        };
+    }
     None
 }
 
@@ -66,9 +114,47 @@ fn extract_users_action(segments: &[&str], method: &str) -> Option<String> {
             _ => None,
         };
     }
+    if segments.get(2) == Some(&"sessions") {
+        return match method {
+            "GET" => Some("sessions:list".to_string()),
+            "DELETE" => Some("sessions:revoke".to_string()),
+            _ => None,
+        };
+    }
+    match (segments.get(2), method) {
+        (Some(&"two-factor"), "DELETE") => return Some("two_factor:reset".to_string()),
+        (Some(&"lockout"), "DELETE") => return Some("lockout:clear".to_string()),
+        _ => {},
+    }
     None
 }
 
+/// Extract action for service-accounts/{id}/tokens paths
+fn extract_service_accounts_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(2) != Some(&"tokens") {
+        return None;
+    }
+    match (segments.len(), method) {
+        (3, "GET") => Some("tokens:list".to_string()),
+        (3, "POST") => Some("tokens:create".to_string()),
+        (4, "DELETE") => Some("tokens:revoke".to_string()),
+        _ => None,
+    }
+}
+
+/// Extract action for sso/mappings paths
+fn extract_sso_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(1) != Some(&"mappings") {
+        return None;
+    }
+    match (segments.len(), method) {
+        (2, "GET") => Some("mappings:list".to_string()),
+        (2, "POST") => Some("mappings:create".to_string()),
+        (3, "DELETE") => Some("mappings:delete".to_string()),
+        _ => None,
+    }
+}
+
 /// Extract action for clusters resource with special handlers
 fn extract_clusters_action_special(segments: &[&str], method: &str) -> Option<String> {
     if segments.len() < 2 {
@@ -99,6 +185,14 @@ fn extract_clusters_id_action(segments: &[&str], method: &str) -> Option<String>
             let action = segments.get(2)?;
             if method == "POST" && *action == "health" {
                 Some("health:post".to_string())
+            } else if method == "PUT" && *action == "collection" {
+                Some("collection:update".to_string())
+            } else if *action == "audit-source" {
+                match (method, segments.get(3)) {
+                    ("GET", None) => Some("audit_source".to_string()),
+                    ("POST", Some(&"validate")) => Some("audit_source:validate".to_string()),
+                    _ => Some("audit_source:update".to_string()),
+                }
             } else {
                 Some(action.to_string())
             }
@@ -167,6 +261,18 @@ fn extract_clusters_special_paths(segments: &[&str], method: &str) -> Option<Str
                 None
             }
         }),
+        // POST /api/clusters/profiles/:query_id/archive -> profiles:archive
+        Box::new(|seg, m| {
+            if m == "POST"
+                && seg.len() >= 4
+                && seg.get(1) == Some(&"profiles")
+                && seg.last() == Some(&"archive")
+            {
+                Some("profiles:archive".to_string())
+            } else {
+                None
+            }
+        }),
         // GET /api/clusters/profiles/:query_id -> profiles:get
         // query_id can be any string (number or UUID with colons)
         // Note: query_id with colons will be split into multiple segments by split('/')
@@ -194,6 +300,11 @@ fn extract_clusters_special_paths(segments: &[&str], method: &str) -> Option<Str
             }
         }),
         Box::new(extract_materialized_views_action),
+        Box::new(extract_loads_action),
+        Box::new(extract_routine_loads_action),
+        Box::new(extract_profile_archives_action),
+        Box::new(extract_alerts_action),
+        Box::new(extract_overview_nodes_action),
         Box::new(extract_variables_action),
         Box::new(extract_system_functions_action),
     ];
@@ -214,10 +325,14 @@ fn extract_materialized_views_action(segments: &[&str], method: &str) -> Option<
     }
 
     match segments.len() {
-        3 => match method {
-            "GET" => Some("materialized_views:get".to_string()),
-            "PUT" => Some("materialized_views:update".to_string()),
-            "DELETE" => Some("materialized_views:delete".to_string()),
+        3 => match (segments[2], method) {
+            (action @ ("dependencies" | "staleness" | "impact"), "GET") => {
+                Some(format!("materialized_views:{}", action))
+            },
+            ("refresh-stats", "GET") => Some("materialized_views:refresh_stats".to_string()),
+            (_, "GET") => Some("materialized_views:get".to_string()),
+            (_, "PUT") => Some("materialized_views:update".to_string()),
+            (_, "DELETE") => Some("materialized_views:delete".to_string()),
             _ => None,
         },
         4 => {
@@ -226,6 +341,10 @@ fn extract_materialized_views_action(segments: &[&str], method: &str) -> Option<
                 ("ddl", "GET") => Some("materialized_views:ddl".to_string()),
                 ("refresh", "POST") => Some("materialized_views:refresh".to_string()),
                 ("cancel", "POST") => Some("materialized_views:cancel".to_string()),
+                ("refresh-history", "GET") => {
+                    Some("materialized_views:refresh_history".to_string())
+                },
+                ("refresh-trends", "GET") => Some("materialized_views:refresh_trends".to_string()),
                 _ => None,
             }
         },
@@ -233,14 +352,120 @@ fn extract_materialized_views_action(segments: &[&str], method: &str) -> Option<
     }
 }
 
+/// Extract action for loads paths
+fn extract_loads_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(1) != Some(&"loads") {
+        return None;
+    }
+
+    match (segments.len(), method) {
+        (2, "GET") => Some("loads".to_string()),
+        (3, "GET") => Some("loads:get".to_string()),
+        (4, "POST") if segments.get(3) == Some(&"cancel") => Some("loads:cancel".to_string()),
+        (4, "POST") if segments.get(3) == Some(&"retry") => Some("loads:retry".to_string()),
+        (4, "GET") if segments.get(3) == Some(&"tracking-log") => {
+            Some("loads:tracking_log".to_string())
+        },
+        _ => None,
+    }
+}
+
+/// Extract action for routine-loads paths
+fn extract_routine_loads_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(1) != Some(&"routine-loads") {
+        return None;
+    }
+
+    match (segments.len(), method) {
+        (2, "GET") => Some("routine_loads".to_string()),
+        (4, "GET") => Some("routine_loads:get".to_string()),
+        (5, "GET") => match *segments.get(4)? {
+            action @ ("tasks" | "lag") => Some(format!("routine_loads:{}", action)),
+            _ => None,
+        },
+        (5, "POST") => match *segments.get(4)? {
+            action @ ("pause" | "resume" | "stop") => Some(format!("routine_loads:{}", action)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Extract action for profile-archives paths
+fn extract_profile_archives_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(1) != Some(&"profile-archives") {
+        return None;
+    }
+
+    match (segments.len(), method) {
+        (2, "GET") => Some("profile_archives:list".to_string()),
+        (3, "GET") => Some("profile_archives:get".to_string()),
+        (3, "DELETE") => Some("profile_archives:delete".to_string()),
+        (4, "POST") if segments.get(3) == Some(&"analyze") => {
+            Some("profile_archives:analyze".to_string())
+        },
+        _ => None,
+    }
+}
+
+/// Extract action for alerts paths
+fn extract_alerts_action(segments: &[&str], method: &str) -> Option<String> {
+    if segments.get(1) != Some(&"alerts") {
+        return None;
+    }
+
+    let resource = *segments.get(2)?;
+    let action = match (resource, segments.len(), method) {
+        ("metrics", 3, "GET") => "alerts:metrics",
+        ("history", 3, "GET") => "alerts:history",
+        ("rules" | "channels", 3 | 4, "GET") => "list",
+        ("rules" | "channels", 3, "POST") => "create",
+        ("rules" | "channels", 4, "PUT") => "update",
+        ("rules" | "channels", 4, "DELETE") => "delete",
+        ("rules", 5, "POST" | "DELETE") if segments.get(4) == Some(&"silence") => "silence",
+        ("channels", 5, "POST") if segments.get(4) == Some(&"test") => "test",
+        _ => return None,
+    };
+
+    if action.starts_with("alerts:") {
+        Some(action.to_string())
+    } else {
+        Some(format!("alerts:{}:{}", resource, action))
+    }
+}
+
+/// Extract action for overview node metrics paths
+/// GET /api/clusters/overview/nodes/:node_type/:node_id[/trends]
+fn extract_overview_nodes_action(segments: &[&str], method: &str) -> Option<String> {
+    if method != "GET" || segments.get(1) != Some(&"overview") || segments.get(2) != Some(&"nodes")
+    {
+        return None;
+    }
+
+    match segments.len() {
+        5 => Some("overview:nodes:get".to_string()),
+        6 if segments.get(5) == Some(&"trends") => Some("overview:nodes:trends".to_string()),
+        _ => None,
+    }
+}
+
 /// Extract action for variables paths
 fn extract_variables_action(segments: &[&str], method: &str) -> Option<String> {
-    if method == "PUT" && segments.len() == 3 && segments.get(1) == Some(&"variables") {
-        segments.get(2).and_then(|third| {
-            if third.parse::<i64>().is_err() { Some("variables:update".to_string()) } else { None }
-        })
-    } else {
-        None
+    if segments.get(1) != Some(&"variables") || segments.len() < 3 {
+        return None;
+    }
+
+    match (segments.len(), segments[2], method) {
+        (3, "history", "GET") => Some("variables:history".to_string()),
+        (3, "snapshots", "GET") | (4, "snapshots", "GET") => {
+            Some("variables:snapshots".to_string())
+        },
+        (3, "snapshots", "POST") => Some("variables:snapshots:create".to_string()),
+        (5, "history", "POST") if segments[4] == "rollback" => {
+            Some("variables:rollback".to_string())
+        },
+        (3, name, "PUT") if name.parse::<i64>().is_err() => Some("variables:update".to_string()),
+        _ => None,
     }
 }
 
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// One audited admin action
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AdminAuditLog {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub username: String,
+    pub organization_id: Option<i64>,
+    pub cluster_id: Option<i64>,
+    pub cluster_name: Option<String>,
+    /// Route permission (e.g. clusters:queries:kill), or "METHOD /route" for unmapped routes
+    pub action: String,
+    pub method: String,
+    pub path: String,
+    /// Path parameters of the route (e.g. query_id=...)
+    pub target: Option<String>,
+    /// Request body with secrets redacted
+    pub payload: Option<String>,
+    pub status_code: i64,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    pub client_ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Audit entry captured by the audit middleware
+#[derive(Debug, Clone, Default)]
+pub struct NewAdminAuditLog {
+    pub user_id: Option<i64>,
+    pub username: String,
+    pub organization_id: Option<i64>,
+    pub cluster_id: Option<i64>,
+    pub cluster_name: Option<String>,
+    pub action: String,
+    pub method: String,
+    pub path: String,
+    pub target: Option<String>,
+    pub payload: Option<String>,
+    pub status_code: i64,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub duration_ms: i64,
+    pub client_ip: Option<String>,
+}
+
+/// Filters of the audit log list and export
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct AdminAuditQuery {
+    /// Only honoured for super admins; other users always see their own organization
+    pub organization_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub cluster_id: Option<i64>,
+    /// Action prefix, e.g. "clusters:queries"
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// Matches path, target and payload
+    pub keyword: Option<String>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminAuditListResponse {
+    pub items: Vec<AdminAuditLog>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
@@ -0,0 +1,293 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Comparison between a metric value and the rule threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum AlertComparator {
+    #[serde(rename = ">")]
+    #[sqlx(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    #[sqlx(rename = ">=")]
+    Gte,
+    #[serde(rename = "<")]
+    #[sqlx(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    #[sqlx(rename = "<=")]
+    Lte,
+    #[serde(rename = "==")]
+    #[sqlx(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    #[sqlx(rename = "!=")]
+    Ne,
+}
+
+impl AlertComparator {
+    /// Whether `value <comparator> threshold` holds
+    pub fn matches(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            AlertComparator::Gt => value > threshold,
+            AlertComparator::Gte => value >= threshold,
+            AlertComparator::Lt => value < threshold,
+            AlertComparator::Lte => value <= threshold,
+            AlertComparator::Eq => (value - threshold).abs() < f64::EPSILON,
+            AlertComparator::Ne => (value - threshold).abs() >= f64::EPSILON,
+        }
+    }
+}
+
+impl std::fmt::Display for AlertComparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AlertComparator::Gt => ">",
+            AlertComparator::Gte => ">=",
+            AlertComparator::Lt => "<",
+            AlertComparator::Lte => "<=",
+            AlertComparator::Eq => "==",
+            AlertComparator::Ne => "!=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertSeverity::Info => write!(f, "info"),
+            AlertSeverity::Warning => write!(f, "warning"),
+            AlertSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Evaluation state of a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AlertState {
+    /// Condition does not hold
+    Ok,
+    /// Condition holds but not yet for the rule's duration window
+    Pending,
+    /// Condition has held for the duration window
+    Firing,
+}
+
+/// Alert history event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+impl std::fmt::Display for AlertStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertStatus::Firing => write!(f, "firing"),
+            AlertStatus::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AlertChannelType {
+    /// Generic webhook receiving the alert event as JSON
+    Webhook,
+    /// Email via SMTP
+    Smtp,
+    /// DingTalk robot (markdown message)
+    Dingtalk,
+    /// Feishu/Lark robot (text message)
+    Feishu,
+    /// Slack incoming webhook
+    Slack,
+}
+
+// ========================================
+// Alert rules
+// ========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertRule {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub metric: String,
+    pub comparator: AlertComparator,
+    pub threshold: f64,
+    /// Condition must hold this long before the alert fires (0 = fire immediately)
+    pub duration_secs: i64,
+    pub severity: AlertSeverity,
+    pub enabled: bool,
+    pub channel_ids: Vec<i64>,
+    pub state: AlertState,
+    pub pending_since: Option<DateTime<Utc>>,
+    pub last_value: Option<f64>,
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    /// Notifications are suppressed until this time
+    pub silenced_until: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateAlertRuleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub metric: String,
+    pub comparator: AlertComparator,
+    pub threshold: f64,
+    #[serde(default)]
+    pub duration_secs: i64,
+    pub severity: AlertSeverity,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub channel_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateAlertRuleRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub metric: Option<String>,
+    pub comparator: Option<AlertComparator>,
+    pub threshold: Option<f64>,
+    pub duration_secs: Option<i64>,
+    pub severity: Option<AlertSeverity>,
+    pub enabled: Option<bool>,
+    pub channel_ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SilenceAlertRuleRequest {
+    /// Silence notifications for this many seconds from now
+    pub duration_secs: i64,
+}
+
+/// A metric that alert rules can watch
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AlertMetricInfo {
+    pub name: String,
+    pub description: String,
+}
+
+// ========================================
+// Notification channels
+// ========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertChannel {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub name: String,
+    pub channel_type: AlertChannelType,
+    /// Channel settings; secrets (SMTP password) are never returned
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+    pub enabled: bool,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateAlertChannelRequest {
+    pub name: String,
+    pub channel_type: AlertChannelType,
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateAlertChannelRequest {
+    pub name: Option<String>,
+    /// Replaces the whole config; an omitted SMTP password keeps the stored one
+    #[schema(value_type = Option<Object>)]
+    pub config: Option<serde_json::Value>,
+    pub enabled: Option<bool>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// ========================================
+// Alert history
+// ========================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct AlertHistoryItem {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub rule_id: Option<i64>,
+    pub rule_name: String,
+    pub metric: String,
+    pub comparator: AlertComparator,
+    pub threshold: f64,
+    pub value: f64,
+    pub severity: AlertSeverity,
+    pub status: AlertStatus,
+    pub message: String,
+    /// Raised while the rule was silenced (no notification sent)
+    pub silenced: bool,
+    /// Delivered to at least one channel (set once the background delivery finishes)
+    pub notified: bool,
+    pub notification_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AlertHistoryQuery {
+    pub rule_id: Option<i64>,
+    pub status: Option<AlertStatus>,
+    pub severity: Option<AlertSeverity>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AlertHistoryListResponse {
+    pub items: Vec<AlertHistoryItem>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+/// Alert notification payload (sent as-is by the generic webhook channel)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertEvent {
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    pub metric: String,
+    pub comparator: AlertComparator,
+    pub threshold: f64,
+    pub value: f64,
+    pub severity: AlertSeverity,
+    pub status: AlertStatus,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
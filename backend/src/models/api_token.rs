@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::role::RoleResponse;
+
+// ========================================
+// API tokens
+// ========================================
+
+/// Long-lived token acting as a user (the token itself is never stored nor returned again)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    /// First characters of the token, to recognize it
+    pub token_prefix: String,
+    /// Permission codes the token is limited to (`None`: all permissions of the user)
+    pub scopes: Option<Vec<String>>,
+    /// Clusters the token is limited to (`None`: any cluster of the user)
+    pub cluster_ids: Option<Vec<i64>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiToken {
+    /// Whether the token can still authenticate
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+            && self
+                .expires_at
+                .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    /// Permission codes (e.g. `api:clusters:list`), each held by the token's user
+    pub scopes: Option<Vec<String>>,
+    /// Clusters the token may address, each accessible to the token's user
+    pub cluster_ids: Option<Vec<i64>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A newly created token: the only response that carries the token itself
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+}
+
+// ========================================
+// Service accounts
+// ========================================
+
+/// User that only authenticates with API tokens
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceAccount {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub organization_id: Option<i64>,
+    pub roles: Vec<RoleResponse>,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateServiceAccountRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Roles of the account; their permissions must all be held by the caller
+    #[serde(default)]
+    pub role_ids: Vec<i64>,
+    pub organization_id: Option<i64>,
+}
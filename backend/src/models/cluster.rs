@@ -46,6 +46,76 @@ pub struct Cluster {
     pub organization_id: Option<i64>,
     #[serde(default)]
     pub deployment_mode: DeploymentMode,
+    /// Additional FE endpoints (JSON array of `FeEndpoint`) tried after fe_host
+    #[serde(default)]
+    pub fe_endpoints: Option<String>,
+    /// PEM CA bundle trusted for TLS connections (system roots when empty)
+    #[serde(default)]
+    pub tls_ca_cert: Option<String>,
+    /// PEM client certificate for mutual TLS
+    #[serde(default)]
+    pub tls_client_cert: Option<String>,
+    /// Encrypted PEM private key of the client certificate
+    #[serde(default, skip_serializing)]
+    pub tls_client_key_encrypted: Option<String>,
+    /// Check that the FE certificate matches the FE host name
+    #[serde(default = "default_true")]
+    pub tls_verify_hostname: bool,
+}
+
+/// One FE of a cluster: MySQL query port and HTTP port of a frontend
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct FeEndpoint {
+    pub host: String,
+    #[serde(default = "default_http_port")]
+    pub http_port: i32,
+    #[serde(default = "default_query_port")]
+    pub query_port: i32,
+}
+
+impl std::fmt::Display for FeEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}/{}", self.host, self.query_port, self.http_port)
+    }
+}
+
+/// Where an FE endpoint of a cluster comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeEndpointSource {
+    /// fe_host or one of the additional endpoints of the cluster
+    Configured,
+    /// Reported by SHOW FRONTENDS
+    Discovered,
+}
+
+/// Connection state of one FE endpoint as seen by the pool manager
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeEndpointStatus {
+    pub host: String,
+    pub http_port: i32,
+    pub query_port: i32,
+    pub source: FeEndpointSource,
+    /// Endpoint the MySQL pool is currently connected to
+    pub is_current: bool,
+    /// Leader FE according to the last discovery
+    pub is_leader: bool,
+    /// Alive flag from the last discovery (None if the FE was not discovered)
+    pub alive: Option<bool>,
+}
+
+/// TLS settings of a cluster, used for both the MySQL and the HTTP connection when
+/// enable_ssl is set
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ClusterTlsRequest {
+    /// PEM CA bundle (system roots when omitted)
+    pub ca_cert: Option<String>,
+    /// PEM client certificate for mutual TLS
+    pub client_cert: Option<String>,
+    /// PEM private key of the client certificate (kept on update when omitted)
+    pub client_key: Option<String>,
+    #[serde(default = "default_true")]
+    pub verify_hostname: bool,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -70,6 +140,11 @@ pub struct CreateClusterRequest {
     pub organization_id: Option<i64>,
     #[serde(default)]
     pub deployment_mode: DeploymentMode,
+    /// Additional FE endpoints used for failover
+    #[serde(default)]
+    pub fe_endpoints: Option<Vec<FeEndpoint>>,
+    #[serde(default)]
+    pub tls: Option<ClusterTlsRequest>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -87,6 +162,10 @@ pub struct UpdateClusterRequest {
     pub catalog: Option<String>,
     pub organization_id: Option<i64>,
     pub deployment_mode: Option<DeploymentMode>,
+    /// Replaces the additional FE endpoints (empty list removes them)
+    pub fe_endpoints: Option<Vec<FeEndpoint>>,
+    /// Replaces the TLS settings
+    pub tls: Option<ClusterTlsRequest>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -102,12 +181,21 @@ pub struct ClusterResponse {
     pub connection_timeout: i32,
     pub tags: Vec<String>,
     pub catalog: String,
+    /// Active cluster of the caller where known, the organization default otherwise
     pub is_active: bool,
+    /// Default cluster of the organization (used by users who have not selected one)
+    pub is_default: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub organization_id: Option<i64>,
     pub deployment_mode: DeploymentMode,
+    pub fe_endpoints: Vec<FeEndpoint>,
+    pub tls_ca_cert: Option<String>,
+    pub tls_client_cert: Option<String>,
+    /// Whether a client key is stored (the key itself is never returned)
+    pub tls_client_key_configured: bool,
+    pub tls_verify_hostname: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -137,6 +225,10 @@ fn default_http_port() -> i32 {
     8030
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_query_port() -> i32 {
     9030
 }
@@ -151,6 +243,7 @@ fn default_catalog() -> String {
 
 impl From<Cluster> for ClusterResponse {
     fn from(cluster: Cluster) -> Self {
+        let fe_endpoints = cluster.additional_endpoints();
         let tags = cluster
             .tags
             .and_then(|t| serde_json::from_str(&t).ok())
@@ -169,10 +262,16 @@ impl From<Cluster> for ClusterResponse {
             tags,
             catalog: cluster.catalog,
             is_active: cluster.is_active,
+            is_default: cluster.is_active,
             created_at: cluster.created_at,
             updated_at: cluster.updated_at,
             organization_id: cluster.organization_id,
             deployment_mode: cluster.deployment_mode,
+            fe_endpoints,
+            tls_client_key_configured: cluster.tls_client_key_encrypted.is_some(),
+            tls_ca_cert: cluster.tls_ca_cert,
+            tls_client_cert: cluster.tls_client_cert,
+            tls_verify_hostname: cluster.tls_verify_hostname,
         }
     }
 }
@@ -183,6 +282,34 @@ impl Cluster {
         self.deployment_mode == DeploymentMode::SharedData
     }
 
+    /// FE configured as fe_host/fe_http_port/fe_query_port
+    pub fn primary_endpoint(&self) -> FeEndpoint {
+        FeEndpoint {
+            host: self.fe_host.clone(),
+            http_port: self.fe_http_port,
+            query_port: self.fe_query_port,
+        }
+    }
+
+    /// Additional FE endpoints configured for failover
+    pub fn additional_endpoints(&self) -> Vec<FeEndpoint> {
+        self.fe_endpoints
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// All configured FE endpoints, primary first, without duplicates
+    pub fn configured_endpoints(&self) -> Vec<FeEndpoint> {
+        let mut endpoints = vec![self.primary_endpoint()];
+        for endpoint in self.additional_endpoints() {
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+        endpoints
+    }
+
     /// Check if cluster is using shared-nothing (storage-compute integrated) architecture
     pub fn is_shared_nothing(&self) -> bool {
         self.deployment_mode == DeploymentMode::SharedNothing
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Load job (from information_schema.loads): Broker, Spark, INSERT and Stream Load jobs
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct LoadJob {
+    /// Load job ID
+    pub id: i64,
+
+    /// Load label
+    pub label: String,
+
+    /// Database name
+    pub database_name: String,
+
+    /// Target table (StarRocks 3.1+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_name: Option<String>,
+
+    /// User who submitted the job (StarRocks 3.1+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    /// State: PENDING/QUEUEING/LOADING/PREPARED/COMMITTED/FINISHED/CANCELLED
+    pub state: String,
+
+    /// Load type: BROKER/SPARK/INSERT/STREAM_LOAD
+    pub load_type: String,
+
+    /// Progress text as reported by StarRocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<String>,
+
+    /// Job priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+
+    /// Rows scanned from the source
+    pub scan_rows: i64,
+
+    /// Rows filtered out for bad data quality
+    pub filtered_rows: i64,
+
+    /// Rows filtered out by the WHERE clause
+    pub unselected_rows: i64,
+
+    /// Rows written to the table
+    pub sink_rows: i64,
+
+    /// Creation time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+
+    /// Load start time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_start_time: Option<String>,
+
+    /// Load finish time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_finish_time: Option<String>,
+
+    /// Error message of a failed job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+
+    /// URL of the rows filtered out for bad data quality
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking_url: Option<String>,
+
+    /// Path of the rejected records (StarRocks 3.1+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_record_path: Option<String>,
+}
+
+/// Load job with its properties and execution details
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoadJobDetail {
+    #[serde(flatten)]
+    pub job: LoadJob,
+
+    /// Job properties (JSON text, StarRocks 3.1+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<String>,
+
+    /// Runtime details (JSON text): file counts, backends, load ids
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_details: Option<String>,
+
+    /// ETL information (Spark Load)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etl_info: Option<String>,
+
+    /// Task information: resource, timeout, max filter ratio
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_info: Option<String>,
+
+    /// SQL returning the tracking log of the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking_sql: Option<String>,
+
+    /// Whether the job can still be cancelled
+    pub cancellable: bool,
+
+    /// Whether the job can be resubmitted (cancelled Broker, Spark and INSERT loads)
+    pub retryable: bool,
+}
+
+/// Tracking log of a load job: the rows rejected for bad data quality
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoadTrackingLog {
+    /// Load job ID
+    pub job_id: i64,
+
+    /// Load label
+    pub label: String,
+
+    /// Database name
+    pub database_name: String,
+
+    /// Error message of the job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+
+    /// URL of the rows filtered out for bad data quality
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking_url: Option<String>,
+
+    /// Tracking log lines (empty when StarRocks kept none)
+    pub lines: Vec<String>,
+}
+
+/// Filters of the load job list
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadJobFilter {
+    /// State, e.g. LOADING or CANCELLED
+    pub state: Option<String>,
+
+    /// Label substring
+    pub label: Option<String>,
+
+    /// Database name
+    pub database: Option<String>,
+
+    /// Load type, e.g. BROKER or INSERT
+    pub load_type: Option<String>,
+
+    /// Jobs created at or after this time (YYYY-MM-DD HH:MM:SS)
+    pub start_time: Option<String>,
+
+    /// Jobs created before this time (YYYY-MM-DD HH:MM:SS)
+    pub end_time: Option<String>,
+
+    /// Maximum number of jobs, newest first
+    pub limit: Option<usize>,
+}
@@ -99,3 +99,296 @@ pub struct MaterializedViewDDL {
     pub mv_name: String,
     pub ddl: String,
 }
+
+/// Kind of object in the materialized view dependency graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MvDependencyNodeType {
+    MaterializedView,
+    Table,
+    /// Table of an external catalog (Hive, Iceberg, ...); its loads are not tracked
+    ExternalTable,
+}
+
+/// Materialized view or base table in the dependency graph
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct MvDependencyNode {
+    /// `catalog.database.name`
+    pub id: String,
+
+    pub catalog: String,
+
+    pub database_name: String,
+
+    pub name: String,
+
+    pub node_type: MvDependencyNodeType,
+
+    /// Is active (materialized views only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active: Option<bool>,
+
+    /// Last refresh state (materialized views only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh_state: Option<String>,
+
+    /// When the data last changed: last refresh of a materialized view, last load of a table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+}
+
+/// A materialized view reading a base table or another materialized view
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct MvDependencyEdge {
+    /// Materialized view ID (`catalog.database.name`)
+    pub source: String,
+
+    /// ID of the object it reads
+    pub target: String,
+}
+
+/// Dependency DAG of the async materialized views of a cluster
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Default)]
+pub struct MvDependencyGraph {
+    pub nodes: Vec<MvDependencyNode>,
+    pub edges: Vec<MvDependencyEdge>,
+}
+
+/// Why a materialized view is reported as stale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MvStalenessIssue {
+    /// The view is inactive and neither refreshed nor used for query rewrite
+    Inactive,
+    /// The last refreshes failed in a row
+    RefreshFailing,
+    /// A base table changed after the last refresh started
+    OutdatedBaseTable,
+}
+
+/// Freshness of a materialized view
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct MvStaleness {
+    /// `catalog.database.name`
+    pub id: String,
+
+    pub database_name: String,
+
+    pub name: String,
+
+    /// Refresh type: MANUAL/ASYNC/INCREMENTAL
+    pub refresh_type: String,
+
+    pub is_active: bool,
+
+    /// Why the view became inactive, e.g. a base table was dropped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inactive_reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh_state: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh_start_time: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh_finished_time: Option<String>,
+
+    /// Error message of the last refresh
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_refresh_error: Option<String>,
+
+    /// Failed refreshes since the last successful one
+    pub consecutive_failures: i64,
+
+    /// IDs of the base tables changed after the last refresh started
+    pub outdated_base_tables: Vec<String>,
+
+    /// Empty when the view is fresh
+    pub issues: Vec<MvStalenessIssue>,
+}
+
+/// Materialized view depending on a table, directly or through other views
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct MvImpactedView {
+    /// `catalog.database.name`
+    pub id: String,
+
+    pub database_name: String,
+
+    pub name: String,
+
+    /// 1 when the view reads the table, 2 when it reads a view reading it, ...
+    pub depth: i32,
+
+    /// Materialized view it depends on the table through (None when direct)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// Materialized views affected when a table is dropped or changed
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct MvImpact {
+    /// `catalog.database.name`
+    pub table: String,
+
+    /// Affected views, nearest first
+    pub views: Vec<MvImpactedView>,
+}
+
+/// Refresh task run of a materialized view (from information_schema.task_runs)
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct MvRefreshRun {
+    /// Task run query ID
+    pub query_id: String,
+
+    pub database_name: String,
+
+    /// Materialized view name
+    pub name: String,
+
+    /// Refresh task name (`mv-<id>`)
+    pub task_name: String,
+
+    /// State: PENDING/RUNNING/SUCCESS/FAILED/MERGED/SKIPPED
+    pub state: String,
+
+    /// Creation time as reported by the FE
+    pub create_time: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_time: Option<String>,
+
+    /// Milliseconds from creation to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+
+    /// Partitions of the view the run refreshed
+    pub refreshed_partitions: Vec<String>,
+}
+
+/// Refresh history filter
+#[derive(Debug, Deserialize, Default)]
+pub struct MvRefreshHistoryFilter {
+    /// Database name (required when the name exists in several databases)
+    pub database: Option<String>,
+
+    /// Run state, e.g. FAILED
+    pub state: Option<String>,
+
+    /// Runs created at or after this FE time (YYYY-MM-DD HH:MM:SS)
+    pub start_time: Option<String>,
+
+    /// Runs created before this FE time (YYYY-MM-DD HH:MM:SS)
+    pub end_time: Option<String>,
+
+    /// Most recent runs returned (default 100)
+    pub limit: Option<i64>,
+}
+
+/// Refresh statistics of a materialized view
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, Default)]
+pub struct MvRefreshStats {
+    pub database_name: String,
+
+    pub name: String,
+
+    /// Finished runs (SUCCESS or FAILED)
+    pub runs: i64,
+
+    pub succeeded: i64,
+
+    pub failed: i64,
+
+    /// Failed share of the finished runs, 0 to 1
+    pub failure_rate: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_duration_ms: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success_time: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_time: Option<String>,
+
+    /// Error message of the latest failed run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Failed runs sharing the same error
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct MvRefreshErrorGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i64>,
+
+    pub error_message: String,
+
+    pub count: i64,
+
+    pub first_seen: String,
+
+    pub last_seen: String,
+
+    /// Query IDs of the failed runs, newest first
+    pub query_ids: Vec<String>,
+}
+
+/// Refresh history of a materialized view with its failures grouped by error
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MvRefreshDetail {
+    pub stats: MvRefreshStats,
+
+    /// Runs newest first
+    pub runs: Vec<MvRefreshRun>,
+
+    /// Errors of the failed runs, most frequent first
+    pub errors: Vec<MvRefreshErrorGroup>,
+}
+
+/// Refresh duration of one run
+#[derive(Debug, Serialize, ToSchema, Clone, PartialEq)]
+pub struct MvRefreshDurationPoint {
+    pub create_time: String,
+    pub duration_ms: i64,
+    pub state: String,
+}
+
+/// Finished runs and failures in one hour or day
+#[derive(Debug, Serialize, ToSchema, Clone, PartialEq)]
+pub struct MvRefreshFailureBucket {
+    /// `YYYY-MM-DD HH:00:00` or `YYYY-MM-DD`
+    pub bucket: String,
+    pub runs: i64,
+    pub failed: i64,
+    pub failure_rate: f64,
+}
+
+/// Bucket size of the failure rate trend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MvRefreshTrendInterval {
+    Hour,
+    #[default]
+    Day,
+}
+
+/// Refresh duration and failure rate of a materialized view over time
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MvRefreshTrends {
+    pub database_name: String,
+    pub name: String,
+    /// Duration of every finished run, oldest first
+    pub duration: Vec<MvRefreshDurationPoint>,
+    pub failure_rate: Vec<MvRefreshFailureBucket>,
+}
@@ -1,19 +1,41 @@
+pub mod admin_audit;
+pub mod alert;
+pub mod api_token;
 pub mod cluster;
+pub mod load_job;
 pub mod materialized_view;
 pub mod organization;
 pub mod permission;
+pub mod profile_archive;
 pub mod role;
+pub mod routine_load;
+pub mod session;
+pub mod sql_policy;
+pub mod sso;
 pub mod starrocks;
 pub mod system_function;
+pub mod two_factor;
 pub mod user;
+pub mod variable;
 
+pub use admin_audit::*;
+pub use alert::*;
+pub use api_token::*;
 pub use cluster::*;
+pub use load_job::*;
 pub use materialized_view::*;
 pub use organization::*;
 pub use permission::*;
+pub use profile_archive::*;
 pub use role::*;
+pub use routine_load::*;
+pub use session::*;
+pub use sql_policy::*;
+pub use sso::*;
 pub use starrocks::*;
 pub use system_function::*;
+pub use two_factor::*;
 pub use user::*;
+pub use variable::*;
 
 // Re-export newly added models
@@ -53,12 +53,104 @@ pub struct PermissionTree {
     pub resource: Option<String>,
     pub action: Option<String>,
     pub description: Option<String>,
+    /// Finest scope the permission can be granted at below the organization (`None`: only
+    /// organization-wide)
+    pub scope_level: Option<PermissionScopeLevel>,
     pub children: Vec<PermissionTree>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRolePermissionsRequest {
     pub permission_ids: Vec<i64>,
+    /// Grants limited to a cluster, catalog or database; replaces the role's scoped grants
+    /// when set, keeps them when omitted
+    #[serde(default)]
+    pub scoped_permissions: Option<Vec<ScopedPermissionGrant>>,
+}
+
+/// How far below the organization a permission can be scoped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionScopeLevel {
+    /// Per cluster
+    Cluster,
+    /// Per cluster, catalog or database (SQL editor and materialized views)
+    Database,
+}
+
+/// Part of a cluster a permission grant is limited to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PermissionScope {
+    pub cluster_id: i64,
+    /// None = every catalog of the cluster
+    pub catalog: Option<String>,
+    /// None = every database of the catalog
+    pub database: Option<String>,
+}
+
+impl PermissionScope {
+    /// Whether the scope covers the whole cluster
+    pub fn is_cluster_wide(&self) -> bool {
+        self.catalog.is_none() && self.database.is_none()
+    }
+
+    /// Whether the scope covers a database of a catalog of a cluster
+    pub fn covers(&self, cluster_id: i64, catalog: &str, database: &str) -> bool {
+        self.cluster_id == cluster_id
+            && self.catalog.as_deref().is_none_or(|c| c == catalog)
+            && self.database.as_deref().is_none_or(|d| d == database)
+    }
+
+    /// Casbin policy scope, a `keyMatch` pattern (`cluster:1/catalog:c/db:d/*`)
+    pub fn policy_key(&self) -> String {
+        let mut key = format!("cluster:{}/", self.cluster_id);
+        if let Some(catalog) = &self.catalog {
+            key.push_str(&format!("catalog:{}/", catalog));
+        }
+        if let Some(database) = &self.database {
+            key.push_str(&format!("db:{}/", database));
+        }
+        key.push('*');
+        key
+    }
+
+    /// Inverse of `policy_key`
+    pub fn from_policy_key(key: &str) -> Option<Self> {
+        let mut parts = key.strip_suffix("/*")?.split('/');
+        let cluster_id = parts.next()?.strip_prefix("cluster:")?.parse().ok()?;
+        let mut scope = Self { cluster_id, catalog: None, database: None };
+        for part in parts {
+            match part.split_once(':')? {
+                ("catalog", catalog) => scope.catalog = Some(catalog.to_string()),
+                ("db", database) => scope.database = Some(database.to_string()),
+                _ => return None,
+            }
+        }
+        Some(scope)
+    }
+}
+
+/// Permission granted to a role on part of a cluster only
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ScopedPermissionGrant {
+    pub permission_id: i64,
+    pub cluster_id: i64,
+    /// Requires a permission scoped at database level; None = every catalog
+    pub catalog: Option<String>,
+    /// Requires a permission scoped at database level; without a catalog the database is
+    /// one of the default catalog
+    pub database: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct ScopedPermissionResponse {
+    pub permission_id: i64,
+    pub code: String,
+    pub name: String,
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    pub catalog: Option<String>,
+    pub database: Option<String>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
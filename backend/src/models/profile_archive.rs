@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Archived profile summary (list view, without the raw profile text)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProfileArchiveItem {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub query_id: String,
+    pub query_user: Option<String>,
+    pub default_db: Option<String>,
+    pub sql_statement: Option<String>,
+    pub query_state: Option<String>,
+    pub query_start_time: Option<String>,
+    pub total_time_ms: Option<f64>,
+    pub peak_memory_bytes: Option<i64>,
+    pub performance_score: Option<f64>,
+    /// Diagnostic rule ids, most severe first
+    pub rule_ids: Vec<String>,
+    pub diagnostic_count: i64,
+    pub conclusion: Option<String>,
+    /// Compression of the stored profile text: "gzip" or "none"
+    pub compression: String,
+    /// Uncompressed profile size in bytes
+    pub raw_size: i64,
+    /// How the profile was archived: "manual" or "auto"
+    pub source: String,
+    pub archived_by: Option<i64>,
+    pub archived_at: DateTime<Utc>,
+    pub analyzed_at: Option<DateTime<Utc>>,
+}
+
+/// Archived profile with its raw profile text
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProfileArchiveDetail {
+    #[serde(flatten)]
+    pub summary: ProfileArchiveItem,
+    pub profile_content: String,
+}
+
+/// Paged archive list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProfileArchiveListResponse {
+    pub items: Vec<ProfileArchiveItem>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+/// Filters for the archive list
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProfileArchiveQuery {
+    /// Query user (exact match)
+    pub user: Option<String>,
+    /// Default database (exact match)
+    pub db: Option<String>,
+    /// Minimum total time in milliseconds
+    pub min_duration_ms: Option<f64>,
+    /// Maximum total time in milliseconds
+    pub max_duration_ms: Option<f64>,
+    /// Only profiles that triggered this diagnostic rule (e.g. "S001")
+    pub rule_id: Option<String>,
+    /// Query start time lower bound (e.g. "2025-01-01 00:00:00")
+    pub start_time: Option<String>,
+    /// Query start time upper bound
+    pub end_time: Option<String>,
+    /// Search in query id and SQL text
+    pub keyword: Option<String>,
+    /// Page number, starting from 1 (default: 1)
+    pub page: Option<i64>,
+    /// Page size (default: 20, max: 200)
+    pub page_size: Option<i64>,
+}
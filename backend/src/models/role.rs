@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 
-use crate::models::PermissionResponse;
+use crate::models::{PermissionResponse, ScopedPermissionResponse};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Role {
@@ -62,4 +62,6 @@ pub struct RoleWithPermissions {
     #[serde(flatten)]
     pub role: RoleResponse,
     pub permissions: Vec<PermissionResponse>,
+    /// Permissions granted on some clusters, catalogs or databases only
+    pub scoped_permissions: Vec<ScopedPermissionResponse>,
 }
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Routine Load job (from SHOW ROUTINE LOAD)
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct RoutineLoadJob {
+    /// Job ID
+    pub id: i64,
+
+    /// Job name
+    pub name: String,
+
+    /// Database name
+    pub database_name: String,
+
+    /// Target table
+    pub table_name: String,
+
+    /// State: NEED_SCHEDULE/RUNNING/PAUSED/STOPPED/CANCELLED/UNSTABLE
+    pub state: String,
+
+    /// Data source type (KAFKA/PULSAR)
+    pub data_source_type: String,
+
+    /// Creation time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+
+    /// Time the job was last paused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pause_time: Option<String>,
+
+    /// Time the job was stopped or cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+
+    /// Running tasks
+    pub current_task_num: i64,
+
+    /// Rows loaded since the job was created
+    pub loaded_rows: i64,
+
+    /// Rows rejected for bad data quality
+    pub error_rows: i64,
+
+    /// Rows filtered out by the WHERE clause
+    pub unselected_rows: i64,
+
+    /// Rows received from the source
+    pub total_rows: i64,
+
+    /// Bytes received from the source
+    pub received_bytes: i64,
+
+    /// Messages not consumed yet over all partitions (None when no partition offset is known)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_lag: Option<i64>,
+
+    /// Offsets and lag of each source partition
+    pub partitions: Vec<RoutineLoadPartition>,
+
+    /// Why the job last changed state, e.g. the error that paused it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason_of_state_changed: Option<String>,
+
+    /// URLs of the rows rejected by the last tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_log_urls: Option<String>,
+
+    /// SQL returning the tracking log of the rejected rows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking_sql: Option<String>,
+
+    /// Other messages, e.g. the errors of the last tasks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_msg: Option<String>,
+
+    /// Job properties (JSON text): concurrency, max error number, format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_properties: Option<String>,
+
+    /// Data source properties (JSON text): brokers, topic, partitions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_source_properties: Option<String>,
+}
+
+/// Consumption of one source partition
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct RoutineLoadPartition {
+    /// Partition ID
+    pub partition: String,
+
+    /// Offset consumed last, or OFFSET_BEGINNING/OFFSET_END/OFFSET_ZERO before consuming
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumed_offset: Option<String>,
+
+    /// Latest offset of the partition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_offset: Option<String>,
+
+    /// Messages not consumed yet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag: Option<i64>,
+}
+
+/// Routine Load task (from SHOW ROUTINE LOAD TASK)
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone)]
+pub struct RoutineLoadTask {
+    /// Task ID
+    pub task_id: String,
+
+    /// Transaction ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txn_id: Option<String>,
+
+    /// Transaction status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txn_status: Option<String>,
+
+    /// Job ID
+    pub job_id: String,
+
+    /// Creation time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_time: Option<String>,
+
+    /// Execution start time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_start_time: Option<String>,
+
+    /// Timeout (seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+
+    /// BE executing the task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub be_id: Option<String>,
+
+    /// Partitions and offsets the task consumes (JSON text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_source_properties: Option<String>,
+
+    /// Task message, e.g. the last error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Pause, resume or stop a Routine Load job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutineLoadAction {
+    Pause,
+    Resume,
+    /// Stops the job for good; a stopped job cannot be resumed
+    Stop,
+}
+
+impl RoutineLoadAction {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            RoutineLoadAction::Pause => "PAUSE",
+            RoutineLoadAction::Resume => "RESUME",
+            RoutineLoadAction::Stop => "STOP",
+        }
+    }
+}
+
+/// Filters of the Routine Load job list
+#[derive(Debug, Default, Deserialize)]
+pub struct RoutineLoadFilter {
+    /// Database name
+    pub database: Option<String>,
+
+    /// State, e.g. RUNNING or PAUSED
+    pub state: Option<String>,
+
+    /// Include STOPPED and CANCELLED jobs
+    #[serde(default)]
+    pub include_finished: bool,
+}
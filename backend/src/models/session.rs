@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Server-side login session (the refresh token itself is never returned again)
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UserSession {
+    pub id: i64,
+    pub user_id: i64,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_refreshed_at: Option<DateTime<Utc>>,
+    /// Until when the session can be refreshed
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session of the request listing it
+    pub current: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Tokens issued at login and on every refresh
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TokenResponse {
+    /// Access token sent as `Authorization: Bearer ...`
+    pub token: String,
+    /// Single-use token exchanged for new tokens at `/api/auth/refresh`
+    pub refresh_token: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: i64,
+}
+
+/// Number of sessions a revocation ended
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevokedSessions {
+    pub revoked: u64,
+}
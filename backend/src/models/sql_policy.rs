@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Statement classes a SQL policy can allow
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlStatementClass {
+    /// SELECT, SHOW, DESCRIBE, EXPLAIN, USE / SET CATALOG
+    Query,
+    /// INSERT, UPDATE, DELETE
+    Dml,
+    /// CREATE, ALTER, DROP, TRUNCATE
+    Ddl,
+    /// SET, GRANT/REVOKE, KILL, ADMIN, transactions and all other statements
+    Admin,
+}
+
+impl std::fmt::Display for SqlStatementClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqlStatementClass::Query => write!(f, "query"),
+            SqlStatementClass::Dml => write!(f, "dml"),
+            SqlStatementClass::Ddl => write!(f, "ddl"),
+            SqlStatementClass::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// SQL editor policy attached to a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleSqlPolicy {
+    pub role_id: i64,
+    pub allowed_statements: Vec<SqlStatementClass>,
+    /// Empty = any catalog
+    pub allowed_catalogs: Vec<String>,
+    /// Empty = any database
+    pub allowed_databases: Vec<String>,
+    /// Maximum rows returned per statement (None = unlimited)
+    pub max_rows: Option<i64>,
+    /// Maximum execution time per statement in seconds (None = unlimited)
+    pub max_execution_secs: Option<i64>,
+    pub updated_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateRoleSqlPolicyRequest {
+    pub allowed_statements: Vec<SqlStatementClass>,
+    #[serde(default)]
+    pub allowed_catalogs: Vec<String>,
+    #[serde(default)]
+    pub allowed_databases: Vec<String>,
+    pub max_rows: Option<i64>,
+    pub max_execution_secs: Option<i64>,
+}
+
+/// What the policy of one role allows, as statements are checked against it
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SqlPolicyRule {
+    pub role_id: i64,
+    pub allowed_statements: Vec<SqlStatementClass>,
+    /// None = any catalog
+    pub allowed_catalogs: Option<Vec<String>>,
+    /// None = any database
+    pub allowed_databases: Option<Vec<String>>,
+    pub max_execution_secs: Option<i64>,
+}
+
+impl From<RoleSqlPolicy> for SqlPolicyRule {
+    fn from(policy: RoleSqlPolicy) -> Self {
+        let any_if_empty = |names: Vec<String>| (!names.is_empty()).then_some(names);
+        Self {
+            role_id: policy.role_id,
+            allowed_statements: policy.allowed_statements,
+            allowed_catalogs: any_if_empty(policy.allowed_catalogs),
+            allowed_databases: any_if_empty(policy.allowed_databases),
+            max_execution_secs: policy.max_execution_secs,
+        }
+    }
+}
+
+/// Policy applied to a user: the policies of their roles
+///
+/// Each statement must be allowed by the policy of a single role, so the statement classes
+/// of one role never combine with the databases of another. Row and time limits take the
+/// most permissive value among the roles.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct EffectiveSqlPolicy {
+    /// False when any of the user's roles has no policy (or the user is a super admin)
+    pub restricted: bool,
+    pub rules: Vec<SqlPolicyRule>,
+    pub max_rows: Option<i64>,
+    pub max_execution_secs: Option<i64>,
+}
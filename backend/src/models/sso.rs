@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+/// Where a user authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProvider {
+    Local,
+    Ldap,
+    Oidc,
+}
+
+impl AuthProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Ldap => "ldap",
+            Self::Oidc => "oidc",
+        }
+    }
+}
+
+/// Login methods offered on the login page
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthProviders {
+    pub ldap: bool,
+    pub oidc: bool,
+}
+
+/// A directory group (or OIDC groups claim value) placing users in an organization and
+/// granting them a role
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SsoGroupMapping {
+    pub id: i64,
+    pub provider: String,
+    /// Group DN or CN (LDAP), or value of the groups claim (OIDC); matched case-insensitively
+    pub group_name: String,
+    pub organization_id: i64,
+    pub organization_name: Option<String>,
+    pub role_id: Option<i64>,
+    pub role_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSsoGroupMappingRequest {
+    /// ldap or oidc
+    pub provider: AuthProvider,
+    pub group_name: String,
+    pub organization_id: i64,
+    /// Role granted to members (system roles or roles of the organization); none only places
+    /// members in the organization
+    pub role_id: Option<i64>,
+}
+
+/// Redirect of the OIDC provider back to /api/auth/oidc/callback
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set by the provider when the user did not sign in
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
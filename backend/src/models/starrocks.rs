@@ -293,7 +293,7 @@ pub struct Session {
 }
 
 // Variable information
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
 pub struct Variable {
     pub name: String,
     pub value: String,
@@ -455,6 +455,25 @@ pub struct ProfileDetail {
     pub profile_content: String,
 }
 
+// Where a profile to compare comes from
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProfileDiffSource {
+    /// Live profile from the active cluster (SHOW PROFILELIST)
+    QueryId { query_id: String },
+    /// Archived profile of the active cluster
+    Archive { archive_id: i64 },
+    /// Uploaded profile text
+    Text { profile_text: String },
+}
+
+// Profile diff request: baseline ("before") vs target ("after")
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ProfileDiffRequest {
+    pub baseline: ProfileDiffSource,
+    pub target: ProfileDiffSource,
+}
+
 // Catalog with its databases
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CatalogWithDatabases {
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Second factor of the current user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// Unused recovery codes left
+    pub recovery_codes_remaining: i64,
+}
+
+/// Secret to enroll in an authenticator app; two-factor authentication is enabled once a
+/// code of it is confirmed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorSetup {
+    /// Base32 secret, for apps that cannot scan the QR code
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code
+    pub otpauth_url: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TwoFactorCodeRequest {
+    /// Code of the authenticator app (recovery codes are also accepted to disable or to
+    /// regenerate recovery codes)
+    pub code: String,
+}
+
+/// One-time recovery codes, shown only once
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
@@ -16,6 +16,11 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub organization_id: Option<i64>,
+    pub is_active: bool,
+    /// local, ldap or oidc
+    pub auth_provider: String,
+    /// Set while logins are refused after too many failures
+    pub locked_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -40,11 +45,18 @@ pub struct AdminCreateUserRequest {
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// Code of the authenticator app, or a recovery code, for users with two-factor
+    /// authentication (a login without it answers with code 1005)
+    #[serde(default)]
+    pub totp_code: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: i64,
     pub user: UserResponse,
 }
 
@@ -65,6 +77,8 @@ pub struct AdminUpdateUserRequest {
     pub password: Option<String>,
     pub role_ids: Option<Vec<i64>>,
     pub organization_id: Option<i64>,
+    /// Disabling a user ends their sessions and refuses their API tokens
+    pub is_active: Option<bool>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -78,6 +92,17 @@ pub struct UserResponse {
     pub organization_name: Option<String>,
     pub is_super_admin: bool,
     pub is_org_admin: bool,
+    pub is_active: bool,
+    /// local, ldap or oidc (only local users have a password here)
+    pub auth_provider: String,
+    /// Set while logins are refused after too many failures
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Whether anyone can create an account with /api/auth/register
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RegistrationSettings {
+    pub enabled: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -99,6 +124,9 @@ impl UserResponse {
             organization_name: None,
             is_super_admin,
             is_org_admin,
+            is_active: user.is_active,
+            auth_provider: user.auth_provider,
+            locked_until: user.locked_until,
         }
     }
 
@@ -118,6 +146,9 @@ impl UserResponse {
             organization_name,
             is_super_admin,
             is_org_admin,
+            is_active: user.is_active,
+            auth_provider: user.auth_provider,
+            locked_until: user.locked_until,
         }
     }
 }
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Variable change made through starrocks-admin (an update or a rollback)
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, sqlx::FromRow)]
+pub struct VariableChange {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub variable_name: String,
+
+    /// GLOBAL or SESSION
+    pub scope: String,
+
+    /// Value before the change (unset when the variable could not be read)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<String>,
+
+    pub new_value: String,
+
+    /// User who made the change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_by: Option<i64>,
+    pub changed_by_username: String,
+
+    /// Change this one rolled back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollback_of: Option<i64>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Variable change history filter
+#[derive(Debug, Deserialize, Default)]
+pub struct VariableChangeFilter {
+    /// Variable name (exact, case-insensitive)
+    pub variable: Option<String>,
+
+    /// GLOBAL or SESSION
+    pub scope: Option<String>,
+
+    /// Most recent changes returned (default 100)
+    pub limit: Option<i64>,
+}
+
+/// Snapshot of the GLOBAL variables of a cluster
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq, sqlx::FromRow)]
+pub struct VariableSnapshotSummary {
+    pub id: i64,
+    pub cluster_id: i64,
+    pub variable_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Snapshot with its variables, sorted by name
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VariableSnapshot {
+    #[serde(flatten)]
+    pub summary: VariableSnapshotSummary,
+    pub variables: Vec<super::Variable>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableDiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Variable that differs between two snapshots
+#[derive(Debug, Serialize, Deserialize, ToSchema, Clone, PartialEq)]
+pub struct VariableDiffEntry {
+    pub name: String,
+    pub kind: VariableDiffKind,
+
+    /// Value in the older snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_value: Option<String>,
+
+    /// Value in the newer snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_value: Option<String>,
+}
+
+/// Differences between two snapshots, sorted by variable name
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VariableSnapshotDiff {
+    pub from: VariableSnapshotSummary,
+    pub to: VariableSnapshotSummary,
+    pub changes: Vec<VariableDiffEntry>,
+    /// Variables with the same value in both snapshots
+    pub unchanged: i64,
+}
@@ -0,0 +1,317 @@
+// Admin Audit Service
+// Purpose: Persist the audit trail of admin actions performed through starrocks-admin
+//          (written by the admin audit middleware) and query/export it per organization
+
+use crate::models::{AdminAuditListResponse, AdminAuditLog, AdminAuditQuery, NewAdminAuditLog};
+use crate::utils::ApiResult;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Maximum stored payload length in characters
+const MAX_PAYLOAD_CHARS: usize = 4096;
+
+/// Maximum number of rows in one CSV export
+const MAX_EXPORT_ROWS: i64 = 10_000;
+
+/// Placeholder for redacted secrets
+const REDACTED: &str = "******";
+
+/// JSON keys whose values are never stored (matched case-insensitively as substrings)
+const SECRET_KEYS: &[&str] =
+    &["password", "passwd", "secret", "token", "credential", "private_key", "api_key"];
+
+/// Key name suffixes of key material (e.g. `client_key`, `private_key_pem`)
+const SECRET_KEY_SUFFIXES: &[&str] = &["_key", "pem"];
+
+/// JSON keys (exact) whose values are never stored: alert robot urls carry their access token
+const SECRET_EXACT_KEYS: &[&str] = &["url"];
+
+/// JSON keys of objects whose every value is a secret, e.g. webhook `headers`
+/// (`Authorization`, ...); the names are kept
+const SECRET_MAP_KEYS: &[&str] = &["headers"];
+
+const CSV_HEADER: &str = "id,created_at,username,organization_id,cluster_id,cluster_name,action,\
+     method,path,target,status_code,success,error_message,duration_ms,client_ip,payload";
+
+/// Bind value of a list filter
+enum FilterValue {
+    Int(i64),
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+#[derive(Clone)]
+pub struct AdminAuditService {
+    db: SqlitePool,
+}
+
+impl AdminAuditService {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// Append an audit entry
+    pub async fn record(&self, entry: NewAdminAuditLog) -> ApiResult<i64> {
+        let result = sqlx::query(
+            "INSERT INTO admin_audit_log (user_id, username, organization_id, cluster_id,
+             cluster_name, action, method, path, target, payload, status_code, success,
+             error_message, duration_ms, client_ip, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.user_id)
+        .bind(&entry.username)
+        .bind(entry.organization_id)
+        .bind(entry.cluster_id)
+        .bind(&entry.cluster_name)
+        .bind(&entry.action)
+        .bind(&entry.method)
+        .bind(&entry.path)
+        .bind(&entry.target)
+        .bind(&entry.payload)
+        .bind(entry.status_code)
+        .bind(entry.success)
+        .bind(&entry.error_message)
+        .bind(entry.duration_ms)
+        .bind(&entry.client_ip)
+        .bind(Utc::now())
+        .execute(&self.db)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// List audit entries visible to the caller, newest first
+    ///
+    /// Super admins see every organization (optionally filtered by `organization_id`);
+    /// other users only see their own organization.
+    pub async fn list(
+        &self,
+        organization_id: Option<i64>,
+        is_super_admin: bool,
+        query: &AdminAuditQuery,
+    ) -> ApiResult<AdminAuditListResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(20).clamp(1, 200);
+        let (where_clause, params) = build_filters(organization_id, is_super_admin, query);
+
+        let count_sql = format!("SELECT COUNT(*) FROM admin_audit_log WHERE {}", where_clause);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in &params {
+            count_query = match param {
+                FilterValue::Int(value) => count_query.bind(value),
+                FilterValue::Text(value) => count_query.bind(value),
+                FilterValue::Time(value) => count_query.bind(value),
+            };
+        }
+        let (total,) = count_query.fetch_one(&self.db).await?;
+
+        let items = self
+            .fetch(&where_clause, &params, page_size, (page - 1) * page_size)
+            .await?;
+
+        Ok(AdminAuditListResponse { items, total, page, page_size })
+    }
+
+    /// Export the entries matching the filters as CSV (newest first, at most 10000 rows)
+    pub async fn export_csv(
+        &self,
+        organization_id: Option<i64>,
+        is_super_admin: bool,
+        query: &AdminAuditQuery,
+    ) -> ApiResult<String> {
+        let (where_clause, params) = build_filters(organization_id, is_super_admin, query);
+        let items = self
+            .fetch(&where_clause, &params, MAX_EXPORT_ROWS, 0)
+            .await?;
+
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        for item in items {
+            let fields = [
+                item.id.to_string(),
+                item.created_at.to_rfc3339(),
+                item.username,
+                item.organization_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                item.cluster_id.map(|id| id.to_string()).unwrap_or_default(),
+                item.cluster_name.unwrap_or_default(),
+                item.action,
+                item.method,
+                item.path,
+                item.target.unwrap_or_default(),
+                item.status_code.to_string(),
+                item.success.to_string(),
+                item.error_message.unwrap_or_default(),
+                item.duration_ms.to_string(),
+                item.client_ip.unwrap_or_default(),
+                item.payload.unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
+    async fn fetch(
+        &self,
+        where_clause: &str,
+        params: &[FilterValue],
+        limit: i64,
+        offset: i64,
+    ) -> ApiResult<Vec<AdminAuditLog>> {
+        let list_sql = format!(
+            "SELECT * FROM admin_audit_log WHERE {} ORDER BY created_at DESC, id DESC
+             LIMIT ? OFFSET ?",
+            where_clause
+        );
+        let mut list_query = sqlx::query_as::<_, AdminAuditLog>(&list_sql);
+        for param in params {
+            list_query = match param {
+                FilterValue::Int(value) => list_query.bind(value),
+                FilterValue::Text(value) => list_query.bind(value),
+                FilterValue::Time(value) => list_query.bind(value),
+            };
+        }
+        Ok(list_query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.db)
+            .await?)
+    }
+}
+
+fn build_filters(
+    organization_id: Option<i64>,
+    is_super_admin: bool,
+    query: &AdminAuditQuery,
+) -> (String, Vec<FilterValue>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+
+    let organization_id = if is_super_admin { query.organization_id } else { organization_id };
+    match organization_id {
+        Some(org_id) => {
+            conditions.push("organization_id = ?");
+            params.push(FilterValue::Int(org_id));
+        },
+        // Users outside any organization only see entries without one
+        None if !is_super_admin => conditions.push("organization_id IS NULL"),
+        None => {},
+    }
+
+    if let Some(user_id) = query.user_id {
+        conditions.push("user_id = ?");
+        params.push(FilterValue::Int(user_id));
+    }
+    if let Some(username) = query.username.as_deref().filter(|u| !u.is_empty()) {
+        conditions.push("username = ?");
+        params.push(FilterValue::Text(username.to_string()));
+    }
+    if let Some(cluster_id) = query.cluster_id {
+        conditions.push("cluster_id = ?");
+        params.push(FilterValue::Int(cluster_id));
+    }
+    if let Some(action) = query.action.as_deref().filter(|a| !a.is_empty()) {
+        conditions.push("action LIKE ? ESCAPE '\\'");
+        params.push(FilterValue::Text(format!("{}%", escape_like(action))));
+    }
+    if let Some(success) = query.success {
+        conditions.push("success = ?");
+        params.push(FilterValue::Int(success as i64));
+    }
+    if let Some(start_time) = query.start_time {
+        conditions.push("created_at >= ?");
+        params.push(FilterValue::Time(start_time));
+    }
+    if let Some(end_time) = query.end_time {
+        conditions.push("created_at <= ?");
+        params.push(FilterValue::Time(end_time));
+    }
+    if let Some(keyword) = query.keyword.as_deref().filter(|k| !k.is_empty()) {
+        conditions.push(
+            "(path LIKE ? ESCAPE '\\' OR target LIKE ? ESCAPE '\\' OR payload LIKE ? ESCAPE '\\')",
+        );
+        let pattern = format!("%{}%", escape_like(keyword));
+        for _ in 0..3 {
+            params.push(FilterValue::Text(pattern.clone()));
+        }
+    }
+
+    if conditions.is_empty() {
+        conditions.push("1 = 1");
+    }
+    (conditions.join(" AND "), params)
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Request body as stored in the audit log: JSON with secrets redacted, truncated
+///
+/// Returns None for an empty body; non-JSON bodies are only recorded by size.
+pub fn redact_payload(body: &[u8]) -> Option<String> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    let payload = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        },
+        Err(_) => format!("<{} bytes, not JSON>", body.len()),
+    };
+    Some(truncate(payload))
+}
+
+fn redact_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SECRET_MAP_KEYS.contains(&key.as_str())
+                    && let Some(entries) = value.as_object_mut()
+                {
+                    for entry in entries.values_mut() {
+                        *entry = serde_json::Value::String(REDACTED.to_string());
+                    }
+                } else if is_secret_key(&key) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        },
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {},
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    SECRET_EXACT_KEYS.contains(&key)
+        || SECRET_KEYS.iter().any(|secret| key.contains(secret))
+        || SECRET_KEY_SUFFIXES
+            .iter()
+            .any(|suffix| key.ends_with(suffix))
+}
+
+fn truncate(mut payload: String) -> String {
+    if let Some((index, _)) = payload.char_indices().nth(MAX_PAYLOAD_CHARS) {
+        payload.truncate(index);
+        payload.push_str("...(truncated)");
+    }
+    payload
+}
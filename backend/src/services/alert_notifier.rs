@@ -0,0 +1,395 @@
+// Alert Notifiers
+// Purpose: Deliver alert events to notification channels (webhook, SMTP, DingTalk, Feishu, Slack)
+
+use crate::models::{AlertChannelType, AlertEvent, AlertStatus};
+use crate::utils::{ApiError, ApiResult, CredentialCipher};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A notification channel implementation
+pub trait Notifier: Send + Sync {
+    fn send<'a>(
+        &'a self,
+        event: &'a AlertEvent,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<()>> + Send + 'a>>;
+}
+
+/// Placeholder the API returns in place of channel secrets
+pub const SECRET_MASK: &str = "******";
+
+/// Config of HTTP-based channels (webhook, DingTalk, Feishu, Slack)
+///
+/// Robot URLs carry their access token, so the url and header values are stored encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpChannelConfig {
+    pub url: String,
+    /// Extra request headers (generic webhook only)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl HttpChannelConfig {
+    fn decrypt(mut self, cipher: &CredentialCipher) -> ApiResult<Self> {
+        self.url = cipher.decrypt(&self.url)?;
+        for value in self.headers.values_mut() {
+            *value = cipher.decrypt(value)?;
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Implicit TLS (usually port 465)
+    #[default]
+    Tls,
+    /// STARTTLS upgrade (usually port 587)
+    Starttls,
+    /// Plain connection, for local relays only
+    None,
+}
+
+/// Config of the SMTP channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpChannelConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Stored encrypted; never returned by the API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    465
+}
+
+/// Validate a channel config and return it normalized
+pub fn validate_channel_config(
+    channel_type: AlertChannelType,
+    config: &serde_json::Value,
+) -> ApiResult<serde_json::Value> {
+    let invalid = |e: serde_json::Error| {
+        ApiError::validation_error(format!("Invalid {:?} channel config: {}", channel_type, e))
+    };
+
+    match channel_type {
+        AlertChannelType::Smtp => {
+            let smtp: SmtpChannelConfig =
+                serde_json::from_value(config.clone()).map_err(invalid)?;
+            if smtp.host.trim().is_empty() {
+                return Err(ApiError::validation_error("SMTP host is required"));
+            }
+            if smtp.to.is_empty() {
+                return Err(ApiError::validation_error("At least one SMTP recipient is required"));
+            }
+            for address in std::iter::once(&smtp.from).chain(smtp.to.iter()) {
+                address.parse::<lettre::message::Mailbox>().map_err(|e| {
+                    ApiError::validation_error(format!(
+                        "Invalid email address '{}': {}",
+                        address, e
+                    ))
+                })?;
+            }
+            Ok(serde_json::to_value(smtp)?)
+        },
+        _ => {
+            let http: HttpChannelConfig =
+                serde_json::from_value(config.clone()).map_err(invalid)?;
+            if !(http.url.starts_with("http://") || http.url.starts_with("https://")) {
+                return Err(ApiError::validation_error("Channel url must be http(s)"));
+            }
+            Ok(serde_json::to_value(http)?)
+        },
+    }
+}
+
+/// Replace the secrets of a stored channel config with placeholders
+///
+/// HTTP urls keep their scheme and host so the channel stays recognizable.
+pub fn mask_channel_config(
+    channel_type: AlertChannelType,
+    config: &mut serde_json::Value,
+    cipher: &CredentialCipher,
+) {
+    let Some(obj) = config.as_object_mut() else {
+        return;
+    };
+    if channel_type == AlertChannelType::Smtp {
+        obj.remove("password");
+        return;
+    }
+
+    if let Some(url) = obj.get("url").and_then(|u| u.as_str()) {
+        let masked = cipher
+            .decrypt(url)
+            .ok()
+            .and_then(|url| reqwest::Url::parse(&url).ok())
+            .and_then(|url| {
+                url.host_str()
+                    .map(|host| format!("{}://{}/{}", url.scheme(), host, SECRET_MASK))
+            })
+            .unwrap_or_else(|| SECRET_MASK.to_string());
+        obj.insert("url".to_string(), serde_json::Value::String(masked));
+    }
+    if let Some(headers) = obj.get_mut("headers").and_then(|h| h.as_object_mut()) {
+        for value in headers.values_mut() {
+            *value = serde_json::Value::String(SECRET_MASK.to_string());
+        }
+    }
+}
+
+/// Seal the secrets of a stored channel config again, e.g. under a new master key
+pub fn reseal_channel_config(
+    channel_type: AlertChannelType,
+    config: &mut serde_json::Value,
+    mut reseal: impl FnMut(&str) -> ApiResult<String>,
+) -> ApiResult<()> {
+    let Some(obj) = config.as_object_mut() else {
+        return Ok(());
+    };
+    let mut reseal_value = |value: &mut serde_json::Value| -> ApiResult<()> {
+        if let Some(sealed) = value.as_str() {
+            *value = serde_json::Value::String(reseal(sealed)?);
+        }
+        Ok(())
+    };
+
+    if channel_type == AlertChannelType::Smtp {
+        if let Some(password) = obj.get_mut("password") {
+            reseal_value(password)?;
+        }
+        return Ok(());
+    }
+    if let Some(url) = obj.get_mut("url") {
+        reseal_value(url)?;
+    }
+    if let Some(headers) = obj.get_mut("headers").and_then(|h| h.as_object_mut()) {
+        for value in headers.values_mut() {
+            reseal_value(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Build a notifier from a stored channel config (secrets encrypted with `cipher`)
+pub fn build_notifier(
+    channel_type: AlertChannelType,
+    config: &serde_json::Value,
+    cipher: &CredentialCipher,
+    timeout: Duration,
+) -> ApiResult<Box<dyn Notifier>> {
+    if channel_type == AlertChannelType::Smtp {
+        let config = validate_channel_config(channel_type, config)?;
+        let mut smtp: SmtpChannelConfig = serde_json::from_value(config)?;
+        smtp.password = smtp.password.map(|p| cipher.decrypt(&p)).transpose()?;
+        return Ok(Box::new(SmtpNotifier { config: smtp, timeout }));
+    }
+
+    let http: HttpChannelConfig = serde_json::from_value(config.clone()).map_err(|e| {
+        ApiError::validation_error(format!("Invalid {:?} channel config: {}", channel_type, e))
+    })?;
+    let config = serde_json::to_value(http.decrypt(cipher)?)?;
+    let http: HttpChannelConfig =
+        serde_json::from_value(validate_channel_config(channel_type, &config)?)?;
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| ApiError::internal_error(format!("Failed to create HTTP client: {}", e)))?;
+    Ok(Box::new(HttpNotifier { channel_type, config: http, client }))
+}
+
+/// Short title and plain text body of an alert event
+fn render_text(event: &AlertEvent) -> (String, String) {
+    let title = match event.status {
+        AlertStatus::Firing => {
+            format!("[FIRING][{}] {}", event.severity, event.rule_name)
+        },
+        AlertStatus::Resolved => format!("[RESOLVED] {}", event.rule_name),
+    };
+    let body = format!(
+        "Cluster: {}\nRule: {}\nSeverity: {}\nStatus: {}\nMetric: {} = {:.2} (threshold {} {})\nTime: {}\n\n{}",
+        event.cluster_name,
+        event.rule_name,
+        event.severity,
+        event.status,
+        event.metric,
+        event.value,
+        event.comparator,
+        event.threshold,
+        event.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        event.message
+    );
+    (title, body)
+}
+
+// ========================================
+// HTTP channels
+// ========================================
+
+struct HttpNotifier {
+    channel_type: AlertChannelType,
+    config: HttpChannelConfig,
+    client: reqwest::Client,
+}
+
+impl HttpNotifier {
+    /// Request body in the format the receiving service expects
+    fn payload(&self, event: &AlertEvent) -> ApiResult<serde_json::Value> {
+        let (title, body) = render_text(event);
+        let payload = match self.channel_type {
+            AlertChannelType::Dingtalk => serde_json::json!({
+                "msgtype": "markdown",
+                "markdown": {
+                    "title": title,
+                    "text": format!("### {}\n\n{}", title, body.replace('\n', "\n\n")),
+                },
+            }),
+            AlertChannelType::Feishu => serde_json::json!({
+                "msg_type": "text",
+                "content": { "text": format!("{}\n{}", title, body) },
+            }),
+            AlertChannelType::Slack => serde_json::json!({
+                "text": format!("*{}*\n```{}```", title, body),
+            }),
+            _ => serde_json::to_value(event)?,
+        };
+        Ok(payload)
+    }
+
+    async fn post(&self, event: &AlertEvent) -> ApiResult<()> {
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .json(&self.payload(event)?);
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            // The url holds the robot token; keep it out of the recorded error
+            .map_err(|e| {
+                ApiError::internal_error(format!(
+                    "Notification request failed: {}",
+                    e.without_url()
+                ))
+            })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(ApiError::internal_error(format!(
+                "Notification endpoint returned {}: {}",
+                status,
+                truncate(&text, 200)
+            )));
+        }
+
+        // DingTalk ({"errcode": 0}) and Feishu ({"code": 0}) report failures with HTTP 200
+        if let Ok(body) = serde_json::from_str::<serde_json::Value>(&text) {
+            let code = body
+                .get("errcode")
+                .or_else(|| body.get("code"))
+                .and_then(|c| c.as_i64());
+            if let Some(code) = code.filter(|c| *c != 0) {
+                return Err(ApiError::internal_error(format!(
+                    "Notification rejected (code {}): {}",
+                    code,
+                    truncate(&text, 200)
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for HttpNotifier {
+    fn send<'a>(
+        &'a self,
+        event: &'a AlertEvent,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<()>> + Send + 'a>> {
+        Box::pin(self.post(event))
+    }
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
+
+// ========================================
+// SMTP channel
+// ========================================
+
+struct SmtpNotifier {
+    config: SmtpChannelConfig,
+    timeout: Duration,
+}
+
+impl SmtpNotifier {
+    async fn deliver(&self, event: &AlertEvent) -> ApiResult<()> {
+        let smtp_error = |e: &dyn std::fmt::Display| {
+            ApiError::internal_error(format!("SMTP delivery failed: {}", e))
+        };
+        let (title, body) = render_text(event);
+
+        let mut builder = Message::builder()
+            .from(self.config.from.parse().map_err(|e| smtp_error(&e))?)
+            .subject(title)
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.config.to {
+            builder = builder.to(to.parse().map_err(|e| smtp_error(&e))?);
+        }
+        let message = builder.body(body).map_err(|e| smtp_error(&e))?;
+
+        let host = self.config.host.as_str();
+        let mut transport = match self.config.tls {
+            SmtpTls::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(|e| smtp_error(&e))?
+            },
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| smtp_error(&e))?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(self.config.port)
+        .timeout(Some(self.timeout));
+
+        if let Some(username) = &self.config.username {
+            let password = self.config.password.clone().unwrap_or_default();
+            transport = transport.credentials(Credentials::new(username.clone(), password));
+        }
+
+        transport
+            .build()
+            .send(message)
+            .await
+            .map_err(|e| smtp_error(&e))?;
+        Ok(())
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn send<'a>(
+        &'a self,
+        event: &'a AlertEvent,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<()>> + Send + 'a>> {
+        Box::pin(self.deliver(event))
+    }
+}
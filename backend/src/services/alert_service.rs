@@ -0,0 +1,957 @@
+// Alert Service
+// Purpose: User-defined alert rules evaluated against each metrics snapshot, with
+//          firing/resolved state, silencing, history and channel notifications
+
+use crate::config::AlertConfig;
+use crate::models::{
+    AlertChannel, AlertChannelType, AlertComparator, AlertEvent, AlertHistoryItem,
+    AlertHistoryListResponse, AlertHistoryQuery, AlertMetricInfo, AlertRule, AlertSeverity,
+    AlertState, AlertStatus, Cluster, CreateAlertChannelRequest, CreateAlertRuleRequest,
+    UpdateAlertChannelRequest, UpdateAlertRuleRequest,
+};
+use crate::services::MetricsSnapshot;
+use crate::services::alert_notifier::{
+    SECRET_MASK, build_notifier, mask_channel_config, validate_channel_config,
+};
+use crate::services::mysql_pool_manager::MySQLPoolManager;
+use crate::utils::{ApiError, ApiResult, CredentialCipher};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Metrics that alert rules can watch: (name, description)
+const ALERT_METRICS: &[(&str, &str)] = &[
+    ("qps", "Queries per second"),
+    ("rps", "Requests per second"),
+    ("query_latency_p50", "Query latency P50 (ms)"),
+    ("query_latency_p95", "Query latency P95 (ms)"),
+    ("query_latency_p99", "Query latency P99 (ms)"),
+    ("query_error", "Failed queries (cumulative)"),
+    ("query_timeout", "Timed out queries (cumulative)"),
+    ("backend_alive", "Alive BE/CN nodes"),
+    ("backend_offline", "Offline BE/CN nodes"),
+    ("frontend_alive", "Alive FE nodes"),
+    ("frontend_offline", "Offline FE nodes"),
+    ("avg_cpu_usage", "Average BE/CN CPU usage (%)"),
+    ("avg_memory_usage", "Average BE/CN memory usage (%)"),
+    ("disk_usage_pct", "Highest BE/CN disk usage (%)"),
+    ("disk_used_bytes", "Disk used on the fullest BE/CN (bytes)"),
+    ("tablet_count", "Total tablets"),
+    ("max_compaction_score", "Max tablet compaction score"),
+    ("txn_failed_total", "Failed transactions (cumulative)"),
+    ("routine_load_paused", "Paused Routine Load jobs"),
+    ("routine_load_max_lag", "Largest Routine Load lag (messages)"),
+    ("jvm_heap_usage_pct", "FE JVM heap usage (%)"),
+    ("jvm_thread_count", "FE JVM threads"),
+    ("network_send_rate", "BE network send rate (bytes/s)"),
+    ("network_receive_rate", "BE network receive rate (bytes/s)"),
+    ("io_read_rate", "BE disk read rate (bytes/s)"),
+    ("io_write_rate", "BE disk write rate (bytes/s)"),
+];
+
+/// Current value of an alert metric in a snapshot
+fn snapshot_metric(snapshot: &MetricsSnapshot, metric: &str) -> Option<f64> {
+    let value = match metric {
+        "qps" => snapshot.qps,
+        "rps" => snapshot.rps,
+        "query_latency_p50" => snapshot.query_latency_p50,
+        "query_latency_p95" => snapshot.query_latency_p95,
+        "query_latency_p99" => snapshot.query_latency_p99,
+        "query_error" => snapshot.query_error as f64,
+        "query_timeout" => snapshot.query_timeout as f64,
+        "backend_alive" => snapshot.backend_alive as f64,
+        "backend_offline" => (snapshot.backend_total - snapshot.backend_alive) as f64,
+        "frontend_alive" => snapshot.frontend_alive as f64,
+        "frontend_offline" => (snapshot.frontend_total - snapshot.frontend_alive) as f64,
+        "avg_cpu_usage" => snapshot.avg_cpu_usage,
+        "avg_memory_usage" => snapshot.avg_memory_usage,
+        "disk_usage_pct" => snapshot.disk_usage_pct,
+        "disk_used_bytes" => snapshot.disk_used_bytes as f64,
+        "tablet_count" => snapshot.tablet_count as f64,
+        "max_compaction_score" => snapshot.max_compaction_score,
+        "txn_failed_total" => snapshot.txn_failed_total as f64,
+        "routine_load_paused" => snapshot.routine_load_paused as f64,
+        "routine_load_max_lag" => snapshot.routine_load_max_lag as f64,
+        "jvm_heap_usage_pct" => snapshot.jvm_heap_usage_pct,
+        "jvm_thread_count" => snapshot.jvm_thread_count as f64,
+        "network_send_rate" => snapshot.network_send_rate,
+        "network_receive_rate" => snapshot.network_receive_rate,
+        "io_read_rate" => snapshot.io_read_rate,
+        "io_write_rate" => snapshot.io_write_rate,
+        _ => return None,
+    };
+    Some(value)
+}
+
+const RULE_COLUMNS: &str = "id, cluster_id, name, description, metric, comparator, threshold, \
+     duration_secs, severity, enabled, channel_ids, state, pending_since, last_value, \
+     last_evaluated_at, silenced_until, created_by, created_at, updated_at";
+
+#[derive(Debug, sqlx::FromRow)]
+struct AlertRuleRow {
+    id: i64,
+    cluster_id: i64,
+    name: String,
+    description: Option<String>,
+    metric: String,
+    comparator: AlertComparator,
+    threshold: f64,
+    duration_secs: i64,
+    severity: AlertSeverity,
+    enabled: bool,
+    channel_ids: String,
+    state: AlertState,
+    pending_since: Option<DateTime<Utc>>,
+    last_value: Option<f64>,
+    last_evaluated_at: Option<DateTime<Utc>>,
+    silenced_until: Option<DateTime<Utc>>,
+    created_by: Option<i64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<AlertRuleRow> for AlertRule {
+    fn from(row: AlertRuleRow) -> Self {
+        Self {
+            id: row.id,
+            cluster_id: row.cluster_id,
+            name: row.name,
+            description: row.description,
+            metric: row.metric,
+            comparator: row.comparator,
+            threshold: row.threshold,
+            duration_secs: row.duration_secs,
+            severity: row.severity,
+            enabled: row.enabled,
+            channel_ids: serde_json::from_str(&row.channel_ids).unwrap_or_default(),
+            state: row.state,
+            pending_since: row.pending_since,
+            last_value: row.last_value,
+            last_evaluated_at: row.last_evaluated_at,
+            silenced_until: row.silenced_until,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AlertChannelRow {
+    id: i64,
+    cluster_id: i64,
+    name: String,
+    channel_type: AlertChannelType,
+    config: String,
+    enabled: bool,
+    created_by: Option<i64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl AlertChannelRow {
+    fn config_value(&self) -> serde_json::Value {
+        serde_json::from_str(&self.config).unwrap_or_else(|_| serde_json::json!({}))
+    }
+
+    /// API view with secrets masked
+    fn into_masked(self, cipher: &CredentialCipher) -> AlertChannel {
+        let mut config = self.config_value();
+        mask_channel_config(self.channel_type, &mut config, cipher);
+        AlertChannel {
+            id: self.id,
+            cluster_id: self.cluster_id,
+            name: self.name,
+            channel_type: self.channel_type,
+            config,
+            enabled: self.enabled,
+            created_by: self.created_by,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AlertService {
+    db: SqlitePool,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    config: AlertConfig,
+}
+
+impl AlertService {
+    /// Create a new AlertService
+    ///
+    /// Channel secrets are encrypted with the credential cipher of `mysql_pool_manager`.
+    pub fn new(
+        db: SqlitePool,
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+        config: AlertConfig,
+    ) -> Self {
+        Self { db, mysql_pool_manager, config }
+    }
+
+    /// Metrics that can be used in alert rules
+    pub fn list_metrics(&self) -> Vec<AlertMetricInfo> {
+        ALERT_METRICS
+            .iter()
+            .map(|(name, description)| AlertMetricInfo {
+                name: name.to_string(),
+                description: description.to_string(),
+            })
+            .collect()
+    }
+
+    // ========================================
+    // Rules
+    // ========================================
+
+    pub async fn list_rules(&self, cluster_id: i64) -> ApiResult<Vec<AlertRule>> {
+        let rows: Vec<AlertRuleRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM alert_rules WHERE cluster_id = ? ORDER BY id",
+            RULE_COLUMNS
+        ))
+        .bind(cluster_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_rule(&self, cluster_id: i64, id: i64) -> ApiResult<AlertRule> {
+        let row: Option<AlertRuleRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM alert_rules WHERE id = ? AND cluster_id = ?",
+            RULE_COLUMNS
+        ))
+        .bind(id)
+        .bind(cluster_id)
+        .fetch_optional(&self.db)
+        .await?;
+        row.map(Into::into)
+            .ok_or_else(|| ApiError::not_found(format!("Alert rule {} not found", id)))
+    }
+
+    pub async fn create_rule(
+        &self,
+        cluster_id: i64,
+        req: CreateAlertRuleRequest,
+        created_by: Option<i64>,
+    ) -> ApiResult<AlertRule> {
+        self.validate_rule(
+            cluster_id,
+            &req.name,
+            &req.metric,
+            req.threshold,
+            req.duration_secs,
+            &req.channel_ids,
+        )
+        .await?;
+
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO alert_rules (cluster_id, name, description, metric, comparator, threshold,
+             duration_secs, severity, enabled, channel_ids, created_by, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(cluster_id)
+        .bind(req.name.trim())
+        .bind(&req.description)
+        .bind(&req.metric)
+        .bind(req.comparator)
+        .bind(req.threshold)
+        .bind(req.duration_secs)
+        .bind(req.severity)
+        .bind(req.enabled)
+        .bind(serde_json::to_string(&req.channel_ids)?)
+        .bind(created_by)
+        .bind(now)
+        .bind(now)
+        .execute(&self.db)
+        .await
+        .map_err(|e| unique_violation(e, "Alert rule", &req.name))?;
+
+        tracing::info!("Created alert rule '{}' for cluster {}", req.name, cluster_id);
+        self.get_rule(cluster_id, result.last_insert_rowid()).await
+    }
+
+    pub async fn update_rule(
+        &self,
+        cluster_id: i64,
+        id: i64,
+        req: UpdateAlertRuleRequest,
+    ) -> ApiResult<AlertRule> {
+        let mut rule = self.get_rule(cluster_id, id).await?;
+
+        if let Some(name) = req.name {
+            rule.name = name;
+        }
+        if let Some(description) = req.description {
+            rule.description = Some(description);
+        }
+        if let Some(metric) = req.metric {
+            rule.metric = metric;
+        }
+        if let Some(comparator) = req.comparator {
+            rule.comparator = comparator;
+        }
+        if let Some(threshold) = req.threshold {
+            rule.threshold = threshold;
+        }
+        if let Some(duration_secs) = req.duration_secs {
+            rule.duration_secs = duration_secs;
+        }
+        if let Some(severity) = req.severity {
+            rule.severity = severity;
+        }
+        if let Some(channel_ids) = req.channel_ids {
+            rule.channel_ids = channel_ids;
+        }
+        if let Some(enabled) = req.enabled {
+            // A disabled rule is no longer evaluated, so it cannot stay pending/firing
+            if !enabled {
+                rule.state = AlertState::Ok;
+                rule.pending_since = None;
+            }
+            rule.enabled = enabled;
+        }
+
+        self.validate_rule(
+            cluster_id,
+            &rule.name,
+            &rule.metric,
+            rule.threshold,
+            rule.duration_secs,
+            &rule.channel_ids,
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE alert_rules SET name = ?, description = ?, metric = ?, comparator = ?,
+             threshold = ?, duration_secs = ?, severity = ?, enabled = ?, channel_ids = ?,
+             state = ?, pending_since = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(rule.name.trim())
+        .bind(&rule.description)
+        .bind(&rule.metric)
+        .bind(rule.comparator)
+        .bind(rule.threshold)
+        .bind(rule.duration_secs)
+        .bind(rule.severity)
+        .bind(rule.enabled)
+        .bind(serde_json::to_string(&rule.channel_ids)?)
+        .bind(rule.state)
+        .bind(rule.pending_since)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| unique_violation(e, "Alert rule", &rule.name))?;
+
+        self.get_rule(cluster_id, id).await
+    }
+
+    pub async fn delete_rule(&self, cluster_id: i64, id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM alert_rules WHERE id = ? AND cluster_id = ?")
+            .bind(id)
+            .bind(cluster_id)
+            .execute(&self.db)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Alert rule {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Suppress notifications of a rule for `duration_secs`; state and history are still tracked
+    pub async fn silence_rule(
+        &self,
+        cluster_id: i64,
+        id: i64,
+        duration_secs: i64,
+    ) -> ApiResult<AlertRule> {
+        if duration_secs <= 0 {
+            return Err(ApiError::validation_error("Silence duration must be > 0"));
+        }
+        let until = Utc::now() + chrono::Duration::seconds(duration_secs);
+        self.set_silenced_until(cluster_id, id, Some(until)).await
+    }
+
+    pub async fn unsilence_rule(&self, cluster_id: i64, id: i64) -> ApiResult<AlertRule> {
+        self.set_silenced_until(cluster_id, id, None).await
+    }
+
+    async fn set_silenced_until(
+        &self,
+        cluster_id: i64,
+        id: i64,
+        until: Option<DateTime<Utc>>,
+    ) -> ApiResult<AlertRule> {
+        let result = sqlx::query(
+            "UPDATE alert_rules SET silenced_until = ?, updated_at = ? WHERE id = ? AND cluster_id = ?",
+        )
+        .bind(until)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(cluster_id)
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Alert rule {} not found", id)));
+        }
+        self.get_rule(cluster_id, id).await
+    }
+
+    /// Currently firing rules of a cluster
+    pub async fn firing_rules(&self, cluster_id: i64) -> ApiResult<Vec<AlertRule>> {
+        let rows: Vec<AlertRuleRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM alert_rules WHERE cluster_id = ? AND enabled = 1 AND state = 'firing'
+             ORDER BY id",
+            RULE_COLUMNS
+        ))
+        .bind(cluster_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn validate_rule(
+        &self,
+        cluster_id: i64,
+        name: &str,
+        metric: &str,
+        threshold: f64,
+        duration_secs: i64,
+        channel_ids: &[i64],
+    ) -> ApiResult<()> {
+        if name.trim().is_empty() {
+            return Err(ApiError::validation_error("Alert rule name is required"));
+        }
+        if !ALERT_METRICS.iter().any(|(m, _)| *m == metric) {
+            return Err(ApiError::validation_error(format!("Unknown alert metric: {}", metric)));
+        }
+        if !threshold.is_finite() {
+            return Err(ApiError::validation_error("Threshold must be a finite number"));
+        }
+        if duration_secs < 0 {
+            return Err(ApiError::validation_error("duration_secs must be >= 0"));
+        }
+
+        for channel_id in channel_ids {
+            let exists: Option<(i64,)> =
+                sqlx::query_as("SELECT id FROM alert_channels WHERE id = ? AND cluster_id = ?")
+                    .bind(channel_id)
+                    .bind(cluster_id)
+                    .fetch_optional(&self.db)
+                    .await?;
+            if exists.is_none() {
+                return Err(ApiError::validation_error(format!(
+                    "Alert channel {} not found",
+                    channel_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // ========================================
+    // Channels
+    // ========================================
+
+    pub async fn list_channels(&self, cluster_id: i64) -> ApiResult<Vec<AlertChannel>> {
+        let rows: Vec<AlertChannelRow> =
+            sqlx::query_as("SELECT * FROM alert_channels WHERE cluster_id = ? ORDER BY id")
+                .bind(cluster_id)
+                .fetch_all(&self.db)
+                .await?;
+        let cipher = self.mysql_pool_manager.cipher();
+        Ok(rows
+            .into_iter()
+            .map(|row| row.into_masked(cipher))
+            .collect())
+    }
+
+    async fn get_channel_row(&self, cluster_id: i64, id: i64) -> ApiResult<AlertChannelRow> {
+        let row: Option<AlertChannelRow> =
+            sqlx::query_as("SELECT * FROM alert_channels WHERE id = ? AND cluster_id = ?")
+                .bind(id)
+                .bind(cluster_id)
+                .fetch_optional(&self.db)
+                .await?;
+        row.ok_or_else(|| ApiError::not_found(format!("Alert channel {} not found", id)))
+    }
+
+    pub async fn get_channel(&self, cluster_id: i64, id: i64) -> ApiResult<AlertChannel> {
+        Ok(self
+            .get_channel_row(cluster_id, id)
+            .await?
+            .into_masked(self.mysql_pool_manager.cipher()))
+    }
+
+    pub async fn create_channel(
+        &self,
+        cluster_id: i64,
+        req: CreateAlertChannelRequest,
+        created_by: Option<i64>,
+    ) -> ApiResult<AlertChannel> {
+        if req.name.trim().is_empty() {
+            return Err(ApiError::validation_error("Alert channel name is required"));
+        }
+        let config = self.seal_config(req.channel_type, &req.config, None)?;
+
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO alert_channels (cluster_id, name, channel_type, config, enabled,
+             created_by, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(cluster_id)
+        .bind(req.name.trim())
+        .bind(req.channel_type)
+        .bind(config.to_string())
+        .bind(req.enabled)
+        .bind(created_by)
+        .bind(now)
+        .bind(now)
+        .execute(&self.db)
+        .await
+        .map_err(|e| unique_violation(e, "Alert channel", &req.name))?;
+
+        self.get_channel(cluster_id, result.last_insert_rowid())
+            .await
+    }
+
+    pub async fn update_channel(
+        &self,
+        cluster_id: i64,
+        id: i64,
+        req: UpdateAlertChannelRequest,
+    ) -> ApiResult<AlertChannel> {
+        let current = self.get_channel_row(cluster_id, id).await?;
+
+        let name = req.name.unwrap_or_else(|| current.name.clone());
+        if name.trim().is_empty() {
+            return Err(ApiError::validation_error("Alert channel name is required"));
+        }
+        let config = match req.config {
+            Some(config) => {
+                self.seal_config(current.channel_type, &config, Some(&current.config_value()))?
+            },
+            None => current.config_value(),
+        };
+        let enabled = req.enabled.unwrap_or(current.enabled);
+
+        sqlx::query(
+            "UPDATE alert_channels SET name = ?, config = ?, enabled = ?, updated_at = ?
+             WHERE id = ?",
+        )
+        .bind(name.trim())
+        .bind(config.to_string())
+        .bind(enabled)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.db)
+        .await
+        .map_err(|e| unique_violation(e, "Alert channel", &name))?;
+
+        self.get_channel(cluster_id, id).await
+    }
+
+    /// Delete a channel and detach it from the cluster's rules
+    pub async fn delete_channel(&self, cluster_id: i64, id: i64) -> ApiResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query("DELETE FROM alert_channels WHERE id = ? AND cluster_id = ?")
+            .bind(id)
+            .bind(cluster_id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Alert channel {} not found", id)));
+        }
+
+        sqlx::query(
+            "UPDATE alert_rules SET channel_ids = (
+                SELECT COALESCE(json_group_array(value), '[]') FROM json_each(alert_rules.channel_ids)
+                WHERE value != ?
+             )
+             WHERE cluster_id = ? AND EXISTS (
+                SELECT 1 FROM json_each(alert_rules.channel_ids) WHERE value = ?
+             )",
+        )
+        .bind(id)
+        .bind(cluster_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Send a test notification through a channel
+    pub async fn test_channel(&self, cluster: &Cluster, id: i64) -> ApiResult<()> {
+        let channel = self.get_channel_row(cluster.id, id).await?;
+        let event = AlertEvent {
+            rule_id: 0,
+            rule_name: "Test notification".to_string(),
+            cluster_id: cluster.id,
+            cluster_name: cluster.name.clone(),
+            metric: "test".to_string(),
+            comparator: AlertComparator::Gt,
+            threshold: 0.0,
+            value: 1.0,
+            severity: AlertSeverity::Info,
+            status: AlertStatus::Firing,
+            message: format!("Test notification from channel '{}'", channel.name),
+            timestamp: Utc::now(),
+        };
+
+        build_notifier(
+            channel.channel_type,
+            &channel.config_value(),
+            self.mysql_pool_manager.cipher(),
+            self.notify_timeout(),
+        )?
+        .send(&event)
+        .await
+    }
+
+    /// Validate a channel config and encrypt its secrets
+    ///
+    /// When updating, an omitted SMTP password and masked url or header values keep the
+    /// previous (encrypted) ones.
+    fn seal_config(
+        &self,
+        channel_type: AlertChannelType,
+        config: &serde_json::Value,
+        previous: Option<&serde_json::Value>,
+    ) -> ApiResult<serde_json::Value> {
+        let mut config = validate_channel_config(channel_type, config)?;
+        let cipher = self.mysql_pool_manager.cipher();
+        let Some(obj) = config.as_object_mut() else {
+            return Ok(config);
+        };
+
+        if channel_type == AlertChannelType::Smtp {
+            match obj.get("password").and_then(|p| p.as_str()) {
+                Some(password) => {
+                    let sealed = cipher.encrypt(password)?;
+                    obj.insert("password".to_string(), serde_json::Value::String(sealed));
+                },
+                None => {
+                    if let Some(old) = previous.and_then(|p| p.get("password")) {
+                        obj.insert("password".to_string(), old.clone());
+                    }
+                },
+            }
+            return Ok(config);
+        }
+
+        let keep = |old: Option<&serde_json::Value>, what: &str| {
+            old.cloned().ok_or_else(|| {
+                ApiError::validation_error(format!(
+                    "Channel {} is masked, provide the full value",
+                    what
+                ))
+            })
+        };
+
+        let url = obj.get("url").and_then(|u| u.as_str()).unwrap_or_default();
+        let url = if url.ends_with(SECRET_MASK) {
+            keep(previous.and_then(|p| p.get("url")), "url")?
+        } else {
+            serde_json::Value::String(cipher.encrypt(url)?)
+        };
+        obj.insert("url".to_string(), url);
+
+        if let Some(headers) = obj.get_mut("headers").and_then(|h| h.as_object_mut()) {
+            for (name, value) in headers.iter_mut() {
+                let plain = value.as_str().unwrap_or_default();
+                *value = if plain == SECRET_MASK {
+                    let old = previous
+                        .and_then(|p| p.get("headers"))
+                        .and_then(|h| h.get(name));
+                    keep(old, &format!("header '{}'", name))?
+                } else {
+                    serde_json::Value::String(cipher.encrypt(plain)?)
+                };
+            }
+        }
+        Ok(config)
+    }
+
+    fn notify_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.notify_timeout_secs)
+    }
+
+    // ========================================
+    // History
+    // ========================================
+
+    pub async fn list_history(
+        &self,
+        cluster_id: i64,
+        query: &AlertHistoryQuery,
+    ) -> ApiResult<AlertHistoryListResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(20).clamp(1, 200);
+
+        let mut conditions = vec!["cluster_id = ?"];
+        let mut params: Vec<String> = vec![cluster_id.to_string()];
+        if let Some(rule_id) = query.rule_id {
+            conditions.push("rule_id = ?");
+            params.push(rule_id.to_string());
+        }
+        if let Some(status) = query.status {
+            conditions.push("status = ?");
+            params.push(status.to_string());
+        }
+        if let Some(severity) = query.severity {
+            conditions.push("severity = ?");
+            params.push(severity.to_string());
+        }
+        let where_clause = conditions.join(" AND ");
+
+        let count_sql = format!("SELECT COUNT(*) FROM alert_history WHERE {}", where_clause);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let (total,) = count_query.fetch_one(&self.db).await?;
+
+        let list_sql = format!(
+            "SELECT * FROM alert_history WHERE {} ORDER BY created_at DESC, id DESC
+             LIMIT ? OFFSET ?",
+            where_clause
+        );
+        let mut list_query = sqlx::query_as::<_, AlertHistoryItem>(&list_sql);
+        for param in &params {
+            list_query = list_query.bind(param);
+        }
+        let items = list_query
+            .bind(page_size)
+            .bind((page - 1) * page_size)
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(AlertHistoryListResponse { items, total, page, page_size })
+    }
+
+    /// Delete alert history older than the retention period
+    pub async fn cleanup_history(&self) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.history_retention_days);
+        let result = sqlx::query("DELETE FROM alert_history WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ========================================
+    // Evaluation
+    // ========================================
+
+    /// Evaluate the cluster's enabled rules against a new metrics snapshot
+    ///
+    /// Returns the firing/resolved events raised by this snapshot.
+    pub async fn evaluate(
+        &self,
+        cluster: &Cluster,
+        snapshot: &MetricsSnapshot,
+    ) -> ApiResult<Vec<AlertEvent>> {
+        let rows: Vec<AlertRuleRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM alert_rules WHERE cluster_id = ? AND enabled = 1 ORDER BY id",
+            RULE_COLUMNS
+        ))
+        .bind(cluster.id)
+        .fetch_all(&self.db)
+        .await?;
+
+        let now = snapshot.collected_at;
+        let mut events = Vec::new();
+
+        for rule in rows.into_iter().map(AlertRule::from) {
+            let Some(value) = snapshot_metric(snapshot, &rule.metric) else {
+                tracing::warn!("Alert rule {} uses unknown metric {}", rule.id, rule.metric);
+                continue;
+            };
+            let breached = rule.comparator.matches(value, rule.threshold);
+            let window = chrono::Duration::seconds(rule.duration_secs);
+
+            let (state, pending_since, event) = match (rule.state, breached) {
+                (AlertState::Ok, true) if rule.duration_secs == 0 => {
+                    (AlertState::Firing, None, Some(AlertStatus::Firing))
+                },
+                (AlertState::Ok, true) => (AlertState::Pending, Some(now), None),
+                (AlertState::Pending, true) => {
+                    let since = rule.pending_since.unwrap_or(now);
+                    if now - since >= window {
+                        (AlertState::Firing, None, Some(AlertStatus::Firing))
+                    } else {
+                        (AlertState::Pending, Some(since), None)
+                    }
+                },
+                (AlertState::Firing, true) => (AlertState::Firing, None, None),
+                (AlertState::Firing, false) => (AlertState::Ok, None, Some(AlertStatus::Resolved)),
+                (_, false) => (AlertState::Ok, None, None),
+            };
+
+            sqlx::query(
+                "UPDATE alert_rules SET state = ?, pending_since = ?, last_value = ?,
+                 last_evaluated_at = ? WHERE id = ?",
+            )
+            .bind(state)
+            .bind(pending_since)
+            .bind(value)
+            .bind(now)
+            .bind(rule.id)
+            .execute(&self.db)
+            .await?;
+
+            if let Some(status) = event {
+                events.push(self.raise(cluster, &rule, status, value, now).await?);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Record a firing/resolved transition and notify the rule's channels unless silenced
+    async fn raise(
+        &self,
+        cluster: &Cluster,
+        rule: &AlertRule,
+        status: AlertStatus,
+        value: f64,
+        now: DateTime<Utc>,
+    ) -> ApiResult<AlertEvent> {
+        let message = match status {
+            AlertStatus::Firing => {
+                format!("{} is {:.2}, {} {}", rule.metric, value, rule.comparator, rule.threshold)
+            },
+            AlertStatus::Resolved => format!("{} is back to {:.2}", rule.metric, value),
+        };
+        let event = AlertEvent {
+            rule_id: rule.id,
+            rule_name: rule.name.clone(),
+            cluster_id: cluster.id,
+            cluster_name: cluster.name.clone(),
+            metric: rule.metric.clone(),
+            comparator: rule.comparator,
+            threshold: rule.threshold,
+            value,
+            severity: rule.severity,
+            status,
+            message,
+            timestamp: now,
+        };
+
+        let silenced = rule.silenced_until.is_some_and(|until| until > now);
+
+        tracing::info!(
+            "Alert {} for rule '{}' on cluster {} (value={:.2}, silenced={})",
+            status,
+            rule.name,
+            cluster.id,
+            value,
+            silenced
+        );
+
+        let result = sqlx::query(
+            "INSERT INTO alert_history (cluster_id, rule_id, rule_name, metric, comparator,
+             threshold, value, severity, status, message, silenced, notified, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?)",
+        )
+        .bind(cluster.id)
+        .bind(rule.id)
+        .bind(&rule.name)
+        .bind(&rule.metric)
+        .bind(rule.comparator)
+        .bind(rule.threshold)
+        .bind(value)
+        .bind(rule.severity)
+        .bind(status)
+        .bind(&event.message)
+        .bind(silenced)
+        .bind(now)
+        .execute(&self.db)
+        .await?;
+
+        // Channels may each take up to notify_timeout; deliver outside the collection tick
+        if !silenced && !rule.channel_ids.is_empty() {
+            let service = self.clone();
+            let history_id = result.last_insert_rowid();
+            let cluster_id = cluster.id;
+            let channel_ids = rule.channel_ids.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                let (notified, error) = service.notify(cluster_id, &channel_ids, &event).await;
+                let recorded = sqlx::query(
+                    "UPDATE alert_history SET notified = ?, notification_error = ? WHERE id = ?",
+                )
+                .bind(notified)
+                .bind(&error)
+                .bind(history_id)
+                .execute(&service.db)
+                .await;
+                if let Err(e) = recorded {
+                    tracing::warn!("Failed to record delivery of alert {}: {}", history_id, e);
+                }
+            });
+        }
+
+        Ok(event)
+    }
+
+    /// Deliver an event to the enabled channels; returns (any delivered, joined errors)
+    async fn notify(
+        &self,
+        cluster_id: i64,
+        channel_ids: &[i64],
+        event: &AlertEvent,
+    ) -> (bool, Option<String>) {
+        let mut delivered = false;
+        let mut errors = Vec::new();
+
+        for channel_id in channel_ids {
+            let channel = match self.get_channel_row(cluster_id, *channel_id).await {
+                Ok(channel) if channel.enabled => channel,
+                Ok(_) => continue,
+                Err(e) => {
+                    errors.push(format!("channel {}: {}", channel_id, e));
+                    continue;
+                },
+            };
+
+            let result = match build_notifier(
+                channel.channel_type,
+                &channel.config_value(),
+                self.mysql_pool_manager.cipher(),
+                self.notify_timeout(),
+            ) {
+                Ok(notifier) => notifier.send(event).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => delivered = true,
+                Err(e) => {
+                    tracing::warn!("Alert notification via '{}' failed: {}", channel.name, e);
+                    errors.push(format!("{}: {}", channel.name, e));
+                },
+            }
+        }
+
+        let error = if errors.is_empty() { None } else { Some(errors.join("; ")) };
+        (delivered, error)
+    }
+}
+
+/// Map UNIQUE(cluster_id, name) violations to a validation error
+fn unique_violation(err: sqlx::Error, what: &str, name: &str) -> ApiError {
+    if let sqlx::Error::Database(db_err) = &err
+        && db_err.message().contains("UNIQUE")
+    {
+        return ApiError::validation_error(format!("{} '{}' already exists", what, name));
+    }
+    err.into()
+}
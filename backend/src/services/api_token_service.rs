@@ -0,0 +1,379 @@
+// API Token Service
+// Purpose: Long-lived, revocable API tokens acting as a user (personal access tokens and
+//          service account tokens), limited to a subset of the user's permissions and
+//          optionally to some clusters. Only the SHA-256 hash of a token is stored.
+
+use crate::middleware::OrgContext;
+use crate::models::{ApiToken, CreateApiTokenRequest, CreatedApiToken};
+use crate::services::casbin_service::CasbinService;
+use crate::utils::crypto::{random_hex, sha256_hex};
+use crate::utils::{ApiError, ApiResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::{BTreeSet, HashSet};
+
+/// Prefix of every API token, telling it apart from a login JWT
+pub const API_TOKEN_PREFIX: &str = "sra_";
+
+/// Random bytes in a token (hex-encoded after the prefix)
+const TOKEN_BYTES: usize = 32;
+
+/// Characters of the token kept to recognize it (prefix + 8 hex digits)
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// `last_used_at` is written at most once per this many seconds per token
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+const MAX_NAME_LEN: usize = 100;
+
+const TOKEN_COLUMNS: &str = "id, user_id, name, token_prefix, scopes, cluster_ids, expires_at, \
+     last_used_at, revoked_at, created_by, created_at";
+
+#[derive(Debug, sqlx::FromRow)]
+struct ApiTokenRow {
+    id: i64,
+    user_id: i64,
+    name: String,
+    token_prefix: String,
+    scopes: Option<String>,
+    cluster_ids: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    created_by: Option<i64>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<ApiTokenRow> for ApiToken {
+    fn from(row: ApiTokenRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            token_prefix: row.token_prefix,
+            scopes: row
+                .scopes
+                .and_then(|scopes| serde_json::from_str(&scopes).ok()),
+            cluster_ids: row
+                .cluster_ids
+                .and_then(|ids| serde_json::from_str(&ids).ok()),
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            created_by: row.created_by,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Caller authenticated with an API token
+#[derive(Debug, Clone)]
+pub struct ApiTokenPrincipal {
+    pub token_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    /// (resource, action) pairs the token is limited to (`None`: all of the user's)
+    pub scopes: Option<HashSet<(String, String)>>,
+    /// Clusters the token is limited to (`None`: any cluster of the user)
+    pub cluster_ids: Option<Vec<i64>>,
+}
+
+impl ApiTokenPrincipal {
+    /// Whether the token's scopes cover a permission (the user must still hold it)
+    pub fn allows(&self, resource: &str, action: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&(resource.to_string(), action.to_string())))
+    }
+}
+
+/// (token id, user id, username, expires_at, revoked_at)
+type TokenOwnerRow = (i64, i64, String, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+#[derive(Clone)]
+pub struct ApiTokenService {
+    db: SqlitePool,
+}
+
+impl ApiTokenService {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// Tokens of a user, newest first (revoked and expired ones included)
+    pub async fn list_tokens(&self, user_id: i64) -> ApiResult<Vec<ApiToken>> {
+        let rows: Vec<ApiTokenRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM api_tokens WHERE user_id = ? ORDER BY id DESC",
+            TOKEN_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_token(&self, user_id: i64, id: i64) -> ApiResult<ApiToken> {
+        let row: Option<ApiTokenRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM api_tokens WHERE id = ? AND user_id = ?",
+            TOKEN_COLUMNS
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+        row.map(Into::into)
+            .ok_or_else(|| ApiError::not_found(format!("API token {} not found", id)))
+    }
+
+    /// Create a token acting as `user_id` on behalf of `creator`
+    ///
+    /// Scopes must be API permissions held by the user, and by the creator when the creator
+    /// is someone else (a service account token); clusters must be accessible to the user.
+    /// A request authenticated with an API token cannot create tokens.
+    pub async fn create_token(
+        &self,
+        user_id: i64,
+        req: CreateApiTokenRequest,
+        creator: &OrgContext,
+    ) -> ApiResult<CreatedApiToken> {
+        if creator.api_token_id.is_some() {
+            return Err(ApiError::forbidden("API tokens cannot be created with an API token"));
+        }
+
+        let name = req.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(ApiError::validation_error(format!(
+                "Token name must be 1-{} characters",
+                MAX_NAME_LEN
+            )));
+        }
+        if let Some(expires_at) = req.expires_at
+            && expires_at <= Utc::now()
+        {
+            return Err(ApiError::validation_error("Token expiry must be in the future"));
+        }
+
+        let scopes = match req.scopes {
+            Some(scopes) => Some(self.validate_scopes(user_id, scopes, creator).await?),
+            None => None,
+        };
+        let cluster_ids = match req.cluster_ids {
+            Some(cluster_ids) => Some(self.validate_clusters(user_id, cluster_ids).await?),
+            None => None,
+        };
+
+        let token = format!("{}{}", API_TOKEN_PREFIX, random_hex(TOKEN_BYTES));
+        let token_prefix: String = token.chars().take(DISPLAY_PREFIX_LEN).collect();
+        let result = sqlx::query(
+            "INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, cluster_ids,
+             expires_at, created_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(&token_prefix)
+        .bind(sha256_hex(&token))
+        .bind(scopes.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(
+            cluster_ids
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .bind(req.expires_at)
+        .bind(creator.user_id)
+        .bind(Utc::now())
+        .execute(&self.db)
+        .await?;
+
+        tracing::info!(
+            "User {} created API token '{}' ({}) for user {}",
+            creator.user_id,
+            name,
+            token_prefix,
+            user_id
+        );
+        let api_token = self.get_token(user_id, result.last_insert_rowid()).await?;
+        Ok(CreatedApiToken { token, api_token })
+    }
+
+    /// Revoke a token of a user (revoking twice keeps the first revocation time)
+    pub async fn revoke_token(&self, user_id: i64, id: i64) -> ApiResult<ApiToken> {
+        self.get_token(user_id, id).await?;
+        sqlx::query(
+            "UPDATE api_tokens SET revoked_at = ? WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(Utc::now())
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+
+        tracing::info!("Revoked API token {} of user {}", id, user_id);
+        self.get_token(user_id, id).await
+    }
+
+    /// Authenticate a presented token and record its use
+    pub async fn authenticate(&self, token: &str) -> ApiResult<ApiTokenPrincipal> {
+        let row: Option<TokenOwnerRow> = sqlx::query_as(
+            "SELECT t.id, t.user_id, u.username, t.expires_at, t.revoked_at
+                 FROM api_tokens t
+                 JOIN users u ON u.id = t.user_id
+                 WHERE t.token_hash = ?",
+        )
+        .bind(sha256_hex(token))
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some((token_id, user_id, username, expires_at, revoked_at)) = row else {
+            return Err(ApiError::unauthorized("Invalid API token"));
+        };
+        if revoked_at.is_some() {
+            return Err(ApiError::unauthorized("API token has been revoked"));
+        }
+        if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(ApiError::unauthorized("API token has expired"));
+        }
+
+        let api_token = self.get_token(user_id, token_id).await?;
+        let scopes = match &api_token.scopes {
+            Some(codes) => Some(self.scope_policies(codes).await?),
+            None => None,
+        };
+
+        let now = Utc::now();
+        sqlx::query(
+            "UPDATE api_tokens SET last_used_at = ?
+             WHERE id = ? AND (last_used_at IS NULL OR last_used_at < ?)",
+        )
+        .bind(now)
+        .bind(token_id)
+        .bind(now - chrono::Duration::seconds(LAST_USED_RESOLUTION_SECS))
+        .execute(&self.db)
+        .await?;
+
+        Ok(ApiTokenPrincipal {
+            token_id,
+            user_id,
+            username,
+            scopes,
+            cluster_ids: api_token.cluster_ids,
+        })
+    }
+
+    /// (resource, action) pairs Casbin checks for a set of permission codes
+    async fn scope_policies(&self, codes: &[String]) -> ApiResult<HashSet<(String, String)>> {
+        let mut policies = HashSet::new();
+        for code in codes {
+            let action: Option<(String,)> =
+                sqlx::query_as("SELECT COALESCE(action, '') FROM permissions WHERE code = ?")
+                    .bind(code)
+                    .fetch_optional(&self.db)
+                    .await?;
+            // A scope whose permission was removed no longer grants anything
+            if let Some((action,)) = action
+                && let Some(policy) = CasbinService::permission_policy(code, &action)
+            {
+                policies.insert(policy);
+            }
+        }
+        Ok(policies)
+    }
+
+    /// Deduplicated scopes, each an API permission of the user (and of the creator)
+    async fn validate_scopes(
+        &self,
+        user_id: i64,
+        scopes: Vec<String>,
+        creator: &OrgContext,
+    ) -> ApiResult<Vec<String>> {
+        let scopes: BTreeSet<String> = scopes.into_iter().map(|s| s.trim().to_string()).collect();
+        if scopes.is_empty() {
+            return Err(ApiError::validation_error(
+                "Token scopes cannot be empty (omit them to grant all of the user's permissions)",
+            ));
+        }
+
+        let held = self.api_permission_codes(user_id).await?;
+        let creator_held = if creator.user_id != user_id && !creator.is_super_admin {
+            Some(self.api_permission_codes(creator.user_id).await?)
+        } else {
+            None
+        };
+
+        for scope in &scopes {
+            if !held.contains(scope) {
+                return Err(ApiError::validation_error(format!(
+                    "Scope '{}' is not an API permission of the token's user",
+                    scope
+                )));
+            }
+            if creator_held
+                .as_ref()
+                .is_some_and(|creator_held| !creator_held.contains(scope))
+            {
+                return Err(ApiError::forbidden(format!(
+                    "Cannot grant scope '{}': you do not hold this permission",
+                    scope
+                )));
+            }
+        }
+        Ok(scopes.into_iter().collect())
+    }
+
+    /// Deduplicated cluster ids, each accessible to the user
+    async fn validate_clusters(&self, user_id: i64, cluster_ids: Vec<i64>) -> ApiResult<Vec<i64>> {
+        let cluster_ids: BTreeSet<i64> = cluster_ids.into_iter().collect();
+        if cluster_ids.is_empty() {
+            return Err(ApiError::validation_error(
+                "Token clusters cannot be empty (omit them to allow any cluster)",
+            ));
+        }
+
+        let (is_super_admin, organization_id): (bool, Option<i64>) = sqlx::query_as(
+            "SELECT
+                 EXISTS (SELECT 1 FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+                         WHERE ur.user_id = u.id AND r.code = 'super_admin'),
+                 NULLIF(u.organization_id, 0)
+             FROM users u WHERE u.id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+        for cluster_id in &cluster_ids {
+            let cluster_org: Option<(Option<i64>,)> =
+                sqlx::query_as("SELECT organization_id FROM clusters WHERE id = ?")
+                    .bind(cluster_id)
+                    .fetch_optional(&self.db)
+                    .await?;
+            match cluster_org {
+                None => return Err(ApiError::cluster_not_found(*cluster_id)),
+                Some((org,)) if !is_super_admin && org != organization_id => {
+                    return Err(ApiError::forbidden(format!(
+                        "Cluster {} does not belong to the token user's organization",
+                        cluster_id
+                    )));
+                },
+                _ => {},
+            }
+        }
+        Ok(cluster_ids.into_iter().collect())
+    }
+
+    /// API permission codes granted to a user through their roles
+    async fn api_permission_codes(&self, user_id: i64) -> ApiResult<HashSet<String>> {
+        let codes: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT p.code
+             FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN user_roles ur ON ur.role_id = rp.role_id
+             WHERE ur.user_id = ? AND p.type = 'api'",
+        )
+        .bind(user_id)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(codes.into_iter().map(|(code,)| code).collect())
+    }
+}
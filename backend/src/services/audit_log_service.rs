@@ -4,9 +4,8 @@
 
 #![allow(dead_code)]
 
-use crate::config::AuditLogConfig;
 use crate::models::Cluster;
-use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::services::{AuditColumn, AuditSourceService, MySQLClient, MySQLPoolManager};
 use crate::utils::ApiResult;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -41,21 +40,21 @@ pub struct SlowQuery {
 
 pub struct AuditLogService {
     mysql_pool_manager: Arc<MySQLPoolManager>,
-    audit_config: AuditLogConfig,
+    audit_source_service: Arc<AuditSourceService>,
 }
 
 impl AuditLogService {
-    pub fn new(mysql_pool_manager: Arc<MySQLPoolManager>, audit_config: AuditLogConfig) -> Self {
-        Self {
-            mysql_pool_manager,
-            audit_config,
-        }
+    pub fn new(
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+        audit_source_service: Arc<AuditSourceService>,
+    ) -> Self {
+        Self { mysql_pool_manager, audit_source_service }
     }
 
     /// Get top tables by access count
-    /// 
+    ///
     /// This queries the audit log to find the most frequently accessed tables.
-    /// 
+    ///
     /// # Arguments
     /// * `cluster` - The StarRocks cluster
     /// * `hours` - Time window in hours (default: 24)
@@ -68,18 +67,18 @@ impl AuditLogService {
     ) -> ApiResult<Vec<TopTableByAccess>> {
         let pool = self.mysql_pool_manager.get_pool(cluster).await?;
         let mysql_client = MySQLClient::from_pool(pool);
-        let audit_table = self.audit_config.full_table_name();
-        let audit_table_filter = &self.audit_config.table;
+        // Audit table of the cluster
+        let audit = self.audit_source_service.resolve(cluster).await?;
         let query = format!(
             r#"
             SELECT 
-                COALESCE(NULLIF(`catalog`, 'default_catalog'), '') as catalog,
-                COALESCE(NULLIF(`db`, ''), '') as database,
+                COALESCE(NULLIF({catalog}, 'default_catalog'), '') as catalog,
+                COALESCE(NULLIF({db}, ''), '') as database,
                 -- Extract full table reference from stmt (handles catalog.db.table format)
                 TRIM(BOTH '`' FROM 
                     REGEXP_REPLACE(
                         REGEXP_REPLACE(
-                            `stmt`, 
+                            {stmt}, 
                             '.*\\b(?:FROM|JOIN|INTO|TABLE)\\s+(`?[a-zA-Z0-9_]+`?(?:\\.[a-zA-Z0-9_]+){{1,2}}|`?[a-zA-Z0-9_]+`?).*', 
                             '$1'
                         ),
@@ -87,17 +86,17 @@ impl AuditLogService {
                     )
                 ) as full_table_name,
                 COUNT(*) as access_count,
-                MAX(`timestamp`) as last_access,
-                COUNT(DISTINCT `user`) as unique_users
-            FROM {audit_table}
-            WHERE `timestamp` >= DATE_SUB(NOW(), INTERVAL {hours} HOUR)
-                AND isQuery = 1
-                AND `state` = 'EOF'
-                AND `queryType` IN ('SELECT', 'INSERT', 'UPDATE', 'DELETE')
-                AND `catalog` != ''
-                AND (`db` != 'information_schema' OR `db` IS NULL)
-                AND (`db` != '_statistics_' OR `db` IS NULL)
-                AND LOWER(`stmt`) NOT LIKE '%{audit_table_filter}%'
+                MAX({ts}) as last_access,
+                COUNT(DISTINCT {user}) as unique_users
+            FROM {table}
+            WHERE {ts} >= DATE_SUB(NOW(), INTERVAL {hours} HOUR)
+                AND {is_query} = 1
+                AND {state} = 'EOF'
+                AND {query_type} IN ('SELECT', 'INSERT', 'UPDATE', 'DELETE')
+                AND {catalog} != ''
+                AND ({db} != 'information_schema' OR {db} IS NULL)
+                AND ({db} != '_statistics_' OR {db} IS NULL)
+                AND LOWER({stmt}) NOT LIKE '%{audit_table}%'
             GROUP BY catalog, database, full_table_name
             HAVING full_table_name != ''
                 AND full_table_name NOT LIKE '%(%'
@@ -107,18 +106,28 @@ impl AuditLogService {
             ORDER BY access_count DESC
             LIMIT {limit}
             "#,
+            catalog = audit.col(AuditColumn::Catalog),
+            db = audit.col(AuditColumn::Db),
+            stmt = audit.col(AuditColumn::Stmt),
+            ts = audit.col(AuditColumn::Timestamp),
+            user = audit.col(AuditColumn::User),
+            is_query = audit.col(AuditColumn::IsQuery),
+            state = audit.col(AuditColumn::State),
+            query_type = audit.col(AuditColumn::QueryType),
+            table = audit.full_table_name(),
+            audit_table = audit.table.to_lowercase(),
         );
-        
+
         tracing::debug!("Querying top tables by access: hours={}, limit={}", hours, limit);
-        
+
         let (columns, rows) = mysql_client.query_raw(&query).await?;
-        
+
         // Build column index map
         let mut col_idx = std::collections::HashMap::new();
         for (i, col) in columns.iter().enumerate() {
             col_idx.insert(col.clone(), i);
         }
-        
+
         let mut tables = Vec::new();
         for row in rows {
             if let (Some(full_table_name), Some(access_count_str)) = (
@@ -130,23 +139,25 @@ impl AuditLogService {
                     .get("last_access")
                     .and_then(|&i| row.get(i))
                     .cloned();
-                
+
                 let unique_users = col_idx
                     .get("unique_users")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i32>().ok())
                     .unwrap_or(0);
-                
+
                 let catalog = col_idx
                     .get("catalog")
                     .and_then(|&i| row.get(i))
+                    .map(String::as_str)
                     .unwrap_or("");
-                
+
                 let db_field = col_idx
                     .get("database")
                     .and_then(|&i| row.get(i))
+                    .map(String::as_str)
                     .unwrap_or("");
-                
+
                 // Parse full_table_name: could be "table", "db.table", or "catalog.db.table"
                 let parts: Vec<&str> = full_table_name.split('.').collect();
                 let (final_db, final_table) = match parts.len() {
@@ -170,7 +181,7 @@ impl AuditLogService {
                     },
                     _ => continue, // Invalid format, skip
                 };
-                
+
                 tables.push(TopTableByAccess {
                     database: final_db,
                     table: final_table,
@@ -180,20 +191,16 @@ impl AuditLogService {
                 });
             }
         }
-        
-        tracing::info!(
-            "Found {} top tables by access ({}h window)",
-            tables.len(),
-            hours
-        );
-        
+
+        tracing::info!("Found {} top tables by access ({}h window)", tables.len(), hours);
+
         Ok(tables)
     }
 
     /// Get slow queries
-    /// 
+    ///
     /// This queries the audit log to find slow-running queries.
-    /// 
+    ///
     /// # Arguments
     /// * `cluster` - The StarRocks cluster
     /// * `hours` - Time window in hours (default: 24)
@@ -208,49 +215,64 @@ impl AuditLogService {
     ) -> ApiResult<Vec<SlowQuery>> {
         let pool = self.mysql_pool_manager.get_pool(cluster).await?;
         let mysql_client = MySQLClient::from_pool(pool);
-        let audit_table = self.audit_config.full_table_name();
-        
+        // Audit table of the cluster
+        let audit = self.audit_source_service.resolve(cluster).await?;
+
         // Query audit logs for slow queries
         let query = format!(
             r#"
             SELECT 
-                queryId as query_id,
-                `user`,
-                COALESCE(`db`, '') as `database`,
-                `queryTime` as duration_ms,
-                `scanRows` as scan_rows,
-                `scanBytes` as scan_bytes,
-                `returnRows` as return_rows,
-                `cpuCostNs` / 1000000 as cpu_cost_ms,
-                `memCostBytes` as mem_cost_bytes,
-                `timestamp`,
-                `state`,
-                LEFT(`stmt`, 200) as query_preview
-            FROM {audit_table}
-            WHERE `timestamp` >= DATE_SUB(NOW(), INTERVAL {hours} HOUR)
-                AND `queryTime` >= {min_duration_ms}
-                AND isQuery = 1
-                AND `state` = 'EOF'
-            ORDER BY `queryTime` DESC
+                {query_id} as query_id,
+                {user} as `user`,
+                COALESCE({db}, '') as `database`,
+                {query_time} as duration_ms,
+                {scan_rows} as scan_rows,
+                {scan_bytes} as scan_bytes,
+                {return_rows} as return_rows,
+                {cpu_cost_ns} / 1000000 as cpu_cost_ms,
+                {mem_cost_bytes} as mem_cost_bytes,
+                {ts} as `timestamp`,
+                {state} as `state`,
+                LEFT({stmt}, 200) as query_preview
+            FROM {table}
+            WHERE {ts} >= DATE_SUB(NOW(), INTERVAL {hours} HOUR)
+                AND {query_time} >= {min_duration_ms}
+                AND {is_query} = 1
+                AND {state} = 'EOF'
+            ORDER BY {query_time} DESC
             LIMIT {limit}
             "#,
+            is_query = audit.col(AuditColumn::IsQuery),
+            query_id = audit.col(AuditColumn::QueryId),
+            user = audit.col(AuditColumn::User),
+            db = audit.col(AuditColumn::Db),
+            query_time = audit.col(AuditColumn::QueryTime),
+            scan_rows = audit.col(AuditColumn::ScanRows),
+            scan_bytes = audit.col(AuditColumn::ScanBytes),
+            return_rows = audit.col(AuditColumn::ReturnRows),
+            cpu_cost_ns = audit.col(AuditColumn::CpuCostNs),
+            mem_cost_bytes = audit.col(AuditColumn::MemCostBytes),
+            ts = audit.col(AuditColumn::Timestamp),
+            state = audit.col(AuditColumn::State),
+            stmt = audit.col(AuditColumn::Stmt),
+            table = audit.full_table_name(),
         );
-        
+
         tracing::debug!(
             "Querying slow queries: hours={}, min_duration={}ms, limit={}",
             hours,
             min_duration_ms,
             limit
         );
-        
+
         let (columns, rows) = mysql_client.query_raw(&query).await?;
-        
+
         // Build column index map
         let mut col_idx = std::collections::HashMap::new();
         for (i, col) in columns.iter().enumerate() {
             col_idx.insert(col.clone(), i);
         }
-        
+
         let mut slow_queries = Vec::new();
         for row in rows {
             if let (Some(query_id), Some(user), Some(database), Some(duration_ms_str)) = (
@@ -260,50 +282,50 @@ impl AuditLogService {
                 col_idx.get("duration_ms").and_then(|&i| row.get(i)),
             ) {
                 let duration_ms = duration_ms_str.parse::<i64>().unwrap_or(0);
-                
+
                 let scan_rows = col_idx
                     .get("scan_rows")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i64>().ok());
-                
+
                 let scan_bytes = col_idx
                     .get("scan_bytes")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i64>().ok());
-                
+
                 let return_rows = col_idx
                     .get("return_rows")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i64>().ok());
-                
+
                 let cpu_cost_ms = col_idx
                     .get("cpu_cost_ms")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i64>().ok());
-                
+
                 let mem_cost_bytes = col_idx
                     .get("mem_cost_bytes")
                     .and_then(|&i| row.get(i))
                     .and_then(|s| s.parse::<i64>().ok());
-                
+
                 let timestamp = col_idx
                     .get("timestamp")
                     .and_then(|&i| row.get(i))
                     .cloned()
                     .unwrap_or_default();
-                
+
                 let state = col_idx
                     .get("state")
                     .and_then(|&i| row.get(i))
                     .cloned()
                     .unwrap_or_else(|| "UNKNOWN".to_string());
-                
+
                 let query_preview = col_idx
                     .get("query_preview")
                     .and_then(|&i| row.get(i))
                     .cloned()
                     .unwrap_or_default();
-                
+
                 slow_queries.push(SlowQuery {
                     query_id: query_id.to_string(),
                     user: user.to_string(),
@@ -320,15 +342,14 @@ impl AuditLogService {
                 });
             }
         }
-        
+
         tracing::info!(
             "Found {} slow queries (>{}ms, {}h window)",
             slow_queries.len(),
             min_duration_ms,
             hours
         );
-        
+
         Ok(slow_queries)
     }
 }
-
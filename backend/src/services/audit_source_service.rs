@@ -0,0 +1,542 @@
+// Audit Source Service
+// Purpose: Resolve the StarRocks audit table of each cluster (database, table and column
+//          names, which differ between audit-plugin versions) for every audit log consumer
+
+use crate::config::AuditLogConfig;
+use crate::models::Cluster;
+use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::utils::{ApiError, ApiResult};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// How long detected audit table columns are reused before SHOW COLUMNS runs again
+const DETECTION_TTL: Duration = Duration::from_secs(600);
+
+/// Logical audit log column, mapped to a column of the audit table of each cluster
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditColumn {
+    QueryId,
+    Timestamp,
+    User,
+    Db,
+    Catalog,
+    Stmt,
+    QueryType,
+    QueryTime,
+    State,
+    IsQuery,
+    ScanRows,
+    ScanBytes,
+    ReturnRows,
+    CpuCostNs,
+    MemCostBytes,
+    ResourceGroup,
+}
+
+impl AuditColumn {
+    pub const ALL: [AuditColumn; 16] = [
+        AuditColumn::QueryId,
+        AuditColumn::Timestamp,
+        AuditColumn::User,
+        AuditColumn::Db,
+        AuditColumn::Catalog,
+        AuditColumn::Stmt,
+        AuditColumn::QueryType,
+        AuditColumn::QueryTime,
+        AuditColumn::State,
+        AuditColumn::IsQuery,
+        AuditColumn::ScanRows,
+        AuditColumn::ScanBytes,
+        AuditColumn::ReturnRows,
+        AuditColumn::CpuCostNs,
+        AuditColumn::MemCostBytes,
+        AuditColumn::ResourceGroup,
+    ];
+
+    /// Column names used by the audit-plugin versions, in order of likelihood
+    pub fn candidates(self) -> &'static [&'static str] {
+        match self {
+            AuditColumn::QueryId => &["queryId", "query_id"],
+            AuditColumn::Timestamp => &["timestamp", "event_time", "time"],
+            AuditColumn::User => &["user", "username"],
+            AuditColumn::Db => &["db", "database"],
+            AuditColumn::Catalog => &["catalog"],
+            AuditColumn::Stmt => &["stmt", "statement", "query"],
+            AuditColumn::QueryType => &["queryType", "query_type"],
+            AuditColumn::QueryTime => &["queryTime", "query_time", "duration", "queryDuration"],
+            AuditColumn::State => &["state", "status"],
+            AuditColumn::IsQuery => &["isQuery", "is_query"],
+            AuditColumn::ScanRows => &["scanRows", "scan_rows"],
+            AuditColumn::ScanBytes => &["scanBytes", "scan_bytes"],
+            AuditColumn::ReturnRows => &["returnRows", "return_rows"],
+            AuditColumn::CpuCostNs => &["cpuCostNs", "cpu_cost_ns"],
+            AuditColumn::MemCostBytes => &["memCostBytes", "mem_cost_bytes"],
+            AuditColumn::ResourceGroup => &["resourceGroup", "resource_group", "warehouse"],
+        }
+    }
+
+    /// Columns every consumer needs; queries fail when one of them is missing
+    pub fn is_required(self) -> bool {
+        matches!(
+            self,
+            AuditColumn::QueryId
+                | AuditColumn::Timestamp
+                | AuditColumn::User
+                | AuditColumn::Stmt
+                | AuditColumn::QueryType
+                | AuditColumn::QueryTime
+                | AuditColumn::State
+                | AuditColumn::IsQuery
+        )
+    }
+
+    /// SQL expression read in place of an optional column the table does not have
+    fn missing_expr(self) -> &'static str {
+        match self {
+            AuditColumn::Catalog => "'default_catalog'",
+            AuditColumn::Db | AuditColumn::ResourceGroup => "''",
+            _ => "NULL",
+        }
+    }
+}
+
+/// Audit table of a cluster with the table column of each logical column
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditSource {
+    pub database: String,
+    pub table: String,
+    /// Table column of each logical column found in the table
+    #[schema(value_type = BTreeMap<String, String>)]
+    pub columns: BTreeMap<AuditColumn, String>,
+    /// Logical columns the table does not have
+    pub missing: Vec<AuditColumn>,
+    /// False when the table columns could not be read and the default names are assumed
+    pub detected: bool,
+}
+
+impl AuditSource {
+    /// Resolve the logical columns against the columns of the audit table. Mapped columns
+    /// win over auto-detection; without `table_columns` (detection failed) the mapped or
+    /// default names are assumed to exist.
+    pub fn resolve(
+        database: &str,
+        table: &str,
+        table_columns: Option<&[String]>,
+        mapping: &BTreeMap<AuditColumn, String>,
+    ) -> Self {
+        let mut columns = BTreeMap::new();
+        let mut missing = Vec::new();
+
+        for column in AuditColumn::ALL {
+            let resolved = match (table_columns, mapping.get(&column)) {
+                (Some(existing), Some(mapped)) => existing
+                    .iter()
+                    .find(|name| name.eq_ignore_ascii_case(mapped))
+                    .cloned(),
+                (Some(existing), None) => column.candidates().iter().find_map(|candidate| {
+                    existing
+                        .iter()
+                        .find(|name| name.eq_ignore_ascii_case(candidate))
+                        .cloned()
+                }),
+                (None, Some(mapped)) => Some(mapped.clone()),
+                (None, None) => Some(column.candidates()[0].to_string()),
+            };
+            match resolved {
+                Some(name) => {
+                    columns.insert(column, name);
+                },
+                None => missing.push(column),
+            }
+        }
+
+        Self {
+            database: database.to_string(),
+            table: table.to_string(),
+            columns,
+            missing,
+            detected: table_columns.is_some(),
+        }
+    }
+
+    /// Quoted `database`.`table` for FROM clauses
+    pub fn full_table_name(&self) -> String {
+        format!("`{}`.`{}`", self.database, self.table)
+    }
+
+    /// SQL expression of a logical column: the quoted table column, or for an optional column
+    /// the table does not have a neutral literal. A missing required column keeps its default
+    /// name so the query fails with an unknown column error instead of returning nothing.
+    pub fn col(&self, column: AuditColumn) -> String {
+        match self.columns.get(&column) {
+            Some(name) => format!("`{}`", name),
+            None if column.is_required() => format!("`{}`", column.candidates()[0]),
+            None => column.missing_expr().to_string(),
+        }
+    }
+
+    pub fn has(&self, column: AuditColumn) -> bool {
+        self.columns.contains_key(&column)
+    }
+
+    /// Required columns the table does not have
+    pub fn missing_required(&self) -> Vec<AuditColumn> {
+        self.missing
+            .iter()
+            .copied()
+            .filter(|column| column.is_required())
+            .collect()
+    }
+}
+
+/// Audit source settings of a cluster; unset fields use the [audit] config and auto-detection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditSourceSettings {
+    /// Audit database (default: audit.database)
+    pub database: Option<String>,
+    /// Audit table (default: audit.table)
+    pub table: Option<String>,
+    /// Table column of a logical column, for audit-plugin versions auto-detection misses
+    #[serde(default)]
+    #[schema(value_type = BTreeMap<String, String>)]
+    pub column_mapping: BTreeMap<AuditColumn, String>,
+}
+
+/// Audit source settings of a cluster and the source they resolve to
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClusterAuditSource {
+    pub cluster_id: i64,
+    pub settings: AuditSourceSettings,
+    pub effective: AuditSource,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Result of checking an audit source against the cluster
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditSourceValidation {
+    /// The table exists, is readable and has every required column
+    pub valid: bool,
+    pub table_exists: bool,
+    pub readable: bool,
+    pub source: AuditSource,
+    pub missing_required: Vec<AuditColumn>,
+    pub error: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditSourceRow {
+    database_name: Option<String>,
+    table_name: Option<String>,
+    column_mapping: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+struct CachedSource {
+    source: AuditSource,
+    resolved_at: Instant,
+}
+
+fn validate_identifier(kind: &str, name: &str) -> ApiResult<()> {
+    if name.is_empty()
+        || name.len() > 64
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(ApiError::validation_error(format!(
+            "Invalid audit {} name '{}': use letters, digits and underscores",
+            kind, name
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct AuditSourceService {
+    db: SqlitePool,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    config: AuditLogConfig,
+    /// Resolved source of each cluster (detection runs at most every DETECTION_TTL)
+    cache: Arc<DashMap<i64, CachedSource>>,
+}
+
+impl AuditSourceService {
+    pub fn new(
+        db: SqlitePool,
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+        config: AuditLogConfig,
+    ) -> Self {
+        Self { db, mysql_pool_manager, config, cache: Arc::new(DashMap::new()) }
+    }
+
+    /// Audit source of a cluster: its settings resolved against the columns of the table.
+    /// When the columns cannot be read, the default names are used and detection is retried
+    /// on the next call.
+    pub async fn resolve(&self, cluster: &Cluster) -> ApiResult<AuditSource> {
+        if let Some(cached) = self.cache.get(&cluster.id)
+            && cached.resolved_at.elapsed() < DETECTION_TTL
+        {
+            return Ok(cached.source.clone());
+        }
+
+        let (settings, _) = self.load_settings(cluster.id).await?;
+        let (database, table) = self.table_of(&settings);
+        let columns = match self.mysql_pool_manager.get_pool(cluster).await {
+            Ok(pool) => Self::show_columns(&MySQLClient::from_pool(pool), &database, &table).await,
+            Err(e) => Err(e),
+        };
+        let source = match columns {
+            Ok(columns) => {
+                let source = AuditSource::resolve(
+                    &database,
+                    &table,
+                    Some(&columns),
+                    &settings.column_mapping,
+                );
+                if !source.missing.is_empty() {
+                    tracing::debug!(
+                        "Audit table {} of cluster {} has no column for {:?}",
+                        source.full_table_name(),
+                        cluster.id,
+                        source.missing
+                    );
+                }
+                self.cache.insert(
+                    cluster.id,
+                    CachedSource { source: source.clone(), resolved_at: Instant::now() },
+                );
+                source
+            },
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read columns of audit table {}.{} of cluster {}, assuming defaults: {}",
+                    database,
+                    table,
+                    cluster.id,
+                    e
+                );
+                AuditSource::resolve(&database, &table, None, &settings.column_mapping)
+            },
+        };
+
+        Ok(source)
+    }
+
+    /// Audit source settings of a cluster with the source they resolve to
+    pub async fn get_audit_source(&self, cluster: &Cluster) -> ApiResult<ClusterAuditSource> {
+        let (settings, updated_at) = self.load_settings(cluster.id).await?;
+        let effective = self.resolve(cluster).await?;
+        Ok(ClusterAuditSource { cluster_id: cluster.id, settings, effective, updated_at })
+    }
+
+    /// Change the audit source settings of a cluster
+    pub async fn update_settings(
+        &self,
+        cluster: &Cluster,
+        settings: AuditSourceSettings,
+    ) -> ApiResult<ClusterAuditSource> {
+        Self::validate_settings(&settings)?;
+        let column_mapping = if settings.column_mapping.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&settings.column_mapping).map_err(|e| {
+                ApiError::internal_error(format!("Failed to serialize column mapping: {}", e))
+            })?)
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_audit_sources (cluster_id, database_name, table_name, column_mapping, updated_at)
+            VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(cluster_id) DO UPDATE SET
+                database_name = excluded.database_name,
+                table_name = excluded.table_name,
+                column_mapping = excluded.column_mapping,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(cluster.id)
+        .bind(&settings.database)
+        .bind(&settings.table)
+        .bind(column_mapping)
+        .execute(&self.db)
+        .await?;
+        self.cache.remove(&cluster.id);
+
+        tracing::info!(
+            "Audit source of cluster {} ({}) set to {}.{} ({} mapped columns)",
+            cluster.id,
+            cluster.name,
+            settings
+                .database
+                .as_deref()
+                .unwrap_or(&self.config.database),
+            settings.table.as_deref().unwrap_or(&self.config.table),
+            settings.column_mapping.len()
+        );
+
+        self.get_audit_source(cluster).await
+    }
+
+    /// Drop the audit source settings of a cluster (back to the [audit] config)
+    pub async fn reset_settings(&self, cluster: &Cluster) -> ApiResult<ClusterAuditSource> {
+        sqlx::query("DELETE FROM cluster_audit_sources WHERE cluster_id = ?")
+            .bind(cluster.id)
+            .execute(&self.db)
+            .await?;
+        self.cache.remove(&cluster.id);
+
+        tracing::info!("Audit source of cluster {} ({}) reset", cluster.id, cluster.name);
+        self.get_audit_source(cluster).await
+    }
+
+    /// Check that the audit table exists, is readable and has the required columns, with the
+    /// given settings (before saving them) or the stored ones. Fails when the cluster itself
+    /// is unreachable.
+    pub async fn validate(
+        &self,
+        cluster: &Cluster,
+        settings: Option<AuditSourceSettings>,
+    ) -> ApiResult<AuditSourceValidation> {
+        let settings = match settings {
+            Some(settings) => {
+                Self::validate_settings(&settings)?;
+                settings
+            },
+            None => self.load_settings(cluster.id).await?.0,
+        };
+        let (database, table) = self.table_of(&settings);
+        let client = MySQLClient::from_pool(self.mysql_pool_manager.get_pool(cluster).await?);
+
+        let columns = match Self::show_columns(&client, &database, &table).await {
+            Ok(columns) => columns,
+            Err(e) => {
+                return Ok(AuditSourceValidation {
+                    valid: false,
+                    table_exists: false,
+                    readable: false,
+                    source: AuditSource::resolve(&database, &table, None, &settings.column_mapping),
+                    missing_required: Vec::new(),
+                    error: Some(e.to_string()),
+                });
+            },
+        };
+        let source =
+            AuditSource::resolve(&database, &table, Some(&columns), &settings.column_mapping);
+        let missing_required = source.missing_required();
+
+        let select_list = source
+            .columns
+            .values()
+            .map(|name| format!("`{}`", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let probe = format!("SELECT {} FROM {} LIMIT 1", select_list, source.full_table_name());
+        let (readable, error) = match client.query_raw(&probe).await {
+            Ok(_) => (true, None),
+            Err(e) => (false, Some(e.to_string())),
+        };
+        let error = error.or_else(|| {
+            (!missing_required.is_empty())
+                .then(|| format!("Missing required columns: {:?}", missing_required))
+        });
+
+        Ok(AuditSourceValidation {
+            valid: readable && missing_required.is_empty(),
+            table_exists: true,
+            readable,
+            source,
+            missing_required,
+            error,
+        })
+    }
+
+    fn validate_settings(settings: &AuditSourceSettings) -> ApiResult<()> {
+        if let Some(database) = &settings.database {
+            validate_identifier("database", database)?;
+        }
+        if let Some(table) = &settings.table {
+            validate_identifier("table", table)?;
+        }
+        for name in settings.column_mapping.values() {
+            validate_identifier("column", name)?;
+        }
+        Ok(())
+    }
+
+    fn table_of(&self, settings: &AuditSourceSettings) -> (String, String) {
+        (
+            settings
+                .database
+                .clone()
+                .unwrap_or_else(|| self.config.database.clone()),
+            settings
+                .table
+                .clone()
+                .unwrap_or_else(|| self.config.table.clone()),
+        )
+    }
+
+    async fn load_settings(
+        &self,
+        cluster_id: i64,
+    ) -> ApiResult<(AuditSourceSettings, Option<DateTime<Utc>>)> {
+        let row: Option<AuditSourceRow> = sqlx::query_as(
+            "SELECT database_name, table_name, column_mapping, updated_at \
+             FROM cluster_audit_sources WHERE cluster_id = ?",
+        )
+        .bind(cluster_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok((AuditSourceSettings::default(), None));
+        };
+        let column_mapping = match row.column_mapping.as_deref() {
+            Some(json) => serde_json::from_str(json).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Ignoring invalid audit column mapping of cluster {}: {}",
+                    cluster_id,
+                    e
+                );
+                BTreeMap::new()
+            }),
+            None => BTreeMap::new(),
+        };
+        Ok((
+            AuditSourceSettings {
+                database: row.database_name,
+                table: row.table_name,
+                column_mapping,
+            },
+            row.updated_at,
+        ))
+    }
+
+    /// Column names of the audit table (SHOW COLUMNS)
+    async fn show_columns(
+        client: &MySQLClient,
+        database: &str,
+        table: &str,
+    ) -> ApiResult<Vec<String>> {
+        let (columns, rows) = client
+            .query_raw(&format!("SHOW COLUMNS FROM `{}`.`{}`", database, table))
+            .await?;
+        let field_idx = columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case("Field"))
+            .ok_or_else(|| ApiError::internal_error("SHOW COLUMNS returned no Field column"))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|mut row| (field_idx < row.len()).then(|| row.swap_remove(field_idx)))
+            .collect())
+    }
+}
@@ -1,5 +1,11 @@
-use crate::models::{CreateUserRequest, LoginRequest, UpdateUserRequest, User, UserResponse};
-use crate::utils::{ApiError, ApiResult, JwtUtil};
+use crate::config::PasswordPolicyConfig;
+use crate::models::{
+    AuthProvider, CreateUserRequest, LoginRequest, TokenResponse, UpdateUserRequest, User,
+    UserResponse,
+};
+use crate::services::session_service::{SessionClient, SessionService};
+use crate::services::two_factor_service::TwoFactorService;
+use crate::utils::{ApiError, ApiResult};
 use bcrypt::{DEFAULT_COST, hash, verify};
 use sqlx::SqlitePool;
 use std::sync::Arc;
@@ -7,16 +13,40 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AuthService {
     pool: SqlitePool,
-    jwt_util: Arc<JwtUtil>,
+    session_service: Arc<SessionService>,
+    two_factor_service: Arc<TwoFactorService>,
+    password_policy: PasswordPolicyConfig,
 }
 
+/// Key of the public registration switch in system_settings
+const REGISTRATION_ENABLED_KEY: &str = "registration_enabled";
+
 impl AuthService {
-    pub fn new(pool: SqlitePool, jwt_util: Arc<JwtUtil>) -> Self {
-        Self { pool, jwt_util }
+    pub fn new(
+        pool: SqlitePool,
+        session_service: Arc<SessionService>,
+        two_factor_service: Arc<TwoFactorService>,
+    ) -> Self {
+        Self {
+            pool,
+            session_service,
+            two_factor_service,
+            password_policy: PasswordPolicyConfig::default(),
+        }
+    }
+
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicyConfig) -> Self {
+        self.password_policy = password_policy;
+        self
     }
 
     // Register a new user
     pub async fn register(&self, req: CreateUserRequest) -> ApiResult<User> {
+        if !self.registration_enabled().await? {
+            tracing::warn!("Registration refused for '{}': registration is disabled", req.username);
+            return Err(ApiError::forbidden("Registration is disabled"));
+        }
+
         tracing::debug!("Checking if username exists: {}", req.username);
 
         // Check if username already exists
@@ -30,6 +60,10 @@ impl AuthService {
             return Err(ApiError::validation_error("Username already exists"));
         }
 
+        self.password_policy
+            .check(&req.username, &req.password)
+            .map_err(ApiError::validation_error)?;
+
         tracing::debug!("Hashing password for user: {}", req.username);
         // Hash password
         let password_hash = hash(&req.password, DEFAULT_COST).map_err(|e| {
@@ -62,8 +96,12 @@ impl AuthService {
         Ok(user)
     }
 
-    // Login and generate JWT token
-    pub async fn login(&self, req: LoginRequest) -> ApiResult<(User, String)> {
+    // Login and open a session
+    pub async fn login(
+        &self,
+        req: LoginRequest,
+        client: &SessionClient,
+    ) -> ApiResult<(User, TokenResponse)> {
         tracing::debug!("Looking up user: {}", req.username);
 
         // Find user by username
@@ -77,6 +115,23 @@ impl AuthService {
             ApiError::invalid_credentials()
         })?;
 
+        // Service accounts only authenticate with API tokens
+        let is_service_account: Option<i64> =
+            sqlx::query_scalar("SELECT user_id FROM service_accounts WHERE user_id = ?")
+                .bind(user.id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if is_service_account.is_some() {
+            tracing::warn!("Login failed: '{}' is a service account", req.username);
+            return Err(ApiError::invalid_credentials());
+        }
+
+        // LDAP and OIDC users log in through their identity provider
+        if user.auth_provider != AuthProvider::Local.as_str() {
+            tracing::warn!("Login failed: '{}' is a {} user", req.username, user.auth_provider);
+            return Err(ApiError::invalid_credentials());
+        }
+
         tracing::debug!("Verifying password for user: {}", req.username);
         // Verify password
         let valid = verify(&req.password, &user.password_hash).map_err(|e| {
@@ -89,19 +144,28 @@ impl AuthService {
             return Err(ApiError::invalid_credentials());
         }
 
-        tracing::debug!("Generating JWT token for user: {}", req.username);
-        // Generate JWT token
-        let token = self
-            .jwt_util
-            .generate_token(user.id, &user.username)
+        if !user.is_active {
+            tracing::warn!("Login failed: user '{}' is disabled", req.username);
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+
+        self.two_factor_service
+            .verify_login(user.id, req.totp_code.as_deref())
+            .await?;
+
+        tracing::debug!("Opening session for user: {}", req.username);
+        let tokens = self
+            .session_service
+            .start_session(&user, client)
+            .await
             .map_err(|e| {
-                tracing::error!("JWT token generation failed for user {}: {:?}", req.username, e);
+                tracing::error!("Session creation failed for user {}: {:?}", req.username, e);
                 e
             })?;
 
         tracing::info!("User logged in successfully: {} (ID: {})", user.username, user.id);
 
-        Ok((user, token))
+        Ok((user, tokens))
     }
 
     // Get user by ID
@@ -123,6 +187,13 @@ impl AuthService {
 
         // If changing password, verify current password first
         if let (Some(current_pwd), Some(new_pwd)) = (&req.current_password, &req.new_password) {
+            if user.auth_provider != AuthProvider::Local.as_str() {
+                return Err(ApiError::validation_error(format!(
+                    "The password of {} users is managed by their identity provider",
+                    user.auth_provider
+                )));
+            }
+
             tracing::debug!("Verifying current password for user_id: {}", user_id);
             let valid = verify(current_pwd, &user.password_hash).map_err(|e| {
                 tracing::error!("Password verification error: {}", e);
@@ -133,6 +204,9 @@ impl AuthService {
                 tracing::warn!("Current password verification failed for user_id: {}", user_id);
                 return Err(ApiError::validation_error("Current password is incorrect"));
             }
+            self.password_policy
+                .check(&user.username, new_pwd)
+                .map_err(ApiError::validation_error)?;
 
             // Hash new password
             tracing::debug!("Hashing new password for user_id: {}", user_id);
@@ -184,6 +258,35 @@ impl AuthService {
         Ok(updated_user)
     }
 
+    /// Whether /api/auth/register accepts new accounts
+    pub async fn registration_enabled(&self) -> ApiResult<bool> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM system_settings WHERE key = ?")
+                .bind(REGISTRATION_ENABLED_KEY)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(value.is_none_or(|value| value == "true"))
+    }
+
+    pub async fn set_registration_enabled(&self, enabled: bool, updated_by: i64) -> ApiResult<()> {
+        sqlx::query(
+            "INSERT INTO system_settings (key, value, updated_by) VALUES (?, ?, ?) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, \
+             updated_by = excluded.updated_by, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(REGISTRATION_ENABLED_KEY)
+        .bind(enabled.to_string())
+        .bind(updated_by)
+        .execute(&self.pool)
+        .await?;
+        tracing::info!(
+            "Registration {} by user_id={}",
+            if enabled { "enabled" } else { "disabled" },
+            updated_by
+        );
+        Ok(())
+    }
+
     pub async fn is_user_super_admin(&self, user_id: i64) -> ApiResult<bool> {
         let exists: Option<i64> = sqlx::query_scalar(
             r#"
@@ -1,11 +1,28 @@
+use crate::models::PermissionScope;
 use crate::utils::{ApiError, ApiResult};
 use casbin::prelude::*;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Policy scope of permissions granted organization-wide
+const ANY_SCOPE: &str = "*";
+
+#[derive(sqlx::FromRow)]
+struct RolePermissionRow {
+    role_id: i64,
+    organization_id: Option<i64>,
+    code: String,
+    action: String,
+    cluster_id: Option<i64>,
+    catalog_name: Option<String>,
+    database_name: Option<String>,
+}
+
 /// Casbin service for RBAC permission checking
 ///
-/// Uses in-memory adapter and loads policies from database dynamically
+/// Uses in-memory adapter and loads policies from database dynamically. Policies carry a
+/// scope: `*` for organization-wide grants, a `PermissionScope::policy_key` pattern for grants
+/// limited to a cluster, catalog or database.
 pub struct CasbinService {
     enforcer: Arc<RwLock<Enforcer>>,
 }
@@ -16,10 +33,10 @@ impl CasbinService {
         // Define RBAC model in code (no external conf file needed)
         let model_str = r#"
 [request_definition]
-r = sub, obj, act
+r = sub, obj, act, scope
 
 [policy_definition]
-p = sub, obj, act
+p = sub, obj, act, scope
 
 [role_definition]
 g = _, _
@@ -28,7 +45,7 @@ g = _, _
 e = some(where (p.eft == allow))
 
 [matchers]
-m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
+m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act && keyMatch(r.scope, p.scope)
 "#;
         // CRITICAL SECURITY NOTE:
         // The matcher `g(r.sub, p.sub)` checks if user (r.sub) has role (p.sub).
@@ -54,7 +71,8 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
         Ok(Self { enforcer: Arc::new(RwLock::new(enforcer)) })
     }
 
-    /// Check if a user has permission for a resource and action
+    /// Check if a user has permission for a resource and action, organization-wide (grants
+    /// limited to some clusters are listed by `granted_scopes`)
     ///
     /// SECURITY NOTE: Uses "u:<user_id>" prefix for users and "r:<role_id>" prefix for roles
     /// to prevent ID collision vulnerability where user_id == role_id could cause
@@ -68,8 +86,9 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
 
         // Casbin expects subject (user_id), object (resource), action
         // enforce is a sync method - use Vec<String> format
+        // The empty request scope only matches the `*` policy scope
         let permitted = enforcer
-            .enforce(vec![user_subject, resource.to_string(), action.to_string()])
+            .enforce(vec![user_subject, resource.to_string(), action.to_string(), String::new()])
             .map_err(|e| {
                 tracing::error!("Casbin enforce error: {:?}", e);
                 ApiError::internal_error(format!("Permission check failed: {}", e))
@@ -78,12 +97,40 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
         Ok(permitted)
     }
 
+    /// Clusters, catalogs and databases a user holds a permission on through scoped grants
+    pub async fn granted_scopes(
+        &self,
+        user_id: i64,
+        resource: &str,
+        action: &str,
+    ) -> ApiResult<Vec<PermissionScope>> {
+        let enforcer = self.enforcer.read().await;
+        let mut scopes: Vec<PermissionScope> = Vec::new();
+        let granted = enforcer
+            .get_implicit_permissions_for_user(&format!("u:{}", user_id), None)
+            .into_iter()
+            .filter(|policy| policy.get(1).is_some_and(|obj| obj == resource))
+            .filter(|policy| policy.get(2).is_some_and(|act| act == action))
+            .filter_map(|policy| PermissionScope::from_policy_key(policy.get(3)?));
+        for scope in granted {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        Ok(scopes)
+    }
+
     /// Add a policy rule: role has permission to access resource with action
     pub async fn add_policy(&self, role_id: i64, resource: &str, action: &str) -> ApiResult<bool> {
         let mut enforcer = self.enforcer.write().await;
 
         // SECURITY FIX: Use prefixed format to prevent ID collision
-        let parts = vec![format!("r:{}", role_id), resource.to_string(), action.to_string()];
+        let parts = vec![
+            format!("r:{}", role_id),
+            resource.to_string(),
+            action.to_string(),
+            ANY_SCOPE.to_string(),
+        ];
 
         let added = enforcer.add_policy(parts).await.map_err(|e| {
             tracing::error!("Failed to add policy: {:?}", e);
@@ -103,7 +150,12 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
         let mut enforcer = self.enforcer.write().await;
 
         // SECURITY FIX: Use prefixed format to prevent ID collision
-        let parts = vec![format!("r:{}", role_id), resource.to_string(), action.to_string()];
+        let parts = vec![
+            format!("r:{}", role_id),
+            resource.to_string(),
+            action.to_string(),
+            ANY_SCOPE.to_string(),
+        ];
 
         let removed = enforcer.remove_policy(parts).await.map_err(|e| {
             tracing::error!("Failed to remove policy: {:?}", e);
@@ -154,13 +206,20 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
             ApiError::internal_error(format!("Failed to clear policies: {}", e))
         })?;
 
-        // Load role-permission mappings
-        let role_permissions: Vec<(i64, Option<i64>, String, String)> = sqlx::query_as(
+        // Load role-permission mappings, organization-wide then limited to part of a cluster
+        let role_permissions: Vec<RolePermissionRow> = sqlx::query_as(
             r#"
-            SELECT rp.role_id, r.organization_id, p.code, COALESCE(p.action, '') as action
+            SELECT rp.role_id, r.organization_id, p.code, COALESCE(p.action, '') as action,
+                   NULL AS cluster_id, NULL AS catalog_name, NULL AS database_name
             FROM role_permissions rp
             JOIN permissions p ON rp.permission_id = p.id
             JOIN roles r ON r.id = rp.role_id
+            UNION ALL
+            SELECT rps.role_id, r.organization_id, p.code, COALESCE(p.action, '') as action,
+                   rps.cluster_id, rps.catalog_name, rps.database_name
+            FROM role_permission_scopes rps
+            JOIN permissions p ON rps.permission_id = p.id
+            JOIN roles r ON r.id = rps.role_id
             "#,
         )
         .fetch_all(pool)
@@ -171,29 +230,22 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
         })?;
 
         // Add policies to Casbin
-        for (role_id, org_id, code, action) in role_permissions {
-            // Extract resource and action from permission code
-            // Format: "api:clusters:create" or "menu:dashboard"
-            let parts: Vec<&str> = code.split(':').collect();
-            if parts.len() >= 2 {
-                // Resource is the second part (e.g., "clusters" from "api:clusters:create")
-                let resource = parts[1].to_string();
-
-                // Action: use database action if available, otherwise extract from code
-                let act = if !action.is_empty() {
-                    action
-                } else if parts.len() >= 3 {
-                    // Extract action from code (e.g., "create" from "api:clusters:create")
-                    parts[2..].join(":")
-                } else {
-                    "view".to_string()
+        for row in role_permissions {
+            if let Some((resource, act)) = Self::permission_policy(&row.code, &row.action) {
+                let scoped_resource = Self::format_resource_key(row.organization_id, &resource);
+                let scope = match row.cluster_id {
+                    Some(cluster_id) => PermissionScope {
+                        cluster_id,
+                        catalog: row.catalog_name,
+                        database: row.database_name,
+                    }
+                    .policy_key(),
+                    None => ANY_SCOPE.to_string(),
                 };
 
-                let scoped_resource = Self::format_resource_key(org_id, &resource);
-
                 // SECURITY FIX: Use "r:<role_id>" prefix for roles in policies to prevent ID collision
                 let policy_parts =
-                    vec![format!("r:{}", role_id), scoped_resource.clone(), act.clone()];
+                    vec![format!("r:{}", row.role_id), scoped_resource.clone(), act.clone(), scope];
                 let _ = enforcer.add_policy(policy_parts).await;
             }
         }
@@ -220,6 +272,28 @@ m = g(r.sub, p.sub) && r.obj == p.obj && r.act == p.act
 }
 
 impl CasbinService {
+    /// Resource and action a permission grants, as enforced by Casbin
+    ///
+    /// Codes have the form "api:clusters:create" or "menu:dashboard": the resource is the
+    /// second part, the action is the permission's `action` column when set, otherwise the
+    /// rest of the code.
+    pub(crate) fn permission_policy(code: &str, action: &str) -> Option<(String, String)> {
+        let parts: Vec<&str> = code.split(':').collect();
+        if parts.len() < 2 {
+            return None;
+        }
+
+        let resource = parts[1].to_string();
+        let act = if !action.is_empty() {
+            action.to_string()
+        } else if parts.len() >= 3 {
+            parts[2..].join(":")
+        } else {
+            "view".to_string()
+        };
+        Some((resource, act))
+    }
+
     pub(crate) fn format_resource_key(org_id: Option<i64>, resource: &str) -> String {
         match org_id {
             Some(id) => format!("org:{}:{}", id, resource),
@@ -1,12 +1,41 @@
+use crate::middleware::OrgContext;
 use crate::models::{
-    Cluster, ClusterHealth, CreateClusterRequest, HealthCheck, HealthStatus, UpdateClusterRequest,
+    AlertChannelType, Cluster, ClusterHealth, ClusterTlsRequest, CreateClusterRequest, FeEndpoint,
+    HealthCheck, HealthStatus, UpdateClusterRequest,
 };
+use crate::services::alert_notifier::reseal_channel_config;
 use crate::services::{MySQLPoolManager, StarRocksClient};
-use crate::utils::{ApiError, ApiResult};
+use crate::utils::cluster_tls::{self, describe_connection_error};
+use crate::utils::{ApiError, ApiResult, ClusterTls, CredentialCipher};
 use chrono::Utc;
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
+/// Validated TLS columns of a cluster
+#[derive(Debug, Clone)]
+pub struct ClusterTlsColumns {
+    pub ca_cert: Option<String>,
+    pub client_cert: Option<String>,
+    pub client_key_encrypted: Option<String>,
+    pub verify_hostname: bool,
+}
+
+impl Default for ClusterTlsColumns {
+    fn default() -> Self {
+        Self { ca_cert: None, client_cert: None, client_key_encrypted: None, verify_hostname: true }
+    }
+}
+
+impl ClusterTlsColumns {
+    /// Copy the TLS settings onto a cluster
+    pub fn apply_to(self, cluster: &mut Cluster) {
+        cluster.tls_ca_cert = self.ca_cert;
+        cluster.tls_client_cert = self.client_cert;
+        cluster.tls_client_key_encrypted = self.client_key_encrypted;
+        cluster.tls_verify_hostname = self.verify_hostname;
+    }
+}
+
 #[derive(Clone)]
 pub struct ClusterService {
     pool: SqlitePool,
@@ -45,6 +74,15 @@ impl ClusterService {
         if req.username.is_empty() {
             return Err(ApiError::validation_error("Username cannot be empty"));
         }
+        let fe_endpoints_json = req
+            .fe_endpoints
+            .take()
+            .map(Self::normalize_fe_endpoints)
+            .transpose()?;
+        let tls = match req.tls.take() {
+            Some(tls) => self.prepare_tls(tls, None)?,
+            None => ClusterTlsColumns::default(),
+        };
 
         // Check if cluster name already exists
         let existing: Option<Cluster> = sqlx::query_as("SELECT * FROM clusters WHERE name = ?")
@@ -74,12 +112,14 @@ impl ClusterService {
 
         let is_first_cluster = existing_cluster_count.0 == 0;
 
-        // Insert cluster (password is stored as-is for now, should be encrypted in production)
+        let password_encrypted = self.mysql_pool_manager.cipher().encrypt(&req.password)?;
+
         let result = sqlx::query(
             "INSERT INTO clusters (name, description, fe_host, fe_http_port, fe_query_port, 
              username, password_encrypted, enable_ssl, connection_timeout, tags, catalog, 
-             is_active, created_by, organization_id, deployment_mode)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             is_active, created_by, organization_id, deployment_mode, fe_endpoints,
+             tls_ca_cert, tls_client_cert, tls_client_key_encrypted, tls_verify_hostname)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(&req.name)
         .bind(&req.description)
@@ -87,7 +127,7 @@ impl ClusterService {
         .bind(req.fe_http_port)
         .bind(req.fe_query_port)
         .bind(&req.username)
-        .bind(&req.password) // TODO: Encrypt in production
+        .bind(&password_encrypted)
         .bind(req.enable_ssl)
         .bind(req.connection_timeout)
         .bind(&tags_json)
@@ -96,6 +136,11 @@ impl ClusterService {
         .bind(user_id)
         .bind(target_org_id)
         .bind(req.deployment_mode.to_string())
+        .bind(&fe_endpoints_json)
+        .bind(&tls.ca_cert)
+        .bind(&tls.client_cert)
+        .bind(&tls.client_key_encrypted)
+        .bind(tls.verify_hostname)
         .execute(&self.pool)
         .await?;
 
@@ -193,7 +238,99 @@ impl ClusterService {
         })
     }
 
-    // Set a cluster as active (deactivating all others in the same organization)
+    // Resolve the cluster a request acts on: the cluster addressed by the request (path or
+    // X-Cluster-Id header), otherwise the caller's own active cluster, otherwise the default.
+    // A cluster outside the request's API token restriction, or outside the clusters the
+    // checked permission is granted on, is refused.
+    pub async fn resolve_cluster(&self, org_ctx: &OrgContext) -> ApiResult<Cluster> {
+        let cluster = match org_ctx.cluster_id {
+            Some(cluster_id) => {
+                let cluster = self.get_cluster(cluster_id).await?;
+                if !org_ctx.is_super_admin && cluster.organization_id != org_ctx.organization_id {
+                    return Err(ApiError::forbidden(
+                        "Cluster does not belong to your organization",
+                    ));
+                }
+                cluster
+            },
+            None => match self.get_user_active_cluster(org_ctx).await? {
+                Some(cluster) => cluster,
+                None => self.get_default_cluster(org_ctx).await?,
+            },
+        };
+
+        org_ctx.check_cluster_access(cluster.id)?;
+        Ok(cluster)
+    }
+
+    // Get the default cluster of the caller (the cluster flagged active, global for super
+    // admins, per organization otherwise)
+    pub async fn get_default_cluster(&self, org_ctx: &OrgContext) -> ApiResult<Cluster> {
+        if org_ctx.is_super_admin {
+            self.get_active_cluster().await
+        } else {
+            self.get_active_cluster_by_org(org_ctx.organization_id)
+                .await
+        }
+    }
+
+    // Get the cluster the caller selected as active, if it is still accessible to them
+    pub async fn get_user_active_cluster(
+        &self,
+        org_ctx: &OrgContext,
+    ) -> ApiResult<Option<Cluster>> {
+        let cluster: Option<Cluster> = sqlx::query_as(
+            "SELECT c.* FROM clusters c
+             JOIN user_active_clusters uac ON uac.cluster_id = c.id
+             WHERE uac.user_id = ?",
+        )
+        .bind(org_ctx.user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(cluster.filter(|cluster| {
+            org_ctx.is_super_admin || cluster.organization_id == org_ctx.organization_id
+        }))
+    }
+
+    // Select the active cluster of a user (other users keep their own selection)
+    pub async fn set_user_active_cluster(
+        &self,
+        user_id: i64,
+        cluster_id: i64,
+    ) -> ApiResult<Cluster> {
+        let cluster = self.get_cluster(cluster_id).await?;
+
+        sqlx::query(
+            "INSERT INTO user_active_clusters (user_id, cluster_id, updated_at)
+             VALUES (?, ?, CURRENT_TIMESTAMP)
+             ON CONFLICT(user_id) DO UPDATE SET
+                 cluster_id = excluded.cluster_id,
+                 updated_at = excluded.updated_at",
+        )
+        .bind(user_id)
+        .bind(cluster_id)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!("User {} selected cluster {} as active", user_id, cluster_id);
+        Ok(cluster)
+    }
+
+    // Get the clusters visible to the caller (all for super admins, own organization otherwise)
+    pub async fn list_visible_clusters(&self, org_ctx: &OrgContext) -> ApiResult<Vec<Cluster>> {
+        let clusters = self.list_clusters().await?;
+        Ok(clusters
+            .into_iter()
+            .filter(|cluster| {
+                org_ctx.is_super_admin || cluster.organization_id == org_ctx.organization_id
+            })
+            .filter(|cluster| org_ctx.can_access_cluster(cluster.id))
+            .collect())
+    }
+
+    // Set a cluster as the default of its organization (deactivating all others in the same
+    // organization); users without their own selection act on the default cluster
     pub async fn set_active_cluster(&self, cluster_id: i64) -> ApiResult<Cluster> {
         // Check if cluster exists and fetch its org
         let cluster = self.get_cluster(cluster_id).await?;
@@ -239,7 +376,7 @@ impl ClusterService {
         req: UpdateClusterRequest,
     ) -> ApiResult<Cluster> {
         // Check if cluster exists
-        let _cluster = self.get_cluster(cluster_id).await?;
+        let cluster = self.get_cluster(cluster_id).await?;
 
         // Build dynamic SQL update query
         let mut updates = Vec::new();
@@ -271,7 +408,7 @@ impl ClusterService {
         }
         if let Some(password) = &req.password {
             updates.push("password_encrypted = ?");
-            params.push(password.clone());
+            params.push(self.mysql_pool_manager.cipher().encrypt(password)?);
         }
         if let Some(ssl) = req.enable_ssl {
             updates.push("enable_ssl = ?");
@@ -297,6 +434,22 @@ impl ClusterService {
             updates.push("deployment_mode = ?");
             params.push(mode.to_string());
         }
+        if let Some(endpoints) = req.fe_endpoints {
+            updates.push("fe_endpoints = ?");
+            params.push(Self::normalize_fe_endpoints(endpoints)?);
+        }
+        if let Some(tls) = req.tls {
+            let tls = self.prepare_tls(tls, cluster.tls_client_key_encrypted)?;
+            // Params are bound as strings: '' stands for NULL
+            updates.push("tls_ca_cert = NULLIF(?, '')");
+            params.push(tls.ca_cert.unwrap_or_default());
+            updates.push("tls_client_cert = NULLIF(?, '')");
+            params.push(tls.client_cert.unwrap_or_default());
+            updates.push("tls_client_key_encrypted = NULLIF(?, '')");
+            params.push(tls.client_key_encrypted.unwrap_or_default());
+            updates.push("tls_verify_hostname = ?");
+            params.push((tls.verify_hostname as i32).to_string());
+        }
 
         if updates.is_empty() {
             return self.get_cluster(cluster_id).await;
@@ -314,11 +467,212 @@ impl ClusterService {
 
         query.execute(&self.pool).await?;
 
+        // Connection settings may have changed: reconnect on next use
+        self.mysql_pool_manager.remove_pool(cluster_id).await;
+
         tracing::info!("Cluster updated: ID {}", cluster_id);
 
         self.get_cluster(cluster_id).await
     }
 
+    /// Validate additional FE endpoints and serialize them for the fe_endpoints column
+    fn normalize_fe_endpoints(endpoints: Vec<FeEndpoint>) -> ApiResult<String> {
+        let mut normalized: Vec<FeEndpoint> = Vec::with_capacity(endpoints.len());
+        for mut endpoint in endpoints {
+            endpoint.host = endpoint.host.trim().to_string();
+            if endpoint.host.is_empty() {
+                return Err(ApiError::validation_error("FE endpoint host cannot be empty"));
+            }
+            for port in [endpoint.http_port, endpoint.query_port] {
+                if !(1..=65535).contains(&port) {
+                    return Err(ApiError::validation_error(format!(
+                        "Invalid port {} for FE endpoint {}",
+                        port, endpoint.host
+                    )));
+                }
+            }
+            if !normalized.contains(&endpoint) {
+                normalized.push(endpoint);
+            }
+        }
+        serde_json::to_string(&normalized).map_err(|e| {
+            ApiError::internal_error(format!("Failed to serialize FE endpoints: {}", e))
+        })
+    }
+
+    /// Validate TLS settings and encrypt the client key. Without a new client key the
+    /// stored one is kept, so clients do not have to send the secret again.
+    pub fn prepare_tls(
+        &self,
+        tls: ClusterTlsRequest,
+        stored_client_key: Option<String>,
+    ) -> ApiResult<ClusterTlsColumns> {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let ca_cert = non_empty(tls.ca_cert);
+        let client_cert = non_empty(tls.client_cert);
+        let client_key = non_empty(tls.client_key);
+
+        if let Some(ca_cert) = &ca_cert {
+            cluster_tls::validate_ca_bundle(ca_cert)?;
+        }
+
+        let cipher = self.mysql_pool_manager.cipher();
+        let client_key_encrypted = match (&client_cert, client_key) {
+            (Some(cert), Some(key)) => {
+                cluster_tls::validate_client_identity(cert, &key)?;
+                Some(cipher.encrypt(&key)?)
+            },
+            (Some(cert), None) => {
+                let stored = stored_client_key.ok_or_else(|| {
+                    ApiError::validation_error(
+                        "TLS client key is required with a client certificate",
+                    )
+                })?;
+                cluster_tls::validate_client_identity(cert, &cipher.decrypt(&stored)?)?;
+                Some(stored)
+            },
+            (None, Some(_)) => {
+                return Err(ApiError::validation_error(
+                    "TLS client key requires a client certificate",
+                ));
+            },
+            (None, None) => None,
+        };
+
+        Ok(ClusterTlsColumns {
+            ca_cert,
+            client_cert,
+            client_key_encrypted,
+            verify_hostname: tls.verify_hostname,
+        })
+    }
+
+    /// Encrypt cluster passwords still stored as plaintext (rows written before
+    /// credential encryption existed). Safe to run on every startup.
+    pub async fn encrypt_plaintext_credentials(&self) -> ApiResult<usize> {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, password_encrypted FROM clusters")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let cipher = self.mysql_pool_manager.cipher();
+        let mut tx = self.pool.begin().await?;
+        let mut migrated = 0;
+
+        for (id, stored) in rows {
+            if CredentialCipher::is_encrypted(&stored) {
+                continue;
+            }
+            sqlx::query("UPDATE clusters SET password_encrypted = ? WHERE id = ?")
+                .bind(cipher.encrypt(&stored)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            migrated += 1;
+        }
+
+        tx.commit().await?;
+
+        if migrated > 0 {
+            tracing::info!("Encrypted {} plaintext cluster credential(s)", migrated);
+        }
+        Ok(migrated)
+    }
+
+    /// Re-encrypt every value sealed with the master key under `new_cipher`: cluster
+    /// passwords, TLS client keys, alert channel secrets and two-factor secrets of users.
+    ///
+    /// All values are decrypted with the current master key inside one transaction,
+    /// so a value that cannot be decrypted aborts the rotation without partial writes.
+    pub async fn rotate_credentials(&self, new_cipher: &CredentialCipher) -> ApiResult<usize> {
+        let current = self.mysql_pool_manager.cipher();
+        let mut tx = self.pool.begin().await?;
+
+        let clusters: Vec<(i64, String, String)> =
+            sqlx::query_as("SELECT id, name, password_encrypted FROM clusters")
+                .fetch_all(&mut *tx)
+                .await?;
+        for (id, name, stored) in &clusters {
+            let sealed = reseal(current, new_cipher, stored, || {
+                format!("credential of cluster '{}' (ID {})", name, id)
+            })?;
+            sqlx::query("UPDATE clusters SET password_encrypted = ? WHERE id = ?")
+                .bind(sealed)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let client_keys: Vec<(i64, String, String)> = sqlx::query_as(
+            "SELECT id, name, tls_client_key_encrypted FROM clusters
+             WHERE tls_client_key_encrypted IS NOT NULL",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for (id, name, stored) in &client_keys {
+            let sealed = reseal(current, new_cipher, stored, || {
+                format!("TLS client key of cluster '{}' (ID {})", name, id)
+            })?;
+            sqlx::query("UPDATE clusters SET tls_client_key_encrypted = ? WHERE id = ?")
+                .bind(sealed)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // SMTP passwords, robot urls and webhook headers of alert channels
+        let channels: Vec<(i64, String, AlertChannelType, String)> =
+            sqlx::query_as("SELECT id, name, channel_type, config FROM alert_channels")
+                .fetch_all(&mut *tx)
+                .await?;
+        for (id, name, channel_type, config) in &channels {
+            let mut config: serde_json::Value = serde_json::from_str(config)?;
+            reseal_channel_config(*channel_type, &mut config, |stored| {
+                reseal(current, new_cipher, stored, || {
+                    format!("secret of alert channel '{}' (ID {})", name, id)
+                })
+            })?;
+            sqlx::query("UPDATE alert_channels SET config = ? WHERE id = ?")
+                .bind(config.to_string())
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let secrets: Vec<(i64, String)> = sqlx::query_as("SELECT user_id, secret FROM user_totp")
+            .fetch_all(&mut *tx)
+            .await?;
+        for (user_id, stored) in &secrets {
+            let sealed = reseal(current, new_cipher, stored, || {
+                format!("two-factor secret of user ID {}", user_id)
+            })?;
+            sqlx::query("UPDATE user_totp SET secret = ? WHERE user_id = ?")
+                .bind(sealed)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        self.mysql_pool_manager.clear_all().await;
+
+        tracing::info!(
+            "Rotated {} cluster credential(s), {} TLS client key(s), the secrets of {} alert \
+             channel(s) and {} two-factor secret(s) from master key {} to {}",
+            clusters.len(),
+            client_keys.len(),
+            channels.len(),
+            secrets.len(),
+            current.key_id(),
+            new_cipher.key_id()
+        );
+        Ok(clusters.len() + client_keys.len() + channels.len() + secrets.len())
+    }
+
     // Delete cluster
     pub async fn delete_cluster(&self, cluster_id: i64) -> ApiResult<()> {
         // Check if this is the active cluster and capture organization
@@ -341,6 +695,7 @@ impl ClusterService {
             return Err(ApiError::cluster_not_found(cluster_id));
         }
 
+        self.mysql_pool_manager.remove_pool(cluster_id).await;
         tracing::info!("Cluster deleted: ID {}", cluster_id);
 
         // If we deleted the active cluster, activate another one (first by creation time)
@@ -458,7 +813,10 @@ impl ClusterService {
                 checks.push(HealthCheck {
                     name: "Frontend Nodes".to_string(),
                     status: "critical".to_string(),
-                    message: format!("Failed to check FE nodes: {}", e),
+                    message: format!(
+                        "Failed to check FE nodes: {}",
+                        describe_connection_error(&e.to_string())
+                    ),
                 });
                 overall_status = HealthStatus::Critical;
             },
@@ -523,8 +881,38 @@ impl ClusterService {
         let mut checks = Vec::new();
         let mut overall_status = HealthStatus::Healthy;
 
+        // Connection tests use a throwaway cluster (id 0): give each test its own pools
+        // instead of sharing the cached ones with concurrent tests
+        let pool_manager = if cluster.id == 0 {
+            Arc::new(self.mysql_pool_manager.detached())
+        } else {
+            Arc::clone(&self.mysql_pool_manager)
+        };
+
+        // Check TLS settings first: certificate problems are reported on their own
+        match ClusterTls::from_cluster(cluster, pool_manager.cipher()) {
+            Ok(Some(tls)) => checks.push(HealthCheck {
+                name: "TLS Configuration".to_string(),
+                status: "ok".to_string(),
+                message: tls.summary(),
+            }),
+            Ok(None) => {},
+            Err(e) => {
+                checks.push(HealthCheck {
+                    name: "TLS Configuration".to_string(),
+                    status: "critical".to_string(),
+                    message: e.to_string(),
+                });
+                return Ok(ClusterHealth {
+                    status: HealthStatus::Critical,
+                    checks,
+                    last_check_time: Utc::now(),
+                });
+            },
+        }
+
         // Check connection by getting pool
-        match self.mysql_pool_manager.get_pool(cluster).await {
+        match pool_manager.get_pool(cluster).await {
             Ok(pool) => {
                 let mysql_client = MySQLClient::from_pool(pool);
 
@@ -539,7 +927,7 @@ impl ClusterService {
 
                         // Try to check FE availability via HTTP
                         let client =
-                            StarRocksClient::new(cluster.clone(), self.mysql_pool_manager.clone());
+                            StarRocksClient::new(cluster.clone(), Arc::clone(&pool_manager));
                         match client.get_runtime_info().await {
                             Ok(_) => {
                                 checks.push(HealthCheck {
@@ -552,7 +940,10 @@ impl ClusterService {
                                 checks.push(HealthCheck {
                                     name: "FE Availability".to_string(),
                                     status: "warning".to_string(),
-                                    message: format!("FE HTTP check failed: {}", e),
+                                    message: format!(
+                                        "FE HTTP check failed: {}",
+                                        describe_connection_error(&e.to_string())
+                                    ),
                                 });
                                 if overall_status == HealthStatus::Healthy {
                                     overall_status = HealthStatus::Warning;
@@ -623,7 +1014,10 @@ impl ClusterService {
                         checks.push(HealthCheck {
                             name: "Database Connection".to_string(),
                             status: "critical".to_string(),
-                            message: format!("Connection failed: {}", e),
+                            message: format!(
+                                "Connection failed: {}",
+                                describe_connection_error(&e.to_string())
+                            ),
                         });
                         overall_status = HealthStatus::Critical;
                     },
@@ -633,7 +1027,10 @@ impl ClusterService {
                 checks.push(HealthCheck {
                     name: "Connection Pool".to_string(),
                     status: "critical".to_string(),
-                    message: format!("Failed to create connection pool: {}", e),
+                    message: format!(
+                        "Failed to connect: {}",
+                        describe_connection_error(&e.to_string())
+                    ),
                 });
                 overall_status = HealthStatus::Critical;
             },
@@ -642,3 +1039,16 @@ impl ClusterService {
         Ok(ClusterHealth { status: overall_status, checks, last_check_time: Utc::now() })
     }
 }
+
+/// Decrypt a value sealed with `current` and seal it again with `next`
+pub(crate) fn reseal(
+    current: &CredentialCipher,
+    next: &CredentialCipher,
+    stored: &str,
+    what: impl FnOnce() -> String,
+) -> ApiResult<String> {
+    let plaintext = current
+        .decrypt(stored)
+        .map_err(|e| ApiError::internal_error(format!("Cannot decrypt {}: {}", what(), e)))?;
+    next.encrypt(&plaintext)
+}
@@ -2,9 +2,11 @@
 // Purpose: Collect and cache expensive data statistics (database/table counts, top tables, etc.)
 // Design Ref: CLUSTER_OVERVIEW_PLAN.md
 
-use crate::config::AuditLogConfig;
 use crate::models::Cluster;
-use crate::services::{ClusterService, MaterializedViewService, MySQLClient, MySQLPoolManager};
+use crate::services::{
+    AuditColumn, AuditSource, AuditSourceService, ClusterService, MaterializedViewService,
+    MySQLClient, MySQLPoolManager,
+};
 use crate::utils::ApiResult;
 use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -69,7 +71,7 @@ pub struct DataStatisticsService {
     db: SqlitePool,
     cluster_service: Arc<ClusterService>,
     mysql_pool_manager: Arc<MySQLPoolManager>,
-    audit_config: AuditLogConfig,
+    audit_source_service: Arc<AuditSourceService>,
 }
 
 impl DataStatisticsService {
@@ -78,9 +80,9 @@ impl DataStatisticsService {
         db: SqlitePool,
         cluster_service: Arc<ClusterService>,
         mysql_pool_manager: Arc<MySQLPoolManager>,
-        audit_config: AuditLogConfig,
+        audit_source_service: Arc<AuditSourceService>,
     ) -> Self {
-        Self { db, cluster_service, mysql_pool_manager, audit_config }
+        Self { db, cluster_service, mysql_pool_manager, audit_source_service }
     }
 
     /// Collect and update data statistics for a cluster
@@ -96,6 +98,7 @@ impl DataStatisticsService {
         // Get MySQL connection pool
         let pool = self.mysql_pool_manager.get_pool(&cluster).await?;
         let mysql_client = MySQLClient::from_pool(pool);
+        let audit = self.audit_source_service.resolve(&cluster).await?;
 
         // Get database and table counts using MySQL queries
         let database_count = self.get_database_count_mysql(&mysql_client).await? as i32;
@@ -109,7 +112,7 @@ impl DataStatisticsService {
         let time_range_start =
             time_range_start.unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::days(3));
         let top_tables_by_access = self
-            .get_top_tables_by_access(&cluster, 20, time_range_start)
+            .get_top_tables_by_access(&cluster, &audit, 20, time_range_start)
             .await?;
 
         // Calculate total data size from all tables (not just top 20)
@@ -127,7 +130,7 @@ impl DataStatisticsService {
             schema_change_finished,
             schema_change_failed,
         ) = self
-            .get_schema_change_statistics_mysql(&mysql_client)
+            .get_schema_change_statistics_mysql(&mysql_client, &audit)
             .await?;
 
         // Get active users using MySQL
@@ -330,6 +333,7 @@ impl DataStatisticsService {
     async fn get_top_tables_by_access(
         &self,
         cluster: &Cluster,
+        audit: &AuditSource,
         limit: usize,
         time_range_start: chrono::DateTime<chrono::Utc>,
     ) -> ApiResult<Vec<TopTableByAccess>> {
@@ -345,14 +349,14 @@ impl DataStatisticsService {
         let query_with_table = format!(
             r#"
             SELECT 
-                `catalog`,
-                `db` as database_name,
+                {catalog} as `catalog`,
+                {db} as database_name,
                 LOWER(
                     REPLACE(
                         REPLACE(
                             SUBSTRING_INDEX(
                                 SUBSTRING_INDEX(
-                                    SUBSTRING_INDEX(stmt, 'FROM ', -1),
+                                    SUBSTRING_INDEX({stmt}, 'FROM ', -1),
                                     ' ', 
                                     1
                                 ),
@@ -367,17 +371,17 @@ impl DataStatisticsService {
                     )
                 ) as table_name,
                 SUBSTRING_INDEX(
-                    SUBSTRING_INDEX(stmt, 'FROM ', -1),
+                    SUBSTRING_INDEX({stmt}, 'FROM ', -1),
                     ' ', 
                     1
                 ) as full_table_ref,
                 COUNT(*) as access_count
-            FROM {}
-            WHERE timestamp >= '{}'
-                AND `catalog` != ''
-                AND (`db` NOT IN ('information_schema', '_statistics_', 'sys', '{}', 'recycle_dw') OR `db` IS NULL OR `db` = '')
-                AND UPPER(stmt) LIKE '%FROM %'
-                AND LOWER(stmt) NOT LIKE '%{}%'
+            FROM {table}
+            WHERE {ts} >= '{start}'
+                AND {catalog} != ''
+                AND ({db} NOT IN ('information_schema', '_statistics_', 'sys', '{audit_db}', 'recycle_dw') OR {db} IS NULL OR {db} = '')
+                AND UPPER({stmt}) LIKE '%FROM %'
+                AND LOWER({stmt}) NOT LIKE '%{audit_table}%'
             GROUP BY `catalog`, database_name, table_name, full_table_ref
             HAVING table_name NOT LIKE '%select%'
                 AND table_name NOT LIKE '%(%'
@@ -386,13 +390,16 @@ impl DataStatisticsService {
                 AND LENGTH(table_name) > 0
                 AND LENGTH(table_name) < 100
             ORDER BY access_count DESC
-            LIMIT {}
+            LIMIT {limit}
             "#,
-            self.audit_config.full_table_name(),
-            start_time_str,
-            self.audit_config.database,
-            self.audit_config.table,
-            limit
+            catalog = audit.col(AuditColumn::Catalog),
+            db = audit.col(AuditColumn::Db),
+            stmt = audit.col(AuditColumn::Stmt),
+            table = audit.full_table_name(),
+            ts = audit.col(AuditColumn::Timestamp),
+            start = start_time_str,
+            audit_db = audit.database,
+            audit_table = audit.table.to_lowercase(),
         );
 
         tracing::debug!("Querying top tables by access with table name extraction");
@@ -479,8 +486,8 @@ impl DataStatisticsService {
                         if final_db.contains("information_schema")
                             || final_db.contains("_statistics_")
                             || final_db.contains("sys")
-                            || final_db.contains(&self.audit_config.database)
-                            || final_table == self.audit_config.table
+                            || final_db.contains(&audit.database)
+                            || final_table == audit.table
                         {
                             tracing::debug!(
                                 "Filtering out system table: {}.{}",
@@ -512,19 +519,20 @@ impl DataStatisticsService {
                 let query_db_only = format!(
                     r#"
                     SELECT 
-                        db as database_name,
+                        {db} as database_name,
                         COUNT(*) as access_count
-                    FROM {}
-                    WHERE timestamp >= '{}'
-                        AND db NOT IN ('information_schema', '_statistics_', '', 'sys', '{}', 'recycle_dw')
-                    GROUP BY db
+                    FROM {table}
+                    WHERE {ts} >= '{start}'
+                        AND {db} NOT IN ('information_schema', '_statistics_', '', 'sys', '{audit_db}', 'recycle_dw')
+                    GROUP BY database_name
                     ORDER BY access_count DESC
-                    LIMIT {}
+                    LIMIT {limit}
                     "#,
-                    self.audit_config.full_table_name(),
-                    start_time_str,
-                    self.audit_config.database,
-                    limit
+                    db = audit.col(AuditColumn::Db),
+                    table = audit.full_table_name(),
+                    ts = audit.col(AuditColumn::Timestamp),
+                    start = start_time_str,
+                    audit_db = audit.database,
                 );
 
                 match mysql_client.query(&query_db_only).await {
@@ -715,8 +723,9 @@ impl DataStatisticsService {
     async fn get_schema_change_statistics_mysql(
         &self,
         mysql_client: &MySQLClient,
+        audit: &AuditSource,
     ) -> ApiResult<(i32, i32, i32, i32)> {
-        let databases = self.list_user_databases(mysql_client).await?;
+        let databases = self.list_user_databases(mysql_client, audit).await?;
 
         if databases.is_empty() {
             return Ok((0, 0, 0, 0));
@@ -759,8 +768,13 @@ impl DataStatisticsService {
     }
 
     /// List user databases excluding system schemas
-    async fn list_user_databases(&self, mysql_client: &MySQLClient) -> ApiResult<Vec<String>> {
-        let system_dbs = ["information_schema", "_statistics_", &self.audit_config.database, "sys"];
+    async fn list_user_databases(
+        &self,
+        mysql_client: &MySQLClient,
+        audit: &AuditSource,
+    ) -> ApiResult<Vec<String>> {
+        let audit_db = audit.database.to_lowercase();
+        let system_dbs = ["information_schema", "_statistics_", &audit_db, "sys"];
 
         let mut databases = Vec::new();
         let (columns, rows) = mysql_client.query_raw("SHOW DATABASES").await?;
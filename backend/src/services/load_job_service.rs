@@ -0,0 +1,439 @@
+use chrono::NaiveDateTime;
+use serde_json::Value;
+
+use crate::models::{LoadJob, LoadJobDetail, LoadJobFilter, LoadTrackingLog};
+use crate::services::{AuditColumn, AuditSource, MySQLClient};
+use crate::utils::sql_lexer::{self, Token, TokenKind};
+use crate::utils::{ApiError, ApiResult};
+
+/// Jobs returned when the list request sets no limit
+pub const DEFAULT_LIST_LIMIT: usize = 200;
+/// Upper bound of the list limit
+pub const MAX_LIST_LIMIT: usize = 1000;
+
+/// Catalog load jobs write to
+const DEFAULT_CATALOG: &str = "default_catalog";
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// States a job cannot leave any more (COMMITTED jobs are already being published)
+const SETTLED_STATES: &[&str] = &["COMMITTED", "FINISHED", "CANCELLED"];
+/// Types `CANCEL LOAD` does not apply to: Stream Load is synchronous, Routine Load is paused
+/// or stopped instead
+const UNCANCELLABLE_TYPES: &[&str] = &["STREAM_LOAD", "ROUTINE_LOAD"];
+/// Types that can be resubmitted from their audited statement. Stream Load data is pushed by
+/// its client and Routine Load jobs are resumed instead.
+const RETRYABLE_TYPES: &[&str] = &["BROKER", "SPARK", "INSERT"];
+/// Audited statements considered when looking up the statement of a job
+const STATEMENT_LOOKUP_LIMIT: usize = 10;
+
+/// Load job center over information_schema.loads. Column names differ between StarRocks
+/// versions (3.1 renamed DATABASE_NAME to DB_NAME, JOB_DETAILS to RUNTIME_DETAILS and dropped
+/// TRACKING_URL), so rows are read by any of their known names.
+pub struct LoadJobService {
+    mysql_client: MySQLClient,
+}
+
+impl LoadJobService {
+    pub fn new(mysql_client: MySQLClient) -> Self {
+        Self { mysql_client }
+    }
+
+    /// Load jobs across all databases matching the filter, newest first. The database filter
+    /// and the limit are applied here as the database column name depends on the version.
+    pub async fn list_load_jobs(&self, filter: &LoadJobFilter) -> ApiResult<Vec<LoadJob>> {
+        let sql = Self::build_list_query(filter)?;
+        let results = self.mysql_client.query(&sql).await?;
+
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let jobs: Vec<LoadJob> = results
+            .iter()
+            .map(Self::parse_load_job)
+            .filter(|job| {
+                filter
+                    .database
+                    .as_deref()
+                    .is_none_or(|db| job.database_name == db)
+            })
+            .take(limit)
+            .collect();
+        tracing::debug!("Fetched {} load jobs", jobs.len());
+
+        Ok(jobs)
+    }
+
+    /// Load job with its properties and execution details
+    pub async fn get_load_job(&self, job_id: i64) -> ApiResult<LoadJobDetail> {
+        let sql = format!("SELECT * FROM information_schema.loads WHERE JOB_ID = {}", job_id);
+        let results = self.mysql_client.query(&sql).await?;
+        let row = results
+            .first()
+            .ok_or_else(|| ApiError::not_found(format!("Load job {} not found", job_id)))?;
+
+        Ok(Self::parse_load_job_detail(row))
+    }
+
+    /// Cancel a running load job (`CANCEL LOAD`), addressed by its database and label
+    pub async fn cancel_load_job(&self, job_id: i64) -> ApiResult<LoadJob> {
+        let detail = self.get_load_job(job_id).await?;
+        let job = detail.job;
+        if !detail.cancellable {
+            return Err(ApiError::validation_error(format!(
+                "Load job {} ({} {}) cannot be cancelled",
+                job_id, job.load_type, job.state
+            )));
+        }
+
+        let sql = Self::build_cancel_statement(&job);
+        tracing::info!("Cancelling load job {}: {}", job_id, sql);
+        self.mysql_client.execute(&sql).await?;
+
+        Ok(job)
+    }
+
+    /// Statement a cancelled load job can be resubmitted with, from the audit log of the
+    /// cluster. StarRocks accepts the label of a cancelled job again, so the statement runs
+    /// unchanged. Only statements the job's own user submitted successfully with exactly
+    /// `LABEL <db>.<label>` of the job count; INSERT jobs with a generated label cannot be
+    /// found and are not retried.
+    pub async fn retry_statement(
+        &self,
+        job_id: i64,
+        audit: &AuditSource,
+    ) -> ApiResult<(LoadJob, String)> {
+        let detail = self.get_load_job(job_id).await?;
+        let job = detail.job;
+        if !detail.retryable {
+            return Err(ApiError::validation_error(format!(
+                "Load job {} ({} {}) cannot be retried, only cancelled Broker, Spark and INSERT \
+                 loads can",
+                job_id, job.load_type, job.state
+            )));
+        }
+        let Some(sql) = Self::build_statement_lookup(&job, audit) else {
+            return Err(ApiError::validation_error(format!(
+                "Load job {} reports no user, its statement cannot be looked up",
+                job_id
+            )));
+        };
+
+        let results = self.mysql_client.query(&sql).await?;
+        let statement = results
+            .iter()
+            .filter_map(|row| {
+                let stmt = text(row, &["stmt"])?;
+                let db = text(row, &["db"]).unwrap_or_default();
+                Self::is_load_statement(&stmt, &db, &job).then_some(stmt)
+            })
+            .next()
+            .ok_or_else(|| {
+                ApiError::not_found(format!(
+                    "Statement of load job {} (label {}) not found in the audit log",
+                    job_id, job.label
+                ))
+            })?;
+        // The audit log masks credentials of broker properties
+        if statement.contains("***") {
+            return Err(ApiError::validation_error(format!(
+                "The audited statement of load job {} has masked credentials, resubmit it manually",
+                job_id
+            )));
+        }
+
+        Ok((job, statement))
+    }
+
+    /// Resubmit a load job with its statement (see `retry_statement`) in the job's database
+    pub async fn resubmit_load_job(&self, job: &LoadJob, statement: &str) -> ApiResult<()> {
+        tracing::info!("Retrying load job {} (label {})", job.id, job.label);
+        let mut session = self.mysql_client.create_session().await?;
+        session.use_database(&job.database_name).await?;
+        session.execute(statement).await?;
+        Ok(())
+    }
+
+    /// Tracking log of a load job. Clusters without information_schema.load_tracking_logs
+    /// (before 3.0) only report the tracking URL.
+    pub async fn get_tracking_log(&self, job_id: i64) -> ApiResult<LoadTrackingLog> {
+        let job = self.get_load_job(job_id).await?.job;
+
+        let sql = format!(
+            "SELECT * FROM information_schema.load_tracking_logs WHERE JOB_ID = {}",
+            job_id
+        );
+        let lines = match self.mysql_client.query(&sql).await {
+            Ok(results) => results
+                .iter()
+                .filter_map(|row| text(row, &["TRACKING_LOG"]))
+                .flat_map(|log| {
+                    log.lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to query tracking log of load job {}: {}", job_id, e);
+                Vec::new()
+            },
+        };
+
+        Ok(LoadTrackingLog {
+            job_id,
+            label: job.label,
+            database_name: job.database_name,
+            error_msg: job.error_msg,
+            tracking_url: job.tracking_url,
+            lines,
+        })
+    }
+
+    /// SELECT over information_schema.loads for the state, label, type and time filters
+    pub fn build_list_query(filter: &LoadJobFilter) -> ApiResult<String> {
+        let mut conditions = Vec::new();
+        if let Some(state) = non_empty(&filter.state) {
+            conditions.push(format!("STATE = '{}'", escape_string(&state.to_uppercase())));
+        }
+        if let Some(label) = non_empty(&filter.label) {
+            conditions.push(format!("LABEL LIKE '%{}%'", escape_string(&escape_like(label))));
+        }
+        if let Some(load_type) = non_empty(&filter.load_type) {
+            conditions.push(format!("TYPE = '{}'", escape_string(&load_type.to_uppercase())));
+        }
+        if let Some(start) = non_empty(&filter.start_time) {
+            conditions.push(format!("CREATE_TIME >= '{}'", parse_time("start_time", start)?));
+        }
+        if let Some(end) = non_empty(&filter.end_time) {
+            conditions.push(format!("CREATE_TIME < '{}'", parse_time("end_time", end)?));
+        }
+
+        let mut sql = "SELECT * FROM information_schema.loads".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY CREATE_TIME DESC");
+        Ok(sql)
+    }
+
+    pub fn build_cancel_statement(job: &LoadJob) -> String {
+        format!(
+            "CANCEL LOAD FROM `{}` WHERE LABEL = '{}'",
+            job.database_name.replace('`', "``"),
+            escape_string(&job.label)
+        )
+    }
+
+    /// Recent statements the user of a job ran without error that mention its label, from
+    /// the creation of the job on. None when the job reports no user.
+    pub fn build_statement_lookup(job: &LoadJob, audit: &AuditSource) -> Option<String> {
+        let user = job.user.as_deref()?;
+        let stmt = audit.col(AuditColumn::Stmt);
+        let ts = audit.col(AuditColumn::Timestamp);
+        let mut conditions = vec![
+            format!("{} = '{}'", audit.col(AuditColumn::User), escape_string(user)),
+            format!("{} <> 'ERR'", audit.col(AuditColumn::State)),
+            format!("{} LIKE '%{}%'", stmt, escape_string(&escape_like(&job.label))),
+        ];
+        if let Some(created) = job
+            .create_time
+            .as_deref()
+            .and_then(|time| parse_time("create_time", time).ok())
+        {
+            conditions.push(format!("{} >= DATE_SUB('{}', INTERVAL 1 MINUTE)", ts, created));
+        }
+        Some(format!(
+            "SELECT {} AS stmt, {} AS db FROM {} WHERE {} ORDER BY {} DESC LIMIT {}",
+            stmt,
+            audit.col(AuditColumn::Db),
+            audit.full_table_name(),
+            conditions.join(" AND "),
+            ts,
+            STATEMENT_LOOKUP_LIMIT
+        ))
+    }
+
+    /// Whether an audited statement, run in `session_db`, submitted exactly this job: its
+    /// `LABEL` resolves to the database and label of the job
+    pub fn is_load_statement(stmt: &str, session_db: &str, job: &LoadJob) -> bool {
+        Self::statement_label(stmt, session_db)
+            .is_some_and(|(db, label)| db == job.database_name && label == job.label)
+    }
+
+    /// Database and label a `LOAD LABEL [db.]label` or `INSERT INTO [db.]table ... WITH LABEL
+    /// label` statement submits its job with; unqualified names resolve to `session_db`
+    pub fn statement_label(stmt: &str, session_db: &str) -> Option<(String, String)> {
+        let tokens: Vec<Token<'_>> = sql_lexer::tokenize(stmt)
+            .into_iter()
+            .filter(|t| !t.is_trivia())
+            .collect();
+        let first = tokens.first()?;
+
+        if first.is_keyword("LOAD") {
+            if !tokens.get(1)?.is_keyword("LABEL") {
+                return None;
+            }
+            return match dotted_name(&tokens, 2).as_slice() {
+                [label] => Some((session_db.to_string(), label.clone())),
+                [db, label] => Some((db.clone(), label.clone())),
+                _ => None,
+            };
+        }
+
+        if !first.is_keyword("INSERT")
+            || !tokens
+                .get(1)
+                .is_some_and(|t| t.is_keyword("INTO") || t.is_keyword("OVERWRITE"))
+        {
+            return None;
+        }
+        let target = if tokens.get(2).is_some_and(|t| t.is_keyword("TABLE")) { 3 } else { 2 };
+        let database = match dotted_name(&tokens, target).as_slice() {
+            [_table] => session_db.to_string(),
+            [db, _table] => db.clone(),
+            [catalog, db, _table] if catalog.eq_ignore_ascii_case(DEFAULT_CATALOG) => db.clone(),
+            _ => return None,
+        };
+        let with_label = tokens
+            .windows(2)
+            .position(|pair| pair[0].is_keyword("WITH") && pair[1].is_keyword("LABEL"))?;
+        match dotted_name(&tokens, with_label + 2).as_slice() {
+            [label] => Some((database, label.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn parse_load_job(row: &Value) -> LoadJob {
+        let error_msg = text(row, &["ERROR_MSG"]);
+        // 3.1+ reports the error URL inside the error message only
+        let tracking_url =
+            text(row, &["TRACKING_URL"]).or_else(|| error_msg.as_deref().and_then(error_url));
+        LoadJob {
+            id: number(row, &["JOB_ID", "ID"]),
+            label: text(row, &["LABEL"]).unwrap_or_default(),
+            database_name: text(row, &["DB_NAME", "DATABASE_NAME"]).unwrap_or_default(),
+            table_name: text(row, &["TABLE_NAME"]),
+            user: text(row, &["USER"]),
+            state: text(row, &["STATE"]).unwrap_or_else(|| "UNKNOWN".to_string()),
+            load_type: text(row, &["TYPE"]).unwrap_or_else(|| "UNKNOWN".to_string()),
+            progress: text(row, &["PROGRESS"]),
+            priority: text(row, &["PRIORITY"]),
+            scan_rows: number(row, &["SCAN_ROWS"]),
+            filtered_rows: number(row, &["FILTERED_ROWS"]),
+            unselected_rows: number(row, &["UNSELECTED_ROWS"]),
+            sink_rows: number(row, &["SINK_ROWS"]),
+            create_time: text(row, &["CREATE_TIME"]),
+            load_start_time: text(row, &["LOAD_START_TIME"]),
+            load_finish_time: text(row, &["LOAD_FINISH_TIME"]),
+            error_msg,
+            tracking_url,
+            rejected_record_path: text(row, &["REJECTED_RECORD_PATH"]),
+        }
+    }
+
+    pub fn parse_load_job_detail(row: &Value) -> LoadJobDetail {
+        let job = Self::parse_load_job(row);
+        let cancellable = Self::is_cancellable(&job);
+        let retryable = Self::is_retryable(&job);
+        LoadJobDetail {
+            job,
+            properties: text(row, &["PROPERTIES"]),
+            runtime_details: text(row, &["RUNTIME_DETAILS", "JOB_DETAILS"]),
+            etl_info: text(row, &["ETL_INFO"]),
+            task_info: text(row, &["TASK_INFO"]),
+            tracking_sql: text(row, &["TRACKING_SQL"]),
+            cancellable,
+            retryable,
+        }
+    }
+
+    pub fn is_cancellable(job: &LoadJob) -> bool {
+        let state = job.state.to_uppercase();
+        let load_type = job.load_type.to_uppercase();
+        !SETTLED_STATES.contains(&state.as_str())
+            && !UNCANCELLABLE_TYPES.contains(&load_type.as_str())
+    }
+
+    pub fn is_retryable(job: &LoadJob) -> bool {
+        job.state.eq_ignore_ascii_case("CANCELLED")
+            && RETRYABLE_TYPES.contains(&job.load_type.to_uppercase().as_str())
+    }
+}
+
+/// First non-NULL, non-empty value among the column names, ignoring case
+pub(crate) fn text(row: &Value, names: &[&str]) -> Option<String> {
+    let object = row.as_object()?;
+    names.iter().find_map(|name| {
+        object
+            .iter()
+            .find(|(column, _)| column.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty() && *value != "NULL")
+            .map(str::to_string)
+    })
+}
+
+pub(crate) fn number(row: &Value, names: &[&str]) -> i64 {
+    text(row, names)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Parts of the dotted name starting at token `start`, backticks removed
+fn dotted_name(tokens: &[Token<'_>], start: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut i = start;
+    while let Some(token) = tokens.get(i) {
+        match token.kind {
+            TokenKind::Word => parts.push(token.text.to_string()),
+            TokenKind::QuotedIdentifier => {
+                parts.push(token.text.trim_matches('`').replace("``", "`"))
+            },
+            _ => break,
+        }
+        if tokens.get(i + 1).is_some_and(|t| t.text == ".") {
+            i += 2;
+        } else {
+            break;
+        }
+    }
+    parts
+}
+
+/// First http(s) URL of an error message
+fn error_url(message: &str) -> Option<String> {
+    message
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .find(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|url| url.trim_end_matches(['.', ')', ']']).to_string())
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Validate and normalize a `YYYY-MM-DD HH:MM:SS` filter value
+pub(crate) fn parse_time(field: &str, value: &str) -> ApiResult<String> {
+    NaiveDateTime::parse_from_str(value, TIME_FORMAT)
+        .map(|time| time.format(TIME_FORMAT).to_string())
+        .map_err(|_| {
+            ApiError::validation_error(format!(
+                "{} must be formatted as YYYY-MM-DD HH:MM:SS",
+                field
+            ))
+        })
+}
+
+/// Escape a value for a single-quoted SQL string
+pub(crate) fn escape_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Escape the LIKE wildcards of a value (before `escape_string`)
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,156 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use sqlx::SqlitePool;
+
+use crate::config::LoginLockoutConfig;
+use crate::services::session_service::SessionClient;
+use crate::utils::{ApiError, ApiResult};
+
+struct AddressFailures {
+    count: u32,
+    window_start: Instant,
+}
+
+/// Throttling of failed password logins: accounts are locked in the database after
+/// consecutive failures, client addresses are limited in memory over a window starting at
+/// their first failure.
+pub struct LoginGuard {
+    pool: SqlitePool,
+    config: LoginLockoutConfig,
+    address_failures: DashMap<String, AddressFailures>,
+}
+
+impl LoginGuard {
+    pub fn new(pool: SqlitePool, config: LoginLockoutConfig) -> Self {
+        Self { pool, config, address_failures: DashMap::new() }
+    }
+
+    /// Run a login unless the account or the address is throttled, counting its outcome.
+    /// Only invalid credentials (password or second factor) count as failures.
+    pub async fn attempt<T>(
+        &self,
+        username: &str,
+        client: &SessionClient,
+        login: impl Future<Output = ApiResult<T>>,
+    ) -> ApiResult<T> {
+        self.check(username, client).await?;
+        let result = login.await;
+        match &result {
+            Ok(_) => self.record_success(username).await?,
+            Err(ApiError::InvalidCredentials) => self.record_failure(username, client).await?,
+            Err(_) => {},
+        }
+        result
+    }
+
+    pub async fn check(&self, username: &str, client: &SessionClient) -> ApiResult<()> {
+        if let Some(address) = &client.ip_address
+            && self.address_blocked(address)
+        {
+            tracing::warn!("Login refused: too many failures from {}", address);
+            return Err(ApiError::too_many_attempts(
+                "too many failed logins from this address, try again later",
+            ));
+        }
+
+        if self.config.max_failed_attempts == 0 {
+            return Ok(());
+        }
+        if let Some(locked_until) = self.locked_until(username).await? {
+            tracing::warn!("Login refused: '{}' is locked until {}", username, locked_until);
+            let minutes = (locked_until - Utc::now()).num_minutes() + 1;
+            return Err(ApiError::too_many_attempts(format!(
+                "the account is locked, try again in {} minute(s)",
+                minutes
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn record_failure(&self, username: &str, client: &SessionClient) -> ApiResult<()> {
+        if let Some(address) = &client.ip_address
+            && self.config.ip_max_failed_attempts > 0
+        {
+            let window = Duration::from_secs(self.config.ip_window_secs);
+            self.address_failures
+                .retain(|_, failures| failures.window_start.elapsed() < window);
+            let mut failures = self
+                .address_failures
+                .entry(address.clone())
+                .or_insert_with(|| AddressFailures { count: 0, window_start: Instant::now() });
+            failures.count += 1;
+        }
+
+        if self.config.max_failed_attempts == 0 {
+            return Ok(());
+        }
+        // The counter restarts once the account is locked, so it gets a full set of attempts
+        // when the lock ends
+        let locked_until = Utc::now() + chrono::Duration::seconds(self.config.lockout_secs as i64);
+        let result = sqlx::query(
+            "UPDATE users SET \
+             failed_login_attempts = CASE WHEN failed_login_attempts + 1 >= ? THEN 0 \
+                 ELSE failed_login_attempts + 1 END, \
+             locked_until = CASE WHEN failed_login_attempts + 1 >= ? THEN ? ELSE locked_until END \
+             WHERE username = ?",
+        )
+        .bind(self.config.max_failed_attempts)
+        .bind(self.config.max_failed_attempts)
+        .bind(locked_until)
+        .bind(username)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() > 0 && self.locked_until(username).await?.is_some() {
+            tracing::warn!(
+                "Account '{}' locked for {}s after {} failed logins",
+                username,
+                self.config.lockout_secs,
+                self.config.max_failed_attempts
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn record_success(&self, username: &str) -> ApiResult<()> {
+        sqlx::query(
+            "UPDATE users SET failed_login_attempts = 0, locked_until = NULL \
+             WHERE username = ? AND (failed_login_attempts > 0 OR locked_until IS NOT NULL)",
+        )
+        .bind(username)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Lift the lock of an account (administrators)
+    pub async fn unlock(&self, user_id: i64) -> ApiResult<()> {
+        sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    fn address_blocked(&self, address: &str) -> bool {
+        let max = self.config.ip_max_failed_attempts;
+        let window = Duration::from_secs(self.config.ip_window_secs);
+        max > 0
+            && self.address_failures.get(address).is_some_and(|failures| {
+                failures.count >= max && failures.window_start.elapsed() < window
+            })
+    }
+
+    /// End of the current lock of an account
+    async fn locked_until(&self, username: &str) -> ApiResult<Option<DateTime<Utc>>> {
+        let locked_until: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT locked_until FROM users WHERE username = ?")
+                .bind(username)
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+        Ok(locked_until.filter(|until| *until > Utc::now()))
+    }
+}
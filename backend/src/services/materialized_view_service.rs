@@ -1,6 +1,44 @@
-use crate::models::MaterializedView;
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::models::{
+    MaterializedView, MvDependencyEdge, MvDependencyGraph, MvDependencyNode, MvDependencyNodeType,
+    MvImpact, MvImpactedView, MvStaleness, MvStalenessIssue,
+};
 use crate::services::MySQLClient;
-use crate::utils::{ApiError, ApiResult};
+use crate::services::load_job_service::{escape_string, text};
+use crate::utils::{ApiError, ApiResult, sql_lexer};
+
+/// Catalog of internal tables and materialized views
+pub const DEFAULT_CATALOG: &str = "default_catalog";
+
+/// Failed refreshes in a row after which a view is reported as failing
+pub const REFRESH_FAILURE_THRESHOLD: i64 = 2;
+
+/// Async materialized view as read for the dependency graph
+#[derive(Debug, Clone, Default)]
+pub struct MvDefinition {
+    pub database_name: String,
+    pub name: String,
+    pub refresh_type: String,
+    pub is_active: bool,
+    pub inactive_reason: Option<String>,
+    pub task_name: Option<String>,
+    pub last_refresh_state: Option<String>,
+    pub last_refresh_start_time: Option<String>,
+    pub last_refresh_finished_time: Option<String>,
+    pub last_refresh_error: Option<String>,
+    /// CREATE statement or defining query
+    pub definition: String,
+}
+
+impl MvDefinition {
+    /// `catalog.database.name`
+    pub fn id(&self) -> String {
+        object_id(DEFAULT_CATALOG, &self.database_name, &self.name)
+    }
+}
 
 pub struct MaterializedViewService {
     mysql_client: MySQLClient,
@@ -280,8 +318,181 @@ impl MaterializedViewService {
         Ok(())
     }
 
+    /// Dependency DAG of the async materialized views
+    /// With a database, only its views and the objects they read are returned
+    pub async fn get_dependency_graph(
+        &self,
+        database: Option<&str>,
+    ) -> ApiResult<MvDependencyGraph> {
+        let (_, graph) = self.load_dependencies().await?;
+        let Some(db) = database else {
+            return Ok(graph);
+        };
+
+        let edges: Vec<MvDependencyEdge> = graph
+            .edges
+            .into_iter()
+            .filter(|edge| {
+                graph
+                    .nodes
+                    .iter()
+                    .any(|n| n.id == edge.source && n.database_name == db)
+            })
+            .collect();
+        let nodes = graph
+            .nodes
+            .into_iter()
+            .filter(|node| {
+                (node.node_type == MvDependencyNodeType::MaterializedView
+                    && node.database_name == db)
+                    || edges.iter().any(|edge| edge.target == node.id)
+            })
+            .collect();
+
+        Ok(MvDependencyGraph { nodes, edges })
+    }
+
+    /// Freshness of every async materialized view, optionally of one database
+    pub async fn get_staleness_report(
+        &self,
+        database: Option<&str>,
+    ) -> ApiResult<Vec<MvStaleness>> {
+        let (mvs, graph) = self.load_dependencies().await?;
+        let mvs: Vec<MvDefinition> = mvs
+            .into_iter()
+            .filter(|mv| database.is_none_or(|db| mv.database_name == db))
+            .collect();
+
+        let task_names: Vec<&str> = mvs
+            .iter()
+            .filter_map(|mv| mv.task_name.as_deref())
+            .collect();
+        let run_states = self.get_task_run_states(&task_names).await;
+
+        Ok(mvs
+            .iter()
+            .map(|mv| {
+                let failures = match mv.task_name.as_ref().and_then(|t| run_states.get(t)) {
+                    Some(states) => consecutive_failures(states),
+                    // No run history: only the last refresh is known
+                    None => i64::from(mv.last_refresh_state.as_deref() == Some("FAILED")),
+                };
+                assess_staleness(mv, &graph, failures)
+            })
+            .collect())
+    }
+
+    /// Materialized views that break when a table (`db.table` or `catalog.db.table`) is dropped
+    pub async fn get_impact(&self, table: &str) -> ApiResult<MvImpact> {
+        let (catalog, database, name) = parse_object_id(table)?;
+        let (_, graph) = self.load_dependencies().await?;
+        Ok(impact(&graph, &object_id(&catalog, &database, &name)))
+    }
+
     // ========== Private Helper Methods ==========
 
+    /// Read the async materialized views and build their dependency graph
+    async fn load_dependencies(&self) -> ApiResult<(Vec<MvDefinition>, MvDependencyGraph)> {
+        // SELECT * so that columns missing in older versions (INACTIVE_REASON, ...) are optional
+        let sql = "SELECT * FROM information_schema.materialized_views \
+                   WHERE TABLE_SCHEMA NOT IN ('information_schema', '_statistics_')";
+        tracing::debug!("Querying materialized view definitions: {}", sql);
+        let mvs: Vec<MvDefinition> = self
+            .mysql_client
+            .query(sql)
+            .await?
+            .iter()
+            .map(Self::parse_mv_definition)
+            .collect();
+
+        let mut databases = BTreeSet::new();
+        for mv in &mvs {
+            for (catalog, database, _) in base_tables(mv) {
+                if catalog == DEFAULT_CATALOG {
+                    databases.insert(database);
+                }
+            }
+        }
+        let table_updates = self.get_table_update_times(&databases).await?;
+
+        let graph = build_dependency_graph(&mvs, &table_updates);
+        tracing::debug!(
+            "Built materialized view graph with {} nodes and {} edges",
+            graph.nodes.len(),
+            graph.edges.len()
+        );
+        Ok((mvs, graph))
+    }
+
+    /// Last data change of the tables of the given databases, by table ID
+    async fn get_table_update_times(
+        &self,
+        databases: &BTreeSet<String>,
+    ) -> ApiResult<HashMap<String, String>> {
+        if databases.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let list = databases
+            .iter()
+            .map(|db| format!("'{}'", escape_string(db)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT TABLE_SCHEMA, TABLE_NAME, UPDATE_TIME FROM information_schema.tables \
+             WHERE TABLE_SCHEMA IN ({})",
+            list
+        );
+        tracing::debug!("Querying table update times: {}", sql);
+
+        let results = self.mysql_client.query(&sql).await?;
+        Ok(results
+            .iter()
+            .filter_map(|row| {
+                let database = text(row, &["TABLE_SCHEMA"])?;
+                let name = text(row, &["TABLE_NAME"])?;
+                let updated = text(row, &["UPDATE_TIME"])?;
+                Some((object_id(DEFAULT_CATALOG, &database, &name), updated))
+            })
+            .collect())
+    }
+
+    /// States of the refresh task runs, newest first, by task name
+    ///
+    /// Best effort: task_runs only keeps recent runs and may be unavailable.
+    async fn get_task_run_states(&self, task_names: &[&str]) -> HashMap<String, Vec<String>> {
+        let mut states: HashMap<String, Vec<String>> = HashMap::new();
+        if task_names.is_empty() {
+            return states;
+        }
+
+        let list = task_names
+            .iter()
+            .map(|name| format!("'{}'", escape_string(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT TASK_NAME, STATE FROM information_schema.task_runs \
+             WHERE TASK_NAME IN ({}) ORDER BY CREATE_TIME DESC",
+            list
+        );
+        tracing::debug!("Querying refresh task runs: {}", sql);
+
+        match self.mysql_client.query(&sql).await {
+            Ok(rows) => {
+                for row in &rows {
+                    if let (Some(task), Some(state)) =
+                        (text(row, &["TASK_NAME"]), text(row, &["STATE"]))
+                    {
+                        states.entry(task).or_default().push(state);
+                    }
+                }
+            },
+            Err(e) => tracing::warn!("Failed to query refresh task runs: {}", e),
+        }
+        states
+    }
+
     /// Get all databases (excluding system databases)
     async fn get_all_databases(&self) -> ApiResult<Vec<String>> {
         let sql = "SHOW DATABASES";
@@ -531,4 +742,213 @@ impl MaterializedViewService {
 
         Ok(mvs)
     }
+
+    /// Parse a row of information_schema.materialized_views
+    pub fn parse_mv_definition(row: &Value) -> MvDefinition {
+        MvDefinition {
+            database_name: text(row, &["TABLE_SCHEMA"]).unwrap_or_default(),
+            name: text(row, &["TABLE_NAME"]).unwrap_or_default(),
+            refresh_type: text(row, &["REFRESH_TYPE"]).unwrap_or_else(|| "UNKNOWN".to_string()),
+            is_active: text(row, &["IS_ACTIVE"])
+                .is_some_and(|s| s.eq_ignore_ascii_case("true") || s == "1"),
+            inactive_reason: text(row, &["INACTIVE_REASON"]),
+            task_name: text(row, &["TASK_NAME"]),
+            last_refresh_state: text(row, &["LAST_REFRESH_STATE"]),
+            last_refresh_start_time: text(row, &["LAST_REFRESH_START_TIME"]),
+            last_refresh_finished_time: text(row, &["LAST_REFRESH_FINISHED_TIME"]),
+            last_refresh_error: text(row, &["LAST_REFRESH_ERROR_MESSAGE"]),
+            definition: text(row, &["MATERIALIZED_VIEW_DEFINITION"]).unwrap_or_default(),
+        }
+    }
+}
+
+/// `catalog.database.name` ID of a graph node
+pub fn object_id(catalog: &str, database: &str, name: &str) -> String {
+    format!("{}.{}.{}", catalog, database, name)
+}
+
+/// Split `db.table` or `catalog.db.table` into catalog, database and name
+pub fn parse_object_id(table: &str) -> ApiResult<(String, String, String)> {
+    let parts: Vec<&str> = table.split('.').map(|p| p.trim_matches('`')).collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        return Err(ApiError::validation_error(format!("Invalid table name '{}'", table)));
+    }
+    match parts.as_slice() {
+        [database, name] => {
+            Ok((DEFAULT_CATALOG.to_string(), database.to_string(), name.to_string()))
+        },
+        [catalog, database, name] => {
+            Ok((catalog.to_string(), database.to_string(), name.to_string()))
+        },
+        _ => Err(ApiError::validation_error(format!(
+            "Invalid table name '{}', expected db.table or catalog.db.table",
+            table
+        ))),
+    }
+}
+
+/// Catalog, database and name of the objects a view reads
+///
+/// Unqualified names resolve against the view's database in the default catalog.
+pub fn base_tables(mv: &MvDefinition) -> Vec<(String, String, String)> {
+    sql_lexer::table_references(&mv.definition)
+        .into_iter()
+        .filter_map(|parts| match parts.as_slice() {
+            [name] => Some((DEFAULT_CATALOG.to_string(), mv.database_name.clone(), name.clone())),
+            [database, name] => Some((DEFAULT_CATALOG.to_string(), database.clone(), name.clone())),
+            [catalog, database, name] => Some((catalog.clone(), database.clone(), name.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build the DAG from the views and the last update time of their base tables
+pub fn build_dependency_graph(
+    mvs: &[MvDefinition],
+    table_updates: &HashMap<String, String>,
+) -> MvDependencyGraph {
+    let mut graph = MvDependencyGraph::default();
+    let mut seen = HashSet::new();
+
+    for mv in mvs {
+        let id = mv.id();
+        if seen.insert(id.clone()) {
+            graph.nodes.push(MvDependencyNode {
+                id,
+                catalog: DEFAULT_CATALOG.to_string(),
+                database_name: mv.database_name.clone(),
+                name: mv.name.clone(),
+                node_type: MvDependencyNodeType::MaterializedView,
+                is_active: Some(mv.is_active),
+                last_refresh_state: mv.last_refresh_state.clone(),
+                last_updated: mv.last_refresh_finished_time.clone(),
+            });
+        }
+    }
+
+    for mv in mvs {
+        for (catalog, database, name) in base_tables(mv) {
+            let target = object_id(&catalog, &database, &name);
+            if seen.insert(target.clone()) {
+                let node_type = if catalog == DEFAULT_CATALOG {
+                    MvDependencyNodeType::Table
+                } else {
+                    MvDependencyNodeType::ExternalTable
+                };
+                graph.nodes.push(MvDependencyNode {
+                    last_updated: table_updates.get(&target).cloned(),
+                    id: target.clone(),
+                    catalog,
+                    database_name: database,
+                    name,
+                    node_type,
+                    is_active: None,
+                    last_refresh_state: None,
+                });
+            }
+            graph
+                .edges
+                .push(MvDependencyEdge { source: mv.id(), target });
+        }
+    }
+
+    graph
+}
+
+/// Failed runs before the latest successful one, given run states newest first
+pub fn consecutive_failures(states: &[String]) -> i64 {
+    states
+        .iter()
+        .filter(|state| !matches!(state.as_str(), "PENDING" | "RUNNING"))
+        .take_while(|state| state.as_str() != "SUCCESS")
+        .filter(|state| state.as_str() == "FAILED")
+        .count() as i64
+}
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+}
+
+/// Flag a view that is inactive, failing to refresh or older than the objects it reads
+pub fn assess_staleness(
+    mv: &MvDefinition,
+    graph: &MvDependencyGraph,
+    consecutive_failures: i64,
+) -> MvStaleness {
+    let id = mv.id();
+    let refreshed_at = mv.last_refresh_start_time.as_deref().and_then(parse_time);
+
+    // A base table changed after the last refresh read it (or the view never refreshed)
+    let outdated_base_tables: Vec<String> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.source == id)
+        .filter_map(|edge| graph.nodes.iter().find(|n| n.id == edge.target))
+        .filter(|node| {
+            let updated = node.last_updated.as_deref().and_then(parse_time);
+            match (updated, refreshed_at) {
+                (Some(updated), Some(refreshed)) => updated > refreshed,
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+        })
+        .map(|node| node.id.clone())
+        .collect();
+
+    let mut issues = Vec::new();
+    if !mv.is_active {
+        issues.push(MvStalenessIssue::Inactive);
+    }
+    if consecutive_failures >= REFRESH_FAILURE_THRESHOLD {
+        issues.push(MvStalenessIssue::RefreshFailing);
+    }
+    if !outdated_base_tables.is_empty() {
+        issues.push(MvStalenessIssue::OutdatedBaseTable);
+    }
+
+    MvStaleness {
+        id,
+        database_name: mv.database_name.clone(),
+        name: mv.name.clone(),
+        refresh_type: mv.refresh_type.clone(),
+        is_active: mv.is_active,
+        inactive_reason: mv.inactive_reason.clone(),
+        last_refresh_state: mv.last_refresh_state.clone(),
+        last_refresh_start_time: mv.last_refresh_start_time.clone(),
+        last_refresh_finished_time: mv.last_refresh_finished_time.clone(),
+        last_refresh_error: mv.last_refresh_error.clone(),
+        consecutive_failures,
+        outdated_base_tables,
+        issues,
+    }
+}
+
+/// Views depending on a table directly or through other views, nearest first
+pub fn impact(graph: &MvDependencyGraph, table_id: &str) -> MvImpact {
+    let mut views = Vec::new();
+    let mut visited = HashSet::from([table_id.to_string()]);
+    let mut queue = VecDeque::from([(table_id.to_string(), 0, None::<String>)]);
+
+    while let Some((target, depth, via)) = queue.pop_front() {
+        for edge in graph.edges.iter().filter(|e| e.target == target) {
+            if !visited.insert(edge.source.clone()) {
+                continue;
+            }
+            let Some(node) = graph.nodes.iter().find(|n| n.id == edge.source) else {
+                continue;
+            };
+            views.push(MvImpactedView {
+                id: node.id.clone(),
+                database_name: node.database_name.clone(),
+                name: node.name.clone(),
+                depth: depth + 1,
+                via: via.clone(),
+            });
+            queue.push_back((node.id.clone(), depth + 1, Some(node.id.clone())));
+        }
+    }
+
+    MvImpact { table: table_id.to_string(), views }
 }
@@ -2,18 +2,40 @@
 // Purpose: Periodically collect metrics from StarRocks clusters and store them in SQLite
 // Design Ref: ARCHITECTURE_ANALYSIS_AND_INTEGRATION.md
 
+use crate::config::MetricsCollectorConfig;
 use crate::models::Cluster;
+use crate::models::starrocks::Backend;
 use crate::services::mysql_pool_manager::MySQLPoolManager;
-use crate::services::{ClusterService, StarRocksClient};
-use crate::utils::{ApiResult, ScheduledTask};
-use chrono::Utc;
+use crate::services::node_metrics_service::{
+    NodeDiskPath, NodeType, build_node_snapshots, parse_disk_paths,
+};
+use crate::services::{
+    AlertService, AuditColumn, AuditSourceService, ClusterService, MvRefreshService,
+    NodeMetricsService, RoutineLoadService, RoutineLoadSummary, StarRocksClient,
+    VariableHistoryService,
+};
+use crate::utils::{ApiError, ApiResult, ProcessMetrics, ScheduledTask};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
 use utoipa::ToSchema;
 
+/// How often the scheduler looks for clusters due for collection
+const SCHEDULER_TICK: Duration = Duration::from_secs(5);
+
+/// Bounds of a per-cluster collection interval (seconds)
+const CLUSTER_INTERVAL_RANGE_SECS: (u64, u64) = (5, 86_400);
+
+/// Collection of one cluster is abandoned after this many connection timeouts
+const COLLECTION_TIMEOUT_FACTOR: u32 = 4;
+
 /// Aggregated metrics from database queries
 #[derive(Debug, sqlx::FromRow)]
 struct MetricsAggregation {
@@ -34,7 +56,7 @@ struct MetricsAggregation {
 }
 
 /// Metrics snapshot stored in database
-#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, ToSchema)]
 pub struct MetricsSnapshot {
     pub cluster_id: i64,
     pub collected_at: chrono::DateTime<Utc>,
@@ -80,6 +102,12 @@ pub struct MetricsSnapshot {
     pub load_running: i32,
     pub load_finished_total: i64,
 
+    // Routine Load
+    #[serde(default)]
+    pub routine_load_paused: i32,
+    #[serde(default)]
+    pub routine_load_max_lag: i64,
+
     // JVM metrics
     pub jvm_heap_total: i64,
     pub jvm_heap_used: i64,
@@ -99,12 +127,103 @@ pub struct MetricsSnapshot {
     pub io_write_rate: f64,
 }
 
+/// Collection state of a cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionState {
+    /// Collection is disabled for the cluster
+    Disabled,
+    /// Not collected yet
+    Pending,
+    /// A collection is running
+    Collecting,
+    /// The last collection succeeded
+    Healthy,
+    /// The last collections failed; retries are delayed
+    BackingOff,
+}
+
+/// Collection settings and status of a cluster
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClusterCollectionStatus {
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    pub enabled: bool,
+    /// Effective collection interval in seconds
+    pub interval_secs: u64,
+    /// True when the cluster uses the global metrics.interval_secs
+    pub uses_default_interval: bool,
+    pub state: CollectionState,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub consecutive_failures: i64,
+    pub last_duration_ms: Option<i64>,
+    pub next_collect_at: Option<DateTime<Utc>>,
+}
+
+/// Collection settings of a cluster
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateCollectionSettingsRequest {
+    pub enabled: bool,
+    /// Collection interval in seconds; omit to use the global metrics.interval_secs
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, sqlx::FromRow)]
+struct CollectionRow {
+    enabled: bool,
+    interval_secs: Option<i64>,
+    last_attempt_at: Option<DateTime<Utc>>,
+    last_success_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    last_error_at: Option<DateTime<Utc>>,
+    consecutive_failures: i64,
+    last_duration_ms: Option<i64>,
+    next_collect_at: Option<DateTime<Utc>>,
+}
+
+/// Delay before the next collection of a cluster after `consecutive_failures` failures:
+/// the interval doubles with each failure, up to `max_backoff_secs`
+pub fn backoff_secs(interval_secs: u64, consecutive_failures: i64, max_backoff_secs: u64) -> u64 {
+    let doublings = consecutive_failures.saturating_sub(1).clamp(0, 20) as u32;
+    interval_secs
+        .saturating_mul(1 << doublings)
+        .min(max_backoff_secs.max(interval_secs))
+}
+
+/// Removes a cluster from the in-flight set when its collection task ends
+struct InFlightGuard {
+    in_flight: Arc<Mutex<HashSet<i64>>>,
+    cluster_id: i64,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.cluster_id);
+    }
+}
+
 #[derive(Clone)]
 pub struct MetricsCollectorService {
     db: SqlitePool,
     cluster_service: Arc<ClusterService>,
     mysql_pool_manager: Arc<MySQLPoolManager>,
-    retention_days: i64,
+    alert_service: Arc<AlertService>,
+    process_metrics: Arc<ProcessMetrics>,
+    node_metrics_service: Arc<NodeMetricsService>,
+    audit_source_service: Option<Arc<AuditSourceService>>,
+    routine_load_service: Option<Arc<RoutineLoadService>>,
+    mv_refresh_service: Option<Arc<MvRefreshService>>,
+    variable_history_service: Option<Arc<VariableHistoryService>>,
+    config: MetricsCollectorConfig,
+    /// Bounds the number of clusters collected at the same time
+    permits: Arc<Semaphore>,
+    /// Clusters with a running collection (skipped by later ticks until it ends)
+    in_flight: Arc<Mutex<HashSet<i64>>>,
+    /// Last retention cleanup / daily aggregation check
+    last_housekeeping: Arc<Mutex<Option<Instant>>>,
 }
 
 impl MetricsCollectorService {
@@ -113,23 +232,97 @@ impl MetricsCollectorService {
         db: SqlitePool,
         cluster_service: Arc<ClusterService>,
         mysql_pool_manager: Arc<MySQLPoolManager>,
-        retention_days: i64,
+        alert_service: Arc<AlertService>,
+        process_metrics: Arc<ProcessMetrics>,
+        node_metrics_service: Arc<NodeMetricsService>,
+        config: MetricsCollectorConfig,
     ) -> Self {
-        Self { db, cluster_service, mysql_pool_manager, retention_days }
+        Self {
+            db,
+            cluster_service,
+            mysql_pool_manager,
+            alert_service,
+            process_metrics,
+            node_metrics_service,
+            audit_source_service: None,
+            routine_load_service: None,
+            mv_refresh_service: None,
+            variable_history_service: None,
+            permits: Arc::new(Semaphore::new(config.max_concurrency.max(1))),
+            config,
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+            last_housekeeping: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Set audit source service (optional dependency, latency percentiles from audit logs)
+    pub fn with_audit_source(mut self, service: Arc<AuditSourceService>) -> Self {
+        self.audit_source_service = Some(service);
+        self
+    }
+
+    /// Set Routine Load service (optional dependency, records Routine Load lag each collection)
+    pub fn with_routine_load(mut self, service: Arc<RoutineLoadService>) -> Self {
+        self.routine_load_service = Some(service);
+        self
+    }
+
+    /// Set materialized view refresh service (optional dependency, copies refresh task runs
+    /// each collection)
+    pub fn with_mv_refresh(mut self, service: Arc<MvRefreshService>) -> Self {
+        self.mv_refresh_service = Some(service);
+        self
+    }
+
+    /// Set variable history service (optional dependency, snapshots the GLOBAL variables
+    /// each collection when they changed)
+    pub fn with_variable_history(mut self, service: Arc<VariableHistoryService>) -> Self {
+        self.variable_history_service = Some(service);
+        self
+    }
+
+    /// Interval of the ScheduledExecutor: clusters are checked for due collections on
+    /// every tick, so per-cluster intervals shorter than metrics.interval_secs are honored
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs).min(SCHEDULER_TICK)
     }
 
     /// Execute one collection cycle
     /// This is called periodically by the ScheduledExecutor
     pub async fn collect_once(&self) -> Result<(), anyhow::Error> {
-        // Collect metrics from all clusters
-        self.collect_all_clusters().await?;
+        // Collect metrics from the clusters that are due
+        let started = Instant::now();
+        let collected = self.collect_all_clusters().await;
+        self.process_metrics
+            .observe_collector_tick(started.elapsed());
+        collected?;
+
+        // Retention cleanup and daily aggregation run once per metrics interval
+        if self.housekeeping_due() {
+            if let Err(e) = self.cleanup_old_metrics().await {
+                tracing::error!("Failed to cleanup old metrics: {}", e);
+            }
+            if let Err(e) = self.alert_service.cleanup_history().await {
+                tracing::error!("Failed to cleanup alert history: {}", e);
+            }
 
-        // Check if we need to run daily aggregation
-        self.check_and_run_daily_aggregation().await?;
+            // Check if we need to run daily aggregation
+            self.check_and_run_daily_aggregation().await?;
+        }
 
         Ok(())
     }
 
+    fn housekeeping_due(&self) -> bool {
+        let mut last = self.last_housekeeping.lock().unwrap();
+        let interval = Duration::from_secs(self.config.interval_secs);
+        if last.is_some_and(|at| at.elapsed() < interval) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
     /// Check if daily aggregation is needed and run it
     async fn check_and_run_daily_aggregation(&self) -> Result<(), anyhow::Error> {
         // Check if we've already aggregated today
@@ -151,32 +344,287 @@ impl MetricsCollectorService {
         Ok(())
     }
 
-    /// Collect metrics from all clusters
+    /// Collect metrics from every enabled cluster whose next collection is due.
+    /// Clusters are collected concurrently (at most metrics.max_concurrency at a time). The
+    /// tick waits for its collections up to the tick interval; a cluster still running after
+    /// that keeps running in the background and is skipped until it finishes, so a slow
+    /// cluster never delays the others.
     async fn collect_all_clusters(&self) -> Result<(), anyhow::Error> {
         let clusters = self.cluster_service.list_clusters().await?;
+        let settings = self.load_collection_rows().await?;
+        let now = Utc::now();
+
+        let due: Vec<Cluster> = clusters
+            .into_iter()
+            .filter(|cluster| {
+                settings.get(&cluster.id).is_none_or(|row| {
+                    row.enabled && row.next_collect_at.is_none_or(|next| next <= now)
+                })
+            })
+            .filter(|cluster| !self.in_flight.lock().unwrap().contains(&cluster.id))
+            .collect();
 
-        tracing::debug!("Collecting metrics from {} clusters", clusters.len());
+        tracing::debug!("Collecting metrics from {} due clusters", due.len());
 
-        for cluster in clusters {
-            if let Err(e) = self.collect_cluster_metrics(&cluster).await {
+        let handles: Vec<JoinHandle<()>> = due
+            .into_iter()
+            .map(|cluster| self.spawn_collection(cluster))
+            .collect();
+
+        let deadline = tokio::time::Instant::now() + self.tick_interval();
+        for mut handle in handles {
+            if tokio::time::timeout_at(deadline, &mut handle)
+                .await
+                .is_err()
+            {
+                tracing::debug!(
+                    "Collections still running after the tick, continuing in background"
+                );
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collect one cluster in a background task and record its collection status
+    fn spawn_collection(&self, cluster: Cluster) -> JoinHandle<()> {
+        self.in_flight.lock().unwrap().insert(cluster.id);
+        let guard =
+            InFlightGuard { in_flight: Arc::clone(&self.in_flight), cluster_id: cluster.id };
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let _guard = guard;
+            let Ok(_permit) = service.permits.acquire().await else {
+                return;
+            };
+
+            let attempted_at = Utc::now();
+            let started = Instant::now();
+            let timeout = Duration::from_secs(cluster.connection_timeout.max(1) as u64)
+                * COLLECTION_TIMEOUT_FACTOR;
+            let result = match tokio::time::timeout(
+                timeout,
+                service.collect_cluster_metrics(&cluster),
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(_) => Err(ApiError::cluster_connection_failed(format!(
+                    "Collection timed out after {}s",
+                    timeout.as_secs()
+                ))),
+            };
+            let error = result.err().map(|e| {
                 tracing::error!(
                     "Failed to collect metrics for cluster {} ({}): {}",
                     cluster.id,
                     cluster.name,
                     e
                 );
-                // Continue with other clusters
+                service
+                    .process_metrics
+                    .record_collector_failure(cluster.id, &cluster.name);
+                e.to_string()
+            });
+
+            if let Err(e) = service
+                .record_collection(cluster.id, attempted_at, started.elapsed(), error)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to record collection status of cluster {}: {}",
+                    cluster.id,
+                    e
+                );
             }
-        }
+        })
+    }
 
-        // Cleanup old metrics every collection cycle
-        if let Err(e) = self.cleanup_old_metrics().await {
-            tracing::error!("Failed to cleanup old metrics: {}", e);
+    async fn load_collection_rows(&self) -> ApiResult<HashMap<i64, CollectionRow>> {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            cluster_id: i64,
+            #[sqlx(flatten)]
+            row: CollectionRow,
         }
 
+        let rows: Vec<Row> = sqlx::query_as("SELECT * FROM cluster_metrics_collection")
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.cluster_id, r.row)).collect())
+    }
+
+    async fn load_collection_row(&self, cluster_id: i64) -> ApiResult<Option<CollectionRow>> {
+        Ok(sqlx::query_as("SELECT * FROM cluster_metrics_collection WHERE cluster_id = ?")
+            .bind(cluster_id)
+            .fetch_optional(&self.db)
+            .await?)
+    }
+
+    fn interval_secs(&self, row: Option<&CollectionRow>) -> u64 {
+        row.and_then(|r| r.interval_secs)
+            .map(|secs| secs as u64)
+            .unwrap_or(self.config.interval_secs)
+    }
+
+    /// Record the outcome of a collection and schedule the next one (with backoff on failure)
+    async fn record_collection(
+        &self,
+        cluster_id: i64,
+        attempted_at: DateTime<Utc>,
+        duration: Duration,
+        error: Option<String>,
+    ) -> ApiResult<()> {
+        let row = self.load_collection_row(cluster_id).await?;
+        let interval_secs = self.interval_secs(row.as_ref());
+        let failures = match &error {
+            Some(_) => row.as_ref().map_or(0, |r| r.consecutive_failures) + 1,
+            None => 0,
+        };
+        let delay_secs = match &error {
+            Some(_) => backoff_secs(interval_secs, failures, self.config.max_backoff_secs),
+            None => interval_secs,
+        };
+        let next_collect_at = attempted_at + chrono::Duration::seconds(delay_secs as i64);
+        let finished_at = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_metrics_collection (
+                cluster_id, last_attempt_at, last_success_at, last_error, last_error_at,
+                consecutive_failures, last_duration_ms, next_collect_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(cluster_id) DO UPDATE SET
+                last_attempt_at = excluded.last_attempt_at,
+                last_success_at = COALESCE(excluded.last_success_at, last_success_at),
+                last_error = COALESCE(excluded.last_error, last_error),
+                last_error_at = COALESCE(excluded.last_error_at, last_error_at),
+                consecutive_failures = excluded.consecutive_failures,
+                last_duration_ms = excluded.last_duration_ms,
+                next_collect_at = excluded.next_collect_at,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(cluster_id)
+        .bind(attempted_at)
+        .bind(error.is_none().then_some(finished_at))
+        .bind(&error)
+        .bind(error.is_some().then_some(finished_at))
+        .bind(failures)
+        .bind(duration.as_millis() as i64)
+        .bind(next_collect_at)
+        .execute(&self.db)
+        .await?;
+
         Ok(())
     }
 
+    fn collection_status(
+        &self,
+        cluster: &Cluster,
+        row: Option<CollectionRow>,
+    ) -> ClusterCollectionStatus {
+        let interval_secs = self.interval_secs(row.as_ref());
+        let row = row.unwrap_or(CollectionRow { enabled: true, ..Default::default() });
+        let state = if !row.enabled {
+            CollectionState::Disabled
+        } else if self.in_flight.lock().unwrap().contains(&cluster.id) {
+            CollectionState::Collecting
+        } else if row.last_attempt_at.is_none() {
+            CollectionState::Pending
+        } else if row.consecutive_failures > 0 {
+            CollectionState::BackingOff
+        } else {
+            CollectionState::Healthy
+        };
+
+        ClusterCollectionStatus {
+            cluster_id: cluster.id,
+            cluster_name: cluster.name.clone(),
+            enabled: row.enabled,
+            interval_secs,
+            uses_default_interval: row.interval_secs.is_none(),
+            state,
+            last_attempt_at: row.last_attempt_at,
+            last_success_at: row.last_success_at,
+            last_error: row.last_error,
+            last_error_at: row.last_error_at,
+            consecutive_failures: row.consecutive_failures,
+            last_duration_ms: row.last_duration_ms,
+            next_collect_at: if row.enabled { row.next_collect_at } else { None },
+        }
+    }
+
+    /// Collection status of the given clusters
+    pub async fn list_collection_status(
+        &self,
+        clusters: &[Cluster],
+    ) -> ApiResult<Vec<ClusterCollectionStatus>> {
+        let mut rows = self.load_collection_rows().await?;
+        Ok(clusters
+            .iter()
+            .map(|cluster| self.collection_status(cluster, rows.remove(&cluster.id)))
+            .collect())
+    }
+
+    /// Collection status of a cluster
+    pub async fn get_collection_status(
+        &self,
+        cluster: &Cluster,
+    ) -> ApiResult<ClusterCollectionStatus> {
+        let row = self.load_collection_row(cluster.id).await?;
+        Ok(self.collection_status(cluster, row))
+    }
+
+    /// Change the collection settings of a cluster; the cluster is collected on the next
+    /// tick with the new settings
+    pub async fn update_collection_settings(
+        &self,
+        cluster: &Cluster,
+        req: UpdateCollectionSettingsRequest,
+    ) -> ApiResult<ClusterCollectionStatus> {
+        let (min, max) = CLUSTER_INTERVAL_RANGE_SECS;
+        if let Some(secs) = req.interval_secs
+            && !(min..=max).contains(&secs)
+        {
+            return Err(ApiError::validation_error(format!(
+                "interval_secs must be between {} and {}",
+                min, max
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_metrics_collection (cluster_id, enabled, interval_secs, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(cluster_id) DO UPDATE SET
+                enabled = excluded.enabled,
+                interval_secs = excluded.interval_secs,
+                next_collect_at = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(cluster.id)
+        .bind(req.enabled)
+        .bind(req.interval_secs.map(|secs| secs as i64))
+        .execute(&self.db)
+        .await?;
+
+        tracing::info!(
+            "Metrics collection of cluster {} ({}) {} (interval: {})",
+            cluster.id,
+            cluster.name,
+            if req.enabled { "enabled" } else { "disabled" },
+            req.interval_secs
+                .map(|secs| format!("{}s", secs))
+                .unwrap_or_else(|| "default".to_string())
+        );
+
+        self.get_collection_status(cluster).await
+    }
+
     /// Collect metrics from a single cluster
     async fn collect_cluster_metrics(&self, cluster: &Cluster) -> ApiResult<()> {
         tracing::debug!("Collecting metrics for cluster: {} ({})", cluster.id, cluster.name);
@@ -338,10 +786,49 @@ impl MetricsCollectorService {
             .await
             .unwrap_or((0.0, 0.0, 0.0));
 
+        // Routine Load lag: recorded per job, summarized into the snapshot for alerting
+        let collected_at = Utc::now();
+        let routine_load = match &self.routine_load_service {
+            Some(service) => service
+                .record_lag(cluster, collected_at)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(
+                        "Failed to record Routine Load lag for cluster {}: {}",
+                        cluster.name,
+                        e
+                    );
+                    RoutineLoadSummary::default()
+                }),
+            None => RoutineLoadSummary::default(),
+        };
+
+        // Materialized view refresh runs: kept locally beyond the FE task run retention
+        if let Some(service) = &self.mv_refresh_service
+            && let Err(e) = service.record_runs(cluster).await
+        {
+            tracing::warn!(
+                "Failed to record materialized view refreshes for cluster {}: {}",
+                cluster.name,
+                e
+            );
+        }
+
+        // GLOBAL variables: snapshot when changed, at least once per snapshot interval
+        if let Some(service) = &self.variable_history_service
+            && let Err(e) = service.take_snapshot(cluster, false).await
+        {
+            tracing::warn!(
+                "Failed to snapshot global variables for cluster {}: {}",
+                cluster.name,
+                e
+            );
+        }
+
         // Create snapshot
         let snapshot = MetricsSnapshot {
             cluster_id: cluster.id,
-            collected_at: Utc::now(),
+            collected_at,
 
             // Query metrics: Use real percentiles from audit logs, fallback to Prometheus
             qps: metrics_map.get("starrocks_fe_qps").copied().unwrap_or(0.0),
@@ -427,6 +914,10 @@ impl MetricsCollectorService {
                 .copied()
                 .unwrap_or(0.0) as i64,
 
+            // Routine Load
+            routine_load_paused: routine_load.paused,
+            routine_load_max_lag: routine_load.max_lag,
+
             // JVM metrics
             jvm_heap_total: runtime_info.total_mem,
             jvm_heap_used,
@@ -449,6 +940,35 @@ impl MetricsCollectorService {
         // Save to database
         self.save_snapshot(&snapshot).await?;
 
+        // Per-node history: a failure here must not lose the cluster snapshot
+        let backend_type = if cluster.is_shared_data() { NodeType::Cn } else { NodeType::Be };
+        let mut disk_paths = if backend_type == NodeType::Be {
+            self.collect_disk_paths(&client, &backends).await
+        } else {
+            HashMap::new()
+        };
+        let node_snapshots = build_node_snapshots(
+            cluster.id,
+            snapshot.collected_at,
+            backend_type,
+            &backends,
+            &frontends,
+            &metrics_text,
+            &mut disk_paths,
+        );
+        if let Err(e) = self
+            .node_metrics_service
+            .save_snapshots(&node_snapshots)
+            .await
+        {
+            tracing::warn!("Failed to save node metrics for cluster {}: {}", cluster.id, e);
+        }
+
+        // Evaluate alert rules against the fresh snapshot
+        if let Err(e) = self.alert_service.evaluate(cluster, &snapshot).await {
+            tracing::warn!("Failed to evaluate alert rules for cluster {}: {}", cluster.id, e);
+        }
+
         tracing::debug!(
             "Metrics collected for cluster {} ({}): QPS={:.2}, CPU={:.1}%, Disk={:.1}%",
             cluster.id,
@@ -461,6 +981,29 @@ impl MetricsCollectorService {
         Ok(())
     }
 
+    /// Storage paths of each alive BE by backend id; BEs that fail to answer are skipped
+    async fn collect_disk_paths(
+        &self,
+        client: &StarRocksClient,
+        backends: &[Backend],
+    ) -> HashMap<String, Vec<NodeDiskPath>> {
+        let mut disk_paths = HashMap::new();
+        for backend in backends.iter().filter(|b| b.alive == "true") {
+            match client.get_backend_disks(&backend.backend_id).await {
+                Ok(rows) => {
+                    disk_paths.insert(backend.backend_id.clone(), parse_disk_paths(&rows));
+                },
+                Err(e) => tracing::debug!(
+                    "Failed to get storage paths of BE {} ({}): {}",
+                    backend.backend_id,
+                    backend.host,
+                    e
+                ),
+            }
+        }
+        disk_paths
+    }
+
     /// Save metrics snapshot to database
     async fn save_snapshot(&self, snapshot: &MetricsSnapshot) -> ApiResult<()> {
         sqlx::query(
@@ -475,6 +1018,7 @@ impl MetricsCollectorService {
                 tablet_count, max_compaction_score,
                 txn_running, txn_success_total, txn_failed_total,
                 load_running, load_finished_total,
+                routine_load_paused, routine_load_max_lag,
                 jvm_heap_total, jvm_heap_used, jvm_heap_usage_pct, jvm_thread_count,
                 network_bytes_sent_total, network_bytes_received_total, network_send_rate, network_receive_rate,
                 io_read_bytes_total, io_write_bytes_total, io_read_rate, io_write_rate,
@@ -489,6 +1033,7 @@ impl MetricsCollectorService {
                 ?, ?,
                 ?, ?, ?,
                 ?, ?,
+                ?, ?,
                 ?, ?, ?, ?,
                 ?, ?, ?, ?,
                 ?, ?, ?, ?,
@@ -525,6 +1070,8 @@ impl MetricsCollectorService {
         .bind(snapshot.txn_failed_total)
         .bind(snapshot.load_running)
         .bind(snapshot.load_finished_total)
+        .bind(snapshot.routine_load_paused)
+        .bind(snapshot.routine_load_max_lag)
         .bind(snapshot.jvm_heap_total)
         .bind(snapshot.jvm_heap_used)
         .bind(snapshot.jvm_heap_usage_pct)
@@ -546,7 +1093,7 @@ impl MetricsCollectorService {
 
     /// Cleanup old metrics data based on retention policy
     async fn cleanup_old_metrics(&self) -> Result<(), sqlx::Error> {
-        let cutoff_date = Utc::now() - chrono::Duration::days(self.retention_days);
+        let cutoff_date = Utc::now() - chrono::Duration::days(self.config.retention_days);
 
         let result = sqlx::query("DELETE FROM metrics_snapshots WHERE collected_at < ?")
             .bind(cutoff_date)
@@ -557,10 +1104,55 @@ impl MetricsCollectorService {
             tracing::info!(
                 "Cleaned up {} old metric snapshots (older than {} days)",
                 result.rows_affected(),
-                self.retention_days
+                self.config.retention_days
+            );
+        }
+
+        let node_rows = self
+            .node_metrics_service
+            .cleanup_before(cutoff_date)
+            .await?;
+        if node_rows > 0 {
+            tracing::info!(
+                "Cleaned up {} old node metric snapshots (older than {} days)",
+                node_rows,
+                self.config.retention_days
             );
         }
 
+        if let Some(routine_load_service) = &self.routine_load_service {
+            let lag_rows = routine_load_service.cleanup_before(cutoff_date).await?;
+            if lag_rows > 0 {
+                tracing::info!(
+                    "Cleaned up {} old Routine Load lag records (older than {} days)",
+                    lag_rows,
+                    self.config.retention_days
+                );
+            }
+        }
+
+        if let Some(mv_refresh_service) = &self.mv_refresh_service {
+            let refresh_rows = mv_refresh_service.cleanup_before(cutoff_date).await?;
+            if refresh_rows > 0 {
+                tracing::info!(
+                    "Cleaned up {} old materialized view refresh records (older than {} days)",
+                    refresh_rows,
+                    self.config.retention_days
+                );
+            }
+        }
+
+        if let Some(variable_history_service) = &self.variable_history_service {
+            let snapshot_rows = variable_history_service.cleanup_before(cutoff_date).await?;
+            if snapshot_rows > 0 {
+                tracing::info!(
+                    "Cleaned up {} old variable snapshots (older than {} days)",
+                    snapshot_rows,
+                    self.config.retention_days
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -597,6 +1189,8 @@ impl MetricsCollectorService {
             txn_failed_total: i64,
             load_running: i64,
             load_finished_total: i64,
+            routine_load_paused: i64,
+            routine_load_max_lag: i64,
             jvm_heap_total: i64,
             jvm_heap_used: i64,
             jvm_heap_usage_pct: f64,
@@ -654,6 +1248,8 @@ impl MetricsCollectorService {
                 txn_failed_total: r.txn_failed_total,
                 load_running: r.load_running as i32,
                 load_finished_total: r.load_finished_total,
+                routine_load_paused: r.routine_load_paused as i32,
+                routine_load_max_lag: r.routine_load_max_lag,
                 jvm_heap_total: r.jvm_heap_total,
                 jvm_heap_used: r.jvm_heap_used,
                 jvm_heap_usage_pct: r.jvm_heap_usage_pct,
@@ -674,7 +1270,7 @@ impl MetricsCollectorService {
 }
 
 // Helper function to parse storage size strings like "1.5 TB", "500 GB", etc.
-fn parse_storage_size(size_str: &str) -> Option<i64> {
+pub(crate) fn parse_storage_size(size_str: &str) -> Option<i64> {
     let parts: Vec<&str> = size_str.split_whitespace().collect();
     if parts.len() != 2 {
         return None;
@@ -850,66 +1446,6 @@ impl MetricsCollectorService {
         Ok(())
     }
 
-    /// Detect the available query time column name in audit log table
-    /// Different StarRocks versions may use different column names
-    async fn detect_query_time_column(
-        &self,
-        mysql_client: &crate::services::mysql_client::MySQLClient,
-    ) -> Option<String> {
-        // First, try to get column list using SHOW COLUMNS
-        let show_columns_query = "SHOW COLUMNS FROM starrocks_audit_db__.starrocks_audit_tbl__";
-
-        match mysql_client.query_raw(show_columns_query).await {
-            Ok((columns, rows)) => {
-                // Find the column index for "Field" column
-                let field_idx = columns
-                    .iter()
-                    .position(|c| c.eq_ignore_ascii_case("Field"))?;
-
-                // Possible column names to look for (in order of likelihood)
-                let possible_columns =
-                    vec!["queryTime", "query_time", "duration", "QueryTime", "queryDuration"];
-
-                // Check each row for matching column name
-                for row in rows {
-                    if let Some(col_name) = row.get(field_idx) {
-                        let col_name_lower = col_name.to_lowercase();
-                        for possible_col in &possible_columns {
-                            if col_name_lower == possible_col.to_lowercase() {
-                                tracing::debug!("Detected query time column: {}", col_name);
-                                return Some(col_name.clone());
-                            }
-                        }
-                    }
-                }
-            },
-            Err(e) => {
-                tracing::debug!("Failed to get column list, trying fallback method: {}", e);
-                // Fallback: try direct queries with common column names
-                let possible_columns =
-                    vec!["queryTime", "query_time", "duration", "QueryTime", "queryDuration"];
-
-                for col_name in possible_columns {
-                    // Use a safe query that won't fail if column doesn't exist
-                    let test_query = format!(
-                        "SELECT COUNT(*) as cnt FROM starrocks_audit_db__.starrocks_audit_tbl__ WHERE {} > 0 LIMIT 1",
-                        col_name
-                    );
-
-                    if mysql_client.query(&test_query).await.is_ok() {
-                        tracing::debug!("Detected query time column (fallback): {}", col_name);
-                        return Some(col_name.to_string());
-                    }
-                }
-            },
-        }
-
-        tracing::warn!(
-            "Could not detect query time column in audit log table. Available columns may differ by StarRocks version."
-        );
-        None
-    }
-
     /// Get real latency percentiles from audit logs using StarRocks percentile functions
     /// Reference: https://docs.starrocks.io/zh/docs/category/percentile/
     async fn get_real_latency_percentiles(&self, cluster: &Cluster) -> ApiResult<(f64, f64, f64)> {
@@ -919,32 +1455,38 @@ impl MetricsCollectorService {
         let pool = self.mysql_pool_manager.get_pool(cluster).await?;
         let mysql_client = MySQLClient::from_pool(pool);
 
-        // Detect the available query time column name
-        let query_time_col = match self.detect_query_time_column(&mysql_client).await {
-            Some(col) => col,
-            None => {
-                tracing::debug!(
-                    "Query time column not found, skipping audit log percentile calculation"
-                );
-                return Ok((0.0, 0.0, 0.0)); // Return zeros to fallback to Prometheus
-            },
+        // Audit table of the cluster with its query time column
+        let Some(audit_source_service) = &self.audit_source_service else {
+            return Ok((0.0, 0.0, 0.0)); // Return zeros to fallback to Prometheus
         };
+        let audit = audit_source_service.resolve(cluster).await?;
+        if !audit.has(AuditColumn::QueryTime) {
+            tracing::debug!(
+                "Query time column not found, skipping audit log percentile calculation"
+            );
+            return Ok((0.0, 0.0, 0.0)); // Return zeros to fallback to Prometheus
+        }
+        let query_time_col = audit.col(AuditColumn::QueryTime);
 
         // Use StarRocks percentile_approx function to calculate P50, P95, P99 from audit logs
         // Query recent 3 days of data for comprehensive percentiles
         let query = format!(
             r#"
             SELECT 
-                COALESCE(percentile_approx({}, 0.50), 0) as p50,
-                COALESCE(percentile_approx({}, 0.95), 0) as p95,
-                COALESCE(percentile_approx({}, 0.99), 0) as p99
-            FROM starrocks_audit_db__.starrocks_audit_tbl__
-            WHERE timestamp >= DATE_SUB(NOW(), INTERVAL 3 DAY)
-                AND {} > 0
-                AND state = 'EOF'
-                AND isQuery = 1
+                COALESCE(percentile_approx({qt}, 0.50), 0) as p50,
+                COALESCE(percentile_approx({qt}, 0.95), 0) as p95,
+                COALESCE(percentile_approx({qt}, 0.99), 0) as p99
+            FROM {table}
+            WHERE {ts} >= DATE_SUB(NOW(), INTERVAL 3 DAY)
+                AND {qt} > 0
+                AND {state} = 'EOF'
+                AND {is_query} = 1
         "#,
-            query_time_col, query_time_col, query_time_col, query_time_col
+            qt = query_time_col,
+            table = audit.full_table_name(),
+            ts = audit.col(AuditColumn::Timestamp),
+            state = audit.col(AuditColumn::State),
+            is_query = audit.col(AuditColumn::IsQuery),
         );
 
         match mysql_client.query(&query).await {
@@ -0,0 +1,178 @@
+// Metrics Exporter Service
+// Purpose: Expose the latest MetricsSnapshot of every cluster and the process metrics of
+//          starrocks-admin in Prometheus text format (GET /metrics)
+
+use crate::services::{ClusterService, MetricsCollectorService, MetricsSnapshot, MySQLPoolManager};
+use crate::utils::process_metrics::{escape_label, write_family};
+use crate::utils::{ApiResult, ProcessMetrics};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Prometheus metric type
+#[derive(Debug, Clone, Copy)]
+enum MetricKind {
+    Gauge,
+    Counter,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Gauge => "gauge",
+            MetricKind::Counter => "counter",
+        }
+    }
+}
+
+type SnapshotValue = fn(&MetricsSnapshot) -> f64;
+
+/// Snapshot fields exported per cluster as starrocks_admin_cluster_<name>
+const SNAPSHOT_METRICS: &[(&str, MetricKind, &str, SnapshotValue)] = &[
+    ("qps", MetricKind::Gauge, "Queries per second", |s| s.qps),
+    ("rps", MetricKind::Gauge, "Requests per second", |s| s.rps),
+    ("query_latency_p50_ms", MetricKind::Gauge, "Query latency p50 in ms", |s| s.query_latency_p50),
+    ("query_latency_p95_ms", MetricKind::Gauge, "Query latency p95 in ms", |s| s.query_latency_p95),
+    ("query_latency_p99_ms", MetricKind::Gauge, "Query latency p99 in ms", |s| s.query_latency_p99),
+    ("queries_total", MetricKind::Counter, "Queries executed", |s| s.query_total as f64),
+    ("query_success_total", MetricKind::Counter, "Successful queries", |s| s.query_success as f64),
+    ("query_error_total", MetricKind::Counter, "Failed queries", |s| s.query_error as f64),
+    ("query_timeout_total", MetricKind::Counter, "Timed out queries", |s| s.query_timeout as f64),
+    ("backends", MetricKind::Gauge, "Backend (or compute) nodes", |s| s.backend_total as f64),
+    ("backends_alive", MetricKind::Gauge, "Alive backend (or compute) nodes", |s| {
+        s.backend_alive as f64
+    }),
+    ("frontends", MetricKind::Gauge, "Frontend nodes", |s| s.frontend_total as f64),
+    ("frontends_alive", MetricKind::Gauge, "Alive frontend nodes", |s| s.frontend_alive as f64),
+    ("cpu_usage_total", MetricKind::Gauge, "Summed CPU usage of backends in %", |s| {
+        s.total_cpu_usage
+    }),
+    ("cpu_usage_avg", MetricKind::Gauge, "Average CPU usage of backends in %", |s| s.avg_cpu_usage),
+    ("memory_usage_total", MetricKind::Gauge, "Summed memory usage of backends", |s| {
+        s.total_memory_usage
+    }),
+    ("memory_usage_avg", MetricKind::Gauge, "Average memory usage of backends", |s| {
+        s.avg_memory_usage
+    }),
+    ("disk_total_bytes", MetricKind::Gauge, "Disk capacity in bytes", |s| {
+        s.disk_total_bytes as f64
+    }),
+    ("disk_used_bytes", MetricKind::Gauge, "Disk usage in bytes", |s| s.disk_used_bytes as f64),
+    ("disk_usage_pct", MetricKind::Gauge, "Disk usage in %", |s| s.disk_usage_pct),
+    ("tablets", MetricKind::Gauge, "Tablet count", |s| s.tablet_count as f64),
+    ("max_compaction_score", MetricKind::Gauge, "Highest compaction score", |s| {
+        s.max_compaction_score
+    }),
+    ("txn_running", MetricKind::Gauge, "Running transactions", |s| s.txn_running as f64),
+    ("txn_success_total", MetricKind::Counter, "Committed transactions", |s| {
+        s.txn_success_total as f64
+    }),
+    ("txn_failed_total", MetricKind::Counter, "Failed transactions", |s| s.txn_failed_total as f64),
+    ("load_running", MetricKind::Gauge, "Running load jobs", |s| s.load_running as f64),
+    ("load_finished_total", MetricKind::Counter, "Finished load jobs", |s| {
+        s.load_finished_total as f64
+    }),
+    ("jvm_heap_total_bytes", MetricKind::Gauge, "FE JVM heap size in bytes", |s| {
+        s.jvm_heap_total as f64
+    }),
+    ("jvm_heap_used_bytes", MetricKind::Gauge, "FE JVM heap usage in bytes", |s| {
+        s.jvm_heap_used as f64
+    }),
+    ("jvm_heap_usage_pct", MetricKind::Gauge, "FE JVM heap usage in %", |s| s.jvm_heap_usage_pct),
+    ("jvm_threads", MetricKind::Gauge, "FE JVM threads", |s| s.jvm_thread_count as f64),
+    ("network_sent_bytes_total", MetricKind::Counter, "Bytes sent by backends", |s| {
+        s.network_bytes_sent_total as f64
+    }),
+    ("network_received_bytes_total", MetricKind::Counter, "Bytes received by backends", |s| {
+        s.network_bytes_received_total as f64
+    }),
+    ("network_send_rate", MetricKind::Gauge, "Backend send rate in bytes/s", |s| {
+        s.network_send_rate
+    }),
+    ("network_receive_rate", MetricKind::Gauge, "Backend receive rate in bytes/s", |s| {
+        s.network_receive_rate
+    }),
+    ("io_read_bytes_total", MetricKind::Counter, "Bytes read from disk", |s| {
+        s.io_read_bytes_total as f64
+    }),
+    ("io_write_bytes_total", MetricKind::Counter, "Bytes written to disk", |s| {
+        s.io_write_bytes_total as f64
+    }),
+    ("io_read_rate", MetricKind::Gauge, "Disk read rate in bytes/s", |s| s.io_read_rate),
+    ("io_write_rate", MetricKind::Gauge, "Disk write rate in bytes/s", |s| s.io_write_rate),
+];
+
+#[derive(Clone)]
+pub struct MetricsExporterService {
+    cluster_service: Arc<ClusterService>,
+    metrics_collector_service: Arc<MetricsCollectorService>,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    process_metrics: Arc<ProcessMetrics>,
+}
+
+impl MetricsExporterService {
+    pub fn new(
+        cluster_service: Arc<ClusterService>,
+        metrics_collector_service: Arc<MetricsCollectorService>,
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+        process_metrics: Arc<ProcessMetrics>,
+    ) -> Self {
+        Self { cluster_service, metrics_collector_service, mysql_pool_manager, process_metrics }
+    }
+
+    /// Render the Prometheus exposition of all clusters and the process
+    pub async fn render(&self) -> ApiResult<String> {
+        let clusters = self.cluster_service.list_clusters().await?;
+
+        // Clusters never collected yet have no snapshot and are only counted
+        let mut snapshots = Vec::new();
+        for cluster in &clusters {
+            if let Some(snapshot) = self
+                .metrics_collector_service
+                .get_latest_snapshot(cluster.id)
+                .await?
+            {
+                let labels = format!(
+                    "cluster_id=\"{}\",cluster_name=\"{}\",organization_id=\"{}\"",
+                    cluster.id,
+                    escape_label(&cluster.name),
+                    cluster
+                        .organization_id
+                        .map(|id| id.to_string())
+                        .unwrap_or_default()
+                );
+                snapshots.push((labels, snapshot));
+            }
+        }
+
+        let mut out = String::new();
+
+        write_family(&mut out, "starrocks_admin_clusters", "gauge", "Registered clusters");
+        let _ = writeln!(out, "starrocks_admin_clusters {}", clusters.len());
+
+        let name = "starrocks_admin_cluster_snapshot_timestamp_seconds";
+        write_family(&mut out, name, "gauge", "Collection time of the latest metrics snapshot");
+        for (labels, snapshot) in &snapshots {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, snapshot.collected_at.timestamp());
+        }
+
+        for (suffix, kind, help, value) in SNAPSHOT_METRICS {
+            let name = format!("starrocks_admin_cluster_{}", suffix);
+            write_family(&mut out, &name, kind.as_str(), help);
+            for (labels, snapshot) in &snapshots {
+                let _ = writeln!(out, "{}{{{}}} {}", name, labels, value(snapshot));
+            }
+        }
+
+        write_family(
+            &mut out,
+            "starrocks_admin_mysql_pools",
+            "gauge",
+            "Open MySQL connection pools (one per cluster in use)",
+        );
+        let _ =
+            writeln!(out, "starrocks_admin_mysql_pools {}", self.mysql_pool_manager.pool_count());
+
+        self.process_metrics.render(&mut out);
+        Ok(out)
+    }
+}
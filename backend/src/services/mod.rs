@@ -1,43 +1,89 @@
+pub mod admin_audit_service;
+pub mod alert_notifier;
+pub mod alert_service;
+pub mod api_token_service;
+pub mod audit_log_service;
+pub mod audit_source_service;
 pub mod auth_service;
 pub mod casbin_service;
 pub mod cluster_service;
 pub mod data_statistics_service;
+pub mod load_job_service;
+pub mod login_guard_service;
 pub mod materialized_view_service;
 pub mod metrics_collector_service;
+pub mod metrics_exporter_service;
+pub mod mv_refresh_service;
 pub mod mysql_client;
 pub mod mysql_pool_manager;
+pub mod node_metrics_service;
 pub mod organization_service;
 pub mod overview_service;
 pub mod permission_service;
 pub mod profile_analyzer;
+pub mod profile_archive_service;
 pub mod role_service;
+pub mod routine_load_service;
+pub mod session_service;
+pub mod sql_policy_service;
+pub mod sso_service;
 pub mod starrocks_client;
 pub mod system_function_service;
+pub mod two_factor_service;
 pub mod user_role_service;
 pub mod user_service;
+pub mod variable_history_service;
 
+pub use admin_audit_service::AdminAuditService;
+pub use alert_service::AlertService;
+pub use api_token_service::{API_TOKEN_PREFIX, ApiTokenService};
+pub use audit_source_service::{
+    AuditColumn, AuditSource, AuditSourceService, AuditSourceSettings, AuditSourceValidation,
+    ClusterAuditSource,
+};
 pub use auth_service::AuthService;
 pub use casbin_service::CasbinService;
 pub use cluster_service::ClusterService;
 pub use data_statistics_service::{
     DataStatistics, DataStatisticsService, TopTableByAccess, TopTableBySize,
 };
+pub use load_job_service::LoadJobService;
+pub use login_guard_service::LoginGuard;
 pub use materialized_view_service::MaterializedViewService;
-pub use metrics_collector_service::{MetricsCollectorService, MetricsSnapshot};
+pub use metrics_collector_service::{
+    ClusterCollectionStatus, CollectionState, MetricsCollectorService, MetricsSnapshot,
+    UpdateCollectionSettingsRequest,
+};
+pub use metrics_exporter_service::MetricsExporterService;
+pub use mv_refresh_service::MvRefreshService;
 pub use mysql_client::MySQLClient;
 pub use mysql_pool_manager::MySQLPoolManager;
+pub use node_metrics_service::{
+    NodeDiskPath, NodeDiskPathTrend, NodeMetricsService, NodeMetricsSnapshot, NodeMetricsSummary,
+    NodeMetricsTrends, NodeType,
+};
 pub use organization_service::OrganizationService;
 pub use overview_service::{
     Alert, AlertLevel, BECompactionScore, CapacityPrediction, ClusterHealth, ClusterOverview,
     CompactionDetailStats, CompactionDurationStats, CompactionStats, CompactionTaskStats,
-    ExtendedClusterOverview, HealthCard, HealthStatus, KeyPerformanceIndicators, LoadJobStats,
-    MaterializedViewStats, NetworkIOStats, OverviewService, PerformanceTrends, ResourceMetrics,
-    ResourceTrends, RunningQuery, SchemaChangeStats, SessionStats, TimeRange, TopPartitionByScore,
-    TransactionStats,
+    ExtendedClusterOverview, FleetClusterStatus, FleetClusterSummary, FleetOverview, HealthCard,
+    HealthStatus, KeyPerformanceIndicators, LoadJobStats, MaterializedViewStats, NetworkIOStats,
+    OverviewService, PerformanceTrends, ResourceMetrics, ResourceTrends, RunningQuery,
+    SchemaChangeStats, SessionStats, TimeRange, TopPartitionByScore, TransactionStats,
 };
 pub use permission_service::PermissionService;
+pub use profile_archive_service::ProfileArchiveService;
 pub use role_service::RoleService;
+pub use routine_load_service::{
+    RoutineLoadLagTrends, RoutineLoadPartitionLag, RoutineLoadService, RoutineLoadStateChange,
+    RoutineLoadSummary,
+};
+pub use session_service::{SessionClient, SessionRevokeReason, SessionService};
+pub use sql_policy_service::SqlPolicyService;
+pub use sso_service::SsoService;
 pub use starrocks_client::StarRocksClient;
 pub use system_function_service::SystemFunctionService;
+pub use two_factor_service::TwoFactorService;
 pub use user_role_service::UserRoleService;
 pub use user_service::UserService;
+pub use variable_history_service::VariableHistoryService;
@@ -0,0 +1,559 @@
+// Materialized View Refresh Service
+// Purpose: Refresh task runs of the async materialized views (information_schema.task_runs),
+//          copied locally by the metrics collector so that they outlive the FE task run
+//          retention, with per-view statistics, failures grouped by error and trends
+
+use chrono::{NaiveDateTime, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::models::{
+    Cluster, MvRefreshDetail, MvRefreshDurationPoint, MvRefreshErrorGroup, MvRefreshFailureBucket,
+    MvRefreshHistoryFilter, MvRefreshRun, MvRefreshStats, MvRefreshTrendInterval, MvRefreshTrends,
+};
+use crate::services::load_job_service::{escape_string, parse_time, text};
+use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::utils::{ApiError, ApiResult};
+
+/// Runs returned when the history request sets no limit
+pub const DEFAULT_HISTORY_LIMIT: i64 = 100;
+/// Upper bound of the history limit
+pub const MAX_HISTORY_LIMIT: i64 = 1000;
+/// Query IDs kept per error group
+const ERROR_GROUP_QUERY_IDS: usize = 10;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, sqlx::FromRow)]
+struct RefreshRunRow {
+    database_name: String,
+    mv_name: String,
+    task_name: String,
+    query_id: String,
+    state: String,
+    create_time: String,
+    finish_time: Option<String>,
+    duration_ms: Option<i64>,
+    error_code: Option<i64>,
+    error_message: Option<String>,
+    refreshed_partitions: Option<String>,
+}
+
+impl From<RefreshRunRow> for MvRefreshRun {
+    fn from(row: RefreshRunRow) -> Self {
+        Self {
+            query_id: row.query_id,
+            database_name: row.database_name,
+            name: row.mv_name,
+            task_name: row.task_name,
+            state: row.state,
+            create_time: row.create_time,
+            finish_time: row.finish_time,
+            duration_ms: row.duration_ms,
+            error_code: row.error_code,
+            error_message: row.error_message,
+            refreshed_partitions: row
+                .refreshed_partitions
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+const RUN_COLUMNS: &str = "database_name, mv_name, task_name, query_id, state, create_time, \
+     finish_time, duration_ms, error_code, error_message, refreshed_partitions";
+
+#[derive(Clone)]
+pub struct MvRefreshService {
+    db: SqlitePool,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+}
+
+impl MvRefreshService {
+    pub fn new(db: SqlitePool, mysql_pool_manager: Arc<MySQLPoolManager>) -> Self {
+        Self { db, mysql_pool_manager }
+    }
+
+    async fn client(&self, cluster: &Cluster) -> ApiResult<MySQLClient> {
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        Ok(MySQLClient::from_pool(pool))
+    }
+
+    // ========================================
+    // Collection
+    // ========================================
+
+    /// Copy the refresh task runs created since the last collection (called by the metrics
+    /// collector); returns the number of runs saved
+    pub async fn record_runs(&self, cluster: &Cluster) -> ApiResult<usize> {
+        let client = self.client(cluster).await?;
+
+        // task_runs only knows the task name (mv-<id>) of a view
+        let views = client
+            .query(
+                "SELECT TABLE_SCHEMA, TABLE_NAME, TASK_NAME FROM information_schema.materialized_views \
+                 WHERE TABLE_SCHEMA NOT IN ('information_schema', '_statistics_')",
+            )
+            .await?;
+        let tasks: HashMap<String, (String, String)> = views
+            .iter()
+            .filter_map(|row| {
+                Some((
+                    text(row, &["TASK_NAME"])?,
+                    (text(row, &["TABLE_SCHEMA"])?, text(row, &["TABLE_NAME"])?),
+                ))
+            })
+            .collect();
+        if tasks.is_empty() {
+            return Ok(0);
+        }
+
+        let mut sql = format!(
+            "SELECT * FROM information_schema.task_runs WHERE TASK_NAME IN ({})",
+            tasks
+                .keys()
+                .map(|task| format!("'{}'", escape_string(task)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if let Some(since) = self.collection_cursor(cluster.id).await? {
+            sql.push_str(&format!(" AND CREATE_TIME >= '{}'", escape_string(&since)));
+        }
+        tracing::debug!("Querying refresh task runs of cluster {}", cluster.name);
+
+        let runs: Vec<MvRefreshRun> = client
+            .query(&sql)
+            .await?
+            .iter()
+            .filter_map(|row| parse_task_run(row, &tasks))
+            .collect();
+        self.save_runs(cluster.id, &runs).await?;
+        Ok(runs.len())
+    }
+
+    /// Creation time from which task runs are read again: the oldest run that was still
+    /// pending or running, otherwise the newest run
+    async fn collection_cursor(&self, cluster_id: i64) -> ApiResult<Option<String>> {
+        let (unfinished,): (Option<String>,) = sqlx::query_as(
+            "SELECT MIN(create_time) FROM mv_refresh_history
+             WHERE cluster_id = ? AND state IN ('PENDING', 'RUNNING')",
+        )
+        .bind(cluster_id)
+        .fetch_one(&self.db)
+        .await?;
+        if unfinished.is_some() {
+            return Ok(unfinished);
+        }
+
+        let (latest,): (Option<String>,) =
+            sqlx::query_as("SELECT MAX(create_time) FROM mv_refresh_history WHERE cluster_id = ?")
+                .bind(cluster_id)
+                .fetch_one(&self.db)
+                .await?;
+        Ok(latest)
+    }
+
+    /// Insert new runs and update the ones seen before
+    pub async fn save_runs(&self, cluster_id: i64, runs: &[MvRefreshRun]) -> ApiResult<()> {
+        let collected_at = Utc::now();
+        let mut tx = self.db.begin().await?;
+        for run in runs {
+            sqlx::query(
+                "INSERT INTO mv_refresh_history (cluster_id, database_name, mv_name, task_name,
+                 query_id, state, create_time, finish_time, duration_ms, error_code, error_message,
+                 refreshed_partitions, collected_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(cluster_id, query_id) DO UPDATE SET
+                    state = excluded.state,
+                    finish_time = excluded.finish_time,
+                    duration_ms = excluded.duration_ms,
+                    error_code = excluded.error_code,
+                    error_message = excluded.error_message,
+                    refreshed_partitions = excluded.refreshed_partitions,
+                    collected_at = excluded.collected_at",
+            )
+            .bind(cluster_id)
+            .bind(&run.database_name)
+            .bind(&run.name)
+            .bind(&run.task_name)
+            .bind(&run.query_id)
+            .bind(&run.state)
+            .bind(&run.create_time)
+            .bind(&run.finish_time)
+            .bind(run.duration_ms)
+            .bind(run.error_code)
+            .bind(&run.error_message)
+            .bind(serde_json::to_string(&run.refreshed_partitions)?)
+            .bind(collected_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete runs last collected before `cutoff`; returns the number of deleted rows
+    pub async fn cleanup_before(&self, cutoff: chrono::DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM mv_refresh_history WHERE collected_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ========================================
+    // History
+    // ========================================
+
+    /// Database of a view name; a database is required when several databases have a view
+    /// with that name
+    pub async fn resolve_database(
+        &self,
+        cluster_id: i64,
+        name: &str,
+        database: Option<&str>,
+    ) -> ApiResult<String> {
+        if let Some(database) = database {
+            return Ok(database.to_string());
+        }
+
+        let databases: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT database_name FROM mv_refresh_history
+             WHERE cluster_id = ? AND mv_name = ? ORDER BY database_name",
+        )
+        .bind(cluster_id)
+        .bind(name)
+        .fetch_all(&self.db)
+        .await?;
+        match databases.as_slice() {
+            [] => Err(ApiError::not_found(format!(
+                "No refresh history of materialized view '{}'",
+                name
+            ))),
+            [(database,)] => Ok(database.clone()),
+            _ => Err(ApiError::validation_error(format!(
+                "Materialized view '{}' exists in several databases, set the database",
+                name
+            ))),
+        }
+    }
+
+    /// Runs of a cluster, newest first. State and limit are left to the caller.
+    async fn fetch_runs(
+        &self,
+        cluster_id: i64,
+        database: Option<&str>,
+        name: Option<&str>,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+    ) -> ApiResult<Vec<MvRefreshRun>> {
+        let mut conditions = vec!["cluster_id = ?"];
+        let mut params: Vec<String> = vec![cluster_id.to_string()];
+
+        if let Some(database) = database {
+            conditions.push("database_name = ?");
+            params.push(database.to_string());
+        }
+        if let Some(name) = name {
+            conditions.push("mv_name = ?");
+            params.push(name.to_string());
+        }
+        if let Some(start) = start_time.filter(|s| !s.is_empty()) {
+            conditions.push("create_time >= ?");
+            params.push(parse_time("start_time", start)?);
+        }
+        if let Some(end) = end_time.filter(|s| !s.is_empty()) {
+            conditions.push("create_time < ?");
+            params.push(parse_time("end_time", end)?);
+        }
+
+        let sql = format!(
+            "SELECT {} FROM mv_refresh_history WHERE {} ORDER BY create_time DESC, id DESC",
+            RUN_COLUMNS,
+            conditions.join(" AND ")
+        );
+        let mut query = sqlx::query_as::<_, RefreshRunRow>(&sql);
+        for param in &params {
+            query = query.bind(param);
+        }
+        let rows = query.fetch_all(&self.db).await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Runs of a view with its statistics and failures grouped by error
+    pub async fn get_detail(
+        &self,
+        cluster_id: i64,
+        database: &str,
+        name: &str,
+        filter: &MvRefreshHistoryFilter,
+    ) -> ApiResult<MvRefreshDetail> {
+        let runs = self
+            .fetch_runs(
+                cluster_id,
+                Some(database),
+                Some(name),
+                filter.start_time.as_deref(),
+                filter.end_time.as_deref(),
+            )
+            .await?;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT) as usize;
+
+        Ok(MvRefreshDetail {
+            stats: refresh_stats(database, name, &runs),
+            errors: group_errors(&runs),
+            runs: runs
+                .into_iter()
+                .filter(|run| {
+                    filter
+                        .state
+                        .as_deref()
+                        .is_none_or(|state| run.state.eq_ignore_ascii_case(state))
+                })
+                .take(limit)
+                .collect(),
+        })
+    }
+
+    /// Refresh duration and failure rate of a view over time
+    pub async fn get_trends(
+        &self,
+        cluster_id: i64,
+        database: &str,
+        name: &str,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        interval: MvRefreshTrendInterval,
+    ) -> ApiResult<MvRefreshTrends> {
+        let mut runs = self
+            .fetch_runs(cluster_id, Some(database), Some(name), start_time, end_time)
+            .await?;
+        runs.reverse();
+
+        Ok(MvRefreshTrends {
+            database_name: database.to_string(),
+            name: name.to_string(),
+            duration: runs
+                .iter()
+                .filter(|run| is_finished(run))
+                .filter_map(|run| {
+                    Some(MvRefreshDurationPoint {
+                        create_time: run.create_time.clone(),
+                        duration_ms: run.duration_ms?,
+                        state: run.state.clone(),
+                    })
+                })
+                .collect(),
+            failure_rate: failure_buckets(&runs, interval),
+        })
+    }
+
+    /// Refresh statistics of every view with history, most failures first
+    pub async fn get_stats(
+        &self,
+        cluster_id: i64,
+        database: Option<&str>,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+    ) -> ApiResult<Vec<MvRefreshStats>> {
+        let runs = self
+            .fetch_runs(cluster_id, database, None, start_time, end_time)
+            .await?;
+
+        let mut by_view: BTreeMap<(String, String), Vec<MvRefreshRun>> = BTreeMap::new();
+        for run in runs {
+            by_view
+                .entry((run.database_name.clone(), run.name.clone()))
+                .or_default()
+                .push(run);
+        }
+
+        let mut stats: Vec<MvRefreshStats> = by_view
+            .iter()
+            .map(|((database, name), runs)| refresh_stats(database, name, runs))
+            .collect();
+        stats.sort_by(|a, b| b.failed.cmp(&a.failed));
+        Ok(stats)
+    }
+}
+
+// ========================================
+// Parsing and analytics
+// ========================================
+
+/// A task_runs row of a known view refresh task
+pub fn parse_task_run(
+    row: &Value,
+    tasks: &HashMap<String, (String, String)>,
+) -> Option<MvRefreshRun> {
+    let task_name = text(row, &["TASK_NAME"])?;
+    let (database_name, name) = tasks.get(&task_name)?.clone();
+    let create_time = text(row, &["CREATE_TIME"])?;
+    let finish_time = text(row, &["FINISH_TIME"]);
+    let state = text(row, &["STATE"]).unwrap_or_else(|| "UNKNOWN".to_string());
+
+    Some(MvRefreshRun {
+        query_id: text(row, &["QUERY_ID"])?,
+        database_name,
+        name,
+        task_name,
+        duration_ms: finish_time
+            .as_deref()
+            .and_then(|finish| duration_ms(&create_time, finish)),
+        create_time,
+        finish_time,
+        error_code: text(row, &["ERROR_CODE"])
+            .and_then(|code| code.trim().parse().ok())
+            .filter(|code| *code != 0),
+        // Successful runs may report the message as "null"
+        error_message: text(row, &["ERROR_MESSAGE"])
+            .filter(|message| !message.eq_ignore_ascii_case("null")),
+        state,
+        refreshed_partitions: text(row, &["EXTRA_MESSAGE"])
+            .map(|extra| refreshed_partitions(&extra))
+            .unwrap_or_default(),
+    })
+}
+
+/// `mvPartitionsToRefresh` of the EXTRA_MESSAGE JSON of a refresh task run
+pub fn refreshed_partitions(extra_message: &str) -> Vec<String> {
+    serde_json::from_str::<Value>(extra_message)
+        .ok()
+        .and_then(|extra| {
+            extra
+                .get("mvPartitionsToRefresh")?
+                .as_array()
+                .map(|partitions| {
+                    partitions
+                        .iter()
+                        .filter_map(|p| p.as_str().map(str::to_string))
+                        .collect()
+                })
+        })
+        .unwrap_or_default()
+}
+
+/// Milliseconds between two FE times
+pub fn duration_ms(start: &str, finish: &str) -> Option<i64> {
+    let parse = |value: &str| {
+        NaiveDateTime::parse_from_str(value.trim(), TIME_FORMAT)
+            .or_else(|_| NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S%.f"))
+            .ok()
+    };
+    let duration = parse(finish)? - parse(start)?;
+    Some(duration.num_milliseconds().max(0))
+}
+
+fn is_finished(run: &MvRefreshRun) -> bool {
+    matches!(run.state.as_str(), "SUCCESS" | "FAILED")
+}
+
+/// Statistics of the runs of a view, given newest first
+pub fn refresh_stats(database: &str, name: &str, runs: &[MvRefreshRun]) -> MvRefreshStats {
+    let finished: Vec<&MvRefreshRun> = runs.iter().filter(|run| is_finished(run)).collect();
+    let failed: Vec<&MvRefreshRun> = finished
+        .iter()
+        .copied()
+        .filter(|run| run.state == "FAILED")
+        .collect();
+    let durations: Vec<i64> = finished.iter().filter_map(|run| run.duration_ms).collect();
+
+    MvRefreshStats {
+        database_name: database.to_string(),
+        name: name.to_string(),
+        runs: finished.len() as i64,
+        succeeded: (finished.len() - failed.len()) as i64,
+        failed: failed.len() as i64,
+        failure_rate: rate(failed.len(), finished.len()),
+        avg_duration_ms: (!durations.is_empty())
+            .then(|| durations.iter().sum::<i64>() / durations.len() as i64),
+        max_duration_ms: durations.iter().copied().max(),
+        last_success_time: finished
+            .iter()
+            .find(|run| run.state == "SUCCESS")
+            .map(|run| run.create_time.clone()),
+        last_failure_time: failed.first().map(|run| run.create_time.clone()),
+        last_error: failed.first().and_then(|run| run.error_message.clone()),
+    }
+}
+
+fn rate(part: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+/// Failed runs grouped by error code and message, most frequent first; runs given newest
+/// first
+pub fn group_errors(runs: &[MvRefreshRun]) -> Vec<MvRefreshErrorGroup> {
+    let mut groups: Vec<MvRefreshErrorGroup> = Vec::new();
+    for run in runs.iter().filter(|run| run.state == "FAILED") {
+        let message = run
+            .error_message
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .unwrap_or("Unknown error");
+        match groups
+            .iter_mut()
+            .find(|g| g.error_code == run.error_code && g.error_message == message)
+        {
+            Some(group) => {
+                group.count += 1;
+                group.first_seen = run.create_time.clone();
+                if group.query_ids.len() < ERROR_GROUP_QUERY_IDS {
+                    group.query_ids.push(run.query_id.clone());
+                }
+            },
+            None => groups.push(MvRefreshErrorGroup {
+                error_code: run.error_code,
+                error_message: message.to_string(),
+                count: 1,
+                first_seen: run.create_time.clone(),
+                last_seen: run.create_time.clone(),
+                query_ids: vec![run.query_id.clone()],
+            }),
+        }
+    }
+    groups.sort_by(|a, b| b.count.cmp(&a.count));
+    groups
+}
+
+/// Finished runs and failures per hour or day, oldest first
+pub fn failure_buckets(
+    runs: &[MvRefreshRun],
+    interval: MvRefreshTrendInterval,
+) -> Vec<MvRefreshFailureBucket> {
+    let mut buckets: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for run in runs.iter().filter(|run| is_finished(run)) {
+        let bucket = match interval {
+            MvRefreshTrendInterval::Hour => run
+                .create_time
+                .get(..13)
+                .map(|hour| format!("{}:00:00", hour)),
+            MvRefreshTrendInterval::Day => run.create_time.get(..10).map(str::to_string),
+        };
+        let Some(bucket) = bucket else {
+            continue;
+        };
+        let entry = buckets.entry(bucket).or_default();
+        entry.0 += 1;
+        if run.state == "FAILED" {
+            entry.1 += 1;
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(bucket, (runs, failed))| MvRefreshFailureBucket {
+            bucket,
+            runs: runs as i64,
+            failed: failed as i64,
+            failure_rate: rate(failed, runs),
+        })
+        .collect()
+}
@@ -145,6 +145,29 @@ impl MySQLSession {
         Ok(())
     }
 
+    /// Limit the execution time of queries on this session's connection
+    pub async fn set_query_timeout(&mut self, timeout_secs: i64) -> Result<(), ApiError> {
+        let sql = format!("SET query_timeout = {}", timeout_secs);
+        self.conn
+            .query_drop(&sql)
+            .await
+            .map_err(|e| ApiError::internal_error(format!("Failed to set query timeout: {}", e)))
+    }
+
+    /// Stop the statement running on this session's connection (e.g. after a client-side
+    /// timeout) with `KILL QUERY` from a separate connection to the same FE
+    pub async fn kill_query(&self) -> Result<(), ApiError> {
+        let mut conn = Conn::new(self.conn.opts().clone()).await.map_err(|e| {
+            ApiError::cluster_connection_failed(format!("Failed to get connection: {}", e))
+        })?;
+        let result = conn
+            .query_drop(format!("KILL QUERY {}", self.conn.id()))
+            .await
+            .map_err(|e| ApiError::internal_error(format!("Failed to kill query: {}", e)));
+        let _ = conn.disconnect().await;
+        result
+    }
+
     /// Execute a query and return both results and execution time (SQL only, excluding data processing)
     pub async fn execute(
         &mut self,
@@ -1,8 +1,40 @@
-use crate::models::cluster::Cluster;
-use crate::utils::error::ApiResult;
+use crate::models::cluster::{Cluster, FeEndpoint, FeEndpointSource, FeEndpointStatus};
+use crate::services::mysql_client::MySQLClient;
+use crate::utils::error::{ApiError, ApiResult};
+use crate::utils::{ClusterTls, CredentialCipher};
 use dashmap::DashMap;
-use mysql_async::{OptsBuilder, Pool, SslOpts};
+use mysql_async::prelude::Queryable;
+use mysql_async::{OptsBuilder, Pool};
+use serde_json::Value;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A cached pool is pinged again once its last successful check is older than this
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Frontends are rediscovered with `SHOW FRONTENDS` at most this often
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Pool connected to one FE endpoint
+#[derive(Clone)]
+struct EndpointPool {
+    endpoint: FeEndpoint,
+    pool: Pool,
+    checked_at: Instant,
+}
+
+/// Frontend reported by `SHOW FRONTENDS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredFrontend {
+    pub endpoint: FeEndpoint,
+    pub is_leader: bool,
+    pub alive: bool,
+}
+
+struct Discovery {
+    frontends: Vec<DiscoveredFrontend>,
+    discovered_at: Instant,
+}
 
 /// Manager for MySQL connection pools using mysql_async with DashMap
 ///
@@ -10,58 +42,249 @@ use std::sync::Arc;
 /// Maintains a pool for each cluster to avoid reconnecting on every query.
 ///
 /// Performance: 3-5x better than RwLock<HashMap> under high concurrency.
+///
+/// Credentials: holds the credential cipher so stored cluster passwords are
+/// decrypted only when a pool (or HTTP client) is created.
+///
+/// Failover: a cluster may have several FE endpoints (configured, or discovered with
+/// `SHOW FRONTENDS`). The pool of a cluster is bound to one endpoint that is pinged at most
+/// every `HEALTH_CHECK_INTERVAL`; when the check fails the next reachable endpoint is used.
 #[derive(Clone)]
 pub struct MySQLPoolManager {
-    pools: Arc<DashMap<i64, Pool>>,
+    pools: Arc<DashMap<i64, EndpointPool>>,
+    leader_pools: Arc<DashMap<i64, EndpointPool>>,
+    discoveries: Arc<DashMap<i64, Discovery>>,
+    cipher: Arc<CredentialCipher>,
 }
 
 impl MySQLPoolManager {
-    pub fn new() -> Self {
-        Self { pools: Arc::new(DashMap::new()) }
+    pub fn new(cipher: Arc<CredentialCipher>) -> Self {
+        Self {
+            pools: Arc::new(DashMap::new()),
+            leader_pools: Arc::new(DashMap::new()),
+            discoveries: Arc::new(DashMap::new()),
+            cipher,
+        }
+    }
+
+    /// Cipher used to encrypt/decrypt cluster credentials
+    pub fn cipher(&self) -> &CredentialCipher {
+        &self.cipher
+    }
+
+    /// Manager with the same cipher and none of the cached pools, for connections that must
+    /// not be shared (e.g. testing the settings of a cluster that is not saved yet)
+    pub fn detached(&self) -> Self {
+        Self::new(Arc::clone(&self.cipher))
     }
 }
 
 impl Default for MySQLPoolManager {
+    /// Manager with a process-local random key (tests and throwaway managers)
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(CredentialCipher::ephemeral()))
     }
 }
 
 impl MySQLPoolManager {
     /// Get or create a connection pool for the given cluster
     ///
-    /// Fast path: If pool exists, return immediately (lock-free read)
-    /// Slow path: Create new pool if doesn't exist
+    /// Fast path: If the pool was checked recently, return immediately (lock-free read)
+    /// Slow path: Ping the current endpoint and fail over to the next reachable FE
     pub async fn get_pool(&self, cluster: &Cluster) -> ApiResult<Pool> {
-        let cluster_id = cluster.id;
+        let current = self.pools.get(&cluster.id).map(|entry| entry.clone());
 
-        // Fast path: Try to get existing pool (lock-free)
-        if let Some(pool) = self.pools.get(&cluster_id) {
-            return Ok(pool.clone());
+        // Fast path: Recently checked pool (lock-free)
+        if let Some(current) = &current
+            && current.checked_at.elapsed() < HEALTH_CHECK_INTERVAL
+        {
+            return Ok(current.pool.clone());
         }
 
-        // Slow path: Create new pool
-        let pool = self.create_pool(cluster).await?;
+        // Slow path: First reachable endpoint, starting with the current one
+        let mut errors = Vec::new();
+        for endpoint in self.candidate_endpoints(cluster) {
+            let pool = match &current {
+                Some(current) if current.endpoint == endpoint => current.pool.clone(),
+                _ => self.create_pool(cluster, &endpoint)?,
+            };
 
-        // Insert into map (DashMap handles concurrent inserts gracefully)
-        self.pools.insert(cluster_id, pool.clone());
+            if let Err(e) = Self::check_pool(cluster, &pool).await {
+                tracing::warn!("FE {} of cluster {} is unreachable: {}", endpoint, cluster.id, e);
+                errors.push(format!("{}: {}", endpoint, e));
+                continue;
+            }
 
-        tracing::info!(
-            "Created MySQL connection pool for cluster {} ({}:{})",
-            cluster_id,
-            cluster.fe_host,
-            cluster.fe_query_port
-        );
+            match current.as_ref().map(|current| &current.endpoint) {
+                None => tracing::info!(
+                    "Created MySQL connection pool for cluster {} ({})",
+                    cluster.id,
+                    endpoint
+                ),
+                Some(previous) if *previous != endpoint => tracing::warn!(
+                    "Cluster {} failed over from FE {} to FE {}",
+                    cluster.id,
+                    previous,
+                    endpoint
+                ),
+                Some(_) => {},
+            }
+
+            // Insert into map (DashMap handles concurrent inserts gracefully)
+            self.pools.insert(
+                cluster.id,
+                EndpointPool { endpoint, pool: pool.clone(), checked_at: Instant::now() },
+            );
+            self.refresh_discovery(cluster, &pool).await;
+            return Ok(pool);
+        }
+
+        Err(ApiError::cluster_connection_failed(format!(
+            "No reachable FE for cluster {}: {}",
+            cluster.name,
+            errors.join("; ")
+        )))
+    }
 
-        Ok(pool)
+    /// Pool connected to the leader FE, for statements that must run on the leader
+    /// (e.g. SET GLOBAL). Falls back to the regular pool when the leader is unknown
+    /// or unreachable.
+    pub async fn get_leader_pool(&self, cluster: &Cluster) -> ApiResult<Pool> {
+        let pool = self.get_pool(cluster).await?;
+        let Some(leader) = self.leader_endpoint(cluster.id) else {
+            return Ok(pool);
+        };
+        if self
+            .pools
+            .get(&cluster.id)
+            .is_some_and(|entry| entry.endpoint == leader)
+        {
+            return Ok(pool);
+        }
+
+        let cached = self
+            .leader_pools
+            .get(&cluster.id)
+            .map(|entry| entry.clone())
+            .filter(|entry| entry.endpoint == leader);
+        if let Some(cached) = &cached
+            && cached.checked_at.elapsed() < HEALTH_CHECK_INTERVAL
+        {
+            return Ok(cached.pool.clone());
+        }
+
+        let leader_pool = match cached {
+            Some(cached) => cached.pool,
+            None => self.create_pool(cluster, &leader)?,
+        };
+        match Self::check_pool(cluster, &leader_pool).await {
+            Ok(()) => {
+                self.leader_pools.insert(
+                    cluster.id,
+                    EndpointPool {
+                        endpoint: leader,
+                        pool: leader_pool.clone(),
+                        checked_at: Instant::now(),
+                    },
+                );
+                Ok(leader_pool)
+            },
+            Err(e) => {
+                tracing::warn!(
+                    "Leader FE {} of cluster {} is unreachable ({}), using the current FE",
+                    leader,
+                    cluster.id,
+                    e
+                );
+                self.leader_pools.remove(&cluster.id);
+                Ok(pool)
+            },
+        }
+    }
+
+    /// FE endpoints of a cluster in the order they should be tried: the current endpoint,
+    /// the configured endpoints, then alive endpoints found by discovery
+    pub fn candidate_endpoints(&self, cluster: &Cluster) -> Vec<FeEndpoint> {
+        let configured = cluster.configured_endpoints();
+        let discovered: Vec<FeEndpoint> = self
+            .discovered_frontends(cluster.id)
+            .into_iter()
+            .filter(|frontend| frontend.alive)
+            .map(|frontend| frontend.endpoint)
+            .collect();
+
+        let mut candidates = Vec::with_capacity(configured.len() + discovered.len());
+        // A cached endpoint no longer configured nor discovered (cluster edited) is dropped
+        if let Some(current) = self.current_endpoint(cluster.id)
+            && (configured.contains(&current) || discovered.contains(&current))
+        {
+            candidates.push(current);
+        }
+        for endpoint in configured.into_iter().chain(discovered) {
+            if !candidates.contains(&endpoint) {
+                candidates.push(endpoint);
+            }
+        }
+        candidates
+    }
+
+    /// Endpoint the pool of a cluster is connected to
+    pub fn current_endpoint(&self, cluster_id: i64) -> Option<FeEndpoint> {
+        self.pools
+            .get(&cluster_id)
+            .map(|entry| entry.endpoint.clone())
+    }
+
+    /// Alive leader FE according to the last discovery
+    pub fn leader_endpoint(&self, cluster_id: i64) -> Option<FeEndpoint> {
+        self.discovered_frontends(cluster_id)
+            .into_iter()
+            .find(|frontend| frontend.is_leader && frontend.alive)
+            .map(|frontend| frontend.endpoint)
+    }
+
+    /// Configured and discovered endpoints of a cluster with their connection state
+    pub fn endpoint_status(&self, cluster: &Cluster) -> Vec<FeEndpointStatus> {
+        let current = self.current_endpoint(cluster.id);
+        let discovered = self.discovered_frontends(cluster.id);
+        let configured = cluster.configured_endpoints();
+
+        let status = |endpoint: &FeEndpoint, source: FeEndpointSource| {
+            let frontend = discovered
+                .iter()
+                .find(|frontend| frontend.endpoint == *endpoint);
+            FeEndpointStatus {
+                host: endpoint.host.clone(),
+                http_port: endpoint.http_port,
+                query_port: endpoint.query_port,
+                source,
+                is_current: current.as_ref() == Some(endpoint),
+                is_leader: frontend.is_some_and(|frontend| frontend.is_leader),
+                alive: frontend.map(|frontend| frontend.alive),
+            }
+        };
+
+        let mut endpoints: Vec<FeEndpointStatus> = configured
+            .iter()
+            .map(|endpoint| status(endpoint, FeEndpointSource::Configured))
+            .collect();
+        endpoints.extend(
+            discovered
+                .iter()
+                .filter(|frontend| !configured.contains(&frontend.endpoint))
+                .map(|frontend| status(&frontend.endpoint, FeEndpointSource::Discovered)),
+        );
+        endpoints
     }
 
     /// Remove a pool for a specific cluster
     ///
     /// Useful when cluster is deleted or credentials are updated
     pub async fn remove_pool(&self, cluster_id: i64) {
-        if let Some((_, pool)) = self.pools.remove(&cluster_id) {
-            drop(pool); // Pool will be closed when all references are dropped
+        self.leader_pools.remove(&cluster_id);
+        self.discoveries.remove(&cluster_id);
+        if let Some((_, entry)) = self.pools.remove(&cluster_id) {
+            drop(entry); // Pool will be closed when all references are dropped
             tracing::info!("Removed MySQL connection pool for cluster {}", cluster_id);
         }
     }
@@ -69,6 +292,8 @@ impl MySQLPoolManager {
     /// Clear all pools (useful for cleanup/testing)
     pub async fn clear_all(&self) {
         self.pools.clear();
+        self.leader_pools.clear();
+        self.discoveries.clear();
         tracing::info!("Cleared all MySQL connection pools");
     }
 
@@ -77,23 +302,89 @@ impl MySQLPoolManager {
         self.pools.len()
     }
 
-    /// Create a new MySQL connection pool for a cluster
-    async fn create_pool(&self, cluster: &Cluster) -> ApiResult<Pool> {
+    /// Store the frontends of a cluster found by discovery
+    pub(crate) fn record_discovery(&self, cluster_id: i64, frontends: Vec<DiscoveredFrontend>) {
+        self.discoveries
+            .insert(cluster_id, Discovery { frontends, discovered_at: Instant::now() });
+    }
+
+    fn discovered_frontends(&self, cluster_id: i64) -> Vec<DiscoveredFrontend> {
+        self.discoveries
+            .get(&cluster_id)
+            .map(|discovery| discovery.frontends.clone())
+            .unwrap_or_default()
+    }
+
+    /// Refresh the frontends of a cluster with `SHOW FRONTENDS` unless done recently.
+    /// Failures are not fatal: the configured endpoints are still used.
+    async fn refresh_discovery(&self, cluster: &Cluster, pool: &Pool) {
+        if self
+            .discoveries
+            .get(&cluster.id)
+            .is_some_and(|discovery| discovery.discovered_at.elapsed() < DISCOVERY_INTERVAL)
+        {
+            return;
+        }
+
+        let client = MySQLClient::from_pool(pool.clone());
+        match tokio::time::timeout(Self::probe_timeout(cluster), client.query("SHOW FRONTENDS"))
+            .await
+        {
+            Ok(Ok(rows)) => {
+                let frontends = parse_frontends(&rows);
+                tracing::debug!(
+                    "Discovered {} frontend(s) for cluster {}",
+                    frontends.len(),
+                    cluster.id
+                );
+                self.record_discovery(cluster.id, frontends);
+            },
+            Ok(Err(e)) => {
+                tracing::debug!("Frontend discovery failed for cluster {}: {}", cluster.id, e)
+            },
+            Err(_) => tracing::debug!("Frontend discovery timed out for cluster {}", cluster.id),
+        }
+    }
+
+    fn probe_timeout(cluster: &Cluster) -> Duration {
+        Duration::from_secs(cluster.connection_timeout.max(1) as u64)
+    }
+
+    /// Ping the FE behind a pool within the cluster connection timeout
+    async fn check_pool(cluster: &Cluster, pool: &Pool) -> Result<(), String> {
+        let probe = async {
+            let mut conn = pool.get_conn().await?;
+            conn.ping().await
+        };
+        match tokio::time::timeout(Self::probe_timeout(cluster), probe).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => {
+                Err(format!("no response within {}s", Self::probe_timeout(cluster).as_secs()))
+            },
+        }
+    }
+
+    /// Create a new MySQL connection pool for one FE of a cluster
+    fn create_pool(&self, cluster: &Cluster, endpoint: &FeEndpoint) -> ApiResult<Pool> {
+        let password = self.cipher.decrypt(&cluster.password_encrypted)?;
+        let ssl_opts =
+            ClusterTls::from_cluster(cluster, &self.cipher)?.map(|tls| tls.mysql_ssl_opts());
         let opts = OptsBuilder::default()
-            .ip_or_hostname(&cluster.fe_host)
-            .tcp_port(cluster.fe_query_port as u16)
+            .ip_or_hostname(&endpoint.host)
+            .tcp_port(endpoint.query_port as u16)
             .user(Some(&cluster.username))
-            .pass(Some(&cluster.password_encrypted))
+            .pass(Some(password))
             .db_name(None::<String>) // No default database
             .prefer_socket(false) // Disable socket preference for StarRocks compatibility
-            .ssl_opts(None::<SslOpts>) // No SSL for now
+            .ssl_opts(ssl_opts) // TLS when enable_ssl is set
             .tcp_keepalive(Some(30_000_u32)) // 30 seconds
             .tcp_nodelay(true) // Disable Nagle's algorithm for lower latency
             .pool_opts(
                 mysql_async::PoolOpts::default()
                     .with_constraints(mysql_async::PoolConstraints::new(2, 20).ok_or_else(
                         || {
-                            crate::utils::ApiError::internal_error(
+                            ApiError::internal_error(
                                 "Failed to create pool constraints: invalid min/max values",
                             )
                         },
@@ -105,3 +396,29 @@ impl MySQLPoolManager {
         Ok(Pool::new(opts))
     }
 }
+
+/// Parse `SHOW FRONTENDS` rows. Older versions report `Host`/`IsMaster` instead of
+/// `IP`/`Role`; rows without a host or ports are skipped.
+pub(crate) fn parse_frontends(rows: &[Value]) -> Vec<DiscoveredFrontend> {
+    rows.iter()
+        .filter_map(|row| {
+            let field = |names: &[&str]| {
+                names
+                    .iter()
+                    .find_map(|name| row.get(*name).and_then(Value::as_str))
+                    .map(str::trim)
+            };
+            let host = field(&["IP", "Host"]).filter(|host| !host.is_empty())?;
+            let endpoint = FeEndpoint {
+                host: host.to_string(),
+                http_port: field(&["HttpPort"])?.parse().ok()?,
+                query_port: field(&["QueryPort"])?.parse().ok()?,
+            };
+            let is_leader = field(&["Role"])
+                .is_some_and(|role| role.eq_ignore_ascii_case("LEADER"))
+                || field(&["IsMaster"]).is_some_and(|value| value.eq_ignore_ascii_case("true"));
+            let alive = field(&["Alive"]).is_none_or(|value| value.eq_ignore_ascii_case("true"));
+            Some(DiscoveredFrontend { endpoint, is_leader, alive })
+        })
+        .collect()
+}
@@ -0,0 +1,641 @@
+// Node Metrics Service
+// Purpose: Per-node (BE/CN/FE) metrics history next to the cluster aggregates in
+//          metrics_snapshots, for node level trends and drill-downs
+
+use crate::models::starrocks::{Backend, Frontend};
+use crate::services::TimeRange;
+use crate::services::metrics_collector_service::parse_storage_size;
+use crate::services::overview_service::TimeSeriesPoint;
+use crate::utils::{ApiError, ApiResult};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// FE metric with the max compaction score of each BE, labelled `backend="host:heartbeat_port"`
+const BE_COMPACTION_SCORE_METRIC: &str = "starrocks_fe_tablet_max_compaction_score";
+
+/// Kind of a cluster node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeType {
+    /// Backend (shared-nothing)
+    Be,
+    /// Compute node (shared-data)
+    Cn,
+    /// Frontend
+    Fe,
+}
+
+impl NodeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeType::Be => "be",
+            NodeType::Cn => "cn",
+            NodeType::Fe => "fe",
+        }
+    }
+}
+
+impl std::str::FromStr for NodeType {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "be" => Ok(NodeType::Be),
+            "cn" => Ok(NodeType::Cn),
+            "fe" => Ok(NodeType::Fe),
+            other => Err(ApiError::invalid_data(format!("Unknown node type: {}", other))),
+        }
+    }
+}
+
+/// Usage of one storage path of a BE
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NodeDiskPath {
+    pub root_path: String,
+    pub state: String,
+    pub storage_medium: String,
+    pub total_bytes: Option<i64>,
+    pub used_bytes: Option<i64>,
+    pub data_used_bytes: Option<i64>,
+    pub usage_pct: Option<f64>,
+    pub tablet_count: Option<i64>,
+}
+
+/// Metrics of one node at one collection tick.
+/// Resource and storage fields are only reported for BE/CN nodes, role/leader/journal
+/// fields only for FE nodes.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NodeMetricsSnapshot {
+    pub cluster_id: i64,
+    pub collected_at: DateTime<Utc>,
+    pub node_type: NodeType,
+    pub node_id: String,
+    pub host: String,
+    /// Heartbeat port (BE/CN) or edit log port (FE)
+    pub port: i64,
+    pub alive: bool,
+    pub last_heartbeat: Option<String>,
+
+    // Resources (BE/CN)
+    pub cpu_usage_pct: Option<f64>,
+    pub mem_usage_pct: Option<f64>,
+    pub disk_total_bytes: Option<i64>,
+    pub disk_used_bytes: Option<i64>,
+    pub disk_usage_pct: Option<f64>,
+    pub data_used_bytes: Option<i64>,
+    pub disk_paths: Vec<NodeDiskPath>,
+
+    // Storage (BE/CN)
+    pub tablet_count: Option<i64>,
+    pub max_compaction_score: Option<f64>,
+    pub num_running_queries: Option<i64>,
+
+    // FE
+    pub role: Option<String>,
+    pub is_leader: bool,
+    pub replayed_journal_id: Option<i64>,
+}
+
+/// Latest metrics of a node with its peaks over the queried time range
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeMetricsSummary {
+    pub latest: NodeMetricsSnapshot,
+    pub peak_cpu_usage_pct: Option<f64>,
+    pub peak_mem_usage_pct: Option<f64>,
+    pub peak_disk_usage_pct: Option<f64>,
+    /// Share of the snapshots in the time range in which the node was alive (0-100)
+    pub availability_pct: f64,
+    pub samples: i64,
+}
+
+/// Usage trend of one storage path
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeDiskPathTrend {
+    pub root_path: String,
+    pub usage_pct: Vec<TimeSeriesPoint>,
+    pub used_bytes: Vec<TimeSeriesPoint>,
+}
+
+/// Metrics trends of a node
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeMetricsTrends {
+    pub node_type: NodeType,
+    pub node_id: String,
+    pub cpu_usage_pct: Vec<TimeSeriesPoint>,
+    pub mem_usage_pct: Vec<TimeSeriesPoint>,
+    pub disk_usage_pct: Vec<TimeSeriesPoint>,
+    pub data_used_bytes: Vec<TimeSeriesPoint>,
+    pub tablet_count: Vec<TimeSeriesPoint>,
+    pub max_compaction_score: Vec<TimeSeriesPoint>,
+    pub num_running_queries: Vec<TimeSeriesPoint>,
+    /// 1 when the node was alive, 0 otherwise
+    pub alive: Vec<TimeSeriesPoint>,
+    pub disk_paths: Vec<NodeDiskPathTrend>,
+}
+
+#[derive(sqlx::FromRow)]
+struct NodeSnapshotRow {
+    cluster_id: i64,
+    collected_at: NaiveDateTime,
+    node_type: String,
+    node_id: String,
+    host: String,
+    port: i64,
+    alive: bool,
+    last_heartbeat: Option<String>,
+    cpu_usage_pct: Option<f64>,
+    mem_usage_pct: Option<f64>,
+    disk_total_bytes: Option<i64>,
+    disk_used_bytes: Option<i64>,
+    disk_usage_pct: Option<f64>,
+    data_used_bytes: Option<i64>,
+    disk_paths: Option<String>,
+    tablet_count: Option<i64>,
+    max_compaction_score: Option<f64>,
+    num_running_queries: Option<i64>,
+    role: Option<String>,
+    is_leader: bool,
+    replayed_journal_id: Option<i64>,
+}
+
+impl TryFrom<NodeSnapshotRow> for NodeMetricsSnapshot {
+    type Error = ApiError;
+
+    fn try_from(r: NodeSnapshotRow) -> Result<Self, Self::Error> {
+        Ok(NodeMetricsSnapshot {
+            cluster_id: r.cluster_id,
+            collected_at: r.collected_at.and_utc(),
+            node_type: r.node_type.parse()?,
+            node_id: r.node_id,
+            host: r.host,
+            port: r.port,
+            alive: r.alive,
+            last_heartbeat: r.last_heartbeat,
+            cpu_usage_pct: r.cpu_usage_pct,
+            mem_usage_pct: r.mem_usage_pct,
+            disk_total_bytes: r.disk_total_bytes,
+            disk_used_bytes: r.disk_used_bytes,
+            disk_usage_pct: r.disk_usage_pct,
+            data_used_bytes: r.data_used_bytes,
+            disk_paths: r
+                .disk_paths
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default(),
+            tablet_count: r.tablet_count,
+            max_compaction_score: r.max_compaction_score,
+            num_running_queries: r.num_running_queries,
+            role: r.role,
+            is_leader: r.is_leader,
+            replayed_journal_id: r.replayed_journal_id,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct NodeMetricsService {
+    db: SqlitePool,
+}
+
+impl NodeMetricsService {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// Save the node snapshots of one collection tick
+    pub async fn save_snapshots(&self, snapshots: &[NodeMetricsSnapshot]) -> ApiResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        for s in snapshots {
+            let disk_paths = if s.disk_paths.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_string(&s.disk_paths).map_err(|e| {
+                    ApiError::internal_error(format!("Failed to serialize disk paths: {}", e))
+                })?)
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO node_metrics_snapshots (
+                    cluster_id, collected_at, node_type, node_id, host, port,
+                    alive, last_heartbeat,
+                    cpu_usage_pct, mem_usage_pct,
+                    disk_total_bytes, disk_used_bytes, disk_usage_pct, data_used_bytes, disk_paths,
+                    tablet_count, max_compaction_score, num_running_queries,
+                    role, is_leader, replayed_journal_id
+                ) VALUES (
+                    ?, ?, ?, ?, ?, ?,
+                    ?, ?,
+                    ?, ?,
+                    ?, ?, ?, ?, ?,
+                    ?, ?, ?,
+                    ?, ?, ?
+                )
+                "#,
+            )
+            .bind(s.cluster_id)
+            .bind(s.collected_at)
+            .bind(s.node_type.as_str())
+            .bind(&s.node_id)
+            .bind(&s.host)
+            .bind(s.port)
+            .bind(s.alive)
+            .bind(&s.last_heartbeat)
+            .bind(s.cpu_usage_pct)
+            .bind(s.mem_usage_pct)
+            .bind(s.disk_total_bytes)
+            .bind(s.disk_used_bytes)
+            .bind(s.disk_usage_pct)
+            .bind(s.data_used_bytes)
+            .bind(disk_paths)
+            .bind(s.tablet_count)
+            .bind(s.max_compaction_score)
+            .bind(s.num_running_queries)
+            .bind(&s.role)
+            .bind(s.is_leader)
+            .bind(s.replayed_journal_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete node snapshots collected before `cutoff`; returns the number of deleted rows
+    pub async fn cleanup_before(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM node_metrics_snapshots WHERE collected_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Every node seen in the time range with its latest snapshot and its peaks
+    pub async fn list_nodes(
+        &self,
+        cluster_id: i64,
+        time_range: &TimeRange,
+    ) -> ApiResult<Vec<NodeMetricsSummary>> {
+        #[derive(sqlx::FromRow)]
+        struct PeakRow {
+            node_type: String,
+            node_id: String,
+            latest_at: NaiveDateTime,
+            peak_cpu_usage_pct: Option<f64>,
+            peak_mem_usage_pct: Option<f64>,
+            peak_disk_usage_pct: Option<f64>,
+            alive_samples: i64,
+            samples: i64,
+        }
+
+        let peaks: Vec<PeakRow> = sqlx::query_as(
+            r#"
+            SELECT node_type, node_id,
+                   MAX(collected_at) AS latest_at,
+                   MAX(cpu_usage_pct) AS peak_cpu_usage_pct,
+                   MAX(mem_usage_pct) AS peak_mem_usage_pct,
+                   MAX(disk_usage_pct) AS peak_disk_usage_pct,
+                   SUM(CASE WHEN alive THEN 1 ELSE 0 END) AS alive_samples,
+                   COUNT(*) AS samples
+            FROM node_metrics_snapshots
+            WHERE cluster_id = ? AND collected_at BETWEEN ? AND ?
+            GROUP BY node_type, node_id
+            ORDER BY node_type, node_id
+            "#,
+        )
+        .bind(cluster_id)
+        .bind(time_range.start_time())
+        .bind(time_range.end_time())
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut latest: HashMap<(String, String), NodeSnapshotRow> = sqlx::query_as(
+            r#"
+            SELECT n.* FROM node_metrics_snapshots n
+            JOIN (
+                SELECT node_type, node_id, MAX(collected_at) AS latest_at
+                FROM node_metrics_snapshots
+                WHERE cluster_id = ? AND collected_at BETWEEN ? AND ?
+                GROUP BY node_type, node_id
+            ) l ON n.node_type = l.node_type
+               AND n.node_id = l.node_id
+               AND n.collected_at = l.latest_at
+            WHERE n.cluster_id = ?
+            "#,
+        )
+        .bind(cluster_id)
+        .bind(time_range.start_time())
+        .bind(time_range.end_time())
+        .bind(cluster_id)
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(|row: NodeSnapshotRow| ((row.node_type.clone(), row.node_id.clone()), row))
+        .collect();
+
+        let mut nodes = Vec::with_capacity(peaks.len());
+        for peak in peaks {
+            let Some(row) = latest.remove(&(peak.node_type.clone(), peak.node_id.clone())) else {
+                tracing::debug!(
+                    "No snapshot of node {}/{} at {}",
+                    peak.node_type,
+                    peak.node_id,
+                    peak.latest_at
+                );
+                continue;
+            };
+            nodes.push(NodeMetricsSummary {
+                latest: row.try_into()?,
+                peak_cpu_usage_pct: peak.peak_cpu_usage_pct,
+                peak_mem_usage_pct: peak.peak_mem_usage_pct,
+                peak_disk_usage_pct: peak.peak_disk_usage_pct,
+                availability_pct: availability_pct(peak.alive_samples, peak.samples),
+                samples: peak.samples,
+            });
+        }
+
+        Ok(nodes)
+    }
+
+    /// Drill-down of one node: its latest snapshot (with storage paths) and its peaks
+    pub async fn get_node(
+        &self,
+        cluster_id: i64,
+        node_type: NodeType,
+        node_id: &str,
+        time_range: &TimeRange,
+    ) -> ApiResult<NodeMetricsSummary> {
+        let history = self
+            .get_node_history(cluster_id, node_type, node_id, time_range)
+            .await?;
+        let latest = match history.last() {
+            Some(latest) => latest.clone(),
+            None => {
+                // Not collected in the time range: fall back to the last known snapshot
+                let row: Option<NodeSnapshotRow> = sqlx::query_as(
+                    r#"
+                    SELECT * FROM node_metrics_snapshots
+                    WHERE cluster_id = ? AND node_type = ? AND node_id = ?
+                    ORDER BY collected_at DESC
+                    LIMIT 1
+                    "#,
+                )
+                .bind(cluster_id)
+                .bind(node_type.as_str())
+                .bind(node_id)
+                .fetch_optional(&self.db)
+                .await?;
+                row.ok_or_else(|| {
+                    ApiError::not_found(format!(
+                        "No metrics of {} node {} in cluster {}",
+                        node_type.as_str(),
+                        node_id,
+                        cluster_id
+                    ))
+                })?
+                .try_into()?
+            },
+        };
+
+        let peak = |value: fn(&NodeMetricsSnapshot) -> Option<f64>| {
+            history.iter().filter_map(value).reduce(f64::max)
+        };
+        let alive_samples = history.iter().filter(|s| s.alive).count() as i64;
+
+        Ok(NodeMetricsSummary {
+            peak_cpu_usage_pct: peak(|s| s.cpu_usage_pct),
+            peak_mem_usage_pct: peak(|s| s.mem_usage_pct),
+            peak_disk_usage_pct: peak(|s| s.disk_usage_pct),
+            availability_pct: availability_pct(alive_samples, history.len() as i64),
+            samples: history.len() as i64,
+            latest,
+        })
+    }
+
+    /// Metrics trends of one node
+    pub async fn get_node_trends(
+        &self,
+        cluster_id: i64,
+        node_type: NodeType,
+        node_id: &str,
+        time_range: &TimeRange,
+    ) -> ApiResult<NodeMetricsTrends> {
+        let history = self
+            .get_node_history(cluster_id, node_type, node_id, time_range)
+            .await?;
+
+        let series = |value: &dyn Fn(&NodeMetricsSnapshot) -> Option<f64>| {
+            history
+                .iter()
+                .filter_map(|s| {
+                    value(s).map(|value| TimeSeriesPoint { timestamp: s.collected_at, value })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut disk_paths: BTreeMap<&str, NodeDiskPathTrend> = BTreeMap::new();
+        for snapshot in &history {
+            for path in &snapshot.disk_paths {
+                let trend =
+                    disk_paths
+                        .entry(&path.root_path)
+                        .or_insert_with(|| NodeDiskPathTrend {
+                            root_path: path.root_path.clone(),
+                            usage_pct: Vec::new(),
+                            used_bytes: Vec::new(),
+                        });
+                let timestamp = snapshot.collected_at;
+                if let Some(value) = path.usage_pct {
+                    trend.usage_pct.push(TimeSeriesPoint { timestamp, value });
+                }
+                if let Some(value) = path.used_bytes {
+                    trend
+                        .used_bytes
+                        .push(TimeSeriesPoint { timestamp, value: value as f64 });
+                }
+            }
+        }
+
+        Ok(NodeMetricsTrends {
+            node_type,
+            node_id: node_id.to_string(),
+            cpu_usage_pct: series(&|s| s.cpu_usage_pct),
+            mem_usage_pct: series(&|s| s.mem_usage_pct),
+            disk_usage_pct: series(&|s| s.disk_usage_pct),
+            data_used_bytes: series(&|s| s.data_used_bytes.map(|v| v as f64)),
+            tablet_count: series(&|s| s.tablet_count.map(|v| v as f64)),
+            max_compaction_score: series(&|s| s.max_compaction_score),
+            num_running_queries: series(&|s| s.num_running_queries.map(|v| v as f64)),
+            alive: series(&|s| Some(if s.alive { 1.0 } else { 0.0 })),
+            disk_paths: disk_paths.into_values().collect(),
+        })
+    }
+
+    async fn get_node_history(
+        &self,
+        cluster_id: i64,
+        node_type: NodeType,
+        node_id: &str,
+        time_range: &TimeRange,
+    ) -> ApiResult<Vec<NodeMetricsSnapshot>> {
+        let rows: Vec<NodeSnapshotRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM node_metrics_snapshots
+            WHERE cluster_id = ? AND node_type = ? AND node_id = ?
+              AND collected_at BETWEEN ? AND ?
+            ORDER BY collected_at ASC
+            "#,
+        )
+        .bind(cluster_id)
+        .bind(node_type.as_str())
+        .bind(node_id)
+        .bind(time_range.start_time())
+        .bind(time_range.end_time())
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.into_iter()
+            .map(NodeMetricsSnapshot::try_from)
+            .collect()
+    }
+}
+
+fn availability_pct(alive_samples: i64, samples: i64) -> f64 {
+    if samples > 0 { alive_samples as f64 * 100.0 / samples as f64 } else { 0.0 }
+}
+
+/// Build the node snapshots of one collection tick.
+/// `metrics_text` is the FE Prometheus output (source of the per-BE compaction score) and
+/// `disk_paths` the storage paths of each BE by backend id.
+pub fn build_node_snapshots(
+    cluster_id: i64,
+    collected_at: DateTime<Utc>,
+    backend_type: NodeType,
+    backends: &[Backend],
+    frontends: &[Frontend],
+    metrics_text: &str,
+    disk_paths: &mut HashMap<String, Vec<NodeDiskPath>>,
+) -> Vec<NodeMetricsSnapshot> {
+    let compaction_scores =
+        labelled_metric_values(metrics_text, BE_COMPACTION_SCORE_METRIC, "backend");
+
+    let backend_snapshots = backends.iter().map(|b| {
+        let total = parse_storage_size(&b.total_capacity);
+        let avail = parse_storage_size(&b.avail_capacity);
+        NodeMetricsSnapshot {
+            cluster_id,
+            collected_at,
+            node_type: backend_type,
+            node_id: b.backend_id.clone(),
+            host: b.host.clone(),
+            port: b.heartbeat_port.parse().unwrap_or(0),
+            alive: b.alive == "true",
+            last_heartbeat: non_empty(&b.last_heartbeat),
+            cpu_usage_pct: parse_pct(&b.cpu_used_pct),
+            mem_usage_pct: parse_pct(&b.mem_used_pct),
+            disk_total_bytes: total,
+            disk_used_bytes: total.zip(avail).map(|(total, avail)| total - avail),
+            disk_usage_pct: parse_pct(&b.used_pct),
+            data_used_bytes: parse_storage_size(&b.data_used_capacity),
+            disk_paths: disk_paths.remove(&b.backend_id).unwrap_or_default(),
+            tablet_count: b.tablet_num.trim().parse().ok(),
+            max_compaction_score: compaction_scores
+                .get(&format!("{}:{}", b.host, b.heartbeat_port))
+                .copied(),
+            num_running_queries: b.num_running_queries.trim().parse().ok(),
+            role: None,
+            is_leader: false,
+            replayed_journal_id: None,
+        }
+    });
+
+    let frontend_snapshots = frontends.iter().map(|f| NodeMetricsSnapshot {
+        cluster_id,
+        collected_at,
+        node_type: NodeType::Fe,
+        node_id: f.name.clone(),
+        host: f.host.clone(),
+        port: f.edit_log_port.parse().unwrap_or(0),
+        alive: f.alive == "true",
+        last_heartbeat: non_empty(&f.last_heartbeat),
+        cpu_usage_pct: None,
+        mem_usage_pct: None,
+        disk_total_bytes: None,
+        disk_used_bytes: None,
+        disk_usage_pct: None,
+        data_used_bytes: None,
+        disk_paths: Vec::new(),
+        tablet_count: None,
+        max_compaction_score: None,
+        num_running_queries: None,
+        role: non_empty(&f.role),
+        is_leader: f.is_master.as_deref() == Some("true") || f.role == "LEADER",
+        replayed_journal_id: f.replayed_journal_id.trim().parse().ok(),
+    });
+
+    backend_snapshots.chain(frontend_snapshots).collect()
+}
+
+/// Storage paths of a BE from `SHOW PROC '/backends/<backend_id>'`
+pub fn parse_disk_paths(rows: &[Value]) -> Vec<NodeDiskPath> {
+    let field = |row: &Value, name: &str| {
+        row.get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    rows.iter()
+        .filter(|row| row.get("RootPath").is_some())
+        .map(|row| {
+            let total = parse_storage_size(&field(row, "TotalCapacity"));
+            let avail = parse_storage_size(&field(row, "AvailCapacity"));
+            NodeDiskPath {
+                root_path: field(row, "RootPath"),
+                state: field(row, "State"),
+                storage_medium: field(row, "StorageMedium"),
+                total_bytes: total,
+                used_bytes: total.zip(avail).map(|(total, avail)| total - avail),
+                data_used_bytes: parse_storage_size(&field(row, "DataUsedCapacity")),
+                usage_pct: parse_pct(&field(row, "TotalUsedPct")),
+                tablet_count: field(row, "TabletNum").trim().parse().ok(),
+            }
+        })
+        .collect()
+}
+
+/// Values of a Prometheus metric by the value of one of its labels
+pub fn labelled_metric_values(
+    metrics_text: &str,
+    metric: &str,
+    label: &str,
+) -> HashMap<String, f64> {
+    let needle = format!("{}=\"", label);
+    metrics_text
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| {
+            let labels = line.strip_prefix(metric)?.strip_prefix('{')?;
+            let (labels, value) = labels.rsplit_once('}')?;
+            let value = value.trim().parse::<f64>().ok()?;
+            let start = labels.find(&needle)? + needle.len();
+            let end = labels[start..].find('"')? + start;
+            Some((labels[start..end].to_string(), value))
+        })
+        .collect()
+}
+
+fn parse_pct(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches('%').trim().parse().ok()
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value != "NULL").then(|| value.to_string())
+}
@@ -2,8 +2,10 @@
 // Purpose: Provide aggregated cluster overview data (real-time + historical)
 // Design Ref: ARCHITECTURE_ANALYSIS_AND_INTEGRATION.md
 
+use crate::models::{AlertSeverity, Cluster};
 use crate::services::{
-    ClusterService, DataStatistics, DataStatisticsService, MetricsSnapshot, MySQLClient,
+    AlertService, AuditColumn, AuditSourceService, ClusterService, DataStatistics,
+    DataStatisticsService, MetricsSnapshot, MySQLClient,
 };
 use crate::utils::{ApiError, ApiResult};
 use chrono::{DateTime, NaiveDateTime, Utc};
@@ -123,7 +125,7 @@ pub struct HealthCard {
 }
 
 /// Health status enum
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum HealthStatus {
     Healthy,
@@ -365,12 +367,64 @@ pub struct ExtendedClusterOverview {
     pub alerts: Vec<Alert>,
 }
 
+/// Fleet overview: one summary per cluster visible to the caller
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FleetOverview {
+    pub timestamp: DateTime<Utc>,
+    pub total: usize,
+    pub healthy: usize,
+    pub warning: usize,
+    pub critical: usize,
+    pub no_data: usize,
+    /// Clusters that failed or timed out
+    pub unavailable: usize,
+    pub clusters: Vec<FleetClusterSummary>,
+}
+
+/// Outcome of collecting one cluster of the fleet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetClusterStatus {
+    Ok,
+    /// Reachable, but no metrics snapshot collected yet
+    NoData,
+    Error,
+    Timeout,
+}
+
+/// Health card, KPIs and alerts of one cluster of the fleet
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FleetClusterSummary {
+    pub cluster_id: i64,
+    pub cluster_name: String,
+    pub organization_id: Option<i64>,
+    pub is_active: bool,
+    pub status: FleetClusterStatus,
+    pub error: Option<String>,
+    pub elapsed_ms: i64,
+    pub snapshot_at: Option<DateTime<Utc>>,
+    pub health: Option<ClusterHealth>,
+    pub kpi: Option<KeyPerformanceIndicators>,
+    pub alerts: Vec<Alert>,
+}
+
+/// Data of a reachable fleet cluster (None fields without a snapshot)
+#[derive(Default)]
+struct FleetClusterData {
+    snapshot_at: Option<DateTime<Utc>>,
+    health: Option<ClusterHealth>,
+    kpi: Option<KeyPerformanceIndicators>,
+    alerts: Vec<Alert>,
+}
+
 #[derive(Clone)]
 pub struct OverviewService {
     db: SqlitePool,
     cluster_service: Arc<ClusterService>,
     data_statistics_service: Option<Arc<DataStatisticsService>>,
+    alert_service: Option<Arc<AlertService>>,
     mysql_pool_manager: Arc<crate::services::mysql_pool_manager::MySQLPoolManager>,
+    audit_source_service: Arc<AuditSourceService>,
 }
 
 impl OverviewService {
@@ -379,8 +433,16 @@ impl OverviewService {
         db: SqlitePool,
         cluster_service: Arc<ClusterService>,
         mysql_pool_manager: Arc<crate::services::mysql_pool_manager::MySQLPoolManager>,
+        audit_source_service: Arc<AuditSourceService>,
     ) -> Self {
-        Self { db, cluster_service, data_statistics_service: None, mysql_pool_manager }
+        Self {
+            db,
+            cluster_service,
+            data_statistics_service: None,
+            alert_service: None,
+            mysql_pool_manager,
+            audit_source_service,
+        }
     }
 
     /// Set data statistics service (optional dependency)
@@ -389,6 +451,12 @@ impl OverviewService {
         self
     }
 
+    /// Set alert service (optional dependency, adds firing alert rules to the overview)
+    pub fn with_alert_service(mut self, service: Arc<AlertService>) -> Self {
+        self.alert_service = Some(service);
+        self
+    }
+
     /// Get cluster overview (main API)
     pub async fn get_cluster_overview(
         &self,
@@ -426,6 +494,135 @@ impl OverviewService {
         })
     }
 
+    /// Get the fleet overview of the given clusters.
+    /// Clusters are collected concurrently; a cluster that fails or does not answer within
+    /// `timeout` is reported with its status instead of failing the whole overview.
+    pub async fn get_fleet_overview(
+        &self,
+        clusters: Vec<Cluster>,
+        timeout: std::time::Duration,
+    ) -> FleetOverview {
+        let tasks: Vec<_> = clusters
+            .iter()
+            .map(|cluster| {
+                let service = self.clone();
+                let cluster_id = cluster.id;
+                tokio::spawn(async move {
+                    let started = std::time::Instant::now();
+                    let result =
+                        tokio::time::timeout(timeout, service.get_fleet_cluster_data(cluster_id))
+                            .await;
+                    (result, started.elapsed())
+                })
+            })
+            .collect();
+
+        let mut summaries = Vec::with_capacity(clusters.len());
+        for (cluster, task) in clusters.into_iter().zip(tasks) {
+            let (status, error, data, elapsed) = match task.await {
+                Ok((Ok(Ok(data)), elapsed)) => {
+                    let status = if data.health.is_some() {
+                        FleetClusterStatus::Ok
+                    } else {
+                        FleetClusterStatus::NoData
+                    };
+                    (status, None, Some(data), elapsed)
+                },
+                Ok((Ok(Err(e)), elapsed)) => {
+                    tracing::warn!("Fleet overview of cluster {} failed: {}", cluster.id, e);
+                    (FleetClusterStatus::Error, Some(e.to_string()), None, elapsed)
+                },
+                Ok((Err(_), elapsed)) => {
+                    tracing::warn!(
+                        "Fleet overview of cluster {} timed out after {:?}",
+                        cluster.id,
+                        timeout
+                    );
+                    let message = format!("No response within {}ms", timeout.as_millis());
+                    (FleetClusterStatus::Timeout, Some(message), None, elapsed)
+                },
+                Err(e) => {
+                    tracing::error!("Fleet overview task of cluster {} failed: {}", cluster.id, e);
+                    (FleetClusterStatus::Error, Some(e.to_string()), None, timeout)
+                },
+            };
+            let data = data.unwrap_or_default();
+
+            summaries.push(FleetClusterSummary {
+                cluster_id: cluster.id,
+                cluster_name: cluster.name,
+                organization_id: cluster.organization_id,
+                is_active: cluster.is_active,
+                status,
+                error,
+                elapsed_ms: elapsed.as_millis() as i64,
+                snapshot_at: data.snapshot_at,
+                health: data.health,
+                kpi: data.kpi,
+                alerts: data.alerts,
+            });
+        }
+
+        let health_count = |wanted: HealthStatus| {
+            summaries
+                .iter()
+                .filter(|summary| {
+                    summary
+                        .health
+                        .as_ref()
+                        .is_some_and(|health| health.status == wanted)
+                })
+                .count()
+        };
+        let status_count = |wanted: &[FleetClusterStatus]| {
+            summaries
+                .iter()
+                .filter(|summary| wanted.contains(&summary.status))
+                .count()
+        };
+
+        FleetOverview {
+            timestamp: Utc::now(),
+            total: summaries.len(),
+            healthy: health_count(HealthStatus::Healthy),
+            warning: health_count(HealthStatus::Warning),
+            critical: health_count(HealthStatus::Critical),
+            no_data: status_count(&[FleetClusterStatus::NoData]),
+            unavailable: status_count(&[FleetClusterStatus::Error, FleetClusterStatus::Timeout]),
+            clusters: summaries,
+        }
+    }
+
+    /// Health, KPIs and alerts of one fleet cluster (last hour)
+    async fn get_fleet_cluster_data(&self, cluster_id: i64) -> ApiResult<FleetClusterData> {
+        // Live probe first: an unreachable cluster is reported as such, not with stale metrics
+        let starrocks_version = self.get_starrocks_version(cluster_id).await?;
+
+        let (latest, snapshots) = tokio::try_join!(
+            self.get_latest_snapshot(cluster_id),
+            self.get_history_snapshots(cluster_id, &TimeRange::Hours1)
+        )?;
+        let Some(snapshot) = latest.as_ref() else {
+            return Ok(FleetClusterData::default());
+        };
+
+        let health = self
+            .calculate_cluster_health(cluster_id, snapshot, &starrocks_version)
+            .await?;
+        let kpi = self.calculate_kpi(&latest, &snapshots);
+        let resources = self.calculate_resource_metrics(&latest, &snapshots);
+
+        let mut alerts = self.generate_alerts(&health, &resources);
+        alerts.extend(self.firing_rule_alerts(cluster_id).await);
+
+        Ok(FleetClusterData {
+            snapshot_at: Some(snapshot.collected_at),
+            health: Some(health),
+            kpi: Some(kpi),
+            alerts,
+        })
+    }
+
     /// Get health status cards
     pub async fn get_health_cards(&self, cluster_id: i64) -> ApiResult<Vec<HealthCard>> {
         let snapshot = self.get_latest_snapshot(cluster_id).await?;
@@ -730,6 +927,8 @@ impl OverviewService {
             txn_failed_total: i64,
             load_running: i64,
             load_finished_total: i64,
+            routine_load_paused: i64,
+            routine_load_max_lag: i64,
             jvm_heap_total: i64,
             jvm_heap_used: i64,
             jvm_heap_usage_pct: f64,
@@ -787,6 +986,8 @@ impl OverviewService {
                 txn_failed_total: r.txn_failed_total,
                 load_running: r.load_running as i32,
                 load_finished_total: r.load_finished_total,
+                routine_load_paused: r.routine_load_paused as i32,
+                routine_load_max_lag: r.routine_load_max_lag,
                 jvm_heap_total: r.jvm_heap_total,
                 jvm_heap_used: r.jvm_heap_used,
                 jvm_heap_usage_pct: r.jvm_heap_usage_pct,
@@ -842,6 +1043,8 @@ impl OverviewService {
             txn_failed_total: i64,
             load_running: i64,
             load_finished_total: i64,
+            routine_load_paused: i64,
+            routine_load_max_lag: i64,
             jvm_heap_total: i64,
             jvm_heap_used: i64,
             jvm_heap_usage_pct: f64,
@@ -905,6 +1108,8 @@ impl OverviewService {
                 txn_failed_total: r.txn_failed_total,
                 load_running: r.load_running as i32,
                 load_finished_total: r.load_finished_total,
+                routine_load_paused: r.routine_load_paused as i32,
+                routine_load_max_lag: r.routine_load_max_lag,
                 jvm_heap_total: r.jvm_heap_total,
                 jvm_heap_used: r.jvm_heap_used,
                 jvm_heap_usage_pct: r.jvm_heap_usage_pct,
@@ -1097,7 +1302,8 @@ impl OverviewService {
             cap.real_data_size_bytes = stats.total_data_size;
         }
 
-        let alerts = self.generate_alerts(&health, &resources, &compaction);
+        let mut alerts = self.generate_alerts(&health, &resources);
+        alerts.extend(self.firing_rule_alerts(cluster_id).await);
 
         Ok(ExtendedClusterOverview {
             cluster_id,
@@ -1431,20 +1637,26 @@ impl OverviewService {
 
         // Query ALTER TABLE operations from audit logs
         // Track schema changes by analyzing DDL statements in the audit log
+        let audit = self.audit_source_service.resolve(&cluster).await?;
         let query = format!(
             r#"
             SELECT 
-                queryType,
-                state,
+                {query_type} as queryType,
+                {state} as state,
                 COUNT(*) as count
-            FROM starrocks_audit_db__.starrocks_audit_tbl__
+            FROM {table}
             WHERE 
-                `timestamp` >= '{}'
-                AND queryType LIKE '%ALTER%'
-                AND isQuery = 0  -- DDL operations have isQuery = 0
-            GROUP BY queryType, state
+                {ts} >= '{start}'
+                AND {query_type} LIKE '%ALTER%'
+                AND {is_query} = 0  -- DDL operations have isQuery = 0
+            GROUP BY {query_type}, {state}
             "#,
-            start_time.format("%Y-%m-%d %H:%M:%S")
+            query_type = audit.col(AuditColumn::QueryType),
+            state = audit.col(AuditColumn::State),
+            table = audit.full_table_name(),
+            ts = audit.col(AuditColumn::Timestamp),
+            is_query = audit.col(AuditColumn::IsQuery),
+            start = start_time.format("%Y-%m-%d %H:%M:%S")
         );
 
         let (columns, rows) = mysql_client.query_raw(&query).await?;
@@ -1560,7 +1772,9 @@ impl OverviewService {
 
         // Query 1: Get Top 10 partitions by compaction score
         // Filter out system databases and tables
-        let top_partitions_query = r#"
+        let audit = self.audit_source_service.resolve(&cluster).await?;
+        let top_partitions_query = format!(
+            r#"
             SELECT 
                 DB_NAME, 
                 TABLE_NAME, 
@@ -1570,14 +1784,16 @@ impl OverviewService {
                 P50_CS as p50_score
             FROM information_schema.partitions_meta
             WHERE MAX_CS > 0 
-              AND DB_NAME NOT IN ('_statistics_', 'information_schema', 'sys', 'starrocks_audit_db__')
-              AND TABLE_NAME != 'starrocks_audit_tbl__'
+              AND DB_NAME NOT IN ('_statistics_', 'information_schema', 'sys', '{}')
+              AND TABLE_NAME != '{}'
             ORDER BY MAX_CS DESC
             LIMIT 10
-        "#;
+        "#,
+            audit.database, audit.table
+        );
 
         let (_headers, rows) = client
-            .query_raw(top_partitions_query)
+            .query_raw(&top_partitions_query)
             .await
             .unwrap_or((vec![], vec![]));
 
@@ -1592,8 +1808,8 @@ impl OverviewService {
                     if db_name == "_statistics_"
                         || db_name == "information_schema"
                         || db_name == "sys"
-                        || db_name == "starrocks_audit_db__"
-                        || table_name == "starrocks_audit_tbl__"
+                        || db_name == audit.database
+                        || table_name == audit.table
                     {
                         tracing::debug!("Filtering out system table: {}.{}", db_name, table_name);
                         return None;
@@ -1803,12 +2019,7 @@ impl OverviewService {
     }
 
     /// Module 18: Generate alerts based on current state
-    fn generate_alerts(
-        &self,
-        health: &ClusterHealth,
-        resources: &ResourceMetrics,
-        _compaction: &CompactionStats,
-    ) -> Vec<Alert> {
+    fn generate_alerts(&self, health: &ClusterHealth, resources: &ResourceMetrics) -> Vec<Alert> {
         let mut alerts = Vec::new();
 
         // Critical: Node offline
@@ -1863,6 +2074,42 @@ impl OverviewService {
         alerts
     }
 
+    /// Currently firing user-defined alert rules
+    async fn firing_rule_alerts(&self, cluster_id: i64) -> Vec<Alert> {
+        let Some(service) = &self.alert_service else {
+            return Vec::new();
+        };
+        let rules = match service.firing_rules(cluster_id).await {
+            Ok(rules) => rules,
+            Err(e) => {
+                tracing::warn!("Failed to load firing alert rules: {}", e);
+                return Vec::new();
+            },
+        };
+
+        rules
+            .into_iter()
+            .map(|rule| Alert {
+                level: match rule.severity {
+                    AlertSeverity::Critical => AlertLevel::Critical,
+                    AlertSeverity::Warning => AlertLevel::Warning,
+                    AlertSeverity::Info => AlertLevel::Info,
+                },
+                category: "告警规则".to_string(),
+                message: format!(
+                    "{}: {} = {:.2} ({} {})",
+                    rule.name,
+                    rule.metric,
+                    rule.last_value.unwrap_or_default(),
+                    rule.comparator,
+                    rule.threshold
+                ),
+                timestamp: rule.last_evaluated_at.unwrap_or_else(Utc::now),
+                action: rule.description,
+            })
+            .collect()
+    }
+
     async fn get_starrocks_version(&self, cluster_id: i64) -> ApiResult<String> {
         use crate::services::StarRocksClient;
         let cluster = self.cluster_service.get_cluster(cluster_id).await?;
@@ -1,3 +1,4 @@
+use crate::middleware::permission_extractor;
 use crate::models::{Permission, PermissionResponse, PermissionTree};
 use crate::services::casbin_service::CasbinService;
 use crate::utils::ApiResult;
@@ -57,6 +58,17 @@ impl PermissionService {
 
         // First pass: create all nodes
         for perm in permissions {
+            let scope_level = (perm.r#type == "api")
+                .then(|| {
+                    CasbinService::permission_policy(
+                        &perm.code,
+                        perm.action.as_deref().unwrap_or(""),
+                    )
+                })
+                .flatten()
+                .and_then(|(resource, action)| {
+                    permission_extractor::scope_level(&resource, &action)
+                });
             let node = PermissionTree {
                 id: perm.id,
                 code: perm.code.clone(),
@@ -65,6 +77,7 @@ impl PermissionService {
                 resource: perm.resource.clone(),
                 action: perm.action.clone(),
                 description: perm.description.clone(),
+                scope_level,
                 children: vec![],
             };
 
@@ -87,6 +100,7 @@ impl PermissionService {
                         resource: node.resource.clone(),
                         action: node.action.clone(),
                         description: node.description.clone(),
+                        scope_level: node.scope_level,
                         children: build_tree(Some(node.id), tree_map),
                     })
                     .collect()
@@ -98,13 +112,17 @@ impl PermissionService {
         Ok(build_tree(None, &tree_map))
     }
 
-    /// Get user's all permissions (flat list)
+    /// Get user's all permissions (flat list), including those granted on some clusters only
     pub async fn get_user_permissions(&self, user_id: i64) -> ApiResult<Vec<PermissionResponse>> {
         let permissions: Vec<Permission> = sqlx::query_as(
             r#"
             SELECT DISTINCT p.*
             FROM permissions p
-            JOIN role_permissions rp ON p.id = rp.permission_id
+            JOIN (
+                SELECT role_id, permission_id FROM role_permissions
+                UNION
+                SELECT role_id, permission_id FROM role_permission_scopes
+            ) rp ON p.id = rp.permission_id
             JOIN user_roles ur ON rp.role_id = ur.role_id
             WHERE ur.user_id = ?
             ORDER BY p.type, p.code
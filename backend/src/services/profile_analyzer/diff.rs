@@ -0,0 +1,264 @@
+//! Profile diff
+//!
+//! Compares two analyzed profiles of (usually) the same query, e.g. before and after tuning.
+//! Execution tree nodes are aligned by plan node id and operator name, so nodes that only
+//! exist on one side show up as plan changes rather than metric deltas.
+
+use super::analyzer::rules::{parse_bytes, parse_metric_value};
+use super::models::*;
+use super::parser::core::ValueParser;
+use super::severity_order;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Node-level spill metrics, in order of preference
+const SPILL_BYTES_METRICS: &[&str] = &["SpillBytes", "SpilledBytes", "SpillMergeInputBytes"];
+
+/// Compare two analysis results
+pub fn diff_profiles(
+    baseline: &ProfileAnalysisResponse,
+    target: &ProfileAnalysisResponse,
+) -> ProfileDiffResponse {
+    let (appeared, resolved, persisting) = diff_diagnostics(baseline, target);
+
+    ProfileDiffResponse {
+        baseline: diff_side(baseline),
+        target: diff_side(target),
+        score_delta: target.performance_score - baseline.performance_score,
+        total_time_ms: MetricDelta::new(total_time_ms(baseline), total_time_ms(target)),
+        peak_memory_bytes: MetricDelta::new(
+            summary_metric(baseline, |s| s.query_peak_memory.map(|b| b as f64)),
+            summary_metric(target, |s| s.query_peak_memory.map(|b| b as f64)),
+        ),
+        spill_bytes: MetricDelta::new(query_spill_bytes(baseline), query_spill_bytes(target)),
+        nodes: diff_nodes(baseline.execution_tree.as_ref(), target.execution_tree.as_ref()),
+        appeared_diagnostics: appeared,
+        resolved_diagnostics: resolved,
+        persisting_diagnostics: persisting,
+    }
+}
+
+impl MetricDelta {
+    pub fn new(baseline: Option<f64>, target: Option<f64>) -> Self {
+        let delta = baseline.zip(target).map(|(b, t)| t - b);
+        let change_pct = baseline
+            .zip(delta)
+            .filter(|(b, _)| *b != 0.0)
+            .map(|(b, d)| d / b * 100.0);
+        Self { baseline, target, delta, change_pct }
+    }
+}
+
+fn diff_side(analysis: &ProfileAnalysisResponse) -> ProfileDiffSide {
+    let summary = analysis.summary.as_ref();
+    let non_empty = |s: &String| if s.is_empty() { None } else { Some(s.clone()) };
+
+    ProfileDiffSide {
+        query_id: summary.and_then(|s| non_empty(&s.query_id)),
+        sql_statement: summary.and_then(|s| non_empty(&s.sql_statement)),
+        performance_score: analysis.performance_score,
+        conclusion: analysis.conclusion.clone(),
+    }
+}
+
+fn summary_metric(
+    analysis: &ProfileAnalysisResponse,
+    f: impl Fn(&ProfileSummary) -> Option<f64>,
+) -> Option<f64> {
+    analysis.summary.as_ref().and_then(f)
+}
+
+fn total_time_ms(analysis: &ProfileAnalysisResponse) -> Option<f64> {
+    summary_metric(analysis, |s| {
+        s.total_time_ms
+            .or_else(|| ValueParser::parse_time_to_ms(&s.total_time).ok())
+    })
+}
+
+fn query_spill_bytes(analysis: &ProfileAnalysisResponse) -> Option<f64> {
+    summary_metric(analysis, |s| {
+        s.query_spill_bytes
+            .as_deref()
+            .and_then(parse_bytes)
+            .map(|b| b as f64)
+    })
+}
+
+// ============================================================================
+// Node alignment
+// ============================================================================
+
+/// Alignment key: (plan node id, operator name, occurrence)
+///
+/// The occurrence index separates operators sharing a plan node id and name,
+/// e.g. the same exchange seen from multiple fragments.
+type NodeKey = (Option<i32>, String, usize);
+
+fn node_keys(tree: Option<&ExecutionTree>) -> Vec<(NodeKey, &ExecutionTreeNode)> {
+    let mut seen: HashMap<(Option<i32>, String), usize> = HashMap::new();
+    tree.map(|t| t.nodes.iter().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|node| {
+            let base = (node.plan_node_id, node.operator_name.clone());
+            let occurrence = seen.entry(base.clone()).or_default();
+            let key = (base.0, base.1, *occurrence);
+            *occurrence += 1;
+            (key, node)
+        })
+        .collect()
+}
+
+fn diff_nodes(baseline: Option<&ExecutionTree>, target: Option<&ExecutionTree>) -> Vec<NodeDiff> {
+    let baseline_nodes = node_keys(baseline);
+    let target_nodes = node_keys(target);
+    let baseline_by_key: HashMap<&NodeKey, &ExecutionTreeNode> =
+        baseline_nodes.iter().map(|(k, n)| (k, *n)).collect();
+
+    let mut matched: HashSet<&NodeKey> = HashSet::new();
+    let mut result = Vec::with_capacity(target_nodes.len());
+
+    // Target order first, so the list follows the "after" plan
+    for (key, target_node) in &target_nodes {
+        let baseline_node = baseline_by_key.get(key).copied();
+        if baseline_node.is_some() {
+            matched.insert(key);
+        }
+        result.push(node_diff(baseline_node, Some(target_node)));
+    }
+
+    for (key, baseline_node) in &baseline_nodes {
+        if !matched.contains(key) {
+            result.push(node_diff(Some(baseline_node), None));
+        }
+    }
+
+    result
+}
+
+fn node_diff(baseline: Option<&ExecutionTreeNode>, target: Option<&ExecutionTreeNode>) -> NodeDiff {
+    let node = target
+        .or(baseline)
+        .expect("at least one side of a node diff");
+    let status = match (baseline, target) {
+        (Some(_), Some(_)) => NodeDiffStatus::Matched,
+        (None, _) => NodeDiffStatus::Added,
+        (_, None) => NodeDiffStatus::Removed,
+    };
+    let metric = |f: fn(&ExecutionTreeNode) -> Option<f64>| {
+        MetricDelta::new(baseline.and_then(f), target.and_then(f))
+    };
+
+    let baseline_diags: HashSet<&String> = baseline
+        .map(|n| n.diagnostic_ids.iter().collect())
+        .unwrap_or_default();
+    let target_diags: HashSet<&String> = target
+        .map(|n| n.diagnostic_ids.iter().collect())
+        .unwrap_or_default();
+    let mut appeared: Vec<String> = target_diags
+        .difference(&baseline_diags)
+        .map(|s| s.to_string())
+        .collect();
+    let mut resolved: Vec<String> = baseline_diags
+        .difference(&target_diags)
+        .map(|s| s.to_string())
+        .collect();
+    appeared.sort();
+    resolved.sort();
+
+    NodeDiff {
+        plan_node_id: node.plan_node_id,
+        operator_name: node.operator_name.clone(),
+        status,
+        baseline_node_id: baseline.map(|n| n.id.clone()),
+        target_node_id: target.map(|n| n.id.clone()),
+        time_ms: metric(|n| {
+            n.metrics
+                .operator_total_time
+                .map(|ns| ns as f64 / 1_000_000.0)
+        }),
+        time_percentage: metric(|n| n.time_percentage),
+        rows: metric(node_rows),
+        memory_bytes: metric(|n| n.metrics.memory_usage.map(|b| b as f64)),
+        spill_bytes: metric(node_spill_bytes),
+        appeared_diagnostics: appeared,
+        resolved_diagnostics: resolved,
+    }
+}
+
+fn node_rows(node: &ExecutionTreeNode) -> Option<f64> {
+    node.rows
+        .or(node.metrics.push_row_num)
+        .or(node.metrics.pull_row_num)
+        .map(|r| r as f64)
+}
+
+fn node_spill_bytes(node: &ExecutionTreeNode) -> Option<f64> {
+    SPILL_BYTES_METRICS
+        .iter()
+        .find_map(|name| node.unique_metrics.get(*name))
+        .and_then(|v| parse_metric_value(v))
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+fn diff_diagnostics(
+    baseline: &ProfileAnalysisResponse,
+    target: &ProfileAnalysisResponse,
+) -> (Vec<DiagnosticChange>, Vec<DiagnosticChange>, Vec<DiagnosticChange>) {
+    let baseline_rules = group_by_rule(&baseline.diagnostics);
+    let target_rules = group_by_rule(&target.diagnostics);
+
+    let mut appeared = Vec::new();
+    let mut resolved = Vec::new();
+    let mut persisting = Vec::new();
+
+    for (rule_id, (diag, count)) in &target_rules {
+        let baseline_count = baseline_rules.get(rule_id).map(|(_, c)| *c).unwrap_or(0);
+        let change = diagnostic_change(diag, baseline_count, *count);
+        if baseline_count == 0 {
+            appeared.push(change);
+        } else {
+            persisting.push(change);
+        }
+    }
+    for (rule_id, (diag, count)) in &baseline_rules {
+        if !target_rules.contains_key(rule_id) {
+            resolved.push(diagnostic_change(diag, *count, 0));
+        }
+    }
+
+    for list in [&mut appeared, &mut resolved, &mut persisting] {
+        list.sort_by(|a, b| {
+            severity_order(&b.severity)
+                .cmp(&severity_order(&a.severity))
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+        });
+    }
+    (appeared, resolved, persisting)
+}
+
+/// Group diagnostics by rule id: (highest-severity diagnostic, affected node count)
+fn group_by_rule(diagnostics: &[DiagnosticResult]) -> BTreeMap<&str, (&DiagnosticResult, usize)> {
+    let mut groups: BTreeMap<&str, (&DiagnosticResult, usize)> = BTreeMap::new();
+    for diag in diagnostics {
+        let entry = groups.entry(diag.rule_id.as_str()).or_insert((diag, 0));
+        if severity_order(&diag.severity) > severity_order(&entry.0.severity) {
+            entry.0 = diag;
+        }
+        entry.1 += 1;
+    }
+    groups
+}
+
+fn diagnostic_change(diag: &DiagnosticResult, baseline: usize, target: usize) -> DiagnosticChange {
+    DiagnosticChange {
+        rule_id: diag.rule_id.clone(),
+        rule_name: diag.rule_name.clone(),
+        severity: diag.severity.clone(),
+        message: diag.message.clone(),
+        baseline_count: baseline,
+        target_count: target,
+    }
+}
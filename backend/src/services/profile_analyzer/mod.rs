@@ -44,6 +44,7 @@
 //! ```
 
 pub mod analyzer;
+pub mod diff;
 pub mod models;
 pub mod parser;
 
@@ -51,6 +52,7 @@ pub mod parser;
 mod tests;
 
 pub use analyzer::RuleEngine;
+pub use diff::diff_profiles;
 pub use models::*;
 pub use parser::ProfileComposer;
 
@@ -654,6 +654,99 @@ pub struct ParameterTuningSuggestion {
     pub impact: String,
 }
 
+// ============================================================================
+// Profile Diff
+// ============================================================================
+
+/// Comparison of two analyzed profiles (baseline = "before", target = "after")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileDiffResponse {
+    pub baseline: ProfileDiffSide,
+    pub target: ProfileDiffSide,
+    /// target score - baseline score (positive = improved)
+    pub score_delta: f64,
+    pub total_time_ms: MetricDelta,
+    pub peak_memory_bytes: MetricDelta,
+    pub spill_bytes: MetricDelta,
+    /// Execution tree nodes aligned by plan node id and operator
+    pub nodes: Vec<NodeDiff>,
+    /// Rules triggered only by the target profile
+    pub appeared_diagnostics: Vec<DiagnosticChange>,
+    /// Rules triggered only by the baseline profile
+    pub resolved_diagnostics: Vec<DiagnosticChange>,
+    /// Rules triggered by both profiles
+    pub persisting_diagnostics: Vec<DiagnosticChange>,
+}
+
+/// Headline information of one side of a diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileDiffSide {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sql_statement: Option<String>,
+    pub performance_score: f64,
+    pub conclusion: String,
+}
+
+/// A metric in both profiles and its change
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub baseline: Option<f64>,
+    pub target: Option<f64>,
+    /// target - baseline (only when both sides have the metric)
+    pub delta: Option<f64>,
+    /// Relative change in percent (only when baseline is non-zero)
+    pub change_pct: Option<f64>,
+}
+
+/// Whether an execution tree node exists in one or both profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeDiffStatus {
+    Matched,
+    /// Only in the target profile (plan changed)
+    Added,
+    /// Only in the baseline profile (plan changed)
+    Removed,
+}
+
+/// Per-node metric comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDiff {
+    pub plan_node_id: Option<i32>,
+    pub operator_name: String,
+    pub status: NodeDiffStatus,
+    /// ExecutionTreeNode.id in the baseline tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_node_id: Option<String>,
+    /// ExecutionTreeNode.id in the target tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_node_id: Option<String>,
+    pub time_ms: MetricDelta,
+    pub time_percentage: MetricDelta,
+    pub rows: MetricDelta,
+    pub memory_bytes: MetricDelta,
+    pub spill_bytes: MetricDelta,
+    /// Rule ids reported on this node only in the target profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub appeared_diagnostics: Vec<String>,
+    /// Rule ids reported on this node only in the baseline profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_diagnostics: Vec<String>,
+}
+
+/// A diagnostic rule in the diff with its affected node count on each side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticChange {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub severity: String,
+    pub message: String,
+    pub baseline_count: usize,
+    pub target_count: usize,
+}
+
 // ============================================================================
 // Topology Graph (for parsing)
 // ============================================================================
@@ -1445,4 +1445,165 @@ Query:
             println!("Profile completeness detection test passed!");
         }
     }
+
+    // ========================================================================
+    // Profile Diff Tests
+    // ========================================================================
+
+    mod diff_tests {
+        use super::*;
+        use crate::services::profile_analyzer::diff_profiles;
+        use std::collections::HashSet;
+
+        fn rule_ids(result: &ProfileAnalysisResponse) -> HashSet<String> {
+            result
+                .diagnostics
+                .iter()
+                .map(|d| d.rule_id.clone())
+                .collect()
+        }
+
+        #[test]
+        fn test_metric_delta() {
+            let d = MetricDelta::new(Some(200.0), Some(50.0));
+            assert_eq!(d.delta, Some(-150.0));
+            assert_eq!(d.change_pct, Some(-75.0));
+
+            let zero = MetricDelta::new(Some(0.0), Some(10.0));
+            assert_eq!(zero.delta, Some(10.0));
+            assert_eq!(zero.change_pct, None);
+
+            let one_sided = MetricDelta::new(None, Some(10.0));
+            assert_eq!(one_sided.delta, None);
+            assert_eq!(one_sided.change_pct, None);
+        }
+
+        #[test]
+        fn test_diff_identical_profiles() {
+            let result = analyze_profile(&load_profile("profile1.txt")).unwrap();
+            let diff = diff_profiles(&result, &result);
+
+            assert_eq!(diff.score_delta, 0.0);
+            assert_eq!(diff.total_time_ms.delta, Some(0.0));
+            assert!(diff.appeared_diagnostics.is_empty());
+            assert!(diff.resolved_diagnostics.is_empty());
+            assert_eq!(diff.persisting_diagnostics.len(), rule_ids(&result).len());
+
+            let tree = result.execution_tree.as_ref().unwrap();
+            assert_eq!(diff.nodes.len(), tree.nodes.len());
+            for node in &diff.nodes {
+                assert_eq!(node.status, NodeDiffStatus::Matched);
+                assert_eq!(node.baseline_node_id, node.target_node_id);
+                assert!(node.time_ms.delta.is_none_or(|d| d == 0.0));
+                assert!(node.rows.delta.is_none_or(|d| d == 0.0));
+                assert!(node.appeared_diagnostics.is_empty());
+            }
+        }
+
+        #[test]
+        fn test_diff_reports_node_deltas_and_diagnostic_changes() {
+            let baseline = analyze_profile(&load_profile("profile1.txt")).unwrap();
+            let mut target = baseline.clone();
+
+            // "Tune" the slowest node: halve its time and drop its diagnostics
+            let tree = target.execution_tree.as_mut().unwrap();
+            let slow = tree
+                .nodes
+                .iter_mut()
+                .filter(|n| n.metrics.operator_total_time.is_some())
+                .max_by_key(|n| n.metrics.operator_total_time)
+                .unwrap();
+            let slow_id = slow.id.clone();
+            let old_time = slow.metrics.operator_total_time.unwrap();
+            slow.metrics.operator_total_time = Some(old_time / 2);
+            let dropped_on_node = std::mem::take(&mut slow.diagnostic_ids);
+
+            let dropped_rule = baseline.diagnostics[0].rule_id.clone();
+            target.diagnostics.retain(|d| d.rule_id != dropped_rule);
+            target.performance_score = baseline.performance_score + 10.0;
+
+            let diff = diff_profiles(&baseline, &target);
+            assert_eq!(diff.score_delta, 10.0);
+
+            let node = diff
+                .nodes
+                .iter()
+                .find(|n| n.target_node_id.as_deref() == Some(slow_id.as_str()))
+                .unwrap();
+            assert_eq!(node.status, NodeDiffStatus::Matched);
+            let expected_ms = (old_time / 2) as f64 / 1e6 - old_time as f64 / 1e6;
+            assert!((node.time_ms.delta.unwrap() - expected_ms).abs() < 1e-6);
+            assert!((node.time_ms.change_pct.unwrap() + 50.0).abs() < 0.01);
+            let mut dropped_on_node = dropped_on_node;
+            dropped_on_node.sort();
+            dropped_on_node.dedup();
+            assert_eq!(node.resolved_diagnostics, dropped_on_node);
+
+            assert!(
+                diff.resolved_diagnostics
+                    .iter()
+                    .any(|c| c.rule_id == dropped_rule && c.target_count == 0)
+            );
+            assert!(diff.appeared_diagnostics.is_empty());
+            assert!(
+                diff.persisting_diagnostics
+                    .iter()
+                    .all(|c| c.rule_id != dropped_rule)
+            );
+        }
+
+        #[test]
+        fn test_diff_different_plans() {
+            let baseline = analyze_profile(&load_profile("profile1.txt")).unwrap();
+            let target = analyze_profile(&load_profile("profile2.txt")).unwrap();
+            let diff = diff_profiles(&baseline, &target);
+
+            assert_eq!(diff.score_delta, target.performance_score - baseline.performance_score);
+            assert_eq!(
+                diff.baseline.query_id.as_deref(),
+                Some("c025364c-a999-11f0-a663-f62b9654e895")
+            );
+
+            // Every node of both trees appears exactly once
+            let matched = diff
+                .nodes
+                .iter()
+                .filter(|n| n.status == NodeDiffStatus::Matched)
+                .count();
+            let added = diff
+                .nodes
+                .iter()
+                .filter(|n| n.status == NodeDiffStatus::Added)
+                .count();
+            let removed = diff
+                .nodes
+                .iter()
+                .filter(|n| n.status == NodeDiffStatus::Removed)
+                .count();
+            assert_eq!(matched + added, target.execution_tree.as_ref().unwrap().nodes.len());
+            assert_eq!(matched + removed, baseline.execution_tree.as_ref().unwrap().nodes.len());
+
+            // Diagnostic sets partition into appeared / resolved / persisting
+            let before = rule_ids(&baseline);
+            let after = rule_ids(&target);
+            let appeared: HashSet<String> = diff
+                .appeared_diagnostics
+                .iter()
+                .map(|c| c.rule_id.clone())
+                .collect();
+            let resolved: HashSet<String> = diff
+                .resolved_diagnostics
+                .iter()
+                .map(|c| c.rule_id.clone())
+                .collect();
+            let persisting: HashSet<String> = diff
+                .persisting_diagnostics
+                .iter()
+                .map(|c| c.rule_id.clone())
+                .collect();
+            assert_eq!(appeared, after.difference(&before).cloned().collect());
+            assert_eq!(resolved, before.difference(&after).cloned().collect());
+            assert_eq!(persisting, before.intersection(&after).cloned().collect());
+        }
+    }
 }
@@ -0,0 +1,648 @@
+// Profile Archive Service
+// Purpose: Persist query profiles in SQLite so they outlive the FE PROFILELIST retention,
+//          with an analysis summary for search and re-analysis with the current RuleEngine
+
+use crate::config::ProfileArchiveConfig;
+use crate::models::{
+    Cluster, ProfileArchiveDetail, ProfileArchiveItem, ProfileArchiveListResponse,
+    ProfileArchiveQuery,
+};
+use crate::services::mysql_pool_manager::MySQLPoolManager;
+use crate::services::profile_analyzer::parser::core::ValueParser;
+use crate::services::profile_analyzer::{
+    AnalysisContext, ProfileAnalysisResponse, analyze_profile_with_context,
+};
+use crate::services::{ClusterService, MySQLClient};
+use crate::utils::{ApiError, ApiResult, ScheduledTask};
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use sqlx::SqlitePool;
+use std::future::Future;
+use std::io::{Read, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Maximum number of profiles auto-archived per cluster in one cycle
+const MAX_AUTO_ARCHIVE_PER_CYCLE: usize = 50;
+
+/// Columns of the list view (everything except the profile blob)
+const SUMMARY_COLUMNS: &str = "id, cluster_id, query_id, query_user, default_db, sql_statement, \
+     query_state, query_start_time, total_time_ms, peak_memory_bytes, performance_score, \
+     rule_ids, diagnostic_count, conclusion, compression, raw_size, source, archived_by, \
+     archived_at, analyzed_at";
+
+/// How a profile entered the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveSource {
+    /// Archived explicitly by a user
+    Manual,
+    /// Archived by the background collector
+    Auto,
+}
+
+impl ArchiveSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ArchiveSource::Manual => "manual",
+            ArchiveSource::Auto => "auto",
+        }
+    }
+}
+
+/// Validate and sanitize a query_id before it is embedded in SQL
+/// StarRocks query_id format: UUID like "12345678-1234-1234-1234-123456789abc"
+pub fn sanitize_query_id(query_id: &str) -> Result<String, ApiError> {
+    let id = query_id.trim();
+    // Allow alphanumeric, hyphens, and underscores (UUID format)
+    if id.is_empty()
+        || id.len() > 64
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ApiError::invalid_data("Invalid query_id format"));
+    }
+    Ok(id.to_string())
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ArchiveRow {
+    id: i64,
+    cluster_id: i64,
+    query_id: String,
+    query_user: Option<String>,
+    default_db: Option<String>,
+    sql_statement: Option<String>,
+    query_state: Option<String>,
+    query_start_time: Option<String>,
+    total_time_ms: Option<f64>,
+    peak_memory_bytes: Option<i64>,
+    performance_score: Option<f64>,
+    rule_ids: String,
+    diagnostic_count: i64,
+    conclusion: Option<String>,
+    compression: String,
+    raw_size: i64,
+    source: String,
+    archived_by: Option<i64>,
+    archived_at: DateTime<Utc>,
+    analyzed_at: Option<DateTime<Utc>>,
+}
+
+impl From<ArchiveRow> for ProfileArchiveItem {
+    fn from(row: ArchiveRow) -> Self {
+        Self {
+            id: row.id,
+            cluster_id: row.cluster_id,
+            query_id: row.query_id,
+            query_user: row.query_user,
+            default_db: row.default_db,
+            sql_statement: row.sql_statement,
+            query_state: row.query_state,
+            query_start_time: row.query_start_time,
+            total_time_ms: row.total_time_ms,
+            peak_memory_bytes: row.peak_memory_bytes,
+            performance_score: row.performance_score,
+            rule_ids: serde_json::from_str(&row.rule_ids).unwrap_or_default(),
+            diagnostic_count: row.diagnostic_count,
+            conclusion: row.conclusion,
+            compression: row.compression,
+            raw_size: row.raw_size,
+            source: row.source,
+            archived_by: row.archived_by,
+            archived_at: row.archived_at,
+            analyzed_at: row.analyzed_at,
+        }
+    }
+}
+
+/// Searchable summary extracted from a ProfileAnalysisResponse
+#[derive(Debug, Default)]
+struct ArchiveSummary {
+    query_user: Option<String>,
+    default_db: Option<String>,
+    sql_statement: Option<String>,
+    query_state: Option<String>,
+    query_start_time: Option<String>,
+    total_time_ms: Option<f64>,
+    peak_memory_bytes: Option<i64>,
+    performance_score: Option<f64>,
+    rule_ids: Vec<String>,
+    diagnostic_count: i64,
+    conclusion: Option<String>,
+    analyzed: bool,
+}
+
+impl ArchiveSummary {
+    fn from_analysis(analysis: &ProfileAnalysisResponse) -> Self {
+        let summary = analysis.summary.as_ref();
+        let non_empty = |s: &String| if s.is_empty() { None } else { Some(s.clone()) };
+
+        Self {
+            query_user: summary.and_then(|s| s.user.clone()),
+            default_db: summary.and_then(|s| s.default_db.clone()),
+            sql_statement: summary.and_then(|s| non_empty(&s.sql_statement)),
+            query_state: summary.and_then(|s| non_empty(&s.query_state)),
+            query_start_time: summary.and_then(|s| non_empty(&s.start_time)),
+            total_time_ms: summary.and_then(|s| {
+                s.total_time_ms
+                    .or_else(|| ValueParser::parse_time_to_ms(&s.total_time).ok())
+            }),
+            peak_memory_bytes: summary.and_then(|s| s.query_peak_memory).map(|b| b as i64),
+            performance_score: Some(analysis.performance_score),
+            // aggregated_diagnostics is already ordered by severity, then affected node count
+            rule_ids: analysis
+                .aggregated_diagnostics
+                .iter()
+                .map(|d| d.rule_id.clone())
+                .collect(),
+            diagnostic_count: analysis.diagnostics.len() as i64,
+            conclusion: non_empty(&analysis.conclusion),
+            analyzed: true,
+        }
+    }
+
+    /// Analyze profile text; unparseable profiles are still archived without a summary
+    fn from_profile(query_id: &str, profile_text: &str) -> Self {
+        match analyze_profile_with_context(profile_text, &AnalysisContext::default()) {
+            Ok(analysis) => Self::from_analysis(&analysis),
+            Err(e) => {
+                tracing::warn!("Archiving profile {} without analysis summary: {}", query_id, e);
+                Self::default()
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ProfileArchiveService {
+    db: SqlitePool,
+    cluster_service: Arc<ClusterService>,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+    config: ProfileArchiveConfig,
+}
+
+impl ProfileArchiveService {
+    /// Create a new ProfileArchiveService
+    pub fn new(
+        db: SqlitePool,
+        cluster_service: Arc<ClusterService>,
+        mysql_pool_manager: Arc<MySQLPoolManager>,
+        config: ProfileArchiveConfig,
+    ) -> Self {
+        Self { db, cluster_service, mysql_pool_manager, config }
+    }
+
+    /// Fetch a profile from the cluster and archive it
+    pub async fn archive_from_cluster(
+        &self,
+        cluster: &Cluster,
+        query_id: &str,
+        archived_by: Option<i64>,
+    ) -> ApiResult<ProfileArchiveItem> {
+        let safe_query_id = sanitize_query_id(query_id)?;
+        let profile_text = self.fetch_profile(cluster, &safe_query_id).await?;
+        self.archive_profile(
+            cluster.id,
+            &safe_query_id,
+            &profile_text,
+            ArchiveSource::Manual,
+            archived_by,
+        )
+        .await
+    }
+
+    /// Fetch raw profile text via get_query_profile()
+    pub async fn fetch_profile(&self, cluster: &Cluster, safe_query_id: &str) -> ApiResult<String> {
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let mysql_client = MySQLClient::from_pool(pool);
+
+        let sql = format!("SELECT get_query_profile('{}')", safe_query_id);
+        let (_, rows) = mysql_client.query_raw(&sql).await?;
+
+        let profile_content = rows
+            .first()
+            .and_then(|row| row.first())
+            .cloned()
+            .unwrap_or_default();
+
+        if profile_content.trim().is_empty() {
+            return Err(ApiError::not_found(format!(
+                "Profile not found for query: {}",
+                safe_query_id
+            )));
+        }
+        Ok(profile_content)
+    }
+
+    /// Store a profile with its analysis summary (re-archiving replaces the previous copy)
+    pub async fn archive_profile(
+        &self,
+        cluster_id: i64,
+        query_id: &str,
+        profile_text: &str,
+        source: ArchiveSource,
+        archived_by: Option<i64>,
+    ) -> ApiResult<ProfileArchiveItem> {
+        let summary = ArchiveSummary::from_profile(query_id, profile_text);
+        let (content, compression) = compress_profile(profile_text, self.config.compress)?;
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT INTO profile_archives (cluster_id, query_id, query_user, default_db,
+             sql_statement, query_state, query_start_time, total_time_ms, peak_memory_bytes,
+             performance_score, rule_ids, diagnostic_count, conclusion, profile_content,
+             compression, raw_size, source, archived_by, archived_at, analyzed_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(cluster_id, query_id) DO UPDATE SET
+                query_user = excluded.query_user,
+                default_db = excluded.default_db,
+                sql_statement = excluded.sql_statement,
+                query_state = excluded.query_state,
+                query_start_time = excluded.query_start_time,
+                total_time_ms = excluded.total_time_ms,
+                peak_memory_bytes = excluded.peak_memory_bytes,
+                performance_score = excluded.performance_score,
+                rule_ids = excluded.rule_ids,
+                diagnostic_count = excluded.diagnostic_count,
+                conclusion = excluded.conclusion,
+                profile_content = excluded.profile_content,
+                compression = excluded.compression,
+                raw_size = excluded.raw_size,
+                source = excluded.source,
+                archived_by = excluded.archived_by,
+                archived_at = excluded.archived_at,
+                analyzed_at = excluded.analyzed_at",
+        )
+        .bind(cluster_id)
+        .bind(query_id)
+        .bind(&summary.query_user)
+        .bind(&summary.default_db)
+        .bind(&summary.sql_statement)
+        .bind(&summary.query_state)
+        .bind(&summary.query_start_time)
+        .bind(summary.total_time_ms)
+        .bind(summary.peak_memory_bytes)
+        .bind(summary.performance_score)
+        .bind(serde_json::to_string(&summary.rule_ids)?)
+        .bind(summary.diagnostic_count)
+        .bind(&summary.conclusion)
+        .bind(content)
+        .bind(compression)
+        .bind(profile_text.len() as i64)
+        .bind(source.as_str())
+        .bind(archived_by)
+        .bind(now)
+        .bind(if summary.analyzed { Some(now) } else { None })
+        .execute(&self.db)
+        .await?;
+
+        tracing::info!(
+            "Archived profile {} for cluster {} ({} bytes, {})",
+            query_id,
+            cluster_id,
+            profile_text.len(),
+            compression
+        );
+
+        let row: ArchiveRow = sqlx::query_as(&format!(
+            "SELECT {} FROM profile_archives WHERE cluster_id = ? AND query_id = ?",
+            SUMMARY_COLUMNS
+        ))
+        .bind(cluster_id)
+        .bind(query_id)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.into())
+    }
+
+    /// List archived profiles of a cluster with filters and pagination
+    pub async fn list_archives(
+        &self,
+        cluster_id: i64,
+        query: &ProfileArchiveQuery,
+    ) -> ApiResult<ProfileArchiveListResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(20).clamp(1, 200);
+
+        let mut conditions = vec!["cluster_id = ?"];
+        let mut params: Vec<String> = vec![cluster_id.to_string()];
+
+        if let Some(user) = query.user.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("query_user = ?");
+            params.push(user.to_string());
+        }
+        if let Some(db) = query.db.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("default_db = ?");
+            params.push(db.to_string());
+        }
+        if let Some(min) = query.min_duration_ms {
+            conditions.push("total_time_ms >= CAST(? AS REAL)");
+            params.push(min.to_string());
+        }
+        if let Some(max) = query.max_duration_ms {
+            conditions.push("total_time_ms <= CAST(? AS REAL)");
+            params.push(max.to_string());
+        }
+        if let Some(rule_id) = query.rule_id.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("EXISTS (SELECT 1 FROM json_each(rule_ids) WHERE json_each.value = ?)");
+            params.push(rule_id.to_string());
+        }
+        if let Some(start) = query.start_time.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("query_start_time >= ?");
+            params.push(start.to_string());
+        }
+        if let Some(end) = query.end_time.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("query_start_time <= ?");
+            params.push(end.to_string());
+        }
+        if let Some(keyword) = query.keyword.as_deref().filter(|s| !s.is_empty()) {
+            conditions.push("(query_id LIKE ? OR sql_statement LIKE ?)");
+            let pattern = format!("%{}%", keyword);
+            params.push(pattern.clone());
+            params.push(pattern);
+        }
+
+        let where_clause = conditions.join(" AND ");
+
+        let count_sql = format!("SELECT COUNT(*) FROM profile_archives WHERE {}", where_clause);
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let (total,) = count_query.fetch_one(&self.db).await?;
+
+        let list_sql = format!(
+            "SELECT {} FROM profile_archives WHERE {}
+             ORDER BY COALESCE(query_start_time, archived_at) DESC, id DESC
+             LIMIT ? OFFSET ?",
+            SUMMARY_COLUMNS, where_clause
+        );
+        let mut list_query = sqlx::query_as::<_, ArchiveRow>(&list_sql);
+        for param in &params {
+            list_query = list_query.bind(param);
+        }
+        let rows = list_query
+            .bind(page_size)
+            .bind((page - 1) * page_size)
+            .fetch_all(&self.db)
+            .await?;
+
+        Ok(ProfileArchiveListResponse {
+            items: rows.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// Get an archived profile including its raw text
+    pub async fn get_archive(&self, cluster_id: i64, id: i64) -> ApiResult<ProfileArchiveDetail> {
+        let row: Option<ArchiveRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM profile_archives WHERE id = ? AND cluster_id = ?",
+            SUMMARY_COLUMNS
+        ))
+        .bind(id)
+        .bind(cluster_id)
+        .fetch_optional(&self.db)
+        .await?;
+        let row =
+            row.ok_or_else(|| ApiError::not_found(format!("Archived profile {} not found", id)))?;
+
+        let (content,): (Vec<u8>,) =
+            sqlx::query_as("SELECT profile_content FROM profile_archives WHERE id = ?")
+                .bind(id)
+                .fetch_one(&self.db)
+                .await?;
+        let profile_content = decompress_profile(&content, &row.compression)?;
+
+        Ok(ProfileArchiveDetail { summary: row.into(), profile_content })
+    }
+
+    /// Re-run analysis of an archived profile with the current RuleEngine
+    ///
+    /// The stored summary (score, rule ids, conclusion) is refreshed with the new result.
+    pub async fn reanalyze(&self, cluster_id: i64, id: i64) -> ApiResult<ProfileAnalysisResponse> {
+        let detail = self.get_archive(cluster_id, id).await?;
+
+        let analysis =
+            analyze_profile_with_context(&detail.profile_content, &AnalysisContext::default())
+                .map_err(|e| ApiError::internal_error(format!("Analysis failed: {}", e)))?;
+
+        let summary = ArchiveSummary::from_analysis(&analysis);
+        sqlx::query(
+            "UPDATE profile_archives SET performance_score = ?, rule_ids = ?,
+             diagnostic_count = ?, conclusion = ?, analyzed_at = ? WHERE id = ?",
+        )
+        .bind(summary.performance_score)
+        .bind(serde_json::to_string(&summary.rule_ids)?)
+        .bind(summary.diagnostic_count)
+        .bind(&summary.conclusion)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+
+        Ok(analysis)
+    }
+
+    /// Delete an archived profile
+    pub async fn delete_archive(&self, cluster_id: i64, id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM profile_archives WHERE id = ? AND cluster_id = ?")
+            .bind(id)
+            .bind(cluster_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Archived profile {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Delete archived profiles older than the retention period
+    pub async fn cleanup_expired(&self) -> Result<u64, sqlx::Error> {
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.retention_days);
+
+        let result = sqlx::query("DELETE FROM profile_archives WHERE archived_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+
+        let deleted = result.rows_affected();
+        if deleted > 0 {
+            tracing::info!(
+                "Cleaned up {} archived profiles older than {} days",
+                deleted,
+                self.config.retention_days
+            );
+        }
+        Ok(deleted)
+    }
+
+    /// One archive cycle: auto-archive new profiles from every cluster, then apply retention
+    pub async fn archive_once(&self) -> Result<(), anyhow::Error> {
+        if self.config.auto_archive {
+            // Retention still applies when the clusters cannot be listed
+            let clusters = self
+                .cluster_service
+                .list_clusters()
+                .await
+                .inspect_err(|e| tracing::warn!("Failed to list clusters to archive: {}", e))
+                .unwrap_or_default();
+            for cluster in clusters {
+                if let Err(e) = self.archive_cluster_profiles(&cluster).await {
+                    tracing::warn!(
+                        "Failed to archive profiles for cluster {} ({}): {}",
+                        cluster.id,
+                        cluster.name,
+                        e
+                    );
+                }
+            }
+        }
+
+        self.cleanup_expired().await?;
+        Ok(())
+    }
+
+    /// Archive finished, not-yet-archived profiles above the duration threshold
+    async fn archive_cluster_profiles(&self, cluster: &Cluster) -> ApiResult<usize> {
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        let mysql_client = MySQLClient::from_pool(pool);
+
+        // SHOW PROFILELIST returns: QueryId, StartTime, Time, State, Statement
+        let (_, rows) = mysql_client.query_raw("SHOW PROFILELIST").await?;
+
+        let mut archived = 0;
+        for row in rows {
+            if archived >= MAX_AUTO_ARCHIVE_PER_CYCLE {
+                break;
+            }
+
+            let state = row.get(3).map(|s| s.as_str()).unwrap_or("");
+            if !state.eq_ignore_ascii_case("finished") {
+                continue;
+            }
+
+            let duration_ms = row
+                .get(2)
+                .and_then(|t| ValueParser::parse_time_to_ms(t).ok())
+                .unwrap_or(0.0);
+            if duration_ms < self.config.min_duration_ms as f64 {
+                continue;
+            }
+
+            let Some(Ok(query_id)) = row.first().map(|id| sanitize_query_id(id)) else {
+                continue;
+            };
+
+            let exists: Option<(i64,)> = sqlx::query_as(
+                "SELECT id FROM profile_archives WHERE cluster_id = ? AND query_id = ?",
+            )
+            .bind(cluster.id)
+            .bind(&query_id)
+            .fetch_optional(&self.db)
+            .await?;
+            if exists.is_some() {
+                continue;
+            }
+
+            let profile_text = match self.fetch_profile(cluster, &query_id).await {
+                Ok(profile_text) => profile_text,
+                Err(e) => {
+                    tracing::debug!("Skip archiving profile {}: {}", query_id, e);
+                    continue;
+                },
+            };
+            // A profile that fails to parse or store does not stop the rest of the cluster
+            match self
+                .archive_profile(cluster.id, &query_id, &profile_text, ArchiveSource::Auto, None)
+                .await
+            {
+                Ok(_) => archived += 1,
+                Err(e) => tracing::warn!(
+                    "Failed to archive profile {} of cluster {}: {}",
+                    query_id,
+                    cluster.id,
+                    e
+                ),
+            }
+        }
+
+        if archived > 0 {
+            tracing::info!("Auto-archived {} profiles for cluster {}", archived, cluster.id);
+        }
+        Ok(archived)
+    }
+}
+
+impl ScheduledTask for ProfileArchiveService {
+    fn run(&self) -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + '_>> {
+        Box::pin(async move { self.archive_once().await })
+    }
+}
+
+/// Compress profile text for storage; returns the bytes and the compression tag
+fn compress_profile(text: &str, compress: bool) -> ApiResult<(Vec<u8>, &'static str)> {
+    if !compress {
+        return Ok((text.as_bytes().to_vec(), "none"));
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(text.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map(|bytes| (bytes, "gzip"))
+        .map_err(|e| ApiError::internal_error(format!("Failed to compress profile: {}", e)))
+}
+
+/// Restore profile text stored by `compress_profile`
+fn decompress_profile(data: &[u8], compression: &str) -> ApiResult<String> {
+    match compression {
+        "gzip" => {
+            let mut text = String::new();
+            GzDecoder::new(data)
+                .read_to_string(&mut text)
+                .map_err(|e| {
+                    ApiError::internal_error(format!("Failed to decompress profile: {}", e))
+                })?;
+            Ok(text)
+        },
+        "none" => String::from_utf8(data.to_vec())
+            .map_err(|e| ApiError::internal_error(format!("Invalid profile encoding: {}", e))),
+        other => Err(ApiError::internal_error(format!("Unknown profile compression: {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let text = "Query:\n  Summary:\n     - Query ID: abc\n".repeat(100);
+
+        let (gz, tag) = compress_profile(&text, true).unwrap();
+        assert_eq!(tag, "gzip");
+        assert!(gz.len() < text.len());
+        assert_eq!(decompress_profile(&gz, tag).unwrap(), text);
+
+        let (raw, tag) = compress_profile(&text, false).unwrap();
+        assert_eq!(tag, "none");
+        assert_eq!(decompress_profile(&raw, tag).unwrap(), text);
+
+        assert!(decompress_profile(&raw, "zstd").is_err());
+    }
+
+    #[test]
+    fn test_sanitize_query_id() {
+        assert_eq!(sanitize_query_id(" abc-123_x ").unwrap(), "abc-123_x");
+        assert!(sanitize_query_id("").is_err());
+        assert!(sanitize_query_id("a'; DROP TABLE x; --").is_err());
+        assert!(sanitize_query_id(&"a".repeat(65)).is_err());
+    }
+}
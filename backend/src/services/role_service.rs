@@ -1,5 +1,7 @@
+use crate::middleware::permission_extractor;
 use crate::models::{
-    CreateRoleRequest, PermissionResponse, Role, RoleResponse, RoleWithPermissions,
+    CreateRoleRequest, PermissionResponse, PermissionScope, PermissionScopeLevel, Role,
+    RoleResponse, RoleWithPermissions, ScopedPermissionGrant, ScopedPermissionResponse,
     UpdateRolePermissionsRequest, UpdateRoleRequest,
 };
 use crate::services::{casbin_service::CasbinService, permission_service::PermissionService};
@@ -9,6 +11,9 @@ use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Catalog of a database granted without one
+const DEFAULT_CATALOG: &str = "default_catalog";
+
 #[derive(Clone)]
 pub struct RoleService {
     pool: SqlitePool,
@@ -84,7 +89,22 @@ impl RoleService {
         .map(|p: crate::models::Permission| p.into())
         .collect();
 
-        Ok(RoleWithPermissions { role, permissions })
+        let scoped_permissions: Vec<ScopedPermissionResponse> = sqlx::query_as(
+            r#"
+            SELECT rps.permission_id, p.code, p.name, rps.cluster_id, c.name AS cluster_name,
+                   rps.catalog_name AS catalog, rps.database_name AS database
+            FROM role_permission_scopes rps
+            JOIN permissions p ON p.id = rps.permission_id
+            JOIN clusters c ON c.id = rps.cluster_id
+            WHERE rps.role_id = ?
+            ORDER BY p.code, c.name, rps.catalog_name, rps.database_name
+            "#,
+        )
+        .bind(role_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(RoleWithPermissions { role, permissions, scoped_permissions })
     }
 
     /// Create a new role (organization-scoped)
@@ -296,7 +316,7 @@ impl RoleService {
         is_super_admin: bool,
     ) -> ApiResult<()> {
         // Check if role exists and within org scope
-        let _role = self
+        let role = self
             .get_role(role_id, requestor_org, is_super_admin)
             .await?;
 
@@ -313,6 +333,14 @@ impl RoleService {
             perm_map.insert(perm.id, perm);
         }
 
+        let scoped_grants = match &req.scoped_permissions {
+            Some(grants) => Some(
+                self.validate_scoped_grants(&role, grants, &perm_map)
+                    .await?,
+            ),
+            None => None,
+        };
+
         // Build menu->API mapping (for child API permissions)
         let mut menu_to_apis: HashMap<i64, Vec<i64>> = HashMap::new();
         for api_perm in all_permissions.iter().filter(|p| p.r#type == "api") {
@@ -347,9 +375,19 @@ impl RoleService {
             }
         }
 
-        // Step 2: Add all parent menu permissions recursively
+        // Step 2: Add all parent menu permissions recursively (menus of scoped grants too,
+        // menus are not cluster-specific)
         let mut parent_count = 0;
-        for permission_id in req.permission_ids.clone() {
+        let scoped_permission_ids = scoped_grants
+            .iter()
+            .flatten()
+            .map(|(permission_id, _)| *permission_id);
+        for permission_id in req
+            .permission_ids
+            .clone()
+            .into_iter()
+            .chain(scoped_permission_ids)
+        {
             let mut current_id = permission_id;
 
             // Walk up the parent chain
@@ -418,6 +456,27 @@ impl RoleService {
                 .await?;
         }
 
+        if let Some(grants) = &scoped_grants {
+            sqlx::query("DELETE FROM role_permission_scopes WHERE role_id = ?")
+                .bind(role_id)
+                .execute(&mut *tx)
+                .await?;
+            for (permission_id, scope) in grants {
+                sqlx::query(
+                    "INSERT INTO role_permission_scopes \
+                     (role_id, permission_id, cluster_id, catalog_name, database_name) \
+                     VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind(role_id)
+                .bind(permission_id)
+                .bind(scope.cluster_id)
+                .bind(&scope.catalog)
+                .bind(&scope.database)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
         tx.commit().await?;
 
         // Reload Casbin policies
@@ -435,6 +494,80 @@ impl RoleService {
         Ok(())
     }
 
+    /// Check scoped grants of a role: scopable API permissions, on clusters of the role's
+    /// organization, catalogs and databases only for database-level permissions
+    async fn validate_scoped_grants(
+        &self,
+        role: &RoleResponse,
+        grants: &[ScopedPermissionGrant],
+        perm_map: &HashMap<i64, &crate::models::Permission>,
+    ) -> ApiResult<Vec<(i64, PermissionScope)>> {
+        let mut validated: Vec<(i64, PermissionScope)> = Vec::new();
+        for grant in grants {
+            let permission = perm_map
+                .get(&grant.permission_id)
+                .filter(|p| p.r#type == "api")
+                .ok_or_else(|| {
+                    ApiError::validation_error(format!(
+                        "Permission {} is not an API permission",
+                        grant.permission_id
+                    ))
+                })?;
+            let level = CasbinService::permission_policy(
+                &permission.code,
+                permission.action.as_deref().unwrap_or(""),
+            )
+            .and_then(|(resource, action)| permission_extractor::scope_level(&resource, &action))
+            .ok_or_else(|| {
+                ApiError::validation_error(format!(
+                    "Permission {} cannot be granted per cluster",
+                    permission.code
+                ))
+            })?;
+
+            let catalog = scope_name(grant.catalog.as_deref(), "catalog")?;
+            let database = scope_name(grant.database.as_deref(), "database")?;
+            if (catalog.is_some() || database.is_some()) && level != PermissionScopeLevel::Database
+            {
+                return Err(ApiError::validation_error(format!(
+                    "Permission {} cannot be granted per catalog or database",
+                    permission.code
+                )));
+            }
+            let catalog = match (catalog, &database) {
+                (None, Some(_)) => Some(DEFAULT_CATALOG.to_string()),
+                (catalog, _) => catalog,
+            };
+
+            let cluster_org: Option<Option<i64>> =
+                sqlx::query_scalar("SELECT organization_id FROM clusters WHERE id = ?")
+                    .bind(grant.cluster_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            match cluster_org {
+                None => {
+                    return Err(ApiError::validation_error(format!(
+                        "Cluster {} not found",
+                        grant.cluster_id
+                    )));
+                },
+                Some(org) if role.organization_id.is_some() && org != role.organization_id => {
+                    return Err(ApiError::validation_error(format!(
+                        "Cluster {} does not belong to the role's organization",
+                        grant.cluster_id
+                    )));
+                },
+                Some(_) => {},
+            }
+
+            let scope = PermissionScope { cluster_id: grant.cluster_id, catalog, database };
+            if !validated.contains(&(grant.permission_id, scope.clone())) {
+                validated.push((grant.permission_id, scope));
+            }
+        }
+        Ok(validated)
+    }
+
     /// Get role permissions (organization-scoped)
     pub async fn get_role_permissions(
         &self,
@@ -463,3 +596,15 @@ impl RoleService {
         Ok(permissions.into_iter().map(|p| p.into()).collect())
     }
 }
+
+/// Trimmed catalog or database name of a scoped grant; `/` and `*` would break the Casbin
+/// scope pattern and are not valid in StarRocks names anyway
+fn scope_name(name: Option<&str>, what: &str) -> ApiResult<Option<String>> {
+    let Some(name) = name.map(str::trim) else {
+        return Ok(None);
+    };
+    if name.is_empty() || name.contains(['/', '*']) {
+        return Err(ApiError::validation_error(format!("Invalid {} name '{}'", what, name)));
+    }
+    Ok(Some(name.to_string()))
+}
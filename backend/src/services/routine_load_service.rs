@@ -0,0 +1,522 @@
+// Routine Load Service
+// Purpose: Routine Load jobs of a cluster (SHOW ROUTINE LOAD / SHOW ROUTINE LOAD TASK) with
+//          per-partition offsets and lag, pause/resume/stop, and the lag history recorded by
+//          the metrics collector for trends and alert rules
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::models::{
+    Cluster, RoutineLoadAction, RoutineLoadFilter, RoutineLoadJob, RoutineLoadPartition,
+    RoutineLoadTask,
+};
+use crate::services::load_job_service::{escape_string, number, text};
+use crate::services::overview_service::{TimeRange, TimeSeriesPoint};
+use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::utils::{ApiError, ApiResult};
+
+/// Databases without user Routine Load jobs
+const SYSTEM_DATABASES: &[&str] = &["information_schema", "_statistics_", "sys"];
+/// States a job cannot leave any more
+const FINISHED_STATES: &[&str] = &["STOPPED", "CANCELLED"];
+
+/// Routine Load jobs of a cluster at one collection, for alert rules
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RoutineLoadSummary {
+    /// Jobs in the PAUSED state
+    pub paused: i32,
+    /// Largest total lag of a job
+    pub max_lag: i64,
+}
+
+impl RoutineLoadSummary {
+    pub fn of(jobs: &[RoutineLoadJob]) -> Self {
+        Self {
+            paused: jobs.iter().filter(|job| job.state == "PAUSED").count() as i32,
+            max_lag: jobs
+                .iter()
+                .filter_map(|job| job.total_lag)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Lag history of a Routine Load job
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoutineLoadLagTrends {
+    pub database_name: String,
+    pub name: String,
+    /// Messages not consumed yet over all partitions
+    pub total_lag: Vec<TimeSeriesPoint>,
+    /// Rows rejected since the job was created
+    pub error_rows: Vec<TimeSeriesPoint>,
+    /// Rows loaded since the job was created
+    pub loaded_rows: Vec<TimeSeriesPoint>,
+    pub partitions: Vec<RoutineLoadPartitionLag>,
+    /// First state recorded in the range, then every change
+    pub state_changes: Vec<RoutineLoadStateChange>,
+}
+
+/// Lag history of one source partition
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoutineLoadPartitionLag {
+    pub partition: String,
+    pub lag: Vec<TimeSeriesPoint>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoutineLoadStateChange {
+    pub timestamp: DateTime<Utc>,
+    pub state: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct LagHistoryRow {
+    collected_at: DateTime<Utc>,
+    state: String,
+    total_lag: Option<i64>,
+    partition_lag: Option<String>,
+    loaded_rows: i64,
+    error_rows: i64,
+}
+
+#[derive(Clone)]
+pub struct RoutineLoadService {
+    db: SqlitePool,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+}
+
+impl RoutineLoadService {
+    pub fn new(db: SqlitePool, mysql_pool_manager: Arc<MySQLPoolManager>) -> Self {
+        Self { db, mysql_pool_manager }
+    }
+
+    async fn client(&self, cluster: &Cluster) -> ApiResult<MySQLClient> {
+        let pool = self.mysql_pool_manager.get_pool(cluster).await?;
+        Ok(MySQLClient::from_pool(pool))
+    }
+
+    // ========================================
+    // Jobs
+    // ========================================
+
+    /// Routine Load jobs of every database (or the filtered one). SHOW ROUTINE LOAD only
+    /// covers one database, so the databases are listed first; a database that fails to
+    /// answer is skipped.
+    pub async fn list_jobs(
+        &self,
+        cluster: &Cluster,
+        filter: &RoutineLoadFilter,
+    ) -> ApiResult<Vec<RoutineLoadJob>> {
+        let client = self.client(cluster).await?;
+        let databases = match filter.database.as_deref() {
+            Some(database) => vec![database.to_string()],
+            None => Self::list_databases(&client).await?,
+        };
+        let state = filter.state.as_deref().map(str::to_uppercase);
+
+        let mut jobs = Vec::new();
+        for database in &databases {
+            let sql = format!(
+                "SHOW {}ROUTINE LOAD FROM {}",
+                if filter.include_finished { "ALL " } else { "" },
+                quote_identifier(database)
+            );
+            let rows = match client.query(&sql).await {
+                Ok(rows) => rows,
+                Err(e) if filter.database.is_none() => {
+                    tracing::warn!("Failed to list Routine Load jobs of {}: {}", database, e);
+                    continue;
+                },
+                Err(e) => return Err(e),
+            };
+            jobs.extend(
+                rows.iter()
+                    .map(|row| Self::parse_job(row, database))
+                    .filter(|job| state.as_deref().is_none_or(|s| job.state == s)),
+            );
+        }
+        jobs.sort_by(|a, b| {
+            (&a.database_name, &a.name, a.id).cmp(&(&b.database_name, &b.name, b.id))
+        });
+
+        Ok(jobs)
+    }
+
+    /// A job by name; a name reused after a stop resolves to the job still running, else
+    /// to the latest one
+    pub async fn get_job(
+        &self,
+        cluster: &Cluster,
+        database: &str,
+        name: &str,
+    ) -> ApiResult<RoutineLoadJob> {
+        let client = self.client(cluster).await?;
+        let sql = format!(
+            "SHOW ALL ROUTINE LOAD FOR {}.{}",
+            quote_identifier(database),
+            quote_identifier(name)
+        );
+        let rows = client.query(&sql).await?;
+
+        rows.iter()
+            .map(|row| Self::parse_job(row, database))
+            .filter(|job| job.name == name)
+            .max_by_key(|job| (!FINISHED_STATES.contains(&job.state.as_str()), job.id))
+            .ok_or_else(|| {
+                ApiError::not_found(format!("Routine Load job {}.{} not found", database, name))
+            })
+    }
+
+    /// Tasks of a job (SHOW ROUTINE LOAD TASK)
+    pub async fn list_tasks(
+        &self,
+        cluster: &Cluster,
+        database: &str,
+        name: &str,
+    ) -> ApiResult<Vec<RoutineLoadTask>> {
+        let client = self.client(cluster).await?;
+        let sql = format!(
+            "SHOW ROUTINE LOAD TASK FROM {} WHERE JobName = '{}'",
+            quote_identifier(database),
+            escape_string(name)
+        );
+        let rows = client.query(&sql).await?;
+        Ok(rows.iter().map(Self::parse_task).collect())
+    }
+
+    /// Pause, resume or stop a job; returns the job as it is after the action
+    pub async fn control_job(
+        &self,
+        cluster: &Cluster,
+        database: &str,
+        name: &str,
+        action: RoutineLoadAction,
+    ) -> ApiResult<RoutineLoadJob> {
+        let job = self.get_job(cluster, database, name).await?;
+        Self::check_action(&job, action)?;
+
+        let client = self.client(cluster).await?;
+        let sql = Self::build_control_statement(database, name, action);
+        tracing::info!("Routine Load {} on cluster {}: {}", action.as_sql(), cluster.id, sql);
+        client.execute(&sql).await?;
+
+        self.get_job(cluster, database, name).await
+    }
+
+    pub fn check_action(job: &RoutineLoadJob, action: RoutineLoadAction) -> ApiResult<()> {
+        let allowed = match action {
+            RoutineLoadAction::Pause => matches!(job.state.as_str(), "NEED_SCHEDULE" | "RUNNING"),
+            RoutineLoadAction::Resume => job.state == "PAUSED",
+            RoutineLoadAction::Stop => !FINISHED_STATES.contains(&job.state.as_str()),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(ApiError::validation_error(format!(
+                "Cannot {} Routine Load job {}.{} in state {}",
+                action.as_sql().to_lowercase(),
+                job.database_name,
+                job.name,
+                job.state
+            )))
+        }
+    }
+
+    pub fn build_control_statement(
+        database: &str,
+        name: &str,
+        action: RoutineLoadAction,
+    ) -> String {
+        format!(
+            "{} ROUTINE LOAD FOR {}.{}",
+            action.as_sql(),
+            quote_identifier(database),
+            quote_identifier(name)
+        )
+    }
+
+    async fn list_databases(client: &MySQLClient) -> ApiResult<Vec<String>> {
+        let rows = client.query("SHOW DATABASES").await?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| text(row, &["Database"]))
+            .filter(|db| !SYSTEM_DATABASES.contains(&db.as_str()))
+            .collect())
+    }
+
+    // ========================================
+    // Lag history
+    // ========================================
+
+    /// Record the lag of the cluster's active jobs (called by the metrics collector)
+    pub async fn record_lag(
+        &self,
+        cluster: &Cluster,
+        collected_at: DateTime<Utc>,
+    ) -> ApiResult<RoutineLoadSummary> {
+        let jobs = self
+            .list_jobs(cluster, &RoutineLoadFilter::default())
+            .await?;
+        self.save_lag(cluster.id, collected_at, &jobs).await?;
+        Ok(RoutineLoadSummary::of(&jobs))
+    }
+
+    pub async fn save_lag(
+        &self,
+        cluster_id: i64,
+        collected_at: DateTime<Utc>,
+        jobs: &[RoutineLoadJob],
+    ) -> ApiResult<()> {
+        let mut tx = self.db.begin().await?;
+        for job in jobs {
+            let partition_lag: BTreeMap<&str, i64> = job
+                .partitions
+                .iter()
+                .filter_map(|p| p.lag.map(|lag| (p.partition.as_str(), lag)))
+                .collect();
+            sqlx::query(
+                "INSERT INTO routine_load_lag_history (cluster_id, database_name, job_name, job_id,
+                 state, total_lag, partition_lag, loaded_rows, error_rows, collected_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(cluster_id)
+            .bind(&job.database_name)
+            .bind(&job.name)
+            .bind(job.id)
+            .bind(&job.state)
+            .bind(job.total_lag)
+            .bind(serde_json::to_string(&partition_lag)?)
+            .bind(job.loaded_rows)
+            .bind(job.error_rows)
+            .bind(collected_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Lag, loaded and rejected rows, and state changes of a job over a time range
+    pub async fn get_lag_trends(
+        &self,
+        cluster_id: i64,
+        database: &str,
+        name: &str,
+        time_range: &TimeRange,
+    ) -> ApiResult<RoutineLoadLagTrends> {
+        let rows: Vec<LagHistoryRow> = sqlx::query_as(
+            "SELECT collected_at, state, total_lag, partition_lag, loaded_rows, error_rows
+             FROM routine_load_lag_history
+             WHERE cluster_id = ? AND database_name = ? AND job_name = ? AND collected_at >= ?
+             ORDER BY collected_at",
+        )
+        .bind(cluster_id)
+        .bind(database)
+        .bind(name)
+        .bind(time_range.start_time())
+        .fetch_all(&self.db)
+        .await?;
+
+        let point = |timestamp: DateTime<Utc>, value: i64| TimeSeriesPoint {
+            timestamp,
+            value: value as f64,
+        };
+        let mut partitions: BTreeMap<String, Vec<TimeSeriesPoint>> = BTreeMap::new();
+        let mut state_changes: Vec<RoutineLoadStateChange> = Vec::new();
+        for row in &rows {
+            let lags: BTreeMap<String, i64> = row
+                .partition_lag
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default();
+            for (partition, lag) in lags {
+                partitions
+                    .entry(partition)
+                    .or_default()
+                    .push(point(row.collected_at, lag));
+            }
+            if state_changes
+                .last()
+                .is_none_or(|last| last.state != row.state)
+            {
+                state_changes.push(RoutineLoadStateChange {
+                    timestamp: row.collected_at,
+                    state: row.state.clone(),
+                });
+            }
+        }
+
+        Ok(RoutineLoadLagTrends {
+            database_name: database.to_string(),
+            name: name.to_string(),
+            total_lag: rows
+                .iter()
+                .filter_map(|r| r.total_lag.map(|lag| point(r.collected_at, lag)))
+                .collect(),
+            error_rows: rows
+                .iter()
+                .map(|r| point(r.collected_at, r.error_rows))
+                .collect(),
+            loaded_rows: rows
+                .iter()
+                .map(|r| point(r.collected_at, r.loaded_rows))
+                .collect(),
+            partitions: partitions
+                .into_iter()
+                .map(|(partition, lag)| RoutineLoadPartitionLag { partition, lag })
+                .collect(),
+            state_changes,
+        })
+    }
+
+    /// Delete lag history collected before `cutoff`; returns the number of deleted rows
+    pub async fn cleanup_before(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM routine_load_lag_history WHERE collected_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ========================================
+    // Parsing
+    // ========================================
+
+    /// A SHOW ROUTINE LOAD row; `database` is used when the row has no DbName
+    pub fn parse_job(row: &Value, database: &str) -> RoutineLoadJob {
+        let statistic: Value = text(row, &["Statistic"])
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or(Value::Null);
+        let stat = |key: &str| statistic.get(key).and_then(json_i64).unwrap_or(0);
+
+        let partitions = parse_partitions(
+            text(row, &["Progress"]).as_deref(),
+            text(row, &["LatestSourcePosition"]).as_deref(),
+            text(row, &["OffsetLag"]).as_deref(),
+        );
+        let lags: Vec<i64> = partitions.iter().filter_map(|p| p.lag).collect();
+        let total_lag = if lags.is_empty() { None } else { Some(lags.iter().sum()) };
+
+        RoutineLoadJob {
+            id: number(row, &["Id"]),
+            name: text(row, &["Name"]).unwrap_or_default(),
+            database_name: text(row, &["DbName"])
+                .map(|db| {
+                    db.strip_prefix("default_cluster:")
+                        .unwrap_or(&db)
+                        .to_string()
+                })
+                .unwrap_or_else(|| database.to_string()),
+            table_name: text(row, &["TableName"]).unwrap_or_default(),
+            state: text(row, &["State"])
+                .map(|s| s.to_uppercase())
+                .unwrap_or_else(|| "UNKNOWN".to_string()),
+            data_source_type: text(row, &["DataSourceType"]).unwrap_or_default(),
+            create_time: text(row, &["CreateTime"]),
+            pause_time: text(row, &["PauseTime"]),
+            end_time: text(row, &["EndTime"]),
+            current_task_num: number(row, &["CurrentTaskNum"]),
+            loaded_rows: stat("loadedRows"),
+            error_rows: stat("errorRows"),
+            unselected_rows: stat("unselectedRows"),
+            total_rows: stat("totalRows"),
+            received_bytes: stat("receivedBytes"),
+            total_lag,
+            partitions,
+            reason_of_state_changed: text(row, &["ReasonOfStateChanged"]),
+            error_log_urls: text(row, &["ErrorLogUrls"]),
+            tracking_sql: text(row, &["TrackingSQL"]),
+            other_msg: text(row, &["OtherMsg"]),
+            job_properties: text(row, &["JobProperties"]),
+            data_source_properties: text(row, &["DataSourceProperties"]),
+        }
+    }
+
+    /// A SHOW ROUTINE LOAD TASK row
+    pub fn parse_task(row: &Value) -> RoutineLoadTask {
+        RoutineLoadTask {
+            task_id: text(row, &["TaskId"]).unwrap_or_default(),
+            txn_id: text(row, &["TxnId"]),
+            txn_status: text(row, &["TxnStatus"]),
+            job_id: text(row, &["JobId"]).unwrap_or_default(),
+            create_time: text(row, &["CreateTime"]),
+            execute_start_time: text(row, &["ExecuteStartTime"]),
+            timeout: text(row, &["Timeout"]),
+            be_id: text(row, &["BeId"]),
+            data_source_properties: text(row, &["DataSourceProperties"]),
+            message: text(row, &["Message"]),
+        }
+    }
+}
+
+/// Offsets of each partition from the Progress (consumed), LatestSourcePosition and
+/// OffsetLag (newer versions) JSON objects. The lag is OffsetLag when reported, else the
+/// latest offset minus the consumed one; it is unknown before the first message is consumed.
+pub fn parse_partitions(
+    progress: Option<&str>,
+    latest: Option<&str>,
+    offset_lag: Option<&str>,
+) -> Vec<RoutineLoadPartition> {
+    let parse = |json: Option<&str>| -> BTreeMap<String, String> {
+        json.and_then(|json| serde_json::from_str::<serde_json::Map<String, Value>>(json).ok())
+            .map(|map| {
+                map.into_iter()
+                    .map(|(partition, offset)| {
+                        let offset = match offset {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        (partition, offset)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let consumed = parse(progress);
+    let latest = parse(latest);
+    let reported_lag = parse(offset_lag);
+
+    let partitions: BTreeSet<&String> = consumed.keys().chain(latest.keys()).collect();
+    let mut partitions: Vec<RoutineLoadPartition> = partitions
+        .into_iter()
+        .map(|partition| {
+            let consumed_offset = consumed.get(partition).cloned();
+            let latest_offset = latest.get(partition).cloned();
+            let offset = |value: &Option<String>| value.as_deref()?.trim().parse::<i64>().ok();
+            let lag = reported_lag
+                .get(partition)
+                .and_then(|lag| lag.trim().parse::<i64>().ok())
+                .or_else(|| Some(offset(&latest_offset)? - offset(&consumed_offset)?))
+                .map(|lag| lag.max(0));
+            RoutineLoadPartition {
+                partition: partition.clone(),
+                consumed_offset,
+                latest_offset,
+                lag,
+            }
+        })
+        .collect();
+    // Kafka partitions are numbered
+    partitions
+        .sort_by_key(|p| (p.partition.parse::<i64>().unwrap_or(i64::MAX), p.partition.clone()));
+    partitions
+}
+
+fn json_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
@@ -0,0 +1,310 @@
+// Session Service
+// Purpose: Server-side login sessions. Access tokens are short-lived JWTs bound to a session;
+//          refresh tokens rotate on every use (only their SHA-256 hash is stored) and presenting
+//          a rotated-out refresh token again revokes the session, as it was likely stolen.
+
+use crate::models::{TokenResponse, User, UserSession};
+use crate::utils::crypto::{random_hex, sha256_hex};
+use crate::utils::{ApiError, ApiResult, JwtUtil};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// Random bytes in a refresh token (hex-encoded)
+const REFRESH_TOKEN_BYTES: usize = 32;
+
+/// Lifetime of a refresh token when the configured one cannot be parsed
+const DEFAULT_REFRESH_EXPIRES_IN_MINUTES: i64 = 7 * 24 * 60;
+
+/// Longest user agent kept for a session
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Why a session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionRevokeReason {
+    Logout,
+    Revoked,
+    PasswordChanged,
+    UserDisabled,
+    RefreshTokenReused,
+}
+
+impl SessionRevokeReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Logout => "logout",
+            Self::Revoked => "revoked",
+            Self::PasswordChanged => "password_changed",
+            Self::UserDisabled => "user_disabled",
+            Self::RefreshTokenReused => "refresh_token_reused",
+        }
+    }
+}
+
+/// Client a session is opened from
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl SessionClient {
+    pub fn from_headers(headers: &axum::http::HeaderMap) -> Self {
+        Self {
+            user_agent: headers
+                .get(axum::http::header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|agent| agent.chars().take(MAX_USER_AGENT_LEN).collect()),
+            ip_address: crate::middleware::admin_audit::client_ip(headers),
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SessionRow {
+    id: i64,
+    user_id: i64,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: DateTime<Utc>,
+    last_refreshed_at: Option<DateTime<Utc>>,
+    expires_at: DateTime<Utc>,
+}
+
+impl SessionRow {
+    fn into_session(self, current_session_id: Option<i64>) -> UserSession {
+        UserSession {
+            current: current_session_id == Some(self.id),
+            id: self.id,
+            user_id: self.user_id,
+            user_agent: self.user_agent,
+            ip_address: self.ip_address,
+            created_at: self.created_at,
+            last_refreshed_at: self.last_refreshed_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionService {
+    db: SqlitePool,
+    jwt_util: Arc<JwtUtil>,
+    refresh_expires_in: Duration,
+}
+
+impl SessionService {
+    pub fn new(db: SqlitePool, jwt_util: Arc<JwtUtil>, refresh_expires_in: &str) -> Self {
+        let minutes = JwtUtil::parse_expiration(refresh_expires_in)
+            .unwrap_or(DEFAULT_REFRESH_EXPIRES_IN_MINUTES);
+        Self { db, jwt_util, refresh_expires_in: Duration::minutes(minutes) }
+    }
+
+    /// Open a session for an authenticated user and issue its first tokens
+    pub async fn start_session(
+        &self,
+        user: &User,
+        client: &SessionClient,
+    ) -> ApiResult<TokenResponse> {
+        let refresh_token = random_hex(REFRESH_TOKEN_BYTES);
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO user_sessions (user_id, refresh_token_hash, user_agent, ip_address,
+             created_at, expires_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user.id)
+        .bind(sha256_hex(&refresh_token))
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .bind(now)
+        .bind(now + self.refresh_expires_in)
+        .execute(&self.db)
+        .await?;
+
+        let session_id = result.last_insert_rowid();
+        tracing::info!(
+            "Opened session {} for user {} (ID: {})",
+            session_id,
+            user.username,
+            user.id
+        );
+        self.issue_tokens(user.id, &user.username, session_id, refresh_token)
+    }
+
+    /// Exchange a refresh token for a new access token and a new refresh token
+    pub async fn refresh(&self, refresh_token: &str) -> ApiResult<TokenResponse> {
+        let token_hash = sha256_hex(refresh_token);
+        let session: Option<(i64, i64, String, bool, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT s.id, s.user_id, u.username, u.is_active, s.expires_at
+             FROM user_sessions s
+             JOIN users u ON u.id = s.user_id
+             WHERE s.refresh_token_hash = ? AND s.revoked_at IS NULL",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        let Some((session_id, user_id, username, is_active, expires_at)) = session else {
+            self.revoke_reused_token(&token_hash).await?;
+            tracing::warn!("Refresh with an unknown or revoked refresh token");
+            return Err(ApiError::TokenExpired);
+        };
+        if !is_active {
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+        if expires_at <= Utc::now() {
+            tracing::debug!("Session {} of user {} has expired", session_id, user_id);
+            return Err(ApiError::TokenExpired);
+        }
+
+        let new_token = random_hex(REFRESH_TOKEN_BYTES);
+        let now = Utc::now();
+        // Conditional on the presented token so that concurrent refreshes cannot both rotate
+        let rotated = sqlx::query(
+            "UPDATE user_sessions
+             SET refresh_token_hash = ?, previous_token_hash = ?, last_refreshed_at = ?,
+                 expires_at = ?
+             WHERE id = ? AND refresh_token_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(sha256_hex(&new_token))
+        .bind(&token_hash)
+        .bind(now)
+        .bind(now + self.refresh_expires_in)
+        .bind(session_id)
+        .bind(&token_hash)
+        .execute(&self.db)
+        .await?;
+        if rotated.rows_affected() == 0 {
+            return Err(ApiError::TokenExpired);
+        }
+
+        tracing::debug!("Refreshed session {} of user {}", session_id, user_id);
+        self.issue_tokens(user_id, &username, session_id, new_token)
+    }
+
+    /// Check that the session of an access token is still open (`TokenExpired` otherwise, so
+    /// that clients log in again)
+    pub async fn validate_session(&self, session_id: i64, user_id: i64) -> ApiResult<()> {
+        let open: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM user_sessions WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+        open.map(|_| ()).ok_or(ApiError::TokenExpired)
+    }
+
+    /// Open, unexpired sessions of a user, most recently used first
+    pub async fn list_sessions(
+        &self,
+        user_id: i64,
+        current_session_id: Option<i64>,
+    ) -> ApiResult<Vec<UserSession>> {
+        let rows: Vec<SessionRow> = sqlx::query_as(
+            "SELECT id, user_id, user_agent, ip_address, created_at, last_refreshed_at, expires_at
+             FROM user_sessions
+             WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ?
+             ORDER BY COALESCE(last_refreshed_at, created_at) DESC, id DESC",
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_all(&self.db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| row.into_session(current_session_id))
+            .collect())
+    }
+
+    /// Revoke one session of a user
+    pub async fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: i64,
+        reason: SessionRevokeReason,
+    ) -> ApiResult<()> {
+        let result = sqlx::query(
+            "UPDATE user_sessions SET revoked_at = ?, revoke_reason = ?
+             WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(Utc::now())
+        .bind(reason.as_str())
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Session {} not found", session_id)));
+        }
+
+        tracing::info!("Revoked session {} of user {} ({})", session_id, user_id, reason.as_str());
+        Ok(())
+    }
+
+    /// Revoke every open session of a user, except `keep_session_id` when set
+    pub async fn revoke_user_sessions(
+        &self,
+        user_id: i64,
+        keep_session_id: Option<i64>,
+        reason: SessionRevokeReason,
+    ) -> ApiResult<u64> {
+        let result = sqlx::query(
+            "UPDATE user_sessions SET revoked_at = ?, revoke_reason = ?
+             WHERE user_id = ? AND revoked_at IS NULL AND id IS NOT ?",
+        )
+        .bind(Utc::now())
+        .bind(reason.as_str())
+        .bind(user_id)
+        .bind(keep_session_id)
+        .execute(&self.db)
+        .await?;
+
+        let revoked = result.rows_affected();
+        if revoked > 0 {
+            tracing::info!(
+                "Revoked {} session(s) of user {} ({})",
+                revoked,
+                user_id,
+                reason.as_str()
+            );
+        }
+        Ok(revoked)
+    }
+
+    /// A rotated-out refresh token presented again means two parties hold the session
+    async fn revoke_reused_token(&self, token_hash: &str) -> ApiResult<()> {
+        let session: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT id, user_id FROM user_sessions
+             WHERE previous_token_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        if let Some((session_id, user_id)) = session {
+            tracing::warn!(
+                "Refresh token of session {} (user {}) was reused, revoking the session",
+                session_id,
+                user_id
+            );
+            self.revoke_session(user_id, session_id, SessionRevokeReason::RefreshTokenReused)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn issue_tokens(
+        &self,
+        user_id: i64,
+        username: &str,
+        session_id: i64,
+        refresh_token: String,
+    ) -> ApiResult<TokenResponse> {
+        let token = self
+            .jwt_util
+            .generate_token(user_id, username, session_id)?;
+        Ok(TokenResponse { token, refresh_token, expires_in: self.jwt_util.expires_in_secs() })
+    }
+}
@@ -0,0 +1,552 @@
+// SQL Policy Service
+// Purpose: Per-role SQL editor policies (statement classes, catalogs/databases, row and time
+//          limits), merged per user and enforced before statements reach the cluster
+
+use crate::models::{
+    EffectiveSqlPolicy, PermissionScope, RoleSqlPolicy, SqlPolicyRule, SqlStatementClass,
+    UpdateRoleSqlPolicyRequest,
+};
+use crate::utils::sql_lexer::{self, StatementKind};
+use crate::utils::{ApiError, ApiResult};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Catalog a session starts in
+const DEFAULT_CATALOG: &str = "default_catalog";
+
+/// Session variables bounding the execution time, which the role's limit sets itself
+const TIMEOUT_VARIABLES: &[&str] = &["query_timeout", "insert_timeout"];
+
+#[derive(Debug, sqlx::FromRow)]
+struct RoleSqlPolicyRow {
+    role_id: i64,
+    allowed_statements: String,
+    allowed_catalogs: String,
+    allowed_databases: String,
+    max_rows: Option<i64>,
+    max_execution_secs: Option<i64>,
+    updated_by: Option<i64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<RoleSqlPolicyRow> for RoleSqlPolicy {
+    fn from(row: RoleSqlPolicyRow) -> Self {
+        Self {
+            role_id: row.role_id,
+            allowed_statements: serde_json::from_str(&row.allowed_statements).unwrap_or_default(),
+            allowed_catalogs: serde_json::from_str(&row.allowed_catalogs).unwrap_or_default(),
+            allowed_databases: serde_json::from_str(&row.allowed_databases).unwrap_or_default(),
+            max_rows: row.max_rows,
+            max_execution_secs: row.max_execution_secs,
+            updated_by: row.updated_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqlPolicyService {
+    pool: SqlitePool,
+}
+
+impl SqlPolicyService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Policy of a role, None when the role is unrestricted
+    pub async fn get_role_policy(&self, role_id: i64) -> ApiResult<Option<RoleSqlPolicy>> {
+        let row: Option<RoleSqlPolicyRow> =
+            sqlx::query_as("SELECT * FROM role_sql_policies WHERE role_id = ?")
+                .bind(role_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Create or replace the policy of a role
+    pub async fn set_role_policy(
+        &self,
+        role_id: i64,
+        req: UpdateRoleSqlPolicyRequest,
+        updated_by: Option<i64>,
+    ) -> ApiResult<RoleSqlPolicy> {
+        if req.max_rows.is_some_and(|rows| rows <= 0) {
+            return Err(ApiError::validation_error("max_rows must be > 0"));
+        }
+        if req.max_execution_secs.is_some_and(|secs| secs <= 0) {
+            return Err(ApiError::validation_error("max_execution_secs must be > 0"));
+        }
+
+        let mut statements = req.allowed_statements;
+        statements.sort();
+        statements.dedup();
+        let catalogs = normalize_names(req.allowed_catalogs, "catalog")?;
+        let databases = normalize_names(req.allowed_databases, "database")?;
+
+        sqlx::query(
+            "INSERT INTO role_sql_policies (role_id, allowed_statements, allowed_catalogs,
+             allowed_databases, max_rows, max_execution_secs, updated_by)
+             VALUES (?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(role_id) DO UPDATE SET
+                allowed_statements = excluded.allowed_statements,
+                allowed_catalogs = excluded.allowed_catalogs,
+                allowed_databases = excluded.allowed_databases,
+                max_rows = excluded.max_rows,
+                max_execution_secs = excluded.max_execution_secs,
+                updated_by = excluded.updated_by,
+                updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(role_id)
+        .bind(serde_json::to_string(&statements)?)
+        .bind(serde_json::to_string(&catalogs)?)
+        .bind(serde_json::to_string(&databases)?)
+        .bind(req.max_rows)
+        .bind(req.max_execution_secs)
+        .bind(updated_by)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!("SQL policy of role {} updated by {:?}", role_id, updated_by);
+        self.get_role_policy(role_id)
+            .await?
+            .ok_or_else(|| ApiError::internal_error("SQL policy not saved"))
+    }
+
+    /// Remove the policy of a role (the role becomes unrestricted)
+    pub async fn delete_role_policy(&self, role_id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM role_sql_policies WHERE role_id = ?")
+            .bind(role_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found("SQL policy not found"));
+        }
+        Ok(())
+    }
+
+    /// Effective policy of a user: the policies of their roles
+    ///
+    /// Any role without a policy makes the user unrestricted; otherwise statements are
+    /// checked against each role's policy and the limits take the most permissive value.
+    pub async fn effective_policy(&self, user_id: i64) -> ApiResult<EffectiveSqlPolicy> {
+        let rows: Vec<(i64, Option<String>)> = sqlx::query_as(
+            "SELECT ur.role_id, p.allowed_statements
+             FROM user_roles ur
+             LEFT JOIN role_sql_policies p ON p.role_id = ur.role_id
+             WHERE ur.user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.is_empty() || rows.iter().any(|(_, policy)| policy.is_none()) {
+            return Ok(EffectiveSqlPolicy::default());
+        }
+
+        let mut effective = EffectiveSqlPolicy { restricted: true, ..Default::default() };
+        let mut unlimited_rows = false;
+        let mut unlimited_time = false;
+
+        for (role_id, _) in rows {
+            let Some(policy) = self.get_role_policy(role_id).await? else {
+                continue;
+            };
+            merge_limit(&mut effective.max_rows, &mut unlimited_rows, policy.max_rows);
+            merge_limit(
+                &mut effective.max_execution_secs,
+                &mut unlimited_time,
+                policy.max_execution_secs,
+            );
+            effective.rules.push(policy.into());
+        }
+
+        Ok(effective)
+    }
+}
+
+fn normalize_names(names: Vec<String>, what: &str) -> ApiResult<Vec<String>> {
+    let mut normalized = Vec::new();
+    for name in names {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(ApiError::validation_error(format!("Empty {} name", what)));
+        }
+        if !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Keep the largest limit; a role without the limit makes it unlimited
+fn merge_limit(merged: &mut Option<i64>, unlimited: &mut bool, limit: Option<i64>) {
+    match limit {
+        None => {
+            *unlimited = true;
+            *merged = None;
+        },
+        Some(value) if !*unlimited => *merged = Some(merged.map_or(value, |m| m.max(value))),
+        Some(_) => {},
+    }
+}
+
+/// Policy class of a statement kind
+pub fn statement_class(kind: StatementKind) -> SqlStatementClass {
+    match kind {
+        StatementKind::Select
+        | StatementKind::Show
+        | StatementKind::Describe
+        | StatementKind::Explain
+        | StatementKind::Use
+        | StatementKind::Empty => SqlStatementClass::Query,
+        StatementKind::Insert | StatementKind::Update | StatementKind::Delete => {
+            SqlStatementClass::Dml
+        },
+        StatementKind::Create
+        | StatementKind::Alter
+        | StatementKind::Drop
+        | StatementKind::Truncate => SqlStatementClass::Ddl,
+        _ => SqlStatementClass::Admin,
+    }
+}
+
+/// Policy class of a statement and its name in error messages
+///
+/// `SELECT ... INTO OUTFILE` exports data to external storage, so it is an Admin statement.
+fn classify_statement(sql: &str) -> (SqlStatementClass, String) {
+    if sql_lexer::has_into_outfile(sql) {
+        (SqlStatementClass::Admin, "SELECT INTO OUTFILE".to_string())
+    } else {
+        let kind = sql_lexer::classify(sql);
+        (statement_class(kind), kind.to_string())
+    }
+}
+
+/// Refuse statements raising the execution time limit of the role: `SET query_timeout`
+/// and `/*+ SET_VAR(query_timeout = ...) */` hints
+fn check_timeout_override(sql: &str) -> ApiResult<()> {
+    let sets_timeout = sql_lexer::classify(sql) == StatementKind::Set
+        && sql_lexer::find_keyword(sql, TIMEOUT_VARIABLES).is_some();
+    let hints_timeout = sql_lexer::hint_variables(sql)
+        .iter()
+        .any(|name| TIMEOUT_VARIABLES.contains(&name.as_str()));
+    if sets_timeout || hints_timeout {
+        return Err(ApiError::sql_safety_violation(
+            "当前角色限制了最长执行时间，不允许修改 query_timeout".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Statement classes an execute permission granted on some catalogs or databases allows
+const SCOPED_STATEMENT_CLASSES: &[SqlStatementClass] =
+    &[SqlStatementClass::Query, SqlStatementClass::Dml, SqlStatementClass::Ddl];
+
+/// Check editor statements against the catalogs and databases the execute permission is
+/// granted on in a cluster (see `OrgContext::database_scopes`)
+///
+/// Every database a statement touches must be covered by one scope as a catalog/database
+/// pair, so `catA.db1` and `catB.db2` do not grant `catA.db2`. Unqualified names resolve
+/// against the session context, which follows `USE` and `SET CATALOG` through the batch.
+/// Only queries, DML and DDL are allowed, and DML or DDL must name an in-scope database or
+/// table: statements on no database (GRANT, CREATE USER, SET GLOBAL, ALTER SYSTEM, KILL,
+/// ...) are refused.
+pub fn check_scoped_statements(
+    scopes: &[&PermissionScope],
+    statements: &[String],
+    catalog: Option<&str>,
+    database: Option<&str>,
+) -> ApiResult<()> {
+    let mut catalog = catalog
+        .filter(|c| !c.is_empty())
+        .unwrap_or(DEFAULT_CATALOG)
+        .to_string();
+    let mut database = database.filter(|d| !d.is_empty()).map(str::to_string);
+    if let Some(database) = &database {
+        check_scoped_database(scopes, &catalog, database)?;
+    }
+
+    for sql in statements {
+        let kind = sql_lexer::classify(sql);
+        let (class, name) = classify_statement(sql);
+        if !SCOPED_STATEMENT_CLASSES.contains(&class) {
+            return Err(ApiError::sql_safety_violation(format!(
+                "当前权限范围不允许执行 {} 语句",
+                name
+            )));
+        }
+
+        let references = sql_lexer::object_references(sql);
+
+        // USE [catalog.]database, SET CATALOG: move the session context
+        if kind == StatementKind::Use {
+            for reference in &references {
+                match (reference.keyword.as_str(), reference.parts.as_slice()) {
+                    ("CATALOG", [next]) => {
+                        check_scoped_catalog(scopes, next)?;
+                        catalog = next.clone();
+                        database = None;
+                    },
+                    ("USE", [next]) => {
+                        check_scoped_database(scopes, &catalog, next)?;
+                        database = Some(next.clone());
+                    },
+                    ("USE", [next_catalog, next]) => {
+                        check_scoped_database(scopes, next_catalog, next)?;
+                        catalog = next_catalog.clone();
+                        database = Some(next.clone());
+                    },
+                    _ => {},
+                }
+            }
+            continue;
+        }
+
+        // SHOW DATABASES [FROM <catalog>] lists a catalog
+        if kind == StatementKind::Show
+            && sql_lexer::find_keyword(sql, &["DATABASES", "SCHEMAS"]).is_some()
+        {
+            let listed = references
+                .iter()
+                .find_map(|r| match (r.keyword.as_str(), r.parts.as_slice()) {
+                    ("FROM" | "IN", [listed]) => Some(listed.as_str()),
+                    _ => None,
+                })
+                .unwrap_or(&catalog);
+            check_scoped_catalog(scopes, listed)?;
+            continue;
+        }
+
+        let shows_container = kind == StatementKind::Show
+            && sql_lexer::find_keyword(sql, &["TABLES", "VIEWS"]).is_some();
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        let mut uses_context = false;
+        for reference in &references {
+            let parts = reference.parts.as_slice();
+            match (reference.keyword.as_str(), parts) {
+                ("CATALOG", _) => {
+                    return Err(ApiError::sql_safety_violation(
+                        "当前权限范围不允许操作 Catalog".to_string(),
+                    ));
+                },
+                ("DATABASE" | "SCHEMA", [db]) => pairs.push((catalog.clone(), db.clone())),
+                ("FROM" | "IN", [db]) if shows_container => {
+                    pairs.push((catalog.clone(), db.clone()))
+                },
+                ("DATABASE" | "SCHEMA", [cat, db]) => pairs.push((cat.clone(), db.clone())),
+                ("FROM" | "IN", [cat, db]) if shows_container => {
+                    pairs.push((cat.clone(), db.clone()))
+                },
+                (_, [_table]) => uses_context = true,
+                (_, [db, _table]) => pairs.push((catalog.clone(), db.clone())),
+                (_, [cat, db, _table]) => pairs.push((cat.clone(), db.clone())),
+                _ => {},
+            }
+        }
+        if pairs.is_empty() && class != SqlStatementClass::Query && !uses_context {
+            return Err(ApiError::sql_safety_violation(format!(
+                "当前权限范围只允许操作已授权数据库中的对象，{} 语句未指定数据库或表",
+                kind
+            )));
+        }
+        if uses_context || pairs.is_empty() {
+            let Some(database) = &database else {
+                return Err(ApiError::sql_safety_violation(
+                    "当前权限范围需要先选择已授权的数据库".to_string(),
+                ));
+            };
+            pairs.push((catalog.clone(), database.clone()));
+        }
+        for (cat, db) in &pairs {
+            check_scoped_database(scopes, cat, db)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_scoped_catalog(scopes: &[&PermissionScope], catalog: &str) -> ApiResult<()> {
+    if scopes
+        .iter()
+        .any(|scope| scope.catalog.as_deref().is_none_or(|c| c == catalog))
+    {
+        Ok(())
+    } else {
+        Err(ApiError::sql_safety_violation(format!("当前权限范围无权访问 Catalog：{}", catalog)))
+    }
+}
+
+fn check_scoped_database(
+    scopes: &[&PermissionScope],
+    catalog: &str,
+    database: &str,
+) -> ApiResult<()> {
+    if scopes
+        .iter()
+        .any(|scope| scope.covers(scope.cluster_id, catalog, database))
+    {
+        Ok(())
+    } else {
+        Err(ApiError::sql_safety_violation(format!(
+            "当前权限范围无权访问数据库：{}.{}",
+            catalog, database
+        )))
+    }
+}
+
+/// Row limit to apply to an editor request
+pub fn effective_row_limit(policy: &EffectiveSqlPolicy, requested: Option<i32>) -> i32 {
+    let requested = requested.unwrap_or(1000);
+    match policy.max_rows {
+        Some(max) => requested.min(i32::try_from(max).unwrap_or(i32::MAX)).max(1),
+        None => requested,
+    }
+}
+
+/// Check editor statements against a policy before any of them is executed
+///
+/// `catalog`/`database` are the session context of the request, which must be allowed by
+/// one of the roles and follows `USE` and `SET CATALOG` through the batch. Each statement
+/// must be allowed as a whole by the policy of a single role: its class, every catalog and
+/// database it touches (unqualified names resolving against the context) and, when the
+/// role limits the execution time, no timeout override.
+pub fn check_statements(
+    policy: &EffectiveSqlPolicy,
+    statements: &[String],
+    catalog: Option<&str>,
+    database: Option<&str>,
+) -> ApiResult<()> {
+    if !policy.restricted {
+        return Ok(());
+    }
+
+    let mut catalog = catalog
+        .filter(|c| !c.is_empty())
+        .unwrap_or(DEFAULT_CATALOG)
+        .to_string();
+    let mut database = database.filter(|d| !d.is_empty()).map(str::to_string);
+    let context = [(catalog.clone(), database.clone())];
+    check_rules(policy, |rule| check_targets(rule, &context))?;
+
+    for sql in statements {
+        let kind = sql_lexer::classify(sql);
+        let (class, name) = classify_statement(sql);
+        let targets = statement_targets(sql, kind, &catalog, database.as_deref());
+
+        check_rules(policy, |rule| {
+            if !rule.allowed_statements.contains(&class) {
+                return Err(ApiError::sql_safety_violation(format!(
+                    "当前角色不允许执行 {} 语句",
+                    name
+                )));
+            }
+            check_targets(rule, &targets)?;
+            if rule.max_execution_secs.is_some() {
+                check_timeout_override(sql)?;
+            }
+            Ok(())
+        })?;
+
+        // USE [catalog.]database, SET CATALOG: move the session context
+        if kind == StatementKind::Use
+            && let Some((next_catalog, next_database)) = targets.into_iter().next()
+        {
+            catalog = next_catalog;
+            database = next_database;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ok when one of the rules passes the check, the error of the first rule otherwise
+fn check_rules(
+    policy: &EffectiveSqlPolicy,
+    check: impl Fn(&SqlPolicyRule) -> ApiResult<()>,
+) -> ApiResult<()> {
+    let mut denied = None;
+    for rule in &policy.rules {
+        match check(rule) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                denied.get_or_insert(e);
+            },
+        }
+    }
+    Err(denied.unwrap_or_else(|| {
+        ApiError::sql_safety_violation("当前角色不允许执行 SQL 语句".to_string())
+    }))
+}
+
+/// Catalogs, and databases where the statement names one, a statement touches. Unqualified
+/// names resolve against the session context, which a statement naming no database at all
+/// touches as a whole.
+fn statement_targets(
+    sql: &str,
+    kind: StatementKind,
+    catalog: &str,
+    database: Option<&str>,
+) -> Vec<(String, Option<String>)> {
+    // SHOW TABLES/VIEWS FROM <db>, SHOW DATABASES FROM <catalog>
+    let shows_databases = kind == StatementKind::Show
+        && sql_lexer::find_keyword(sql, &["DATABASES", "SCHEMAS"]).is_some();
+    let shows_container = shows_databases
+        || (kind == StatementKind::Show
+            && sql_lexer::find_keyword(sql, &["TABLES", "VIEWS"]).is_some());
+    let context = || (catalog.to_string(), database.map(str::to_string));
+
+    let mut targets = Vec::new();
+    for reference in sql_lexer::object_references(sql) {
+        let parts = reference.parts.as_slice();
+        let target = match (reference.keyword.as_str(), parts) {
+            ("CATALOG", [cat]) => (cat.clone(), None),
+            ("FROM" | "IN", [cat]) if shows_databases => (cat.clone(), None),
+            ("USE" | "DATABASE" | "SCHEMA", [db]) => (catalog.to_string(), Some(db.clone())),
+            ("FROM" | "IN", [db]) if shows_container => (catalog.to_string(), Some(db.clone())),
+            ("USE" | "DATABASE" | "SCHEMA", [cat, db]) => (cat.clone(), Some(db.clone())),
+            ("FROM" | "IN", [cat, db]) if shows_container => (cat.clone(), Some(db.clone())),
+            (_, [_table]) => context(),
+            (_, [db, _table]) => (catalog.to_string(), Some(db.clone())),
+            (_, [cat, db, _table]) => (cat.clone(), Some(db.clone())),
+            _ => continue,
+        };
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        targets.push(context());
+    }
+    targets
+}
+
+fn check_targets(rule: &SqlPolicyRule, targets: &[(String, Option<String>)]) -> ApiResult<()> {
+    for (catalog, database) in targets {
+        check_catalog(rule, catalog)?;
+        if let Some(database) = database {
+            check_database(rule, database)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_catalog(rule: &SqlPolicyRule, catalog: &str) -> ApiResult<()> {
+    match &rule.allowed_catalogs {
+        Some(allowed) if !allowed.iter().any(|c| c == catalog) => {
+            Err(ApiError::sql_safety_violation(format!("当前角色无权访问 Catalog：{}", catalog)))
+        },
+        _ => Ok(()),
+    }
+}
+
+fn check_database(rule: &SqlPolicyRule, database: &str) -> ApiResult<()> {
+    match &rule.allowed_databases {
+        Some(allowed) if !allowed.iter().any(|d| d == database) => {
+            Err(ApiError::sql_safety_violation(format!("当前角色无权访问数据库：{}", database)))
+        },
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,891 @@
+// SSO Service
+// Purpose: Log users in through an LDAP directory or an OpenID Connect provider. Users are
+//          provisioned on their first login; group mappings of their directory groups (or
+//          groups claim) decide their organization and roles on every login.
+
+use crate::config::{LdapConfig, OidcConfig, SsoConfig};
+use crate::models::{
+    AuthProvider, AuthProviders, CreateSsoGroupMappingRequest, LoginRequest, SsoGroupMapping,
+    TokenResponse, User,
+};
+use crate::services::CasbinService;
+use crate::services::session_service::{SessionClient, SessionService};
+use crate::utils::crypto::random_hex;
+use crate::utils::{ApiError, ApiResult};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use bcrypt::{DEFAULT_COST, hash};
+use dashmap::DashMap;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use ldap3::{LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry, ldap_escape};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// How long a started OIDC login can be completed
+pub const PENDING_LOGIN_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How long discovery metadata and signing keys of the OIDC provider are cached
+const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// User entry read from a directory after a successful bind
+#[derive(Debug, Clone)]
+pub struct DirectoryUser {
+    pub dn: String,
+    pub username: String,
+    pub email: Option<String>,
+    /// DNs of the groups of the user
+    pub groups: Vec<String>,
+}
+
+/// A directory users authenticate against
+pub trait LdapDirectory: Send + Sync {
+    /// Check the password of a login name and read the user entry and groups
+    /// (None for unknown users and wrong passwords)
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<Option<DirectoryUser>>> + Send + 'a>>;
+}
+
+/// Identity asserted by a provider, before provisioning
+#[derive(Debug, Clone)]
+struct ExternalIdentity {
+    provider: AuthProvider,
+    /// Stable identifier at the provider (DN or OIDC subject)
+    subject: String,
+    username: String,
+    email: Option<String>,
+    groups: Vec<String>,
+}
+
+pub struct SsoService {
+    db: SqlitePool,
+    casbin_service: Arc<CasbinService>,
+    session_service: Arc<SessionService>,
+    allow_unmapped_users: bool,
+    directory: Option<Arc<dyn LdapDirectory>>,
+    oidc: Option<OidcClient>,
+    post_login_redirect: String,
+}
+
+impl SsoService {
+    pub fn new(
+        db: SqlitePool,
+        config: &SsoConfig,
+        casbin_service: Arc<CasbinService>,
+        session_service: Arc<SessionService>,
+    ) -> Self {
+        let directory = config.ldap.enabled.then(|| {
+            Arc::new(LdapClient { config: config.ldap.clone() }) as Arc<dyn LdapDirectory>
+        });
+        let oidc = config
+            .oidc
+            .enabled
+            .then(|| OidcClient::new(config.oidc.clone()));
+        Self {
+            db,
+            casbin_service,
+            session_service,
+            allow_unmapped_users: config.allow_unmapped_users,
+            directory,
+            oidc,
+            post_login_redirect: config.oidc.post_login_redirect.clone(),
+        }
+    }
+
+    /// Authenticate LDAP logins against another directory implementation
+    pub fn with_directory(mut self, directory: Arc<dyn LdapDirectory>) -> Self {
+        self.directory = Some(directory);
+        self
+    }
+
+    pub fn providers(&self) -> AuthProviders {
+        AuthProviders { ldap: self.directory.is_some(), oidc: self.oidc.is_some() }
+    }
+
+    /// Frontend page the OIDC callback redirects to
+    pub fn post_login_redirect(&self) -> &str {
+        &self.post_login_redirect
+    }
+
+    // ========================================
+    // Login
+    // ========================================
+
+    /// Log in with a directory password, provisioning the user on first login
+    pub async fn ldap_login(
+        &self,
+        req: &LoginRequest,
+        client: &SessionClient,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let directory = self
+            .directory
+            .as_ref()
+            .ok_or_else(|| ApiError::validation_error("LDAP login is not enabled"))?;
+
+        let entry = directory
+            .authenticate(&req.username, &req.password)
+            .await?
+            .ok_or_else(|| {
+                tracing::warn!("LDAP login failed for '{}'", req.username);
+                ApiError::invalid_credentials()
+            })?;
+
+        let identity = ExternalIdentity {
+            provider: AuthProvider::Ldap,
+            subject: entry.dn,
+            username: entry.username,
+            email: entry.email,
+            groups: entry.groups,
+        };
+        self.login(identity, client).await
+    }
+
+    /// Start an OIDC login: remember its state and return the authorization URL with the
+    /// state, which the caller binds to the browser
+    pub async fn oidc_authorization_url(&self) -> ApiResult<(String, String)> {
+        self.oidc_client()?.authorization_url().await
+    }
+
+    /// Whether the OIDC callback is served over HTTPS (cookies of the login are Secure then)
+    pub fn oidc_callback_is_https(&self) -> bool {
+        self.oidc
+            .as_ref()
+            .is_some_and(|oidc| oidc.config.redirect_url.starts_with("https://"))
+    }
+
+    /// Finish an OIDC login with the authorization code the provider redirected back with
+    pub async fn oidc_login(
+        &self,
+        code: &str,
+        state: &str,
+        client: &SessionClient,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let identity = self.oidc_client()?.exchange_code(code, state).await?;
+        self.login(identity, client).await
+    }
+
+    fn oidc_client(&self) -> ApiResult<&OidcClient> {
+        self.oidc
+            .as_ref()
+            .ok_or_else(|| ApiError::validation_error("OIDC login is not enabled"))
+    }
+
+    async fn login(
+        &self,
+        identity: ExternalIdentity,
+        client: &SessionClient,
+    ) -> ApiResult<(User, TokenResponse)> {
+        let user = self.provision(&identity).await?;
+        let tokens = self.session_service.start_session(&user, client).await?;
+        tracing::info!(
+            "User logged in through {}: {} (ID: {})",
+            identity.provider.as_str(),
+            user.username,
+            user.id
+        );
+        Ok((user, tokens))
+    }
+
+    // ========================================
+    // Provisioning
+    // ========================================
+
+    /// Create or update the local user of an identity and sync its organization and roles
+    async fn provision(&self, identity: &ExternalIdentity) -> ApiResult<User> {
+        let (organization_id, role_ids) = self.resolve_mappings(identity).await?;
+
+        let existing: Option<User> =
+            sqlx::query_as("SELECT * FROM users WHERE auth_provider = ? AND external_id = ?")
+                .bind(identity.provider.as_str())
+                .bind(&identity.subject)
+                .fetch_optional(&self.db)
+                .await?;
+        if let Some(user) = &existing
+            && !user.is_active
+        {
+            tracing::warn!("Login failed: user '{}' is disabled", user.username);
+            return Err(ApiError::unauthorized("User account is disabled"));
+        }
+
+        // Hashed before the transaction, bcrypt is slow
+        let password_hash = match existing {
+            Some(_) => None,
+            None => {
+                let unusable = hash(random_hex(32), DEFAULT_COST).map_err(|e| {
+                    ApiError::internal_error(format!("Failed to hash password: {}", e))
+                })?;
+                Some(unusable)
+            },
+        };
+
+        let mut tx = self.db.begin().await?;
+        let user_id = match &existing {
+            Some(user) => {
+                sqlx::query(
+                    "UPDATE users SET email = COALESCE(?, email), organization_id = ?,
+                     updated_at = CURRENT_TIMESTAMP
+                     WHERE id = ?",
+                )
+                .bind(&identity.email)
+                .bind(organization_id)
+                .bind(user.id)
+                .execute(&mut *tx)
+                .await?;
+                user.id
+            },
+            None => {
+                let taken: Option<i64> =
+                    sqlx::query_scalar("SELECT id FROM users WHERE username = ?")
+                        .bind(&identity.username)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                if taken.is_some() {
+                    tracing::warn!(
+                        "Cannot provision {} user '{}': username already taken",
+                        identity.provider.as_str(),
+                        identity.username
+                    );
+                    return Err(ApiError::unauthorized(format!(
+                        "Username '{}' is already used by another account",
+                        identity.username
+                    )));
+                }
+
+                let result = sqlx::query(
+                    "INSERT INTO users (username, password_hash, email, organization_id,
+                     auth_provider, external_id)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&identity.username)
+                .bind(password_hash)
+                .bind(&identity.email)
+                .bind(organization_id)
+                .bind(identity.provider.as_str())
+                .bind(&identity.subject)
+                .execute(&mut *tx)
+                .await?;
+                result.last_insert_rowid()
+            },
+        };
+
+        sqlx::query(
+            "INSERT INTO user_organizations (user_id, organization_id) VALUES (?, ?)
+             ON CONFLICT(user_id) DO UPDATE SET organization_id = excluded.organization_id",
+        )
+        .bind(user_id)
+        .bind(organization_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Group mappings are the source of truth for the roles of provisioned users
+        let current: HashSet<i64> =
+            sqlx::query_scalar::<_, i64>("SELECT role_id FROM user_roles WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+        let to_remove: Vec<i64> = current.difference(&role_ids).copied().collect();
+        let to_add: Vec<i64> = role_ids.difference(&current).copied().collect();
+        for role_id in &to_remove {
+            sqlx::query("DELETE FROM user_roles WHERE user_id = ? AND role_id = ?")
+                .bind(user_id)
+                .bind(role_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for role_id in &to_add {
+            sqlx::query("INSERT INTO user_roles (user_id, role_id) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(role_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        for role_id in &to_remove {
+            self.casbin_service
+                .remove_role_for_user(user_id, *role_id)
+                .await?;
+        }
+        for role_id in &to_add {
+            self.casbin_service
+                .add_role_for_user(user_id, *role_id)
+                .await?;
+        }
+
+        if existing.is_none() {
+            tracing::info!(
+                "Provisioned {} user {} (ID: {}) in organization {} with {} role(s)",
+                identity.provider.as_str(),
+                identity.username,
+                user_id,
+                organization_id,
+                role_ids.len()
+            );
+        }
+
+        let user = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(user)
+    }
+
+    /// Organization and roles the groups of an identity map to. The first matching mapping
+    /// decides the organization; roles of the matching mappings of that organization apply.
+    async fn resolve_mappings(
+        &self,
+        identity: &ExternalIdentity,
+    ) -> ApiResult<(i64, HashSet<i64>)> {
+        let mappings: Vec<(String, i64, Option<i64>)> = sqlx::query_as(
+            "SELECT group_name, organization_id, role_id FROM sso_group_mappings
+             WHERE provider = ? ORDER BY id",
+        )
+        .bind(identity.provider.as_str())
+        .fetch_all(&self.db)
+        .await?;
+
+        let matched: Vec<(i64, Option<i64>)> = mappings
+            .into_iter()
+            .filter(|(group_name, _, _)| {
+                identity
+                    .groups
+                    .iter()
+                    .any(|group| group_matches(group, group_name))
+            })
+            .map(|(_, organization_id, role_id)| (organization_id, role_id))
+            .collect();
+
+        let Some(&(organization_id, _)) = matched.first() else {
+            if !self.allow_unmapped_users {
+                tracing::warn!(
+                    "{} login refused for '{}': no group mapping matches {:?}",
+                    identity.provider.as_str(),
+                    identity.username,
+                    identity.groups
+                );
+                return Err(ApiError::unauthorized(format!(
+                    "No group mapping grants '{}' access",
+                    identity.username
+                )));
+            }
+            let default_org: i64 =
+                sqlx::query_scalar("SELECT id FROM organizations WHERE code = 'default_org'")
+                    .fetch_optional(&self.db)
+                    .await?
+                    .ok_or_else(|| ApiError::internal_error("Default organization not found"))?;
+            return Ok((default_org, HashSet::new()));
+        };
+
+        let role_ids = matched
+            .iter()
+            .filter(|(org_id, _)| *org_id == organization_id)
+            .filter_map(|(_, role_id)| *role_id)
+            .collect();
+        Ok((organization_id, role_ids))
+    }
+
+    // ========================================
+    // Group mappings
+    // ========================================
+
+    pub async fn list_mappings(&self) -> ApiResult<Vec<SsoGroupMapping>> {
+        let mappings = sqlx::query_as(&format!("{} ORDER BY m.provider, m.id", MAPPING_SELECT))
+            .fetch_all(&self.db)
+            .await?;
+        Ok(mappings)
+    }
+
+    pub async fn create_mapping(
+        &self,
+        req: CreateSsoGroupMappingRequest,
+    ) -> ApiResult<SsoGroupMapping> {
+        if req.provider == AuthProvider::Local {
+            return Err(ApiError::validation_error("Group mappings apply to ldap and oidc only"));
+        }
+        let group_name = req.group_name.trim();
+        if group_name.is_empty() {
+            return Err(ApiError::validation_error("Group name is required"));
+        }
+
+        let organization: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM organizations WHERE id = ?")
+                .bind(req.organization_id)
+                .fetch_optional(&self.db)
+                .await?;
+        if organization.is_none() {
+            return Err(ApiError::not_found("Organization not found"));
+        }
+
+        if let Some(role_id) = req.role_id {
+            let role: Option<(i64, Option<i64>)> =
+                sqlx::query_as("SELECT id, organization_id FROM roles WHERE id = ?")
+                    .bind(role_id)
+                    .fetch_optional(&self.db)
+                    .await?;
+            let (_, role_org) = role.ok_or_else(|| ApiError::not_found("Role not found"))?;
+            if role_org.is_some_and(|org_id| org_id != req.organization_id) {
+                return Err(ApiError::validation_error(
+                    "Role belongs to another organization than the mapping",
+                ));
+            }
+        }
+
+        let result = sqlx::query(
+            "INSERT INTO sso_group_mappings (provider, group_name, organization_id, role_id)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(req.provider.as_str())
+        .bind(group_name)
+        .bind(req.organization_id)
+        .bind(req.role_id)
+        .execute(&self.db)
+        .await?;
+
+        let id = result.last_insert_rowid();
+        tracing::info!(
+            "Created {} group mapping {} for '{}'",
+            req.provider.as_str(),
+            id,
+            group_name
+        );
+        let mapping = sqlx::query_as(&format!("{} WHERE m.id = ?", MAPPING_SELECT))
+            .bind(id)
+            .fetch_one(&self.db)
+            .await?;
+        Ok(mapping)
+    }
+
+    pub async fn delete_mapping(&self, id: i64) -> ApiResult<()> {
+        let result = sqlx::query("DELETE FROM sso_group_mappings WHERE id = ?")
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(ApiError::not_found(format!("Group mapping {} not found", id)));
+        }
+        tracing::info!("Deleted group mapping {}", id);
+        Ok(())
+    }
+}
+
+const MAPPING_SELECT: &str = "SELECT m.id, m.provider, m.group_name, m.organization_id,
+    o.name AS organization_name, m.role_id, r.name AS role_name, m.created_at
+    FROM sso_group_mappings m
+    LEFT JOIN organizations o ON o.id = m.organization_id
+    LEFT JOIN roles r ON r.id = m.role_id";
+
+/// A group matches a mapping by its full DN or by the value of its first RDN
+/// (`cn=dba,ou=groups,dc=example,dc=com` matches `dba`), ignoring case
+fn group_matches(group: &str, group_name: &str) -> bool {
+    if group.eq_ignore_ascii_case(group_name) {
+        return true;
+    }
+    group
+        .split(',')
+        .next()
+        .and_then(|rdn| rdn.split_once('='))
+        .is_some_and(|(_, value)| value.trim().eq_ignore_ascii_case(group_name))
+}
+
+// ========================================
+// LDAP
+// ========================================
+
+struct LdapClient {
+    config: LdapConfig,
+}
+
+impl LdapClient {
+    async fn search_and_bind(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<DirectoryUser>, LdapError> {
+        let config = &self.config;
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(config.timeout_secs))
+            .set_starttls(config.starttls);
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &config.url).await?;
+        ldap3::drive!(conn);
+
+        if let Some(bind_dn) = &config.bind_dn {
+            let bind_password = config.bind_password.as_deref().unwrap_or_default();
+            ldap.simple_bind(bind_dn, bind_password).await?.success()?;
+        }
+
+        let filter = config
+            .user_filter
+            .replace("{username}", &ldap_escape(username));
+        let attributes = [
+            config.username_attribute.as_str(),
+            config.email_attribute.as_str(),
+            config.group_membership_attribute.as_str(),
+        ];
+        let (entries, _) = ldap
+            .search(&config.user_base_dn, Scope::Subtree, &filter, attributes)
+            .await?
+            .success()?;
+        // Ambiguous filters must not let one user log in as another
+        let mut entries = entries.into_iter();
+        let (Some(entry), None) = (entries.next(), entries.next()) else {
+            tracing::debug!("LDAP search for '{}' did not return exactly one entry", username);
+            ldap.unbind().await?;
+            return Ok(None);
+        };
+        let entry = SearchEntry::construct(entry);
+
+        // 49: invalid credentials
+        let bind = ldap.simple_bind(&entry.dn, password).await?;
+        if bind.rc != 0 {
+            tracing::debug!("LDAP bind as {} failed with code {}", entry.dn, bind.rc);
+            ldap.unbind().await?;
+            return Ok(None);
+        }
+
+        let first_value = |attribute: &str| {
+            entry
+                .attrs
+                .get(attribute)
+                .and_then(|values| values.first())
+                .cloned()
+        };
+        let groups = match &config.group_base_dn {
+            Some(group_base_dn) => {
+                // Search groups with the service account, users may not be allowed to
+                if let Some(bind_dn) = &config.bind_dn {
+                    let bind_password = config.bind_password.as_deref().unwrap_or_default();
+                    ldap.simple_bind(bind_dn, bind_password).await?.success()?;
+                }
+                let filter = config
+                    .group_filter
+                    .replace("{dn}", &ldap_escape(entry.dn.as_str()))
+                    .replace("{username}", &ldap_escape(username));
+                let (groups, _) = ldap
+                    .search(group_base_dn, Scope::Subtree, &filter, ["1.1"])
+                    .await?
+                    .success()?;
+                groups
+                    .into_iter()
+                    .map(|group| SearchEntry::construct(group).dn)
+                    .collect()
+            },
+            None => entry
+                .attrs
+                .get(&config.group_membership_attribute)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        ldap.unbind().await?;
+
+        Ok(Some(DirectoryUser {
+            username: first_value(&config.username_attribute)
+                .unwrap_or_else(|| username.to_string()),
+            email: first_value(&config.email_attribute),
+            dn: entry.dn,
+            groups,
+        }))
+    }
+}
+
+impl LdapDirectory for LdapClient {
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<Option<DirectoryUser>>> + Send + 'a>> {
+        Box::pin(async move {
+            // Servers accept an empty password as an unauthenticated bind
+            if username.is_empty() || password.is_empty() {
+                return Ok(None);
+            }
+            let timeout = Duration::from_secs(self.config.timeout_secs);
+            tokio::time::timeout(timeout, self.search_and_bind(username, password))
+                .await
+                .map_err(|_| ApiError::internal_error("LDAP server timed out"))?
+                .map_err(|e| {
+                    tracing::error!("LDAP login of '{}' failed: {}", username, e);
+                    ApiError::internal_error(format!("LDAP request failed: {}", e))
+                })
+        })
+    }
+}
+
+// ========================================
+// OpenID Connect
+// ========================================
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+struct CachedProvider {
+    fetched_at: Instant,
+    metadata: ProviderMetadata,
+    keys: JwkSet,
+}
+
+/// Login started at the provider and not completed yet
+struct PendingLogin {
+    nonce: String,
+    code_verifier: String,
+    started_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: String,
+}
+
+struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    provider: RwLock<Option<Arc<CachedProvider>>>,
+    pending: DashMap<String, PendingLogin>,
+}
+
+impl OidcClient {
+    fn new(config: OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+        Self { config, http, provider: RwLock::new(None), pending: DashMap::new() }
+    }
+
+    async fn authorization_url(&self) -> ApiResult<(String, String)> {
+        let provider = self.provider(false).await?;
+
+        let state = random_hex(16);
+        let nonce = random_hex(16);
+        let code_verifier = random_hex(32);
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+        self.pending
+            .retain(|_, login| login.started_at.elapsed() < PENDING_LOGIN_TTL);
+        self.pending.insert(
+            state.clone(),
+            PendingLogin { nonce: nonce.clone(), code_verifier, started_at: Instant::now() },
+        );
+
+        let url = reqwest::Url::parse_with_params(
+            &provider.metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("scope", self.config.scopes.join(" ").as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| ApiError::internal_error(format!("Invalid authorization endpoint: {}", e)))?;
+        Ok((url.into(), state))
+    }
+
+    async fn exchange_code(&self, code: &str, state: &str) -> ApiResult<ExternalIdentity> {
+        let pending = self
+            .pending
+            .remove(state)
+            .map(|(_, login)| login)
+            .filter(|login| login.started_at.elapsed() < PENDING_LOGIN_TTL)
+            .ok_or_else(|| {
+                ApiError::unauthorized("Sign-in request expired or unknown, please sign in again")
+            })?;
+        let provider = self.provider(false).await?;
+
+        let response = self
+            .http
+            .post(&provider.metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("code_verifier", pending.code_verifier.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(provider_error)?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            tracing::warn!("OIDC token endpoint answered {}: {}", status, body);
+            return Err(ApiError::unauthorized("OIDC provider rejected the authorization code"));
+        }
+        let tokens: TokenEndpointResponse = response.json().await.map_err(provider_error)?;
+
+        let claims = self
+            .verify_id_token(&tokens.id_token, &pending.nonce)
+            .await?;
+        self.identity_from_claims(&claims)
+    }
+
+    /// Check the signature, issuer, audience, expiry and nonce of an ID token
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> ApiResult<serde_json::Map<String, Value>> {
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            tracing::warn!("Invalid ID token: {}", e);
+            ApiError::unauthorized("Invalid ID token")
+        };
+        let header = decode_header(id_token).map_err(invalid)?;
+
+        let key = match header.alg {
+            // Symmetric ID tokens are signed with the client secret
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                if self.config.client_secret.is_empty() {
+                    return Err(ApiError::unauthorized("Invalid ID token"));
+                }
+                DecodingKey::from_secret(self.config.client_secret.as_bytes())
+            },
+            _ => self.signing_key(header.kid.as_deref()).await?,
+        };
+
+        let provider = self.provider(false).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<serde_json::Map<String, Value>>(id_token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            tracing::warn!("ID token nonce does not match the sign-in request");
+            return Err(ApiError::unauthorized("Invalid ID token"));
+        }
+        Ok(claims)
+    }
+
+    /// Public key of the provider with the given key id, refetched once for rotated keys
+    async fn signing_key(&self, kid: Option<&str>) -> ApiResult<DecodingKey> {
+        for refresh in [false, true] {
+            let provider = self.provider(refresh).await?;
+            let jwk = match kid {
+                Some(kid) => provider.keys.find(kid),
+                None if provider.keys.keys.len() == 1 => provider.keys.keys.first(),
+                None => None,
+            };
+            if let Some(jwk) = jwk {
+                return DecodingKey::from_jwk(jwk).map_err(|e| {
+                    ApiError::internal_error(format!("Unsupported OIDC signing key: {}", e))
+                });
+            }
+        }
+        tracing::warn!("ID token signed with unknown key {:?}", kid);
+        Err(ApiError::unauthorized("Invalid ID token"))
+    }
+
+    fn identity_from_claims(
+        &self,
+        claims: &serde_json::Map<String, Value>,
+    ) -> ApiResult<ExternalIdentity> {
+        let string_claim = |name: &str| {
+            claim(claims, name)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .filter(|value| !value.is_empty())
+        };
+        let subject =
+            string_claim("sub").ok_or_else(|| ApiError::unauthorized("ID token has no subject"))?;
+        let username = string_claim(&self.config.username_claim).ok_or_else(|| {
+            ApiError::unauthorized(format!("ID token has no {} claim", self.config.username_claim))
+        })?;
+        let groups = match claim(claims, &self.config.groups_claim) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(ExternalIdentity {
+            provider: AuthProvider::Oidc,
+            subject,
+            username,
+            email: string_claim(&self.config.email_claim),
+            groups,
+        })
+    }
+
+    /// Discovery metadata and signing keys, cached for an hour
+    async fn provider(&self, refresh: bool) -> ApiResult<Arc<CachedProvider>> {
+        if !refresh
+            && let Some(cached) = self.provider.read().await.as_ref()
+            && cached.fetched_at.elapsed() < PROVIDER_CACHE_TTL
+        {
+            return Ok(Arc::clone(cached));
+        }
+
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer_url.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = self.get_json(&discovery_url).await?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer_url.trim_end_matches('/') {
+            return Err(ApiError::internal_error(format!(
+                "OIDC provider announces issuer {} instead of {}",
+                metadata.issuer, self.config.issuer_url
+            )));
+        }
+        let keys: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+
+        let cached = Arc::new(CachedProvider { fetched_at: Instant::now(), metadata, keys });
+        *self.provider.write().await = Some(Arc::clone(&cached));
+        Ok(cached)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> ApiResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)
+    }
+}
+
+/// Claim by name, or by dotted path into nested objects (e.g. `realm_access.roles`)
+fn claim<'a>(claims: &'a serde_json::Map<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(value) = claims.get(name) {
+        return Some(value);
+    }
+    let mut parts = name.split('.');
+    let mut value = claims.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn provider_error(e: reqwest::Error) -> ApiError {
+    tracing::error!("OIDC provider request failed: {}", e);
+    ApiError::internal_error(format!("OIDC provider request failed: {}", e))
+}
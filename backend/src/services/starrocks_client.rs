@@ -1,7 +1,7 @@
-use crate::models::{Backend, Cluster, Frontend, Query, RuntimeInfo};
+use crate::models::{Backend, Cluster, FeEndpoint, Frontend, Query, RuntimeInfo};
 use crate::services::{mysql_client::MySQLClient, mysql_pool_manager::MySQLPoolManager};
-use crate::utils::{ApiError, ApiResult};
-use reqwest::Client;
+use crate::utils::{ApiError, ApiResult, ClusterTls};
+use reqwest::{Client, Method, RequestBuilder, Response};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::sync::Arc;
@@ -15,27 +15,107 @@ pub struct StarRocksClient {
 
 impl StarRocksClient {
     pub fn new(cluster: Cluster, mysql_pool_manager: Arc<MySQLPoolManager>) -> Self {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(cluster.connection_timeout as u64))
-            .build()
-            .unwrap_or_else(|e| {
-                // HTTP client build failure is rare and usually indicates system resource issues
-                tracing::error!(
-                    "Failed to build HTTP client for cluster {}: {}. This is a critical error.",
-                    cluster.name,
-                    e
-                );
-                // Use default client as fallback but log the issue
-                tracing::warn!("Using default HTTP client configuration as fallback");
-                Client::default()
-            });
+        let mut builder =
+            Client::builder().timeout(Duration::from_secs(cluster.connection_timeout as u64));
+        match ClusterTls::from_cluster(&cluster, mysql_pool_manager.cipher()) {
+            Ok(Some(tls)) => builder = tls.apply_to_http(builder),
+            Ok(None) => {},
+            // Requests still use https and fail certificate verification
+            Err(e) => tracing::error!("Invalid TLS settings for cluster {}: {}", cluster.name, e),
+        }
+        let http_client = builder.build().unwrap_or_else(|e| {
+            // HTTP client build failure is rare and usually indicates system resource issues
+            tracing::error!(
+                "Failed to build HTTP client for cluster {}: {}. This is a critical error.",
+                cluster.name,
+                e
+            );
+            // Use default client as fallback but log the issue
+            tracing::warn!("Using default HTTP client configuration as fallback");
+            Client::default()
+        });
 
         Self { http_client, cluster, mysql_pool_manager }
     }
 
-    pub fn get_base_url(&self) -> String {
+    fn base_url(&self, endpoint: &FeEndpoint) -> String {
         let protocol = if self.cluster.enable_ssl { "https" } else { "http" };
-        format!("{}://{}:{}", protocol, self.cluster.fe_host, self.cluster.fe_http_port)
+        format!("{}://{}:{}", protocol, endpoint.host, endpoint.http_port)
+    }
+
+    /// FE endpoints in failover order
+    fn fe_endpoints(&self) -> Vec<FeEndpoint> {
+        self.mysql_pool_manager.candidate_endpoints(&self.cluster)
+    }
+
+    /// FE endpoints in failover order with the leader first
+    fn leader_first_endpoints(&self) -> Vec<FeEndpoint> {
+        let mut endpoints = self.fe_endpoints();
+        if let Some(leader) = self.mysql_pool_manager.leader_endpoint(self.cluster.id) {
+            endpoints.retain(|endpoint| *endpoint != leader);
+            endpoints.insert(0, leader);
+        }
+        endpoints
+    }
+
+    /// Send an HTTP request to the first FE that answers.
+    /// Connection errors fail over to the next endpoint; timeouts only do for GET requests,
+    /// as other requests may already have been applied.
+    async fn send_with_failover(
+        &self,
+        endpoints: Vec<FeEndpoint>,
+        path: &str,
+        build: impl Fn(&str) -> RequestBuilder,
+    ) -> ApiResult<Response> {
+        let mut errors = Vec::new();
+        for endpoint in endpoints {
+            let url = format!("{}{}", self.base_url(&endpoint), path);
+            let request = build(&url)
+                .build()
+                .map_err(|e| ApiError::internal_error(format!("Invalid request: {}", e)))?;
+            let idempotent = request.method() == Method::GET;
+
+            match self.http_client.execute(request).await {
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => {
+                    let message = Self::error_chain(&e);
+                    tracing::warn!(
+                        "FE {} of cluster {} did not answer {}: {}",
+                        endpoint,
+                        self.cluster.name,
+                        path,
+                        message
+                    );
+                    errors.push(format!("{}: {}", endpoint, message));
+                },
+                Err(e) => {
+                    return Err(ApiError::cluster_connection_failed(format!(
+                        "Request failed: {}",
+                        Self::error_chain(&e)
+                    )));
+                },
+            }
+        }
+        Err(ApiError::cluster_connection_failed(format!("Request failed: {}", errors.join("; "))))
+    }
+
+    /// Error message including its causes; reqwest hides TLS failures in the source chain
+    fn error_chain(error: &reqwest::Error) -> String {
+        let mut message = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    }
+
+    /// Decrypted cluster password for HTTP basic auth
+    fn password(&self) -> ApiResult<String> {
+        self.mysql_pool_manager
+            .cipher()
+            .decrypt(&self.cluster.password_encrypted)
     }
 
     async fn mysql_client(&self) -> ApiResult<MySQLClient> {
@@ -105,6 +185,12 @@ impl StarRocksClient {
         self.show_proc_entities::<Backend>("/backends").await
     }
 
+    // Get the storage paths of a BE (shared-nothing only)
+    pub async fn get_backend_disks(&self, backend_id: &str) -> ApiResult<Vec<Value>> {
+        self.show_proc_raw(&format!("/backends/{}", backend_id))
+            .await
+    }
+
     // Get compute nodes for shared-data architecture
     async fn get_compute_nodes(&self) -> ApiResult<Vec<Backend>> {
         let compute_nodes = self.show_proc_entities::<Backend>("/compute_nodes").await?;
@@ -114,24 +200,23 @@ impl StarRocksClient {
 
     // Execute SQL command via HTTP API
     pub async fn execute_sql(&self, sql: &str) -> ApiResult<()> {
-        let url = format!("{}/api/query", self.get_base_url());
         tracing::debug!("Executing SQL: {}", sql);
 
         let body = serde_json::json!({
             "query": sql
         });
+        let password = self.password()?;
 
+        // Statements like ALTER SYSTEM must run on the leader: try it first
         let response = self
-            .http_client
-            .post(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .json(&body)
-            .send()
+            .send_with_failover(self.leader_first_endpoints(), "/api/query", |url| {
+                self.http_client
+                    .post(url)
+                    .basic_auth(&self.cluster.username, Some(&password))
+                    .json(&body)
+            })
             .await
-            .map_err(|e| {
-                tracing::error!("Failed to execute SQL: {}", e);
-                ApiError::cluster_connection_failed(format!("Request failed: {}", e))
-            })?;
+            .inspect_err(|e| tracing::error!("Failed to execute SQL: {}", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -189,15 +274,15 @@ impl StarRocksClient {
 
     // Get runtime info
     pub async fn get_runtime_info(&self) -> ApiResult<RuntimeInfo> {
-        let url = format!("{}/api/show_runtime_info", self.get_base_url());
+        let password = self.password()?;
 
         let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
+            .send_with_failover(self.fe_endpoints(), "/api/show_runtime_info", |url| {
+                self.http_client
+                    .get(url)
+                    .basic_auth(&self.cluster.username, Some(&password))
+            })
+            .await?;
 
         if !response.status().is_success() {
             return Err(ApiError::cluster_connection_failed(format!(
@@ -215,15 +300,15 @@ impl StarRocksClient {
 
     // Get metrics in Prometheus format
     pub async fn get_metrics(&self) -> ApiResult<String> {
-        let url = format!("{}/metrics", self.get_base_url());
+        let password = self.password()?;
 
         let response = self
-            .http_client
-            .get(&url)
-            .basic_auth(&self.cluster.username, Some(&self.cluster.password_encrypted))
-            .send()
-            .await
-            .map_err(|e| ApiError::cluster_connection_failed(format!("Request failed: {}", e)))?;
+            .send_with_failover(self.fe_endpoints(), "/metrics", |url| {
+                self.http_client
+                    .get(url)
+                    .basic_auth(&self.cluster.username, Some(&password))
+            })
+            .await?;
 
         if !response.status().is_success() {
             return Err(ApiError::cluster_connection_failed(format!(
@@ -8,6 +8,7 @@ use crate::models::{
     UpdateOrderRequest,
 };
 use crate::services::{ClusterService, MySQLClient, MySQLPoolManager};
+use crate::utils::sql_lexer::{self, StatementKind};
 use crate::utils::{ApiError, ApiResult};
 
 #[derive(Clone)]
@@ -398,28 +399,30 @@ impl SystemFunctionService {
 
     // Validate SQL safety (only allow SELECT/SHOW)
     fn validate_sql_safety(&self, sql: &str) -> ApiResult<()> {
-        let trimmed_sql = sql.trim().to_uppercase();
+        let statements = sql_lexer::split_statements(sql);
+        if statements.len() > 1 {
+            return Err(ApiError::sql_safety_violation("SQL查询只能包含一条语句"));
+        }
 
-        // Check if starts with SELECT or SHOW
-        if !trimmed_sql.starts_with("SELECT") && !trimmed_sql.starts_with("SHOW") {
+        // Only SELECT (including CTEs) and SHOW are allowed
+        let kind = statements
+            .first()
+            .map_or(StatementKind::Empty, |stmt| sql_lexer::classify(stmt));
+        if !matches!(kind, StatementKind::Select | StatementKind::Show) {
             return Err(ApiError::invalid_sql("Only SELECT and SHOW type SQL queries are allowed"));
         }
 
-        // Check for dangerous keywords
-        let dangerous_keywords = vec![
+        // Check for dangerous keywords (whole words only, outside strings and comments)
+        let dangerous_keywords = [
             "DROP", "DELETE", "UPDATE", "INSERT", "ALTER", "CREATE", "TRUNCATE", "EXEC", "EXECUTE",
             "CALL", "GRANT", "REVOKE", "COMMIT", "ROLLBACK",
         ];
 
-        for keyword in dangerous_keywords {
-            if trimmed_sql.contains(&format!(" {}", keyword))
-                || trimmed_sql.contains(&format!("{} ", keyword))
-            {
-                return Err(ApiError::sql_safety_violation(format!(
-                    "SQL查询包含不允许的关键字：{}",
-                    keyword
-                )));
-            }
+        if let Some(keyword) = sql_lexer::find_keyword(sql, &dangerous_keywords) {
+            return Err(ApiError::sql_safety_violation(format!(
+                "SQL查询包含不允许的关键字：{}",
+                keyword
+            )));
         }
 
         Ok(())
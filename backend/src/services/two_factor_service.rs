@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use sqlx::{FromRow, SqlitePool};
+
+use crate::models::{AuthProvider, RecoveryCodes, TwoFactorSetup, TwoFactorStatus, User};
+use crate::utils::crypto::{random_hex, sha256_hex};
+use crate::utils::{ApiError, ApiResult, CredentialCipher, totp};
+
+/// Issuer shown next to the account in authenticator apps
+const TOTP_ISSUER: &str = "StarRocks Admin";
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(FromRow)]
+struct TotpRecord {
+    secret: String,
+    enabled: bool,
+    last_used_step: Option<i64>,
+}
+
+/// TOTP second factor of local users, with one-time recovery codes
+pub struct TwoFactorService {
+    pool: SqlitePool,
+    cipher: Arc<CredentialCipher>,
+}
+
+impl TwoFactorService {
+    pub fn new(pool: SqlitePool, cipher: Arc<CredentialCipher>) -> Self {
+        Self { pool, cipher }
+    }
+
+    pub async fn status(&self, user_id: i64) -> ApiResult<TwoFactorStatus> {
+        let enabled = self
+            .fetch(user_id)
+            .await?
+            .is_some_and(|record| record.enabled);
+        let recovery_codes_remaining: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM user_recovery_codes WHERE user_id = ? AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(TwoFactorStatus { enabled, recovery_codes_remaining })
+    }
+
+    /// Generate a new secret, pending until confirmed with `enable`
+    pub async fn begin_setup(&self, user: &User) -> ApiResult<TwoFactorSetup> {
+        if user.auth_provider != AuthProvider::Local.as_str() {
+            return Err(ApiError::validation_error(format!(
+                "The second factor of {} users is managed by their identity provider",
+                user.auth_provider
+            )));
+        }
+        if self
+            .fetch(user.id)
+            .await?
+            .is_some_and(|record| record.enabled)
+        {
+            return Err(ApiError::validation_error(
+                "Two-factor authentication is already enabled, disable it first",
+            ));
+        }
+
+        let secret = totp::generate_secret();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO user_totp (user_id, secret, enabled) VALUES (?, ?, 0) \
+             ON CONFLICT(user_id) DO UPDATE SET secret = excluded.secret, enabled = 0, \
+             last_used_step = NULL, created_at = CURRENT_TIMESTAMP, enabled_at = NULL",
+        )
+        .bind(user.id)
+        .bind(self.cipher.encrypt(&secret)?)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let otpauth_url = totp::provisioning_uri(TOTP_ISSUER, &user.username, &secret);
+        Ok(TwoFactorSetup { secret, otpauth_url })
+    }
+
+    /// Confirm the pending secret with a code of the app and hand out recovery codes
+    pub async fn enable(&self, user_id: i64, code: &str) -> ApiResult<RecoveryCodes> {
+        let record = match self.fetch(user_id).await? {
+            Some(record) if !record.enabled => record,
+            Some(_) => {
+                return Err(ApiError::validation_error(
+                    "Two-factor authentication is already enabled",
+                ));
+            },
+            None => {
+                return Err(ApiError::validation_error(
+                    "Start the two-factor setup before enabling it",
+                ));
+            },
+        };
+
+        let secret = self.cipher.decrypt(&record.secret)?;
+        let Some(step) = totp::verify(&secret, code, Utc::now().timestamp(), None)? else {
+            return Err(ApiError::validation_error("Invalid two-factor code"));
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE user_totp SET enabled = 1, enabled_at = CURRENT_TIMESTAMP, last_used_step = ? \
+             WHERE user_id = ?",
+        )
+        .bind(step)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        let codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!("Two-factor authentication enabled for user_id={}", user_id);
+        Ok(codes)
+    }
+
+    /// Turn the second factor off, proven with a code of the app or a recovery code
+    pub async fn disable(&self, user_id: i64, code: &str) -> ApiResult<()> {
+        self.require_code(user_id, code).await?;
+        self.reset(user_id).await?;
+        tracing::info!("Two-factor authentication disabled for user_id={}", user_id);
+        Ok(())
+    }
+
+    /// Replace all recovery codes, proven with a code of the app or a recovery code
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: i64,
+        code: &str,
+    ) -> ApiResult<RecoveryCodes> {
+        self.require_code(user_id, code).await?;
+        let mut tx = self.pool.begin().await?;
+        let codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+        tx.commit().await?;
+        Ok(codes)
+    }
+
+    /// Remove the second factor of a user (administrators, for users who lost their device)
+    pub async fn reset(&self, user_id: i64) -> ApiResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Second step of a password login: users with two-factor authentication need a valid code
+    pub async fn verify_login(&self, user_id: i64, code: Option<&str>) -> ApiResult<()> {
+        let Some(record) = self.fetch(user_id).await?.filter(|record| record.enabled) else {
+            return Ok(());
+        };
+        let Some(code) = code.map(str::trim).filter(|code| !code.is_empty()) else {
+            return Err(ApiError::TwoFactorRequired);
+        };
+        if !self.consume_code(user_id, &record, code).await? {
+            tracing::warn!("Login failed: invalid two-factor code for user_id={}", user_id);
+            return Err(ApiError::invalid_credentials());
+        }
+        Ok(())
+    }
+
+    async fn require_code(&self, user_id: i64, code: &str) -> ApiResult<()> {
+        let Some(record) = self.fetch(user_id).await?.filter(|record| record.enabled) else {
+            return Err(ApiError::validation_error("Two-factor authentication is not enabled"));
+        };
+        if !self.consume_code(user_id, &record, code).await? {
+            return Err(ApiError::validation_error("Invalid two-factor code"));
+        }
+        Ok(())
+    }
+
+    /// Accept a code of the app once per time step, or an unused recovery code once
+    async fn consume_code(&self, user_id: i64, record: &TotpRecord, code: &str) -> ApiResult<bool> {
+        let secret = self.cipher.decrypt(&record.secret)?;
+        let now = Utc::now().timestamp();
+        if let Some(step) = totp::verify(&secret, code, now, record.last_used_step)? {
+            // Conditional so that two concurrent logins cannot both use the code
+            let updated = sqlx::query(
+                "UPDATE user_totp SET last_used_step = ? \
+                 WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+            )
+            .bind(step)
+            .bind(user_id)
+            .bind(step)
+            .execute(&self.pool)
+            .await?;
+            return Ok(updated.rows_affected() == 1);
+        }
+
+        let used = sqlx::query(
+            "UPDATE user_recovery_codes SET used_at = CURRENT_TIMESTAMP WHERE id = (\
+             SELECT id FROM user_recovery_codes \
+             WHERE user_id = ? AND code_hash = ? AND used_at IS NULL LIMIT 1)",
+        )
+        .bind(user_id)
+        .bind(sha256_hex(&normalize_recovery_code(code)))
+        .execute(&self.pool)
+        .await?;
+        if used.rows_affected() == 1 {
+            tracing::info!("Recovery code used by user_id={}", user_id);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    async fn replace_recovery_codes(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        user_id: i64,
+    ) -> ApiResult<RecoveryCodes> {
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await?;
+
+        let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+        for _ in 0..RECOVERY_CODE_COUNT {
+            let raw = random_hex(5);
+            sqlx::query("INSERT INTO user_recovery_codes (user_id, code_hash) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(sha256_hex(&raw))
+                .execute(&mut **tx)
+                .await?;
+            codes.push(format!("{}-{}", &raw[..5], &raw[5..]));
+        }
+        Ok(RecoveryCodes { codes })
+    }
+
+    async fn fetch(&self, user_id: i64) -> ApiResult<Option<TotpRecord>> {
+        let record = sqlx::query_as(
+            "SELECT secret, enabled, last_used_step FROM user_totp WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(record)
+    }
+}
+
+/// Recovery codes are handed out as `xxxxx-xxxxx`; accept them without the dash or in capitals
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
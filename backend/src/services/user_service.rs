@@ -5,10 +5,13 @@ use bcrypt::{DEFAULT_COST, hash};
 use chrono::{DateTime, Utc};
 use sqlx::{FromRow, SqlitePool, Transaction, sqlite::Sqlite};
 
+use crate::config::PasswordPolicyConfig;
 use crate::models::{
-    AdminCreateUserRequest, AdminUpdateUserRequest, RoleResponse, User, UserWithRolesResponse,
+    AdminCreateUserRequest, AdminUpdateUserRequest, CreateServiceAccountRequest, RoleResponse,
+    ServiceAccount, User, UserWithRolesResponse,
 };
 use crate::services::casbin_service::CasbinService;
+use crate::utils::crypto::random_hex;
 use crate::utils::organization_filter::apply_organization_filter;
 use crate::utils::{ApiError, ApiResult};
 
@@ -24,15 +27,48 @@ struct UserRoleRecord {
     created_at: DateTime<Utc>,
 }
 
+const SERVICE_ACCOUNT_QUERY: &str = "SELECT u.id, u.username, sa.description, u.organization_id, \
+     sa.created_by, sa.created_at FROM users u JOIN service_accounts sa ON sa.user_id = u.id";
+
+#[derive(FromRow)]
+struct ServiceAccountRecord {
+    id: i64,
+    username: String,
+    description: Option<String>,
+    organization_id: Option<i64>,
+    created_by: Option<i64>,
+    created_at: DateTime<Utc>,
+}
+
+impl ServiceAccountRecord {
+    fn into_service_account(self, roles: Vec<RoleResponse>) -> ServiceAccount {
+        ServiceAccount {
+            id: self.id,
+            name: self.username,
+            description: self.description,
+            organization_id: self.organization_id,
+            roles,
+            created_by: self.created_by,
+            created_at: self.created_at,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct UserService {
     pool: SqlitePool,
     casbin_service: Arc<CasbinService>,
+    password_policy: PasswordPolicyConfig,
 }
 
 impl UserService {
     pub fn new(pool: SqlitePool, casbin_service: Arc<CasbinService>) -> Self {
-        Self { pool, casbin_service }
+        Self { pool, casbin_service, password_policy: PasswordPolicyConfig::default() }
+    }
+
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicyConfig) -> Self {
+        self.password_policy = password_policy;
+        self
     }
 
     pub async fn list_users(
@@ -55,6 +91,9 @@ impl UserService {
             organization_id: Option<i64>,
             created_at: DateTime<Utc>,
             updated_at: DateTime<Utc>,
+            is_active: bool,
+            auth_provider: String,
+            locked_until: Option<DateTime<Utc>>,
             organization_name: Option<String>,
         }
 
@@ -76,6 +115,9 @@ impl UserService {
                     organization_id: user_with_org.organization_id,
                     created_at: user_with_org.created_at,
                     updated_at: user_with_org.updated_at,
+                    is_active: user_with_org.is_active,
+                    auth_provider: user_with_org.auth_provider,
+                    locked_until: user_with_org.locked_until,
                 };
                 let roles = roles_map.get(&user.id);
                 self.compose_user_with_org(user, user_with_org.organization_name, roles)
@@ -106,14 +148,33 @@ impl UserService {
         if !is_super_admin && organization_id.is_none() {
             return Err(ApiError::forbidden("Organization context required for user creation"));
         }
+        self.password_policy
+            .check(&req.username, &req.password)
+            .map_err(ApiError::validation_error)?;
 
         let target_org_id = self
             .resolve_target_org(req.organization_id, organization_id, is_super_admin)
             .await?;
 
         let mut tx = self.pool.begin().await?;
+        let user_id = self
+            .insert_user(&mut tx, &req, target_org_id, is_super_admin)
+            .await?;
+        tx.commit().await?;
 
-        self.ensure_username_available(&mut tx, &req.username, None)
+        self.get_user(user_id, organization_id, is_super_admin)
+            .await
+    }
+
+    // Insert a user with its organization and roles (the caller commits)
+    async fn insert_user(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        req: &AdminCreateUserRequest,
+        target_org_id: i64,
+        is_super_admin: bool,
+    ) -> ApiResult<i64> {
+        self.ensure_username_available(tx, &req.username, None)
             .await?;
 
         let password_hash = hash(&req.password, DEFAULT_COST)
@@ -136,24 +197,15 @@ impl UserService {
         let user_id = result.last_insert_rowid();
 
         // Assign user to organization
-        self.upsert_user_organization(&mut tx, user_id, target_org_id)
+        self.upsert_user_organization(tx, user_id, target_org_id)
             .await?;
 
         if let Some(role_ids) = &req.role_ids {
-            self.replace_user_roles(
-                &mut tx,
-                user_id,
-                role_ids,
-                Some(target_org_id),
-                is_super_admin,
-            )
-            .await?;
+            self.replace_user_roles(tx, user_id, role_ids, Some(target_org_id), is_super_admin)
+                .await?;
         }
 
-        tx.commit().await?;
-
-        self.get_user(user_id, organization_id, is_super_admin)
-            .await
+        Ok(user_id)
     }
 
     pub async fn update_user(
@@ -211,14 +263,20 @@ impl UserService {
         }
 
         if let Some(password) = &req.password {
+            let username = req.username.as_deref().unwrap_or(&existing_user.username);
+            self.password_policy
+                .check(username, password)
+                .map_err(ApiError::validation_error)?;
             let password_hash = hash(password, DEFAULT_COST).map_err(|err| {
                 ApiError::internal_error(format!("Failed to hash password: {}", err))
             })?;
 
             {
                 let conn = tx.as_mut();
+                // A new password also lifts a lock caused by failed logins
                 sqlx::query(
-                    "UPDATE users SET password_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    "UPDATE users SET password_hash = ?, failed_login_attempts = 0, \
+                     locked_until = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
                 )
                 .bind(&password_hash)
                 .bind(user_id)
@@ -232,6 +290,17 @@ impl UserService {
                 .await?;
         }
 
+        if let Some(is_active) = req.is_active {
+            let conn = tx.as_mut();
+            sqlx::query(
+                "UPDATE users SET is_active = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(is_active)
+            .bind(user_id)
+            .execute(conn)
+            .await?;
+        }
+
         if let Some(new_org_id) = req.organization_id {
             // Only check if organization_id is being changed (not just present)
             if !is_super_admin && Some(new_org_id) != existing_user.organization_id {
@@ -321,6 +390,155 @@ impl UserService {
         Ok(())
     }
 
+    // ========================================
+    // Service accounts
+    // ========================================
+
+    pub async fn list_service_accounts(
+        &self,
+        requestor_org: Option<i64>,
+        is_super_admin: bool,
+    ) -> ApiResult<Vec<ServiceAccount>> {
+        let base_query = format!("{} ORDER BY u.id", SERVICE_ACCOUNT_QUERY);
+        let (filtered_query, _) =
+            apply_organization_filter(&base_query, is_super_admin, requestor_org);
+        let records: Vec<ServiceAccountRecord> = sqlx::query_as(&filtered_query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let roles_map = self.load_all_user_roles().await?;
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let roles = roles_map.get(&record.id).cloned().unwrap_or_default();
+                record.into_service_account(roles)
+            })
+            .collect())
+    }
+
+    pub async fn get_service_account(
+        &self,
+        user_id: i64,
+        requestor_org: Option<i64>,
+        is_super_admin: bool,
+    ) -> ApiResult<ServiceAccount> {
+        let base_query = format!("{} WHERE u.id = ?", SERVICE_ACCOUNT_QUERY);
+        let (filtered_query, _) =
+            apply_organization_filter(&base_query, is_super_admin, requestor_org);
+        let record: ServiceAccountRecord = sqlx::query_as(&filtered_query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ApiError::not_found("Service account not found"))?;
+
+        let roles = self.fetch_user_roles(user_id).await?;
+        Ok(record.into_service_account(roles))
+    }
+
+    /// Create a user that only authenticates with API tokens. Unless the creator is a super
+    /// admin, every permission of the account's roles must be held by the creator.
+    pub async fn create_service_account(
+        &self,
+        req: CreateServiceAccountRequest,
+        created_by: i64,
+        organization_id: Option<i64>,
+        is_super_admin: bool,
+    ) -> ApiResult<ServiceAccount> {
+        if !is_super_admin && organization_id.is_none() {
+            return Err(ApiError::forbidden(
+                "Organization context required for service account creation",
+            ));
+        }
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(ApiError::validation_error("Service account name cannot be empty"));
+        }
+        if !is_super_admin {
+            self.ensure_roles_within_user(&req.role_ids, created_by)
+                .await?;
+        }
+
+        // Nobody knows the password; password login is refused for service accounts anyway
+        let user_req = AdminCreateUserRequest {
+            username: name.to_string(),
+            password: random_hex(32),
+            email: None,
+            avatar: None,
+            role_ids: Some(req.role_ids),
+            organization_id: req.organization_id,
+        };
+
+        let target_org_id = self
+            .resolve_target_org(user_req.organization_id, organization_id, is_super_admin)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let user_id = self
+            .insert_user(&mut tx, &user_req, target_org_id, is_super_admin)
+            .await?;
+        sqlx::query(
+            "INSERT INTO service_accounts (user_id, description, created_by) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(&req.description)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!("User {} created service account {} (ID: {})", created_by, name, user_id);
+        self.get_service_account(user_id, organization_id, is_super_admin)
+            .await
+    }
+
+    /// Delete a service account with its tokens
+    pub async fn delete_service_account(
+        &self,
+        user_id: i64,
+        requestor_org: Option<i64>,
+        is_super_admin: bool,
+    ) -> ApiResult<()> {
+        self.get_service_account(user_id, requestor_org, is_super_admin)
+            .await?;
+        self.delete_user(user_id, requestor_org, is_super_admin)
+            .await
+    }
+
+    // Refuse roles granting a permission the user does not hold
+    async fn ensure_roles_within_user(&self, role_ids: &[i64], user_id: i64) -> ApiResult<()> {
+        for role_id in role_ids {
+            let missing: Option<(String,)> = sqlx::query_as(
+                r#"
+                SELECT p.code
+                FROM role_permissions rp
+                JOIN permissions p ON p.id = rp.permission_id
+                WHERE rp.role_id = ?
+                  AND rp.permission_id NOT IN (
+                      SELECT held.permission_id
+                      FROM role_permissions held
+                      JOIN user_roles ur ON ur.role_id = held.role_id
+                      WHERE ur.user_id = ?
+                  )
+                ORDER BY p.code
+                LIMIT 1
+                "#,
+            )
+            .bind(role_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some((code,)) = missing {
+                return Err(ApiError::forbidden(format!(
+                    "Role {} grants '{}', which you do not hold",
+                    role_id, code
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn compose_user_with_org(
         &self,
         user: User,
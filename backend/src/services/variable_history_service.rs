@@ -0,0 +1,468 @@
+// Variable History Service
+// Purpose: Variable changes made through starrocks-admin (who, when, old and new value),
+//          rollback of a GLOBAL change, and snapshots of all GLOBAL variables taken by the
+//          metrics collector with a diff between any two of them
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::models::{
+    Cluster, Variable, VariableChange, VariableChangeFilter, VariableDiffEntry, VariableDiffKind,
+    VariableSnapshot, VariableSnapshotDiff, VariableSnapshotSummary,
+};
+use crate::services::load_job_service::escape_string;
+use crate::services::{MySQLClient, MySQLPoolManager};
+use crate::utils::{ApiError, ApiResult};
+
+/// Changes returned when the history request sets no limit
+pub const DEFAULT_HISTORY_LIMIT: i64 = 100;
+/// Upper bound of the history and snapshot list limits
+pub const MAX_HISTORY_LIMIT: i64 = 1000;
+/// The collector stores a snapshot when the variables changed, and at least this often
+pub const SNAPSHOT_INTERVAL_SECS: i64 = 3600;
+
+const CHANGE_COLUMNS: &str = "id, cluster_id, variable_name, scope, old_value, new_value, \
+     changed_by, changed_by_username, rollback_of, created_at";
+
+/// Variables of a snapshot, by name
+pub type VariableValues = BTreeMap<String, String>;
+
+#[derive(Debug, sqlx::FromRow)]
+struct SnapshotRow {
+    id: i64,
+    cluster_id: i64,
+    variable_count: i64,
+    variables: String,
+    created_at: DateTime<Utc>,
+}
+
+impl SnapshotRow {
+    fn summary(&self) -> VariableSnapshotSummary {
+        VariableSnapshotSummary {
+            id: self.id,
+            cluster_id: self.cluster_id,
+            variable_count: self.variable_count,
+            created_at: self.created_at,
+        }
+    }
+
+    fn values(&self) -> ApiResult<VariableValues> {
+        Ok(serde_json::from_str(&self.variables)?)
+    }
+}
+
+/// Change to record once the SET statement succeeded
+struct NewChange<'a> {
+    cluster_id: i64,
+    variable_name: &'a str,
+    scope: &'static str,
+    old_value: Option<String>,
+    new_value: String,
+    changed_by: i64,
+    changed_by_username: &'a str,
+    rollback_of: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct VariableHistoryService {
+    db: SqlitePool,
+    mysql_pool_manager: Arc<MySQLPoolManager>,
+}
+
+impl VariableHistoryService {
+    pub fn new(db: SqlitePool, mysql_pool_manager: Arc<MySQLPoolManager>) -> Self {
+        Self { db, mysql_pool_manager }
+    }
+
+    /// Global variables are read and changed on the leader FE
+    async fn client(&self, cluster: &Cluster, scope: &str) -> ApiResult<MySQLClient> {
+        let pool = if scope == "GLOBAL" {
+            self.mysql_pool_manager.get_leader_pool(cluster).await?
+        } else {
+            self.mysql_pool_manager.get_pool(cluster).await?
+        };
+        Ok(MySQLClient::from_pool(pool))
+    }
+
+    // ========================================
+    // Changes
+    // ========================================
+
+    /// Set a variable and record the change with the value it replaced. `value` is the SQL
+    /// literal of the SET statement (quoted or not).
+    pub async fn update_variable(
+        &self,
+        cluster: &Cluster,
+        variable_name: &str,
+        scope: &str,
+        value: &str,
+        changed_by: i64,
+        changed_by_username: &str,
+    ) -> ApiResult<VariableChange> {
+        let scope = normalize_scope(scope)?;
+        let old_value = self
+            .set_variable(cluster, scope, variable_name, value)
+            .await?;
+        self.record_change(NewChange {
+            cluster_id: cluster.id,
+            variable_name,
+            scope,
+            old_value,
+            new_value: unquote(value).to_string(),
+            changed_by,
+            changed_by_username,
+            rollback_of: None,
+        })
+        .await
+    }
+
+    /// Set the variable of a GLOBAL change back to the value it replaced; the rollback is
+    /// recorded as a new change
+    pub async fn rollback(
+        &self,
+        cluster: &Cluster,
+        change_id: i64,
+        changed_by: i64,
+        changed_by_username: &str,
+    ) -> ApiResult<VariableChange> {
+        let change = self.get_change(cluster.id, change_id).await?;
+        if change.scope != "GLOBAL" {
+            return Err(ApiError::validation_error(
+                "Only GLOBAL changes can be rolled back: a SESSION value only applied to one connection",
+            ));
+        }
+        let previous = change.old_value.ok_or_else(|| {
+            ApiError::validation_error(format!(
+                "The value of {} before change {} is unknown",
+                change.variable_name, change_id
+            ))
+        })?;
+
+        let old_value = self
+            .set_variable(cluster, "GLOBAL", &change.variable_name, &sql_literal(&previous))
+            .await?;
+        self.record_change(NewChange {
+            cluster_id: cluster.id,
+            variable_name: &change.variable_name,
+            scope: "GLOBAL",
+            old_value,
+            new_value: previous,
+            changed_by,
+            changed_by_username,
+            rollback_of: Some(change_id),
+        })
+        .await
+    }
+
+    /// Run SET on the cluster; returns the value before the change, if it could be read
+    async fn set_variable(
+        &self,
+        cluster: &Cluster,
+        scope: &'static str,
+        variable_name: &str,
+        value: &str,
+    ) -> ApiResult<Option<String>> {
+        if !is_valid_variable_name(variable_name) {
+            return Err(ApiError::invalid_data(format!(
+                "Invalid variable name: {}",
+                variable_name
+            )));
+        }
+        let client = self.client(cluster, scope).await?;
+
+        let sql = format!("SHOW {} VARIABLES LIKE '{}'", scope, escape_string(variable_name));
+        let old_value = match client.query_raw(&sql).await {
+            Ok((_, rows)) => rows.into_iter().find_map(|row| {
+                let mut columns = row.into_iter();
+                let name = columns.next()?;
+                name.eq_ignore_ascii_case(variable_name)
+                    .then(|| columns.next().unwrap_or_default())
+            }),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read variable {} before the change: {}",
+                    variable_name,
+                    e
+                );
+                None
+            },
+        };
+
+        client
+            .execute(&format!("SET {} {} = {}", scope, variable_name, value))
+            .await?;
+        Ok(old_value)
+    }
+
+    async fn record_change(&self, change: NewChange<'_>) -> ApiResult<VariableChange> {
+        let id = sqlx::query(
+            "INSERT INTO variable_changes (cluster_id, variable_name, scope, old_value, new_value,
+             changed_by, changed_by_username, rollback_of)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(change.cluster_id)
+        .bind(change.variable_name)
+        .bind(change.scope)
+        .bind(&change.old_value)
+        .bind(&change.new_value)
+        .bind(change.changed_by)
+        .bind(change.changed_by_username)
+        .bind(change.rollback_of)
+        .execute(&self.db)
+        .await?
+        .last_insert_rowid();
+        self.get_change(change.cluster_id, id).await
+    }
+
+    pub async fn get_change(&self, cluster_id: i64, change_id: i64) -> ApiResult<VariableChange> {
+        sqlx::query_as::<_, VariableChange>(&format!(
+            "SELECT {} FROM variable_changes WHERE id = ? AND cluster_id = ?",
+            CHANGE_COLUMNS
+        ))
+        .bind(change_id)
+        .bind(cluster_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Variable change {} not found", change_id)))
+    }
+
+    /// Changes of a cluster, newest first
+    pub async fn list_changes(
+        &self,
+        cluster_id: i64,
+        filter: &VariableChangeFilter,
+    ) -> ApiResult<Vec<VariableChange>> {
+        let mut sql =
+            format!("SELECT {} FROM variable_changes WHERE cluster_id = ?", CHANGE_COLUMNS);
+        if filter.variable.is_some() {
+            sql.push_str(" AND LOWER(variable_name) = LOWER(?)");
+        }
+        let scope = filter.scope.as_deref().map(normalize_scope).transpose()?;
+        if scope.is_some() {
+            sql.push_str(" AND scope = ?");
+        }
+        sql.push_str(" ORDER BY id DESC LIMIT ?");
+
+        let mut query = sqlx::query_as::<_, VariableChange>(&sql).bind(cluster_id);
+        if let Some(variable) = &filter.variable {
+            query = query.bind(variable);
+        }
+        if let Some(scope) = scope {
+            query = query.bind(scope);
+        }
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+        Ok(query.bind(limit).fetch_all(&self.db).await?)
+    }
+
+    // ========================================
+    // Snapshots
+    // ========================================
+
+    /// Read the GLOBAL variables of a cluster and store them. Unless `force` is set, nothing
+    /// is stored when they equal the latest snapshot and that one is recent enough.
+    pub async fn take_snapshot(
+        &self,
+        cluster: &Cluster,
+        force: bool,
+    ) -> ApiResult<Option<VariableSnapshotSummary>> {
+        let client = self.client(cluster, "GLOBAL").await?;
+        let (_, rows) = client.query_raw("SHOW GLOBAL VARIABLES").await?;
+        let variables: VariableValues = rows
+            .into_iter()
+            .filter_map(|row| {
+                let mut columns = row.into_iter();
+                Some((columns.next()?, columns.next().unwrap_or_default()))
+            })
+            .collect();
+        self.save_snapshot(cluster.id, &variables, force).await
+    }
+
+    /// Store a snapshot (see `take_snapshot`)
+    pub async fn save_snapshot(
+        &self,
+        cluster_id: i64,
+        variables: &VariableValues,
+        force: bool,
+    ) -> ApiResult<Option<VariableSnapshotSummary>> {
+        if !force {
+            let latest = sqlx::query_as::<_, SnapshotRow>(
+                "SELECT id, cluster_id, variable_count, variables, created_at
+                 FROM variable_snapshots WHERE cluster_id = ? ORDER BY id DESC LIMIT 1",
+            )
+            .bind(cluster_id)
+            .fetch_optional(&self.db)
+            .await?;
+            if let Some(latest) = latest
+                && Utc::now() - latest.created_at < Duration::seconds(SNAPSHOT_INTERVAL_SECS)
+                && latest.values()? == *variables
+            {
+                return Ok(None);
+            }
+        }
+
+        let summary = sqlx::query_as::<_, VariableSnapshotSummary>(
+            "INSERT INTO variable_snapshots (cluster_id, variable_count, variables, created_at)
+             VALUES (?, ?, ?, ?)
+             RETURNING id, cluster_id, variable_count, created_at",
+        )
+        .bind(cluster_id)
+        .bind(variables.len() as i64)
+        .bind(serde_json::to_string(variables)?)
+        .bind(Utc::now())
+        .fetch_one(&self.db)
+        .await?;
+        Ok(Some(summary))
+    }
+
+    /// Snapshots of a cluster, newest first
+    pub async fn list_snapshots(
+        &self,
+        cluster_id: i64,
+        limit: Option<i64>,
+    ) -> ApiResult<Vec<VariableSnapshotSummary>> {
+        Ok(sqlx::query_as::<_, VariableSnapshotSummary>(
+            "SELECT id, cluster_id, variable_count, created_at FROM variable_snapshots
+             WHERE cluster_id = ? ORDER BY id DESC LIMIT ?",
+        )
+        .bind(cluster_id)
+        .bind(
+            limit
+                .unwrap_or(DEFAULT_HISTORY_LIMIT)
+                .clamp(1, MAX_HISTORY_LIMIT),
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn load_snapshot(&self, cluster_id: i64, snapshot_id: i64) -> ApiResult<SnapshotRow> {
+        sqlx::query_as::<_, SnapshotRow>(
+            "SELECT id, cluster_id, variable_count, variables, created_at
+             FROM variable_snapshots WHERE id = ? AND cluster_id = ?",
+        )
+        .bind(snapshot_id)
+        .bind(cluster_id)
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Variable snapshot {} not found", snapshot_id)))
+    }
+
+    pub async fn get_snapshot(
+        &self,
+        cluster_id: i64,
+        snapshot_id: i64,
+    ) -> ApiResult<VariableSnapshot> {
+        let row = self.load_snapshot(cluster_id, snapshot_id).await?;
+        Ok(VariableSnapshot {
+            summary: row.summary(),
+            variables: row
+                .values()?
+                .into_iter()
+                .map(|(name, value)| Variable { name, value })
+                .collect(),
+        })
+    }
+
+    /// Differences from snapshot `from` to snapshot `to`
+    pub async fn diff_snapshots(
+        &self,
+        cluster_id: i64,
+        from: i64,
+        to: i64,
+    ) -> ApiResult<VariableSnapshotDiff> {
+        let from = self.load_snapshot(cluster_id, from).await?;
+        let to = self.load_snapshot(cluster_id, to).await?;
+        let (changes, unchanged) = diff_variables(&from.values()?, &to.values()?);
+        Ok(VariableSnapshotDiff { from: from.summary(), to: to.summary(), changes, unchanged })
+    }
+
+    /// Delete snapshots taken before `cutoff`; returns the number of deleted rows. Changes
+    /// are kept as an audit trail.
+    pub async fn cleanup_before(&self, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM variable_snapshots WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&self.db)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// GLOBAL or SESSION, case-insensitive
+pub fn normalize_scope(scope: &str) -> ApiResult<&'static str> {
+    match scope.to_uppercase().as_str() {
+        "GLOBAL" => Ok("GLOBAL"),
+        "SESSION" => Ok("SESSION"),
+        _ => Err(ApiError::invalid_data("Invalid scope. Must be GLOBAL or SESSION")),
+    }
+}
+
+/// Variable names are identifiers, so they can be put into SET unquoted
+pub fn is_valid_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Value of a SET literal as SHOW VARIABLES reports it: without the surrounding quotes
+pub fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['\'', '"'] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// SET literal of a value read from SHOW VARIABLES: numbers and booleans as they are,
+/// anything else quoted
+pub fn sql_literal(value: &str) -> String {
+    if value.parse::<f64>().is_ok()
+        || value.eq_ignore_ascii_case("true")
+        || value.eq_ignore_ascii_case("false")
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", escape_string(value))
+    }
+}
+
+/// Variables added, removed or changed from `from` to `to`, and the number of unchanged ones
+pub fn diff_variables(from: &VariableValues, to: &VariableValues) -> (Vec<VariableDiffEntry>, i64) {
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for (name, from_value) in from {
+        match to.get(name) {
+            Some(to_value) if to_value == from_value => unchanged += 1,
+            Some(to_value) => changes.push(VariableDiffEntry {
+                name: name.clone(),
+                kind: VariableDiffKind::Changed,
+                from_value: Some(from_value.clone()),
+                to_value: Some(to_value.clone()),
+            }),
+            None => changes.push(VariableDiffEntry {
+                name: name.clone(),
+                kind: VariableDiffKind::Removed,
+                from_value: Some(from_value.clone()),
+                to_value: None,
+            }),
+        }
+    }
+    for (name, to_value) in to {
+        if !from.contains_key(name) {
+            changes.push(VariableDiffEntry {
+                name: name.clone(),
+                kind: VariableDiffKind::Added,
+                from_value: None,
+                to_value: Some(to_value.clone()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    (changes, unchanged)
+}
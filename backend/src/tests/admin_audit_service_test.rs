@@ -0,0 +1,394 @@
+// Admin audit service and middleware tests
+
+use crate::middleware::{OrgContext, admin_audit_middleware};
+use crate::models::{AdminAuditQuery, CreateClusterRequest, NewAdminAuditLog};
+use crate::services::AdminAuditService;
+use crate::services::admin_audit_service::redact_payload;
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::ApiError;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::{delete, get, put};
+use axum::{Json, Router};
+use chrono::{Duration, Utc};
+use tower::ServiceExt;
+
+fn entry(
+    username: &str,
+    organization_id: Option<i64>,
+    action: &str,
+    success: bool,
+) -> NewAdminAuditLog {
+    NewAdminAuditLog {
+        username: username.to_string(),
+        organization_id,
+        action: action.to_string(),
+        method: "POST".to_string(),
+        path: format!("/api/{}", action.replace(':', "/")),
+        status_code: if success { 200 } else { 400 },
+        success,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_list_is_scoped_by_organization() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let service = AdminAuditService::new(pool.clone());
+
+    service
+        .record(entry("alice", Some(data.org1_id), "clusters:queries:kill", true))
+        .await
+        .unwrap();
+    service
+        .record(entry("alice", Some(data.org1_id), "clusters:variables:update", false))
+        .await
+        .unwrap();
+    service
+        .record(entry("bob", Some(data.org2_id), "clusters:queries:kill", true))
+        .await
+        .unwrap();
+    service
+        .record(entry("root", None, "roles:create", true))
+        .await
+        .unwrap();
+
+    let all = AdminAuditQuery::default();
+    let org1 = service.list(Some(data.org1_id), false, &all).await.unwrap();
+    assert_eq!(org1.total, 2);
+    assert!(
+        org1.items
+            .iter()
+            .all(|item| item.organization_id == Some(data.org1_id))
+    );
+
+    // The organization filter is ignored for non super admins
+    let other_org = AdminAuditQuery { organization_id: Some(data.org2_id), ..Default::default() };
+    assert_eq!(
+        service
+            .list(Some(data.org1_id), false, &other_org)
+            .await
+            .unwrap()
+            .total,
+        2
+    );
+
+    // Users without an organization only see entries without one
+    assert_eq!(service.list(None, false, &all).await.unwrap().total, 1);
+
+    assert_eq!(service.list(None, true, &all).await.unwrap().total, 4);
+    assert_eq!(service.list(None, true, &other_org).await.unwrap().total, 1);
+}
+
+#[tokio::test]
+async fn test_list_filters_and_paging() {
+    let pool = create_test_db().await;
+    let service = AdminAuditService::new(pool.clone());
+
+    service
+        .record(entry("alice", None, "clusters:queries:kill", true))
+        .await
+        .unwrap();
+    service
+        .record(entry("alice", None, "clusters:variables:update", false))
+        .await
+        .unwrap();
+    service
+        .record(entry("bob", None, "clusters:queries:execute", true))
+        .await
+        .unwrap();
+    service
+        .record(NewAdminAuditLog {
+            target: Some("variable_name=query_timeout".to_string()),
+            ..entry("bob", None, "clusters:variables:update", true)
+        })
+        .await
+        .unwrap();
+
+    let list = |query: AdminAuditQuery| {
+        let service = service.clone();
+        async move { service.list(None, true, &query).await.unwrap() }
+    };
+
+    let queries =
+        list(AdminAuditQuery { action: Some("clusters:queries".into()), ..Default::default() })
+            .await;
+    assert_eq!(queries.total, 2);
+    // LIKE wildcards in the filter are literal
+    assert_eq!(
+        list(AdminAuditQuery { action: Some("clusters_".into()), ..Default::default() })
+            .await
+            .total,
+        0
+    );
+
+    assert_eq!(
+        list(AdminAuditQuery { success: Some(false), ..Default::default() })
+            .await
+            .total,
+        1
+    );
+    assert_eq!(
+        list(AdminAuditQuery { username: Some("bob".into()), ..Default::default() })
+            .await
+            .total,
+        2
+    );
+    assert_eq!(
+        list(AdminAuditQuery { keyword: Some("query_timeout".into()), ..Default::default() })
+            .await
+            .total,
+        1
+    );
+
+    let now = Utc::now();
+    let future =
+        AdminAuditQuery { start_time: Some(now + Duration::hours(1)), ..Default::default() };
+    assert_eq!(list(future).await.total, 0);
+    let window = AdminAuditQuery {
+        start_time: Some(now - Duration::hours(1)),
+        end_time: Some(now + Duration::hours(1)),
+        ..Default::default()
+    };
+    assert_eq!(list(window).await.total, 4);
+
+    let page =
+        list(AdminAuditQuery { page: Some(2), page_size: Some(3), ..Default::default() }).await;
+    assert_eq!((page.total, page.items.len(), page.page), (4, 1, 2));
+    // Newest first: the last page holds the first entry
+    assert_eq!(page.items[0].action, "clusters:queries:kill");
+}
+
+#[tokio::test]
+async fn test_export_csv() {
+    let pool = create_test_db().await;
+    let service = AdminAuditService::new(pool.clone());
+
+    service
+        .record(NewAdminAuditLog {
+            payload: Some(r#"{"sql":"SELECT 1, \"a\""}"#.to_string()),
+            error_message: Some("line1\nline2".to_string()),
+            ..entry("alice", None, "clusters:queries:execute", false)
+        })
+        .await
+        .unwrap();
+
+    let csv = service
+        .export_csv(None, true, &AdminAuditQuery::default())
+        .await
+        .unwrap();
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().starts_with("id,created_at,username,"));
+    assert!(csv.contains(r#""{""sql"":""SELECT 1, \""a\""""}""#));
+    assert!(csv.contains("\"line1\nline2\""));
+    assert!(csv.contains(",alice,"));
+}
+
+#[test]
+fn test_redact_payload() {
+    let payload = redact_payload(
+        br#"{"name":"c1","password":"secret","config":{"smtp_password":"x","to":["a@b.c"]},
+            "items":[{"api_key":"k","AccessToken":"t"}],
+            "tls":{"client_cert":"cert","client_key":"key","private_key_pem":"pem"}}"#,
+    )
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    assert_eq!(value["name"], "c1");
+    assert_eq!(value["password"], "******");
+    assert_eq!(value["config"]["smtp_password"], "******");
+    assert_eq!(value["config"]["to"][0], "a@b.c");
+    assert_eq!(value["items"][0]["api_key"], "******");
+    assert_eq!(value["items"][0]["AccessToken"], "******");
+    assert_eq!(value["tls"]["client_cert"], "cert");
+    assert_eq!(value["tls"]["client_key"], "******");
+    assert_eq!(value["tls"]["private_key_pem"], "******");
+
+    // Alert channel robot urls and webhook headers carry access tokens
+    let payload = redact_payload(
+        br#"{"name":"ops","channel_type":"webhook","config":{"url":"https://hook/?token=t",
+            "headers":{"Authorization":"Bearer t","X-Team":"ops"}},"issuer_url":"https://idp"}"#,
+    )
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    assert_eq!(value["channel_type"], "webhook");
+    assert_eq!(value["config"]["url"], "******");
+    assert_eq!(value["config"]["headers"]["Authorization"], "******");
+    assert_eq!(value["config"]["headers"]["X-Team"], "******");
+    assert_eq!(value["issuer_url"], "https://idp");
+
+    assert_eq!(redact_payload(b""), None);
+    assert_eq!(redact_payload(b"  \n"), None);
+    assert_eq!(redact_payload(b"plain text").unwrap(), "<10 bytes, not JSON>");
+
+    let long = format!(r#"{{"sql":"{}"}}"#, "x".repeat(10_000));
+    let truncated = redact_payload(long.as_bytes()).unwrap();
+    assert!(truncated.ends_with("...(truncated)"));
+    assert!(truncated.chars().count() < 4200);
+}
+
+#[tokio::test]
+async fn test_middleware_records_admin_actions() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    let cluster = state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "audit_cluster".to_string(),
+                description: None,
+                fe_host: "audit.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 30,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: Some(data.org1_id),
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            data.super_admin_user_id,
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+    state
+        .cluster_service
+        .set_active_cluster(cluster.id)
+        .await
+        .unwrap();
+
+    let org_ctx = OrgContext {
+        user_id: data.org1_admin_user_id,
+        username: "org1_admin".to_string(),
+        organization_id: Some(data.org1_id),
+        is_super_admin: false,
+        cluster_id: None,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: None,
+    };
+    let app = Router::new()
+        .route(
+            "/api/clusters/variables/:variable_name",
+            put(|Json(body): Json<serde_json::Value>| async move { Json(body) }),
+        )
+        .route(
+            "/api/clusters/queries/:query_id",
+            delete(|| async { Err::<(), _>(ApiError::not_found("Query not found")) }),
+        )
+        .route("/api/clusters/queries", get(|| async { "[]" }))
+        .route_layer(axum::middleware::from_fn_with_state(
+            std::sync::Arc::clone(&state),
+            admin_audit_middleware,
+        ))
+        .layer(axum::Extension(org_ctx))
+        .with_state(std::sync::Arc::clone(&state));
+
+    let body = r#"{"value":"300","scope":"GLOBAL","password":"hidden"}"#;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::put("/api/clusters/variables/query_timeout")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", "10.0.0.8, 10.0.0.1")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // The handler still receives the full body
+    let echoed = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&echoed).unwrap()["password"], "hidden");
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::delete("/api/clusters/queries/abc:123")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let error = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&error).contains("Query not found"));
+
+    // Reads are not audited
+    app.oneshot(
+        Request::get("/api/clusters/queries")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let logs = state
+        .admin_audit_service
+        .list(Some(data.org1_id), false, &AdminAuditQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(logs.total, 2);
+
+    let kill = &logs.items[0];
+    assert_eq!(kill.action, "clusters:queries:kill");
+    assert_eq!(kill.target.as_deref(), Some("query_id=abc:123"));
+    assert_eq!((kill.status_code, kill.success), (404, false));
+    assert!(
+        kill.error_message
+            .as_deref()
+            .unwrap()
+            .contains("Query not found")
+    );
+    assert_eq!(kill.payload, None);
+
+    let update = &logs.items[1];
+    assert_eq!(update.action, "clusters:variables:update");
+    assert_eq!(update.user_id, Some(data.org1_admin_user_id));
+    assert_eq!(update.username, "org1_admin");
+    assert_eq!(update.cluster_id, Some(cluster.id));
+    assert_eq!(update.cluster_name.as_deref(), Some("audit_cluster"));
+    assert_eq!(update.target.as_deref(), Some("variable_name=query_timeout"));
+    assert_eq!(update.client_ip.as_deref(), Some("10.0.0.8"));
+    assert!(update.success);
+    let payload: serde_json::Value =
+        serde_json::from_str(update.payload.as_deref().unwrap()).unwrap();
+    assert_eq!(payload["value"], "300");
+    assert_eq!(payload["password"], "******");
+}
+
+#[tokio::test]
+async fn test_audit_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let routes = [("GET", "/api/audit-logs", "list"), ("GET", "/api/audit-logs/export", "export")];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("audit_logs", action));
+
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM permissions WHERE resource = ? AND action = ?")
+                .bind(&resource)
+                .bind(&extracted)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not seeded", resource, extracted);
+    }
+}
@@ -0,0 +1,747 @@
+// Alert service tests
+
+use crate::config::AlertConfig;
+use crate::models::{
+    AlertChannelType, AlertComparator, AlertHistoryQuery, AlertSeverity, AlertState, AlertStatus,
+    Cluster, CreateAlertChannelRequest, CreateAlertRuleRequest, CreateClusterRequest,
+    UpdateAlertChannelRequest, UpdateAlertRuleRequest,
+};
+use crate::services::{
+    AlertService, MetricsSnapshot, cluster_service::ClusterService,
+    mysql_pool_manager::MySQLPoolManager,
+};
+use crate::tests::common::{create_test_db, setup_multi_tenant_test_data};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Json, Router, routing::post};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::{Arc, Mutex};
+
+/// Local HTTP endpoint recording posted JSON bodies
+///
+/// `/ok` answers like a DingTalk robot accepting the message, `/reject` like one refusing it,
+/// `/auth` accepts only requests carrying `Authorization: Bearer hook-token`.
+async fn start_receiver() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let ok_store = Arc::clone(&received);
+    let app = Router::new()
+        .route(
+            "/ok",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let store = Arc::clone(&ok_store);
+                async move {
+                    store.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "errcode": 0, "errmsg": "ok" }))
+                }
+            }),
+        )
+        .route(
+            "/reject",
+            post(|| async {
+                Json(serde_json::json!({ "errcode": 310000, "errmsg": "keywords not in content" }))
+            }),
+        )
+        .route(
+            "/auth",
+            post(|headers: HeaderMap| async move {
+                match headers.get("authorization") {
+                    Some(value) if value == "Bearer hook-token" => StatusCode::OK,
+                    _ => StatusCode::UNAUTHORIZED,
+                }
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), received)
+}
+
+async fn setup(pool: &SqlitePool) -> (AlertService, Cluster, Cluster) {
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
+    let cluster_service = ClusterService::new(pool.clone(), Arc::clone(&mysql_pool_manager));
+    let test_data = setup_multi_tenant_test_data(pool).await;
+
+    let mut clusters = Vec::new();
+    for name in ["alert_a", "alert_b"] {
+        let cluster = cluster_service
+            .create_cluster(
+                CreateClusterRequest {
+                    name: name.to_string(),
+                    description: None,
+                    fe_host: format!("{}.example.com", name),
+                    fe_http_port: 8030,
+                    fe_query_port: 9030,
+                    username: "root".to_string(),
+                    password: "secret".to_string(),
+                    enable_ssl: false,
+                    connection_timeout: 30,
+                    tags: None,
+                    catalog: "default_catalog".to_string(),
+                    organization_id: Some(test_data.org1_id),
+                    deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                    fe_endpoints: None,
+                    tls: None,
+                },
+                test_data.super_admin_user_id,
+                None,
+                true,
+            )
+            .await
+            .unwrap();
+        clusters.push(cluster);
+    }
+
+    let service = AlertService::new(pool.clone(), mysql_pool_manager, AlertConfig::default());
+    let second = clusters.pop().unwrap();
+    (service, clusters.pop().unwrap(), second)
+}
+
+/// Wait until every unsilenced alert of the cluster has finished its background delivery
+async fn wait_for_delivery(service: &AlertService, cluster_id: i64) {
+    for _ in 0..100 {
+        let history = service
+            .list_history(cluster_id, &AlertHistoryQuery::default())
+            .await
+            .unwrap();
+        let done = history
+            .items
+            .iter()
+            .all(|h| h.silenced || h.notified || h.notification_error.is_some());
+        if done {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("Alert delivery did not finish");
+}
+
+fn snapshot(cluster_id: i64, at: DateTime<Utc>, cpu: f64) -> MetricsSnapshot {
+    MetricsSnapshot {
+        cluster_id,
+        collected_at: at,
+        avg_cpu_usage: cpu,
+        backend_total: 3,
+        backend_alive: 3,
+        ..Default::default()
+    }
+}
+
+fn cpu_rule(name: &str, duration_secs: i64, channel_ids: Vec<i64>) -> CreateAlertRuleRequest {
+    CreateAlertRuleRequest {
+        name: name.to_string(),
+        description: None,
+        metric: "avg_cpu_usage".to_string(),
+        comparator: AlertComparator::Gt,
+        threshold: 80.0,
+        duration_secs,
+        severity: AlertSeverity::Critical,
+        enabled: true,
+        channel_ids,
+    }
+}
+
+fn http_channel(
+    name: &str,
+    channel_type: AlertChannelType,
+    url: &str,
+) -> CreateAlertChannelRequest {
+    CreateAlertChannelRequest {
+        name: name.to_string(),
+        channel_type,
+        config: serde_json::json!({ "url": url }),
+        enabled: true,
+    }
+}
+
+#[tokio::test]
+async fn test_rule_fires_after_duration_and_resolves() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let (base_url, received) = start_receiver().await;
+
+    let channel = service
+        .create_channel(
+            cluster.id,
+            http_channel("hook", AlertChannelType::Webhook, &format!("{}/ok", base_url)),
+            None,
+        )
+        .await
+        .unwrap();
+    let rule = service
+        .create_rule(cluster.id, cpu_rule("high cpu", 60, vec![channel.id]), None)
+        .await
+        .unwrap();
+
+    let t0 = Utc::now();
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t0, 90.0))
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+    assert_eq!(service.get_rule(cluster.id, rule.id).await.unwrap().state, AlertState::Pending);
+
+    // Still inside the duration window
+    let t1 = t0 + Duration::seconds(30);
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t1, 95.0))
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+
+    let t2 = t0 + Duration::seconds(60);
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t2, 92.0))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, AlertStatus::Firing);
+    let current = service.get_rule(cluster.id, rule.id).await.unwrap();
+    assert_eq!(current.state, AlertState::Firing);
+    assert_eq!(current.last_value, Some(92.0));
+    wait_for_delivery(&service, cluster.id).await;
+
+    // No repeated notification while still firing
+    let t3 = t0 + Duration::seconds(90);
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t3, 99.0))
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+
+    let t4 = t0 + Duration::seconds(120);
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t4, 40.0))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, AlertStatus::Resolved);
+    assert_eq!(service.get_rule(cluster.id, rule.id).await.unwrap().state, AlertState::Ok);
+    wait_for_delivery(&service, cluster.id).await;
+
+    // Generic webhook receives the event as-is
+    let payloads = received.lock().unwrap().clone();
+    assert_eq!(payloads.len(), 2);
+    assert_eq!(payloads[0]["status"], "firing");
+    assert_eq!(payloads[0]["rule_name"], "high cpu");
+    assert_eq!(payloads[0]["cluster_name"], "alert_a");
+    assert_eq!(payloads[0]["comparator"], ">");
+    assert_eq!(payloads[0]["value"], 92.0);
+    assert_eq!(payloads[1]["status"], "resolved");
+
+    let history = service
+        .list_history(cluster.id, &AlertHistoryQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(history.total, 2);
+    assert_eq!(history.items[0].status, AlertStatus::Resolved);
+    assert_eq!(history.items[1].status, AlertStatus::Firing);
+    assert!(
+        history
+            .items
+            .iter()
+            .all(|h| h.notified && h.notification_error.is_none())
+    );
+
+    let firing_only = service
+        .list_history(
+            cluster.id,
+            &AlertHistoryQuery { status: Some(AlertStatus::Firing), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    assert_eq!(firing_only.total, 1);
+}
+
+#[tokio::test]
+async fn test_pending_rule_resets_when_condition_clears() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let rule = service
+        .create_rule(cluster.id, cpu_rule("flapping cpu", 60, vec![]), None)
+        .await
+        .unwrap();
+
+    let t0 = Utc::now();
+    service
+        .evaluate(&cluster, &snapshot(cluster.id, t0, 90.0))
+        .await
+        .unwrap();
+    service
+        .evaluate(&cluster, &snapshot(cluster.id, t0 + Duration::seconds(30), 50.0))
+        .await
+        .unwrap();
+    let current = service.get_rule(cluster.id, rule.id).await.unwrap();
+    assert_eq!(current.state, AlertState::Ok);
+    assert!(current.pending_since.is_none());
+
+    // The window restarts from the next breach
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, t0 + Duration::seconds(70), 90.0))
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+    assert_eq!(service.get_rule(cluster.id, rule.id).await.unwrap().state, AlertState::Pending);
+
+    // Derived metric: offline BE nodes
+    let offline = service
+        .create_rule(
+            cluster.id,
+            CreateAlertRuleRequest {
+                metric: "backend_offline".to_string(),
+                comparator: AlertComparator::Gte,
+                threshold: 1.0,
+                duration_secs: 0,
+                ..cpu_rule("be offline", 0, vec![])
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let mut degraded = snapshot(cluster.id, t0 + Duration::seconds(80), 10.0);
+    degraded.backend_alive = 2;
+    let events = service.evaluate(&cluster, &degraded).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].rule_id, offline.id);
+    assert_eq!(events[0].value, 1.0);
+}
+
+#[tokio::test]
+async fn test_silenced_rule_records_without_notifying() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let (base_url, received) = start_receiver().await;
+
+    let channel = service
+        .create_channel(
+            cluster.id,
+            http_channel("hook", AlertChannelType::Webhook, &format!("{}/ok", base_url)),
+            None,
+        )
+        .await
+        .unwrap();
+    let rule = service
+        .create_rule(cluster.id, cpu_rule("high cpu", 0, vec![channel.id]), None)
+        .await
+        .unwrap();
+
+    let silenced = service
+        .silence_rule(cluster.id, rule.id, 3600)
+        .await
+        .unwrap();
+    assert!(silenced.silenced_until.is_some());
+
+    let events = service
+        .evaluate(&cluster, &snapshot(cluster.id, Utc::now(), 90.0))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(received.lock().unwrap().is_empty());
+
+    let history = service
+        .list_history(cluster.id, &AlertHistoryQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(history.total, 1);
+    assert!(history.items[0].silenced);
+    assert!(!history.items[0].notified);
+
+    // After unsilencing, the resolution is delivered again
+    service.unsilence_rule(cluster.id, rule.id).await.unwrap();
+    service
+        .evaluate(&cluster, &snapshot(cluster.id, Utc::now(), 10.0))
+        .await
+        .unwrap();
+    wait_for_delivery(&service, cluster.id).await;
+    let payloads = received.lock().unwrap().clone();
+    assert_eq!(payloads.len(), 1);
+    assert_eq!(payloads[0]["status"], "resolved");
+
+    assert!(service.silence_rule(cluster.id, rule.id, 0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_chat_channel_payload_formats() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let (base_url, received) = start_receiver().await;
+    let url = format!("{}/ok", base_url);
+
+    for (name, channel_type) in [
+        ("dingtalk", AlertChannelType::Dingtalk),
+        ("feishu", AlertChannelType::Feishu),
+        ("slack", AlertChannelType::Slack),
+    ] {
+        let channel = service
+            .create_channel(cluster.id, http_channel(name, channel_type, &url), None)
+            .await
+            .unwrap();
+        service.test_channel(&cluster, channel.id).await.unwrap();
+    }
+
+    let payloads = received.lock().unwrap().clone();
+    assert_eq!(payloads.len(), 3);
+
+    assert_eq!(payloads[0]["msgtype"], "markdown");
+    let title = payloads[0]["markdown"]["title"].as_str().unwrap();
+    assert_eq!(title, "[FIRING][info] Test notification");
+    assert!(
+        payloads[0]["markdown"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("alert_a")
+    );
+
+    assert_eq!(payloads[1]["msg_type"], "text");
+    assert!(
+        payloads[1]["content"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Cluster: alert_a")
+    );
+
+    assert!(
+        payloads[2]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("*[FIRING][info]")
+    );
+}
+
+#[tokio::test]
+async fn test_notification_failure_is_recorded() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let (base_url, _) = start_receiver().await;
+
+    let channel = service
+        .create_channel(
+            cluster.id,
+            http_channel("robot", AlertChannelType::Dingtalk, &format!("{}/reject", base_url)),
+            None,
+        )
+        .await
+        .unwrap();
+    service
+        .create_rule(cluster.id, cpu_rule("high cpu", 0, vec![channel.id]), None)
+        .await
+        .unwrap();
+
+    assert!(service.test_channel(&cluster, channel.id).await.is_err());
+
+    service
+        .evaluate(&cluster, &snapshot(cluster.id, Utc::now(), 90.0))
+        .await
+        .unwrap();
+    wait_for_delivery(&service, cluster.id).await;
+    let history = service
+        .list_history(cluster.id, &AlertHistoryQuery::default())
+        .await
+        .unwrap();
+    assert!(!history.items[0].notified);
+    let error = history.items[0].notification_error.as_deref().unwrap();
+    assert!(error.contains("robot") && error.contains("310000"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_smtp_password_encrypted_and_masked() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+
+    let channel = service
+        .create_channel(
+            cluster.id,
+            CreateAlertChannelRequest {
+                name: "mail".to_string(),
+                channel_type: AlertChannelType::Smtp,
+                config: serde_json::json!({
+                    "host": "smtp.example.com",
+                    "port": 587,
+                    "tls": "starttls",
+                    "username": "alerts",
+                    "password": "smtp-secret",
+                    "from": "StarRocks Admin <alerts@example.com>",
+                    "to": ["dba@example.com"],
+                }),
+                enabled: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(channel.config.get("password").is_none());
+    assert_eq!(channel.config["port"], 587);
+
+    let stored_password = || async {
+        let (config,): (String,) = sqlx::query_as("SELECT config FROM alert_channels WHERE id = ?")
+            .bind(channel.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let config: serde_json::Value = serde_json::from_str(&config).unwrap();
+        config["password"].as_str().unwrap().to_string()
+    };
+    let sealed = stored_password().await;
+    assert_ne!(sealed, "smtp-secret");
+
+    // Omitting the password on update keeps the stored one
+    service
+        .update_channel(
+            cluster.id,
+            channel.id,
+            UpdateAlertChannelRequest {
+                config: Some(serde_json::json!({
+                    "host": "smtp2.example.com",
+                    "from": "alerts@example.com",
+                    "to": ["dba@example.com", "oncall@example.com"],
+                    "username": "alerts",
+                })),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(stored_password().await, sealed);
+
+    let invalid = service
+        .create_channel(
+            cluster.id,
+            CreateAlertChannelRequest {
+                name: "bad mail".to_string(),
+                channel_type: AlertChannelType::Smtp,
+                config: serde_json::json!({
+                    "host": "smtp.example.com",
+                    "from": "not an address",
+                    "to": ["dba@example.com"],
+                }),
+                enabled: true,
+            },
+            None,
+        )
+        .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_webhook_url_and_headers_encrypted_and_masked() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+    let (base_url, _) = start_receiver().await;
+    let url = format!("{}/auth", base_url);
+
+    let channel = service
+        .create_channel(
+            cluster.id,
+            CreateAlertChannelRequest {
+                name: "hook".to_string(),
+                channel_type: AlertChannelType::Webhook,
+                config: serde_json::json!({
+                    "url": url,
+                    "headers": { "Authorization": "Bearer hook-token" },
+                }),
+                enabled: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let host = base_url
+        .trim_start_matches("http://")
+        .split(':')
+        .next()
+        .unwrap();
+    assert_eq!(channel.config["url"], format!("http://{}/******", host));
+    assert_eq!(channel.config["headers"]["Authorization"], "******");
+    let listed = service.list_channels(cluster.id).await.unwrap();
+    assert_eq!(listed[0].config, channel.config);
+
+    let stored = || async {
+        let (config,): (String,) = sqlx::query_as("SELECT config FROM alert_channels WHERE id = ?")
+            .bind(channel.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        serde_json::from_str::<serde_json::Value>(&config).unwrap()
+    };
+    let sealed = stored().await;
+    assert!(!sealed.to_string().contains("hook-token"));
+    assert!(!sealed.to_string().contains("/auth"));
+
+    // Delivery uses the decrypted url and headers
+    service.test_channel(&cluster, channel.id).await.unwrap();
+
+    // Sending the masked view back keeps the stored secrets
+    service
+        .update_channel(
+            cluster.id,
+            channel.id,
+            UpdateAlertChannelRequest {
+                config: Some(channel.config.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(stored().await, sealed);
+    service.test_channel(&cluster, channel.id).await.unwrap();
+
+    // A masked value with nothing to keep is refused
+    let masked_new_header = serde_json::json!({
+        "url": channel.config["url"],
+        "headers": { "X-Token": "******" },
+    });
+    let refused = service
+        .update_channel(
+            cluster.id,
+            channel.id,
+            UpdateAlertChannelRequest { config: Some(masked_new_header), ..Default::default() },
+        )
+        .await;
+    assert!(refused.is_err());
+}
+
+#[tokio::test]
+async fn test_rule_validation_and_cluster_scope() {
+    let pool = create_test_db().await;
+    let (service, cluster_a, cluster_b) = setup(&pool).await;
+
+    let unknown_metric =
+        CreateAlertRuleRequest { metric: "not_a_metric".to_string(), ..cpu_rule("bad", 0, vec![]) };
+    assert!(
+        service
+            .create_rule(cluster_a.id, unknown_metric, None)
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .create_rule(cluster_a.id, cpu_rule("negative", -1, vec![]), None)
+            .await
+            .is_err()
+    );
+
+    // Channels of another cluster cannot be attached
+    let foreign = service
+        .create_channel(
+            cluster_b.id,
+            http_channel("hook", AlertChannelType::Slack, "https://hooks.example.com/x"),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(
+        service
+            .create_rule(cluster_a.id, cpu_rule("cpu", 0, vec![foreign.id]), None)
+            .await
+            .is_err()
+    );
+
+    let rule = service
+        .create_rule(cluster_a.id, cpu_rule("cpu", 0, vec![]), None)
+        .await
+        .unwrap();
+    assert!(
+        service
+            .create_rule(cluster_a.id, cpu_rule("cpu", 0, vec![]), None)
+            .await
+            .is_err()
+    );
+    assert!(service.get_rule(cluster_b.id, rule.id).await.is_err());
+    assert!(service.list_rules(cluster_b.id).await.unwrap().is_empty());
+
+    // Disabling a firing rule clears its state
+    service
+        .evaluate(&cluster_a, &snapshot(cluster_a.id, Utc::now(), 90.0))
+        .await
+        .unwrap();
+    assert_eq!(service.firing_rules(cluster_a.id).await.unwrap().len(), 1);
+    let disabled = service
+        .update_rule(
+            cluster_a.id,
+            rule.id,
+            UpdateAlertRuleRequest { enabled: Some(false), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    assert_eq!(disabled.state, AlertState::Ok);
+    assert!(service.firing_rules(cluster_a.id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_delete_channel_detaches_it_from_rules() {
+    let pool = create_test_db().await;
+    let (service, cluster, _) = setup(&pool).await;
+
+    let keep = service
+        .create_channel(
+            cluster.id,
+            http_channel("keep", AlertChannelType::Webhook, "https://hooks.example.com/a"),
+            None,
+        )
+        .await
+        .unwrap();
+    let drop = service
+        .create_channel(
+            cluster.id,
+            http_channel("drop", AlertChannelType::Webhook, "https://hooks.example.com/b"),
+            None,
+        )
+        .await
+        .unwrap();
+    let rule = service
+        .create_rule(cluster.id, cpu_rule("cpu", 0, vec![keep.id, drop.id]), None)
+        .await
+        .unwrap();
+
+    service.delete_channel(cluster.id, drop.id).await.unwrap();
+    assert_eq!(
+        service
+            .get_rule(cluster.id, rule.id)
+            .await
+            .unwrap()
+            .channel_ids,
+        vec![keep.id]
+    );
+    assert_eq!(service.list_channels(cluster.id).await.unwrap().len(), 1);
+    assert!(service.delete_channel(cluster.id, drop.id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_alert_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let routes = [
+        ("GET", "/api/clusters/alerts/metrics", "alerts:metrics"),
+        ("GET", "/api/clusters/alerts/rules", "alerts:rules:list"),
+        ("GET", "/api/clusters/alerts/rules/3", "alerts:rules:list"),
+        ("POST", "/api/clusters/alerts/rules", "alerts:rules:create"),
+        ("PUT", "/api/clusters/alerts/rules/3", "alerts:rules:update"),
+        ("DELETE", "/api/clusters/alerts/rules/3", "alerts:rules:delete"),
+        ("POST", "/api/clusters/alerts/rules/3/silence", "alerts:rules:silence"),
+        ("DELETE", "/api/clusters/alerts/rules/3/silence", "alerts:rules:silence"),
+        ("GET", "/api/clusters/alerts/channels", "alerts:channels:list"),
+        ("POST", "/api/clusters/alerts/channels", "alerts:channels:create"),
+        ("PUT", "/api/clusters/alerts/channels/2", "alerts:channels:update"),
+        ("DELETE", "/api/clusters/alerts/channels/2", "alerts:channels:delete"),
+        ("POST", "/api/clusters/alerts/channels/2/test", "alerts:channels:test"),
+        ("GET", "/api/clusters/alerts/history", "alerts:history"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM permissions WHERE resource = ? AND action = ?")
+                .bind(&resource)
+                .bind(&extracted)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not seeded", resource, extracted);
+    }
+}
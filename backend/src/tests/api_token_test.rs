@@ -0,0 +1,523 @@
+// API token and service account tests
+
+use crate::middleware::{AuthState, OrgContext, auth_middleware};
+use crate::models::{
+    Cluster, CreateApiTokenRequest, CreateClusterRequest, CreateServiceAccountRequest, LoginRequest,
+};
+use crate::services::{API_TOKEN_PREFIX, ApiTokenService};
+use crate::tests::common::{
+    MultiTenantTestData, create_test_app_state, create_test_db, create_test_session_token,
+    setup_multi_tenant_test_data,
+};
+use crate::utils::ApiError;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{StatusCode, header};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn org_admin_ctx(data: &MultiTenantTestData) -> OrgContext {
+    OrgContext {
+        user_id: data.org1_admin_user_id,
+        username: "org1_admin".to_string(),
+        organization_id: Some(data.org1_id),
+        is_super_admin: false,
+        cluster_id: None,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: None,
+    }
+}
+
+fn token_request(name: &str) -> CreateApiTokenRequest {
+    CreateApiTokenRequest { name: name.to_string(), ..Default::default() }
+}
+
+async fn create_cluster(
+    state: &crate::AppState,
+    name: &str,
+    organization_id: i64,
+    created_by: i64,
+) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: "127.0.0.1".to_string(),
+                fe_http_port: 1,
+                fe_query_port: 1,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 1,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: Some(organization_id),
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_personal_token_lifecycle() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let service = &state.api_token_service;
+    let ctx = org_admin_ctx(&data);
+
+    let created = service
+        .create_token(data.org1_admin_user_id, token_request(" ci "), &ctx)
+        .await
+        .unwrap();
+    assert!(created.token.starts_with(API_TOKEN_PREFIX));
+    assert_eq!(created.api_token.name, "ci");
+    assert!(created.token.starts_with(&created.api_token.token_prefix));
+    assert!(created.api_token.last_used_at.is_none());
+    assert!(created.api_token.is_active());
+
+    // Only the hash is stored
+    let (stored,): (String,) = sqlx::query_as("SELECT token_hash FROM api_tokens WHERE id = ?")
+        .bind(created.api_token.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_ne!(stored, created.token);
+    assert!(!stored.contains(&created.token[API_TOKEN_PREFIX.len()..]));
+
+    let principal = service.authenticate(&created.token).await.unwrap();
+    assert_eq!(principal.user_id, data.org1_admin_user_id);
+    assert_eq!(principal.username, "org1_admin");
+    assert!(principal.allows("clusters", "delete"));
+
+    let tokens = service.list_tokens(data.org1_admin_user_id).await.unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens[0].last_used_at.is_some());
+
+    let revoked = service
+        .revoke_token(data.org1_admin_user_id, created.api_token.id)
+        .await
+        .unwrap();
+    assert!(!revoked.is_active());
+    assert!(service.authenticate(&created.token).await.is_err());
+    assert!(service.authenticate("sra_unknown").await.is_err());
+
+    // Tokens of other users are not reachable
+    let err = service
+        .revoke_token(data.org2_admin_user_id, created.api_token.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ResourceNotFound(_)));
+}
+
+#[tokio::test]
+async fn test_token_creation_is_validated() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let service = &state.api_token_service;
+    let ctx = org_admin_ctx(&data);
+    let other_org_cluster =
+        create_cluster(&state, "org2", data.org2_id, data.super_admin_user_id).await;
+
+    for req in [
+        token_request("  "),
+        CreateApiTokenRequest {
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+            ..token_request("expired")
+        },
+        CreateApiTokenRequest { scopes: Some(vec![]), ..token_request("empty") },
+        // Org admins of the test data hold no cluster permission
+        CreateApiTokenRequest {
+            scopes: Some(vec!["api:clusters:delete".to_string()]),
+            ..token_request("not held")
+        },
+    ] {
+        let err = service
+            .create_token(data.org1_admin_user_id, req, &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+    }
+
+    let err = service
+        .create_token(
+            data.org1_admin_user_id,
+            CreateApiTokenRequest {
+                cluster_ids: Some(vec![other_org_cluster.id]),
+                ..token_request("other org")
+            },
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)), "{:?}", err);
+
+    // A token cannot mint tokens
+    let token_ctx = OrgContext { api_token_id: Some(1), ..ctx.clone() };
+    let err = service
+        .create_token(data.org1_admin_user_id, token_request("nested"), &token_ctx)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)), "{:?}", err);
+
+    // Expired tokens stop authenticating
+    let created = service
+        .create_token(
+            data.org1_admin_user_id,
+            CreateApiTokenRequest {
+                expires_at: Some(Utc::now() + Duration::hours(1)),
+                ..token_request("short")
+            },
+            &ctx,
+        )
+        .await
+        .unwrap();
+    sqlx::query("UPDATE api_tokens SET expires_at = ? WHERE id = ?")
+        .bind(Utc::now() - Duration::seconds(1))
+        .bind(created.api_token.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(service.authenticate(&created.token).await.is_err());
+}
+
+#[tokio::test]
+async fn test_middleware_enforces_token_scopes_and_clusters() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action)
+         VALUES ('api:clusters:get', 'Get Cluster', 'api', 'clusters', 'get')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO role_permissions (role_id, permission_id)
+         SELECT r.id, p.id FROM roles r, permissions p
+         WHERE r.code = 'org_admin' AND p.code = 'api:clusters:get'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let state = create_test_app_state(&pool).await;
+    let allowed = create_cluster(&state, "allowed", data.org1_id, data.super_admin_user_id).await;
+    let other = create_cluster(&state, "other", data.org1_id, data.super_admin_user_id).await;
+
+    let created = state
+        .api_token_service
+        .create_token(
+            data.org1_admin_user_id,
+            CreateApiTokenRequest {
+                scopes: Some(vec!["api:users:list".to_string(), "api:clusters:get".to_string()]),
+                cluster_ids: Some(vec![allowed.id]),
+                ..token_request("scoped")
+            },
+            &org_admin_ctx(&data),
+        )
+        .await
+        .unwrap();
+
+    let auth_state = AuthState {
+        jwt_util: Arc::clone(&state.jwt_util),
+        casbin_service: Arc::clone(&state.casbin_service),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::clone(&state.session_service),
+        db: pool.clone(),
+    };
+    let app = Router::new()
+        .route("/api/users", get(|| async { "users" }))
+        .route("/api/roles", get(|| async { "roles" }))
+        .route("/api/clusters/:id", get(|| async { "cluster" }))
+        .route(
+            "/api/auth/me",
+            get(|Extension(org_ctx): Extension<OrgContext>| async move { Json(org_ctx) }),
+        )
+        .route("/api/clusters/active", get(|| async { "active" }))
+        .route("/api/organizations", get(|| async { "organizations" }))
+        .route("/api/system-functions", get(|| async { "functions" }))
+        .layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+    let send = |token: String, path: String| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder()
+                .uri(path)
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap()
+        }
+    };
+
+    let token = created.token.clone();
+    assert_eq!(send(token.clone(), "/api/users".into()).await.status(), StatusCode::OK);
+    // The user holds roles:list, the token's scopes do not
+    assert_eq!(send(token.clone(), "/api/roles".into()).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        send(token.clone(), format!("/api/clusters/{}", allowed.id))
+            .await
+            .status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        send(token.clone(), format!("/api/clusters/{}", other.id))
+            .await
+            .status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Routes without a permission are refused to tokens unless allow-listed
+    assert_eq!(
+        send(token.clone(), "/api/clusters/active".into())
+            .await
+            .status(),
+        StatusCode::OK
+    );
+    for path in ["/api/organizations", "/api/system-functions"] {
+        assert_eq!(send(token.clone(), path.into()).await.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = send(token.clone(), "/api/auth/me".into()).await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let org_ctx: OrgContext = serde_json::from_slice(&body).unwrap();
+    assert_eq!(org_ctx.user_id, data.org1_admin_user_id);
+    assert_eq!(org_ctx.api_token_id, Some(created.api_token.id));
+    assert_eq!(org_ctx.allowed_cluster_ids, Some(vec![allowed.id]));
+
+    // Handlers resolving the cluster honor the restriction too
+    let clusters = &state.cluster_service;
+    assert!(
+        clusters
+            .resolve_cluster(&OrgContext { cluster_id: Some(other.id), ..org_ctx.clone() })
+            .await
+            .is_err()
+    );
+    let visible = clusters.list_visible_clusters(&org_ctx).await.unwrap();
+    assert_eq!(visible.iter().map(|c| c.id).collect::<Vec<_>>(), vec![allowed.id]);
+
+    // The login session of the same user is not restricted
+    let jwt =
+        create_test_session_token(&pool, &state.jwt_util, data.org1_admin_user_id, "org1_admin")
+            .await;
+    assert_eq!(send(jwt.clone(), "/api/roles".into()).await.status(), StatusCode::OK);
+    assert_eq!(
+        send(jwt.clone(), "/api/organizations".into())
+            .await
+            .status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        send(jwt, format!("/api/clusters/{}", other.id))
+            .await
+            .status(),
+        StatusCode::OK
+    );
+
+    state
+        .api_token_service
+        .revoke_token(data.org1_admin_user_id, created.api_token.id)
+        .await
+        .unwrap();
+    assert_eq!(send(token, "/api/users".into()).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_service_account_lifecycle() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let users = &state.user_service;
+    let ctx = org_admin_ctx(&data);
+
+    // An org admin cannot hand out permissions they do not hold
+    let (wide_role_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO roles (code, name, is_system, organization_id)
+         VALUES ('cluster_ops', 'Cluster Ops', 0, ?) RETURNING id",
+    )
+    .bind(data.org1_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action)
+         VALUES ('api:clusters:delete', 'Delete Cluster', 'api', 'clusters', 'delete')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO role_permissions (role_id, permission_id)
+         SELECT ?, id FROM permissions WHERE code = 'api:clusters:delete'",
+    )
+    .bind(wide_role_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let err = users
+        .create_service_account(
+            CreateServiceAccountRequest {
+                name: "escalate".to_string(),
+                description: None,
+                role_ids: vec![wide_role_id],
+                organization_id: None,
+            },
+            data.org1_admin_user_id,
+            Some(data.org1_id),
+            false,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)), "{:?}", err);
+
+    let account = users
+        .create_service_account(
+            CreateServiceAccountRequest {
+                name: "etl-bot".to_string(),
+                description: Some("Nightly ETL".to_string()),
+                role_ids: vec![data.regular_role_id],
+                organization_id: None,
+            },
+            data.org1_admin_user_id,
+            Some(data.org1_id),
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(account.name, "etl-bot");
+    assert_eq!(account.organization_id, Some(data.org1_id));
+    assert_eq!(account.roles.len(), 1);
+    assert_eq!(account.created_by, Some(data.org1_admin_user_id));
+
+    let listed = users
+        .list_service_accounts(Some(data.org1_id), false)
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(
+        users
+            .list_service_accounts(Some(data.org2_id), false)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        users
+            .get_service_account(account.id, Some(data.org2_id), false)
+            .await
+            .is_err()
+    );
+    // Regular users are not service accounts
+    assert!(
+        users
+            .get_service_account(data.org1_regular_user_id, Some(data.org1_id), false)
+            .await
+            .is_err()
+    );
+
+    // Service accounts cannot log in with a password
+    let err = state
+        .auth_service
+        .login(
+            LoginRequest {
+                username: "etl-bot".to_string(),
+                password: "x".to_string(),
+                totp_code: None,
+            },
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidCredentials));
+
+    // Token scopes are limited to the account's permissions
+    let tokens = &state.api_token_service;
+    let err = tokens
+        .create_token(
+            account.id,
+            CreateApiTokenRequest {
+                scopes: Some(vec!["api:users:list".to_string()]),
+                ..token_request("too wide")
+            },
+            &ctx,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+
+    let created = tokens
+        .create_token(
+            account.id,
+            CreateApiTokenRequest {
+                scopes: Some(vec!["api:users:get".to_string()]),
+                ..token_request("etl")
+            },
+            &ctx,
+        )
+        .await
+        .unwrap();
+    let principal = tokens.authenticate(&created.token).await.unwrap();
+    assert_eq!(principal.user_id, account.id);
+    assert!(principal.allows("users", "get"));
+    assert!(!principal.allows("users", "list"));
+    assert_eq!(created.api_token.created_by, Some(data.org1_admin_user_id));
+
+    users
+        .delete_service_account(account.id, Some(data.org1_id), false)
+        .await
+        .unwrap();
+    assert!(tokens.authenticate(&created.token).await.is_err());
+    assert!(tokens.list_tokens(account.id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_service_account_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    for (method, path, expected) in [
+        ("GET", "/api/service-accounts", "list"),
+        ("POST", "/api/service-accounts", "create"),
+        ("GET", "/api/service-accounts/3", "get"),
+        ("DELETE", "/api/service-accounts/3", "delete"),
+        ("GET", "/api/service-accounts/3/tokens", "tokens:list"),
+        ("POST", "/api/service-accounts/3/tokens", "tokens:create"),
+        ("DELETE", "/api/service-accounts/3/tokens/7", "tokens:revoke"),
+    ] {
+        let (resource, action) = extract_permission(method, path).unwrap();
+        assert_eq!(
+            (resource.as_str(), action.as_str()),
+            ("service_accounts", expected),
+            "{}",
+            path
+        );
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions WHERE resource = 'service_accounts' AND action = ?",
+        )
+        .bind(expected)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "{}", expected);
+    }
+
+    // Personal tokens only require authentication
+    assert!(extract_permission("POST", "/api/auth/tokens").is_none());
+    assert!(extract_permission("DELETE", "/api/auth/tokens/1").is_none());
+}
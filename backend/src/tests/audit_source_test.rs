@@ -0,0 +1,207 @@
+// Per-cluster audit source tests
+
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::services::{AuditColumn, AuditSource, AuditSourceSettings};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::ApiError;
+use std::collections::BTreeMap;
+
+async fn create_cluster(state: &crate::AppState, created_by: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "audit".to_string(),
+                description: None,
+                // Nothing listens on port 1: the audit table cannot be read
+                fe_host: "127.0.0.1".to_string(),
+                fe_http_port: 1,
+                fe_query_port: 1,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 1,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn names(columns: &[&str]) -> Vec<String> {
+    columns.iter().map(|c| c.to_string()).collect()
+}
+
+#[test]
+fn test_resolve_detects_columns_of_older_plugin_versions() {
+    let columns = names(&[
+        "query_id",
+        "Timestamp",
+        "user",
+        "db",
+        "stmt",
+        "query_type",
+        "query_time",
+        "state",
+        "is_query",
+        "scan_rows",
+    ]);
+    let source = AuditSource::resolve("audit_db", "audit_tbl", Some(&columns), &BTreeMap::new());
+
+    assert!(source.detected);
+    assert_eq!(source.full_table_name(), "`audit_db`.`audit_tbl`");
+    assert_eq!(source.col(AuditColumn::QueryTime), "`query_time`");
+    assert_eq!(source.col(AuditColumn::IsQuery), "`is_query`");
+    // Matching is case-insensitive and keeps the table's spelling
+    assert_eq!(source.col(AuditColumn::Timestamp), "`Timestamp`");
+    assert!(source.missing_required().is_empty());
+
+    // Optional columns the table lacks read as neutral literals
+    assert!(!source.has(AuditColumn::Catalog));
+    assert_eq!(source.col(AuditColumn::Catalog), "'default_catalog'");
+    assert_eq!(source.col(AuditColumn::ResourceGroup), "''");
+    assert_eq!(source.col(AuditColumn::CpuCostNs), "NULL");
+}
+
+#[test]
+fn test_resolve_prefers_mapping_and_reports_missing_required() {
+    let columns = names(&["queryId", "timestamp", "user", "stmt", "state", "isQuery", "elapsed"]);
+    let mapping = BTreeMap::from([
+        (AuditColumn::QueryTime, "elapsed".to_string()),
+        (AuditColumn::Stmt, "sql_text".to_string()),
+    ]);
+    let source = AuditSource::resolve("db", "tbl", Some(&columns), &mapping);
+
+    assert_eq!(source.col(AuditColumn::QueryTime), "`elapsed`");
+    // A mapped column the table does not have is missing, not auto-detected
+    assert!(!source.has(AuditColumn::Stmt));
+    assert_eq!(source.missing_required(), vec![AuditColumn::Stmt, AuditColumn::QueryType]);
+    // Missing required columns keep their default name so queries fail loudly
+    assert_eq!(source.col(AuditColumn::QueryType), "`queryType`");
+}
+
+#[test]
+fn test_resolve_without_table_columns_assumes_defaults() {
+    let mapping = BTreeMap::from([(AuditColumn::QueryTime, "query_time".to_string())]);
+    let source = AuditSource::resolve("db", "tbl", None, &mapping);
+
+    assert!(!source.detected);
+    assert!(source.missing.is_empty());
+    assert_eq!(source.col(AuditColumn::QueryTime), "`query_time`");
+    assert_eq!(source.col(AuditColumn::QueryId), "`queryId`");
+    assert_eq!(source.col(AuditColumn::Catalog), "`catalog`");
+}
+
+#[tokio::test]
+async fn test_audit_source_settings_override_config() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.audit_source_service;
+
+    let source = service.get_audit_source(&cluster).await.unwrap();
+    assert_eq!(source.settings, AuditSourceSettings::default());
+    assert_eq!(source.effective.database, "starrocks_audit_db__");
+    assert_eq!(source.effective.table, "starrocks_audit_tbl__");
+    assert!(!source.effective.detected);
+
+    let settings = AuditSourceSettings {
+        database: Some("ops_audit".to_string()),
+        table: None,
+        column_mapping: BTreeMap::from([(AuditColumn::QueryTime, "query_time".to_string())]),
+    };
+    let source = service
+        .update_settings(&cluster, settings.clone())
+        .await
+        .unwrap();
+    assert_eq!(source.settings, settings);
+    assert!(source.updated_at.is_some());
+
+    // Every consumer resolves the same per-cluster source
+    let resolved = service.resolve(&cluster).await.unwrap();
+    assert_eq!(resolved.full_table_name(), "`ops_audit`.`starrocks_audit_tbl__`");
+    assert_eq!(resolved.col(AuditColumn::QueryTime), "`query_time`");
+
+    let source = service.reset_settings(&cluster).await.unwrap();
+    assert_eq!(source.settings, AuditSourceSettings::default());
+    assert_eq!(source.effective.database, "starrocks_audit_db__");
+}
+
+#[tokio::test]
+async fn test_audit_source_rejects_invalid_identifiers() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.audit_source_service;
+
+    for settings in [
+        AuditSourceSettings {
+            database: Some("audit; DROP TABLE t".to_string()),
+            ..Default::default()
+        },
+        AuditSourceSettings { table: Some("tbl`".to_string()), ..Default::default() },
+        AuditSourceSettings { table: Some(String::new()), ..Default::default() },
+        AuditSourceSettings {
+            column_mapping: BTreeMap::from([(AuditColumn::Stmt, "stmt) OR (1".to_string())]),
+            ..Default::default()
+        },
+    ] {
+        let err = service
+            .update_settings(&cluster, settings.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", settings);
+
+        let err = service
+            .validate(&cluster, Some(settings.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", settings);
+    }
+}
+
+#[tokio::test]
+async fn test_validate_fails_when_cluster_is_unreachable() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+
+    let result = state.audit_source_service.validate(&cluster, None).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_audit_source_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    for (method, path, expected) in [
+        ("GET", "/api/clusters/3/audit-source", "audit_source"),
+        ("PUT", "/api/clusters/3/audit-source", "audit_source:update"),
+        ("DELETE", "/api/clusters/3/audit-source", "audit_source:update"),
+        ("POST", "/api/clusters/3/audit-source/validate", "audit_source:validate"),
+    ] {
+        let (resource, action) = extract_permission(method, path).unwrap();
+        assert_eq!((resource.as_str(), action.as_str()), ("clusters", expected), "{}", path);
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions WHERE resource = 'clusters' AND action = ?",
+        )
+        .bind(expected)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "{}", expected);
+    }
+}
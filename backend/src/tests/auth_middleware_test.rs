@@ -5,9 +5,10 @@ use crate::middleware::{
     AuthState, auth::auth_middleware, permission_extractor::extract_permission,
 };
 use crate::services::casbin_service::CasbinService;
+use crate::services::{ApiTokenService, SessionService};
 use crate::tests::common::{
-    assign_role_to_user, create_role, create_test_casbin_service, create_test_db, create_test_user,
-    grant_permissions, setup_test_data,
+    assign_role_to_user, create_role, create_test_casbin_service, create_test_db,
+    create_test_session_token, create_test_user, grant_permissions, setup_test_data,
 };
 use crate::utils::JwtUtil;
 use axum::{
@@ -35,7 +36,9 @@ fn create_test_router(
     casbin_service: Arc<CasbinService>,
     db: SqlitePool,
 ) -> Router {
-    let auth_state = AuthState { jwt_util, casbin_service, db };
+    let api_token_service = Arc::new(ApiTokenService::new(db.clone()));
+    let session_service = Arc::new(SessionService::new(db.clone(), jwt_util.clone(), "7d"));
+    let auth_state = AuthState { jwt_util, casbin_service, api_token_service, session_service, db };
 
     Router::new()
         .route("/api/roles", get(mock_handler))
@@ -48,13 +51,6 @@ fn create_test_router(
         .route_layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware))
 }
 
-/// Generate JWT token for a user
-fn generate_token(jwt_util: &JwtUtil, user_id: i64, username: &str) -> String {
-    jwt_util
-        .generate_token(user_id, username)
-        .expect("Failed to generate token")
-}
-
 async fn create_menu_only_role(pool: &sqlx::SqlitePool) -> i64 {
     let role_id = create_role(pool, "ops", "Operator", "Operator role", false).await;
 
@@ -93,7 +89,7 @@ async fn test_admin_user_has_all_permissions() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token for admin user
-    let token = generate_token(&jwt_util, admin_user_id, "admin_user");
+    let token = create_test_session_token(&pool, &jwt_util, admin_user_id, "admin_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -159,7 +155,8 @@ async fn test_operator_user_has_limited_permissions() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token for operator user
-    let token = generate_token(&jwt_util, operator_user_id, "operator_user");
+    let token =
+        create_test_session_token(&pool, &jwt_util, operator_user_id, "operator_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -226,7 +223,7 @@ async fn test_user_with_no_role_has_no_permissions() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token for user
-    let token = generate_token(&jwt_util, user_id, "no_role_user");
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "no_role_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -323,7 +320,7 @@ async fn test_custom_role_with_specific_permissions() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token for user
-    let token = generate_token(&jwt_util, user_id, "custom_user");
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "custom_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -413,9 +410,11 @@ async fn test_multiple_users_different_permissions() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate tokens for all users
-    let admin_token = generate_token(&jwt_util, admin_user_id, "admin1");
-    let operator_token = generate_token(&jwt_util, operator_user_id, "operator1");
-    let no_role_token = generate_token(&jwt_util, no_role_user_id, "norole1");
+    let admin_token = create_test_session_token(&pool, &jwt_util, admin_user_id, "admin1").await;
+    let operator_token =
+        create_test_session_token(&pool, &jwt_util, operator_user_id, "operator1").await;
+    let no_role_token =
+        create_test_session_token(&pool, &jwt_util, no_role_user_id, "norole1").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -536,7 +535,7 @@ async fn test_permission_check_skipped_for_public_endpoint() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token
-    let token = generate_token(&jwt_util, user_id, "test_user");
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "test_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -616,6 +615,49 @@ async fn test_missing_token_returns_unauthorized() {
     );
 }
 
+#[tokio::test]
+async fn test_deleted_or_disabled_user_is_refused() {
+    let pool = create_test_db().await;
+    let casbin_service = create_test_casbin_service().await;
+    let jwt_util = Arc::new(JwtUtil::new("test-secret-key-for-deleted-user-test", "24h"));
+
+    let data = setup_test_data(&pool).await;
+    let user_id = create_test_user(&pool, "leaving_user").await;
+    assign_role_to_user(&pool, user_id, data.admin_role_id).await;
+    casbin_service.reload_policies_from_db(&pool).await.unwrap();
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "leaving_user").await;
+    let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
+
+    let status = |app: Router| {
+        let req = Request::builder()
+            .uri("/api/roles")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        async move { app.oneshot(req).await.unwrap().status() }
+    };
+    assert_eq!(status(app.clone()).await, StatusCode::OK);
+
+    sqlx::query("UPDATE users SET is_active = 0 WHERE id = ?")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status(app.clone()).await, StatusCode::UNAUTHORIZED);
+
+    // The user row is gone while its session survives (no cascade)
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM users WHERE id = ?")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status(app).await, StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn test_user_permissions_updated_after_role_change() {
     let pool = create_test_db().await;
@@ -637,7 +679,7 @@ async fn test_user_permissions_updated_after_role_change() {
     casbin_service.reload_policies_from_db(&pool).await.unwrap();
 
     // Generate token
-    let token = generate_token(&jwt_util, user_id, "dynamic_user");
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "dynamic_user").await;
 
     // Create test router
     let app = create_test_router(jwt_util.clone(), casbin_service.clone(), pool.clone());
@@ -0,0 +1,518 @@
+// Cluster-level role permissions: grants limited to a cluster, catalog or database
+
+use crate::AppState;
+use crate::handlers;
+use crate::middleware::{AuthState, OrgContext, auth_middleware, cluster_scope_middleware};
+use crate::models::{
+    Cluster, CreateClusterRequest, PermissionScope, PermissionScopeLevel, ScopedPermissionGrant,
+    UpdateRolePermissionsRequest,
+};
+use crate::services::sql_policy_service;
+use crate::tests::common::{
+    MultiTenantTestData, assign_role_to_user, create_test_app_state, create_test_db,
+    create_test_session_token, setup_multi_tenant_test_data,
+};
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{StatusCode, header};
+use axum::routing::get;
+use serde_json::Value;
+use std::sync::Arc;
+use tower::{Layer, ServiceExt};
+
+struct Fixture {
+    state: Arc<AppState>,
+    data: MultiTenantTestData,
+    prod: Cluster,
+    staging: Cluster,
+    other_org: Cluster,
+    role_id: i64,
+}
+
+async fn create_cluster(state: &AppState, name: &str, organization_id: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: format!("{}.example.com", name),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: Some(organization_id),
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            1,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+async fn permission_id(pool: &sqlx::SqlitePool, code: &str) -> i64 {
+    sqlx::query_scalar("SELECT id FROM permissions WHERE code = ?")
+        .bind(code)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+/// Cluster permissions, two clusters in org1 and one in org2, and an org1 role held by the
+/// org1 regular user
+async fn setup() -> Fixture {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action) VALUES
+         ('menu:alerts', 'Alerts', 'menu', 'alerts', NULL),
+         ('api:clusters:list', 'List clusters', 'api', 'clusters', 'list'),
+         ('api:clusters:get', 'Get cluster', 'api', 'clusters', 'get'),
+         ('api:clusters:alerts:rules:list', 'List rules', 'api', 'clusters', 'alerts:rules:list'),
+         ('api:clusters:queries:execute', 'Execute', 'api', 'clusters', 'queries:execute')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "UPDATE permissions SET parent_id = (SELECT id FROM permissions WHERE code = 'menu:alerts')
+         WHERE code = 'api:clusters:alerts:rules:list'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let role_id: i64 = sqlx::query_scalar(
+        "INSERT INTO roles (code, name, is_system, organization_id)
+         VALUES ('prod_viewer', 'Prod viewer', 0, ?) RETURNING id",
+    )
+    .bind(data.org1_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assign_role_to_user(&pool, data.org1_regular_user_id, role_id).await;
+
+    let state = create_test_app_state(&pool).await;
+    let prod = create_cluster(&state, "prod", data.org1_id).await;
+    let staging = create_cluster(&state, "staging", data.org1_id).await;
+    let other_org = create_cluster(&state, "other", data.org2_id).await;
+    Fixture { state, data, prod, staging, other_org, role_id }
+}
+
+fn grant(permission_id: i64, cluster_id: i64) -> ScopedPermissionGrant {
+    ScopedPermissionGrant { permission_id, cluster_id, catalog: None, database: None }
+}
+
+async fn set_role_permissions(
+    fixture: &Fixture,
+    permission_ids: Vec<i64>,
+    scoped: Option<Vec<ScopedPermissionGrant>>,
+) -> crate::utils::ApiResult<()> {
+    fixture
+        .state
+        .role_service
+        .assign_permissions_to_role(
+            fixture.role_id,
+            UpdateRolePermissionsRequest { permission_ids, scoped_permissions: scoped },
+            Some(fixture.data.org1_id),
+            false,
+        )
+        .await
+}
+
+/// Cluster routes behind the authentication middleware, with `/api/clusters/:id/...`
+/// rewritten before routing as in the application
+fn app(state: &Arc<AppState>) -> Router {
+    let auth_state = AuthState {
+        jwt_util: Arc::clone(&state.jwt_util),
+        casbin_service: Arc::clone(&state.casbin_service),
+        api_token_service: Arc::clone(&state.api_token_service),
+        session_service: Arc::clone(&state.session_service),
+        db: state.db.clone(),
+    };
+    let router = Router::new()
+        .route("/api/clusters", get(handlers::cluster::list_clusters))
+        .route("/api/clusters/:id", get(handlers::cluster::get_cluster))
+        .route("/api/clusters/alerts/rules", get(handlers::alert::list_alert_rules))
+        .route_layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware))
+        .with_state(Arc::clone(state));
+    Router::new()
+        .fallback_service(axum::middleware::from_fn(cluster_scope_middleware).layer(router))
+}
+
+async fn call(app: &Router, uri: &str, token: &str) -> (StatusCode, Value) {
+    let request = Request::get(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[test]
+fn test_policy_key_round_trip() {
+    let scopes = [
+        PermissionScope { cluster_id: 3, catalog: None, database: None },
+        PermissionScope { cluster_id: 3, catalog: Some("hive".into()), database: None },
+        PermissionScope {
+            cluster_id: 3,
+            catalog: Some("default_catalog".into()),
+            database: Some("sales".into()),
+        },
+    ];
+    assert_eq!(scopes[2].policy_key(), "cluster:3/catalog:default_catalog/db:sales/*");
+    for scope in scopes {
+        assert_eq!(PermissionScope::from_policy_key(&scope.policy_key()), Some(scope));
+    }
+    assert_eq!(PermissionScope::from_policy_key("*"), None);
+}
+
+#[tokio::test]
+async fn test_scoped_grants_are_stored_and_loaded_into_casbin() {
+    let fixture = setup().await;
+    let pool = &fixture.state.db;
+    let get = permission_id(pool, "api:clusters:get").await;
+    let rules = permission_id(pool, "api:clusters:alerts:rules:list").await;
+
+    set_role_permissions(
+        &fixture,
+        vec![],
+        Some(vec![grant(get, fixture.prod.id), grant(rules, fixture.prod.id)]),
+    )
+    .await
+    .unwrap();
+
+    let role = fixture
+        .state
+        .role_service
+        .get_role_with_permissions(fixture.role_id, Some(fixture.data.org1_id), false)
+        .await
+        .unwrap();
+    assert_eq!(role.scoped_permissions.len(), 2);
+    assert!(
+        role.scoped_permissions
+            .iter()
+            .all(|p| p.cluster_name == "prod")
+    );
+    // The menu of a scoped permission is granted organization-wide
+    assert_eq!(
+        role.permissions
+            .iter()
+            .map(|p| p.code.as_str())
+            .collect::<Vec<_>>(),
+        vec!["menu:alerts"]
+    );
+
+    let casbin = &fixture.state.casbin_service;
+    let key = format!("org:{}:clusters", fixture.data.org1_id);
+    let user = fixture.data.org1_regular_user_id;
+    assert!(!casbin.enforce(user, &key, "get").await.unwrap());
+    assert_eq!(
+        casbin.granted_scopes(user, &key, "get").await.unwrap(),
+        vec![PermissionScope { cluster_id: fixture.prod.id, catalog: None, database: None }]
+    );
+
+    // Omitting the scoped grants keeps them, an empty list removes them
+    set_role_permissions(&fixture, vec![], None).await.unwrap();
+    assert_eq!(
+        casbin
+            .granted_scopes(user, &key, "get")
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    set_role_permissions(&fixture, vec![], Some(vec![]))
+        .await
+        .unwrap();
+    assert!(
+        casbin
+            .granted_scopes(user, &key, "get")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_scoped_grants_are_validated() {
+    let fixture = setup().await;
+    let pool = &fixture.state.db;
+    let get = permission_id(pool, "api:clusters:get").await;
+    let execute = permission_id(pool, "api:clusters:queries:execute").await;
+    let list = permission_id(pool, "api:clusters:list").await;
+    let menu = permission_id(pool, "menu:alerts").await;
+
+    // A cluster of another organization
+    assert!(
+        set_role_permissions(&fixture, vec![], Some(vec![grant(get, fixture.other_org.id)]))
+            .await
+            .is_err()
+    );
+    // Menus are not scoped
+    assert!(
+        set_role_permissions(&fixture, vec![], Some(vec![grant(menu, fixture.prod.id)]))
+            .await
+            .is_err()
+    );
+    // Databases only for the SQL editor and materialized views
+    let database = |permission_id| ScopedPermissionGrant {
+        database: Some("sales".to_string()),
+        ..grant(permission_id, fixture.prod.id)
+    };
+    assert!(
+        set_role_permissions(&fixture, vec![], Some(vec![database(get)]))
+            .await
+            .is_err()
+    );
+    assert!(
+        set_role_permissions(
+            &fixture,
+            vec![],
+            Some(vec![ScopedPermissionGrant {
+                database: Some("a/b".to_string()),
+                ..database(execute)
+            }])
+        )
+        .await
+        .is_err()
+    );
+
+    // A database without a catalog belongs to the default catalog; duplicates collapse
+    set_role_permissions(
+        &fixture,
+        vec![],
+        Some(vec![database(execute), database(execute), grant(list, fixture.staging.id)]),
+    )
+    .await
+    .unwrap();
+    let role = fixture
+        .state
+        .role_service
+        .get_role_with_permissions(fixture.role_id, Some(fixture.data.org1_id), false)
+        .await
+        .unwrap();
+    assert_eq!(role.scoped_permissions.len(), 2);
+    let execute_grant = role
+        .scoped_permissions
+        .iter()
+        .find(|p| p.permission_id == execute)
+        .unwrap();
+    assert_eq!(execute_grant.catalog.as_deref(), Some("default_catalog"));
+    assert_eq!(execute_grant.database.as_deref(), Some("sales"));
+}
+
+#[tokio::test]
+async fn test_permission_tree_reports_scope_levels() {
+    let fixture = setup().await;
+    let tree = fixture
+        .state
+        .permission_service
+        .get_permission_tree()
+        .await
+        .unwrap();
+    let nodes: Vec<_> = tree
+        .iter()
+        .flat_map(|node| std::iter::once(node).chain(node.children.iter()))
+        .map(|node| (node.code.as_str(), node.scope_level))
+        .collect();
+
+    assert!(nodes.contains(&("api:clusters:get", Some(PermissionScopeLevel::Cluster))));
+    assert!(
+        nodes.contains(&("api:clusters:queries:execute", Some(PermissionScopeLevel::Database)))
+    );
+    assert!(nodes.contains(&("menu:alerts", None)));
+    assert!(nodes.contains(&("api:users:list", None)));
+}
+
+#[tokio::test]
+async fn test_middleware_limits_requests_to_granted_clusters() {
+    let fixture = setup().await;
+    let state = &fixture.state;
+    let pool = &state.db;
+    let get = permission_id(pool, "api:clusters:get").await;
+    let list = permission_id(pool, "api:clusters:list").await;
+    let rules = permission_id(pool, "api:clusters:alerts:rules:list").await;
+    set_role_permissions(
+        &fixture,
+        vec![],
+        Some(vec![
+            grant(get, fixture.prod.id),
+            grant(list, fixture.prod.id),
+            grant(rules, fixture.prod.id),
+        ]),
+    )
+    .await
+    .unwrap();
+
+    let user = fixture.data.org1_regular_user_id;
+    let token = create_test_session_token(pool, &state.jwt_util, user, "org1_user").await;
+    let app = app(state);
+
+    let (status, _) = call(&app, &format!("/api/clusters/{}", fixture.prod.id), &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&app, &format!("/api/clusters/{}", fixture.staging.id), &token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Listings only show the granted clusters
+    let (status, body) = call(&app, "/api/clusters", &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["prod"]);
+
+    // Addressed by path, or resolved from the user's active cluster
+    let (status, _) =
+        call(&app, &format!("/api/clusters/{}/alerts/rules", fixture.prod.id), &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) =
+        call(&app, &format!("/api/clusters/{}/alerts/rules", fixture.staging.id), &token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    state
+        .cluster_service
+        .set_user_active_cluster(user, fixture.prod.id)
+        .await
+        .unwrap();
+    let (status, _) = call(&app, "/api/clusters/alerts/rules", &token).await;
+    assert_eq!(status, StatusCode::OK);
+    state
+        .cluster_service
+        .set_user_active_cluster(user, fixture.staging.id)
+        .await
+        .unwrap();
+    let (status, body) = call(&app, "/api/clusters/alerts/rules", &token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("Permission denied on cluster")
+    );
+
+    // An organization-wide grant covers every cluster again
+    set_role_permissions(&fixture, vec![rules], Some(vec![]))
+        .await
+        .unwrap();
+    let (status, _) = call(&app, "/api/clusters/alerts/rules", &token).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[test]
+fn test_database_scopes_confine_the_sql_editor() {
+    let scope = |cluster_id, database: Option<&str>| PermissionScope {
+        cluster_id,
+        catalog: database.map(|_| "default_catalog".to_string()),
+        database: database.map(str::to_string),
+    };
+    let ctx = OrgContext {
+        user_id: 1,
+        username: "analyst".to_string(),
+        organization_id: Some(1),
+        is_super_admin: false,
+        cluster_id: None,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: Some(vec![scope(1, Some("sales")), scope(2, None)]),
+    };
+
+    // Whole cluster 2, one database of cluster 1
+    assert!(ctx.database_scopes(2).is_none());
+    assert!(
+        ctx.check_database_access(2, "default_catalog", "hr")
+            .is_ok()
+    );
+    assert!(
+        ctx.check_database_access(1, "default_catalog", "sales")
+            .is_ok()
+    );
+    assert!(
+        ctx.check_database_access(1, "default_catalog", "hr")
+            .is_err()
+    );
+    assert!(ctx.check_database_access(1, "hive", "sales").is_err());
+    assert!(!ctx.can_access_cluster(3));
+
+    let scopes = ctx.database_scopes(1).unwrap();
+    let check = |sql: &str, database: Option<&str>| {
+        sql_policy_service::check_scoped_statements(&scopes, &[sql.to_string()], None, database)
+    };
+    assert!(check("SELECT * FROM orders", Some("sales")).is_ok());
+    assert!(check("SELECT * FROM sales.orders JOIN sales.items USING (id)", None).is_ok());
+    assert!(check("INSERT INTO sales.orders SELECT * FROM hr.salaries", None).is_err());
+    assert!(check("SELECT 1", Some("hr")).is_err());
+    assert!(check("SELECT * FROM hive.sales.orders", None).is_err());
+    assert!(check("CREATE TABLE sales.t2 (id INT)", None).is_ok());
+    assert!(check("SHOW TABLES FROM sales", None).is_ok());
+    // Tables listed after derived tables are checked too
+    assert!(check("SELECT * FROM (SELECT 1) x, hr.salaries", Some("sales")).is_err());
+    assert!(
+        check("SELECT * FROM t1 JOIN (SELECT 1) s ON 1=1, hr.salaries", Some("sales")).is_err()
+    );
+    assert!(check("SELECT * FROM (SELECT 1) x, sales.orders", None).is_ok());
+
+    // Statements on no database are refused, even with an in-scope session database
+    for sql in [
+        "GRANT SELECT ON *.* TO 'bob'",
+        "CREATE USER 'bob' IDENTIFIED BY 'x'",
+        "SET GLOBAL query_timeout = 1",
+        "ALTER SYSTEM ADD BACKEND 'be:9050'",
+        "ADMIN SET FRONTEND CONFIG ('a' = 'b')",
+        "KILL 42",
+        "CREATE CATALOG hive2 PROPERTIES ('type' = 'hive')",
+    ] {
+        assert!(check(sql, Some("sales")).is_err(), "{}", sql);
+    }
+    assert!(check("SELECT 1", None).is_err());
+    assert!(check("SELECT 1", Some("sales")).is_ok());
+
+    // The session context follows USE through a batch
+    let batch = |sqls: &[&str]| {
+        let sqls: Vec<String> = sqls.iter().map(|s| s.to_string()).collect();
+        sql_policy_service::check_scoped_statements(&scopes, &sqls, None, None)
+    };
+    assert!(batch(&["USE sales", "SELECT * FROM orders"]).is_ok());
+    assert!(batch(&["USE sales", "USE hr", "SELECT * FROM salaries"]).is_err());
+
+    // Catalog and database are matched as pairs
+    let pairs = [
+        PermissionScope {
+            cluster_id: 1,
+            catalog: Some("catA".into()),
+            database: Some("db1".into()),
+        },
+        PermissionScope {
+            cluster_id: 1,
+            catalog: Some("catB".into()),
+            database: Some("db2".into()),
+        },
+    ];
+    let pairs: Vec<&PermissionScope> = pairs.iter().collect();
+    let check = |sql: &str| {
+        sql_policy_service::check_scoped_statements(&pairs, &[sql.to_string()], None, None)
+    };
+    assert!(check("SELECT * FROM catA.db1.t").is_ok());
+    assert!(check("SELECT * FROM catB.db2.t").is_ok());
+    assert!(check("SELECT * FROM catA.db2.t").is_err());
+    assert!(check("SELECT * FROM catB.db1.t").is_err());
+    let sqls = ["SET CATALOG catA".to_string(), "USE db2".to_string()];
+    assert!(sql_policy_service::check_scoped_statements(&pairs, &sqls, None, None).is_err());
+}
@@ -0,0 +1,503 @@
+// Cluster TLS settings tests
+
+use crate::models::{Cluster, ClusterResponse, ClusterTlsRequest, UpdateClusterRequest};
+use crate::services::{MySQLPoolManager, StarRocksClient};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::cluster_tls::describe_connection_error;
+use crate::utils::{ApiError, ClusterTls};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+use openssl::x509::extension::{BasicConstraints, ExtendedKeyUsage, SubjectAlternativeName};
+use openssl::x509::{X509, X509NameBuilder};
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+struct TestPki {
+    ca: X509,
+    ca_key: PKey<Private>,
+}
+
+fn ec_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+fn build_cert(
+    common_name: &str,
+    key: &PKey<Private>,
+    issuer: Option<&TestPki>,
+    server_name: Option<&str>,
+) -> X509 {
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(next_serial())
+        .unwrap()
+        .to_asn1_integer()
+        .unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+
+    match issuer {
+        Some(pki) => {
+            builder.set_issuer_name(pki.ca.subject_name()).unwrap();
+            let usage = match server_name {
+                Some(_) => ExtendedKeyUsage::new().server_auth().build().unwrap(),
+                None => ExtendedKeyUsage::new().client_auth().build().unwrap(),
+            };
+            builder.append_extension(usage).unwrap();
+            if let Some(server_name) = server_name {
+                let san = SubjectAlternativeName::new()
+                    .dns(server_name)
+                    .build(&builder.x509v3_context(Some(&pki.ca), None))
+                    .unwrap();
+                builder.append_extension(san).unwrap();
+            }
+            builder.sign(&pki.ca_key, MessageDigest::sha256()).unwrap();
+        },
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            builder
+                .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                .unwrap();
+            builder.sign(key, MessageDigest::sha256()).unwrap();
+        },
+    }
+    builder.build()
+}
+
+fn next_serial() -> u32 {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    static SERIAL: AtomicU32 = AtomicU32::new(1);
+    SERIAL.fetch_add(1, Ordering::Relaxed)
+}
+
+impl TestPki {
+    fn new() -> Self {
+        let ca_key = ec_key();
+        let ca = build_cert("Test CA", &ca_key, None, None);
+        Self { ca, ca_key }
+    }
+
+    fn ca_pem(&self) -> String {
+        String::from_utf8(self.ca.to_pem().unwrap()).unwrap()
+    }
+
+    /// (certificate PEM, key PEM, certificate, key) of a leaf signed by the CA
+    fn leaf(&self, server_name: Option<&str>) -> (String, String, X509, PKey<Private>) {
+        let key = ec_key();
+        let cert = build_cert("leaf", &key, Some(self), server_name);
+        (
+            String::from_utf8(cert.to_pem().unwrap()).unwrap(),
+            String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap(),
+            cert,
+            key,
+        )
+    }
+}
+
+/// Blocking HTTPS server answering every request with a metrics line.
+/// With `client_ca`, connections without a client certificate signed by it are rejected.
+fn spawn_tls_fe(cert: X509, key: PKey<Private>, client_ca: Option<X509>) -> i32 {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&cert).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    if let Some(client_ca) = client_ca {
+        acceptor.cert_store_mut().add_cert(client_ca).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+    let acceptor = Arc::new(acceptor.build());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port() as i32;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let Ok(mut stream) = acceptor.accept(stream) else {
+                continue;
+            };
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let body = "starrocks_fe_query_total 1\n";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.shutdown();
+        }
+    });
+    port
+}
+
+fn tls_cluster(manager: &MySQLPoolManager, host: &str, port: i32) -> Cluster {
+    Cluster {
+        id: 7,
+        name: "tls".to_string(),
+        description: None,
+        fe_host: host.to_string(),
+        fe_http_port: port,
+        fe_query_port: port,
+        username: "root".to_string(),
+        password_encrypted: manager.cipher().encrypt("secret").unwrap(),
+        enable_ssl: true,
+        connection_timeout: 5,
+        tags: None,
+        catalog: "default_catalog".to_string(),
+        is_active: false,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        created_by: None,
+        organization_id: None,
+        deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls_ca_cert: None,
+        tls_client_cert: None,
+        tls_client_key_encrypted: None,
+        tls_verify_hostname: true,
+    }
+}
+
+fn tls_request(
+    ca_cert: Option<String>,
+    client_cert: Option<String>,
+    client_key: Option<String>,
+) -> ClusterTlsRequest {
+    ClusterTlsRequest { ca_cert, client_cert, client_key, verify_hostname: true }
+}
+
+#[tokio::test]
+async fn test_tls_settings_are_validated_and_key_encrypted() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let service = &state.cluster_service;
+    let pki = TestPki::new();
+    let (client_cert, client_key, _, _) = pki.leaf(None);
+    let (_, other_key, _, _) = pki.leaf(None);
+
+    let invalid_ca = service.prepare_tls(tls_request(Some("not a pem".into()), None, None), None);
+    assert!(
+        matches!(invalid_ca, Err(ApiError::ValidationError(ref m)) if m.contains("CA certificate"))
+    );
+    let mismatch =
+        service.prepare_tls(tls_request(None, Some(client_cert.clone()), Some(other_key)), None);
+    assert!(
+        matches!(mismatch, Err(ApiError::ValidationError(ref m)) if m.contains("does not match"))
+    );
+    let missing_key = service.prepare_tls(tls_request(None, Some(client_cert.clone()), None), None);
+    assert!(matches!(missing_key, Err(ApiError::ValidationError(_))));
+    let missing_cert = service.prepare_tls(tls_request(None, None, Some(client_key.clone())), None);
+    assert!(matches!(missing_cert, Err(ApiError::ValidationError(_))));
+
+    let cluster = service
+        .create_cluster(
+            crate::models::CreateClusterRequest {
+                name: "tls-cluster".to_string(),
+                description: None,
+                fe_host: "fe.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: true,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: Some(data.org1_id),
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: Some(tls_request(
+                    Some(pki.ca_pem()),
+                    Some(client_cert.clone()),
+                    Some(client_key.clone()),
+                )),
+            },
+            data.super_admin_user_id,
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+    let stored_key = cluster.tls_client_key_encrypted.clone().unwrap();
+    assert!(crate::utils::CredentialCipher::is_encrypted(&stored_key));
+    assert_eq!(
+        state
+            .mysql_pool_manager
+            .cipher()
+            .decrypt(&stored_key)
+            .unwrap(),
+        client_key.trim()
+    );
+    let response = ClusterResponse::from(cluster.clone());
+    assert!(response.tls_client_key_configured);
+    assert!(
+        !serde_json::to_string(&response)
+            .unwrap()
+            .contains("PRIVATE KEY")
+    );
+
+    // Updating without a key keeps the stored one; turning verification off is stored
+    let update = |tls: ClusterTlsRequest| UpdateClusterRequest {
+        name: None,
+        description: None,
+        fe_host: None,
+        fe_http_port: None,
+        fe_query_port: None,
+        username: None,
+        password: None,
+        enable_ssl: None,
+        connection_timeout: None,
+        tags: None,
+        catalog: None,
+        organization_id: None,
+        deployment_mode: None,
+        fe_endpoints: None,
+        tls: Some(tls),
+    };
+    let updated = service
+        .update_cluster(
+            cluster.id,
+            update(ClusterTlsRequest {
+                verify_hostname: false,
+                ..tls_request(None, Some(client_cert), None)
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.tls_client_key_encrypted.as_deref(), Some(stored_key.as_str()));
+    assert!(updated.tls_ca_cert.is_none());
+    assert!(!updated.tls_verify_hostname);
+
+    let cleared = service
+        .update_cluster(cluster.id, update(tls_request(None, None, None)))
+        .await
+        .unwrap();
+    assert!(cleared.tls_client_cert.is_none() && cleared.tls_client_key_encrypted.is_none());
+    assert!(cleared.tls_verify_hostname);
+}
+
+#[test]
+fn test_mysql_ssl_opts_follow_cluster_settings() {
+    let manager = MySQLPoolManager::default();
+    let pki = TestPki::new();
+    let (client_cert, client_key, _, _) = pki.leaf(None);
+
+    let mut cluster = tls_cluster(&manager, "fe.example.com", 9030);
+    cluster.enable_ssl = false;
+    assert!(
+        ClusterTls::from_cluster(&cluster, manager.cipher())
+            .unwrap()
+            .is_none()
+    );
+
+    cluster.enable_ssl = true;
+    cluster.tls_ca_cert = Some(pki.ca_pem());
+    cluster.tls_client_cert = Some(client_cert);
+    cluster.tls_client_key_encrypted = Some(manager.cipher().encrypt(&client_key).unwrap());
+    cluster.tls_verify_hostname = false;
+
+    let tls = ClusterTls::from_cluster(&cluster, manager.cipher())
+        .unwrap()
+        .unwrap();
+    let opts = tls.mysql_ssl_opts();
+    assert_eq!(opts.root_certs().len(), 1);
+    assert!(opts.client_identity().is_some());
+    assert!(opts.skip_domain_validation());
+    assert!(tls.summary().contains("mutual TLS"));
+
+    // A certificate without its key is rejected instead of silently dropping mTLS
+    cluster.tls_client_key_encrypted = None;
+    assert!(ClusterTls::from_cluster(&cluster, manager.cipher()).is_err());
+}
+
+#[tokio::test]
+async fn test_http_client_uses_ca_and_hostname_verification() {
+    let manager = Arc::new(MySQLPoolManager::default());
+    let pki = TestPki::new();
+    let (_, _, server_cert, server_key) = pki.leaf(Some("localhost"));
+    let port = spawn_tls_fe(server_cert, server_key, None);
+
+    // Unknown CA: the certificate error is visible in the message
+    let cluster = tls_cluster(&manager, "localhost", port);
+    let err = StarRocksClient::new(cluster.clone(), manager.clone())
+        .get_metrics()
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(describe_connection_error(&err).starts_with("TLS error"), "{}", err);
+
+    // Trusted CA
+    let trusted = Cluster { tls_ca_cert: Some(pki.ca_pem()), ..cluster.clone() };
+    let metrics = StarRocksClient::new(trusted.clone(), manager.clone())
+        .get_metrics()
+        .await
+        .unwrap();
+    assert!(metrics.contains("starrocks_fe_query_total"));
+
+    // The certificate is issued for localhost only
+    let by_ip = Cluster { fe_host: "127.0.0.1".to_string(), ..trusted };
+    assert!(
+        StarRocksClient::new(by_ip.clone(), manager.clone())
+            .get_metrics()
+            .await
+            .is_err()
+    );
+    let unverified = Cluster { tls_verify_hostname: false, ..by_ip };
+    assert!(
+        StarRocksClient::new(unverified, manager)
+            .get_metrics()
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_http_client_presents_client_certificate() {
+    let manager = Arc::new(MySQLPoolManager::default());
+    let pki = TestPki::new();
+    let (_, _, server_cert, server_key) = pki.leaf(Some("localhost"));
+    let (client_cert, client_key, _, _) = pki.leaf(None);
+    let port = spawn_tls_fe(server_cert, server_key, Some(pki.ca.clone()));
+
+    let cluster =
+        Cluster { tls_ca_cert: Some(pki.ca_pem()), ..tls_cluster(&manager, "localhost", port) };
+    assert!(
+        StarRocksClient::new(cluster.clone(), manager.clone())
+            .get_metrics()
+            .await
+            .is_err()
+    );
+
+    let mutual = Cluster {
+        tls_client_cert: Some(client_cert),
+        tls_client_key_encrypted: Some(manager.cipher().encrypt(&client_key).unwrap()),
+        ..cluster
+    };
+    let metrics = StarRocksClient::new(mutual, manager)
+        .get_metrics()
+        .await
+        .unwrap();
+    assert!(metrics.contains("starrocks_fe_query_total"));
+}
+
+#[tokio::test]
+async fn test_connection_test_reports_tls_configuration_errors() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+
+    let mut cluster = tls_cluster(&state.mysql_pool_manager, "127.0.0.1", 1);
+    cluster.id = 0;
+    cluster.tls_ca_cert =
+        Some("-----BEGIN CERTIFICATE-----\nbroken\n-----END CERTIFICATE-----".into());
+
+    let health = state
+        .cluster_service
+        .get_cluster_health_for_cluster(&cluster)
+        .await
+        .unwrap();
+    assert_eq!(health.status, crate::models::HealthStatus::Critical);
+    assert_eq!(health.checks.len(), 1);
+    assert_eq!(health.checks[0].name, "TLS Configuration");
+    assert!(
+        health.checks[0]
+            .message
+            .contains("Invalid TLS CA certificate")
+    );
+
+    assert_eq!(describe_connection_error("Connection refused"), "Connection refused");
+}
+
+#[tokio::test]
+async fn test_cluster_creation_audit_redacts_tls_private_key() {
+    use crate::middleware::{OrgContext, admin_audit_middleware};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let pki = TestPki::new();
+    let (client_cert, client_key, _, _) = pki.leaf(None);
+
+    let org_ctx = OrgContext {
+        user_id: data.super_admin_user_id,
+        username: "super_admin".to_string(),
+        organization_id: None,
+        is_super_admin: true,
+        cluster_id: None,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: None,
+    };
+    let app = axum::Router::new()
+        .route("/api/clusters", axum::routing::post(crate::handlers::cluster::create_cluster))
+        .route_layer(axum::middleware::from_fn_with_state(
+            Arc::clone(&state),
+            admin_audit_middleware,
+        ))
+        .layer(axum::Extension(org_ctx))
+        .with_state(Arc::clone(&state));
+
+    let body = serde_json::json!({
+        "name": "audited-tls",
+        "fe_host": "fe.example.com",
+        "username": "root",
+        "password": "secret",
+        "enable_ssl": true,
+        "catalog": "default_catalog",
+        "tls": { "ca_cert": pki.ca_pem(), "client_cert": client_cert, "client_key": client_key },
+    });
+    let response = app
+        .oneshot(
+            Request::post("/api/clusters")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (payload,): (String,) = sqlx::query_as(
+        "SELECT payload FROM admin_audit_log WHERE action = 'clusters:create' ORDER BY id DESC LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(!payload.contains("PRIVATE KEY"), "{}", payload);
+    let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    assert_eq!(payload["name"], "audited-tls");
+    assert_eq!(payload["password"], "******");
+    assert_eq!(payload["tls"]["client_key"], "******");
+    assert!(
+        payload["tls"]["client_cert"]
+            .as_str()
+            .unwrap()
+            .contains("BEGIN CERTIFICATE")
+    );
+}
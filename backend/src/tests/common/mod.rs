@@ -472,3 +472,165 @@ pub async fn grant_permissions(pool: &SqlitePool, role_id: i64, permission_ids:
         .await
         .expect("Failed to commit permission grants");
 }
+
+/// Open a login session for a user and return an access token bound to it
+pub async fn create_test_session_token(
+    pool: &SqlitePool,
+    jwt_util: &crate::utils::JwtUtil,
+    user_id: i64,
+    username: &str,
+) -> String {
+    let session_id: i64 = sqlx::query_scalar(
+        "INSERT INTO user_sessions (user_id, refresh_token_hash, expires_at)
+         VALUES (?, ?, datetime('now', '+1 day')) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(crate::utils::crypto::random_hex(32))
+    .fetch_one(pool)
+    .await
+    .expect("Failed to create test session");
+
+    jwt_util
+        .generate_token(user_id, username, session_id)
+        .expect("Failed to generate token")
+}
+
+/// Build the full application state on a test database (no StarRocks cluster is contacted)
+pub async fn create_test_app_state(pool: &SqlitePool) -> Arc<crate::AppState> {
+    use crate::config::{
+        AlertConfig, AuditLogConfig, MetricsCollectorConfig, ProfileArchiveConfig,
+    };
+    use crate::services::*;
+    use crate::utils::JwtUtil;
+
+    let jwt_util = Arc::new(JwtUtil::new("test-secret", "24h"));
+    let process_metrics = Arc::new(crate::utils::ProcessMetrics::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
+    let cluster_service =
+        Arc::new(ClusterService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+    let alert_service = Arc::new(AlertService::new(
+        pool.clone(),
+        Arc::clone(&mysql_pool_manager),
+        AlertConfig::default(),
+    ));
+    let node_metrics_service = Arc::new(NodeMetricsService::new(pool.clone()));
+    let routine_load_service =
+        Arc::new(RoutineLoadService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+    let mv_refresh_service =
+        Arc::new(MvRefreshService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+    let variable_history_service =
+        Arc::new(VariableHistoryService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+    let audit_source_service = Arc::new(AuditSourceService::new(
+        pool.clone(),
+        Arc::clone(&mysql_pool_manager),
+        AuditLogConfig::default(),
+    ));
+    let metrics_collector_service = Arc::new(
+        MetricsCollectorService::new(
+            pool.clone(),
+            Arc::clone(&cluster_service),
+            Arc::clone(&mysql_pool_manager),
+            Arc::clone(&alert_service),
+            Arc::clone(&process_metrics),
+            Arc::clone(&node_metrics_service),
+            MetricsCollectorConfig { retention_days: 7, ..Default::default() },
+        )
+        .with_audit_source(Arc::clone(&audit_source_service))
+        .with_routine_load(Arc::clone(&routine_load_service))
+        .with_mv_refresh(Arc::clone(&mv_refresh_service))
+        .with_variable_history(Arc::clone(&variable_history_service)),
+    );
+    let data_statistics_service = Arc::new(DataStatisticsService::new(
+        pool.clone(),
+        Arc::clone(&cluster_service),
+        Arc::clone(&mysql_pool_manager),
+        Arc::clone(&audit_source_service),
+    ));
+    let casbin_service = create_test_casbin_service().await;
+    casbin_service
+        .reload_policies_from_db(pool)
+        .await
+        .expect("Failed to load Casbin policies");
+    let permission_service =
+        Arc::new(PermissionService::new(pool.clone(), Arc::clone(&casbin_service)));
+    let session_service = Arc::new(SessionService::new(pool.clone(), Arc::clone(&jwt_util), "7d"));
+    let two_factor_service = Arc::new(TwoFactorService::new(
+        pool.clone(),
+        Arc::new(crate::utils::CredentialCipher::ephemeral()),
+    ));
+
+    Arc::new(crate::AppState {
+        db: pool.clone(),
+        mysql_pool_manager: Arc::clone(&mysql_pool_manager),
+        jwt_util: Arc::clone(&jwt_util),
+        process_metrics: Arc::clone(&process_metrics),
+        metrics_exporter_token: None,
+        metrics_allow_unauthenticated_local: false,
+        auth_service: Arc::new(AuthService::new(
+            pool.clone(),
+            Arc::clone(&session_service),
+            Arc::clone(&two_factor_service),
+        )),
+        sso_service: Arc::new(SsoService::new(
+            pool.clone(),
+            &crate::config::SsoConfig::default(),
+            Arc::clone(&casbin_service),
+            Arc::clone(&session_service),
+        )),
+        session_service,
+        login_guard: Arc::new(LoginGuard::new(pool.clone(), Default::default())),
+        two_factor_service,
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        cluster_service: Arc::clone(&cluster_service),
+        organization_service: Arc::new(OrganizationService::new(pool.clone())),
+        system_function_service: Arc::new(SystemFunctionService::new(
+            Arc::new(pool.clone()),
+            Arc::clone(&mysql_pool_manager),
+            Arc::clone(&cluster_service),
+        )),
+        metrics_exporter_service: Arc::new(MetricsExporterService::new(
+            Arc::clone(&cluster_service),
+            Arc::clone(&metrics_collector_service),
+            Arc::clone(&mysql_pool_manager),
+            Arc::clone(&process_metrics),
+        )),
+        metrics_collector_service,
+        data_statistics_service: Arc::clone(&data_statistics_service),
+        overview_service: Arc::new(
+            OverviewService::new(
+                pool.clone(),
+                Arc::clone(&cluster_service),
+                Arc::clone(&mysql_pool_manager),
+                Arc::clone(&audit_source_service),
+            )
+            .with_data_statistics(Arc::clone(&data_statistics_service))
+            .with_alert_service(Arc::clone(&alert_service)),
+        ),
+        node_metrics_service,
+        audit_source_service,
+        profile_archive_service: Arc::new(ProfileArchiveService::new(
+            pool.clone(),
+            Arc::clone(&cluster_service),
+            Arc::clone(&mysql_pool_manager),
+            ProfileArchiveConfig::default(),
+        )),
+        alert_service,
+        routine_load_service,
+        mv_refresh_service,
+        variable_history_service,
+        casbin_service: Arc::clone(&casbin_service),
+        permission_service: Arc::clone(&permission_service),
+        role_service: Arc::new(RoleService::new(
+            pool.clone(),
+            Arc::clone(&casbin_service),
+            Arc::clone(&permission_service),
+        )),
+        user_role_service: Arc::new(UserRoleService::new(
+            pool.clone(),
+            Arc::clone(&casbin_service),
+        )),
+        user_service: Arc::new(UserService::new(pool.clone(), Arc::clone(&casbin_service))),
+        sql_policy_service: Arc::new(SqlPolicyService::new(pool.clone())),
+        admin_audit_service: Arc::new(AdminAuditService::new(pool.clone())),
+    })
+}
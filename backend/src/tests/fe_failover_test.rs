@@ -0,0 +1,352 @@
+// Multiple FE endpoints and failover tests
+
+use crate::models::{
+    Cluster, ClusterResponse, CreateClusterRequest, FeEndpoint, FeEndpointSource,
+    UpdateClusterRequest,
+};
+use crate::services::mysql_pool_manager::{DiscoveredFrontend, parse_frontends};
+use crate::services::{MySQLPoolManager, StarRocksClient};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::ApiError;
+use axum::Router;
+use axum::http::Uri;
+use std::sync::{Arc, Mutex};
+
+fn endpoint(host: &str, port: i32) -> FeEndpoint {
+    FeEndpoint { host: host.to_string(), http_port: port, query_port: port }
+}
+
+fn cluster(manager: &MySQLPoolManager, primary: FeEndpoint, extra: &[FeEndpoint]) -> Cluster {
+    Cluster {
+        id: 42,
+        name: "failover".to_string(),
+        description: None,
+        fe_host: primary.host,
+        fe_http_port: primary.http_port,
+        fe_query_port: primary.query_port,
+        username: "root".to_string(),
+        password_encrypted: manager.cipher().encrypt("secret").unwrap(),
+        enable_ssl: false,
+        connection_timeout: 2,
+        tags: None,
+        catalog: "default_catalog".to_string(),
+        is_active: false,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        created_by: None,
+        organization_id: None,
+        deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: Some(serde_json::to_string(extra).unwrap()),
+        tls_ca_cert: None,
+        tls_client_cert: None,
+        tls_client_key_encrypted: None,
+        tls_verify_hostname: true,
+    }
+}
+
+/// Minimal FE HTTP server recording the paths it receives
+async fn spawn_fe(hits: Arc<Mutex<Vec<String>>>) -> i32 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port() as i32;
+    let app = Router::new().fallback(move |uri: Uri| {
+        let hits = hits.clone();
+        async move {
+            hits.lock().unwrap().push(uri.path().to_string());
+            "starrocks_fe_query_total 1\n"
+        }
+    });
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    port
+}
+
+#[tokio::test]
+async fn test_fe_endpoints_are_validated_and_stored() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    let request = |fe_endpoints: Vec<FeEndpoint>| CreateClusterRequest {
+        name: "multi-fe".to_string(),
+        description: None,
+        fe_host: "fe1".to_string(),
+        fe_http_port: 8030,
+        fe_query_port: 9030,
+        username: "root".to_string(),
+        password: "secret".to_string(),
+        enable_ssl: false,
+        connection_timeout: 10,
+        tags: None,
+        catalog: "default_catalog".to_string(),
+        organization_id: Some(data.org1_id),
+        deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: Some(fe_endpoints),
+        tls: None,
+    };
+
+    let invalid = state
+        .cluster_service
+        .create_cluster(request(vec![endpoint("fe2", 0)]), data.super_admin_user_id, None, true)
+        .await;
+    assert!(matches!(invalid, Err(ApiError::ValidationError(_))));
+    let blank = state
+        .cluster_service
+        .create_cluster(request(vec![endpoint("  ", 9030)]), data.super_admin_user_id, None, true)
+        .await;
+    assert!(matches!(blank, Err(ApiError::ValidationError(_))));
+
+    let fe2 = FeEndpoint { host: "fe2".to_string(), http_port: 8030, query_port: 9030 };
+    let created = state
+        .cluster_service
+        .create_cluster(
+            request(vec![fe2.clone(), fe2.clone(), endpoint(" fe3 ", 9030)]),
+            data.super_admin_user_id,
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+    // Duplicates are dropped and the primary endpoint comes first
+    let configured = created.configured_endpoints();
+    assert_eq!(configured.len(), 3);
+    assert_eq!(configured[0], created.primary_endpoint());
+    assert_eq!(configured[1], fe2);
+    assert_eq!(configured[2], endpoint("fe3", 9030));
+    assert_eq!(ClusterResponse::from(created.clone()).fe_endpoints.len(), 2);
+
+    let update = UpdateClusterRequest {
+        name: None,
+        description: None,
+        fe_host: None,
+        fe_http_port: None,
+        fe_query_port: None,
+        username: None,
+        password: None,
+        enable_ssl: None,
+        connection_timeout: None,
+        tags: None,
+        catalog: None,
+        organization_id: None,
+        deployment_mode: None,
+        fe_endpoints: Some(Vec::new()),
+        tls: None,
+    };
+    let updated = state
+        .cluster_service
+        .update_cluster(created.id, update)
+        .await
+        .unwrap();
+    assert_eq!(updated.configured_endpoints(), vec![updated.primary_endpoint()]);
+}
+
+#[test]
+fn test_parse_show_frontends_rows() {
+    let rows = vec![
+        serde_json::json!({
+            "Name": "fe1", "IP": "10.0.0.1", "HttpPort": "8030", "QueryPort": "9030",
+            "Role": "LEADER", "Alive": "true"
+        }),
+        serde_json::json!({
+            "Name": "fe2", "IP": "10.0.0.2", "HttpPort": "8030", "QueryPort": "9030",
+            "Role": "FOLLOWER", "Alive": "false"
+        }),
+        // Older versions report Host/IsMaster
+        serde_json::json!({
+            "Host": "10.0.0.3", "HttpPort": "8031", "QueryPort": "9031",
+            "IsMaster": "true", "Alive": "true"
+        }),
+        serde_json::json!({ "IP": "", "HttpPort": "8030", "QueryPort": "9030" }),
+        serde_json::json!({ "IP": "10.0.0.5", "HttpPort": "n/a", "QueryPort": "9030" }),
+    ];
+
+    let frontends = parse_frontends(&rows);
+    assert_eq!(
+        frontends,
+        vec![
+            DiscoveredFrontend {
+                endpoint: FeEndpoint {
+                    host: "10.0.0.1".to_string(),
+                    http_port: 8030,
+                    query_port: 9030
+                },
+                is_leader: true,
+                alive: true,
+            },
+            DiscoveredFrontend {
+                endpoint: FeEndpoint {
+                    host: "10.0.0.2".to_string(),
+                    http_port: 8030,
+                    query_port: 9030
+                },
+                is_leader: false,
+                alive: false,
+            },
+            DiscoveredFrontend {
+                endpoint: FeEndpoint {
+                    host: "10.0.0.3".to_string(),
+                    http_port: 8031,
+                    query_port: 9031
+                },
+                is_leader: true,
+                alive: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_candidate_order_and_leader_from_discovery() {
+    let manager = MySQLPoolManager::default();
+    let cluster = cluster(&manager, endpoint("fe1", 9030), &[endpoint("fe2", 9030)]);
+    assert_eq!(
+        manager.candidate_endpoints(&cluster),
+        vec![endpoint("fe1", 9030), endpoint("fe2", 9030)]
+    );
+    assert!(manager.leader_endpoint(cluster.id).is_none());
+
+    manager.record_discovery(
+        cluster.id,
+        vec![
+            DiscoveredFrontend { endpoint: endpoint("fe2", 9030), is_leader: false, alive: true },
+            DiscoveredFrontend { endpoint: endpoint("fe3", 9030), is_leader: true, alive: true },
+            DiscoveredFrontend { endpoint: endpoint("fe4", 9030), is_leader: false, alive: false },
+        ],
+    );
+
+    // Configured endpoints first, then alive discovered ones
+    assert_eq!(
+        manager.candidate_endpoints(&cluster),
+        vec![endpoint("fe1", 9030), endpoint("fe2", 9030), endpoint("fe3", 9030)]
+    );
+    assert_eq!(manager.leader_endpoint(cluster.id), Some(endpoint("fe3", 9030)));
+
+    let status = manager.endpoint_status(&cluster);
+    let sources: Vec<(&str, FeEndpointSource, Option<bool>)> = status
+        .iter()
+        .map(|s| (s.host.as_str(), s.source, s.alive))
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            ("fe1", FeEndpointSource::Configured, None),
+            ("fe2", FeEndpointSource::Configured, Some(true)),
+            ("fe3", FeEndpointSource::Discovered, Some(true)),
+            ("fe4", FeEndpointSource::Discovered, Some(false)),
+        ]
+    );
+    assert!(status.iter().all(|s| !s.is_current));
+    assert!(status.iter().any(|s| s.host == "fe3" && s.is_leader));
+}
+
+#[test]
+fn test_detached_manager_shares_cipher_but_no_state() {
+    let manager = MySQLPoolManager::default();
+    let mut cluster = cluster(&manager, endpoint("fe1", 9030), &[]);
+    cluster.id = 0;
+    manager.record_discovery(
+        cluster.id,
+        vec![DiscoveredFrontend { endpoint: endpoint("fe9", 9030), is_leader: true, alive: true }],
+    );
+
+    // Connection tests of unsaved clusters (id 0) neither see nor leave discovered FEs
+    let detached = manager.detached();
+    assert_eq!(detached.candidate_endpoints(&cluster), vec![endpoint("fe1", 9030)]);
+    assert!(detached.leader_endpoint(cluster.id).is_none());
+    assert_eq!(detached.pool_count(), 0);
+    assert_eq!(
+        detached
+            .cipher()
+            .decrypt(&cluster.password_encrypted)
+            .unwrap(),
+        "secret"
+    );
+    assert_eq!(manager.leader_endpoint(cluster.id), Some(endpoint("fe9", 9030)));
+}
+
+#[tokio::test]
+async fn test_get_pool_reports_every_unreachable_endpoint() {
+    let manager = MySQLPoolManager::default();
+    let closed = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port() as i32
+    };
+    let cluster = cluster(&manager, endpoint("127.0.0.1", 1), &[endpoint("127.0.0.1", closed)]);
+
+    let err = manager.get_pool(&cluster).await.unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("No reachable FE"), "{}", message);
+    assert!(message.contains("127.0.0.1:1/1"), "{}", message);
+    assert!(message.contains(&format!("127.0.0.1:{}/{}", closed, closed)), "{}", message);
+    assert_eq!(manager.pool_count(), 0);
+}
+
+#[tokio::test]
+async fn test_http_requests_fail_over_to_next_endpoint() {
+    let manager = Arc::new(MySQLPoolManager::default());
+    let hits = Arc::new(Mutex::new(Vec::new()));
+    let port = spawn_fe(hits.clone()).await;
+    let cluster = cluster(&manager, endpoint("127.0.0.1", 1), &[endpoint("127.0.0.1", port)]);
+
+    let client = StarRocksClient::new(cluster, manager);
+    let metrics = client.get_metrics().await.unwrap();
+    assert!(metrics.contains("starrocks_fe_query_total"));
+    assert_eq!(*hits.lock().unwrap(), vec!["/metrics".to_string()]);
+}
+
+#[tokio::test]
+async fn test_execute_sql_goes_to_leader_first() {
+    let manager = Arc::new(MySQLPoolManager::default());
+    let follower_hits = Arc::new(Mutex::new(Vec::new()));
+    let leader_hits = Arc::new(Mutex::new(Vec::new()));
+    let follower = spawn_fe(follower_hits.clone()).await;
+    let leader = spawn_fe(leader_hits.clone()).await;
+    let cluster = cluster(&manager, endpoint("127.0.0.1", follower), &[]);
+
+    manager.record_discovery(
+        cluster.id,
+        vec![
+            DiscoveredFrontend {
+                endpoint: endpoint("127.0.0.1", follower),
+                is_leader: false,
+                alive: true,
+            },
+            DiscoveredFrontend {
+                endpoint: endpoint("127.0.0.1", leader),
+                is_leader: true,
+                alive: true,
+            },
+        ],
+    );
+
+    let client = StarRocksClient::new(cluster, manager);
+    client
+        .execute_sql("ALTER SYSTEM DROP BACKEND \"10.0.0.1:9050\"")
+        .await
+        .unwrap();
+    assert_eq!(*leader_hits.lock().unwrap(), vec!["/api/query".to_string()]);
+    assert!(follower_hits.lock().unwrap().is_empty());
+
+    // Reads keep using the preferred endpoint
+    client.get_metrics().await.unwrap();
+    assert_eq!(*follower_hits.lock().unwrap(), vec!["/metrics".to_string()]);
+}
+
+#[tokio::test]
+async fn test_endpoints_route_maps_to_seeded_permission() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let (resource, action) =
+        extract_permission("GET", "/api/clusters/frontends/endpoints").unwrap();
+    assert_eq!((resource.as_str(), action.as_str()), ("clusters", "frontends:endpoints"));
+
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM permissions p
+         JOIN permissions parent ON parent.id = p.parent_id
+         WHERE p.code = 'api:clusters:frontends:endpoints' AND parent.code = 'menu:nodes:frontends'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
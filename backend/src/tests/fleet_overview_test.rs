@@ -0,0 +1,363 @@
+// Fleet overview and cluster-scoped route tests
+
+use crate::middleware::cluster_scope::split_cluster_scoped_path;
+use crate::middleware::{
+    AuthState, ClusterScope, OrgContext, auth_middleware, cluster_scope_middleware,
+};
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::services::{ApiTokenService, FleetClusterStatus, SessionService};
+use crate::tests::common::{
+    assign_role_to_user, create_test_app_state, create_test_casbin_service, create_test_db,
+    create_test_session_token, create_test_user, setup_multi_tenant_test_data, setup_test_data,
+};
+use crate::utils::{ApiError, JwtUtil};
+use axum::body::Body;
+use axum::extract::{OriginalUri, Request};
+use axum::http::{StatusCode, Uri, header};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::{Layer, ServiceExt};
+
+async fn create_cluster(
+    state: &crate::AppState,
+    name: &str,
+    port: i32,
+    organization_id: Option<i64>,
+    created_by: i64,
+) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: "127.0.0.1".to_string(),
+                fe_http_port: port,
+                fe_query_port: port,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 30,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn org_ctx(user_id: i64, organization_id: Option<i64>, cluster_id: Option<i64>) -> OrgContext {
+    OrgContext {
+        user_id,
+        username: "tester".to_string(),
+        organization_id,
+        is_super_admin: false,
+        cluster_id,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: None,
+    }
+}
+
+#[test]
+fn test_split_cluster_scoped_path() {
+    assert_eq!(
+        split_cluster_scoped_path("/api/clusters/7/queries"),
+        Some((7, "/api/clusters/queries".to_string()))
+    );
+    assert_eq!(
+        split_cluster_scoped_path("/api/clusters/7/overview/health"),
+        Some((7, "/api/clusters/overview/health".to_string()))
+    );
+    assert_eq!(
+        split_cluster_scoped_path("/api/clusters/7/backends/10.0.0.1/9050"),
+        Some((7, "/api/clusters/backends/10.0.0.1/9050".to_string()))
+    );
+
+    // Routes that already take the cluster id stay as they are
+    assert_eq!(split_cluster_scoped_path("/api/clusters/7"), None);
+    assert_eq!(split_cluster_scoped_path("/api/clusters/7/activate"), None);
+    assert_eq!(split_cluster_scoped_path("/api/clusters/7/health"), None);
+    assert_eq!(split_cluster_scoped_path("/api/clusters/queries"), None);
+    assert_eq!(split_cluster_scoped_path("/api/clusters/abc/queries"), None);
+    assert_eq!(split_cluster_scoped_path("/api/roles/7/permissions"), None);
+}
+
+#[tokio::test]
+async fn test_cluster_scope_middleware_rewrites_before_routing() {
+    let router = Router::new()
+        .route(
+            "/api/clusters/variables",
+            get(|scope: Option<Extension<ClusterScope>>, original: OriginalUri, uri: Uri| async move {
+                Json(serde_json::json!({
+                    "scope": scope.map(|Extension(ClusterScope(id))| id),
+                    "original": original.to_string(),
+                    "uri": uri.to_string(),
+                }))
+            }),
+        )
+        .route("/api/clusters/:id/activate", post(|| async { "activated" }));
+    let app = axum::middleware::from_fn(cluster_scope_middleware).layer(router);
+
+    let call = |uri: &'static str, method: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, body)
+        }
+    };
+
+    let (status, body) = call("/api/clusters/42/variables?filter=query_%25", "GET").await;
+    assert_eq!(status, StatusCode::OK);
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(value["scope"], 42);
+    assert_eq!(value["uri"], "/api/clusters/variables?filter=query_%25");
+    assert_eq!(value["original"], "/api/clusters/42/variables?filter=query_%25");
+
+    let (status, body) = call("/api/clusters/variables", "GET").await;
+    assert_eq!(status, StatusCode::OK);
+    let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(value["scope"], serde_json::Value::Null);
+
+    let (status, body) = call("/api/clusters/42/activate", "POST").await;
+    assert_eq!((status, body.as_ref()), (StatusCode::OK, b"activated".as_ref()));
+}
+
+#[tokio::test]
+async fn test_auth_middleware_carries_scope_into_org_context() {
+    let pool = create_test_db().await;
+    let casbin_service = create_test_casbin_service().await;
+    let jwt_util = Arc::new(JwtUtil::new("test-secret-key-for-scope-test", "24h"));
+    let data = setup_test_data(&pool).await;
+
+    // Scoped requests are authorized against the permission of the unscoped route
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action)
+         VALUES ('api:clusters:variables', 'Variables', 'api', 'clusters', 'variables')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO role_permissions (role_id, permission_id)
+         SELECT ?, id FROM permissions WHERE code = 'api:clusters:variables'",
+    )
+    .bind(data.admin_role_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let user_id = create_test_user(&pool, "scope_user").await;
+    assign_role_to_user(&pool, user_id, data.admin_role_id).await;
+    casbin_service.reload_policies_from_db(&pool).await.unwrap();
+    let token = create_test_session_token(&pool, &jwt_util, user_id, "scope_user").await;
+
+    let auth_state = AuthState {
+        jwt_util: jwt_util.clone(),
+        casbin_service,
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util, "7d")),
+        db: pool.clone(),
+    };
+    let router = Router::new()
+        .route(
+            "/api/clusters/variables",
+            get(
+                |Extension(org_ctx): Extension<OrgContext>| async move { Json(org_ctx.cluster_id) },
+            ),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+    let app = axum::middleware::from_fn(cluster_scope_middleware).layer(router);
+
+    for (uri, expected) in [
+        ("/api/clusters/9/variables", serde_json::json!(9)),
+        ("/api/clusters/variables", serde_json::Value::Null),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_resolve_cluster_enforces_organization() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let clusters = &state.cluster_service;
+
+    let org1 =
+        create_cluster(&state, "org1", 9030, Some(data.org1_id), data.super_admin_user_id).await;
+    let org1_other =
+        create_cluster(&state, "org1_other", 9031, Some(data.org1_id), data.super_admin_user_id)
+            .await;
+    let org2 =
+        create_cluster(&state, "org2", 9032, Some(data.org2_id), data.super_admin_user_id).await;
+    clusters.set_active_cluster(org1.id).await.unwrap();
+
+    let user = data.org1_admin_user_id;
+    // No scope: the active cluster of the organization
+    let resolved = clusters
+        .resolve_cluster(&org_ctx(user, Some(data.org1_id), None))
+        .await
+        .unwrap();
+    assert_eq!(resolved.id, org1.id);
+
+    // Scoped to another cluster of the organization, regardless of the active flag
+    let resolved = clusters
+        .resolve_cluster(&org_ctx(user, Some(data.org1_id), Some(org1_other.id)))
+        .await
+        .unwrap();
+    assert_eq!(resolved.id, org1_other.id);
+
+    // Clusters of other organizations are forbidden
+    let err = clusters
+        .resolve_cluster(&org_ctx(user, Some(data.org1_id), Some(org2.id)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)), "{:?}", err);
+
+    // Super admins can address any cluster
+    let super_admin = OrgContext { is_super_admin: true, ..org_ctx(user, None, Some(org2.id)) };
+    assert_eq!(clusters.resolve_cluster(&super_admin).await.unwrap().id, org2.id);
+
+    assert!(
+        clusters
+            .resolve_cluster(&org_ctx(user, Some(data.org1_id), Some(999_999)))
+            .await
+            .is_err()
+    );
+
+    let visible = clusters
+        .list_visible_clusters(&org_ctx(user, Some(data.org1_id), None))
+        .await
+        .unwrap();
+    let mut ids: Vec<i64> = visible.iter().map(|cluster| cluster.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![org1.id, org1_other.id]);
+}
+
+#[tokio::test]
+async fn test_fleet_overview_returns_partial_results() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    // Accepts connections but never answers: collecting this cluster times out
+    let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let silent_port = silent.local_addr().unwrap().port() as i32;
+    let accept_loop = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = silent.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    // Nothing listens on port 1: collecting this cluster fails
+    let refused =
+        create_cluster(&state, "refused", 1, Some(data.org1_id), data.super_admin_user_id).await;
+    let hanging = create_cluster(
+        &state,
+        "hanging",
+        silent_port,
+        Some(data.org1_id),
+        data.super_admin_user_id,
+    )
+    .await;
+    create_cluster(&state, "org2", 1, Some(data.org2_id), data.super_admin_user_id).await;
+
+    let clusters = state
+        .cluster_service
+        .list_visible_clusters(&org_ctx(data.org1_admin_user_id, Some(data.org1_id), None))
+        .await
+        .unwrap();
+    let started = std::time::Instant::now();
+    let fleet = state
+        .overview_service
+        .get_fleet_overview(clusters, Duration::from_millis(500))
+        .await;
+    // The timed out cluster does not hold back the whole overview
+    assert!(started.elapsed() < Duration::from_secs(5));
+    accept_loop.abort();
+
+    assert_eq!(fleet.total, 2);
+    assert_eq!(fleet.unavailable, 2);
+    assert_eq!((fleet.healthy, fleet.warning, fleet.critical, fleet.no_data), (0, 0, 0, 0));
+
+    let summary = |id: i64| fleet.clusters.iter().find(|c| c.cluster_id == id).unwrap();
+    let refused = summary(refused.id);
+    assert_eq!(refused.status, FleetClusterStatus::Error);
+    assert!(refused.error.is_some());
+    assert!(refused.health.is_none() && refused.kpi.is_none());
+
+    let hanging = summary(hanging.id);
+    assert_eq!(hanging.status, FleetClusterStatus::Timeout);
+    assert_eq!(hanging.error.as_deref(), Some("No response within 500ms"));
+    assert_eq!(hanging.cluster_name, "hanging");
+}
+
+#[tokio::test]
+async fn test_fleet_route_maps_to_seeded_permission() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let (resource, action) = extract_permission("GET", "/api/clusters/fleet").unwrap();
+    assert_eq!((resource.as_str(), action.as_str()), ("clusters", "fleet"));
+
+    // Granted to every role that can see the single-cluster overview
+    let (missing,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM role_permissions rp
+         JOIN permissions p ON p.id = rp.permission_id AND p.code = 'api:clusters:overview'
+         WHERE NOT EXISTS (
+             SELECT 1 FROM role_permissions fleet
+             JOIN permissions fp ON fp.id = fleet.permission_id AND fp.code = 'api:clusters:fleet'
+             WHERE fleet.role_id = rp.role_id
+         )",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(missing, 0);
+
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM permissions WHERE resource = 'clusters' AND action = 'fleet'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
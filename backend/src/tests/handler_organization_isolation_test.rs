@@ -10,7 +10,7 @@ use std::sync::Arc;
 #[tokio::test]
 async fn test_get_active_cluster_by_org_isolation() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -29,6 +29,8 @@ async fn test_get_active_cluster_by_org_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster = cluster_service
@@ -56,6 +58,8 @@ async fn test_get_active_cluster_by_org_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
@@ -107,7 +111,7 @@ async fn test_get_active_cluster_by_org_isolation() {
 #[tokio::test]
 async fn test_get_active_cluster_by_org_no_cluster() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -130,7 +134,7 @@ async fn test_get_active_cluster_by_org_no_cluster() {
 #[tokio::test]
 async fn test_get_active_cluster_by_org_none_org_id() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     // Test: Calling with None org_id should fail
@@ -143,7 +147,7 @@ async fn test_get_active_cluster_by_org_none_org_id() {
 #[tokio::test]
 async fn test_super_admin_vs_regular_user_cluster_access() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -162,6 +166,8 @@ async fn test_super_admin_vs_regular_user_cluster_access() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster = cluster_service
@@ -198,7 +204,7 @@ async fn test_super_admin_vs_regular_user_cluster_access() {
 #[tokio::test]
 async fn test_multiple_orgs_multiple_active_clusters() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -217,6 +223,8 @@ async fn test_multiple_orgs_multiple_active_clusters() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster1 = cluster_service
@@ -243,6 +251,8 @@ async fn test_multiple_orgs_multiple_active_clusters() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let _org1_cluster2 = cluster_service
@@ -270,6 +280,8 @@ async fn test_multiple_orgs_multiple_active_clusters() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
@@ -311,7 +323,7 @@ async fn test_multiple_orgs_multiple_active_clusters() {
 #[tokio::test]
 async fn test_switching_active_cluster_isolation() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -330,6 +342,8 @@ async fn test_switching_active_cluster_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster1 = cluster_service
@@ -356,6 +370,8 @@ async fn test_switching_active_cluster_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster2 = cluster_service
@@ -383,6 +399,8 @@ async fn test_switching_active_cluster_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
@@ -441,7 +459,7 @@ async fn test_switching_active_cluster_isolation() {
 #[tokio::test]
 async fn test_sql_query_organization_filter() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
@@ -460,6 +478,8 @@ async fn test_sql_query_organization_filter() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster = cluster_service
@@ -486,6 +506,8 @@ async fn test_sql_query_organization_filter() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
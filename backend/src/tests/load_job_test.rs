@@ -0,0 +1,227 @@
+// Load job center: queries over information_schema.loads, row parsing and permissions
+
+use crate::middleware::cluster_scope::split_cluster_scoped_path;
+use crate::middleware::permission_extractor::{extract_permission, scope_level};
+use crate::models::{LoadJobFilter, PermissionScopeLevel};
+use crate::services::{AuditSource, LoadJobService};
+use crate::tests::common::create_test_db;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_build_list_query() {
+    let sql = LoadJobService::build_list_query(&LoadJobFilter::default()).unwrap();
+    assert_eq!(sql, "SELECT * FROM information_schema.loads ORDER BY CREATE_TIME DESC");
+
+    let filter = LoadJobFilter {
+        state: Some("cancelled".to_string()),
+        label: Some("daily_50%_o'k".to_string()),
+        load_type: Some("broker".to_string()),
+        start_time: Some("2025-02-01 00:00:00".to_string()),
+        end_time: Some(" ".to_string()),
+        ..Default::default()
+    };
+    let sql = LoadJobService::build_list_query(&filter).unwrap();
+    assert_eq!(
+        sql,
+        "SELECT * FROM information_schema.loads WHERE STATE = 'CANCELLED' \
+         AND LABEL LIKE '%daily\\\\_50\\\\%\\\\_o\\'k%' AND TYPE = 'BROKER' \
+         AND CREATE_TIME >= '2025-02-01 00:00:00' ORDER BY CREATE_TIME DESC"
+    );
+
+    let filter =
+        LoadJobFilter { end_time: Some("2025-02-01' OR '1'='1".to_string()), ..Default::default() };
+    assert!(LoadJobService::build_list_query(&filter).is_err());
+}
+
+#[test]
+fn test_parse_load_job_across_versions() {
+    // StarRocks 3.0 layout
+    let row = json!({
+        "JOB_ID": "10143",
+        "LABEL": "orders_20250201",
+        "DATABASE_NAME": "sales",
+        "STATE": "CANCELLED",
+        "TYPE": "BROKER",
+        "SCAN_ROWS": "1000",
+        "FILTERED_ROWS": "12",
+        "UNSELECTED_ROWS": "0",
+        "SINK_ROWS": "0",
+        "CREATE_TIME": "2025-02-01 10:00:00",
+        "LOAD_FINISH_TIME": "NULL",
+        "ERROR_MSG": "type:ETL_QUALITY_UNSATISFIED; msg:quality not good enough to cancel",
+        "TRACKING_URL": "http://be1:8040/api/_load_error_log?file=error_log_1",
+        "JOB_DETAILS": "{\"ScannedRows\":1000}",
+        "TRACKING_SQL": "select tracking_log from information_schema.load_tracking_logs where job_id=10143",
+    });
+    let detail = LoadJobService::parse_load_job_detail(&row);
+    assert_eq!(detail.job.id, 10143);
+    assert_eq!(detail.job.database_name, "sales");
+    assert_eq!(detail.job.filtered_rows, 12);
+    assert_eq!(detail.job.load_finish_time, None);
+    assert_eq!(
+        detail.job.tracking_url.as_deref(),
+        Some("http://be1:8040/api/_load_error_log?file=error_log_1")
+    );
+    assert_eq!(detail.runtime_details.as_deref(), Some("{\"ScannedRows\":1000}"));
+    assert!(detail.tracking_sql.is_some());
+    assert!(!detail.cancellable);
+
+    // StarRocks 3.1+ layout: DB_NAME, RUNTIME_DETAILS, error URL inside the message
+    let row = json!({
+        "ID": "20001",
+        "JOB_ID": "20001",
+        "LABEL": "insert_abc",
+        "DB_NAME": "logs",
+        "TABLE_NAME": "events",
+        "USER": "etl",
+        "STATE": "LOADING",
+        "TYPE": "INSERT",
+        "SCAN_ROWS": "NULL",
+        "ERROR_MSG": "Error. url: http://be2:8040/api/_load_error_log?file=x. Check it",
+        "RUNTIME_DETAILS": "{}",
+    });
+    let detail = LoadJobService::parse_load_job_detail(&row);
+    assert_eq!(detail.job.id, 20001);
+    assert_eq!(detail.job.database_name, "logs");
+    assert_eq!(detail.job.table_name.as_deref(), Some("events"));
+    assert_eq!(detail.job.scan_rows, 0);
+    assert_eq!(
+        detail.job.tracking_url.as_deref(),
+        Some("http://be2:8040/api/_load_error_log?file=x")
+    );
+    assert!(detail.cancellable);
+}
+
+#[test]
+fn test_cancel_statement_and_cancellable_jobs() {
+    let mut job = LoadJobService::parse_load_job(&json!({
+        "JOB_ID": "1",
+        "LABEL": "it's",
+        "DB_NAME": "my`db",
+        "STATE": "PENDING",
+        "TYPE": "BROKER",
+    }));
+    assert_eq!(
+        LoadJobService::build_cancel_statement(&job),
+        "CANCEL LOAD FROM `my``db` WHERE LABEL = 'it\\'s'"
+    );
+    assert!(LoadJobService::is_cancellable(&job));
+
+    for state in ["COMMITTED", "FINISHED", "CANCELLED"] {
+        job.state = state.to_string();
+        assert!(!LoadJobService::is_cancellable(&job), "{} is cancellable", state);
+    }
+    job.state = "LOADING".to_string();
+    job.load_type = "STREAM_LOAD".to_string();
+    assert!(!LoadJobService::is_cancellable(&job));
+}
+
+#[test]
+fn test_retryable_jobs_and_statement_lookup() {
+    let mut job = LoadJobService::parse_load_job(&json!({
+        "JOB_ID": "1",
+        "LABEL": "orders_20250201",
+        "DB_NAME": "sales",
+        "STATE": "CANCELLED",
+        "TYPE": "BROKER",
+        "CREATE_TIME": "2025-02-01 10:00:00",
+    }));
+    assert!(LoadJobService::is_retryable(&job));
+    for load_type in ["STREAM_LOAD", "ROUTINE_LOAD"] {
+        job.load_type = load_type.to_string();
+        assert!(!LoadJobService::is_retryable(&job), "{} is retryable", load_type);
+    }
+    job.load_type = "INSERT".to_string();
+    job.state = "FINISHED".to_string();
+    assert!(!LoadJobService::is_retryable(&job));
+
+    let audit = AuditSource::resolve("audit_db", "audit_tbl", None, &BTreeMap::new());
+    assert_eq!(LoadJobService::build_statement_lookup(&job, &audit), None);
+    job.user = Some("etl".to_string());
+    assert_eq!(
+        LoadJobService::build_statement_lookup(&job, &audit).unwrap(),
+        "SELECT `stmt` AS stmt, `db` AS db FROM `audit_db`.`audit_tbl` WHERE `user` = 'etl' \
+         AND `state` <> 'ERR' AND `stmt` LIKE '%orders\\\\_20250201%' \
+         AND `timestamp` >= DATE_SUB('2025-02-01 10:00:00', INTERVAL 1 MINUTE) \
+         ORDER BY `timestamp` DESC LIMIT 10"
+    );
+}
+
+#[test]
+fn test_retry_statement_must_submit_the_job_label() {
+    let job = LoadJobService::parse_load_job(&json!({
+        "JOB_ID": "1",
+        "LABEL": "orders_2025",
+        "DB_NAME": "sales",
+        "STATE": "CANCELLED",
+        "TYPE": "BROKER",
+    }));
+    let submits =
+        |stmt: &str, session_db: &str| LoadJobService::is_load_statement(stmt, session_db, &job);
+
+    assert!(submits(
+        "LOAD LABEL sales.orders_2025 (DATA INFILE('s3://b/o.csv') INTO TABLE orders)",
+        ""
+    ));
+    assert!(submits("load label `sales`.`orders_2025` (...)", "ops"));
+    assert!(submits("LOAD LABEL orders_2025 (...)", "sales"));
+    assert!(submits("insert into orders with label orders_2025 select * from staging", "sales"));
+    assert!(submits(
+        "INSERT INTO default_catalog.sales.orders WITH LABEL orders_2025 SELECT 1",
+        ""
+    ));
+
+    // Another database, another label or the label only mentioned
+    assert!(!submits("LOAD LABEL orders_2025 (...)", "ops"));
+    assert!(!submits("LOAD LABEL ops.orders_2025 (...)", "sales"));
+    assert!(!submits("LOAD LABEL sales.orders_2025_v2 (...)", "sales"));
+    assert!(!submits(
+        "INSERT INTO other_db.t SELECT * FROM sales.orders /* orders_2025 */",
+        "sales"
+    ));
+    assert!(!submits("INSERT INTO ops.orders WITH LABEL orders_2025 SELECT 1", "sales"));
+    assert!(!submits("INSERT INTO hive.sales.orders WITH LABEL orders_2025 SELECT 1", ""));
+    assert!(!submits(
+        "SELECT * FROM information_schema.loads WHERE LABEL = 'orders_2025'",
+        "sales"
+    ));
+    assert!(!submits("/* LOAD LABEL sales.orders_2025 */ DROP TABLE sales.orders", "sales"));
+}
+
+#[tokio::test]
+async fn test_load_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+    let routes = [
+        ("GET", "/api/clusters/loads", "loads"),
+        ("GET", "/api/clusters/loads/10143", "loads:get"),
+        ("POST", "/api/clusters/loads/10143/cancel", "loads:cancel"),
+        ("POST", "/api/clusters/loads/10143/retry", "loads:retry"),
+        ("GET", "/api/clusters/loads/10143/tracking-log", "loads:tracking_log"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+        assert_eq!(scope_level(&resource, &extracted), Some(PermissionScopeLevel::Database));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN roles r ON r.id = rp.role_id
+             WHERE p.resource = ? AND p.action = ? AND r.code = 'admin'",
+        )
+        .bind(&resource)
+        .bind(&extracted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not granted to admin", resource, extracted);
+    }
+
+    assert_eq!(
+        split_cluster_scoped_path("/api/clusters/3/loads/10143/cancel"),
+        Some((3, "/api/clusters/loads/10143/cancel".to_string()))
+    );
+}
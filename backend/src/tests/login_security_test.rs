@@ -0,0 +1,637 @@
+// Login hardening tests: password policy, failed-login lockout, TOTP second factor and the
+// registration switch
+
+use crate::AppState;
+use crate::config::{LoginLockoutConfig, PasswordPolicyConfig};
+use crate::handlers;
+use crate::middleware::{AuthState, auth_middleware, permission_extractor::extract_permission};
+use crate::models::{AdminCreateUserRequest, CreateUserRequest, LoginRequest, UpdateUserRequest};
+use crate::services::{LoginGuard, SessionClient, UserService};
+use crate::tests::common::{
+    create_test_app_state, create_test_db, create_test_session_token, setup_multi_tenant_test_data,
+};
+use crate::utils::{ApiError, totp};
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{StatusCode, header};
+use axum::routing::{delete, get, post, put};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const PASSWORD: &str = "initial-password";
+
+async fn register(state: &AppState, username: &str) -> i64 {
+    state
+        .auth_service
+        .register(CreateUserRequest {
+            username: username.to_string(),
+            password: PASSWORD.to_string(),
+            email: None,
+            avatar: None,
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+fn login_request(username: &str, password: &str, totp_code: Option<&str>) -> LoginRequest {
+    LoginRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+        totp_code: totp_code.map(str::to_string),
+    }
+}
+
+fn client(ip_address: &str) -> SessionClient {
+    SessionClient { user_agent: None, ip_address: Some(ip_address.to_string()) }
+}
+
+fn current_code(secret: &str, steps_ahead: i64) -> String {
+    totp::code_at(secret, totp::step_at(Utc::now().timestamp()) + steps_ahead).unwrap()
+}
+
+/// The login hardening routes of the application, behind the authentication middleware
+fn app(state: &Arc<AppState>) -> Router {
+    let auth_state = AuthState {
+        jwt_util: Arc::clone(&state.jwt_util),
+        casbin_service: Arc::clone(&state.casbin_service),
+        api_token_service: Arc::clone(&state.api_token_service),
+        session_service: Arc::clone(&state.session_service),
+        db: state.db.clone(),
+    };
+    let protected = Router::new()
+        .route("/api/auth/registration", put(handlers::auth::update_registration))
+        .route("/api/auth/2fa", get(handlers::auth::two_factor_status))
+        .route("/api/auth/2fa/setup", post(handlers::auth::setup_two_factor))
+        .route("/api/auth/2fa/enable", post(handlers::auth::enable_two_factor))
+        .route("/api/auth/2fa/disable", post(handlers::auth::disable_two_factor))
+        .route("/api/auth/2fa/recovery-codes", post(handlers::auth::regenerate_recovery_codes))
+        .route("/api/users/:id/two-factor", delete(handlers::user::reset_user_two_factor))
+        .route("/api/users/:id/lockout", delete(handlers::user::unlock_user))
+        .route_layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+    Router::new()
+        .route("/api/auth/register", post(handlers::auth::register))
+        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/registration", get(handlers::auth::get_registration))
+        .merge(protected)
+        .with_state(Arc::clone(state))
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("x-forwarded-for", "203.0.113.7");
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+    let response = app
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn grant_to_super_admin(pool: &sqlx::SqlitePool, role_id: i64) {
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action) VALUES
+         ('api:users:two_factor:reset', 'Reset 2FA', 'api', 'users', 'two_factor:reset'),
+         ('api:users:lockout:clear', 'Unlock', 'api', 'users', 'lockout:clear')",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO role_permissions (role_id, permission_id)
+         SELECT ?, id FROM permissions WHERE code IN
+         ('api:users:two_factor:reset', 'api:users:lockout:clear')",
+    )
+    .bind(role_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_password_policy_lists_unmet_rules() {
+    let default = PasswordPolicyConfig::default();
+    assert!(default.check("alice", "long enough").is_ok());
+    assert_eq!(
+        default.check("alice", "short").unwrap_err(),
+        "Password must contain at least 8 characters"
+    );
+    assert_eq!(
+        default.check("alice", "Alice2025!").unwrap_err(),
+        "Password must not contain the username"
+    );
+
+    let strict = PasswordPolicyConfig {
+        min_length: 10,
+        require_uppercase: true,
+        require_lowercase: true,
+        require_digit: true,
+        require_symbol: true,
+        reject_username: false,
+    };
+    assert_eq!(
+        strict.check("bob", "lowercase").unwrap_err(),
+        "Password must contain at least 10 characters, an uppercase letter, a digit, a symbol"
+    );
+    assert!(strict.check("bob", "Bob-Password-42").is_ok());
+}
+
+#[tokio::test]
+async fn test_password_policy_is_enforced_everywhere_passwords_are_set() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    // Registration
+    let err = state
+        .auth_service
+        .register(CreateUserRequest {
+            username: "weak".to_string(),
+            password: "1234".to_string(),
+            email: None,
+            avatar: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+
+    // Own password change
+    let user_id = register(&state, "changer").await;
+    let err = state
+        .auth_service
+        .update_user(
+            user_id,
+            UpdateUserRequest {
+                username: None,
+                email: None,
+                avatar: None,
+                current_password: Some(PASSWORD.to_string()),
+                new_password: Some("changer-2025".to_string()),
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("must not contain the username"), "{}", err);
+
+    // Users created and reset by administrators
+    let users = UserService::new(pool.clone(), Arc::clone(&state.casbin_service))
+        .with_password_policy(PasswordPolicyConfig { require_digit: true, ..Default::default() });
+    let err = users
+        .create_user(
+            AdminCreateUserRequest {
+                username: "created".to_string(),
+                password: "no-digits-here".to_string(),
+                email: None,
+                avatar: None,
+                role_ids: None,
+                organization_id: Some(data.org1_id),
+            },
+            None,
+            true,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Validation error: Password must contain a digit");
+
+    let err = users
+        .update_user(
+            user_id,
+            crate::models::AdminUpdateUserRequest {
+                username: None,
+                email: None,
+                avatar: None,
+                password: Some("still-no-digits".to_string()),
+                role_ids: None,
+                organization_id: None,
+                is_active: None,
+            },
+            None,
+            true,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_failed_logins_lock_the_account() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "target").await;
+    let guard = LoginGuard::new(
+        pool.clone(),
+        LoginLockoutConfig {
+            max_failed_attempts: 3,
+            ip_max_failed_attempts: 0,
+            ..Default::default()
+        },
+    );
+    let attempt = |password: &'static str, ip: &'static str| {
+        let guard = &guard;
+        let auth = &state.auth_service;
+        async move {
+            let client = client(ip);
+            guard
+                .attempt(
+                    "target",
+                    &client,
+                    auth.login(login_request("target", password, None), &client),
+                )
+                .await
+        }
+    };
+
+    // A success resets the count of consecutive failures
+    for _ in 0..2 {
+        assert!(matches!(attempt("wrong", "10.0.0.1").await, Err(ApiError::InvalidCredentials)));
+    }
+    attempt(PASSWORD, "10.0.0.1").await.unwrap();
+    for _ in 0..3 {
+        assert!(matches!(attempt("wrong", "10.0.0.2").await, Err(ApiError::InvalidCredentials)));
+    }
+
+    // Locked from every address, even with the right password
+    let err = attempt(PASSWORD, "10.0.0.3").await.unwrap_err();
+    assert!(matches!(err, ApiError::TooManyAttempts(_)), "{:?}", err);
+    let user = state.auth_service.get_user_by_id(user_id).await.unwrap();
+    assert!(user.locked_until.is_some_and(|until| until > Utc::now()));
+
+    // The lock ends by itself
+    sqlx::query("UPDATE users SET locked_until = ? WHERE id = ?")
+        .bind(Utc::now() - Duration::seconds(1))
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    attempt(PASSWORD, "10.0.0.3").await.unwrap();
+    let user = state.auth_service.get_user_by_id(user_id).await.unwrap();
+    assert!(user.locked_until.is_none());
+
+    // Unknown users and other errors are not tracked
+    let client = client("10.0.0.4");
+    let err = guard
+        .attempt(
+            "nobody",
+            &client,
+            state
+                .auth_service
+                .login(login_request("nobody", "x", None), &client),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn test_failed_logins_throttle_the_client_address() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    register(&state, "sprayed").await;
+    let guard = LoginGuard::new(
+        pool.clone(),
+        LoginLockoutConfig {
+            max_failed_attempts: 0,
+            ip_max_failed_attempts: 2,
+            ..Default::default()
+        },
+    );
+
+    // Password spraying: one attempt per account, all from the same address
+    for username in ["guess1", "guess2"] {
+        let client = client("198.51.100.1");
+        let result = guard
+            .attempt(
+                username,
+                &client,
+                state
+                    .auth_service
+                    .login(login_request(username, "x", None), &client),
+            )
+            .await;
+        assert!(matches!(result, Err(ApiError::InvalidCredentials)));
+    }
+
+    let blocked = client("198.51.100.1");
+    let err = guard
+        .attempt(
+            "sprayed",
+            &blocked,
+            state
+                .auth_service
+                .login(login_request("sprayed", PASSWORD, None), &blocked),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::TooManyAttempts(_)), "{:?}", err);
+
+    let other = client("198.51.100.2");
+    guard
+        .attempt(
+            "sprayed",
+            &other,
+            state
+                .auth_service
+                .login(login_request("sprayed", PASSWORD, None), &other),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_locked_account_answers_429_and_admin_unlocks_it() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    grant_to_super_admin(&pool, data.super_admin_role_id).await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "forgetful").await;
+    let app = app(&state);
+    let admin =
+        create_test_session_token(&pool, &state.jwt_util, data.super_admin_user_id, "super").await;
+
+    let wrong = json!({ "username": "forgetful", "password": "wrong" });
+    for _ in 0..LoginLockoutConfig::default().max_failed_attempts {
+        let (status, body) = call(&app, "POST", "/api/auth/login", None, Some(wrong.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], json!(1003));
+    }
+    let right = json!({ "username": "forgetful", "password": PASSWORD });
+    let (status, body) = call(&app, "POST", "/api/auth/login", None, Some(right.clone())).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], json!(1004));
+
+    let uri = format!("/api/users/{}/lockout", user_id);
+    let (status, _) = call(&app, "DELETE", &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&app, "POST", "/api/auth/login", None, Some(right)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_totp_second_factor_lifecycle() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "careful").await;
+    let app = app(&state);
+    let token = create_test_session_token(&pool, &state.jwt_util, user_id, "careful").await;
+
+    // Enrollment is only enabled once a code of the app is confirmed
+    let (status, setup) = call(&app, "POST", "/api/auth/2fa/setup", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let secret = setup["secret"].as_str().unwrap().to_string();
+    assert!(
+        setup["otpauth_url"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/StarRocks%20Admin%3Acareful?secret=")
+    );
+    let (status, _) =
+        call(&app, "POST", "/api/auth/2fa/enable", Some(&token), Some(json!({ "code": "000000" })))
+            .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let enable_code = current_code(&secret, 0);
+    let (status, codes) = call(
+        &app,
+        "POST",
+        "/api/auth/2fa/enable",
+        Some(&token),
+        Some(json!({ "code": enable_code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", codes);
+    let recovery: Vec<String> = codes["codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(recovery.len(), 10);
+    let stored: String = sqlx::query_scalar("SELECT secret FROM user_totp WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_ne!(stored, secret, "the secret is stored encrypted");
+
+    // The password alone no longer logs in
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/api/auth/login",
+        None,
+        Some(json!({ "username": "careful", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], json!(1005));
+
+    // A code is accepted once
+    let client = SessionClient::default();
+    let err = state
+        .auth_service
+        .login(login_request("careful", PASSWORD, Some(&enable_code)), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidCredentials), "{:?}", err);
+    let next_code = current_code(&secret, 1);
+    state
+        .auth_service
+        .login(login_request("careful", PASSWORD, Some(&next_code)), &client)
+        .await
+        .unwrap();
+
+    // Recovery codes work once, with or without the dash
+    let undashed = recovery[0].replace('-', "").to_uppercase();
+    state
+        .auth_service
+        .login(login_request("careful", PASSWORD, Some(&undashed)), &client)
+        .await
+        .unwrap();
+    assert!(
+        state
+            .auth_service
+            .login(login_request("careful", PASSWORD, Some(&recovery[0])), &client)
+            .await
+            .is_err()
+    );
+    let (_, status_body) = call(&app, "GET", "/api/auth/2fa", Some(&token), None).await;
+    assert_eq!(status_body, json!({ "enabled": true, "recovery_codes_remaining": 9 }));
+
+    // Regenerating replaces every recovery code
+    let (status, fresh) = call(
+        &app,
+        "POST",
+        "/api/auth/2fa/recovery-codes",
+        Some(&token),
+        Some(json!({ "code": recovery[1] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        state
+            .auth_service
+            .login(login_request("careful", PASSWORD, Some(&recovery[2])), &client)
+            .await
+            .is_err()
+    );
+
+    // Disabling needs a valid code too
+    let (status, _) =
+        call(&app, "POST", "/api/auth/2fa/disable", Some(&token), Some(json!({ "code": "wrong" })))
+            .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/auth/2fa/disable",
+        Some(&token),
+        Some(json!({ "code": fresh["codes"][0] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    state
+        .auth_service
+        .login(login_request("careful", PASSWORD, None), &client)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_admin_resets_a_lost_second_factor() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    grant_to_super_admin(&pool, data.super_admin_role_id).await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "lost-phone").await;
+    let app = app(&state);
+    let admin =
+        create_test_session_token(&pool, &state.jwt_util, data.super_admin_user_id, "super").await;
+
+    let user = state.auth_service.get_user_by_id(user_id).await.unwrap();
+    let setup = state.two_factor_service.begin_setup(&user).await.unwrap();
+    state
+        .two_factor_service
+        .enable(user_id, &current_code(&setup.secret, 0))
+        .await
+        .unwrap();
+    let session = create_test_session_token(&pool, &state.jwt_util, user_id, "lost-phone").await;
+
+    let uri = format!("/api/users/{}/two-factor", user_id);
+    let (status, _) = call(&app, "DELETE", &uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(
+        !state
+            .two_factor_service
+            .status(user_id)
+            .await
+            .unwrap()
+            .enabled
+    );
+    state
+        .auth_service
+        .login(login_request("lost-phone", PASSWORD, None), &SessionClient::default())
+        .await
+        .unwrap();
+    // Sessions opened before the reset are ended
+    let (status, _) = call(&app, "GET", "/api/auth/2fa", Some(&session), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    for (method, uri, action) in [
+        ("DELETE", "/api/users/3/two-factor", "two_factor:reset"),
+        ("DELETE", "/api/users/3/lockout", "lockout:clear"),
+    ] {
+        let (resource, extracted) = extract_permission(method, uri).unwrap();
+        assert_eq!((resource.as_str(), extracted.as_str()), ("users", action));
+    }
+    assert!(extract_permission("POST", "/api/auth/2fa/setup").is_none());
+}
+
+#[tokio::test]
+async fn test_sso_users_cannot_enroll_a_second_factor() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "directory").await;
+    sqlx::query("UPDATE users SET auth_provider = 'ldap' WHERE id = ?")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let user = state.auth_service.get_user_by_id(user_id).await.unwrap();
+    let err = state
+        .two_factor_service
+        .begin_setup(&user)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("managed by their identity provider"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_super_admin_closes_registration() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let app = app(&state);
+    let admin =
+        create_test_session_token(&pool, &state.jwt_util, data.super_admin_user_id, "super").await;
+    let regular =
+        create_test_session_token(&pool, &state.jwt_util, data.org1_regular_user_id, "regular")
+            .await;
+
+    let (_, body) = call(&app, "GET", "/api/auth/registration", None, None).await;
+    assert_eq!(body, json!({ "enabled": true }));
+
+    let closed = json!({ "enabled": false });
+    let (status, _) =
+        call(&app, "PUT", "/api/auth/registration", Some(&regular), Some(closed.clone())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(&app, "PUT", "/api/auth/registration", Some(&admin), Some(closed)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = call(&app, "GET", "/api/auth/registration", None, None).await;
+    assert_eq!(body, json!({ "enabled": false }));
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/api/auth/register",
+        None,
+        Some(json!({ "username": "latecomer", "password": PASSWORD })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["message"], json!("Unauthorized: Registration is disabled"));
+
+    // The switch records who changed it
+    let updated_by: Option<i64> = sqlx::query_scalar(
+        "SELECT updated_by FROM system_settings WHERE key = 'registration_enabled'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(updated_by, Some(data.super_admin_user_id));
+}
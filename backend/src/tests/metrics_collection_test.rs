@@ -0,0 +1,214 @@
+// Per-cluster metrics collection scheduling tests
+
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::services::metrics_collector_service::backoff_secs;
+use crate::services::{CollectionState, UpdateCollectionSettingsRequest};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::ApiError;
+use std::time::Duration;
+
+async fn create_cluster(
+    state: &crate::AppState,
+    name: &str,
+    port: i32,
+    connection_timeout: i32,
+    created_by: i64,
+) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: "127.0.0.1".to_string(),
+                fe_http_port: port,
+                fe_query_port: port,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+#[test]
+fn test_backoff_doubles_up_to_max() {
+    assert_eq!(backoff_secs(30, 1, 600), 30);
+    assert_eq!(backoff_secs(30, 2, 600), 60);
+    assert_eq!(backoff_secs(30, 3, 600), 120);
+    assert_eq!(backoff_secs(30, 6, 600), 600);
+    assert_eq!(backoff_secs(30, 1000, 600), 600);
+    // A cluster interval above the cap is never shortened
+    assert_eq!(backoff_secs(900, 3, 600), 900);
+}
+
+#[tokio::test]
+async fn test_collection_status_defaults_to_pending() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, "pending", 1, 1, data.super_admin_user_id).await;
+
+    let status = state
+        .metrics_collector_service
+        .get_collection_status(&cluster)
+        .await
+        .unwrap();
+    assert_eq!(status.state, CollectionState::Pending);
+    assert!(status.enabled);
+    assert!(status.uses_default_interval);
+    assert_eq!(status.interval_secs, 30);
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(status.last_attempt_at.is_none());
+}
+
+#[tokio::test]
+async fn test_update_collection_settings() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, "settings", 1, 1, data.super_admin_user_id).await;
+    let service = &state.metrics_collector_service;
+
+    let status = service
+        .update_collection_settings(
+            &cluster,
+            UpdateCollectionSettingsRequest { enabled: true, interval_secs: Some(120) },
+        )
+        .await
+        .unwrap();
+    assert_eq!(status.interval_secs, 120);
+    assert!(!status.uses_default_interval);
+
+    let status = service
+        .update_collection_settings(
+            &cluster,
+            UpdateCollectionSettingsRequest { enabled: false, interval_secs: None },
+        )
+        .await
+        .unwrap();
+    assert_eq!(status.state, CollectionState::Disabled);
+    assert_eq!(status.interval_secs, 30);
+    assert!(status.next_collect_at.is_none());
+
+    for interval_secs in [0, 1, 86_401] {
+        let err = service
+            .update_collection_settings(
+                &cluster,
+                UpdateCollectionSettingsRequest {
+                    enabled: true,
+                    interval_secs: Some(interval_secs),
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "{}", interval_secs);
+    }
+}
+
+#[tokio::test]
+async fn test_failing_cluster_backs_off_without_blocking_others() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let service = &state.metrics_collector_service;
+
+    // Accepts connections but never answers: collecting this cluster hangs until its timeout
+    let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let silent_port = silent.local_addr().unwrap().port() as i32;
+    let accept_loop = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = silent.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    // Nothing listens on port 1: collecting this cluster fails right away
+    let refused = create_cluster(&state, "refused", 1, 1, data.super_admin_user_id).await;
+    let hanging =
+        create_cluster(&state, "hanging", silent_port, 30, data.super_admin_user_id).await;
+    let disabled = create_cluster(&state, "disabled", 1, 1, data.super_admin_user_id).await;
+    service
+        .update_collection_settings(
+            &disabled,
+            UpdateCollectionSettingsRequest { enabled: false, interval_secs: None },
+        )
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    service.collect_once().await.unwrap();
+    assert!(started.elapsed() <= service.tick_interval() + Duration::from_secs(2));
+
+    let status = service.get_collection_status(&refused).await.unwrap();
+    assert_eq!(status.state, CollectionState::BackingOff);
+    assert_eq!(status.consecutive_failures, 1);
+    assert!(status.last_error.is_some());
+    assert!(status.last_success_at.is_none());
+    let first_next = status.next_collect_at.unwrap();
+
+    // The hanging cluster is still collecting and is skipped by the next tick
+    let hanging_status = service.get_collection_status(&hanging).await.unwrap();
+    assert_eq!(hanging_status.state, CollectionState::Collecting);
+
+    let disabled_status = service.get_collection_status(&disabled).await.unwrap();
+    assert_eq!(disabled_status.state, CollectionState::Disabled);
+    assert!(disabled_status.last_attempt_at.is_none());
+
+    // Not due yet: a second tick leaves the failing cluster alone
+    service.collect_once().await.unwrap();
+    let status = service.get_collection_status(&refused).await.unwrap();
+    assert_eq!(status.consecutive_failures, 1);
+
+    // Once due again, the next failure doubles the delay
+    sqlx::query(
+        "UPDATE cluster_metrics_collection SET next_collect_at = NULL WHERE cluster_id = ?",
+    )
+    .bind(refused.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    service.collect_once().await.unwrap();
+    let status = service.get_collection_status(&refused).await.unwrap();
+    assert_eq!(status.consecutive_failures, 2);
+    let delay = status.next_collect_at.unwrap() - status.last_attempt_at.unwrap();
+    assert_eq!(delay.num_seconds(), 60);
+    assert!(status.next_collect_at.unwrap() > first_next);
+
+    accept_loop.abort();
+}
+
+#[tokio::test]
+async fn test_collection_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    for (method, path, expected) in [
+        ("GET", "/api/clusters/collection", "collection"),
+        ("GET", "/api/clusters/3/collection", "collection"),
+        ("PUT", "/api/clusters/3/collection", "collection:update"),
+    ] {
+        let (resource, action) = extract_permission(method, path).unwrap();
+        assert_eq!((resource.as_str(), action.as_str()), ("clusters", expected), "{}", path);
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions WHERE resource = 'clusters' AND action = ?",
+        )
+        .bind(expected)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "{}", expected);
+    }
+}
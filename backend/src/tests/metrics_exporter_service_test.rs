@@ -0,0 +1,305 @@
+// Prometheus exporter tests
+
+use crate::middleware::http_metrics_middleware;
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use crate::utils::ProcessMetrics;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::connect_info::MockConnectInfo;
+use axum::http::{Request, StatusCode, header};
+use axum::routing::get;
+use sqlx::SqlitePool;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn create_cluster(
+    state: &crate::AppState,
+    name: &str,
+    fe_host: &str,
+    fe_http_port: i32,
+    organization_id: Option<i64>,
+    created_by: i64,
+) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: fe_host.to_string(),
+                fe_http_port,
+                fe_query_port: fe_http_port,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 2,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+async fn insert_snapshot(pool: &SqlitePool, cluster_id: i64, qps: f64, query_total: i64) {
+    sqlx::query(
+        "INSERT INTO metrics_snapshots (cluster_id, qps, query_total, backend_total, backend_alive)
+         VALUES (?, ?, ?, 3, 2)",
+    )
+    .bind(cluster_id)
+    .bind(qps)
+    .bind(query_total)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Value of the sample with exactly this name and label set
+fn sample(exposition: &str, series: &str) -> Option<f64> {
+    exposition
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' ')?.parse().ok())
+}
+
+#[test]
+fn test_process_metrics_render() {
+    let metrics = ProcessMetrics::new();
+    metrics.observe_http_request("GET", "/api/clusters/:id", 200, Duration::from_millis(3));
+    metrics.observe_http_request("GET", "/api/clusters/:id", 200, Duration::from_millis(300));
+    metrics.observe_collector_tick(Duration::from_secs(2));
+    metrics.record_collector_failure(7, "prod \"east\"");
+    metrics.record_collector_failure(7, "prod \"east\"");
+
+    let mut out = String::new();
+    metrics.render(&mut out);
+
+    let labels = r#"method="GET",route="/api/clusters/:id",status="200""#;
+    let bucket = |le: &str| {
+        sample(
+            &out,
+            &format!(
+                "starrocks_admin_http_request_duration_seconds_bucket{{{},le=\"{}\"}}",
+                labels, le
+            ),
+        )
+    };
+    assert_eq!(bucket("0.005"), Some(1.0));
+    assert_eq!(bucket("0.25"), Some(1.0));
+    assert_eq!(bucket("0.5"), Some(2.0));
+    assert_eq!(bucket("+Inf"), Some(2.0));
+    assert_eq!(
+        sample(&out, &format!("starrocks_admin_http_request_duration_seconds_count{{{}}}", labels)),
+        Some(2.0)
+    );
+
+    assert_eq!(sample(&out, "starrocks_admin_collector_tick_duration_seconds_count"), Some(1.0));
+    assert_eq!(
+        sample(&out, r#"starrocks_admin_collector_tick_duration_seconds_bucket{le="1"}"#),
+        Some(0.0)
+    );
+    assert_eq!(
+        sample(
+            &out,
+            r#"starrocks_admin_collector_failures_total{cluster_id="7",cluster_name="prod \"east\""}"#
+        ),
+        Some(2.0)
+    );
+    assert!(out.contains("# TYPE starrocks_admin_collector_failures_total counter"));
+}
+
+#[tokio::test]
+async fn test_render_exports_latest_snapshot_per_cluster() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    let east = create_cluster(
+        &state,
+        "east",
+        "east.example.com",
+        8030,
+        Some(data.org1_id),
+        data.super_admin_user_id,
+    )
+    .await;
+    create_cluster(&state, "west", "west.example.com", 8030, None, data.super_admin_user_id).await;
+    insert_snapshot(&pool, east.id, 1.5, 100).await;
+    // The newest snapshot wins
+    sqlx::query("UPDATE metrics_snapshots SET collected_at = datetime('now', '-1 minute')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    insert_snapshot(&pool, east.id, 4.25, 120).await;
+
+    let out = state.metrics_exporter_service.render().await.unwrap();
+
+    let labels = format!(
+        "cluster_id=\"{}\",cluster_name=\"east\",organization_id=\"{}\"",
+        east.id, data.org1_id
+    );
+    assert_eq!(sample(&out, "starrocks_admin_clusters"), Some(2.0));
+    assert_eq!(sample(&out, &format!("starrocks_admin_cluster_qps{{{}}}", labels)), Some(4.25));
+    assert_eq!(
+        sample(&out, &format!("starrocks_admin_cluster_queries_total{{{}}}", labels)),
+        Some(120.0)
+    );
+    assert_eq!(
+        sample(&out, &format!("starrocks_admin_cluster_backends_alive{{{}}}", labels)),
+        Some(2.0)
+    );
+    assert!(out.contains("# TYPE starrocks_admin_cluster_queries_total counter"));
+    assert!(out.contains("# TYPE starrocks_admin_cluster_qps gauge"));
+    // West was never collected
+    assert!(!out.contains("cluster_name=\"west\""));
+    assert_eq!(sample(&out, "starrocks_admin_mysql_pools"), Some(0.0));
+}
+
+#[tokio::test]
+async fn test_collector_records_ticks_and_failures() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+
+    // Nothing listens on port 1: every collection of this cluster fails
+    let cluster =
+        create_cluster(&state, "unreachable", "127.0.0.1", 1, None, data.super_admin_user_id).await;
+    state
+        .metrics_collector_service
+        .collect_once()
+        .await
+        .unwrap();
+
+    let mut out = String::new();
+    state.process_metrics.render(&mut out);
+    assert_eq!(sample(&out, "starrocks_admin_collector_tick_duration_seconds_count"), Some(1.0));
+    assert_eq!(
+        sample(
+            &out,
+            &format!(
+                "starrocks_admin_collector_failures_total{{cluster_id=\"{}\",cluster_name=\"unreachable\"}}",
+                cluster.id
+            )
+        ),
+        Some(1.0)
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_and_http_latencies() {
+    let pool = create_test_db().await;
+    let mut state = (*create_test_app_state(&pool).await).clone();
+    state.metrics_exporter_token = Some("scrape-token".to_string());
+    let state = Arc::new(state);
+
+    let app = Router::new()
+        .route("/metrics", get(crate::handlers::metrics::prometheus_metrics))
+        .route("/api/clusters/:id", get(|| async { "ok" }))
+        .with_state(Arc::clone(&state))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::clone(&state.process_metrics),
+            http_metrics_middleware,
+        ));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/api/clusters/42")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(
+            Request::get("/metrics")
+                .header(header::AUTHORIZATION, "Bearer scrape-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4")
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let out = String::from_utf8(body.to_vec()).unwrap();
+
+    // Route templates, not raw paths, label the latencies
+    assert_eq!(
+        sample(
+            &out,
+            r#"starrocks_admin_http_request_duration_seconds_count{method="GET",route="/api/clusters/:id",status="200"}"#
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(
+            &out,
+            r#"starrocks_admin_http_request_duration_seconds_count{method="GET",route="/metrics",status="401"}"#
+        ),
+        Some(1.0)
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_without_token_serves_local_scrapers_only_when_allowed() {
+    let pool = create_test_db().await;
+    let mut state = (*create_test_app_state(&pool).await).clone();
+    assert!(state.metrics_exporter_token.is_none());
+    assert!(!state.metrics_allow_unauthenticated_local);
+
+    let scrape = |state: Arc<crate::AppState>, peer: Option<&str>| {
+        let mut app = Router::new()
+            .route("/metrics", get(crate::handlers::metrics::prometheus_metrics))
+            .with_state(state);
+        if let Some(peer) = peer {
+            app = app.layer(MockConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        }
+        async move {
+            app.oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+
+    // Not served by default, a local reverse proxy would expose it
+    let closed = Arc::new(state.clone());
+    assert_eq!(
+        scrape(Arc::clone(&closed), Some("127.0.0.1:40000")).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    state.metrics_allow_unauthenticated_local = true;
+    let local = Arc::new(state);
+    assert_eq!(scrape(Arc::clone(&local), Some("127.0.0.1:40000")).await, StatusCode::OK);
+    assert_eq!(scrape(Arc::clone(&local), Some("[::1]:40000")).await, StatusCode::OK);
+    assert_eq!(scrape(Arc::clone(&local), Some("10.0.0.8:40000")).await, StatusCode::UNAUTHORIZED);
+    // Unknown peer
+    assert_eq!(scrape(local, None).await, StatusCode::UNAUTHORIZED);
+}
@@ -1,16 +1,39 @@
 // Test modules
 
+mod admin_audit_service_test;
+mod alert_service_test;
+mod api_token_test;
+mod audit_source_test;
 mod auth_middleware_test;
 mod casbin_service_test;
+mod cluster_credential_encryption_test;
+mod cluster_permission_test;
+mod cluster_tls_test;
 pub mod common;
+mod fe_failover_test;
+mod fleet_overview_test;
 mod handler_organization_isolation_test;
+mod load_job_test;
+mod login_security_test;
+mod metrics_collection_test;
+mod metrics_exporter_service_test;
 mod models_test;
 mod multi_tenant_cluster_service_test;
 mod multi_tenant_integration_test;
 mod multi_tenant_middleware_test;
 mod multi_tenant_role_service_test;
 mod multi_tenant_user_service_test;
+mod mv_dependency_test;
+mod mv_refresh_test;
+mod node_metrics_test;
 mod organization_service_test;
 mod permission_service_test;
+mod profile_archive_service_test;
 mod role_service_test;
+mod routine_load_test;
+mod session_test;
+mod sql_policy_service_test;
+mod sso_test;
+mod user_active_cluster_test;
 mod user_role_service_test;
+mod variable_history_test;
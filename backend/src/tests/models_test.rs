@@ -111,6 +111,7 @@ fn test_permission_tree_structure() {
         resource: Some("dashboard".to_string()),
         action: None,
         description: Some("Dashboard menu".to_string()),
+        scope_level: None,
         children: vec![PermissionTree {
             id: 2,
             code: "menu:dashboard:sub".to_string(),
@@ -119,6 +120,7 @@ fn test_permission_tree_structure() {
             resource: Some("dashboard".to_string()),
             action: None,
             description: None,
+            scope_level: None,
             children: vec![],
         }],
     };
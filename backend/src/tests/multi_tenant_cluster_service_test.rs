@@ -8,7 +8,7 @@ use std::sync::Arc;
 #[tokio::test]
 async fn test_cluster_organization_filtering() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -28,6 +28,8 @@ async fn test_cluster_organization_filtering() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster = cluster_service
@@ -59,6 +61,8 @@ async fn test_cluster_organization_filtering() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
@@ -99,7 +103,7 @@ async fn test_cluster_organization_filtering() {
 #[tokio::test]
 async fn test_cluster_creation_organization_scoping() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -119,6 +123,8 @@ async fn test_cluster_creation_organization_scoping() {
         catalog: "default_catalog".to_string(),
         organization_id: Some(test_data.org1_id),
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let super_cluster = cluster_service
@@ -143,6 +149,8 @@ async fn test_cluster_creation_organization_scoping() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org_cluster = cluster_service
@@ -172,6 +180,8 @@ async fn test_cluster_creation_organization_scoping() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let result = cluster_service
@@ -184,7 +194,7 @@ async fn test_cluster_creation_organization_scoping() {
 #[tokio::test]
 async fn test_active_cluster_per_organization() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -204,6 +214,8 @@ async fn test_active_cluster_per_organization() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let cluster1 = cluster_service
@@ -228,6 +240,8 @@ async fn test_active_cluster_per_organization() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let cluster2 = cluster_service
@@ -291,7 +305,7 @@ async fn test_active_cluster_per_organization() {
 #[tokio::test]
 async fn test_active_cluster_organization_isolation() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -311,6 +325,8 @@ async fn test_active_cluster_organization_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster = cluster_service
@@ -337,6 +353,8 @@ async fn test_active_cluster_organization_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org2_cluster = cluster_service
@@ -384,6 +402,8 @@ async fn test_active_cluster_organization_isolation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let org1_cluster2 = cluster_service
@@ -430,7 +450,7 @@ async fn test_active_cluster_organization_isolation() {
 #[tokio::test]
 async fn test_cluster_first_auto_activation() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -450,6 +470,8 @@ async fn test_cluster_first_auto_activation() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let first_cluster = cluster_service
@@ -472,7 +494,7 @@ async fn test_cluster_first_auto_activation() {
 #[tokio::test]
 async fn test_cluster_activation_without_organization() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -492,6 +514,8 @@ async fn test_cluster_activation_without_organization() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     // Org admin tries to create cluster without org context - should fail
@@ -508,7 +532,7 @@ async fn test_cluster_activation_without_organization() {
 #[tokio::test]
 async fn test_cluster_duplicate_name_prevention() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -528,6 +552,8 @@ async fn test_cluster_duplicate_name_prevention() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     cluster_service
@@ -550,6 +576,8 @@ async fn test_cluster_duplicate_name_prevention() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let result = cluster_service
@@ -562,7 +590,7 @@ async fn test_cluster_duplicate_name_prevention() {
 #[tokio::test]
 async fn test_super_admin_cross_organization_cluster_access() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -582,6 +610,8 @@ async fn test_super_admin_cross_organization_cluster_access() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     cluster_service
@@ -608,6 +638,8 @@ async fn test_super_admin_cross_organization_cluster_access() {
         catalog: "default_catalog".to_string(),
         organization_id: None,
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     cluster_service
@@ -644,6 +676,8 @@ async fn test_super_admin_cross_organization_cluster_access() {
         catalog: "default_catalog".to_string(),
         organization_id: Some(test_data.org1_id),
         deployment_mode: crate::models::cluster::DeploymentMode::default(),
+        fe_endpoints: None,
+        tls: None,
     };
 
     let super_cluster = cluster_service
@@ -657,7 +691,7 @@ async fn test_super_admin_cross_organization_cluster_access() {
 #[tokio::test]
 async fn test_cluster_activation_concurrency() {
     let pool = create_test_db().await;
-    let mysql_pool_manager = Arc::new(MySQLPoolManager::new());
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
     let cluster_service = ClusterService::new(pool.clone(), mysql_pool_manager);
 
     let test_data = setup_multi_tenant_test_data(&pool).await;
@@ -679,6 +713,8 @@ async fn test_cluster_activation_concurrency() {
             catalog: "default_catalog".to_string(),
             organization_id: None,
             deployment_mode: crate::models::cluster::DeploymentMode::default(),
+            fe_endpoints: None,
+            tls: None,
         };
 
         let cluster = cluster_service
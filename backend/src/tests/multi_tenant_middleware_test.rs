@@ -1,8 +1,10 @@
 // Multi-tenant middleware tests
 
 use crate::middleware::{AuthState, OrgContext, auth_middleware};
+use crate::services::{ApiTokenService, SessionService};
 use crate::tests::common::{
-    create_test_casbin_service, create_test_db, setup_multi_tenant_test_data,
+    create_test_casbin_service, create_test_db, create_test_session_token,
+    setup_multi_tenant_test_data,
 };
 use crate::utils::JwtUtil;
 use axum::{
@@ -13,13 +15,6 @@ use axum::{
 use std::sync::Arc;
 use tower::ServiceExt;
 
-/// Helper function to create test JWT token
-fn create_test_token(jwt_util: &JwtUtil, user_id: i64, username: &str) -> String {
-    jwt_util
-        .generate_token(user_id, username)
-        .expect("Failed to create test token")
-}
-
 /// Helper function to create test request with authentication
 fn create_auth_request(token: &str, path: &str, method: Method) -> Request {
     Request::builder()
@@ -46,12 +41,16 @@ async fn test_super_admin_org_context() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Create super admin token
-    let token = create_test_token(&jwt_util, test_data.super_admin_user_id, "super_admin");
+    let token =
+        create_test_session_token(&pool, &jwt_util, test_data.super_admin_user_id, "super_admin")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -89,12 +88,16 @@ async fn test_org_admin_org_context() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Create org admin token
-    let token = create_test_token(&jwt_util, test_data.org1_admin_user_id, "org1_admin");
+    let token =
+        create_test_session_token(&pool, &jwt_util, test_data.org1_admin_user_id, "org1_admin")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -132,12 +135,16 @@ async fn test_regular_user_org_context() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Create regular user token
-    let token = create_test_token(&jwt_util, test_data.org1_regular_user_id, "org1_regular");
+    let token =
+        create_test_session_token(&pool, &jwt_util, test_data.org1_regular_user_id, "org1_regular")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -175,12 +182,16 @@ async fn test_cross_organization_user_access() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Create token for org1 user trying to access org2 resources
-    let token = create_test_token(&jwt_util, test_data.org1_regular_user_id, "org1_regular");
+    let token =
+        create_test_session_token(&pool, &jwt_util, test_data.org1_regular_user_id, "org1_regular")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -218,12 +229,14 @@ async fn test_user_without_organization() {
     let no_org_user_id = crate::tests::common::create_test_user(&pool, "no_org_user").await;
 
     // Create token for user without organization
-    let token = create_test_token(&jwt_util, no_org_user_id, "no_org_user");
+    let token = create_test_session_token(&pool, &jwt_util, no_org_user_id, "no_org_user").await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -262,6 +275,8 @@ async fn test_invalid_token_rejection() {
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -294,6 +309,8 @@ async fn test_missing_token_rejection() {
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -324,12 +341,16 @@ async fn test_org_context_persistence() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Create org admin token
-    let token = create_test_token(&jwt_util, test_data.org2_admin_user_id, "org2_admin");
+    let token =
+        create_test_session_token(&pool, &jwt_util, test_data.org2_admin_user_id, "org2_admin")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -373,13 +394,19 @@ async fn test_organization_user_isolation() {
     let test_data = setup_multi_tenant_test_data(&pool).await;
 
     // Test that users from different organizations have different contexts
-    let org1_token = create_test_token(&jwt_util, test_data.org1_regular_user_id, "org1_regular");
-    let org2_token = create_test_token(&jwt_util, test_data.org2_regular_user_id, "org2_regular");
+    let org1_token =
+        create_test_session_token(&pool, &jwt_util, test_data.org1_regular_user_id, "org1_regular")
+            .await;
+    let org2_token =
+        create_test_session_token(&pool, &jwt_util, test_data.org2_regular_user_id, "org2_regular")
+            .await;
 
     // Create auth state
     let auth_state = AuthState {
         jwt_util: jwt_util.clone(),
         casbin_service: casbin_service.clone(),
+        api_token_service: Arc::new(ApiTokenService::new(pool.clone())),
+        session_service: Arc::new(SessionService::new(pool.clone(), jwt_util.clone(), "7d")),
         db: pool.clone(),
     };
 
@@ -292,7 +292,10 @@ async fn test_role_permission_assignment_organization_validation() {
     role_service
         .assign_permissions_to_role(
             org1_role_id,
-            UpdateRolePermissionsRequest { permission_ids: permission_ids.clone() },
+            UpdateRolePermissionsRequest {
+                permission_ids: permission_ids.clone(),
+                scoped_permissions: None,
+            },
             Some(test_data.org1_id),
             false,
         )
@@ -328,7 +331,10 @@ async fn test_role_permission_assignment_organization_validation() {
     let result = role_service
         .assign_permissions_to_role(
             org2_role_id,
-            UpdateRolePermissionsRequest { permission_ids: permission_ids.clone() },
+            UpdateRolePermissionsRequest {
+                permission_ids: permission_ids.clone(),
+                scoped_permissions: None,
+            },
             Some(test_data.org1_id),
             false,
         )
@@ -340,7 +346,7 @@ async fn test_role_permission_assignment_organization_validation() {
     role_service
         .assign_permissions_to_role(
             org2_role_id,
-            UpdateRolePermissionsRequest { permission_ids },
+            UpdateRolePermissionsRequest { permission_ids, scoped_permissions: None },
             None,
             true,
         )
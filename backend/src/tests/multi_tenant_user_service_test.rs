@@ -140,6 +140,7 @@ async fn test_user_update_organization_validation() {
         password: Some("new_password".to_string()),
         role_ids: None,
         organization_id: None,
+        is_active: None,
     };
 
     let updated_user = user_service
@@ -161,6 +162,7 @@ async fn test_user_update_organization_validation() {
         password: Some("new_password".to_string()),
         role_ids: None,
         organization_id: None,
+        is_active: None,
     };
 
     let result = user_service
@@ -177,6 +179,7 @@ async fn test_user_update_organization_validation() {
         password: Some("new_password".to_string()),
         role_ids: None,
         organization_id: None,
+        is_active: None,
     };
 
     let updated_user = user_service
@@ -249,6 +252,7 @@ async fn test_user_role_assignment_organization_validation() {
         password: None,
         role_ids: Some(vec![test_data.regular_role_id]),
         organization_id: None,
+        is_active: None,
     };
     user_service
         .update_user(org1_user_id, update_req, Some(test_data.org1_id), false)
@@ -275,6 +279,7 @@ async fn test_user_role_assignment_organization_validation() {
         password: None,
         role_ids: Some(vec![test_data.regular_role_id]),
         organization_id: None,
+        is_active: None,
     };
     let result = user_service
         .update_user(org2_user_id, update_req, Some(test_data.org1_id), false)
@@ -293,6 +298,7 @@ async fn test_user_role_assignment_organization_validation() {
         password: None,
         role_ids: Some(vec![test_data.super_admin_role_id]),
         organization_id: None,
+        is_active: None,
     };
     user_service
         .update_user(org2_user_id, super_admin_update_req, None, true)
@@ -384,6 +390,7 @@ async fn test_user_cross_organization_access_prevention() {
         password: None,
         role_ids: None,
         organization_id: None,
+        is_active: None,
     };
 
     let result = user_service
@@ -408,6 +415,7 @@ async fn test_user_cross_organization_access_prevention() {
         password: None,
         role_ids: None,
         organization_id: None,
+        is_active: None,
     };
     let result = user_service
         .update_user(test_data.org2_admin_user_id, update_req2, Some(test_data.org1_id), false)
@@ -0,0 +1,237 @@
+// Materialized view dependency tests: base table extraction, graph building, staleness,
+// drop impact and permissions
+
+use crate::middleware::permission_extractor::{extract_permission, scope_level};
+use crate::models::{MvDependencyNodeType, MvStalenessIssue, PermissionScopeLevel};
+use crate::services::MaterializedViewService;
+use crate::services::materialized_view_service::{
+    MvDefinition, assess_staleness, build_dependency_graph, consecutive_failures, impact,
+    parse_object_id,
+};
+use crate::tests::common::create_test_db;
+use crate::utils::sql_lexer::table_references;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn mv(database: &str, name: &str, definition: &str, refreshed_at: &str) -> MvDefinition {
+    MvDefinition {
+        database_name: database.to_string(),
+        name: name.to_string(),
+        refresh_type: "ASYNC".to_string(),
+        is_active: true,
+        task_name: Some(format!("mv-{}", name)),
+        last_refresh_state: Some("SUCCESS".to_string()),
+        last_refresh_start_time: Some(refreshed_at.to_string()),
+        last_refresh_finished_time: Some(refreshed_at.to_string()),
+        definition: definition.to_string(),
+        ..Default::default()
+    }
+}
+
+fn states(states: &[&str]) -> Vec<String> {
+    states.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_table_references_skip_ctes_and_function_arguments() {
+    let sql = "CREATE MATERIALIZED VIEW `daily` REFRESH ASYNC AS
+        WITH recent AS (SELECT * FROM orders WHERE EXTRACT(YEAR FROM dt) = 2025)
+        SELECT r.id, SUBSTRING(c.name FROM 1 FOR 3)
+        FROM recent r
+        JOIN `crm`.customers c ON r.customer_id = c.id
+        LEFT JOIN hive.ods.events e ON e.id = r.id, dim_date d, TABLE(generate_series(1, 3))
+        WHERE r.id IN (SELECT id FROM orders)";
+
+    assert_eq!(
+        table_references(sql),
+        vec![
+            vec!["orders".to_string()],
+            vec!["crm".to_string(), "customers".to_string()],
+            vec!["hive".to_string(), "ods".to_string(), "events".to_string()],
+        ]
+    );
+    assert_eq!(
+        table_references("SELECT * FROM a x, b.c AS y"),
+        vec![vec!["a".to_string()], vec!["b".to_string(), "c".to_string()]]
+    );
+}
+
+#[test]
+fn test_parse_materialized_view_row() {
+    let row = json!({
+        "TABLE_SCHEMA": "sales",
+        "TABLE_NAME": "daily_orders",
+        "REFRESH_TYPE": "ASYNC",
+        "IS_ACTIVE": "false",
+        "INACTIVE_REASON": "base-table dropped: orders",
+        "TASK_NAME": "mv-1001",
+        "LAST_REFRESH_STATE": "FAILED",
+        "LAST_REFRESH_ERROR_MESSAGE": "",
+        "MATERIALIZED_VIEW_DEFINITION": "SELECT * FROM orders",
+    });
+    let mv = MaterializedViewService::parse_mv_definition(&row);
+
+    assert_eq!(mv.id(), "default_catalog.sales.daily_orders");
+    assert!(!mv.is_active);
+    assert_eq!(mv.inactive_reason.as_deref(), Some("base-table dropped: orders"));
+    assert_eq!(mv.task_name.as_deref(), Some("mv-1001"));
+    assert_eq!(mv.last_refresh_error, None);
+    assert_eq!(mv.last_refresh_start_time, None);
+}
+
+#[test]
+fn test_dependency_graph_links_views_and_tables() {
+    let mvs = vec![
+        mv(
+            "sales",
+            "daily",
+            "SELECT * FROM orders JOIN crm.customers USING (id)",
+            "2025-02-01 10:00:00",
+        ),
+        mv("report", "monthly", "SELECT * FROM sales.daily", "2025-02-01 11:00:00"),
+        mv("sales", "ext", "SELECT * FROM hive.ods.events", "2025-02-01 09:00:00"),
+    ];
+    let updates = HashMap::from([(
+        "default_catalog.sales.orders".to_string(),
+        "2025-02-01 10:30:00".to_string(),
+    )]);
+    let graph = build_dependency_graph(&mvs, &updates);
+
+    assert_eq!(graph.nodes.len(), 6);
+    assert_eq!(graph.edges.len(), 4);
+    let node = |id: &str| graph.nodes.iter().find(|n| n.id == id).unwrap();
+    assert_eq!(
+        node("default_catalog.sales.daily").node_type,
+        MvDependencyNodeType::MaterializedView
+    );
+    assert_eq!(node("default_catalog.sales.orders").node_type, MvDependencyNodeType::Table);
+    assert_eq!(
+        node("default_catalog.sales.orders").last_updated.as_deref(),
+        Some("2025-02-01 10:30:00")
+    );
+    assert_eq!(node("hive.ods.events").node_type, MvDependencyNodeType::ExternalTable);
+    // The view read by another view is not duplicated as a table
+    assert!(
+        graph
+            .edges
+            .iter()
+            .any(|e| e.source == "default_catalog.report.monthly"
+                && e.target == "default_catalog.sales.daily")
+    );
+}
+
+#[test]
+fn test_staleness_flags_inactive_failing_and_outdated_views() {
+    let mut daily = mv("sales", "daily", "SELECT * FROM orders", "2025-02-01 10:00:00");
+    let monthly = mv("report", "monthly", "SELECT * FROM sales.daily", "2025-02-01 11:00:00");
+    let updates = HashMap::from([(
+        "default_catalog.sales.orders".to_string(),
+        "2025-02-01 10:30:00".to_string(),
+    )]);
+    let graph = build_dependency_graph(&[daily.clone(), monthly.clone()], &updates);
+
+    let report = assess_staleness(&daily, &graph, 0);
+    assert_eq!(report.outdated_base_tables, vec!["default_catalog.sales.orders".to_string()]);
+    assert_eq!(report.issues, vec![MvStalenessIssue::OutdatedBaseTable]);
+
+    // Refreshed after daily finished its last refresh
+    assert!(assess_staleness(&monthly, &graph, 1).issues.is_empty());
+
+    daily.is_active = false;
+    let report = assess_staleness(&daily, &graph, 3);
+    assert_eq!(report.consecutive_failures, 3);
+    assert_eq!(
+        report.issues,
+        vec![
+            MvStalenessIssue::Inactive,
+            MvStalenessIssue::RefreshFailing,
+            MvStalenessIssue::OutdatedBaseTable
+        ]
+    );
+}
+
+#[test]
+fn test_consecutive_failures_stop_at_last_success() {
+    assert_eq!(consecutive_failures(&states(&["FAILED", "FAILED", "SUCCESS", "FAILED"])), 2);
+    assert_eq!(consecutive_failures(&states(&["RUNNING", "FAILED", "SUCCESS"])), 1);
+    assert_eq!(consecutive_failures(&states(&["SUCCESS", "FAILED"])), 0);
+    assert_eq!(consecutive_failures(&[]), 0);
+}
+
+#[test]
+fn test_impact_follows_nested_views() {
+    let mvs = vec![
+        mv("sales", "daily", "SELECT * FROM orders", "2025-02-01 10:00:00"),
+        mv("sales", "weekly", "SELECT * FROM orders", "2025-02-01 10:00:00"),
+        mv(
+            "report",
+            "monthly",
+            "SELECT * FROM sales.daily JOIN sales.weekly USING (d)",
+            "2025-02-01 11:00:00",
+        ),
+        mv("report", "other", "SELECT * FROM customers", "2025-02-01 11:00:00"),
+    ];
+    let graph = build_dependency_graph(&mvs, &HashMap::new());
+
+    let (catalog, database, name) = parse_object_id("sales.orders").unwrap();
+    let result = impact(&graph, &format!("{}.{}.{}", catalog, database, name));
+    let views: Vec<(&str, i32, Option<&str>)> = result
+        .views
+        .iter()
+        .map(|v| (v.name.as_str(), v.depth, v.via.as_deref()))
+        .collect();
+    assert_eq!(
+        views,
+        vec![
+            ("daily", 1, None),
+            ("weekly", 1, None),
+            ("monthly", 2, Some("default_catalog.sales.daily")),
+        ]
+    );
+
+    assert!(
+        impact(&graph, "default_catalog.sales.unused")
+            .views
+            .is_empty()
+    );
+    assert!(parse_object_id("orders").is_err());
+    assert!(parse_object_id("sales..orders").is_err());
+    assert_eq!(
+        parse_object_id("hive.ods.events").unwrap(),
+        ("hive".to_string(), "ods".to_string(), "events".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_mv_dependency_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+    let routes = [
+        ("/api/clusters/materialized_views/dependencies", "materialized_views:dependencies"),
+        ("/api/clusters/materialized_views/staleness", "materialized_views:staleness"),
+        ("/api/clusters/materialized_views/impact", "materialized_views:impact"),
+    ];
+
+    for (uri, action) in routes {
+        let (resource, extracted) = extract_permission("GET", uri).unwrap();
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+        assert_eq!(scope_level(&resource, &extracted), Some(PermissionScopeLevel::Database));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN roles r ON r.id = rp.role_id
+             WHERE p.resource = ? AND p.action = ? AND r.code = 'admin'",
+        )
+        .bind(&resource)
+        .bind(&extracted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not granted to admin", resource, extracted);
+    }
+
+    assert_eq!(
+        extract_permission("GET", "/api/clusters/materialized_views/daily_orders").map(|p| p.1),
+        Some("materialized_views:get".to_string())
+    );
+}
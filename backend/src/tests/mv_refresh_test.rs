@@ -0,0 +1,321 @@
+// Materialized view refresh history tests: task run parsing, statistics, error grouping,
+// trends, local retention and permissions
+
+use crate::middleware::permission_extractor::{extract_permission, scope_level};
+use crate::models::{
+    Cluster, CreateClusterRequest, MvRefreshHistoryFilter, MvRefreshRun, MvRefreshTrendInterval,
+    PermissionScopeLevel,
+};
+use crate::services::mv_refresh_service::{
+    duration_ms, failure_buckets, group_errors, parse_task_run, refresh_stats,
+};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+
+async fn create_cluster(state: &crate::AppState, created_by: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "mv-refresh".to_string(),
+                description: None,
+                fe_host: "fe.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn run(
+    query_id: &str,
+    name: &str,
+    state: &str,
+    create_time: &str,
+    duration: Option<i64>,
+    error: Option<&str>,
+) -> MvRefreshRun {
+    MvRefreshRun {
+        query_id: query_id.to_string(),
+        database_name: "sales".to_string(),
+        name: name.to_string(),
+        task_name: format!("mv-{}", name),
+        state: state.to_string(),
+        create_time: create_time.to_string(),
+        finish_time: None,
+        duration_ms: duration,
+        error_code: error.map(|_| -1),
+        error_message: error.map(str::to_string),
+        refreshed_partitions: Vec::new(),
+    }
+}
+
+#[test]
+fn test_parse_task_run() {
+    let tasks =
+        HashMap::from([("mv-1001".to_string(), ("sales".to_string(), "daily_orders".to_string()))]);
+    let row = json!({
+        "QUERY_ID": "7f3a-01",
+        "TASK_NAME": "mv-1001",
+        "CREATE_TIME": "2025-02-01 10:00:00",
+        "FINISH_TIME": "2025-02-01 10:01:30",
+        "STATE": "FAILED",
+        "ERROR_CODE": "-1",
+        "ERROR_MESSAGE": "Memory of query exceed limit",
+        "EXTRA_MESSAGE": "{\"forceRefresh\":false,\"mvPartitionsToRefresh\":[\"p20250131\",\"p20250201\"]}",
+    });
+    let parsed = parse_task_run(&row, &tasks).unwrap();
+
+    assert_eq!(parsed.name, "daily_orders");
+    assert_eq!(parsed.database_name, "sales");
+    assert_eq!(parsed.duration_ms, Some(90_000));
+    assert_eq!(parsed.error_code, Some(-1));
+    assert_eq!(parsed.error_message.as_deref(), Some("Memory of query exceed limit"));
+    assert_eq!(parsed.refreshed_partitions, vec!["p20250131", "p20250201"]);
+
+    let running = json!({
+        "QUERY_ID": "7f3a-02",
+        "TASK_NAME": "mv-1001",
+        "CREATE_TIME": "2025-02-01 11:00:00",
+        "STATE": "RUNNING",
+        "ERROR_CODE": "0",
+        "ERROR_MESSAGE": "null",
+    });
+    let parsed = parse_task_run(&running, &tasks).unwrap();
+    assert_eq!(parsed.duration_ms, None);
+    assert_eq!(parsed.error_code, None);
+    assert_eq!(parsed.error_message, None);
+
+    // Tasks that do not refresh a known view are ignored
+    let other =
+        json!({ "QUERY_ID": "x", "TASK_NAME": "ctas-1", "CREATE_TIME": "2025-02-01 11:00:00" });
+    assert!(parse_task_run(&other, &tasks).is_none());
+    assert_eq!(duration_ms("2025-02-01 10:00:00", "2025-02-01 09:59:00"), Some(0));
+}
+
+#[test]
+fn test_refresh_stats_errors_and_failure_buckets() {
+    // Newest first
+    let runs = vec![
+        run("q5", "daily", "RUNNING", "2025-02-02 09:00:00", None, None),
+        run(
+            "q4",
+            "daily",
+            "FAILED",
+            "2025-02-02 08:00:00",
+            Some(1_000),
+            Some("Memory exceed limit"),
+        ),
+        run("q3", "daily", "SUCCESS", "2025-02-01 12:00:00", Some(4_000), None),
+        run(
+            "q2",
+            "daily",
+            "FAILED",
+            "2025-02-01 11:00:00",
+            Some(2_000),
+            Some("Memory exceed limit"),
+        ),
+        run(
+            "q1",
+            "daily",
+            "FAILED",
+            "2025-02-01 10:00:00",
+            Some(3_000),
+            Some("Partition p1 not found"),
+        ),
+    ];
+
+    let stats = refresh_stats("sales", "daily", &runs);
+    assert_eq!((stats.runs, stats.succeeded, stats.failed), (4, 1, 3));
+    assert_eq!(stats.failure_rate, 0.75);
+    assert_eq!(stats.avg_duration_ms, Some(2_500));
+    assert_eq!(stats.max_duration_ms, Some(4_000));
+    assert_eq!(stats.last_success_time.as_deref(), Some("2025-02-01 12:00:00"));
+    assert_eq!(stats.last_failure_time.as_deref(), Some("2025-02-02 08:00:00"));
+    assert_eq!(stats.last_error.as_deref(), Some("Memory exceed limit"));
+
+    let errors = group_errors(&runs);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].error_message, "Memory exceed limit");
+    assert_eq!(errors[0].count, 2);
+    assert_eq!(errors[0].query_ids, vec!["q4", "q2"]);
+    assert_eq!(errors[0].first_seen, "2025-02-01 11:00:00");
+    assert_eq!(errors[0].last_seen, "2025-02-02 08:00:00");
+
+    let mut oldest_first = runs.clone();
+    oldest_first.reverse();
+    let days = failure_buckets(&oldest_first, MvRefreshTrendInterval::Day);
+    let days: Vec<(&str, i64, i64)> = days
+        .iter()
+        .map(|b| (b.bucket.as_str(), b.runs, b.failed))
+        .collect();
+    assert_eq!(days, vec![("2025-02-01", 3, 2), ("2025-02-02", 1, 1)]);
+    let hours = failure_buckets(&oldest_first, MvRefreshTrendInterval::Hour);
+    assert_eq!(hours.len(), 4);
+    assert_eq!(hours[0].bucket, "2025-02-01 10:00:00");
+
+    assert_eq!(refresh_stats("sales", "none", &[]).failure_rate, 0.0);
+}
+
+#[tokio::test]
+async fn test_refresh_history_is_kept_and_updated_locally() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.mv_refresh_service;
+
+    service
+        .save_runs(
+            cluster.id,
+            &[
+                run("q1", "daily", "SUCCESS", "2025-02-01 10:00:00", Some(3_000), None),
+                run("q2", "daily", "RUNNING", "2025-02-01 11:00:00", None, None),
+                run("q3", "weekly", "SUCCESS", "2025-02-01 11:30:00", Some(9_000), None),
+            ],
+        )
+        .await
+        .unwrap();
+    // The running refresh failed by the next collection
+    service
+        .save_runs(
+            cluster.id,
+            &[run("q2", "daily", "FAILED", "2025-02-01 11:00:00", Some(5_000), Some("Timeout"))],
+        )
+        .await
+        .unwrap();
+
+    let database = service
+        .resolve_database(cluster.id, "daily", None)
+        .await
+        .unwrap();
+    assert_eq!(database, "sales");
+    let detail = service
+        .get_detail(cluster.id, &database, "daily", &MvRefreshHistoryFilter::default())
+        .await
+        .unwrap();
+    let ids: Vec<&str> = detail.runs.iter().map(|r| r.query_id.as_str()).collect();
+    assert_eq!(ids, vec!["q2", "q1"]);
+    assert_eq!(detail.runs[0].state, "FAILED");
+    assert_eq!(detail.stats.failed, 1);
+    assert_eq!(detail.errors[0].error_message, "Timeout");
+    assert_eq!(detail.errors[0].query_ids, vec!["q2"]);
+
+    let failed_only = service
+        .get_detail(
+            cluster.id,
+            "sales",
+            "daily",
+            &MvRefreshHistoryFilter {
+                state: Some("failed".to_string()),
+                start_time: Some("2025-02-01 10:30:00".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(failed_only.runs.len(), 1);
+    assert_eq!(failed_only.stats.runs, 1);
+
+    let trends = service
+        .get_trends(cluster.id, "sales", "daily", None, None, MvRefreshTrendInterval::Day)
+        .await
+        .unwrap();
+    let durations: Vec<i64> = trends.duration.iter().map(|p| p.duration_ms).collect();
+    assert_eq!(durations, vec![3_000, 5_000]);
+    assert_eq!(trends.failure_rate[0].failure_rate, 0.5);
+
+    let stats = service
+        .get_stats(cluster.id, None, None, None)
+        .await
+        .unwrap();
+    let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["daily", "weekly"]);
+
+    assert!(
+        service
+            .get_stats(cluster.id, None, Some("yesterday"), None)
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .resolve_database(cluster.id, "missing", None)
+            .await
+            .is_err()
+    );
+
+    let mut other_db = run("q4", "daily", "SUCCESS", "2025-02-01 12:00:00", Some(1_000), None);
+    other_db.database_name = "report".to_string();
+    service.save_runs(cluster.id, &[other_db]).await.unwrap();
+    assert!(
+        service
+            .resolve_database(cluster.id, "daily", None)
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        service
+            .resolve_database(cluster.id, "daily", Some("report"))
+            .await
+            .unwrap(),
+        "report"
+    );
+
+    let deleted = service
+        .cleanup_before(Utc::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 4);
+}
+
+#[tokio::test]
+async fn test_refresh_history_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+    let routes = [
+        ("/api/clusters/materialized_views/refresh-stats", "materialized_views:refresh_stats"),
+        (
+            "/api/clusters/materialized_views/daily/refresh-history",
+            "materialized_views:refresh_history",
+        ),
+        (
+            "/api/clusters/materialized_views/daily/refresh-trends",
+            "materialized_views:refresh_trends",
+        ),
+    ];
+
+    for (uri, action) in routes {
+        let (resource, extracted) = extract_permission("GET", uri).unwrap();
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+        assert_eq!(scope_level(&resource, &extracted), Some(PermissionScopeLevel::Database));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN roles r ON r.id = rp.role_id
+             WHERE p.resource = ? AND p.action = ? AND r.code = 'admin'",
+        )
+        .bind(&resource)
+        .bind(&extracted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not granted to admin", resource, extracted);
+    }
+}
@@ -0,0 +1,324 @@
+// Per-node metrics history tests
+
+use crate::models::starrocks::{Backend, Frontend};
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::services::node_metrics_service::{
+    build_node_snapshots, labelled_metric_values, parse_disk_paths,
+};
+use crate::services::{NodeMetricsService, NodeMetricsSnapshot, NodeType, TimeRange};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+
+const GB: i64 = 1024 * 1024 * 1024;
+
+async fn create_cluster(state: &crate::AppState, created_by: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "nodes".to_string(),
+                description: None,
+                fe_host: "fe.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn backend(id: &str, host: &str, cpu: &str, alive: bool) -> Backend {
+    serde_json::from_value(json!({
+        "BackendId": id,
+        "IP": host,
+        "HeartbeatPort": "9050",
+        "LastHeartbeat": "2025-02-06 10:00:00",
+        "Alive": alive.to_string(),
+        "TabletNum": "1200",
+        "DataUsedCapacity": "40.000 GB",
+        "AvailCapacity": "60.000 GB",
+        "TotalCapacity": "100.000 GB",
+        "UsedPct": "40.00 %",
+        "CpuUsedPct": cpu,
+        "MemUsedPct": "55.5 %",
+        "NumRunningQueries": "3",
+    }))
+    .unwrap()
+}
+
+fn frontend(name: &str, host: &str, leader: bool) -> Frontend {
+    serde_json::from_value(json!({
+        "Name": name,
+        "IP": host,
+        "EditLogPort": "9010",
+        "HttpPort": "8030",
+        "QueryPort": "9030",
+        "RpcPort": "9020",
+        "Role": if leader { "LEADER" } else { "FOLLOWER" },
+        "ClusterId": "1",
+        "Join": "true",
+        "Alive": "true",
+        "ReplayedJournalId": "4242",
+        "LastHeartbeat": "2025-02-06 10:00:00",
+        "ErrMsg": "",
+        "Version": "3.3.0",
+    }))
+    .unwrap()
+}
+
+fn be_snapshot(cluster_id: i64, minutes_ago: i64, cpu: f64, alive: bool) -> NodeMetricsSnapshot {
+    let mut disk_paths = HashMap::from([(
+        "10001".to_string(),
+        parse_disk_paths(&[json!({
+            "RootPath": "/data1",
+            "State": "ONLINE",
+            "StorageMedium": "HDD",
+            "TotalCapacity": "100.000 GB",
+            "AvailCapacity": format!("{}.000 GB", 100 - minutes_ago),
+            "DataUsedCapacity": "1.000 GB",
+            "TotalUsedPct": format!("{}.00 %", minutes_ago),
+            "TabletNum": "600",
+        })]),
+    )]);
+    let mut snapshots = build_node_snapshots(
+        cluster_id,
+        Utc::now() - Duration::minutes(minutes_ago),
+        NodeType::Be,
+        &[backend("10001", "be1", &format!("{} %", cpu), alive)],
+        &[],
+        "",
+        &mut disk_paths,
+    );
+    snapshots.remove(0)
+}
+
+#[test]
+fn test_build_node_snapshots_from_show_proc() {
+    let metrics = r#"
+# TYPE starrocks_fe_tablet_max_compaction_score gauge
+starrocks_fe_tablet_max_compaction_score{backend="be1:9050"} 12.5
+starrocks_fe_tablet_max_compaction_score{backend="be2:9050"} 3
+starrocks_fe_max_tablet_compaction_score 12.5
+"#;
+    assert_eq!(
+        labelled_metric_values(metrics, "starrocks_fe_tablet_max_compaction_score", "backend"),
+        HashMap::from([("be1:9050".to_string(), 12.5), ("be2:9050".to_string(), 3.0)])
+    );
+
+    let disks = parse_disk_paths(&[
+        json!({
+            "RootPath": "/data1",
+            "State": "ONLINE",
+            "StorageMedium": "SSD",
+            "TotalCapacity": "100.000 GB",
+            "AvailCapacity": "25.000 GB",
+            "DataUsedCapacity": "70.000 GB",
+            "TotalUsedPct": "75.00 %",
+            "TabletNum": "600",
+        }),
+        json!({ "Unexpected": "row" }),
+    ]);
+    assert_eq!(disks.len(), 1);
+    assert_eq!(disks[0].used_bytes, Some(75 * GB));
+    assert_eq!(disks[0].usage_pct, Some(75.0));
+    assert_eq!(disks[0].tablet_count, Some(600));
+
+    let mut disk_paths = HashMap::from([("10001".to_string(), disks)]);
+    let snapshots = build_node_snapshots(
+        1,
+        Utc::now(),
+        NodeType::Be,
+        &[backend("10001", "be1", "12.5 %", true), backend("10002", "be2", "", false)],
+        &[frontend("fe1", "fe1", true), frontend("fe2", "fe2", false)],
+        metrics,
+        &mut disk_paths,
+    );
+    assert_eq!(snapshots.len(), 4);
+
+    let be1 = &snapshots[0];
+    assert_eq!((be1.node_type, be1.node_id.as_str(), be1.port), (NodeType::Be, "10001", 9050));
+    assert!(be1.alive);
+    assert_eq!(be1.cpu_usage_pct, Some(12.5));
+    assert_eq!(be1.mem_usage_pct, Some(55.5));
+    assert_eq!(be1.disk_total_bytes, Some(100 * GB));
+    assert_eq!(be1.disk_used_bytes, Some(40 * GB));
+    assert_eq!(be1.disk_usage_pct, Some(40.0));
+    assert_eq!(be1.data_used_bytes, Some(40 * GB));
+    assert_eq!(be1.tablet_count, Some(1200));
+    assert_eq!(be1.max_compaction_score, Some(12.5));
+    assert_eq!(be1.num_running_queries, Some(3));
+    assert_eq!(be1.disk_paths.len(), 1);
+
+    let be2 = &snapshots[1];
+    assert!(!be2.alive);
+    assert_eq!(be2.cpu_usage_pct, None);
+    assert_eq!(be2.max_compaction_score, Some(3.0));
+    assert!(be2.disk_paths.is_empty());
+
+    let (fe1, fe2) = (&snapshots[2], &snapshots[3]);
+    assert_eq!((fe1.node_type, fe1.node_id.as_str(), fe1.port), (NodeType::Fe, "fe1", 9010));
+    assert!(fe1.is_leader && !fe2.is_leader);
+    assert_eq!(fe1.role.as_deref(), Some("LEADER"));
+    assert_eq!(fe1.replayed_journal_id, Some(4242));
+    assert_eq!(fe1.cpu_usage_pct, None);
+}
+
+#[tokio::test]
+async fn test_node_history_list_detail_and_trends() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.node_metrics_service;
+
+    // Three ticks of one BE (CPU spike in the middle, offline in the last one) and one FE
+    let fe = build_node_snapshots(
+        cluster.id,
+        Utc::now() - Duration::minutes(1),
+        NodeType::Be,
+        &[],
+        &[frontend("fe1", "fe1", true)],
+        "",
+        &mut HashMap::new(),
+    );
+    service
+        .save_snapshots(&[be_snapshot(cluster.id, 30, 20.0, true)])
+        .await
+        .unwrap();
+    service
+        .save_snapshots(&[be_snapshot(cluster.id, 20, 95.0, true)])
+        .await
+        .unwrap();
+    service
+        .save_snapshots(&[be_snapshot(cluster.id, 10, 10.0, false)])
+        .await
+        .unwrap();
+    service.save_snapshots(&fe).await.unwrap();
+
+    let nodes = service
+        .list_nodes(cluster.id, &TimeRange::Hours1)
+        .await
+        .unwrap();
+    assert_eq!(nodes.len(), 2);
+    let be = &nodes[0];
+    assert_eq!((be.latest.node_type, be.latest.node_id.as_str()), (NodeType::Be, "10001"));
+    assert_eq!(be.latest.cpu_usage_pct, Some(10.0));
+    assert!(!be.latest.alive);
+    assert_eq!(be.peak_cpu_usage_pct, Some(95.0));
+    assert_eq!(be.samples, 3);
+    assert!((be.availability_pct - 200.0 / 3.0).abs() < 1e-9);
+    assert_eq!(be.latest.disk_paths[0].root_path, "/data1");
+    assert_eq!(nodes[1].latest.node_type, NodeType::Fe);
+    assert!(nodes[1].latest.is_leader);
+
+    let detail = service
+        .get_node(cluster.id, NodeType::Be, "10001", &TimeRange::Hours1)
+        .await
+        .unwrap();
+    assert_eq!(detail.peak_cpu_usage_pct, Some(95.0));
+    assert_eq!(detail.peak_disk_usage_pct, Some(40.0));
+    assert_eq!(detail.latest.disk_paths[0].usage_pct, Some(10.0));
+
+    let trends = service
+        .get_node_trends(cluster.id, NodeType::Be, "10001", &TimeRange::Hours1)
+        .await
+        .unwrap();
+    let values = |points: &[crate::services::overview_service::TimeSeriesPoint]| {
+        points.iter().map(|p| p.value).collect::<Vec<_>>()
+    };
+    assert_eq!(values(&trends.cpu_usage_pct), vec![20.0, 95.0, 10.0]);
+    assert_eq!(values(&trends.alive), vec![1.0, 1.0, 0.0]);
+    assert_eq!(values(&trends.tablet_count), vec![1200.0; 3]);
+    assert_eq!(trends.disk_paths.len(), 1);
+    assert_eq!(values(&trends.disk_paths[0].usage_pct), vec![30.0, 20.0, 10.0]);
+
+    // Nodes are scoped to their cluster and type
+    assert!(
+        service
+            .get_node(cluster.id, NodeType::Cn, "10001", &TimeRange::Hours1)
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .list_nodes(cluster.id + 1, &TimeRange::Hours1)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_node_history_follows_retention() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = NodeMetricsService::new(pool.clone());
+
+    let old = be_snapshot(cluster.id, 60 * 24 * 10, 50.0, true);
+    let recent = be_snapshot(cluster.id, 5, 60.0, true);
+    service.save_snapshots(&[old, recent]).await.unwrap();
+
+    let deleted = service
+        .cleanup_before(Utc::now() - Duration::days(7))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+    let (remaining,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM node_metrics_snapshots")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 1);
+
+    // Deleting the cluster drops its node history
+    state
+        .cluster_service
+        .delete_cluster(cluster.id)
+        .await
+        .unwrap();
+    let (remaining,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM node_metrics_snapshots")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn test_node_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    for (path, expected) in [
+        ("/api/clusters/overview/nodes", "overview:nodes"),
+        ("/api/clusters/overview/nodes/be/10001", "overview:nodes:get"),
+        ("/api/clusters/overview/nodes/fe/fe1_9010_1700000000/trends", "overview:nodes:trends"),
+    ] {
+        let (resource, action) = extract_permission("GET", path).unwrap();
+        assert_eq!((resource.as_str(), action.as_str()), ("clusters", expected), "{}", path);
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions WHERE resource = 'clusters' AND action = ?",
+        )
+        .bind(expected)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "{}", expected);
+    }
+}
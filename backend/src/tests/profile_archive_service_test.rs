@@ -0,0 +1,401 @@
+// Profile archive service tests
+
+use crate::config::ProfileArchiveConfig;
+use crate::models::{CreateClusterRequest, ProfileArchiveQuery};
+use crate::services::profile_archive_service::{ArchiveSource, ProfileArchiveService};
+use crate::services::{cluster_service::ClusterService, mysql_pool_manager::MySQLPoolManager};
+use crate::tests::common::{create_test_db, setup_multi_tenant_test_data};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+fn load_profile(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/profiles/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e))
+}
+
+async fn setup(
+    pool: &SqlitePool,
+    config: ProfileArchiveConfig,
+) -> (ProfileArchiveService, i64, i64) {
+    let mysql_pool_manager = Arc::new(MySQLPoolManager::default());
+    let cluster_service =
+        Arc::new(ClusterService::new(pool.clone(), Arc::clone(&mysql_pool_manager)));
+    let test_data = setup_multi_tenant_test_data(pool).await;
+
+    let mut cluster_ids = Vec::new();
+    for name in ["archive_a", "archive_b"] {
+        let cluster = cluster_service
+            .create_cluster(
+                CreateClusterRequest {
+                    name: name.to_string(),
+                    description: None,
+                    fe_host: format!("{}.example.com", name),
+                    fe_http_port: 8030,
+                    fe_query_port: 9030,
+                    username: "root".to_string(),
+                    password: "secret".to_string(),
+                    enable_ssl: false,
+                    connection_timeout: 30,
+                    tags: None,
+                    catalog: "default_catalog".to_string(),
+                    organization_id: Some(test_data.org1_id),
+                    deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                    fe_endpoints: None,
+                    tls: None,
+                },
+                test_data.super_admin_user_id,
+                None,
+                true,
+            )
+            .await
+            .unwrap();
+        cluster_ids.push(cluster.id);
+    }
+
+    let service =
+        ProfileArchiveService::new(pool.clone(), cluster_service, mysql_pool_manager, config);
+    (service, cluster_ids[0], cluster_ids[1])
+}
+
+#[tokio::test]
+async fn test_archive_profile_stores_summary_and_compressed_text() {
+    let pool = create_test_db().await;
+    let (service, cluster_id, _) = setup(&pool, ProfileArchiveConfig::default()).await;
+    let profile = load_profile("profile1.txt");
+
+    let item = service
+        .archive_profile(cluster_id, "query-1", &profile, ArchiveSource::Manual, None)
+        .await
+        .unwrap();
+
+    assert_eq!(item.cluster_id, cluster_id);
+    assert_eq!(item.query_id, "query-1");
+    assert_eq!(item.query_user.as_deref(), Some("explore_service"));
+    assert_eq!(item.query_state.as_deref(), Some("Finished"));
+    assert_eq!(item.query_start_time.as_deref(), Some("2025-10-15 15:37:06"));
+    // Total: 9m41s
+    assert_eq!(item.total_time_ms, Some(581_000.0));
+    assert!(item.performance_score.is_some());
+    assert!(item.analyzed_at.is_some());
+    assert_eq!(item.compression, "gzip");
+    assert_eq!(item.raw_size, profile.len() as i64);
+    assert_eq!(item.source, "manual");
+
+    let (stored_size,): (i64,) =
+        sqlx::query_as("SELECT LENGTH(profile_content) FROM profile_archives WHERE id = ?")
+            .bind(item.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(stored_size < item.raw_size);
+
+    let detail = service.get_archive(cluster_id, item.id).await.unwrap();
+    assert_eq!(detail.profile_content, profile);
+}
+
+#[tokio::test]
+async fn test_archive_unparseable_profile_without_summary() {
+    let pool = create_test_db().await;
+    let config = ProfileArchiveConfig { compress: false, ..Default::default() };
+    let (service, cluster_id, _) = setup(&pool, config).await;
+
+    let item = service
+        .archive_profile(cluster_id, "broken", "not a profile", ArchiveSource::Auto, None)
+        .await
+        .unwrap();
+
+    assert_eq!(item.compression, "none");
+    assert_eq!(item.source, "auto");
+    assert!(item.performance_score.is_none());
+    assert!(item.rule_ids.is_empty());
+    assert!(item.analyzed_at.is_none());
+
+    let detail = service.get_archive(cluster_id, item.id).await.unwrap();
+    assert_eq!(detail.profile_content, "not a profile");
+}
+
+#[tokio::test]
+async fn test_rearchive_replaces_existing_copy() {
+    let pool = create_test_db().await;
+    let (service, cluster_id, _) = setup(&pool, ProfileArchiveConfig::default()).await;
+
+    let first = service
+        .archive_profile(cluster_id, "query-1", "placeholder", ArchiveSource::Auto, None)
+        .await
+        .unwrap();
+    let second = service
+        .archive_profile(
+            cluster_id,
+            "query-1",
+            &load_profile("profile2.txt"),
+            ArchiveSource::Manual,
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(first.id, second.id);
+    assert_eq!(second.source, "manual");
+    assert!(second.performance_score.is_some());
+
+    let list = service
+        .list_archives(cluster_id, &ProfileArchiveQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(list.total, 1);
+}
+
+#[tokio::test]
+async fn test_list_archives_filters() {
+    let pool = create_test_db().await;
+    let (service, cluster_id, _) = setup(&pool, ProfileArchiveConfig::default()).await;
+
+    let mut items = Vec::new();
+    for (i, name) in ["profile1.txt", "profile2.txt", "profile3.txt"]
+        .iter()
+        .enumerate()
+    {
+        let item = service
+            .archive_profile(
+                cluster_id,
+                &format!("query-{}", i),
+                &load_profile(name),
+                ArchiveSource::Manual,
+                None,
+            )
+            .await
+            .unwrap();
+        items.push(item);
+    }
+
+    let all = service
+        .list_archives(cluster_id, &ProfileArchiveQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(all.total, 3);
+    assert_eq!(all.page, 1);
+    assert_eq!(all.page_size, 20);
+
+    // User filter
+    let by_user = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery {
+                user: Some("explore_service".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let expected = items
+        .iter()
+        .filter(|i| i.query_user.as_deref() == Some("explore_service"))
+        .count();
+    assert_eq!(by_user.total as usize, expected);
+    assert!(expected >= 1);
+
+    // Duration filter
+    let slow = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery { min_duration_ms: Some(581_000.0), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    assert!(
+        slow.items
+            .iter()
+            .all(|i| i.total_time_ms.unwrap() >= 581_000.0)
+    );
+    assert!(slow.items.iter().any(|i| i.query_id == "query-0"));
+
+    // Rule filter matches any archive whose diagnostics include the rule
+    let rule_id = items
+        .iter()
+        .flat_map(|i| i.rule_ids.iter())
+        .next()
+        .cloned()
+        .expect("fixture profiles should trigger at least one rule");
+    let by_rule = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery { rule_id: Some(rule_id.clone()), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    let expected = items
+        .iter()
+        .filter(|i| i.rule_ids.contains(&rule_id))
+        .count();
+    assert_eq!(by_rule.total as usize, expected);
+
+    let no_rule = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery { rule_id: Some("X999".to_string()), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    assert_eq!(no_rule.total, 0);
+
+    // Date range
+    let in_range = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery {
+                start_time: Some("2025-10-15 15:00:00".to_string()),
+                end_time: Some("2025-10-15 16:00:00".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(in_range.items.iter().any(|i| i.query_id == "query-0"));
+
+    // Pagination
+    let page = service
+        .list_archives(
+            cluster_id,
+            &ProfileArchiveQuery { page: Some(2), page_size: Some(2), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.total, 3);
+    assert_eq!(page.items.len(), 1);
+}
+
+#[tokio::test]
+async fn test_archives_are_scoped_by_cluster() {
+    let pool = create_test_db().await;
+    let (service, cluster_a, cluster_b) = setup(&pool, ProfileArchiveConfig::default()).await;
+
+    let item = service
+        .archive_profile(
+            cluster_a,
+            "query-1",
+            &load_profile("profile1.txt"),
+            ArchiveSource::Manual,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let other = service
+        .list_archives(cluster_b, &ProfileArchiveQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(other.total, 0);
+    assert!(service.get_archive(cluster_b, item.id).await.is_err());
+    assert!(service.delete_archive(cluster_b, item.id).await.is_err());
+    assert!(service.get_archive(cluster_a, item.id).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_reanalyze_refreshes_summary() {
+    let pool = create_test_db().await;
+    let (service, cluster_id, _) = setup(&pool, ProfileArchiveConfig::default()).await;
+
+    let item = service
+        .archive_profile(
+            cluster_id,
+            "query-1",
+            &load_profile("profile1.txt"),
+            ArchiveSource::Manual,
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Simulate a summary produced by an older rule set
+    sqlx::query(
+        "UPDATE profile_archives SET rule_ids = '[\"OLD1\"]', performance_score = 0, analyzed_at = NULL WHERE id = ?",
+    )
+    .bind(item.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let analysis = service.reanalyze(cluster_id, item.id).await.unwrap();
+
+    let detail = service.get_archive(cluster_id, item.id).await.unwrap();
+    assert_eq!(detail.summary.performance_score, Some(analysis.performance_score));
+    assert!(!detail.summary.rule_ids.contains(&"OLD1".to_string()));
+    // Rules with equal severity may come back in any order
+    let mut refreshed = detail.summary.rule_ids.clone();
+    let mut original = item.rule_ids.clone();
+    refreshed.sort();
+    original.sort();
+    assert_eq!(refreshed, original);
+    assert!(detail.summary.analyzed_at.is_some());
+}
+
+#[tokio::test]
+async fn test_delete_and_retention_cleanup() {
+    let pool = create_test_db().await;
+    let config = ProfileArchiveConfig { retention_days: 7, ..Default::default() };
+    let (service, cluster_id, _) = setup(&pool, config).await;
+
+    let mut ids = Vec::new();
+    for query_id in ["keep", "expire", "delete"] {
+        let item = service
+            .archive_profile(cluster_id, query_id, "placeholder", ArchiveSource::Auto, None)
+            .await
+            .unwrap();
+        ids.push(item.id);
+    }
+
+    service.delete_archive(cluster_id, ids[2]).await.unwrap();
+    assert!(service.delete_archive(cluster_id, ids[2]).await.is_err());
+
+    sqlx::query("UPDATE profile_archives SET archived_at = ? WHERE id = ?")
+        .bind(chrono::Utc::now() - chrono::Duration::days(8))
+        .bind(ids[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(service.cleanup_expired().await.unwrap(), 1);
+
+    let remaining = service
+        .list_archives(cluster_id, &ProfileArchiveQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(remaining.total, 1);
+    assert_eq!(remaining.items[0].query_id, "keep");
+}
+
+#[tokio::test]
+async fn test_profile_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let routes = [
+        ("POST", "/api/clusters/profiles/abc-123/archive", "profiles:archive"),
+        ("POST", "/api/clusters/profiles/diff", "profiles:diff"),
+        ("GET", "/api/clusters/profile-archives", "profile_archives:list"),
+        ("GET", "/api/clusters/profile-archives/7", "profile_archives:get"),
+        ("POST", "/api/clusters/profile-archives/7/analyze", "profile_archives:analyze"),
+        ("DELETE", "/api/clusters/profile-archives/7", "profile_archives:delete"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM permissions WHERE resource = ? AND action = ?")
+                .bind(&resource)
+                .bind(&extracted)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not seeded", resource, extracted);
+    }
+
+    // Fetching a live profile keeps its own permission
+    assert_eq!(
+        extract_permission("GET", "/api/clusters/profiles/abc-123").map(|(_, a)| a),
+        Some("profiles:get".to_string())
+    );
+}
@@ -226,7 +226,10 @@ async fn test_delete_role() {
     let permission_ids = data.permission_ids.clone();
 
     // Assign permissions
-    let req = UpdateRolePermissionsRequest { permission_ids: permission_ids[0..3].to_vec() };
+    let req = UpdateRolePermissionsRequest {
+        permission_ids: permission_ids[0..3].to_vec(),
+        scoped_permissions: None,
+    };
     let result = service
         .assign_permissions_to_role(operator_role_id, req, None, true)
         .await;
@@ -253,14 +256,20 @@ async fn test_assign_permissions_to_role_replace() {
     let permission_ids = data.permission_ids.clone();
 
     // First assignment
-    let req1 = UpdateRolePermissionsRequest { permission_ids: permission_ids[0..3].to_vec() };
+    let req1 = UpdateRolePermissionsRequest {
+        permission_ids: permission_ids[0..3].to_vec(),
+        scoped_permissions: None,
+    };
     service
         .assign_permissions_to_role(operator_role_id, req1, None, true)
         .await
         .unwrap();
 
     // Second assignment (should replace)
-    let req2 = UpdateRolePermissionsRequest { permission_ids: permission_ids[3..6].to_vec() };
+    let req2 = UpdateRolePermissionsRequest {
+        permission_ids: permission_ids[3..6].to_vec(),
+        scoped_permissions: None,
+    };
     let result = service
         .assign_permissions_to_role(operator_role_id, req2, None, true)
         .await;
@@ -291,7 +300,7 @@ async fn test_assign_permissions_to_role_empty() {
     let _data = setup_test_data(&pool).await;
     let operator_role_id = create_role(&pool, "ops", "Operator", "Operator role", false).await;
 
-    let req = UpdateRolePermissionsRequest { permission_ids: vec![] };
+    let req = UpdateRolePermissionsRequest { permission_ids: vec![], scoped_permissions: None };
 
     let result = service
         .assign_permissions_to_role(operator_role_id, req, None, true)
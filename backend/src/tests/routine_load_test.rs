@@ -0,0 +1,326 @@
+// Routine Load tests: SHOW ROUTINE LOAD parsing, lifecycle checks, lag history, alerting
+// and permissions
+
+use crate::middleware::cluster_scope::split_cluster_scoped_path;
+use crate::middleware::permission_extractor::{extract_permission, scope_level};
+use crate::models::{
+    AlertComparator, AlertSeverity, Cluster, CreateAlertRuleRequest, CreateClusterRequest,
+    PermissionScopeLevel, RoutineLoadAction, RoutineLoadJob, RoutineLoadPartition,
+};
+use crate::services::routine_load_service::parse_partitions;
+use crate::services::{MetricsSnapshot, RoutineLoadService, RoutineLoadSummary, TimeRange};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+async fn create_cluster(state: &crate::AppState, created_by: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "routine".to_string(),
+                description: None,
+                fe_host: "fe.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn job(state: &str, lags: &[(&str, Option<i64>)]) -> RoutineLoadJob {
+    let mut job = RoutineLoadService::parse_job(
+        &json!({ "Id": "1", "Name": "orders_kafka", "State": state }),
+        "sales",
+    );
+    job.partitions = lags
+        .iter()
+        .map(|(partition, lag)| RoutineLoadPartition {
+            partition: partition.to_string(),
+            consumed_offset: None,
+            latest_offset: None,
+            lag: *lag,
+        })
+        .collect();
+    let known: Vec<i64> = lags.iter().filter_map(|(_, lag)| *lag).collect();
+    job.total_lag = if known.is_empty() { None } else { Some(known.iter().sum()) };
+    job
+}
+
+#[test]
+fn test_parse_routine_load_job() {
+    let row = json!({
+        "Id": "10086",
+        "Name": "orders_kafka",
+        "CreateTime": "2025-02-01 10:00:00",
+        "PauseTime": "NULL",
+        "EndTime": "NULL",
+        "DbName": "default_cluster:sales",
+        "TableName": "orders",
+        "State": "PAUSED",
+        "DataSourceType": "KAFKA",
+        "CurrentTaskNum": "0",
+        "Statistic": "{\"receivedBytes\":2048,\"errorRows\":3,\"loadedRows\":\"997\",\"totalRows\":1000,\"unselectedRows\":0}",
+        "Progress": "{\"0\":\"99\",\"1\":\"OFFSET_BEGINNING\",\"10\":\"5\"}",
+        "LatestSourcePosition": "{\"0\":\"150\",\"1\":\"20\",\"10\":\"3\"}",
+        "ReasonOfStateChanged": "ErrorReason{errCode = 102, msg='too many filtered rows'}",
+        "ErrorLogUrls": "http://be1:8040/api/_load_error_log?file=x",
+        "OtherMsg": "",
+    });
+    let job = RoutineLoadService::parse_job(&row, "ignored");
+    assert_eq!(job.id, 10086);
+    assert_eq!(job.database_name, "sales");
+    assert_eq!(job.table_name, "orders");
+    assert_eq!(job.state, "PAUSED");
+    assert_eq!(job.pause_time, None);
+    assert_eq!((job.loaded_rows, job.error_rows, job.total_rows), (997, 3, 1000));
+    assert_eq!(job.received_bytes, 2048);
+    assert_eq!(job.other_msg, None);
+    assert!(
+        job.reason_of_state_changed
+            .unwrap()
+            .contains("too many filtered rows")
+    );
+
+    // Partitions sorted numerically; nothing consumed yet on partition 1, latest behind
+    // consumed is floored at zero
+    let partitions: Vec<(&str, Option<i64>)> = job
+        .partitions
+        .iter()
+        .map(|p| (p.partition.as_str(), p.lag))
+        .collect();
+    assert_eq!(partitions, vec![("0", Some(51)), ("1", None), ("10", Some(0))]);
+    assert_eq!(job.total_lag, Some(51));
+
+    // A row without a DbName keeps the database it was listed from
+    let job = RoutineLoadService::parse_job(&json!({ "Id": "1", "State": "running" }), "logs");
+    assert_eq!(job.database_name, "logs");
+    assert_eq!(job.state, "RUNNING");
+    assert_eq!(job.total_lag, None);
+}
+
+#[test]
+fn test_parse_partitions_prefers_reported_lag() {
+    let partitions = parse_partitions(
+        Some("{\"0\":\"99\",\"1\":\"10\"}"),
+        Some("{\"0\":\"150\",\"1\":\"20\"}"),
+        Some("{\"0\":49}"),
+    );
+    assert_eq!(
+        partitions,
+        vec![
+            RoutineLoadPartition {
+                partition: "0".to_string(),
+                consumed_offset: Some("99".to_string()),
+                latest_offset: Some("150".to_string()),
+                lag: Some(49),
+            },
+            RoutineLoadPartition {
+                partition: "1".to_string(),
+                consumed_offset: Some("10".to_string()),
+                latest_offset: Some("20".to_string()),
+                lag: Some(10),
+            },
+        ]
+    );
+    assert!(parse_partitions(None, Some("not json"), None).is_empty());
+}
+
+#[test]
+fn test_control_actions_follow_job_state() {
+    let cases = [
+        ("RUNNING", [true, false, true]),
+        ("NEED_SCHEDULE", [true, false, true]),
+        ("PAUSED", [false, true, true]),
+        ("STOPPED", [false, false, false]),
+        ("CANCELLED", [false, false, false]),
+    ];
+    let actions = [RoutineLoadAction::Pause, RoutineLoadAction::Resume, RoutineLoadAction::Stop];
+
+    for (state, allowed) in cases {
+        let job = job(state, &[]);
+        for (action, allowed) in actions.into_iter().zip(allowed) {
+            assert_eq!(
+                RoutineLoadService::check_action(&job, action).is_ok(),
+                allowed,
+                "{:?} on {}",
+                action,
+                state
+            );
+        }
+    }
+
+    assert_eq!(
+        RoutineLoadService::build_control_statement(
+            "sales",
+            "orders`kafka",
+            RoutineLoadAction::Resume
+        ),
+        "RESUME ROUTINE LOAD FOR `sales`.`orders``kafka`"
+    );
+}
+
+#[tokio::test]
+async fn test_lag_history_trends_and_cleanup() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.routine_load_service;
+
+    let now = Utc::now();
+    let ticks = [
+        (now - Duration::minutes(3), job("RUNNING", &[("0", Some(10)), ("1", Some(5))])),
+        (now - Duration::minutes(2), job("RUNNING", &[("0", Some(40)), ("1", None)])),
+        (now - Duration::minutes(1), job("PAUSED", &[("0", Some(90)), ("1", Some(30))])),
+    ];
+    for (at, job) in &ticks {
+        service
+            .save_lag(cluster.id, *at, std::slice::from_ref(job))
+            .await
+            .unwrap();
+    }
+
+    let trends = service
+        .get_lag_trends(cluster.id, "sales", "orders_kafka", &TimeRange::Hours1)
+        .await
+        .unwrap();
+    let values = |points: &[crate::services::overview_service::TimeSeriesPoint]| -> Vec<f64> {
+        points.iter().map(|p| p.value).collect()
+    };
+    assert_eq!(values(&trends.total_lag), vec![15.0, 40.0, 120.0]);
+    assert_eq!(trends.partitions.len(), 2);
+    assert_eq!(values(&trends.partitions[0].lag), vec![10.0, 40.0, 90.0]);
+    assert_eq!(values(&trends.partitions[1].lag), vec![5.0, 30.0]);
+    let states: Vec<&str> = trends
+        .state_changes
+        .iter()
+        .map(|c| c.state.as_str())
+        .collect();
+    assert_eq!(states, vec!["RUNNING", "PAUSED"]);
+
+    let other = service
+        .get_lag_trends(cluster.id, "sales", "other", &TimeRange::Hours1)
+        .await
+        .unwrap();
+    assert!(other.total_lag.is_empty());
+
+    let deleted = service
+        .cleanup_before(now - Duration::seconds(90))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+}
+
+#[tokio::test]
+async fn test_paused_jobs_and_lag_feed_alert_rules() {
+    let jobs = [
+        job("RUNNING", &[("0", Some(700))]),
+        job("PAUSED", &[("0", Some(20)), ("1", Some(30))]),
+        job("NEED_SCHEDULE", &[("0", None)]),
+    ];
+    assert_eq!(RoutineLoadSummary::of(&jobs), RoutineLoadSummary { paused: 1, max_lag: 700 });
+    assert_eq!(RoutineLoadSummary::of(&[]), RoutineLoadSummary::default());
+
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+
+    let metrics = state.alert_service.list_metrics();
+    assert!(metrics.iter().any(|m| m.name == "routine_load_paused"));
+    assert!(metrics.iter().any(|m| m.name == "routine_load_max_lag"));
+
+    let rule = state
+        .alert_service
+        .create_rule(
+            cluster.id,
+            CreateAlertRuleRequest {
+                name: "routine load paused".to_string(),
+                description: None,
+                metric: "routine_load_paused".to_string(),
+                comparator: AlertComparator::Gte,
+                threshold: 1.0,
+                duration_secs: 0,
+                severity: AlertSeverity::Warning,
+                enabled: true,
+                channel_ids: vec![],
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let snapshot = MetricsSnapshot {
+        cluster_id: cluster.id,
+        collected_at: Utc::now(),
+        routine_load_paused: 1,
+        routine_load_max_lag: 700,
+        ..Default::default()
+    };
+    let events = state
+        .alert_service
+        .evaluate(&cluster, &snapshot)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].rule_id, rule.id);
+    assert_eq!(events[0].value, 1.0);
+}
+
+#[tokio::test]
+async fn test_routine_load_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+    let routes = [
+        ("GET", "/api/clusters/routine-loads", "routine_loads"),
+        ("GET", "/api/clusters/routine-loads/sales/orders_kafka", "routine_loads:get"),
+        ("GET", "/api/clusters/routine-loads/sales/orders_kafka/tasks", "routine_loads:tasks"),
+        ("GET", "/api/clusters/routine-loads/sales/orders_kafka/lag", "routine_loads:lag"),
+        ("POST", "/api/clusters/routine-loads/sales/orders_kafka/pause", "routine_loads:pause"),
+        ("POST", "/api/clusters/routine-loads/sales/orders_kafka/resume", "routine_loads:resume"),
+        ("POST", "/api/clusters/routine-loads/sales/orders_kafka/stop", "routine_loads:stop"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+        assert_eq!(scope_level(&resource, &extracted), Some(PermissionScopeLevel::Database));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN roles r ON r.id = rp.role_id
+             WHERE p.resource = ? AND p.action = ? AND r.code = 'admin'",
+        )
+        .bind(&resource)
+        .bind(&extracted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not granted to admin", resource, extracted);
+    }
+
+    assert!(
+        extract_permission("POST", "/api/clusters/routine-loads/sales/orders_kafka/drop")
+            .is_none_or(|(_, action)| !action.starts_with("routine_loads"))
+    );
+    assert_eq!(
+        split_cluster_scoped_path("/api/clusters/3/routine-loads/sales/orders_kafka/pause"),
+        Some((3, "/api/clusters/routine-loads/sales/orders_kafka/pause".to_string()))
+    );
+}
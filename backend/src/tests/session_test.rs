@@ -0,0 +1,387 @@
+// Login session tests: refresh token rotation, logout and server-side revocation
+
+use crate::AppState;
+use crate::handlers;
+use crate::middleware::{AuthState, auth_middleware, permission_extractor::extract_permission};
+use crate::models::{CreateUserRequest, LoginRequest};
+use crate::services::SessionClient;
+use crate::tests::common::{
+    create_test_app_state, create_test_db, create_test_session_token, setup_multi_tenant_test_data,
+};
+use crate::utils::ApiError;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{StatusCode, header};
+use axum::routing::{delete, get, post, put};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const PASSWORD: &str = "initial-password";
+
+async fn register(state: &AppState, username: &str) -> i64 {
+    state
+        .auth_service
+        .register(CreateUserRequest {
+            username: username.to_string(),
+            password: PASSWORD.to_string(),
+            email: None,
+            avatar: None,
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+fn login_request(username: &str, password: &str) -> LoginRequest {
+    LoginRequest { username: username.to_string(), password: password.to_string(), totp_code: None }
+}
+
+/// The session routes of the application, behind the authentication middleware
+fn app(state: &Arc<AppState>) -> Router {
+    let auth_state = AuthState {
+        jwt_util: Arc::clone(&state.jwt_util),
+        casbin_service: Arc::clone(&state.casbin_service),
+        api_token_service: Arc::clone(&state.api_token_service),
+        session_service: Arc::clone(&state.session_service),
+        db: state.db.clone(),
+    };
+    let protected = Router::new()
+        .route("/api/auth/me", put(handlers::auth::update_me))
+        .route("/api/auth/logout", post(handlers::auth::logout))
+        .route(
+            "/api/auth/sessions",
+            get(handlers::auth::list_sessions).delete(handlers::auth::revoke_other_sessions),
+        )
+        .route("/api/users/:id", put(handlers::user::update_user))
+        .route(
+            "/api/users/:id/sessions",
+            get(handlers::user::list_user_sessions).delete(handlers::user::revoke_user_sessions),
+        )
+        .route("/api/users/:id/sessions/:session_id", delete(handlers::user::revoke_user_session))
+        .route_layer(axum::middleware::from_fn_with_state(auth_state, auth_middleware));
+    Router::new()
+        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .merge(protected)
+        .with_state(Arc::clone(state))
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, "session-test");
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+    let response = app
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn login(app: &Router, username: &str, password: &str) -> (String, String) {
+    let (status, body) = call(
+        app,
+        "POST",
+        "/api/auth/login",
+        None,
+        Some(json!({ "username": username, "password": password })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    (
+        body["token"].as_str().unwrap().to_string(),
+        body["refresh_token"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_refresh_tokens_rotate_and_detect_reuse() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "rotating").await;
+    let sessions = &state.session_service;
+
+    let client = SessionClient { user_agent: Some("cli".to_string()), ip_address: None };
+    let (_, first) = state
+        .auth_service
+        .login(login_request("rotating", PASSWORD), &client)
+        .await
+        .unwrap();
+    assert_eq!(first.expires_in, state.jwt_util.expires_in_secs());
+    let claims = state.jwt_util.verify_token(&first.token).unwrap();
+    let session_id = claims.sid.unwrap();
+    sessions
+        .validate_session(session_id, user_id)
+        .await
+        .unwrap();
+
+    // Every refresh hands out a new refresh token for the same session
+    let second = sessions.refresh(&first.refresh_token).await.unwrap();
+    assert_ne!(second.refresh_token, first.refresh_token);
+    let claims = state.jwt_util.verify_token(&second.token).unwrap();
+    assert_eq!(claims.sid, Some(session_id));
+
+    // Presenting the used-up token again ends the session for both holders
+    let err = sessions.refresh(&first.refresh_token).await.unwrap_err();
+    assert!(matches!(err, ApiError::TokenExpired), "{:?}", err);
+    assert!(sessions.refresh(&second.refresh_token).await.is_err());
+    assert!(
+        sessions
+            .validate_session(session_id, user_id)
+            .await
+            .is_err()
+    );
+    let (reason,): (String,) =
+        sqlx::query_as("SELECT revoke_reason FROM user_sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(reason, "refresh_token_reused");
+
+    // Expired sessions cannot be refreshed
+    let (_, tokens) = state
+        .auth_service
+        .login(login_request("rotating", PASSWORD), &client)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE user_sessions SET expires_at = ? WHERE user_id = ?")
+        .bind(Utc::now() - Duration::seconds(1))
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(sessions.refresh(&tokens.refresh_token).await.is_err());
+    assert!(sessions.refresh("unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn test_logout_and_own_session_management() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    register(&state, "traveller").await;
+    let app = app(&state);
+
+    let (laptop, _) = login(&app, "traveller", PASSWORD).await;
+    let (phone, phone_refresh) = login(&app, "traveller", PASSWORD).await;
+
+    let (status, body) = call(&app, "GET", "/api/auth/sessions", Some(&laptop), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = body.as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(
+        listed
+            .iter()
+            .filter(|s| s["current"] == json!(true))
+            .count(),
+        1
+    );
+    assert_eq!(listed[0]["user_agent"], "session-test");
+
+    // Logging out everywhere else keeps the calling session
+    let (status, body) = call(&app, "DELETE", "/api/auth/sessions", Some(&laptop), None).await;
+    assert_eq!((status, body["revoked"].as_u64()), (StatusCode::OK, Some(1)));
+    let (status, _) = call(&app, "GET", "/api/auth/sessions", Some(&phone), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/auth/refresh",
+        None,
+        Some(json!({ "refresh_token": phone_refresh })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Logout ends the access token before its expiry
+    let (status, _) = call(&app, "POST", "/api/auth/logout", Some(&laptop), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&app, "GET", "/api/auth/sessions", Some(&laptop), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Tokens without a session (issued before sessions existed) are refused
+    let legacy = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &json!({ "sub": "1", "username": "traveller", "exp": Utc::now().timestamp() + 600,
+                 "iat": Utc::now().timestamp() }),
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+    )
+    .unwrap();
+    let (status, _) = call(&app, "GET", "/api/auth/sessions", Some(&legacy), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_password_change_and_disable_end_sessions() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    sqlx::query(
+        "INSERT INTO permissions (code, name, type, resource, action) VALUES
+         ('api:users:sessions:list', 'List Sessions', 'api', 'users', 'sessions:list'),
+         ('api:users:sessions:revoke', 'Revoke Sessions', 'api', 'users', 'sessions:revoke')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO role_permissions (role_id, permission_id)
+         SELECT ?, id FROM permissions WHERE code LIKE 'api:users:sessions:%'",
+    )
+    .bind(data.super_admin_role_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let state = create_test_app_state(&pool).await;
+    let user_id = register(&state, "managed").await;
+    let app = app(&state);
+    let admin =
+        create_test_session_token(&pool, &state.jwt_util, data.super_admin_user_id, "super").await;
+
+    // Changing one's own password ends the other sessions only
+    let (first, _) = login(&app, "managed", PASSWORD).await;
+    let (second, _) = login(&app, "managed", PASSWORD).await;
+    let (status, body) = call(
+        &app,
+        "PUT",
+        "/api/auth/me",
+        Some(&first),
+        Some(json!({ "current_password": PASSWORD, "new_password": "second-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        call(&app, "GET", "/api/auth/sessions", Some(&second), None)
+            .await
+            .0,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        call(&app, "GET", "/api/auth/sessions", Some(&first), None)
+            .await
+            .0,
+        StatusCode::OK
+    );
+
+    // An administrator sees and revokes the user's sessions
+    let sessions_uri = format!("/api/users/{}/sessions", user_id);
+    let (status, body) = call(&app, "GET", &sessions_uri, Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let listed = body.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["current"], json!(false));
+    let session_id = listed[0]["id"].as_i64().unwrap();
+    let (status, _) =
+        call(&app, "DELETE", &format!("{}/{}", sessions_uri, session_id), Some(&admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        call(&app, "GET", "/api/auth/sessions", Some(&first), None)
+            .await
+            .0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // A password reset by an administrator ends every session
+    let (token, _) = login(&app, "managed", "second-password").await;
+    let user_uri = format!("/api/users/{}", user_id);
+    let (status, body) =
+        call(&app, "PUT", &user_uri, Some(&admin), Some(json!({ "password": "third-password" })))
+            .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        call(&app, "GET", "/api/auth/sessions", Some(&token), None)
+            .await
+            .0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Disabled users lose their sessions and cannot log in again
+    let (token, refresh_token) = login(&app, "managed", "third-password").await;
+    let (status, body) =
+        call(&app, "PUT", &user_uri, Some(&admin), Some(json!({ "is_active": false }))).await;
+    assert_eq!((status, &body["is_active"]), (StatusCode::OK, &json!(false)), "{}", body);
+    assert_eq!(
+        call(&app, "GET", "/api/auth/sessions", Some(&token), None)
+            .await
+            .0,
+        StatusCode::UNAUTHORIZED
+    );
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/auth/refresh",
+        None,
+        Some(json!({ "refresh_token": refresh_token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/auth/login",
+        None,
+        Some(json!({ "username": "managed", "password": "third-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Administrators cannot lock themselves out
+    let (status, _) = call(
+        &app,
+        "PUT",
+        &format!("/api/users/{}", data.super_admin_user_id),
+        Some(&admin),
+        Some(json!({ "is_active": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_user_session_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+
+    for (method, uri, action) in [
+        ("GET", "/api/users/3/sessions", "sessions:list"),
+        ("DELETE", "/api/users/3/sessions", "sessions:revoke"),
+        ("DELETE", "/api/users/3/sessions/9", "sessions:revoke"),
+    ] {
+        let (resource, extracted) = extract_permission(method, uri).unwrap();
+        assert_eq!((resource.as_str(), extracted.as_str()), ("users", action));
+
+        let code = format!("api:users:{}", action);
+        let granted: Vec<(String,)> = sqlx::query_as(
+            "SELECT r.code FROM roles r
+             JOIN role_permissions rp ON rp.role_id = r.id
+             JOIN permissions p ON p.id = rp.permission_id
+             WHERE p.code = ? ORDER BY r.code",
+        )
+        .bind(&code)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert!(granted.iter().any(|(role,)| role == "admin"), "{} not granted", code);
+    }
+
+    // A user's own sessions need no permission
+    assert!(extract_permission("GET", "/api/auth/sessions").is_none());
+    assert!(extract_permission("POST", "/api/auth/logout").is_none());
+}
@@ -0,0 +1,333 @@
+// SQL policy service tests
+
+use crate::models::{
+    EffectiveSqlPolicy, SqlPolicyRule, SqlStatementClass, UpdateRoleSqlPolicyRequest,
+};
+use crate::services::SqlPolicyService;
+use crate::services::sql_policy_service::{check_statements, effective_row_limit};
+use crate::tests::common::{assign_role_to_user, create_role, create_test_db, create_test_user};
+
+fn policy_request(
+    statements: &[SqlStatementClass],
+    catalogs: &[&str],
+    databases: &[&str],
+    max_rows: Option<i64>,
+    max_execution_secs: Option<i64>,
+) -> UpdateRoleSqlPolicyRequest {
+    UpdateRoleSqlPolicyRequest {
+        allowed_statements: statements.to_vec(),
+        allowed_catalogs: catalogs.iter().map(|c| c.to_string()).collect(),
+        allowed_databases: databases.iter().map(|d| d.to_string()).collect(),
+        max_rows,
+        max_execution_secs,
+    }
+}
+
+fn rule(
+    statements: &[SqlStatementClass],
+    catalogs: Option<&[&str]>,
+    databases: Option<&[&str]>,
+) -> SqlPolicyRule {
+    let to_vec = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+    SqlPolicyRule {
+        role_id: 1,
+        allowed_statements: statements.to_vec(),
+        allowed_catalogs: catalogs.map(to_vec),
+        allowed_databases: databases.map(to_vec),
+        max_execution_secs: None,
+    }
+}
+
+fn policy_of(rules: Vec<SqlPolicyRule>) -> EffectiveSqlPolicy {
+    EffectiveSqlPolicy { restricted: true, rules, max_rows: Some(100), max_execution_secs: None }
+}
+
+fn restricted(catalogs: Option<&[&str]>, databases: Option<&[&str]>) -> EffectiveSqlPolicy {
+    policy_of(vec![rule(&[SqlStatementClass::Query], catalogs, databases)])
+}
+
+fn statements(sqls: &[&str]) -> Vec<String> {
+    sqls.iter().map(|s| s.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_role_policy_crud() {
+    let pool = create_test_db().await;
+    let service = SqlPolicyService::new(pool.clone());
+    let role_id = create_role(&pool, "analyst", "Analyst", "", false).await;
+
+    assert!(service.get_role_policy(role_id).await.unwrap().is_none());
+
+    let saved = service
+        .set_role_policy(
+            role_id,
+            policy_request(
+                &[SqlStatementClass::Dml, SqlStatementClass::Query, SqlStatementClass::Query],
+                &[" default_catalog ", "default_catalog"],
+                &["sales"],
+                Some(500),
+                Some(30),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(saved.allowed_statements, vec![SqlStatementClass::Query, SqlStatementClass::Dml]);
+    assert_eq!(saved.allowed_catalogs, vec!["default_catalog"]);
+    assert_eq!(saved.max_rows, Some(500));
+
+    // Upsert replaces the previous policy
+    let updated = service
+        .set_role_policy(
+            role_id,
+            policy_request(&[SqlStatementClass::Query], &[], &[], None, None),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(updated.allowed_databases.is_empty());
+    assert_eq!(updated.max_execution_secs, None);
+
+    service.delete_role_policy(role_id).await.unwrap();
+    assert!(service.get_role_policy(role_id).await.unwrap().is_none());
+    assert!(service.delete_role_policy(role_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_role_policy_validation() {
+    let pool = create_test_db().await;
+    let service = SqlPolicyService::new(pool.clone());
+    let role_id = create_role(&pool, "analyst", "Analyst", "", false).await;
+
+    let invalid = [
+        policy_request(&[SqlStatementClass::Query], &[], &[], Some(0), None),
+        policy_request(&[SqlStatementClass::Query], &[], &[], None, Some(-1)),
+        policy_request(&[SqlStatementClass::Query], &["  "], &[], None, None),
+        policy_request(&[SqlStatementClass::Query], &[], &[""], None, None),
+    ];
+    for req in invalid {
+        assert!(service.set_role_policy(role_id, req, None).await.is_err());
+    }
+    assert!(service.get_role_policy(role_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_effective_policy_merges_roles() {
+    let pool = create_test_db().await;
+    let service = SqlPolicyService::new(pool.clone());
+    let user_id = create_test_user(&pool, "analyst_user").await;
+    let reader = create_role(&pool, "reader", "Reader", "", false).await;
+    let writer = create_role(&pool, "writer", "Writer", "", false).await;
+
+    // No roles at all: unrestricted
+    assert!(!service.effective_policy(user_id).await.unwrap().restricted);
+
+    service
+        .set_role_policy(
+            reader,
+            policy_request(&[SqlStatementClass::Query], &[], &["sales"], Some(100), Some(10)),
+            None,
+        )
+        .await
+        .unwrap();
+    service
+        .set_role_policy(
+            writer,
+            policy_request(
+                &[SqlStatementClass::Dml],
+                &["default_catalog"],
+                &["sales", "ops"],
+                Some(1000),
+                None,
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assign_role_to_user(&pool, user_id, reader).await;
+    assign_role_to_user(&pool, user_id, writer).await;
+
+    let policy = service.effective_policy(user_id).await.unwrap();
+    assert!(policy.restricted);
+    let rules: Vec<_> = policy
+        .rules
+        .iter()
+        .map(|r| (r.role_id, r.allowed_catalogs.clone(), r.allowed_databases.clone()))
+        .collect();
+    // An empty allow-list means any
+    assert_eq!(
+        rules,
+        vec![
+            (reader, None, Some(vec!["sales".to_string()])),
+            (
+                writer,
+                Some(vec!["default_catalog".to_string()]),
+                Some(vec!["sales".to_string(), "ops".to_string()])
+            ),
+        ]
+    );
+    assert_eq!(policy.max_rows, Some(1000));
+    // writer has no time limit
+    assert_eq!(policy.max_execution_secs, None);
+
+    // A role without a policy lifts every restriction
+    let plain = create_role(&pool, "plain", "Plain", "", false).await;
+    assign_role_to_user(&pool, user_id, plain).await;
+    assert!(!service.effective_policy(user_id).await.unwrap().restricted);
+}
+
+#[test]
+fn test_check_statement_classes() {
+    let policy = restricted(None, None);
+
+    assert!(
+        check_statements(&policy, &statements(&["SELECT 1", "SHOW DATABASES"]), None, None).is_ok()
+    );
+    assert!(
+        check_statements(&policy, &statements(&["EXPLAIN SELECT 1", "DESC t"]), None, None).is_ok()
+    );
+    assert!(
+        check_statements(&policy, &statements(&["SELECT 1", "DELETE FROM t"]), None, None).is_err()
+    );
+    assert!(check_statements(&policy, &statements(&["DROP TABLE t"]), None, None).is_err());
+    assert!(
+        check_statements(&policy, &statements(&["SET query_timeout = 1000"]), None, None).is_err()
+    );
+    assert!(check_statements(&policy, &statements(&["KILL 42"]), None, None).is_err());
+
+    let dml =
+        policy_of(vec![rule(&[SqlStatementClass::Query, SqlStatementClass::Dml], None, None)]);
+    assert!(check_statements(&dml, &statements(&["INSERT INTO t VALUES (1)"]), None, None).is_ok());
+    assert!(check_statements(&dml, &statements(&["TRUNCATE TABLE t"]), None, None).is_err());
+
+    // Exports are not queries
+    let export = "SELECT * FROM t INTO OUTFILE 's3://bucket/t_' FORMAT AS CSV";
+    assert!(check_statements(&dml, &statements(&[export]), None, None).is_err());
+
+    // Unrestricted policies allow everything
+    let open = EffectiveSqlPolicy::default();
+    assert!(check_statements(&open, &statements(&["DROP DATABASE sales"]), None, None).is_ok());
+}
+
+#[test]
+fn test_execution_time_limit_cannot_be_raised() {
+    let limited_rule = SqlPolicyRule {
+        max_execution_secs: Some(30),
+        ..rule(&[SqlStatementClass::Query, SqlStatementClass::Admin], None, None)
+    };
+    let limited = policy_of(vec![limited_rule.clone()]);
+    let check = |sql: &str| check_statements(&limited, &statements(&[sql]), None, None);
+
+    assert!(check("SELECT /*+ SET_VAR(query_timeout = 86400) */ * FROM t").is_err());
+    assert!(check("SELECT /*+ SET_VAR(insert_timeout=1) */ 1").is_err());
+    assert!(check("SET query_timeout = 86400").is_err());
+    assert!(check("SELECT /*+ SET_VAR(pipeline_dop = 1) */ * FROM t").is_ok());
+    assert!(check("SET pipeline_dop = 1").is_ok());
+    assert!(check("SELECT 'query_timeout'").is_ok());
+
+    // Without a limit the hint is the user's business
+    let unlimited = policy_of(vec![SqlPolicyRule { max_execution_secs: None, ..limited_rule }]);
+    let hinted = statements(&["SELECT /*+ SET_VAR(query_timeout = 86400) */ * FROM t"]);
+    assert!(check_statements(&unlimited, &hinted, None, None).is_ok());
+}
+
+#[test]
+fn test_check_catalogs_and_databases() {
+    let policy = restricted(Some(&["default_catalog"]), Some(&["sales"]));
+    let check = |sql: &str, catalog: Option<&str>, database: Option<&str>| {
+        check_statements(&policy, &statements(&[sql]), catalog, database).is_ok()
+    };
+
+    // Request context
+    assert!(check("SELECT 1", None, Some("sales")));
+    assert!(check("SELECT 1", Some("default_catalog"), None));
+    assert!(!check("SELECT 1", Some("hive_catalog"), None));
+    assert!(!check("SELECT 1", None, Some("hr")));
+
+    // Context switches
+    assert!(check("USE sales", None, None));
+    assert!(!check("USE hr", None, None));
+    assert!(!check("USE hive_catalog.sales", None, None));
+    assert!(!check("SET CATALOG hive_catalog", None, None));
+    assert!(check("SET CATALOG default_catalog", None, None));
+
+    // Qualified names
+    assert!(check("SELECT * FROM sales.orders", None, None));
+    assert!(!check("SELECT * FROM hr.salaries", None, Some("sales")));
+    assert!(!check("SELECT * FROM sales.orders o JOIN hr.salaries s ON o.id = s.id", None, None));
+    assert!(!check("SELECT * FROM sales.orders, hr.salaries", None, None));
+    assert!(!check("SELECT * FROM hive_catalog.sales.orders", None, None));
+    assert!(check("SELECT * FROM `default_catalog`.`sales`.`orders`", None, None));
+
+    // SHOW containers
+    assert!(check("SHOW TABLES FROM sales", None, None));
+    assert!(!check("SHOW TABLES FROM hr", None, None));
+    assert!(!check("SHOW DATABASES FROM hive_catalog", None, None));
+}
+
+#[test]
+fn test_each_statement_needs_a_single_role() {
+    // Role A reads db1, role B changes the schema of db2
+    let policy = policy_of(vec![
+        rule(&[SqlStatementClass::Query], None, Some(&["db1"])),
+        SqlPolicyRule { role_id: 2, ..rule(&[SqlStatementClass::Ddl], None, Some(&["db2"])) },
+    ]);
+    let check = |sqls: &[&str], database: Option<&str>| {
+        check_statements(&policy, &statements(sqls), None, database).is_ok()
+    };
+
+    assert!(check(&["SELECT * FROM db1.t"], None));
+    assert!(check(&["DROP TABLE db2.t"], None));
+    assert!(check(&["SELECT * FROM t"], Some("db1")));
+    assert!(check(&["CREATE TABLE t (id INT)"], Some("db2")));
+
+    // DDL of B never applies to the databases of A
+    assert!(!check(&["DROP TABLE db1.t"], None));
+    assert!(!check(&["DROP TABLE t"], Some("db1")));
+    assert!(!check(&["USE db1", "TRUNCATE TABLE t"], None));
+    assert!(!check(&["SELECT * FROM db1.t JOIN db2.u ON t.id = u.id"], None));
+    assert!(!check(&["SELECT * FROM db2.t"], None));
+
+    // Tables after derived tables are checked too
+    assert!(!check(&["SELECT * FROM (SELECT 1) x, db3.secret"], Some("db1")));
+    assert!(!check(&["SELECT * FROM db1.t JOIN (SELECT 1) s ON 1=1, db3.secret"], None));
+}
+
+#[test]
+fn test_effective_row_limit() {
+    let open = EffectiveSqlPolicy::default();
+    assert_eq!(effective_row_limit(&open, None), 1000);
+    assert_eq!(effective_row_limit(&open, Some(5000)), 5000);
+
+    let capped = restricted(None, None);
+    assert_eq!(effective_row_limit(&capped, None), 100);
+    assert_eq!(effective_row_limit(&capped, Some(10)), 10);
+    assert_eq!(effective_row_limit(&capped, Some(5000)), 100);
+}
+
+#[tokio::test]
+async fn test_sql_policy_routes_map_to_seeded_permissions() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let routes = [
+        ("GET", "/api/roles/3/sql-policy", "sql_policy:get"),
+        ("PUT", "/api/roles/3/sql-policy", "sql_policy:update"),
+        ("DELETE", "/api/roles/3/sql-policy", "sql_policy:delete"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri)
+            .unwrap_or_else(|| panic!("No permission for {} {}", method, uri));
+        assert_eq!((resource.as_str(), extracted.as_str()), ("roles", action));
+
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM permissions WHERE resource = ? AND action = ?")
+                .bind(&resource)
+                .bind(&extracted)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not seeded", resource, extracted);
+    }
+}
@@ -0,0 +1,678 @@
+// Single sign-on tests: LDAP login against a stand-in directory, OIDC login against a local
+// mock identity provider, just-in-time provisioning and group mappings
+
+use crate::config::{OidcConfig, SsoConfig};
+use crate::handlers;
+use crate::middleware::permission_extractor::extract_permission;
+use crate::models::{AuthProvider, CreateSsoGroupMappingRequest, CreateUserRequest, LoginRequest};
+use crate::services::sso_service::{DirectoryUser, LdapDirectory};
+use crate::services::{
+    AuthService, CasbinService, SessionClient, SessionService, SsoService, TwoFactorService,
+};
+use crate::tests::common::{
+    MultiTenantTestData, create_test_app_state, create_test_casbin_service, create_test_db,
+    setup_multi_tenant_test_data,
+};
+use crate::utils::{ApiError, ApiResult, CredentialCipher, JwtUtil};
+use axum::body::Body;
+use axum::extract::{Form, Request};
+use axum::http::{StatusCode, header};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{EncodingKey, Header};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+const DBA_GROUP: &str = "cn=dba,ou=groups,dc=example,dc=com";
+const OPS_GROUP: &str = "cn=ops,ou=groups,dc=example,dc=com";
+
+/// Directory stand-in: login name -> (password, groups)
+#[derive(Default)]
+struct MockDirectory {
+    users: Mutex<HashMap<String, (String, Vec<String>)>>,
+}
+
+impl MockDirectory {
+    fn set_user(&self, username: &str, password: &str, groups: &[&str]) {
+        self.users.lock().unwrap().insert(
+            username.to_string(),
+            (password.to_string(), groups.iter().map(|group| group.to_string()).collect()),
+        );
+    }
+}
+
+impl LdapDirectory for MockDirectory {
+    fn authenticate<'a>(
+        &'a self,
+        username: &'a str,
+        password: &'a str,
+    ) -> Pin<Box<dyn Future<Output = ApiResult<Option<DirectoryUser>>> + Send + 'a>> {
+        let user = self
+            .users
+            .lock()
+            .unwrap()
+            .get(username)
+            .filter(|(expected, _)| expected == password)
+            .map(|(_, groups)| DirectoryUser {
+                dn: format!("uid={},ou=people,dc=example,dc=com", username),
+                username: username.to_string(),
+                email: Some(format!("{}@example.com", username)),
+                groups: groups.clone(),
+            });
+        Box::pin(async move { Ok(user) })
+    }
+}
+
+struct Fixture {
+    pool: SqlitePool,
+    data: MultiTenantTestData,
+    casbin: Arc<CasbinService>,
+    session_service: Arc<SessionService>,
+}
+
+async fn fixture() -> Fixture {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let casbin = create_test_casbin_service().await;
+    casbin.reload_policies_from_db(&pool).await.unwrap();
+    let jwt_util = Arc::new(JwtUtil::new("test-secret", "15m"));
+    let session_service = Arc::new(SessionService::new(pool.clone(), jwt_util, "7d"));
+    Fixture { pool, data, casbin, session_service }
+}
+
+impl Fixture {
+    fn sso(&self, config: &SsoConfig) -> SsoService {
+        SsoService::new(
+            self.pool.clone(),
+            config,
+            Arc::clone(&self.casbin),
+            Arc::clone(&self.session_service),
+        )
+    }
+
+    fn ldap_sso(&self, config: &SsoConfig) -> (SsoService, Arc<MockDirectory>) {
+        let directory = Arc::new(MockDirectory::default());
+        let sso = self
+            .sso(config)
+            .with_directory(Arc::clone(&directory) as Arc<dyn LdapDirectory>);
+        (sso, directory)
+    }
+
+    async fn map(
+        &self,
+        sso: &SsoService,
+        provider: AuthProvider,
+        group: &str,
+        organization_id: i64,
+        role_id: Option<i64>,
+    ) {
+        sso.create_mapping(CreateSsoGroupMappingRequest {
+            provider,
+            group_name: group.to_string(),
+            organization_id,
+            role_id,
+        })
+        .await
+        .unwrap();
+    }
+
+    async fn role_ids(&self, user_id: i64) -> Vec<i64> {
+        sqlx::query_scalar("SELECT role_id FROM user_roles WHERE user_id = ? ORDER BY role_id")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .unwrap()
+    }
+
+    async fn organization_of(&self, user_id: i64) -> i64 {
+        sqlx::query_scalar("SELECT organization_id FROM user_organizations WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+            .unwrap()
+    }
+}
+
+fn login_request(username: &str, password: &str) -> LoginRequest {
+    LoginRequest { username: username.to_string(), password: password.to_string(), totp_code: None }
+}
+
+#[tokio::test]
+async fn test_ldap_login_provisions_user_from_group_mappings() {
+    let f = fixture().await;
+    let (sso, directory) = f.ldap_sso(&SsoConfig::default());
+    f.map(&sso, AuthProvider::Ldap, "dba", f.data.org1_id, Some(f.data.regular_role_id))
+        .await;
+    directory.set_user("alice", "directory-password", &[DBA_GROUP]);
+
+    let client = SessionClient::default();
+    let (user, tokens) = sso
+        .ldap_login(&login_request("alice", "directory-password"), &client)
+        .await
+        .unwrap();
+    assert!(!tokens.refresh_token.is_empty());
+    assert_eq!(user.auth_provider, "ldap");
+    assert_eq!(user.email.as_deref(), Some("alice@example.com"));
+    assert_eq!(user.organization_id, Some(f.data.org1_id));
+    assert_eq!(f.organization_of(user.id).await, f.data.org1_id);
+    assert_eq!(f.role_ids(user.id).await, vec![f.data.regular_role_id]);
+    let org1_users = format!("org:{}:users", f.data.org1_id);
+    assert!(f.casbin.enforce(user.id, &org1_users, "get").await.unwrap());
+
+    // Later logins reuse the provisioned user
+    let (again, _) = sso
+        .ldap_login(&login_request("alice", "directory-password"), &client)
+        .await
+        .unwrap();
+    assert_eq!(again.id, user.id);
+
+    let err = sso
+        .ldap_login(&login_request("alice", "wrong"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidCredentials));
+}
+
+#[tokio::test]
+async fn test_ldap_login_syncs_roles_and_organization_on_every_login() {
+    let f = fixture().await;
+    let (sso, directory) = f.ldap_sso(&SsoConfig::default());
+    f.map(&sso, AuthProvider::Ldap, DBA_GROUP, f.data.org1_id, Some(f.data.regular_role_id))
+        .await;
+    // Only places members in org2
+    f.map(&sso, AuthProvider::Ldap, "OPS", f.data.org2_id, None)
+        .await;
+    // Ignored for LDAP users
+    f.map(&sso, AuthProvider::Oidc, "ops", f.data.org1_id, Some(f.data.super_admin_role_id))
+        .await;
+    let client = SessionClient::default();
+
+    directory.set_user("bob", "pw", &[DBA_GROUP]);
+    let (user, _) = sso
+        .ldap_login(&login_request("bob", "pw"), &client)
+        .await
+        .unwrap();
+    assert_eq!(f.role_ids(user.id).await, vec![f.data.regular_role_id]);
+
+    directory.set_user("bob", "pw", &[OPS_GROUP]);
+    let (user, _) = sso
+        .ldap_login(&login_request("bob", "pw"), &client)
+        .await
+        .unwrap();
+    assert_eq!(f.organization_of(user.id).await, f.data.org2_id);
+    assert!(f.role_ids(user.id).await.is_empty());
+    let org1_users = format!("org:{}:users", f.data.org1_id);
+    assert!(!f.casbin.enforce(user.id, &org1_users, "get").await.unwrap());
+
+    // Without any matching group the login is refused
+    directory.set_user("bob", "pw", &["cn=interns,ou=groups,dc=example,dc=com"]);
+    let err = sso
+        .ldap_login(&login_request("bob", "pw"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)));
+}
+
+#[tokio::test]
+async fn test_unmapped_users_join_default_organization_when_allowed() {
+    let f = fixture().await;
+    sqlx::query(
+        "INSERT INTO organizations (code, name, is_system) VALUES ('default_org', 'Default', 1)",
+    )
+    .execute(&f.pool)
+    .await
+    .unwrap();
+    let default_org: i64 =
+        sqlx::query_scalar("SELECT id FROM organizations WHERE code = 'default_org'")
+            .fetch_one(&f.pool)
+            .await
+            .unwrap();
+
+    let config = SsoConfig { allow_unmapped_users: true, ..Default::default() };
+    let (sso, directory) = f.ldap_sso(&config);
+    directory.set_user("carol", "pw", &[]);
+
+    let (user, _) = sso
+        .ldap_login(&login_request("carol", "pw"), &SessionClient::default())
+        .await
+        .unwrap();
+    assert_eq!(f.organization_of(user.id).await, default_org);
+    assert!(f.role_ids(user.id).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_directory_users_and_local_users_stay_apart() {
+    let f = fixture().await;
+    let (sso, directory) =
+        f.ldap_sso(&SsoConfig { allow_unmapped_users: true, ..Default::default() });
+    sqlx::query(
+        "INSERT INTO organizations (code, name, is_system) VALUES ('default_org', 'Default', 1)",
+    )
+    .execute(&f.pool)
+    .await
+    .unwrap();
+    let two_factor =
+        Arc::new(TwoFactorService::new(f.pool.clone(), Arc::new(CredentialCipher::ephemeral())));
+    let auth = AuthService::new(f.pool.clone(), Arc::clone(&f.session_service), two_factor);
+    let client = SessionClient::default();
+
+    // A directory login cannot take over a local account with the same name
+    auth.register(CreateUserRequest {
+        username: "dave".to_string(),
+        password: "local-password".to_string(),
+        email: None,
+        avatar: None,
+    })
+    .await
+    .unwrap();
+    directory.set_user("dave", "pw", &[]);
+    let err = sso
+        .ldap_login(&login_request("dave", "pw"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)));
+
+    // Provisioned users have no usable local password
+    directory.set_user("erin", "pw", &[]);
+    let (erin, _) = sso
+        .ldap_login(&login_request("erin", "pw"), &client)
+        .await
+        .unwrap();
+    let err = auth
+        .login(login_request("erin", "pw"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::InvalidCredentials));
+
+    // Disabled provisioned users cannot log in through the directory either
+    sqlx::query("UPDATE users SET is_active = 0 WHERE id = ?")
+        .bind(erin.id)
+        .execute(&f.pool)
+        .await
+        .unwrap();
+    let err = sso
+        .ldap_login(&login_request("erin", "pw"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)));
+
+    // Logins of a disabled provider are refused
+    let err = f
+        .sso(&SsoConfig::default())
+        .ldap_login(&login_request("erin", "pw"), &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)));
+}
+
+#[tokio::test]
+async fn test_group_mapping_validation() {
+    let f = fixture().await;
+    let sso = f.sso(&SsoConfig::default());
+    sqlx::query("INSERT INTO roles (code, name, organization_id) VALUES ('org2_only', 'Org2', ?)")
+        .bind(f.data.org2_id)
+        .execute(&f.pool)
+        .await
+        .unwrap();
+    let org2_role: i64 = sqlx::query_scalar("SELECT id FROM roles WHERE code = 'org2_only'")
+        .fetch_one(&f.pool)
+        .await
+        .unwrap();
+
+    let request = |provider, group: &str, organization_id, role_id| CreateSsoGroupMappingRequest {
+        provider,
+        group_name: group.to_string(),
+        organization_id,
+        role_id,
+    };
+
+    let err = sso
+        .create_mapping(request(AuthProvider::Ldap, "dba", f.data.org1_id, Some(org2_role)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)));
+    let err = sso
+        .create_mapping(request(AuthProvider::Local, "dba", f.data.org1_id, None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)));
+    let err = sso
+        .create_mapping(request(AuthProvider::Ldap, "  ", f.data.org1_id, None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ValidationError(_)));
+    let err = sso
+        .create_mapping(request(AuthProvider::Ldap, "dba", 9999, None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ResourceNotFound(_)));
+
+    let mapping = sso
+        .create_mapping(request(AuthProvider::Oidc, " admins ", f.data.org2_id, Some(org2_role)))
+        .await
+        .unwrap();
+    assert_eq!(mapping.group_name, "admins");
+    assert_eq!(mapping.role_name.as_deref(), Some("Org2"));
+    assert_eq!(sso.list_mappings().await.unwrap().len(), 1);
+
+    sso.delete_mapping(mapping.id).await.unwrap();
+    assert!(sso.list_mappings().await.unwrap().is_empty());
+    let err = sso.delete_mapping(mapping.id).await.unwrap_err();
+    assert!(matches!(err, ApiError::ResourceNotFound(_)));
+}
+
+#[test]
+fn test_sso_mapping_permissions() {
+    for (method, uri, expected) in [
+        ("GET", "/api/sso/mappings", "mappings:list"),
+        ("POST", "/api/sso/mappings", "mappings:create"),
+        ("DELETE", "/api/sso/mappings/3", "mappings:delete"),
+    ] {
+        assert_eq!(
+            extract_permission(method, uri),
+            Some(("sso".to_string(), expected.to_string())),
+            "{} {}",
+            method,
+            uri
+        );
+    }
+    assert_eq!(extract_permission("POST", "/api/auth/ldap/login"), None);
+}
+
+// ========================================
+// OIDC against a mock identity provider
+// ========================================
+
+const CLIENT_ID: &str = "starrocks-admin";
+const KEY_ID: &str = "test-key";
+
+/// Mock identity provider: serves discovery, JWKS and a token endpoint answering with an ID
+/// token built from `claims`, after checking the PKCE verifier against `challenge`
+struct MockIdp {
+    issuer: String,
+    claims: Arc<Mutex<Value>>,
+    challenge: Arc<Mutex<String>>,
+}
+
+async fn start_idp() -> MockIdp {
+    let rsa = openssl::rsa::Rsa::generate(2048).unwrap();
+    let jwk = json!({
+        "kty": "RSA",
+        "kid": KEY_ID,
+        "use": "sig",
+        "alg": "RS256",
+        "n": URL_SAFE_NO_PAD.encode(rsa.n().to_vec()),
+        "e": URL_SAFE_NO_PAD.encode(rsa.e().to_vec()),
+    });
+    let signing_key = EncodingKey::from_rsa_pem(&rsa.private_key_to_pem().unwrap()).unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+    let claims = Arc::new(Mutex::new(json!({})));
+    let challenge = Arc::new(Mutex::new(String::new()));
+
+    let discovery = json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/authorize", issuer),
+        "token_endpoint": format!("{}/token", issuer),
+        "jwks_uri": format!("{}/jwks", issuer),
+    });
+    let token_claims = Arc::clone(&claims);
+    let token_challenge = Arc::clone(&challenge);
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(move || async move { Json(discovery) }))
+        .route("/jwks", get(move || async move { Json(json!({ "keys": [jwk] })) }))
+        .route(
+            "/token",
+            post(move |Form(form): Form<HashMap<String, String>>| async move {
+                let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+                let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+                if form.get("code").map(String::as_str) != Some("good-code")
+                    || form.get("client_id").map(String::as_str) != Some(CLIENT_ID)
+                    || challenge != *token_challenge.lock().unwrap()
+                {
+                    return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" })));
+                }
+                let mut header = Header::new(jsonwebtoken::Algorithm::RS256);
+                header.kid = Some(KEY_ID.to_string());
+                let claims = token_claims.lock().unwrap().clone();
+                let id_token = jsonwebtoken::encode(&header, &claims, &signing_key).unwrap();
+                (StatusCode::OK, Json(json!({ "id_token": id_token, "token_type": "Bearer" })))
+            }),
+        );
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    MockIdp { issuer, claims, challenge }
+}
+
+impl MockIdp {
+    fn config(&self) -> SsoConfig {
+        SsoConfig {
+            oidc: OidcConfig {
+                enabled: true,
+                issuer_url: self.issuer.clone(),
+                client_id: CLIENT_ID.to_string(),
+                client_secret: "client-secret".to_string(),
+                redirect_url: "http://admin.example.com/api/auth/oidc/callback".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Start a login and return its state, after recording the challenge and issuing the next
+    /// ID token with `claims` and the nonce of the login
+    async fn authorize(&self, sso: &SsoService, claims: Value) -> String {
+        let (url, state) = sso.oidc_authorization_url().await.unwrap();
+        assert_eq!(self.accept(&url, claims), state);
+        state
+    }
+
+    /// Record the challenge of the authorization URL and issue the next ID token with `claims`
+    /// and the nonce of the login; returns the state of the login
+    fn accept(&self, url: &str, mut claims: Value) -> String {
+        let url = reqwest::Url::parse(url).unwrap();
+        assert!(
+            url.as_str()
+                .starts_with(&format!("{}/authorize?", self.issuer))
+        );
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["scope"], "openid profile email");
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        *self.challenge.lock().unwrap() = params["code_challenge"].clone();
+        if claims.get("nonce").is_none() {
+            claims["nonce"] = json!(params["nonce"]);
+        }
+        *self.claims.lock().unwrap() = claims;
+        params["state"].clone()
+    }
+
+    fn claims(&self, subject: &str, username: &str, groups: &[&str]) -> Value {
+        json!({
+            "iss": self.issuer,
+            "aud": CLIENT_ID,
+            "sub": subject,
+            "exp": chrono::Utc::now().timestamp() + 300,
+            "preferred_username": username,
+            "email": format!("{}@example.com", username),
+            "groups": groups,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_oidc_login_with_mock_identity_provider() {
+    let f = fixture().await;
+    let idp = start_idp().await;
+    let sso = f.sso(&idp.config());
+    assert!(sso.providers().oidc && !sso.providers().ldap);
+    f.map(&sso, AuthProvider::Oidc, "admins", f.data.org2_id, Some(f.data.super_admin_role_id))
+        .await;
+    let client = SessionClient::default();
+
+    let state = idp
+        .authorize(&sso, idp.claims("subject-1", "frank", &["admins"]))
+        .await;
+    let (user, tokens) = sso.oidc_login("good-code", &state, &client).await.unwrap();
+    assert!(!tokens.token.is_empty());
+    assert_eq!(user.username, "frank");
+    assert_eq!(user.auth_provider, "oidc");
+    assert_eq!(f.organization_of(user.id).await, f.data.org2_id);
+    assert_eq!(f.role_ids(user.id).await, vec![f.data.super_admin_role_id]);
+
+    // States are single-use
+    let err = sso
+        .oidc_login("good-code", &state, &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)));
+
+    // The subject identifies the user, even when the username changes
+    let state = idp
+        .authorize(&sso, idp.claims("subject-1", "frank.renamed", &["admins"]))
+        .await;
+    let (again, _) = sso.oidc_login("good-code", &state, &client).await.unwrap();
+    assert_eq!(again.id, user.id);
+
+    // Rejected codes
+    let state = idp
+        .authorize(&sso, idp.claims("subject-1", "frank", &["admins"]))
+        .await;
+    let err = sso
+        .oidc_login("bad-code", &state, &client)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Unauthorized(_)));
+}
+
+#[tokio::test]
+async fn test_oidc_rejects_invalid_id_tokens() {
+    let f = fixture().await;
+    let idp = start_idp().await;
+    let sso = f.sso(&idp.config());
+    f.map(&sso, AuthProvider::Oidc, "admins", f.data.org1_id, None)
+        .await;
+    let client = SessionClient::default();
+
+    let mut wrong_nonce = idp.claims("subject-2", "grace", &["admins"]);
+    wrong_nonce["nonce"] = json!("replayed");
+    let mut wrong_audience = idp.claims("subject-2", "grace", &["admins"]);
+    wrong_audience["aud"] = json!("another-client");
+    let mut expired = idp.claims("subject-2", "grace", &["admins"]);
+    expired["exp"] = json!(chrono::Utc::now().timestamp() - 3600);
+    let mut no_username = idp.claims("subject-2", "grace", &["admins"]);
+    no_username
+        .as_object_mut()
+        .unwrap()
+        .remove("preferred_username");
+
+    for claims in [wrong_nonce, wrong_audience, expired, no_username] {
+        let state = idp.authorize(&sso, claims).await;
+        let err = sso
+            .oidc_login("good-code", &state, &client)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiError::Unauthorized(_)), "{:?}", err);
+    }
+
+    // Groups can be read from a nested claim
+    let mut config = idp.config();
+    config.oidc.groups_claim = "realm_access.roles".to_string();
+    let sso = f.sso(&config);
+    let mut nested = idp.claims("subject-2", "grace", &[]);
+    nested["realm_access"] = json!({ "roles": ["admins"] });
+    let state = idp.authorize(&sso, nested).await;
+    let (user, _) = sso.oidc_login("good-code", &state, &client).await.unwrap();
+    assert_eq!(f.organization_of(user.id).await, f.data.org1_id);
+}
+
+#[tokio::test]
+async fn test_oidc_callback_requires_the_browser_that_started_the_login() {
+    let f = fixture().await;
+    let idp = start_idp().await;
+    let sso = f.sso(&idp.config());
+    f.map(&sso, AuthProvider::Oidc, "admins", f.data.org1_id, None)
+        .await;
+    let mut state = (*create_test_app_state(&f.pool).await).clone();
+    state.sso_service = Arc::new(sso);
+    let app = Router::new()
+        .route("/api/auth/oidc/authorize", get(handlers::sso::oidc_authorize))
+        .route("/api/auth/oidc/callback", get(handlers::sso::oidc_callback))
+        .with_state(Arc::new(state));
+    let get = |uri: &str, cookie: Option<&str>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+    let header_of = |response: &axum::response::Response, name| {
+        response.headers()[name].to_str().unwrap().to_string()
+    };
+
+    let response = get("/api/auth/oidc/authorize", None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let login_state = idp.accept(
+        &header_of(&response, header::LOCATION),
+        idp.claims("subject-3", "heidi", &["admins"]),
+    );
+    let set_cookie = header_of(&response, header::SET_COOKIE);
+    assert!(set_cookie.starts_with(&format!("oidc_state={};", login_state)), "{}", set_cookie);
+    assert!(set_cookie.contains("HttpOnly") && set_cookie.contains("SameSite=Lax"));
+
+    // The callback link followed in another browser (login CSRF)
+    let callback = format!("/api/auth/oidc/callback?code=good-code&state={}", login_state);
+    for cookie in [None, Some("oidc_state=another"), Some("theme=dark")] {
+        let response = get(&callback, cookie).await.unwrap();
+        let location = header_of(&response, header::LOCATION);
+        assert!(location.contains("error=Unauthorized%3A%20Sign-in%20was%20not%20started"));
+        assert!(!location.contains("token="), "{}", location);
+    }
+
+    // The browser that started the login
+    let cookie = format!("theme=dark; oidc_state={}", login_state);
+    let response = get(&callback, Some(&cookie)).await.unwrap();
+    let location = header_of(&response, header::LOCATION);
+    assert!(location.contains("?token="), "{}", location);
+    assert!(header_of(&response, header::SET_COOKIE).contains("Max-Age=0"));
+}
+
+#[tokio::test]
+async fn test_oidc_callback_redirects_errors_to_frontend() {
+    let pool = create_test_db().await;
+    let state = create_test_app_state(&pool).await;
+    let app = Router::new()
+        .route("/api/auth/oidc/callback", get(handlers::sso::oidc_callback))
+        .with_state(state);
+
+    for (query, expected) in [
+        ("error=access_denied", "/#/auth/sso?error=Unauthorized%3A%20Sign-in%20failed"),
+        ("code=abc&state=def", "/#/auth/sso?error=Unauthorized%3A%20Sign-in%20was%20not"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/auth/oidc/callback?{}", query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let location = response.headers()[header::LOCATION].to_str().unwrap();
+        assert!(location.starts_with(expected), "{}", location);
+    }
+}
@@ -0,0 +1,210 @@
+// Per-user active cluster tests
+
+use crate::middleware::{ClusterScope, OrgContext, cluster_scope_middleware};
+use crate::models::{Cluster, CreateClusterRequest};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use tower::{Layer, ServiceExt};
+
+async fn create_cluster(
+    state: &crate::AppState,
+    name: &str,
+    organization_id: Option<i64>,
+    created_by: i64,
+) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: name.to_string(),
+                description: None,
+                fe_host: format!("{}.example.com", name),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn org_ctx(user_id: i64, organization_id: Option<i64>) -> OrgContext {
+    OrgContext {
+        user_id,
+        username: format!("user{}", user_id),
+        organization_id,
+        is_super_admin: false,
+        cluster_id: None,
+        session_id: None,
+        api_token_id: None,
+        allowed_cluster_ids: None,
+        permission_scopes: None,
+    }
+}
+
+#[tokio::test]
+async fn test_active_cluster_is_selected_per_user() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let clusters = &state.cluster_service;
+
+    let first = create_cluster(&state, "first", Some(data.org1_id), data.super_admin_user_id).await;
+    let second =
+        create_cluster(&state, "second", Some(data.org1_id), data.super_admin_user_id).await;
+    clusters.set_active_cluster(first.id).await.unwrap();
+
+    let admin = org_ctx(data.org1_admin_user_id, Some(data.org1_id));
+    let colleague = org_ctx(data.org1_regular_user_id, Some(data.org1_id));
+
+    clusters
+        .set_user_active_cluster(admin.user_id, second.id)
+        .await
+        .unwrap();
+    assert_eq!(clusters.resolve_cluster(&admin).await.unwrap().id, second.id);
+    // The colleague keeps working on the organization default
+    assert_eq!(clusters.resolve_cluster(&colleague).await.unwrap().id, first.id);
+    assert!(clusters.get_cluster(first.id).await.unwrap().is_active);
+    assert!(!clusters.get_cluster(second.id).await.unwrap().is_active);
+
+    // Selecting again replaces the selection
+    clusters
+        .set_user_active_cluster(admin.user_id, first.id)
+        .await
+        .unwrap();
+    assert_eq!(clusters.resolve_cluster(&admin).await.unwrap().id, first.id);
+
+    // An explicitly addressed cluster wins over the selection
+    let scoped = OrgContext { cluster_id: Some(second.id), ..admin.clone() };
+    assert_eq!(clusters.resolve_cluster(&scoped).await.unwrap().id, second.id);
+}
+
+#[tokio::test]
+async fn test_stale_selection_falls_back_to_default() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let clusters = &state.cluster_service;
+
+    let default =
+        create_cluster(&state, "default", Some(data.org1_id), data.super_admin_user_id).await;
+    let selected =
+        create_cluster(&state, "selected", Some(data.org1_id), data.super_admin_user_id).await;
+    let foreign =
+        create_cluster(&state, "foreign", Some(data.org2_id), data.super_admin_user_id).await;
+    clusters.set_active_cluster(default.id).await.unwrap();
+
+    let user = org_ctx(data.org1_admin_user_id, Some(data.org1_id));
+
+    // A selection outside the caller's organization is ignored
+    clusters
+        .set_user_active_cluster(user.user_id, foreign.id)
+        .await
+        .unwrap();
+    assert!(
+        clusters
+            .get_user_active_cluster(&user)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(clusters.resolve_cluster(&user).await.unwrap().id, default.id);
+
+    // Deleting the selected cluster drops the selection
+    clusters
+        .set_user_active_cluster(user.user_id, selected.id)
+        .await
+        .unwrap();
+    assert_eq!(clusters.resolve_cluster(&user).await.unwrap().id, selected.id);
+    clusters.delete_cluster(selected.id).await.unwrap();
+    let (selections,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM user_active_clusters WHERE user_id = ?")
+            .bind(user.user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(selections, 0);
+    assert_eq!(clusters.resolve_cluster(&user).await.unwrap().id, default.id);
+
+    assert!(
+        clusters
+            .set_user_active_cluster(user.user_id, 999_999)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_cluster_id_header_addresses_cluster() {
+    let router = Router::new().route(
+        "/api/clusters/queries",
+        get(|scope: Option<Extension<ClusterScope>>| async move {
+            Json(scope.map(|Extension(ClusterScope(id))| id))
+        }),
+    );
+    let app = axum::middleware::from_fn(cluster_scope_middleware).layer(router);
+
+    let call = |uri: &'static str, header: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = Request::get(uri);
+            if let Some(value) = header {
+                request = request.header("X-Cluster-Id", value);
+            }
+            let response = app
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+        }
+    };
+
+    assert_eq!(call("/api/clusters/queries", Some("5")).await, (StatusCode::OK, 5.into()));
+    assert_eq!(
+        call("/api/clusters/queries", None).await,
+        (StatusCode::OK, serde_json::Value::Null)
+    );
+    // The path wins over the header
+    assert_eq!(call("/api/clusters/8/queries", Some("5")).await, (StatusCode::OK, 8.into()));
+
+    let (status, body) = call("/api/clusters/queries", Some("abc")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("X-Cluster-Id"));
+}
+
+#[tokio::test]
+async fn test_default_route_maps_to_seeded_permission() {
+    use crate::middleware::permission_extractor::extract_permission;
+
+    let pool = create_test_db().await;
+    let (resource, action) = extract_permission("PUT", "/api/clusters/3/default").unwrap();
+    assert_eq!((resource.as_str(), action.as_str()), ("clusters", "default"));
+
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM permissions WHERE resource = 'clusters' AND action = 'default'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
@@ -0,0 +1,326 @@
+// Variable history tests: SET literals, snapshot storage and diff, change history, rollback
+// checks and permissions
+
+use crate::middleware::permission_extractor::{extract_permission, scope_level};
+use crate::models::{
+    Cluster, CreateClusterRequest, PermissionScopeLevel, VariableChangeFilter, VariableDiffKind,
+};
+use crate::services::variable_history_service::{
+    VariableValues, diff_variables, is_valid_variable_name, normalize_scope, sql_literal, unquote,
+};
+use crate::tests::common::{create_test_app_state, create_test_db, setup_multi_tenant_test_data};
+use chrono::{Duration, Utc};
+
+async fn create_cluster(state: &crate::AppState, created_by: i64) -> Cluster {
+    state
+        .cluster_service
+        .create_cluster(
+            CreateClusterRequest {
+                name: "variables".to_string(),
+                description: None,
+                fe_host: "fe.example.com".to_string(),
+                fe_http_port: 8030,
+                fe_query_port: 9030,
+                username: "root".to_string(),
+                password: "secret".to_string(),
+                enable_ssl: false,
+                connection_timeout: 10,
+                tags: None,
+                catalog: "default_catalog".to_string(),
+                organization_id: None,
+                deployment_mode: crate::models::cluster::DeploymentMode::default(),
+                fe_endpoints: None,
+                tls: None,
+            },
+            created_by,
+            None,
+            true,
+        )
+        .await
+        .unwrap()
+}
+
+fn values(pairs: &[(&str, &str)]) -> VariableValues {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+async fn insert_change(
+    pool: &sqlx::SqlitePool,
+    cluster_id: i64,
+    name: &str,
+    scope: &str,
+    old_value: Option<&str>,
+    new_value: &str,
+    user_id: i64,
+) -> i64 {
+    sqlx::query(
+        "INSERT INTO variable_changes (cluster_id, variable_name, scope, old_value, new_value,
+         changed_by, changed_by_username)
+         VALUES (?, ?, ?, ?, ?, ?, 'admin')",
+    )
+    .bind(cluster_id)
+    .bind(name)
+    .bind(scope)
+    .bind(old_value)
+    .bind(new_value)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .unwrap()
+    .last_insert_rowid()
+}
+
+#[test]
+fn test_set_literals() {
+    assert_eq!(normalize_scope("global").unwrap(), "GLOBAL");
+    assert_eq!(normalize_scope("Session").unwrap(), "SESSION");
+    assert!(normalize_scope("local").is_err());
+
+    assert!(is_valid_variable_name("query_timeout"));
+    assert!(is_valid_variable_name("exec_mem_limit"));
+    assert!(!is_valid_variable_name(""));
+    assert!(!is_valid_variable_name("query_timeout = 1; DROP DATABASE x"));
+
+    assert_eq!(unquote("'Asia/Shanghai'"), "Asia/Shanghai");
+    assert_eq!(unquote("\"utf8\""), "utf8");
+    assert_eq!(unquote(" 300 "), "300");
+    assert_eq!(unquote("'"), "'");
+
+    assert_eq!(sql_literal("300"), "300");
+    assert_eq!(sql_literal("0.5"), "0.5");
+    assert_eq!(sql_literal("TRUE"), "TRUE");
+    assert_eq!(sql_literal("Asia/Shanghai"), "'Asia/Shanghai'");
+    assert_eq!(sql_literal("it's"), "'it\\'s'");
+    assert_eq!(sql_literal(""), "''");
+}
+
+#[test]
+fn test_diff_variables() {
+    let from = values(&[("query_timeout", "300"), ("time_zone", "UTC"), ("old_var", "1")]);
+    let to = values(&[("query_timeout", "600"), ("time_zone", "UTC"), ("new_var", "on")]);
+
+    let (changes, unchanged) = diff_variables(&from, &to);
+    assert_eq!(unchanged, 1);
+    let summary: Vec<(&str, VariableDiffKind, Option<&str>, Option<&str>)> = changes
+        .iter()
+        .map(|c| (c.name.as_str(), c.kind, c.from_value.as_deref(), c.to_value.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("new_var", VariableDiffKind::Added, None, Some("on")),
+            ("old_var", VariableDiffKind::Removed, Some("1"), None),
+            ("query_timeout", VariableDiffKind::Changed, Some("300"), Some("600")),
+        ]
+    );
+
+    let (changes, unchanged) = diff_variables(&from, &from);
+    assert!(changes.is_empty());
+    assert_eq!(unchanged, 3);
+}
+
+#[tokio::test]
+async fn test_snapshots_are_stored_on_change_and_compared() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.variable_history_service;
+
+    let before = values(&[("query_timeout", "300"), ("time_zone", "UTC")]);
+    let first = service
+        .save_snapshot(cluster.id, &before, false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.variable_count, 2);
+
+    // Unchanged variables are not stored again within the snapshot interval, unless forced
+    assert!(
+        service
+            .save_snapshot(cluster.id, &before, false)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let forced = service
+        .save_snapshot(cluster.id, &before, true)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let after = values(&[("query_timeout", "600"), ("time_zone", "UTC"), ("pipeline_dop", "0")]);
+    let changed = service
+        .save_snapshot(cluster.id, &after, false)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let snapshots = service.list_snapshots(cluster.id, None).await.unwrap();
+    let ids: Vec<i64> = snapshots.iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![changed.id, forced.id, first.id]);
+    assert_eq!(
+        service
+            .list_snapshots(cluster.id, Some(1))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let snapshot = service.get_snapshot(cluster.id, changed.id).await.unwrap();
+    let names: Vec<&str> = snapshot.variables.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["pipeline_dop", "query_timeout", "time_zone"]);
+
+    let diff = service
+        .diff_snapshots(cluster.id, first.id, changed.id)
+        .await
+        .unwrap();
+    assert_eq!(diff.from.id, first.id);
+    assert_eq!(diff.unchanged, 1);
+    let kinds: Vec<(&str, VariableDiffKind)> = diff
+        .changes
+        .iter()
+        .map(|c| (c.name.as_str(), c.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("pipeline_dop", VariableDiffKind::Added),
+            ("query_timeout", VariableDiffKind::Changed)
+        ]
+    );
+
+    // Snapshots of another cluster are not found
+    assert!(
+        service
+            .diff_snapshots(cluster.id + 1, first.id, changed.id)
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .get_snapshot(cluster.id, changed.id + 100)
+            .await
+            .is_err()
+    );
+
+    let deleted = service
+        .cleanup_before(Utc::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 3);
+}
+
+#[tokio::test]
+async fn test_change_history_and_rollback_checks() {
+    let pool = create_test_db().await;
+    let data = setup_multi_tenant_test_data(&pool).await;
+    let state = create_test_app_state(&pool).await;
+    let cluster = create_cluster(&state, data.super_admin_user_id).await;
+    let service = &state.variable_history_service;
+    let user = data.super_admin_user_id;
+
+    let timeout =
+        insert_change(&pool, cluster.id, "query_timeout", "GLOBAL", Some("300"), "600", user).await;
+    let session =
+        insert_change(&pool, cluster.id, "pipeline_dop", "SESSION", Some("0"), "8", user).await;
+    let unknown =
+        insert_change(&pool, cluster.id, "Query_Timeout", "GLOBAL", None, "900", user).await;
+
+    let all = service
+        .list_changes(cluster.id, &VariableChangeFilter::default())
+        .await
+        .unwrap();
+    let ids: Vec<i64> = all.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![unknown, session, timeout]);
+    assert_eq!(all[2].old_value.as_deref(), Some("300"));
+    assert_eq!(all[2].changed_by_username, "admin");
+
+    let timeouts = service
+        .list_changes(
+            cluster.id,
+            &VariableChangeFilter {
+                variable: Some("QUERY_TIMEOUT".to_string()),
+                scope: Some("global".to_string()),
+                limit: Some(1),
+            },
+        )
+        .await
+        .unwrap();
+    let ids: Vec<i64> = timeouts.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![unknown]);
+    assert!(
+        service
+            .list_changes(
+                cluster.id,
+                &VariableChangeFilter { scope: Some("local".to_string()), ..Default::default() }
+            )
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .list_changes(cluster.id + 1, &VariableChangeFilter::default())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Checked before the cluster is contacted
+    let err = service
+        .rollback(&cluster, session, user, "admin")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("GLOBAL"), "{}", err);
+    let err = service
+        .rollback(&cluster, unknown, user, "admin")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown"), "{}", err);
+    assert!(
+        service
+            .rollback(&cluster, unknown + 100, user, "admin")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_variable_history_routes_map_to_seeded_permissions() {
+    let pool = create_test_db().await;
+    let routes = [
+        ("GET", "/api/clusters/variables/history", "variables:history"),
+        ("POST", "/api/clusters/variables/history/12/rollback", "variables:rollback"),
+        ("GET", "/api/clusters/variables/snapshots", "variables:snapshots"),
+        ("GET", "/api/clusters/variables/snapshots/3", "variables:snapshots"),
+        ("GET", "/api/clusters/variables/snapshots/diff", "variables:snapshots"),
+        ("POST", "/api/clusters/variables/snapshots", "variables:snapshots:create"),
+    ];
+
+    for (method, uri, action) in routes {
+        let (resource, extracted) = extract_permission(method, uri).unwrap();
+        assert_eq!((resource.as_str(), extracted.as_str()), ("clusters", action));
+        assert_eq!(scope_level(&resource, &extracted), Some(PermissionScopeLevel::Cluster));
+
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM permissions p
+             JOIN role_permissions rp ON rp.permission_id = p.id
+             JOIN roles r ON r.id = rp.role_id
+             WHERE p.resource = ? AND p.action = ? AND r.code = 'admin'",
+        )
+        .bind(&resource)
+        .bind(&extracted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "permission {}:{} not granted to admin", resource, extracted);
+    }
+
+    // Updating a variable keeps its permission
+    let (_, action) = extract_permission("PUT", "/api/clusters/variables/query_timeout").unwrap();
+    assert_eq!(action, "variables:update");
+}
@@ -0,0 +1,181 @@
+//! TLS settings of the connections to a StarRocks cluster.
+//!
+//! The same settings apply to the MySQL protocol connection (mysql_async) and the HTTP
+//! client (reqwest). Both use native-tls, which only accepts a client identity as a
+//! PKCS#12 archive, so the PEM certificate and key of a cluster are converted once here.
+
+use mysql_async::{ClientIdentity, SslOpts};
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::X509;
+
+use crate::models::Cluster;
+use crate::utils::CredentialCipher;
+use crate::utils::error::{ApiError, ApiResult};
+
+/// Password of the in-memory PKCS#12 archive (never stored)
+const PKCS12_PASSWORD: &str = "starrocks-admin";
+
+/// Resolved TLS settings of a cluster
+#[derive(Clone)]
+pub struct ClusterTls {
+    ca_bundle: Option<Vec<u8>>,
+    identity: Option<Vec<u8>>,
+    http_root_certs: Vec<reqwest::Certificate>,
+    http_identity: Option<reqwest::Identity>,
+    verify_hostname: bool,
+}
+
+impl ClusterTls {
+    /// TLS settings of a cluster; `None` when the cluster does not use TLS
+    pub fn from_cluster(cluster: &Cluster, cipher: &CredentialCipher) -> ApiResult<Option<Self>> {
+        if !cluster.enable_ssl {
+            return Ok(None);
+        }
+
+        let (ca_bundle, http_root_certs) = match &cluster.tls_ca_cert {
+            Some(pem) => {
+                parse_ca_bundle(pem)?;
+                let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes()).map_err(|e| {
+                    ApiError::validation_error(format!("Invalid TLS CA certificate: {}", e))
+                })?;
+                (Some(pem.as_bytes().to_vec()), certs)
+            },
+            None => (None, Vec::new()),
+        };
+
+        let identity = match (&cluster.tls_client_cert, &cluster.tls_client_key_encrypted) {
+            (Some(cert), Some(key_encrypted)) => {
+                let key = cipher.decrypt(key_encrypted)?;
+                Some(pkcs12_identity(cert, &key)?)
+            },
+            (None, None) => None,
+            _ => {
+                return Err(ApiError::validation_error(
+                    "TLS client certificate and client key must be configured together",
+                ));
+            },
+        };
+
+        let http_identity = identity
+            .as_deref()
+            .map(|der| reqwest::Identity::from_pkcs12_der(der, PKCS12_PASSWORD))
+            .transpose()
+            .map_err(|e| {
+                ApiError::validation_error(format!("Invalid TLS client certificate: {}", e))
+            })?;
+
+        Ok(Some(Self {
+            ca_bundle,
+            identity,
+            http_root_certs,
+            http_identity,
+            verify_hostname: cluster.tls_verify_hostname,
+        }))
+    }
+
+    /// TLS options of the MySQL protocol connection
+    pub fn mysql_ssl_opts(&self) -> SslOpts {
+        let mut opts = SslOpts::default().with_danger_skip_domain_validation(!self.verify_hostname);
+        if let Some(ca_bundle) = &self.ca_bundle {
+            opts = opts.with_root_certs(vec![ca_bundle.clone().into()]);
+        }
+        if let Some(identity) = &self.identity {
+            opts = opts.with_client_identity(Some(
+                ClientIdentity::new(identity.clone().into()).with_password(PKCS12_PASSWORD),
+            ));
+        }
+        opts
+    }
+
+    /// Apply the TLS settings to an HTTP client
+    pub fn apply_to_http(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        for cert in &self.http_root_certs {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        if let Some(identity) = &self.http_identity {
+            builder = builder.identity(identity.clone());
+        }
+        builder.danger_accept_invalid_hostnames(!self.verify_hostname)
+    }
+
+    /// Short description for connection tests
+    pub fn summary(&self) -> String {
+        format!(
+            "TLS enabled ({}, {}, host name verification {})",
+            if self.ca_bundle.is_some() { "custom CA" } else { "system CA" },
+            if self.identity.is_some() { "mutual TLS" } else { "no client certificate" },
+            if self.verify_hostname { "on" } else { "off" }
+        )
+    }
+}
+
+/// Check a PEM CA bundle (one or more certificates)
+pub fn validate_ca_bundle(pem: &str) -> ApiResult<()> {
+    parse_ca_bundle(pem).map(|_| ())
+}
+
+/// Check a PEM client certificate and its private key, and that they belong together
+pub fn validate_client_identity(cert_pem: &str, key_pem: &str) -> ApiResult<()> {
+    let (cert, key) = parse_client_identity(cert_pem, key_pem)?;
+    let matches = cert
+        .public_key()
+        .map(|public| public.public_eq(&key))
+        .unwrap_or(false);
+    if !matches {
+        return Err(ApiError::validation_error(
+            "TLS client key does not match the client certificate",
+        ));
+    }
+    Ok(())
+}
+
+/// Make a connection error caused by TLS recognizable in connection tests
+pub fn describe_connection_error(message: &str) -> String {
+    let lower = message.to_lowercase();
+    let is_tls = ["certificate", "ssl", "tls", "handshake", "x509"]
+        .iter()
+        .any(|needle| lower.contains(needle));
+    if is_tls {
+        format!(
+            "TLS error: {} (check the CA certificate, client certificate and host name verification)",
+            message
+        )
+    } else {
+        message.to_string()
+    }
+}
+
+fn parse_ca_bundle(pem: &str) -> ApiResult<Vec<X509>> {
+    let certs = X509::stack_from_pem(pem.as_bytes())
+        .map_err(|e| ApiError::validation_error(format!("Invalid TLS CA certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(ApiError::validation_error(
+            "Invalid TLS CA certificate: no PEM certificate found",
+        ));
+    }
+    Ok(certs)
+}
+
+fn parse_client_identity(cert_pem: &str, key_pem: &str) -> ApiResult<(X509, PKey<Private>)> {
+    let cert = X509::from_pem(cert_pem.as_bytes()).map_err(|e| {
+        ApiError::validation_error(format!("Invalid TLS client certificate: {}", e))
+    })?;
+    let key = PKey::private_key_from_pem(key_pem.as_bytes())
+        .map_err(|e| ApiError::validation_error(format!("Invalid TLS client key: {}", e)))?;
+    Ok((cert, key))
+}
+
+/// PKCS#12 archive (DER) of a PEM client certificate and key
+fn pkcs12_identity(cert_pem: &str, key_pem: &str) -> ApiResult<Vec<u8>> {
+    let (cert, key) = parse_client_identity(cert_pem, key_pem)?;
+    Pkcs12::builder()
+        .name("starrocks-admin")
+        .pkey(&key)
+        .cert(&cert)
+        .build2(PKCS12_PASSWORD)
+        .and_then(|archive| archive.to_der())
+        .map_err(|e| {
+            ApiError::internal_error(format!("Failed to build TLS client identity: {}", e))
+        })
+}
@@ -0,0 +1,380 @@
+//! Envelope encryption for secrets stored in SQLite (cluster passwords).
+//!
+//! Every value gets its own random 256-bit data key. The value is sealed with
+//! the data key using AES-256-GCM, and the data key is in turn sealed with the
+//! master key. The stored format is:
+//!
+//! ```text
+//! enc:v1:<key_id>:<base64(nonce || wrapped data key)>:<base64(nonce || ciphertext)>
+//! ```
+//!
+//! `key_id` is a short fingerprint of the master key so that a value sealed
+//! with a different key produces a clear error instead of an opaque AEAD failure.
+//! Values without the `enc:v1:` prefix are treated as legacy plaintext.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::SecurityConfig;
+use crate::utils::error::{ApiError, ApiResult};
+
+/// Prefix marking a value produced by [`CredentialCipher::encrypt`]
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Associated data bound to every sealed value
+const AAD: &[u8] = b"starrocks-admin:credential:v1";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// AEAD cipher for secrets at rest, keyed by the configured master key
+#[derive(Clone)]
+pub struct CredentialCipher {
+    master: Aes256Gcm,
+    key_id: String,
+}
+
+impl std::fmt::Debug for CredentialCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialCipher")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+impl CredentialCipher {
+    /// Build a cipher from raw 32-byte key material
+    pub fn from_key(key: &[u8]) -> ApiResult<Self> {
+        if key.len() != KEY_LEN {
+            return Err(ApiError::internal_error(format!(
+                "Master key must be {} bytes, got {}",
+                KEY_LEN,
+                key.len()
+            )));
+        }
+
+        let digest = Sha256::digest(key);
+        let key_id = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+        let master = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+        Ok(Self { master, key_id })
+    }
+
+    /// Build a cipher from a base64-encoded master key
+    pub fn from_encoded_key(encoded: &str) -> ApiResult<Self> {
+        let key = BASE64.decode(encoded.trim()).map_err(|e| {
+            ApiError::internal_error(format!("Master key is not valid base64: {}", e))
+        })?;
+        Self::from_key(&key)
+    }
+
+    /// Load the master key from a file, optionally generating it when missing
+    ///
+    /// Generated key files are written with mode 0600 on Unix.
+    pub fn from_key_file(path: &str, create_if_missing: bool) -> ApiResult<Self> {
+        let key_path = Path::new(path);
+
+        if !key_path.exists() {
+            if !create_if_missing {
+                return Err(ApiError::internal_error(format!(
+                    "Master key file not found: {}",
+                    path
+                )));
+            }
+            write_key_file(key_path, &Self::generate_encoded_key())?;
+            tracing::warn!("Generated new master key file: {} (back it up!)", path);
+        }
+
+        let content = std::fs::read_to_string(key_path).map_err(|e| {
+            ApiError::internal_error(format!("Failed to read master key file {}: {}", path, e))
+        })?;
+        Self::from_encoded_key(&content)
+    }
+
+    /// Build the cipher described by the `[security]` config section
+    ///
+    /// An inline `master_key` wins over `master_key_file`.
+    pub fn from_config(config: &SecurityConfig) -> ApiResult<Self> {
+        match config.master_key.as_deref() {
+            Some(key) if !key.trim().is_empty() => Self::from_encoded_key(key),
+            _ => Self::from_key_file(&config.master_key_file, true),
+        }
+    }
+
+    /// Cipher with a random key that only lives for this process (tests, temp managers)
+    pub fn ephemeral() -> Self {
+        let key = Aes256Gcm::generate_key(OsRng);
+        Self::from_key(key.as_slice()).expect("generated key has the right length")
+    }
+
+    /// Generate a new base64-encoded master key
+    pub fn generate_encoded_key() -> String {
+        BASE64.encode(Aes256Gcm::generate_key(OsRng))
+    }
+
+    /// Short fingerprint of the master key (first 4 bytes of SHA-256, hex)
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Whether a stored value is in the encrypted envelope format
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Seal a plaintext secret into the envelope format
+    pub fn encrypt(&self, plaintext: &str) -> ApiResult<String> {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let data_cipher = Aes256Gcm::new(&data_key);
+
+        let data_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = data_cipher
+            .encrypt(&data_nonce, Payload { msg: plaintext.as_bytes(), aad: AAD })
+            .map_err(|_| ApiError::internal_error("Failed to encrypt credential"))?;
+
+        let wrap_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_key = self
+            .master
+            .encrypt(&wrap_nonce, Payload { msg: data_key.as_slice(), aad: AAD })
+            .map_err(|_| ApiError::internal_error("Failed to wrap data key"))?;
+
+        Ok(format!(
+            "{}{}:{}:{}",
+            ENCRYPTED_PREFIX,
+            self.key_id,
+            BASE64.encode([wrap_nonce.as_slice(), &wrapped_key].concat()),
+            BASE64.encode([data_nonce.as_slice(), &ciphertext].concat()),
+        ))
+    }
+
+    /// Open an envelope-encrypted value
+    ///
+    /// Values without the envelope prefix are returned unchanged, so legacy
+    /// plaintext rows and ad-hoc connection tests keep working.
+    pub fn decrypt(&self, value: &str) -> ApiResult<String> {
+        let Some(envelope) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+
+        let mut parts = envelope.splitn(3, ':');
+        let (Some(key_id), Some(wrapped), Some(sealed)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ApiError::internal_error("Malformed encrypted credential"));
+        };
+
+        if key_id != self.key_id {
+            return Err(ApiError::internal_error(format!(
+                "Credential was encrypted with master key {}, but the configured master key is {}",
+                key_id, self.key_id
+            )));
+        }
+
+        let wrapped = decode_sealed(wrapped)?;
+        let data_key = self
+            .master
+            .decrypt(
+                Nonce::from_slice(&wrapped[..NONCE_LEN]),
+                Payload { msg: &wrapped[NONCE_LEN..], aad: AAD },
+            )
+            .map_err(|_| ApiError::internal_error("Failed to unwrap credential data key"))?;
+        if data_key.len() != KEY_LEN {
+            return Err(ApiError::internal_error("Malformed encrypted credential"));
+        }
+
+        let sealed = decode_sealed(sealed)?;
+        let data_cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key));
+        let plaintext = data_cipher
+            .decrypt(
+                Nonce::from_slice(&sealed[..NONCE_LEN]),
+                Payload { msg: &sealed[NONCE_LEN..], aad: AAD },
+            )
+            .map_err(|_| ApiError::internal_error("Failed to decrypt credential"))?;
+
+        String::from_utf8(plaintext)
+            .map_err(|_| ApiError::internal_error("Decrypted credential is not valid UTF-8"))
+    }
+}
+
+/// Random secret of `len` bytes, hex-encoded (API tokens, unusable passwords)
+pub fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hex-encoded SHA-256 digest of a value
+pub fn sha256_hex(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Compare two secrets in time independent of where they differ (digests hide the length)
+pub fn secrets_equal(a: &str, b: &str) -> bool {
+    let (a, b) = (Sha256::digest(a.as_bytes()), Sha256::digest(b.as_bytes()));
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+/// Decode a base64 `nonce || payload` segment, rejecting truncated input
+fn decode_sealed(segment: &str) -> ApiResult<Vec<u8>> {
+    let bytes = BASE64
+        .decode(segment)
+        .map_err(|_| ApiError::internal_error("Malformed encrypted credential"))?;
+    if bytes.len() <= NONCE_LEN {
+        return Err(ApiError::internal_error("Malformed encrypted credential"));
+    }
+    Ok(bytes)
+}
+
+fn write_key_file(path: &Path, encoded_key: &str) -> ApiResult<()> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            ApiError::internal_error(format!("Failed to create key directory {:?}: {}", dir, e))
+        })?;
+    }
+
+    // Created with mode 0600 from the start, and never over an existing file
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = match options.open(path) {
+        Ok(file) => file,
+        // Generated concurrently by another process: use that key
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(()),
+        Err(e) => {
+            return Err(ApiError::internal_error(format!(
+                "Failed to create master key file {:?}: {}",
+                path, e
+            )));
+        },
+    };
+    file.write_all(format!("{}\n", encoded_key).as_bytes())
+        .map_err(|e| {
+            ApiError::internal_error(format!("Failed to write master key file {:?}: {}", path, e))
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let cipher = CredentialCipher::ephemeral();
+        let sealed = cipher.encrypt("s3cr3t-p@ss:word").unwrap();
+
+        assert!(CredentialCipher::is_encrypted(&sealed));
+        assert!(!sealed.contains("s3cr3t"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "s3cr3t-p@ss:word");
+    }
+
+    #[test]
+    fn test_encrypt_is_randomized() {
+        let cipher = CredentialCipher::ephemeral();
+        let a = cipher.encrypt("same").unwrap();
+        let b = cipher.encrypt("same").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_empty_password_roundtrip() {
+        let cipher = CredentialCipher::ephemeral();
+        let sealed = cipher.encrypt("").unwrap();
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "");
+    }
+
+    #[test]
+    fn test_plaintext_passthrough() {
+        let cipher = CredentialCipher::ephemeral();
+        assert_eq!(cipher.decrypt("legacy-plain").unwrap(), "legacy-plain");
+        assert!(!CredentialCipher::is_encrypted("legacy-plain"));
+    }
+
+    #[test]
+    fn test_wrong_key_reports_key_id() {
+        let a = CredentialCipher::ephemeral();
+        let b = CredentialCipher::ephemeral();
+        let sealed = a.encrypt("secret").unwrap();
+
+        let err = b.decrypt(&sealed).unwrap_err().to_string();
+        assert!(err.contains(a.key_id()));
+    }
+
+    #[test]
+    fn test_tampered_value_is_rejected() {
+        let cipher = CredentialCipher::ephemeral();
+        let sealed = cipher.encrypt("secret").unwrap();
+        let (head, tail) = sealed.rsplit_once(':').unwrap();
+        let mut bytes = BASE64.decode(tail).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = format!("{}:{}", head, BASE64.encode(bytes));
+
+        assert!(cipher.decrypt(&tampered).is_err());
+        assert!(cipher.decrypt("enc:v1:garbage").is_err());
+    }
+
+    #[test]
+    fn test_encoded_key_roundtrip() {
+        let encoded = CredentialCipher::generate_encoded_key();
+        let a = CredentialCipher::from_encoded_key(&encoded).unwrap();
+        let b = CredentialCipher::from_encoded_key(&format!("{}\n", encoded)).unwrap();
+        assert_eq!(a.key_id(), b.key_id());
+
+        let sealed = a.encrypt("secret").unwrap();
+        assert_eq!(b.decrypt(&sealed).unwrap(), "secret");
+
+        assert!(CredentialCipher::from_encoded_key("c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn test_secrets_equal() {
+        assert!(secrets_equal("scrape-token", "scrape-token"));
+        assert!(!secrets_equal("scrape-token", "scrape-tokem"));
+        assert!(!secrets_equal("scrape-token", "scrape-token-longer"));
+        assert!(!secrets_equal("", "scrape-token"));
+    }
+
+    #[test]
+    fn test_key_file_generated_and_reused() {
+        let dir = std::env::temp_dir().join(format!("sr-admin-key-{}", std::process::id()));
+        let path = dir.join("master.key");
+        let path_str = path.to_str().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(CredentialCipher::from_key_file(path_str, false).is_err());
+
+        let first = CredentialCipher::from_key_file(path_str, true).unwrap();
+        let second = CredentialCipher::from_key_file(path_str, false).unwrap();
+        assert_eq!(first.key_id(), second.key_id());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
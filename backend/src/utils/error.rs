@@ -22,6 +22,12 @@ pub enum ApiError {
     #[error("Invalid credentials")]
     InvalidCredentials,
 
+    #[error("Too many failed logins: {0}")]
+    TooManyAttempts(String),
+
+    #[error("Two-factor authentication code required")]
+    TwoFactorRequired,
+
     // Cluster errors 2xxx
     #[error("Cluster {cluster_id} not found")]
     ClusterNotFound { cluster_id: i64 },
@@ -105,6 +111,11 @@ impl ApiError {
         Self::InvalidCredentials
     }
 
+    /// Helper to create too many failed logins error (HTTP 429)
+    pub fn too_many_attempts(message: impl Into<String>) -> Self {
+        Self::TooManyAttempts(message.into())
+    }
+
     /// Helper to create internal error
     pub fn internal_error(message: impl Into<String>) -> Self {
         Self::InternalError(message.into())
@@ -157,6 +168,8 @@ impl ApiError {
             Self::Unauthorized(_) => 1001,
             Self::TokenExpired => 1002,
             Self::InvalidCredentials => 1003,
+            Self::TooManyAttempts(_) => 1004,
+            Self::TwoFactorRequired => 1005,
 
             // Cluster errors 2xxx
             Self::ClusterNotFound { .. } => 2001,
@@ -204,6 +217,7 @@ impl IntoResponse for ApiError {
         let message = self.to_string();
 
         let status = match code {
+            1004 => StatusCode::TOO_MANY_REQUESTS,
             1001..=1999 => StatusCode::UNAUTHORIZED,
             2001..=2999 => StatusCode::BAD_REQUEST,
             3000..=3999 => StatusCode::NOT_FOUND,
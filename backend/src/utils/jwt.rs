@@ -10,46 +10,71 @@ pub struct Claims {
     pub username: String, // Username
     pub exp: i64,         // Expiration time
     pub iat: i64,         // Issued at
+    /// Login session the token belongs to (tokens without one are refused)
+    #[serde(default)]
+    pub sid: Option<i64>,
 }
 
 #[derive(Clone)]
 pub struct JwtUtil {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
-    expires_in_hours: i64,
+    expires_in_minutes: i64,
 }
 
+/// Lifetime of an access token when the configured one cannot be parsed
+const DEFAULT_EXPIRES_IN_MINUTES: i64 = 15;
+
 impl JwtUtil {
     pub fn new(secret: &str, expires_in: &str) -> Self {
-        let hours = Self::parse_expiration(expires_in);
+        let minutes = Self::parse_expiration(expires_in).unwrap_or(DEFAULT_EXPIRES_IN_MINUTES);
 
         Self {
             encoding_key: EncodingKey::from_secret(secret.as_bytes()),
             decoding_key: DecodingKey::from_secret(secret.as_bytes()),
-            expires_in_hours: hours,
+            expires_in_minutes: minutes,
         }
     }
 
-    fn parse_expiration(expires_in: &str) -> i64 {
-        // Parse "24h", "7d", etc.
-        if expires_in.ends_with('h') {
-            expires_in.trim_end_matches('h').parse().unwrap_or(24)
-        } else if expires_in.ends_with('d') {
-            expires_in.trim_end_matches('d').parse::<i64>().unwrap_or(1) * 24
+    /// Parse "15m", "24h", "7d", etc. into minutes
+    pub fn parse_expiration(expires_in: &str) -> Option<i64> {
+        let expires_in = expires_in.trim();
+        let (value, unit_minutes) = if let Some(value) = expires_in.strip_suffix('m') {
+            (value, 1)
+        } else if let Some(value) = expires_in.strip_suffix('h') {
+            (value, 60)
+        } else if let Some(value) = expires_in.strip_suffix('d') {
+            (value, 24 * 60)
         } else {
-            24 // Default 24 hours
-        }
+            return None;
+        };
+        value
+            .parse::<i64>()
+            .ok()
+            .filter(|value| *value > 0)
+            .map(|value| value * unit_minutes)
+    }
+
+    /// Lifetime of an access token in seconds
+    pub fn expires_in_secs(&self) -> i64 {
+        self.expires_in_minutes * 60
     }
 
-    pub fn generate_token(&self, user_id: i64, username: &str) -> Result<String, ApiError> {
+    pub fn generate_token(
+        &self,
+        user_id: i64,
+        username: &str,
+        session_id: i64,
+    ) -> Result<String, ApiError> {
         let now = Utc::now();
-        let exp = now + Duration::hours(self.expires_in_hours);
+        let exp = now + Duration::minutes(self.expires_in_minutes);
 
         let claims = Claims {
             sub: user_id.to_string(),
             username: username.to_string(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
+            sid: Some(session_id),
         };
 
         encode(&Header::default(), &claims, &self.encoding_key)
@@ -73,17 +98,22 @@ mod tests {
     #[test]
     fn test_jwt_generation_and_verification() {
         let jwt_util = JwtUtil::new("test-secret", "24h");
-        let token = jwt_util.generate_token(1, "testuser").unwrap();
+        let token = jwt_util.generate_token(1, "testuser", 7).unwrap();
         let claims = jwt_util.verify_token(&token).unwrap();
 
         assert_eq!(claims.sub, "1");
         assert_eq!(claims.username, "testuser");
+        assert_eq!(claims.sid, Some(7));
+        assert_eq!(jwt_util.expires_in_secs(), 24 * 3600);
     }
 
     #[test]
     fn test_parse_expiration() {
-        assert_eq!(JwtUtil::parse_expiration("24h"), 24);
-        assert_eq!(JwtUtil::parse_expiration("7d"), 168);
-        assert_eq!(JwtUtil::parse_expiration("invalid"), 24);
+        assert_eq!(JwtUtil::parse_expiration("15m"), Some(15));
+        assert_eq!(JwtUtil::parse_expiration("24h"), Some(1440));
+        assert_eq!(JwtUtil::parse_expiration("7d"), Some(10080));
+        assert_eq!(JwtUtil::parse_expiration("0h"), None);
+        assert_eq!(JwtUtil::parse_expiration("invalid"), None);
+        assert_eq!(JwtUtil::new("secret", "invalid").expires_in_secs(), 15 * 60);
     }
 }
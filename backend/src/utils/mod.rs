@@ -1,9 +1,17 @@
+pub mod cluster_tls;
+pub mod crypto;
 pub mod error;
 pub mod jwt;
 pub mod macros;
 pub mod organization_filter;
+pub mod process_metrics;
 pub mod scheduled_executor;
+pub mod sql_lexer;
+pub mod totp;
 
+pub use cluster_tls::ClusterTls;
+pub use crypto::CredentialCipher;
 pub use error::{ApiError, ApiResult};
 pub use jwt::JwtUtil;
+pub use process_metrics::ProcessMetrics;
 pub use scheduled_executor::{ScheduledExecutor, ScheduledTask};
@@ -0,0 +1,162 @@
+// Process Metrics
+// Purpose: In-memory counters and histograms of starrocks-admin itself (HTTP latencies,
+//          collector ticks and failures), rendered in Prometheus text format by /metrics
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Bucket bounds (seconds) of HTTP request latencies
+const HTTP_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Bucket bounds (seconds) of metrics collector ticks
+const COLLECTOR_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Fixed-bucket histogram
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// Non-cumulative count per bound, plus the +Inf bucket
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, counts: vec![0; bounds.len() + 1], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}{}le=\"{}\"}} {}",
+                name, labels, separator, bound, cumulative
+            );
+        }
+        let _ =
+            writeln!(out, "{}_bucket{{{}{}le=\"+Inf\"}} {}", name, labels, separator, self.count);
+        let labels = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+        let _ = writeln!(out, "{}_sum{} {}", name, labels, self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, labels, self.count);
+    }
+}
+
+/// Metrics of the starrocks-admin process, shared by the HTTP layer and the collector
+#[derive(Debug)]
+pub struct ProcessMetrics {
+    /// (method, route, status) -> latency histogram
+    http_requests: Mutex<BTreeMap<(String, String, u16), Histogram>>,
+    collector_ticks: Mutex<Histogram>,
+    /// cluster id -> (cluster name, failed collections)
+    collector_failures: Mutex<HashMap<i64, (String, u64)>>,
+}
+
+impl Default for ProcessMetrics {
+    fn default() -> Self {
+        Self {
+            http_requests: Mutex::new(BTreeMap::new()),
+            collector_ticks: Mutex::new(Histogram::new(COLLECTOR_BUCKETS)),
+            collector_failures: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl ProcessMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a served HTTP request; `route` is the matched route template
+    pub fn observe_http_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let mut requests = self.http_requests.lock().unwrap();
+        requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_insert_with(|| Histogram::new(HTTP_BUCKETS))
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Record the duration of one metrics collector cycle
+    pub fn observe_collector_tick(&self, elapsed: Duration) {
+        self.collector_ticks
+            .lock()
+            .unwrap()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Record a failed metrics collection of a cluster
+    pub fn record_collector_failure(&self, cluster_id: i64, cluster_name: &str) {
+        let mut failures = self.collector_failures.lock().unwrap();
+        let entry = failures
+            .entry(cluster_id)
+            .or_insert_with(|| (cluster_name.to_string(), 0));
+        entry.0 = cluster_name.to_string();
+        entry.1 += 1;
+    }
+
+    /// Render all process metrics in Prometheus text format
+    pub fn render(&self, out: &mut String) {
+        let name = "starrocks_admin_http_request_duration_seconds";
+        write_family(out, name, "histogram", "Latency of HTTP requests served by starrocks-admin");
+        for ((method, route, status), histogram) in self.http_requests.lock().unwrap().iter() {
+            let labels = format!(
+                "method=\"{}\",route=\"{}\",status=\"{}\"",
+                escape_label(method),
+                escape_label(route),
+                status
+            );
+            histogram.render(out, name, &labels);
+        }
+
+        let name = "starrocks_admin_collector_tick_duration_seconds";
+        write_family(out, name, "histogram", "Duration of metrics collector cycles");
+        self.collector_ticks.lock().unwrap().render(out, name, "");
+
+        let name = "starrocks_admin_collector_failures_total";
+        write_family(out, name, "counter", "Failed metrics collections per cluster");
+        let failures = self.collector_failures.lock().unwrap();
+        let mut cluster_ids: Vec<_> = failures.keys().copied().collect();
+        cluster_ids.sort_unstable();
+        for cluster_id in cluster_ids {
+            let (cluster_name, count) = &failures[&cluster_id];
+            let _ = writeln!(
+                out,
+                "{}{{cluster_id=\"{}\",cluster_name=\"{}\"}} {}",
+                name,
+                cluster_id,
+                escape_label(cluster_name),
+                count
+            );
+        }
+    }
+}
+
+/// Write the HELP and TYPE lines of a metric family
+pub fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value (backslash, double quote and newline)
+pub fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
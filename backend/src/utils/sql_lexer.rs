@@ -0,0 +1,815 @@
+//! Lexer and statement classifier for StarRocks SQL.
+//!
+//! Splits editor input into statements, tells what kind of statement each one
+//! is and finds keywords while skipping everything that is not SQL syntax:
+//! `'...'` / `"..."` strings (with backslash and doubled-quote escapes),
+//! `` `...` `` identifiers, `-- ...` line comments and `/* ... */` block
+//! comments (including `/*+ ... */` hints).
+//!
+//! All delimiters are ASCII, so scanning bytes never splits a UTF-8 character.
+
+/// Lexical token kinds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keyword or unquoted identifier
+    Word,
+    /// Backtick-quoted identifier
+    QuotedIdentifier,
+    /// Single- or double-quoted string literal
+    String,
+    Number,
+    /// `-- ...`, `/* ... */` or an optimizer hint
+    Comment,
+    Whitespace,
+    Semicolon,
+    LeftParen,
+    RightParen,
+    /// Any other punctuation or operator character
+    Symbol,
+}
+
+/// A token borrowing its text from the tokenized SQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset of the token in the source
+    pub offset: usize,
+}
+
+impl Token<'_> {
+    /// Whether this is the unquoted word `keyword` (case-insensitive)
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Comments and whitespace
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Comment | TokenKind::Whitespace)
+    }
+}
+
+/// Split SQL into tokens; unterminated strings and comments run to the end of input
+pub fn tokenize(sql: &str) -> Vec<Token<'_>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let b = bytes[pos];
+        let kind = match b {
+            b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                pos = find_byte(bytes, pos + 2, b'\n').map_or(bytes.len(), |i| i + 1);
+                TokenKind::Comment
+            },
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = find_block_comment_end(bytes, pos + 2);
+                TokenKind::Comment
+            },
+            b'\'' | b'"' => {
+                pos = find_quote_end(bytes, pos + 1, b, true);
+                TokenKind::String
+            },
+            b'`' => {
+                pos = find_quote_end(bytes, pos + 1, b'`', false);
+                TokenKind::QuotedIdentifier
+            },
+            b';' => {
+                pos += 1;
+                TokenKind::Semicolon
+            },
+            b'(' => {
+                pos += 1;
+                TokenKind::LeftParen
+            },
+            b')' => {
+                pos += 1;
+                TokenKind::RightParen
+            },
+            _ if b.is_ascii_whitespace() => {
+                while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                TokenKind::Whitespace
+            },
+            _ if is_word_byte(b) => {
+                while pos < bytes.len() && is_word_byte(bytes[pos]) {
+                    pos += 1;
+                }
+                if b.is_ascii_digit() { TokenKind::Number } else { TokenKind::Word }
+            },
+            _ => {
+                pos += 1;
+                TokenKind::Symbol
+            },
+        };
+        tokens.push(Token { kind, text: &sql[start..pos], offset: start });
+    }
+
+    tokens
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || !b.is_ascii()
+}
+
+fn find_byte(bytes: &[u8], from: usize, needle: u8) -> Option<usize> {
+    bytes[from.min(bytes.len())..]
+        .iter()
+        .position(|&b| b == needle)
+        .map(|i| from + i)
+}
+
+fn find_block_comment_end(bytes: &[u8], mut pos: usize) -> usize {
+    while pos + 1 < bytes.len() {
+        if bytes[pos] == b'*' && bytes[pos + 1] == b'/' {
+            return pos + 2;
+        }
+        pos += 1;
+    }
+    bytes.len()
+}
+
+/// Position after the closing quote; a doubled quote is an escaped quote
+fn find_quote_end(bytes: &[u8], mut pos: usize, quote: u8, backslash_escapes: bool) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' if backslash_escapes => pos += 2,
+            b if b == quote => {
+                if bytes.get(pos + 1) == Some(&quote) {
+                    pos += 2;
+                } else {
+                    return pos + 1;
+                }
+            },
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Split SQL into statements on top-level `;`
+///
+/// Statements are trimmed and keep their comments; comment-only fragments are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut has_code = false;
+
+    for token in tokenize(sql) {
+        match token.kind {
+            TokenKind::Semicolon => {
+                if has_code {
+                    statements.push(sql[start..token.offset].trim().to_string());
+                }
+                start = token.offset + 1;
+                has_code = false;
+            },
+            _ if token.is_trivia() => {},
+            _ => has_code = true,
+        }
+    }
+    if has_code {
+        statements.push(sql[start..].trim().to_string());
+    }
+
+    statements
+}
+
+/// Kind of a SQL statement, taken from its leading keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// SELECT, VALUES, TABLE or a CTE ending in SELECT
+    Select,
+    Show,
+    Describe,
+    Explain,
+    /// USE and SET CATALOG
+    Use,
+    Set,
+    Insert,
+    Update,
+    Delete,
+    Create,
+    Alter,
+    Drop,
+    Truncate,
+    Grant,
+    Revoke,
+    /// BEGIN, START TRANSACTION, COMMIT, ROLLBACK
+    Transaction,
+    Kill,
+    Admin,
+    /// Anything else (LOAD, EXPORT, SUBMIT TASK, REFRESH, CANCEL, ...)
+    Other,
+    /// No statement (empty or comments only)
+    Empty,
+}
+
+impl std::fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            StatementKind::Select => "SELECT",
+            StatementKind::Show => "SHOW",
+            StatementKind::Describe => "DESCRIBE",
+            StatementKind::Explain => "EXPLAIN",
+            StatementKind::Use => "USE",
+            StatementKind::Set => "SET",
+            StatementKind::Insert => "INSERT",
+            StatementKind::Update => "UPDATE",
+            StatementKind::Delete => "DELETE",
+            StatementKind::Create => "CREATE",
+            StatementKind::Alter => "ALTER",
+            StatementKind::Drop => "DROP",
+            StatementKind::Truncate => "TRUNCATE",
+            StatementKind::Grant => "GRANT",
+            StatementKind::Revoke => "REVOKE",
+            StatementKind::Transaction => "TRANSACTION",
+            StatementKind::Kill => "KILL",
+            StatementKind::Admin => "ADMIN",
+            StatementKind::Other => "OTHER",
+            StatementKind::Empty => "EMPTY",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn keyword_kind(word: &str) -> StatementKind {
+    match word.to_ascii_uppercase().as_str() {
+        "SELECT" | "VALUES" | "TABLE" => StatementKind::Select,
+        "SHOW" => StatementKind::Show,
+        "DESC" | "DESCRIBE" => StatementKind::Describe,
+        "EXPLAIN" => StatementKind::Explain,
+        "USE" => StatementKind::Use,
+        "SET" => StatementKind::Set,
+        "INSERT" | "REPLACE" => StatementKind::Insert,
+        "UPDATE" => StatementKind::Update,
+        "DELETE" => StatementKind::Delete,
+        "CREATE" => StatementKind::Create,
+        "ALTER" => StatementKind::Alter,
+        "DROP" => StatementKind::Drop,
+        "TRUNCATE" => StatementKind::Truncate,
+        "GRANT" => StatementKind::Grant,
+        "REVOKE" => StatementKind::Revoke,
+        "BEGIN" | "START" | "COMMIT" | "ROLLBACK" => StatementKind::Transaction,
+        "KILL" => StatementKind::Kill,
+        "ADMIN" => StatementKind::Admin,
+        _ => StatementKind::Other,
+    }
+}
+
+/// Classify a single statement by its leading keyword
+///
+/// Leading comments and parentheses are skipped; for `WITH` the statement
+/// following the CTE definitions decides the kind.
+pub fn classify(sql: &str) -> StatementKind {
+    let tokens = tokenize(sql);
+    let mut significant = tokens
+        .iter()
+        .filter(|t| !t.is_trivia() && t.kind != TokenKind::LeftParen);
+
+    let Some(first) = significant.next() else {
+        return StatementKind::Empty;
+    };
+    if first.kind != TokenKind::Word {
+        return StatementKind::Other;
+    }
+    // SET CATALOG switches context like USE
+    if first.is_keyword("SET") && significant.next().is_some_and(|t| t.is_keyword("CATALOG")) {
+        return StatementKind::Use;
+    }
+    if !first.is_keyword("WITH") {
+        return keyword_kind(first.text);
+    }
+
+    // CTE bodies are parenthesized, so the main statement is the first
+    // top-level SELECT/INSERT/UPDATE/DELETE after WITH
+    let mut depth = 0i32;
+    for token in tokens.iter().skip_while(|t| !t.is_keyword("WITH")).skip(1) {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            TokenKind::Word if depth <= 0 => {
+                let kind = keyword_kind(token.text);
+                if matches!(
+                    kind,
+                    StatementKind::Select
+                        | StatementKind::Insert
+                        | StatementKind::Update
+                        | StatementKind::Delete
+                ) {
+                    return kind;
+                }
+            },
+            _ => {},
+        }
+    }
+    StatementKind::Select
+}
+
+/// Whether `keyword` appears as a word outside parentheses, strings and comments
+pub fn has_top_level_keyword(sql: &str, keyword: &str) -> bool {
+    let mut depth = 0i32;
+    for token in tokenize(sql) {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            TokenKind::Word if depth <= 0 && token.is_keyword(keyword) => return true,
+            _ => {},
+        }
+    }
+    false
+}
+
+/// First of `keywords` used as a word anywhere in the SQL (outside strings, identifiers and comments)
+pub fn find_keyword<'k>(sql: &str, keywords: &[&'k str]) -> Option<&'k str> {
+    tokenize(sql).iter().find_map(|token| {
+        keywords
+            .iter()
+            .find(|keyword| token.is_keyword(keyword))
+            .copied()
+    })
+}
+
+/// Whether a query writes its result to files (`SELECT ... INTO OUTFILE 's3://...'`)
+pub fn has_into_outfile(sql: &str) -> bool {
+    let tokens: Vec<Token<'_>> = tokenize(sql)
+        .into_iter()
+        .filter(|t| !t.is_trivia())
+        .collect();
+    tokens
+        .windows(2)
+        .any(|pair| pair[0].is_keyword("INTO") && pair[1].is_keyword("OUTFILE"))
+}
+
+/// Variables set by `/*+ SET_VAR(name = value, ...) */` optimizer hints, lowercased
+pub fn hint_variables(sql: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    for token in tokenize(sql) {
+        let Some(hint) = token
+            .text
+            .strip_prefix("/*+")
+            .filter(|_| token.kind == TokenKind::Comment)
+        else {
+            continue;
+        };
+        let hint = hint.strip_suffix("*/").unwrap_or(hint);
+        let upper = hint.to_ascii_uppercase();
+        let mut rest = 0;
+        while let Some(found) = upper[rest..].find("SET_VAR") {
+            let start = rest + found + "SET_VAR".len();
+            let Some(open) = hint[start..].trim_start().strip_prefix('(') else {
+                rest = start;
+                continue;
+            };
+            let body = open.split(')').next().unwrap_or(open);
+            variables.extend(body.split(',').filter_map(|assignment| {
+                let name = assignment.split('=').next()?.trim();
+                (!name.is_empty()).then(|| name.to_ascii_lowercase())
+            }));
+            rest = start;
+        }
+    }
+    variables
+}
+
+/// A possibly qualified object name and the keyword it follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectReference {
+    /// Uppercased keyword before the name (FROM, JOIN, USE, ...)
+    pub keyword: String,
+    /// Name parts with backticks removed, e.g. `["catalog", "db", "table"]`
+    pub parts: Vec<String>,
+}
+
+/// Keywords that are followed by a table, database or catalog name
+const REFERENCE_KEYWORDS: &[&str] = &[
+    "FROM", "JOIN", "INTO", "UPDATE", "TABLE", "USE", "CATALOG", "IN", "DESC", "DESCRIBE",
+    "DATABASE", "SCHEMA", "VIEW",
+];
+
+/// Keywords ending the table list of a FROM clause
+const FROM_LIST_END_KEYWORDS: &[&str] = &[
+    "WHERE",
+    "GROUP",
+    "HAVING",
+    "QUALIFY",
+    "WINDOW",
+    "ORDER",
+    "LIMIT",
+    "UNION",
+    "EXCEPT",
+    "INTERSECT",
+    "MINUS",
+    "SELECT",
+    "SET",
+    "INTO",
+    "VALUES",
+];
+
+/// Dotted names following FROM, JOIN, INTO, UPDATE, TABLE, USE, ... (outside strings and comments)
+///
+/// Every table of a FROM list is reported, also after derived tables and join conditions
+/// (`FROM (SELECT ...) x, d.t` or `FROM a JOIN (...) s ON ..., d.t`), with the FROM keyword.
+pub fn object_references(sql: &str) -> Vec<ObjectReference> {
+    let tokens: Vec<Token<'_>> = tokenize(sql)
+        .into_iter()
+        .filter(|t| !t.is_trivia())
+        .collect();
+    let mut references = Vec::new();
+    // Whether each parenthesis level is in the table list of a FROM clause, where a comma
+    // starts another table
+    let mut from_lists = vec![false];
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => {
+                from_lists.push(false);
+                continue;
+            },
+            TokenKind::RightParen => {
+                if from_lists.len() > 1 {
+                    from_lists.pop();
+                }
+                continue;
+            },
+            _ => {},
+        }
+        let in_from_list = from_lists
+            .last_mut()
+            .expect("outermost level is never popped");
+        if token.text == "," {
+            if *in_from_list {
+                let (parts, next) = dotted_name(&tokens, i + 1);
+                // Derived tables and table functions are walked like any other tokens
+                let is_function = tokens
+                    .get(next)
+                    .is_some_and(|t| t.kind == TokenKind::LeftParen);
+                if !parts.is_empty() && !is_function {
+                    references.push(ObjectReference { keyword: "FROM".to_string(), parts });
+                }
+            }
+            continue;
+        }
+        if token.is_keyword("FROM") {
+            *in_from_list = true;
+        } else if FROM_LIST_END_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+            *in_from_list = false;
+        }
+        if !REFERENCE_KEYWORDS.iter().any(|k| token.is_keyword(k)) {
+            continue;
+        }
+
+        // Skip `IF [NOT] EXISTS`
+        let mut j = i + 1;
+        if tokens.get(j).is_some_and(|t| t.is_keyword("IF")) {
+            j += 1;
+            if tokens.get(j).is_some_and(|t| t.is_keyword("NOT")) {
+                j += 1;
+            }
+            j += 1;
+        }
+        // A following keyword (`FROM SELECT`, `INSERT INTO TABLE`) is not a name
+        if tokens.get(j).is_some_and(|t| {
+            ["SELECT", "WITH"]
+                .iter()
+                .chain(REFERENCE_KEYWORDS)
+                .any(|k| t.is_keyword(k))
+        }) {
+            continue;
+        }
+
+        let (parts, _) = dotted_name(&tokens, j);
+        if !parts.is_empty() {
+            references.push(ObjectReference { keyword: token.text.to_ascii_uppercase(), parts });
+        }
+    }
+
+    references
+}
+
+/// Parts of the dotted name starting at token `start` and the index of the token after it
+fn dotted_name(tokens: &[Token<'_>], start: usize) -> (Vec<String>, usize) {
+    let mut parts = Vec::new();
+    let mut j = start;
+    while let Some(part) = tokens.get(j).and_then(identifier_text) {
+        parts.push(part);
+        j += 1;
+        if tokens.get(j).is_some_and(|t| t.text == ".") {
+            j += 1;
+        } else {
+            break;
+        }
+    }
+    (parts, j)
+}
+
+/// Functions using FROM as an argument separator, e.g. `EXTRACT(YEAR FROM dt)`
+const FROM_ARGUMENT_FUNCTIONS: &[&str] = &["EXTRACT", "SUBSTRING", "SUBSTR", "TRIM", "POSITION"];
+
+/// Tables and views a query reads: dotted names following FROM and JOIN
+///
+/// CTE names, table functions and the FROM of EXTRACT/SUBSTRING/TRIM arguments are
+/// skipped; each name is reported once, in order of first use.
+pub fn table_references(sql: &str) -> Vec<Vec<String>> {
+    let tokens: Vec<Token<'_>> = tokenize(sql)
+        .into_iter()
+        .filter(|t| !t.is_trivia())
+        .collect();
+
+    // `name AS (` after WITH or a comma defines a CTE
+    let ctes: Vec<String> = tokens
+        .windows(4)
+        .filter(|w| {
+            (w[0].is_keyword("WITH") || w[0].text == ",")
+                && w[2].is_keyword("AS")
+                && w[3].kind == TokenKind::LeftParen
+        })
+        .filter_map(|w| identifier_text(&w[1]))
+        .map(|name| name.to_ascii_lowercase())
+        .collect();
+
+    let mut in_from_argument = Vec::new();
+    let mut references: Vec<Vec<String>> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => {
+                let function = i
+                    .checked_sub(1)
+                    .and_then(|p| tokens.get(p))
+                    .is_some_and(|t| FROM_ARGUMENT_FUNCTIONS.iter().any(|f| t.is_keyword(f)));
+                in_from_argument.push(function);
+                continue;
+            },
+            TokenKind::RightParen => {
+                in_from_argument.pop();
+                continue;
+            },
+            _ => {},
+        }
+        let is_from = token.is_keyword("FROM");
+        if !(is_from || token.is_keyword("JOIN")) || in_from_argument.last() == Some(&true) {
+            continue;
+        }
+
+        let mut j = i + 1;
+        loop {
+            let mut parts = Vec::new();
+            while let Some(part) = tokens.get(j).and_then(identifier_text) {
+                parts.push(part);
+                j += 1;
+                if tokens.get(j).is_some_and(|t| t.text == ".") {
+                    j += 1;
+                } else {
+                    break;
+                }
+            }
+            if parts.is_empty()
+                || tokens
+                    .get(j)
+                    .is_some_and(|t| t.kind == TokenKind::LeftParen)
+            {
+                break;
+            }
+            let is_cte = parts.len() == 1 && ctes.contains(&parts[0].to_ascii_lowercase());
+            if !is_cte && !references.contains(&parts) {
+                references.push(parts);
+            }
+
+            // Comma-separated tables: `FROM a [AS] x, b.c y`
+            if !is_from {
+                break;
+            }
+            if tokens.get(j).is_some_and(|t| t.is_keyword("AS")) {
+                j += 1;
+            }
+            if tokens.get(j).is_some_and(|t| identifier_text(t).is_some()) {
+                j += 1;
+            }
+            if tokens.get(j).is_some_and(|t| t.text == ",") {
+                j += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    references
+}
+
+fn identifier_text(token: &Token<'_>) -> Option<String> {
+    match token.kind {
+        TokenKind::Word => Some(token.text.to_string()),
+        TokenKind::QuotedIdentifier => {
+            let inner = token.text.strip_prefix('`')?;
+            let inner = inner.strip_suffix('`').unwrap_or(inner);
+            Some(inner.replace("``", "`"))
+        },
+        _ => None,
+    }
+}
+
+/// Functions whose result must not be truncated by an injected LIMIT
+const NO_LIMIT_FUNCTIONS: &[&str] = &["GET_QUERY_PROFILE", "SHOW_PROFILE"];
+
+/// Append `LIMIT <limit>` to a SELECT without a top-level LIMIT
+///
+/// Other statements, queries that already limit their result and
+/// `SELECT ... INTO OUTFILE` are returned unchanged (trimmed).
+pub fn apply_limit(sql: &str, limit: i32) -> String {
+    let trimmed = sql.trim();
+    if classify(trimmed) != StatementKind::Select
+        || has_top_level_keyword(trimmed, "LIMIT")
+        || has_top_level_keyword(trimmed, "OUTFILE")
+        || find_keyword(trimmed, NO_LIMIT_FUNCTIONS).is_some()
+    {
+        return trimmed.to_string();
+    }
+
+    // Insert before trailing comments/semicolons so a `-- comment` cannot swallow the LIMIT
+    let end = tokenize(trimmed)
+        .iter()
+        .rev()
+        .find(|t| !t.is_trivia() && t.kind != TokenKind::Semicolon)
+        .map_or(0, |t| t.offset + t.text.len());
+    format!("{} LIMIT {}", &trimmed[..end], limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_ignores_separators_in_strings_comments_and_identifiers() {
+        let sql = "SELECT 'a;b', \"c;d\", `e;f` FROM t; -- note; not a statement\n\
+                   /* block; comment */ SELECT 'it''s; fine', 'back\\'slash;'; ;;";
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "SELECT 'a;b', \"c;d\", `e;f` FROM t",
+                "-- note; not a statement\n/* block; comment */ SELECT 'it''s; fine', 'back\\'slash;'",
+            ]
+        );
+        assert!(split_statements("-- only a comment;\n/* and; another */").is_empty());
+        assert_eq!(split_statements("SELECT 1 -- trailing"), vec!["SELECT 1 -- trailing"]);
+    }
+
+    #[test]
+    fn test_classify_statements() {
+        let cases = [
+            ("select * from t", StatementKind::Select),
+            ("  -- hi\n/*+ SET_VAR(query_timeout=10) */ SELECT 1", StatementKind::Select),
+            ("(SELECT 1) UNION (SELECT 2)", StatementKind::Select),
+            (
+                "WITH a AS (SELECT 1), b (x) AS (DELETE FROM z) SELECT * FROM a",
+                StatementKind::Select,
+            ),
+            ("WITH a AS (SELECT 1) INSERT INTO t SELECT * FROM a", StatementKind::Insert),
+            ("SHOW PROC '/backends'", StatementKind::Show),
+            ("desc t", StatementKind::Describe),
+            ("EXPLAIN COSTS SELECT 1", StatementKind::Explain),
+            ("USE db1", StatementKind::Use),
+            ("SET GLOBAL query_timeout = 10", StatementKind::Set),
+            ("SET CATALOG hive", StatementKind::Use),
+            ("INSERT OVERWRITE t SELECT 1", StatementKind::Insert),
+            ("update t set a = 1", StatementKind::Update),
+            ("DELETE FROM t WHERE id = 1", StatementKind::Delete),
+            ("CREATE TABLE t (id INT)", StatementKind::Create),
+            ("DROP DATABASE d", StatementKind::Drop),
+            ("ALTER TABLE t ADD COLUMN c INT", StatementKind::Alter),
+            ("TRUNCATE TABLE t", StatementKind::Truncate),
+            ("GRANT SELECT ON *.* TO u", StatementKind::Grant),
+            ("KILL QUERY 12", StatementKind::Kill),
+            ("ADMIN SET FRONTEND CONFIG ('a' = 'b')", StatementKind::Admin),
+            ("REFRESH MATERIALIZED VIEW mv", StatementKind::Other),
+            ("-- nothing", StatementKind::Empty),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(classify(sql), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_outfile_and_hint_variables() {
+        assert!(has_into_outfile("SELECT * FROM t INTO OUTFILE 's3://b/x' FORMAT AS CSV"));
+        assert!(!has_into_outfile("SELECT outfile FROM t"));
+        assert!(!has_into_outfile("INSERT INTO t SELECT 'INTO OUTFILE'"));
+
+        assert_eq!(
+            hint_variables(
+                "SELECT /*+ SET_VAR(query_timeout = 1000, Exec_Mem_Limit=1) */ * FROM t \
+                 /* SET_VAR(ignored=1) */"
+            ),
+            vec!["query_timeout", "exec_mem_limit"]
+        );
+        assert_eq!(
+            hint_variables("/*+ set_var(a=1) set_var (b='x') */ INSERT INTO t SELECT 1"),
+            vec!["a", "b"]
+        );
+        assert!(hint_variables("SELECT 'SET_VAR(query_timeout=1)'").is_empty());
+    }
+
+    #[test]
+    fn test_find_keyword_matches_whole_words_only() {
+        let blocked = ["UPDATE", "DROP", "DELETE"];
+        assert_eq!(find_keyword("SELECT update_time, dropped FROM t", &blocked), None);
+        assert_eq!(
+            find_keyword("SELECT 'DROP TABLE x' /* DELETE */ FROM `update`", &blocked),
+            None
+        );
+        assert_eq!(find_keyword("SELECT 1; drop table t", &blocked), Some("DROP"));
+    }
+
+    #[test]
+    fn test_object_references() {
+        let refs = |sql: &str| -> Vec<(String, Vec<String>)> {
+            object_references(sql)
+                .into_iter()
+                .map(|r| (r.keyword, r.parts))
+                .collect()
+        };
+        let parts = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            refs("SELECT * FROM hive.`sales db`.orders o JOIN d2.t2 ON o.id = t2.id"),
+            vec![
+                ("FROM".to_string(), parts(&["hive", "sales db", "orders"])),
+                ("JOIN".to_string(), parts(&["d2", "t2"])),
+            ]
+        );
+        assert_eq!(
+            refs("SELECT a FROM t1 AS x, d.t2 y, t3 WHERE a IN (1, 2) ORDER BY a, b"),
+            vec![
+                ("FROM".to_string(), parts(&["t1"])),
+                ("FROM".to_string(), parts(&["d", "t2"])),
+                ("FROM".to_string(), parts(&["t3"])),
+            ]
+        );
+        // Tables after derived tables, join conditions and table functions
+        assert_eq!(
+            refs("SELECT * FROM (SELECT 1) x, secret_db.tbl"),
+            vec![("FROM".to_string(), parts(&["secret_db", "tbl"]))]
+        );
+        assert_eq!(
+            refs("SELECT * FROM t1 JOIN (SELECT 1) s ON 1=1, secret_db.t"),
+            vec![
+                ("FROM".to_string(), parts(&["t1"])),
+                ("FROM".to_string(), parts(&["secret_db", "t"])),
+            ]
+        );
+        assert_eq!(
+            refs("SELECT * FROM t, unnest(t.a) u, (SELECT b FROM d.s GROUP BY b, c) v, `e`.t2"),
+            vec![
+                ("FROM".to_string(), parts(&["t"])),
+                ("FROM".to_string(), parts(&["d", "s"])),
+                ("FROM".to_string(), parts(&["e", "t2"])),
+            ]
+        );
+        assert_eq!(refs("use `my``db`"), vec![("USE".to_string(), parts(&["my`db"]))]);
+        assert_eq!(refs("SET CATALOG iceberg"), vec![("CATALOG".to_string(), parts(&["iceberg"]))]);
+        assert_eq!(
+            refs("INSERT OVERWRITE TABLE db.t SELECT 'FROM x.y' -- FROM a.b"),
+            vec![("TABLE".to_string(), parts(&["db", "t"]))]
+        );
+        assert_eq!(
+            refs("CREATE TABLE IF NOT EXISTS d.t (id INT)"),
+            vec![("TABLE".to_string(), parts(&["d", "t"]))]
+        );
+        assert_eq!(
+            refs("DROP DATABASE IF EXISTS d; SHOW TABLES FROM d3"),
+            vec![("DATABASE".to_string(), parts(&["d"])), ("FROM".to_string(), parts(&["d3"])),]
+        );
+    }
+
+    #[test]
+    fn test_apply_limit() {
+        assert_eq!(apply_limit("SELECT * FROM t", 100), "SELECT * FROM t LIMIT 100");
+        assert_eq!(
+            apply_limit("WITH a AS (SELECT 1) SELECT * FROM a -- done\n", 10),
+            "WITH a AS (SELECT 1) SELECT * FROM a LIMIT 10"
+        );
+        assert_eq!(apply_limit("SELECT * FROM t;", 5), "SELECT * FROM t LIMIT 5");
+        // LIMIT only inside a subquery, string or column name
+        assert_eq!(
+            apply_limit("SELECT * FROM (SELECT * FROM t LIMIT 5) s", 10),
+            "SELECT * FROM (SELECT * FROM t LIMIT 5) s LIMIT 10"
+        );
+        assert_eq!(
+            apply_limit("SELECT 'LIMIT', limit_count FROM t", 10),
+            "SELECT 'LIMIT', limit_count FROM t LIMIT 10"
+        );
+
+        for unchanged in [
+            "SELECT * FROM t LIMIT 5",
+            "select * from t limit 5 offset 10",
+            "SHOW TABLES",
+            "INSERT INTO t SELECT * FROM s",
+            "SELECT get_query_profile('abc')",
+            "SELECT * FROM t INTO OUTFILE 's3://bucket/x'",
+        ] {
+            assert_eq!(apply_limit(unchanged, 10), unchanged, "{}", unchanged);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Time-based one-time passwords (RFC 6238, HMAC-SHA1, 6 digits, 30 second steps), the
+//! variant every authenticator app supports.
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+use crate::utils::error::{ApiError, ApiResult};
+
+pub const DIGITS: usize = 6;
+pub const STEP_SECS: i64 = 30;
+/// Steps accepted before and after the current one, for clock drift
+const SKEW_STEPS: i64 = 1;
+const SECRET_LEN: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// New random secret, base32-encoded as authenticator apps expect it
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_LEN];
+    OsRng.fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+/// Step of a unix timestamp
+pub fn step_at(unix_secs: i64) -> i64 {
+    unix_secs.div_euclid(STEP_SECS)
+}
+
+/// Code of a secret at a step
+pub fn code_at(secret: &str, step: i64) -> ApiResult<String> {
+    let key = base32_decode(secret)
+        .ok_or_else(|| ApiError::internal_error("Malformed two-factor secret"))?;
+    let hmac_error = |e: openssl::error::ErrorStack| {
+        ApiError::internal_error(format!("Failed to compute one-time password: {}", e))
+    };
+    let key = PKey::hmac(&key).map_err(hmac_error)?;
+    let mut signer = Signer::new(MessageDigest::sha1(), &key).map_err(hmac_error)?;
+    signer.update(&step.to_be_bytes()).map_err(hmac_error)?;
+    let mac = signer.sign_to_vec().map_err(hmac_error)?;
+
+    // Dynamic truncation
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    let value =
+        u32::from_be_bytes([mac[offset], mac[offset + 1], mac[offset + 2], mac[offset + 3]])
+            & 0x7fff_ffff;
+    Ok(format!("{:0width$}", value % 10u32.pow(DIGITS as u32), width = DIGITS))
+}
+
+/// Step matched by a code around `unix_secs`, ignoring steps up to `last_used_step` so a code
+/// cannot be replayed
+pub fn verify(
+    secret: &str,
+    code: &str,
+    unix_secs: i64,
+    last_used_step: Option<i64>,
+) -> ApiResult<Option<i64>> {
+    let code = code.trim();
+    if code.len() != DIGITS || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let current = step_at(unix_secs);
+    for step in current - SKEW_STEPS..=current + SKEW_STEPS {
+        if last_used_step.is_some_and(|used| step <= used) {
+            continue;
+        }
+        if code_at(secret, step)? == code {
+            return Ok(Some(step));
+        }
+    }
+    Ok(None)
+}
+
+/// `otpauth://` URI shown as a QR code to enroll the secret in an authenticator app
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let label = format!("{}:{}", issuer, account);
+    format!(
+        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        urlencoding::encode(&label),
+        secret,
+        urlencoding::encode(issuer),
+        DIGITS,
+        STEP_SECS
+    )
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Decode base32, ignoring case, spaces and padding as apps display secrets in groups
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B, SHA-1 secret "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(code_at(RFC_SECRET, step_at(59)).unwrap(), "287082");
+        assert_eq!(code_at(RFC_SECRET, step_at(1111111109)).unwrap(), "081804");
+        assert_eq!(code_at(RFC_SECRET, step_at(1234567890)).unwrap(), "005924");
+        assert_eq!(code_at(RFC_SECRET, step_at(2000000000)).unwrap(), "279037");
+    }
+
+    #[test]
+    fn test_verify_accepts_drift_and_refuses_replay() {
+        let now = 1111111109;
+        let previous = code_at(RFC_SECRET, step_at(now) - 1).unwrap();
+
+        assert_eq!(verify(RFC_SECRET, &previous, now, None).unwrap(), Some(step_at(now) - 1));
+        assert_eq!(verify(RFC_SECRET, &previous, now, Some(step_at(now) - 1)).unwrap(), None);
+        assert_eq!(verify(RFC_SECRET, &previous, now + 3 * STEP_SECS, None).unwrap(), None);
+        assert_eq!(verify(RFC_SECRET, "12345", now, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        let grouped = secret
+            .to_lowercase()
+            .as_bytes()
+            .chunks(4)
+            .map(|c| std::str::from_utf8(c).unwrap())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(base32_encode(&base32_decode(&grouped).unwrap()), secret);
+    }
+}
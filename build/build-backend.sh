@@ -62,6 +62,7 @@ url = "sqlite://data/starrocks-admin.db"
 [auth]
 jwt_secret = "dev-secret-key-change-in-production"
 jwt_expires_in = "24h"
+refresh_expires_in = "7d"
 
 [logging]
 level = "info,starrocks_admin_backend=debug"
@@ -73,11 +74,35 @@ file = "logs/starrocks-admin.log"
 interval_secs = "30s"    
 retention_days = "7d"    
 enabled = true
+# Bearer token Prometheus must send to scrape /metrics (unset = /metrics is not served)
+# exporter_token = "change-me"
+# Without a token, serve local (loopback) scrapers; not behind a reverse proxy on the same host
+# allow_unauthenticated_local = false
+# Clusters collected concurrently; failing clusters are retried with backoff up to max_backoff_secs
+max_concurrency = 4
+max_backoff_secs = "10m"
 
 # Audit log configuration
 [audit]
 database = "starrocks_audit_db__"
 table = "starrocks_audit_tbl__"
+
+# Credential encryption (cluster passwords are encrypted with this master key)
+# The key file is generated on first start if missing - back it up with the database.
+[security]
+master_key_file = "data/master.key"
+
+# Query profile archive
+[profile_archive]
+auto_archive = true
+interval_secs = "5m"
+retention_days = "30d"
+min_duration_ms = 1000
+compress = true
+
+[alert]
+history_retention_days = "30d"
+notify_timeout_secs = "10s"
 EOF
 echo "Created production config.toml"
 